@@ -0,0 +1,54 @@
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::Rc;
+
+use craft_retained::elements::{AsElement, CustomChange, CustomElement, ElementInternals};
+
+use crate::elements::element::Element;
+use crate::signals::Bindable;
+
+/// Adds a [`CustomElement`] to a tree like any other element.
+#[derive(Clone)]
+pub struct Custom<T: CustomElement> {
+    pub inner: craft_retained::elements::Custom<T>,
+}
+
+impl<T: CustomElement> Element for Custom<T> {}
+
+impl<T: CustomElement> AsElement for Custom<T> {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl<T: CustomElement> Custom<T> {
+    pub fn new(custom: T) -> Self {
+        Self {
+            inner: craft_retained::elements::Custom::new(custom),
+        }
+    }
+
+    pub fn get_custom(&self) -> Ref<'_, T> {
+        self.inner.get_custom()
+    }
+
+    /// Changes the custom element with each value, and measures or draws it again as `update` returns.
+    pub fn bind<V: 'static>(
+        self,
+        value: impl Bindable<V>,
+        update: impl Fn(&mut T, V) -> CustomChange + 'static,
+    ) -> Self {
+        let element = self.inner.clone();
+        value.bind(move |value| {
+            element.clone().update(|custom| update(custom, value));
+        });
+        self
+    }
+}
@@ -728,7 +728,9 @@ pub trait Element: Clone + AsElement {
                     let l = l.clone();
 
                     l.bind(move |l| {
-                        element.borrow_mut().set_border_radius(t, r, b, l);
+                        element
+                            .borrow_mut()
+                            .set_border_radius(t.into(), r.into(), b.into(), l.into());
                     });
                 });
             });
@@ -740,7 +742,7 @@ pub trait Element: Clone + AsElement {
     fn border_radius_all(self, value: impl Bindable<(f32, f32)> + Clone) -> Self {
         let element = self.as_element_rc();
         value.bind(move |v| {
-            element.borrow_mut().set_border_radius_all(v);
+            element.borrow_mut().set_border_radius_all(v.into());
         });
         self
     }
@@ -748,7 +750,7 @@ pub trait Element: Clone + AsElement {
     fn border_radius_vertical(self, value: impl Bindable<(f32, f32)> + Clone) -> Self {
         let element = self.as_element_rc();
         value.bind(move |v| {
-            element.borrow_mut().set_border_radius_vertical(v);
+            element.borrow_mut().set_border_radius_vertical(v.into());
         });
         self
     }
@@ -756,7 +758,7 @@ pub trait Element: Clone + AsElement {
     fn border_radius_horizontal(self, value: impl Bindable<(f32, f32)> + Clone) -> Self {
         let element = self.as_element_rc();
         value.bind(move |v| {
-            element.borrow_mut().set_border_radius_horizontal(v);
+            element.borrow_mut().set_border_radius_horizontal(v.into());
         });
         self
     }
@@ -866,6 +868,15 @@ pub trait Element: Clone + AsElement {
         self
     }
 
+    /// Lets spatial navigation move the focus to the element. See [`craft_retained::spatial_navigation`].
+    fn focusable(self, focusable: impl Bindable<bool>) -> Self {
+        let element = self.as_element_rc();
+        focusable.bind(move |v| {
+            element.borrow_mut().set_focusable(v);
+        });
+        self
+    }
+
     fn is_focused(&self) -> bool {
         self.borrow().is_focused()
     }
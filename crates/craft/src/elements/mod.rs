@@ -1,5 +1,6 @@
 mod conditional;
 mod container;
+mod custom;
 mod element;
 mod image;
 mod slider;
@@ -9,6 +10,8 @@ mod window;
 
 pub use conditional::Conditional;
 pub use container::Container;
+pub use craft_retained::elements::{CustomChange, CustomElement, DrawContext};
+pub use custom::Custom;
 pub use element::Element;
 pub use image::Image;
 pub use slider::Slider;
@@ -1,7 +1,10 @@
 use std::cell::{Ref, RefCell, RefMut};
 use std::rc::Rc;
+use std::time::Duration;
 
-use craft_retained::elements::{AsElement, ElementInternals};
+use craft_retained::elements::{AsElement, DynElement, ElementInternals};
+use craft_retained::geometry::{Point, Rectangle};
+use craft_retained::snapping::SnapGuide;
 use craft_retained::winit::event_loop::ActiveEventLoop;
 use craft_retained::{RendererType, WinitWindow};
 
@@ -43,4 +46,41 @@ impl Window {
             inner: craft_retained::elements::Window::new_advanced(window_fn, renderer_type),
         }
     }
+
+    /// The elements drawn at `point`, from the topmost down. See [`craft_retained::spatial`].
+    pub fn elements_at(&self, point: Point) -> Vec<DynElement> {
+        self.inner.elements_at(point)
+    }
+
+    /// The elements drawn over any part of `rectangle`, from the topmost down.
+    pub fn elements_in_rect(&self, rectangle: Rectangle) -> Vec<DynElement> {
+        self.inner.elements_in_rect(rectangle)
+    }
+
+    /// The drawn element closest to `point` within `max_distance`.
+    pub fn nearest_element(&self, point: Point, max_distance: f64) -> Option<DynElement> {
+        self.inner.nearest_element(point, max_distance)
+    }
+
+    /// Shows `attract` instead of the children after `after` without input. See [`craft_retained::attract_mode`].
+    pub fn attract_mode(self, after: Duration, attract: impl AsElement) -> Self {
+        Self {
+            inner: self.inner.attract_mode(after, attract),
+        }
+    }
+
+    /// Stops showing an attract element, and puts the children back if it was shown.
+    pub fn clear_attract_mode(&self) {
+        self.inner.clear_attract_mode();
+    }
+
+    /// Whether the window shows its attract element.
+    pub fn is_attracting(&self) -> bool {
+        self.inner.is_attracting()
+    }
+
+    /// Draws the guides of a snapped element until other guides are shown. See [`craft_retained::snapping`].
+    pub fn show_snap_guides(&self, guides: &[SnapGuide]) {
+        self.inner.show_snap_guides(guides);
+    }
 }
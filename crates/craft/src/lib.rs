@@ -1,4 +1,4 @@
 pub mod elements;
 mod signals;
 
-pub use signals::Signal;
+pub use signals::{Signal, SignalUpdate, UpdateCause, finish_update_trace, start_update_trace};
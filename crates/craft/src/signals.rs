@@ -1,11 +1,106 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::fmt;
 use std::rc::Rc;
 
 type Subscriber = Rc<dyn Fn()>;
 
+thread_local! {
+    static NEXT_SIGNAL_ID: Cell<u64> = const { Cell::new(1) };
+    /// The updates recorded since [`start_update_trace`], or `None` when updates aren't traced.
+    static UPDATE_TRACE: RefCell<Option<Vec<SignalUpdate>>> = const { RefCell::new(None) };
+    /// The names of the signals whose subscribers are running, innermost last.
+    static UPDATING: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
 struct SignalInner<T> {
     value: T,
     subscribers: Vec<Subscriber>,
+    id: u64,
+    label: Option<String>,
+}
+
+/// Why the subscribers of a signal ran.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UpdateCause {
+    /// The signal was set by the app, e.g. from an event handler.
+    Set,
+    /// The signal was derived from another signal with [`Signal::map`], and that signal changed.
+    Derived { from: String },
+    /// An element was bound to the signal, which runs the binding once with the current value.
+    Bound,
+}
+
+/// One run of the subscribers of a signal, recorded while updates are traced.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignalUpdate {
+    /// The label of the signal, or its id if it has none.
+    pub signal: String,
+    pub cause: UpdateCause,
+    /// How many subscribers ran, like bound elements and derived signals.
+    pub subscribers: usize,
+    /// How many signal updates this one is nested in.
+    pub depth: usize,
+}
+
+impl fmt::Display for SignalUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let indent = "  ".repeat(self.depth);
+        let cause = match &self.cause {
+            UpdateCause::Set => "set".to_string(),
+            UpdateCause::Derived { from } => format!("derived from {from}"),
+            UpdateCause::Bound => "bound".to_string(),
+        };
+        write!(f, "{indent}{} ({cause}), {} subscribers", self.signal, self.subscribers)
+    }
+}
+
+/// Starts recording which signals updated and why, to find out why parts of the UI were updated.
+pub fn start_update_trace() {
+    UPDATE_TRACE.with_borrow_mut(|trace| *trace = Some(Vec::new()));
+}
+
+/// Stops recording, and returns the updates since [`start_update_trace`] in the order they started.
+pub fn finish_update_trace() -> Vec<SignalUpdate> {
+    UPDATE_TRACE.with_borrow_mut(|trace| trace.take()).unwrap_or_default()
+}
+
+fn is_tracing() -> bool {
+    UPDATE_TRACE.with_borrow(|trace| trace.is_some())
+}
+
+/// Runs `subscribers`, and records it if updates are traced.
+fn run_subscribers(name: impl FnOnce() -> String, cause: Option<UpdateCause>, subscribers: &[Subscriber]) {
+    if !is_tracing() {
+        for subscriber in subscribers {
+            subscriber();
+        }
+        return;
+    }
+
+    let name = name();
+    let (cause, depth) = UPDATING.with_borrow(|updating| {
+        let cause = cause.unwrap_or_else(|| match updating.last() {
+            Some(from) => UpdateCause::Derived { from: from.clone() },
+            None => UpdateCause::Set,
+        });
+        (cause, updating.len())
+    });
+    UPDATE_TRACE.with_borrow_mut(|trace| {
+        if let Some(trace) = trace {
+            trace.push(SignalUpdate {
+                signal: name.clone(),
+                cause,
+                subscribers: subscribers.len(),
+                depth,
+            });
+        }
+    });
+
+    UPDATING.with_borrow_mut(|updating| updating.push(name));
+    for subscriber in subscribers {
+        subscriber();
+    }
+    UPDATING.with_borrow_mut(|updating| updating.pop());
 }
 
 #[derive(Clone)]
@@ -19,12 +114,27 @@ impl<T> PartialEq for Signal<T> {
 
 impl<T: Clone + 'static> Signal<T> {
     pub fn new(value: T) -> Self {
+        let id = NEXT_SIGNAL_ID.get();
+        NEXT_SIGNAL_ID.set(id + 1);
         Self(Rc::new(RefCell::new(SignalInner {
             value,
             subscribers: Vec::new(),
+            id,
+            label: None,
         })))
     }
 
+    /// Names the signal in update traces, see [`start_update_trace`].
+    pub fn label(self, label: &str) -> Self {
+        self.0.borrow_mut().label = Some(label.to_string());
+        self
+    }
+
+    fn name(&self) -> String {
+        let inner = self.0.borrow();
+        inner.label.clone().unwrap_or_else(|| format!("signal #{}", inner.id))
+    }
+
     pub fn get(&self) -> T {
         self.0.borrow().value.clone()
     }
@@ -32,9 +142,7 @@ impl<T: Clone + 'static> Signal<T> {
     pub fn set(&self, new_value: T) {
         self.0.borrow_mut().value = new_value;
         let subscribers = self.0.borrow().subscribers.clone();
-        for subscriber in subscribers {
-            subscriber();
-        }
+        run_subscribers(|| self.name(), None, &subscribers);
     }
 
     pub fn subscribe(&self, subscriber: Subscriber) {
@@ -105,11 +213,49 @@ impl<T: Clone + 'static> Bindable<T> for Signal<T> {
     fn bind(self, f: impl Fn(T) + 'static) {
         let signal = self.clone();
 
-        let runner = Rc::new(move || {
+        let runner: Subscriber = Rc::new(move || {
             f(signal.get());
         });
 
-        runner();
+        run_subscribers(|| self.name(), Some(UpdateCause::Bound), std::slice::from_ref(&runner));
         self.subscribe(runner);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trace_records_why_signals_updated() {
+        let count = Signal::new(1).label("count");
+        let doubled = count.map(|count| count * 2).label("doubled");
+        doubled.clone().bind(|_: i32| {});
+
+        start_update_trace();
+        count.set(2);
+        let updates = finish_update_trace();
+
+        assert_eq!(
+            updates,
+            vec![
+                SignalUpdate {
+                    signal: "count".to_string(),
+                    cause: UpdateCause::Set,
+                    subscribers: 1,
+                    depth: 0,
+                },
+                SignalUpdate {
+                    signal: "doubled".to_string(),
+                    cause: UpdateCause::Derived {
+                        from: "count".to_string()
+                    },
+                    subscribers: 1,
+                    depth: 1,
+                },
+            ]
+        );
+        assert_eq!(updates[1].to_string(), "  doubled (derived from count), 1 subscribers");
+        assert!(finish_update_trace().is_empty());
+    }
+}
@@ -12,6 +12,8 @@ use icu::calendar::preferences::FirstDay;
 use icu::calendar::week::WeekPreferences;
 use icu::calendar::{Date, Gregorian};
 use icu::datetime::{fieldsets, FixedCalendarDateTimeFormatter};
+use icu::decimal::input::Decimal;
+use icu::decimal::DecimalFormatter;
 
 pub use icu::calendar::options::DateAddOptions;
 pub use icu::calendar::types::{DateDuration, EraYear, Month, Weekday, YearInfo};
@@ -131,3 +133,30 @@ pub fn year_name(locale: &Locale, year: i32) -> String {
         formatter.format(&date).to_string()
     })
 }
+
+/// The characters a locale writes numbers with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NumberSymbols {
+    pub decimal_separator: char,
+    /// The separator between groups of thousands, or `None` if the locale doesn't group digits.
+    pub group_separator: Option<char>,
+}
+
+/// Returns the separators of numbers in `locale`, e.g. `,` and `.` for German.
+pub fn number_symbols(locale: &Locale) -> NumberSymbols {
+    let formatter = DecimalFormatter::try_new(locale.into(), Default::default()).expect("Failed to create formatter");
+    let number = Decimal::try_from_str("1234567.5").expect("Invalid number");
+
+    // Some locales mark the direction of the text around numbers, which isn't a separator.
+    let separators: Vec<char> = formatter
+        .format(&number)
+        .to_string()
+        .chars()
+        .filter(|c| !c.is_numeric() && !matches!(c, '\u{200E}' | '\u{200F}' | '\u{061C}'))
+        .collect();
+
+    NumberSymbols {
+        decimal_separator: separators.last().copied().unwrap_or('.'),
+        group_separator: separators.first().copied().filter(|_| separators.len() > 1),
+    }
+}
@@ -0,0 +1,94 @@
+//! Runs a binary that calls `craft_retained::preview::Preview::run` and restarts it whenever its sources change.
+//!
+//! Usage: `cargo run -p craft_preview -- <cargo run arguments>`, e.g.
+//! `cargo run -p craft_preview -- -p my_widgets --example button_preview`.
+//!
+//! Rust sources, RON files and manifests under the current directory are watched, except in `target` and hidden
+//! directories.
+
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::time::{Duration, SystemTime};
+use std::{env, fs, thread};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+fn main() {
+    let cargo_args: Vec<String> = env::args().skip(1).collect();
+    if cargo_args.is_empty() {
+        eprintln!("Usage: craft_preview <cargo run arguments>");
+        std::process::exit(1);
+    }
+
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let root = env::current_dir().expect("The current directory must be readable.");
+
+    let mut last_modified = latest_modification(&root);
+    let mut child = spawn(&cargo, &cargo_args);
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let modified = latest_modification(&root);
+        if modified <= last_modified {
+            continue;
+        }
+        last_modified = modified;
+
+        println!("Sources changed, rebuilding the preview.");
+        if let Some(mut running) = child.take() {
+            let _ = running.kill();
+            let _ = running.wait();
+        }
+        child = spawn(&cargo, &cargo_args);
+    }
+}
+
+fn spawn(cargo: &str, cargo_args: &[String]) -> Option<Child> {
+    match Command::new(cargo).arg("run").args(cargo_args).spawn() {
+        Ok(child) => Some(child),
+        Err(error) => {
+            eprintln!("Failed to run cargo: {error}");
+            None
+        }
+    }
+}
+
+/// Returns the latest modification time of the Rust sources and manifests under `dir`.
+fn latest_modification(dir: &Path) -> SystemTime {
+    let mut latest = SystemTime::UNIX_EPOCH;
+    let mut stack: Vec<PathBuf> = vec![dir.to_path_buf()];
+
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+
+            if file_type.is_dir() {
+                let is_hidden_or_target = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name == "target" || name.starts_with('.'));
+                if !is_hidden_or_target {
+                    stack.push(path);
+                }
+            } else if is_watched(&path)
+                && let Ok(modified) = entry.metadata().and_then(|metadata| metadata.modified())
+            {
+                latest = latest.max(modified);
+            }
+        }
+    }
+
+    latest
+}
+
+fn is_watched(path: &Path) -> bool {
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    matches!(extension, Some("rs" | "ron" | "toml"))
+}
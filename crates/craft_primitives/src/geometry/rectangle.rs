@@ -41,6 +41,27 @@ impl Rectangle {
             height: dpi::PhysicalUnit::from_logical::<f32, f32>(self.height, scale_factor).0,
         }
     }
+
+    /// Rounds each edge of a rectangle in physical pixels to the nearest whole pixel.
+    pub fn snap_to_pixels(&self) -> Self {
+        let x0 = self.left().round();
+        let y0 = self.top().round();
+        let x1 = self.right().round();
+        let y1 = self.bottom().round();
+        Rectangle::new(x0, y0, x1 - x0, y1 - y0)
+    }
+
+    /// Snaps a rectangle in logical pixels so that its edges land on physical pixels.
+    pub fn snap_to_device_pixels(&self, scale_factor: f64) -> Self {
+        let scale_factor = scale_factor as f32;
+        let snapped = self.scale(scale_factor as f64).snap_to_pixels();
+        Rectangle::new(
+            snapped.x / scale_factor,
+            snapped.y / scale_factor,
+            snapped.width / scale_factor,
+            snapped.height / scale_factor,
+        )
+    }
 }
 
 impl Rectangle {
@@ -1,4 +1,5 @@
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TrblRectangle<T> {
     pub top: T,
     pub right: T,
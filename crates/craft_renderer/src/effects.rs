@@ -0,0 +1,40 @@
+//! Blend modes and color filters applied to layers.
+
+use peniko::{BlendMode, Color, Compose, Mix};
+
+/// A simple color filter applied to everything drawn inside an effect layer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorFilter {
+    /// Removes all saturation.
+    Grayscale,
+    /// Tints the content a warm brown.
+    Sepia,
+    /// Inverts every color channel.
+    Invert,
+    /// Scales the brightness of the content. `1.0` leaves it unchanged, `0.0` makes it black and `2.0` makes it white.
+    Brightness(f32),
+}
+
+impl ColorFilter {
+    /// The fills, covering the layer, that produce this filter.
+    ///
+    /// Each fill is drawn with `Compose::SrcAtop`, so only pixels already drawn in the layer are affected.
+    pub fn fills(&self) -> Vec<(BlendMode, Color)> {
+        match *self {
+            ColorFilter::Grayscale => vec![(BlendMode::new(Mix::Saturation, Compose::SrcAtop), Color::from_rgb8(128, 128, 128))],
+            ColorFilter::Sepia => vec![
+                (BlendMode::new(Mix::Saturation, Compose::SrcAtop), Color::from_rgb8(128, 128, 128)),
+                (BlendMode::new(Mix::Color, Compose::SrcAtop), Color::from_rgb8(112, 66, 20)),
+            ],
+            ColorFilter::Invert => vec![(BlendMode::new(Mix::Difference, Compose::SrcAtop), Color::WHITE)],
+            ColorFilter::Brightness(amount) if amount < 1.0 => {
+                vec![(BlendMode::new(Mix::Normal, Compose::SrcAtop), Color::BLACK.with_alpha(1.0 - amount.max(0.0)))]
+            }
+            ColorFilter::Brightness(amount) if amount > 1.0 => {
+                vec![(BlendMode::new(Mix::Screen, Compose::SrcAtop), Color::WHITE.with_alpha((amount - 1.0).min(1.0)))]
+            }
+            ColorFilter::Brightness(_) => Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! Decides how long images uploaded to the GPU stay there after they were last drawn.
+//!
+//! Uploading an image is expensive, so an image that scrolled out of view is kept for a while in case it scrolls back
+//! in. Images are evicted once they went unused for too many frames, or, least recently used first, when the uploaded
+//! images take more than the byte budget.
+
+use std::collections::HashMap;
+
+use crate::resource_mapper::RendererResourceId;
+
+/// Images are only compacted once at least this many bytes were freed, so small atlases aren't re-uploaded for
+/// little gain.
+const MIN_COMPACTION_BYTES: usize = 16 * 1024 * 1024;
+
+/// How long uploaded images are kept after they were last drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImageRetention {
+    /// The number of frames an image may go undrawn before it is evicted. `0` evicts images as soon as a frame doesn't
+    /// draw them.
+    pub max_unused_frames: u64,
+    /// The most bytes the uploaded images may take. Images that weren't drawn in the current frame are evicted, least
+    /// recently used first, until the images fit. The images drawn in a frame are never evicted, even when they alone
+    /// take more than the budget.
+    pub byte_budget: usize,
+    /// Whether the images are re-uploaded together once many were evicted, to close the gaps the evicted images left
+    /// in the texture atlas.
+    pub defragment: bool,
+}
+
+impl Default for ImageRetention {
+    fn default() -> Self {
+        Self {
+            max_unused_frames: 120,
+            byte_budget: 256 * 1024 * 1024,
+            defragment: true,
+        }
+    }
+}
+
+/// Counts of what the image cache of a renderer did, since the renderer was created.
+///
+/// A list that keeps uploading and evicting the same images while it is scrolled needs a longer retention or a
+/// larger budget.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ImageCacheStats {
+    /// The images that are uploaded now.
+    pub resident_images: usize,
+    /// The bytes the uploaded images take.
+    pub resident_bytes: usize,
+    pub uploads: u64,
+    pub uploaded_bytes: u64,
+    pub evictions: u64,
+    pub evicted_bytes: u64,
+    /// How often all images were re-uploaded to defragment the atlas.
+    pub compactions: u64,
+}
+
+struct CachedImage {
+    bytes: usize,
+    /// The frame the image was last drawn in.
+    last_used: u64,
+}
+
+/// Tracks when the uploaded images were last drawn, and decides which to evict.
+#[derive(Default)]
+pub(crate) struct ImageCache {
+    retention: ImageRetention,
+    images: HashMap<RendererResourceId, CachedImage>,
+    frame: u64,
+    resident_bytes: usize,
+    /// The bytes evicted since the images were last compacted.
+    freed_bytes: usize,
+    stats: ImageCacheStats,
+}
+
+impl ImageCache {
+    pub(crate) fn set_retention(&mut self, retention: ImageRetention) {
+        self.retention = retention;
+    }
+
+    /// Starts a new frame. Images drawn before this are no longer drawn in the current frame.
+    pub(crate) fn start_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    /// Records an image that was uploaded and drawn in the current frame.
+    pub(crate) fn uploaded(&mut self, id: RendererResourceId, bytes: usize) {
+        if let Some(previous) = self.images.insert(
+            id,
+            CachedImage {
+                bytes,
+                last_used: self.frame,
+            },
+        ) {
+            self.resident_bytes -= previous.bytes;
+        }
+        self.resident_bytes += bytes;
+        self.stats.uploads += 1;
+        self.stats.uploaded_bytes += bytes as u64;
+    }
+
+    /// Records an image that was drawn in the current frame.
+    pub(crate) fn used(&mut self, id: &RendererResourceId) {
+        if let Some(image) = self.images.get_mut(id) {
+            image.last_used = self.frame;
+        }
+    }
+
+    /// Removes and returns the images to evict at the end of the current frame.
+    pub(crate) fn take_evictions(&mut self) -> Vec<RendererResourceId> {
+        let frame = self.frame;
+        let max_unused_frames = self.retention.max_unused_frames;
+        let mut evictions: Vec<RendererResourceId> = self
+            .images
+            .iter()
+            .filter(|(_, image)| frame - image.last_used > max_unused_frames)
+            .map(|(id, _)| id.clone())
+            .collect();
+        let evicted_bytes: usize = evictions.iter().map(|id| self.images[id].bytes).sum();
+
+        let mut remaining_bytes = self.resident_bytes - evicted_bytes;
+        if remaining_bytes > self.retention.byte_budget {
+            let mut unused: Vec<(&RendererResourceId, &CachedImage)> = self
+                .images
+                .iter()
+                .filter(|(id, image)| image.last_used < frame && !evictions.contains(id))
+                .collect();
+            unused.sort_by_key(|(_, image)| image.last_used);
+            let mut over_budget = Vec::new();
+            for (id, image) in unused {
+                if remaining_bytes <= self.retention.byte_budget {
+                    break;
+                }
+                remaining_bytes -= image.bytes;
+                over_budget.push(id.clone());
+            }
+            evictions.extend(over_budget);
+        }
+
+        for id in &evictions {
+            let image = self.images.remove(id).unwrap();
+            self.resident_bytes -= image.bytes;
+            self.freed_bytes += image.bytes;
+            self.stats.evictions += 1;
+            self.stats.evicted_bytes += image.bytes as u64;
+        }
+        evictions
+    }
+
+    /// Whether the evicted images left enough gaps that the remaining images should be uploaded again, packed
+    /// together. Checked at the start of a frame, so the images drawn in it are uploaded again right away.
+    pub(crate) fn should_compact(&self) -> bool {
+        self.retention.defragment && self.freed_bytes >= MIN_COMPACTION_BYTES && self.freed_bytes > self.resident_bytes
+    }
+
+    /// Removes and returns all images, to compact them.
+    pub(crate) fn take_all(&mut self) -> Vec<RendererResourceId> {
+        self.resident_bytes = 0;
+        self.freed_bytes = 0;
+        self.stats.compactions += 1;
+        self.images.drain().map(|(id, _)| id).collect()
+    }
+
+    pub(crate) fn stats(&self) -> ImageCacheStats {
+        ImageCacheStats {
+            resident_images: self.images.len(),
+            resident_bytes: self.resident_bytes,
+            ..self.stats
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(max_unused_frames: u64, byte_budget: usize) -> ImageCache {
+        let mut cache = ImageCache::default();
+        cache.set_retention(ImageRetention {
+            max_unused_frames,
+            byte_budget,
+            defragment: true,
+        });
+        cache.start_frame();
+        cache
+    }
+
+    #[test]
+    fn images_are_kept_until_unused_for_too_long() {
+        let mut cache = cache(2, usize::MAX);
+        cache.uploaded(RendererResourceId(1), 100);
+        cache.uploaded(RendererResourceId(2), 100);
+
+        for _ in 0..2 {
+            cache.start_frame();
+            cache.used(&RendererResourceId(2));
+            assert!(cache.take_evictions().is_empty());
+        }
+        cache.start_frame();
+        cache.used(&RendererResourceId(2));
+        assert_eq!(cache.take_evictions(), vec![RendererResourceId(1)]);
+
+        let stats = cache.stats();
+        assert_eq!((stats.resident_images, stats.resident_bytes), (1, 100));
+        assert_eq!((stats.uploads, stats.evictions), (2, 1));
+    }
+
+    #[test]
+    fn least_recently_used_images_are_evicted_over_budget() {
+        let mut cache = cache(100, 250);
+        cache.uploaded(RendererResourceId(1), 100);
+        cache.start_frame();
+        cache.uploaded(RendererResourceId(2), 100);
+        cache.start_frame();
+        cache.uploaded(RendererResourceId(3), 100);
+        assert_eq!(cache.take_evictions(), vec![RendererResourceId(1)]);
+
+        // The images drawn in the frame are kept, even over the budget.
+        cache.start_frame();
+        cache.uploaded(RendererResourceId(4), 300);
+        let mut evictions = cache.take_evictions();
+        evictions.sort_by_key(|id| id.0);
+        assert_eq!(evictions, vec![RendererResourceId(2), RendererResourceId(3)]);
+        assert_eq!(cache.stats().resident_bytes, 300);
+    }
+
+    #[test]
+    fn images_are_compacted_once_most_of_the_atlas_was_freed() {
+        let mut cache = cache(0, usize::MAX);
+        cache.uploaded(RendererResourceId(1), MIN_COMPACTION_BYTES);
+        cache.uploaded(RendererResourceId(2), MIN_COMPACTION_BYTES / 2);
+        cache.start_frame();
+        cache.used(&RendererResourceId(2));
+        cache.take_evictions();
+        assert!(cache.should_compact());
+
+        assert_eq!(cache.take_all(), vec![RendererResourceId(2)]);
+        assert!(!cache.should_compact());
+        assert_eq!(cache.stats().compactions, 1);
+    }
+}
@@ -7,7 +7,9 @@ pub mod renderer;
 pub mod vello_cpu;
 
 pub mod blank_renderer;
+pub mod effects;
 pub(crate) mod helpers;
+pub mod image_cache;
 mod image_adapter;
 pub mod render_command;
 mod render_list;
@@ -16,6 +18,8 @@ mod screenshot;
 mod sort_commands;
 mod target_item;
 pub mod text_renderer_data;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod threaded;
 #[cfg(feature = "vello_hybrid_renderer")]
 pub mod vello_hybrid;
 pub mod resource_mapper;
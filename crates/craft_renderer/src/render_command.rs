@@ -1,14 +1,15 @@
 use std::cell::RefCell;
 use std::rc::Weak;
 
-use peniko::Color;
+use peniko::{BlendMode, Color};
 
 use craft_primitives::geometry::{Affine, BezPath, Circle, Rectangle, Vec2};
 
 use craft_resource_manager::ResourceId;
 
 use crate::Brush;
-use crate::text_renderer_data::{TextData, TextScroll};
+use crate::effects::ColorFilter;
+use crate::text_renderer_data::{TextAntialiasing, TextData, TextScroll};
 
 #[derive(Clone)]
 pub enum RenderCommand {
@@ -25,6 +26,8 @@ pub enum RenderCommand {
     StrokeBezPath(StrokeBezPathCmd),
     EndOverlay,
     BoxShadowCmd(BoxShadowCmd),
+    PushEffectLayer(EffectLayerCmd),
+    ApplyColorFilter(ColorFilterCmd),
 }
 
 #[derive(Copy, Clone)]
@@ -71,6 +74,9 @@ pub struct DrawTextCmd {
     pub text_scroll: Option<TextScroll>,
     pub show_cursor: bool,
     pub transform: Affine,
+    pub antialiasing: TextAntialiasing,
+    /// The window's text contrast. See [`crate::text_renderer_data::stem_darkening`].
+    pub contrast: f32,
 }
 
 #[derive(Clone)]
@@ -79,6 +85,22 @@ pub enum PushLayerCmd {
     Rect(Rectangle, Affine),
 }
 
+/// Starts a layer that is clipped to `rect` and composited with `blend_mode` when popped.
+#[derive(Clone, Copy)]
+pub struct EffectLayerCmd {
+    pub rect: Rectangle,
+    pub blend_mode: BlendMode,
+    pub transform: Affine,
+}
+
+/// Applies a color filter to everything drawn so far in the current layer, within `rect`.
+#[derive(Clone, Copy)]
+pub struct ColorFilterCmd {
+    pub rect: Rectangle,
+    pub filter: ColorFilter,
+    pub transform: Affine,
+}
+
 #[derive(Clone)]
 pub struct FillBezPathCmd {
     pub path: BezPath,
@@ -1,8 +1,10 @@
 use craft_primitives::geometry::{Affine, Rectangle};
 
 use crate::sort_commands::SortedCommands;
+use crate::text_renderer_data::TextAntialiasing;
 use crate::{RenderCommand, TargetItem};
 
+#[derive(Clone)]
 pub struct RenderList {
     pub current_overlay_depth: u64,
     pub targets: Vec<TargetItem>,
@@ -10,7 +12,13 @@ pub struct RenderList {
     /// Stores a sorted list of render command handles. This gets set in `Renderer::sort_render_list`.
     pub overlay: SortedCommands,
     pub cull: Option<Rectangle>,
-    pub transform: Affine
+    pub transform: Affine,
+    /// The number of layers currently pushed.
+    pub layer_depth: u64,
+    /// The requested text antialiasing mode.
+    pub text_antialiasing: TextAntialiasing,
+    /// The default text contrast.
+    pub text_contrast: f32,
 }
 
 impl Default for RenderList {
@@ -28,6 +36,9 @@ impl RenderList {
             overlay: SortedCommands { children: vec![] },
             cull: None,
             transform: Affine::IDENTITY,
+            layer_depth: 0,
+            text_antialiasing: TextAntialiasing::default(),
+            text_contrast: 0.0,
         }
     }
 }
@@ -7,12 +7,15 @@ use craft_primitives::Color;
 use craft_primitives::geometry::{Affine, BezPath, Circle, Rectangle, Shape};
 
 use craft_resource_manager::{ResourceId, ResourceManager};
-use crate::render_command::{BoxShadowCmd, DrawBoxShadow, DrawCircleCmd, DrawCircleOutlineCmd, DrawImageCmd, DrawRectCmd, DrawRectOutlineCmd, DrawTextCmd, FillBezPathCmd, PushLayerCmd, StrokeBezPathCmd};
+use peniko::{BlendMode, Compose, Mix};
+use crate::effects::ColorFilter;
+use crate::image_cache::{ImageCacheStats, ImageRetention};
+use crate::render_command::{BoxShadowCmd, ColorFilterCmd, EffectLayerCmd, DrawBoxShadow, DrawCircleCmd, DrawCircleOutlineCmd, DrawImageCmd, DrawRectCmd, DrawRectOutlineCmd, DrawTextCmd, FillBezPathCmd, PushLayerCmd, StrokeBezPathCmd};
 use crate::render_list::RenderList;
 use crate::{Brush, RenderCommand, TargetItem};
 pub use crate::screenshot::Screenshot;
 use crate::sort_commands::sort_render_list_internal;
-use crate::text_renderer_data::{TextData, TextScroll};
+use crate::text_renderer_data::{TextAntialiasing, TextData, TextScroll};
 
 pub trait Renderer: Any {
     // Surface Functions
@@ -39,6 +42,10 @@ pub trait Renderer: Any {
 
     fn submit(&mut self, resource_manager: Arc<ResourceManager>);
 
+    /// Returns the last frame drawn to the surface.
+    ///
+    /// Backends that only copy frames on request return an empty screenshot if the last frame wasn't copied, and
+    /// copy the frames drawn after it until one is read. See [`Renderer::request_readback`].
     fn screenshot(&self) -> Screenshot {
         Screenshot {
             width: 0,
@@ -47,11 +54,78 @@ pub trait Renderer: Any {
         }
     }
 
+    /// Renders the render list to an image of `width` by `height` physical pixels on a transparent background,
+    /// instead of to the surface.
+    ///
+    /// Returns `None` if the backend can't render offscreen.
+    fn render_offscreen(
+        &mut self,
+        _resource_manager: Arc<ResourceManager>,
+        _width: u16,
+        _height: u16,
+    ) -> Option<Screenshot> {
+        None
+    }
+
+    /// Returns the color of the pixel at `(x, y)` of the last frame, in physical pixels.
+    ///
+    /// Returns `None` if the point is outside the surface, or if the backend can't read back what it drew.
+    fn read_pixel(&self, x: u32, y: u32) -> Option<Color> {
+        self.screenshot().pixel(x, y)
+    }
+
+    /// Asks the backend to copy the next frames, so [`Renderer::screenshot`] and [`Renderer::read_pixel`] can read
+    /// them back. Backends that keep every frame ignore it.
+    fn request_readback(&self) {}
+
+    /// Whether the renderer stopped drawing for good, like a render thread whose renderer panicked. The app can
+    /// replace it with a new one.
+    fn has_failed(&self) -> bool {
+        false
+    }
+
     fn clear(&mut self) {
         self.render_list_mut().targets.clear();
         self.render_list_mut().commands.clear();
         self.render_list_mut().overlay.children.clear();
         self.render_list_mut().transform = Affine::IDENTITY;
+        self.render_list_mut().layer_depth = 0;
+    }
+
+    /// Returns true if the backend can draw subpixel antialiased text onto its surface.
+    fn supports_subpixel_text(&self) -> bool {
+        false
+    }
+
+    fn set_text_antialiasing(&mut self, text_antialiasing: TextAntialiasing) {
+        self.render_list_mut().text_antialiasing = text_antialiasing;
+    }
+
+    fn set_text_contrast(&mut self, text_contrast: f32) {
+        self.render_list_mut().text_contrast = text_contrast;
+    }
+
+    /// Sets how long images stay uploaded after they were last drawn, for backends that upload images to the GPU.
+    fn set_image_retention(&mut self, _image_retention: ImageRetention) {}
+
+    /// Returns what the image cache of the backend did, or the default if the backend doesn't cache images.
+    fn image_cache_stats(&self) -> ImageCacheStats {
+        ImageCacheStats::default()
+    }
+
+    /// Resolves the requested text antialiasing mode for text drawn at the current position in the render list.
+    ///
+    /// Subpixel antialiasing needs an opaque destination, so text inside a layer falls back to grayscale.
+    fn effective_text_antialiasing(&self) -> TextAntialiasing {
+        let render_list = self.render_list();
+        if render_list.text_antialiasing == TextAntialiasing::Subpixel
+            && render_list.layer_depth == 0
+            && self.supports_subpixel_text()
+        {
+            TextAntialiasing::Subpixel
+        } else {
+            TextAntialiasing::Grayscale
+        }
     }
 
     #[inline(always)]
@@ -161,12 +235,16 @@ pub trait Renderer: Any {
             return;
         }
 
+        let antialiasing = self.effective_text_antialiasing();
+        let contrast = self.render_list().text_contrast;
         self.render_list_mut().commands.push(RenderCommand::DrawText(DrawTextCmd {
             rect,
             data,
             text_scroll,
             show_cursor,
             transform,
+            antialiasing,
+            contrast,
         }));
     }
 
@@ -178,7 +256,7 @@ pub trait Renderer: Any {
         }
 
         self.render_list_mut().commands
-            .push(RenderCommand::DrawImage(DrawImageCmd { rect, resource_id, transform: Default::default() }));
+            .push(RenderCommand::DrawImage(DrawImageCmd { rect, resource_id, transform }));
     }
 
     #[inline(always)]
@@ -186,6 +264,7 @@ pub trait Renderer: Any {
         let transform = self.get_transform();
 
         self.render_list_mut().commands.push(RenderCommand::PushLayer(PushLayerCmd::Rect(rect, transform)));
+        self.render_list_mut().layer_depth += 1;
     }
 
     fn push_layer_with_bez_path(&mut self, path: BezPath) {
@@ -193,11 +272,50 @@ pub trait Renderer: Any {
 
         self.render_list_mut().commands
             .push(RenderCommand::PushLayer(PushLayerCmd::BezPath(path, transform)));
+        self.render_list_mut().layer_depth += 1;
+    }
+
+    /// Pushes a layer clipped to `rect` that is blended onto the content below it with `blend_mode`.
+    ///
+    /// Must be balanced with [`Renderer::pop_layer`].
+    fn push_effect_layer(&mut self, rect: Rectangle, blend_mode: BlendMode) {
+        let transform = self.get_transform();
+
+        self.render_list_mut().commands.push(RenderCommand::PushEffectLayer(EffectLayerCmd {
+            rect,
+            blend_mode,
+            transform,
+        }));
+        self.render_list_mut().layer_depth += 1;
+    }
+
+    /// Filters the content drawn so far in the current layer, within `rect`.
+    ///
+    /// Should be used inside a layer pushed with [`Renderer::push_effect_layer`], otherwise everything drawn before
+    /// in the window is filtered.
+    fn apply_color_filter(&mut self, rect: Rectangle, filter: ColorFilter) {
+        let transform = self.get_transform();
+
+        self.render_list_mut().commands.push(RenderCommand::ApplyColorFilter(ColorFilterCmd {
+            rect,
+            filter,
+            transform,
+        }));
+    }
+
+    /// Pushes a layer whose content is used as the alpha mask of the enclosing layer.
+    ///
+    /// Content drawn until the matching [`Renderer::pop_layer`] is kept only where the mask is opaque. The enclosing
+    /// layer should be an isolated layer, e.g. one pushed with [`Renderer::push_effect_layer`], so that the content
+    /// behind it isn't masked as well.
+    fn push_mask_layer(&mut self, rect: Rectangle) {
+        self.push_effect_layer(rect, BlendMode::new(Mix::Normal, Compose::DestIn));
     }
 
     #[inline(always)]
     fn pop_layer(&mut self) {
         self.render_list_mut().commands.push(RenderCommand::PopLayer);
+        self.render_list_mut().layer_depth = self.render_list().layer_depth.saturating_sub(1);
     }
 
     fn start_overlay(&mut self) {
@@ -226,6 +344,11 @@ pub trait Renderer: Any {
     fn set_cull(&mut self, cull: Option<Rectangle>) {
         self.render_list_mut().cull = cull;
     }
+
+    /// Returns true if a rectangle, after the current transform is applied, is entirely outside the cull rectangle.
+    fn is_culled(&self, rect: &Rectangle) -> bool {
+        should_cull_rect(&self.get_transform(), rect, self.render_list().cull.as_ref())
+    }
 }
 
 #[inline(always)]
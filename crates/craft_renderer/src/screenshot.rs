@@ -1,5 +1,61 @@
+use craft_primitives::Color;
+
 pub struct Screenshot {
     pub width: u16,
     pub height: u16,
     pub pixels: Vec<u8>,
 }
+
+impl Screenshot {
+    /// Returns the color of the pixel at `(x, y)` in physical pixels, or `None` if it is outside the screenshot.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Color> {
+        pixel_at(&self.pixels, self.width as u32, self.height as u32, x, y)
+    }
+
+    /// Returns the pixels as straight (not premultiplied) RGBA8, e.g. to save them as an image.
+    pub fn to_unpremultiplied_rgba8(&self) -> Vec<u8> {
+        self.pixels.chunks_exact(4).flat_map(|pixel| unpremultiply([pixel[0], pixel[1], pixel[2], pixel[3]])).collect()
+    }
+}
+
+fn unpremultiply([red, green, blue, alpha]: [u8; 4]) -> [u8; 4] {
+    if alpha == 0 {
+        return [0, 0, 0, 0];
+    }
+    let unpremultiply = |channel: u8| ((channel as u32 * 255 + alpha as u32 / 2) / alpha as u32).min(255) as u8;
+    [unpremultiply(red), unpremultiply(green), unpremultiply(blue), alpha]
+}
+
+/// Reads a pixel from premultiplied RGBA8 pixels, `width` pixels per row.
+pub(crate) fn pixel_at(pixels: &[u8], width: u32, height: u32, x: u32, y: u32) -> Option<Color> {
+    if x >= width || y >= height {
+        return None;
+    }
+    let offset = 4 * (y as usize * width as usize + x as usize);
+    let pixel: [u8; 4] = pixels.get(offset..offset + 4)?.try_into().ok()?;
+    if pixel[3] == 0 {
+        return Some(Color::TRANSPARENT);
+    }
+    let [red, green, blue, alpha] = unpremultiply(pixel);
+    Some(Color::from_rgba8(red, green, blue, alpha))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixels_are_unpremultiplied() {
+        let screenshot = Screenshot {
+            width: 2,
+            height: 1,
+            pixels: vec![255, 0, 0, 255, 0, 64, 0, 128],
+        };
+
+        assert_eq!(screenshot.pixel(0, 0), Some(Color::from_rgba8(255, 0, 0, 255)));
+        assert_eq!(screenshot.pixel(1, 0), Some(Color::from_rgba8(0, 128, 0, 128)));
+        assert_eq!(screenshot.pixel(2, 0), None);
+        assert_eq!(screenshot.pixel(0, 1), None);
+        assert_eq!(screenshot.to_unpremultiplied_rgba8(), vec![255, 0, 0, 255, 0, 128, 0, 128]);
+    }
+}
@@ -1,12 +1,12 @@
 use crate::{RenderCommand, RenderList};
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum SortedItem {
     Overlay(SortedCommands),
     Other(u32),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct SortedCommands {
     pub children: Vec<SortedItem>,
 }
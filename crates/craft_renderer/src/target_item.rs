@@ -1,6 +1,6 @@
 use craft_primitives::geometry::Rectangle;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TargetItem {
     pub custom_id: u64,
     pub rectangle: Rectangle,
@@ -1,7 +1,7 @@
 use craft_primitives::ColorBrush;
 use craft_primitives::geometry::Rectangle;
 use peniko::Color;
-use peniko::kurbo::{Affine, Line};
+use peniko::kurbo::{Affine, BezPath, Circle, Line, Shape};
 
 #[derive(Debug, Clone, Copy, Default)]
 pub struct TextScroll {
@@ -18,11 +18,135 @@ impl TextScroll {
     }
 }
 
+/// How glyph edges are antialiased.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextAntialiasing {
+    /// Coverage is computed once per pixel.
+    #[default]
+    Grayscale,
+    /// Coverage is computed per color channel, assuming a horizontal RGB subpixel layout.
+    ///
+    /// Only used when the backend supports it and the text is drawn directly onto an opaque surface,
+    /// otherwise grayscale antialiasing is used.
+    Subpixel,
+}
+
 #[derive(Clone, Debug)]
 pub struct TextRender {
     pub lines: Vec<TextRenderLine>,
     pub cursor: Option<(Rectangle, Color)>,
+    /// The cursors of the other carets of a text input with multiple carets, drawn like `cursor` and only with it.
+    pub extra_cursors: Vec<Rectangle>,
     pub override_brush: Option<ColorBrush>,
+    /// Overrides the text contrast configured for the window.
+    pub contrast: Option<f32>,
+    /// The color of the text inside the selections, if it differs from the text outside them.
+    pub selection_text_color: Option<Color>,
+}
+
+impl TextRender {
+    /// Sets the colors of the selections, e.g. to muted ones while the text doesn't have focus.
+    pub fn set_selection_colors(&mut self, background: Color, text: Option<Color>) {
+        for line in &mut self.lines {
+            for (_, color) in &mut line.selections {
+                *color = background;
+            }
+        }
+        self.selection_text_color = text;
+    }
+}
+
+/// The largest horizontal emboldening, in physical pixels, applied at full contrast.
+const MAX_STEM_DARKENING: f32 = 0.5;
+/// Font sizes, in physical pixels, at or above which no darkening is applied.
+const STEM_DARKENING_END_SIZE: f32 = 24.0;
+/// Font sizes, in physical pixels, at or below which the full darkening is applied.
+const STEM_DARKENING_START_SIZE: f32 = 10.0;
+
+/// Returns how far, in physical pixels, glyph stems should be thickened for the given font size and contrast.
+///
+/// Similar to stem darkening, small text is thickened the most and large text is left untouched.
+/// A contrast of `0.0` disables the adjustment and `1.0` applies the maximum.
+pub fn stem_darkening(font_size: f32, contrast: f32) -> f32 {
+    let contrast = contrast.clamp(0.0, 1.0);
+    let size_factor = ((STEM_DARKENING_END_SIZE - font_size) / (STEM_DARKENING_END_SIZE - STEM_DARKENING_START_SIZE))
+        .clamp(0.0, 1.0);
+    MAX_STEM_DARKENING * contrast * size_factor
+}
+
+/// Returns the wavy line drawn along the bottom of `rect`, and the width to stroke it with.
+pub fn squiggle_path(rect: &Rectangle) -> (BezPath, f64) {
+    // Scales with the line height, which is in physical pixels, so the line keeps its shape at any scale factor.
+    let amplitude = (rect.height as f64 / 16.0).max(1.0);
+    let period = amplitude * 4.0;
+    let bottom = rect.bottom() as f64 - amplitude;
+    let end = rect.right() as f64;
+
+    let mut path = BezPath::new();
+    let mut x = rect.left() as f64;
+    path.move_to((x, bottom));
+    let mut up = true;
+    while x < end {
+        let next = (x + period / 2.0).min(end);
+        let y = if up { bottom - amplitude } else { bottom };
+        path.line_to((next, y));
+        x = next;
+        up = !up;
+    }
+    (path, amplitude * 0.75)
+}
+
+/// A faint glyph marking an invisible character or the end of a line broken to fit the width of the text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WhitespaceMark {
+    /// A dot in the middle of a space.
+    Space,
+    /// An arrow across a tab.
+    Tab,
+    /// A ring in the middle of a non-breaking space.
+    NonBreakingSpace,
+    /// A hook at the end of a line that continues on the next one.
+    Wrap,
+}
+
+/// Returns the path of a mark over the box of its character, and the width to stroke it with.
+pub fn whitespace_mark_path(mark: WhitespaceMark, rect: &Rectangle) -> (BezPath, f64) {
+    // Like the squiggles, sized from the line height so marks look the same at any scale factor.
+    let size = rect.height as f64 / 8.0;
+    let width = (size / 2.0).max(1.0);
+    let center_x = rect.x as f64 + rect.width as f64 / 2.0;
+    let center_y = rect.y as f64 + rect.height as f64 / 2.0;
+
+    let mut path = BezPath::new();
+    match mark {
+        WhitespaceMark::Space => {
+            // A tiny circle stroked as wide as itself is a dot.
+            path.extend(Circle::new((center_x, center_y), width / 2.0).path_elements(0.1));
+        }
+        WhitespaceMark::NonBreakingSpace => {
+            path.extend(Circle::new((center_x, center_y), size).path_elements(0.1));
+        }
+        WhitespaceMark::Tab => {
+            let left = rect.x as f64 + size;
+            let right = (rect.right() as f64 - size).max(left + size);
+            path.move_to((left, center_y));
+            path.line_to((right, center_y));
+            path.move_to((right - size, center_y - size));
+            path.line_to((right, center_y));
+            path.line_to((right - size, center_y + size));
+        }
+        WhitespaceMark::Wrap => {
+            let left = rect.x as f64 + size;
+            let right = left + size * 2.0;
+            path.move_to((right, center_y - size * 2.0));
+            path.line_to((right, center_y + size));
+            path.line_to((left, center_y + size));
+            path.move_to((left + size, center_y));
+            path.line_to((left, center_y + size));
+            path.line_to((left + size, center_y + size * 2.0));
+        }
+    }
+    (path, width)
 }
 
 #[derive(Clone, Debug)]
@@ -30,6 +154,10 @@ pub struct TextRenderLine {
     pub items: Vec<TextRenderItem>,
     pub selections: Vec<(Rectangle, Color)>,
     pub backgrounds: Vec<(Rectangle, Color)>,
+    /// Ranges underlined with a wavy line, e.g. misspelled words. The line runs along the bottom of each rectangle.
+    pub squiggles: Vec<(Rectangle, Color)>,
+    /// Marks over invisible characters, each over the box of its character.
+    pub whitespace_marks: Vec<(WhitespaceMark, Rectangle, Color)>,
     pub min_y: f32,
     pub max_y: f32,
 }
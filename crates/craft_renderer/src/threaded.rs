@@ -0,0 +1,399 @@
+//! A renderer that encodes and submits frames on a dedicated render thread, one frame behind the UI thread.
+//!
+//! The UI thread records the render list as usual. Submitting a frame sends a copy of the list to the render thread,
+//! which prepares and submits it with the backend renderer while the UI thread lays out the next frame. The UI
+//! thread only waits when the render thread is still busy with the frame before.
+//!
+//! The renderer is created on the render thread, as it isn't `Send`. Platforms that only create surfaces on the main
+//! thread, like macOS, can't use it.
+
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::pin::pin;
+use std::rc::{Rc, Weak};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, JoinHandle, Thread};
+
+use craft_logging::error;
+use craft_primitives::Color;
+use craft_primitives::geometry::{Affine, Rectangle};
+use craft_resource_manager::ResourceManager;
+use winit::window::Window;
+
+use crate::image_cache::{ImageCacheStats, ImageRetention};
+use crate::render_command::{
+    BoxShadowCmd, ColorFilterCmd, DrawCircleCmd, DrawCircleOutlineCmd, DrawImageCmd, DrawRectCmd, DrawRectOutlineCmd,
+    EffectLayerCmd, FillBezPathCmd, PushLayerCmd, StrokeBezPathCmd,
+};
+use crate::render_list::RenderList;
+use crate::renderer::Renderer;
+use crate::screenshot::Screenshot;
+use crate::sort_commands::SortedCommands;
+use crate::text_renderer_data::{TextAntialiasing, TextData, TextRender, TextScroll};
+use crate::{RenderCommand, RendererType, TargetItem};
+
+/// What the render thread reports back after every frame.
+#[derive(Clone, Copy, Default)]
+struct RenderThreadState {
+    supports_subpixel_text: bool,
+    image_cache_stats: ImageCacheStats,
+}
+
+/// A frame for the render thread. Only holds `Send` values, so the compiler checks that nothing of the UI thread
+/// is sent along.
+struct Frame {
+    render_list: DetachedRenderList,
+    /// The text of the `DrawText` commands, by command index, as the text elements live on the UI thread.
+    texts: Vec<(usize, TextRender)>,
+    width: f32,
+    height: f32,
+    clear_color: Color,
+    image_retention: Option<ImageRetention>,
+    resource_manager: Arc<ResourceManager>,
+}
+
+impl Frame {
+    fn new(renderer: &ThreadedRenderer, resource_manager: Arc<ResourceManager>) -> Self {
+        let (render_list, texts) = DetachedRenderList::new(renderer.render_list.clone());
+        Self {
+            render_list,
+            texts,
+            width: renderer.width,
+            height: renderer.height,
+            clear_color: renderer.clear_color,
+            image_retention: renderer.image_retention,
+            resource_manager,
+        }
+    }
+}
+
+/// A render list whose `DrawText` commands don't point to the text data of the UI thread.
+struct DetachedRenderList(RenderList);
+
+// SAFETY: A `RenderList` is only `!Send` because of the `Weak<RefCell<dyn TextData>>` in `DrawTextCmd::data`, as
+// the rest of it is checked to be `Send` below. `DetachedRenderList::new`, its only constructor, sets every `data` to
+// `Weak::new()`. That `Weak` has no allocation, so cloning, upgrading or dropping it on the render thread doesn't
+// touch a reference count of the UI thread. The `Weak`s the render thread sets in `load_frame` are created and
+// dropped there.
+unsafe impl Send for DetachedRenderList {}
+
+/// Fails to compile if a part of a `RenderList`, other than the text data of the `DrawText` commands, stops being
+/// `Send`, as `DetachedRenderList` relies on it.
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    // The fields of `RenderList`.
+    assert_send::<TargetItem>();
+    assert_send::<SortedCommands>();
+    assert_send::<Rectangle>();
+    assert_send::<Affine>();
+    assert_send::<TextAntialiasing>();
+    // The commands, and the fields of `DrawTextCmd` other than `data`.
+    assert_send::<DrawCircleCmd>();
+    assert_send::<DrawCircleOutlineCmd>();
+    assert_send::<DrawRectCmd>();
+    assert_send::<DrawRectOutlineCmd>();
+    assert_send::<DrawImageCmd>();
+    assert_send::<PushLayerCmd>();
+    assert_send::<FillBezPathCmd>();
+    assert_send::<StrokeBezPathCmd>();
+    assert_send::<BoxShadowCmd>();
+    assert_send::<EffectLayerCmd>();
+    assert_send::<ColorFilterCmd>();
+    assert_send::<Option<TextScroll>>();
+};
+
+impl DetachedRenderList {
+    /// Detaches the `DrawText` commands from their text data. Returns the text of each, by command index.
+    fn new(mut render_list: RenderList) -> (Self, Vec<(usize, TextRender)>) {
+        let mut texts = Vec::new();
+        for (index, command) in render_list.commands.iter_mut().enumerate() {
+            if let RenderCommand::DrawText(text) = command {
+                let data = std::mem::replace(&mut text.data, Weak::<RefCell<TextSnapshot>>::new());
+                if let Some(data) = data.upgrade()
+                    && let Some(text_render) = data.borrow().get_text_renderer()
+                {
+                    texts.push((index, text_render.clone()));
+                }
+            }
+        }
+        (Self(render_list), texts)
+    }
+}
+
+enum Message {
+    Frame(Frame),
+    Screenshot(Sender<Screenshot>),
+    RequestReadback,
+    ReadPixel(u32, u32, Sender<Option<Color>>),
+    Offscreen(Frame, u16, u16, Sender<Option<Screenshot>>),
+}
+
+/// The text of a `DrawText` command, owned by the render thread.
+struct TextSnapshot(TextRender);
+
+impl TextData for TextSnapshot {
+    fn get_text_renderer(&self) -> Option<&TextRender> {
+        Some(&self.0)
+    }
+}
+
+/// Records render lists on the UI thread, and draws them with a renderer of `renderer_type` on a render thread.
+pub struct ThreadedRenderer {
+    render_list: RenderList,
+    width: f32,
+    height: f32,
+    clear_color: Color,
+    image_retention: Option<ImageRetention>,
+    /// Holds one frame, so the UI thread runs at most one frame ahead.
+    messages: Option<SyncSender<Message>>,
+    state: Arc<Mutex<RenderThreadState>>,
+    thread: Option<JoinHandle<()>>,
+    /// Set once a message couldn't be sent, because the renderer of the render thread panicked.
+    failed: Cell<bool>,
+}
+
+impl ThreadedRenderer {
+    pub fn new(renderer_type: RendererType, window: Arc<Window>) -> Self {
+        let size = window.inner_size();
+        let (messages, receiver) = mpsc::sync_channel(1);
+        let state = Arc::new(Mutex::new(RenderThreadState::default()));
+        let thread_state = state.clone();
+        let thread = thread::Builder::new()
+            .name("craft-render".to_string())
+            .spawn(move || {
+                let renderer = block_on(renderer_type.create(window));
+                run(&renderer, &receiver, &thread_state);
+            })
+            .expect("Failed to start the render thread");
+        Self {
+            render_list: RenderList::default(),
+            width: size.width as f32,
+            height: size.height as f32,
+            clear_color: Color::WHITE,
+            image_retention: None,
+            messages: Some(messages),
+            state,
+            thread: Some(thread),
+            failed: Cell::new(false),
+        }
+    }
+
+    /// Sends a message to the render thread. Returns `false` if the render thread stopped.
+    fn send(&self, message: Message) -> bool {
+        let sent = self
+            .messages
+            .as_ref()
+            .is_some_and(|messages| messages.send(message).is_ok());
+        if !sent {
+            self.render_thread_stopped();
+        }
+        sent
+    }
+
+    /// Records that the render thread stopped, which only happens when its renderer panicked. Logged once, and
+    /// reported by [`Renderer::has_failed`] so the app can create a new renderer.
+    fn render_thread_stopped(&self) {
+        if !self.failed.replace(true) {
+            error!("The render thread stopped, so no more frames are drawn");
+        }
+    }
+}
+
+impl Drop for ThreadedRenderer {
+    fn drop(&mut self) {
+        // Closing the channel stops the render thread after the frames that were sent.
+        self.messages = None;
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+impl Renderer for ThreadedRenderer {
+    fn surface_width(&self) -> f32 {
+        self.width
+    }
+
+    fn surface_height(&self) -> f32 {
+        self.height
+    }
+
+    fn resize_surface(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn surface_set_clear_color(&mut self, color: Color) {
+        self.clear_color = color;
+    }
+
+    fn render_list(&self) -> &RenderList {
+        &self.render_list
+    }
+
+    fn render_list_mut(&mut self) -> &mut RenderList {
+        &mut self.render_list
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    /// The render list is prepared by the render thread, when the frame is submitted.
+    fn prepare<'a>(&mut self, _resource_manager: Arc<ResourceManager>, _window: Rectangle) {}
+
+    fn submit(&mut self, resource_manager: Arc<ResourceManager>) {
+        self.send(Message::Frame(Frame::new(self, resource_manager)));
+    }
+
+    fn screenshot(&self) -> Screenshot {
+        let (sender, receiver) = mpsc::channel();
+        let screenshot = self
+            .send(Message::Screenshot(sender))
+            .then(|| receiver.recv().ok())
+            .flatten();
+        screenshot.unwrap_or_else(|| {
+            self.render_thread_stopped();
+            Screenshot {
+                width: 0,
+                height: 0,
+                pixels: Vec::new(),
+            }
+        })
+    }
+
+    /// Reads the pixel on the render thread, instead of copying the whole frame over.
+    fn read_pixel(&self, x: u32, y: u32) -> Option<Color> {
+        let (sender, receiver) = mpsc::channel();
+        if !self.send(Message::ReadPixel(x, y, sender)) {
+            return None;
+        }
+        receiver.recv().ok().flatten()
+    }
+
+    fn request_readback(&self) {
+        self.send(Message::RequestReadback);
+    }
+
+    fn has_failed(&self) -> bool {
+        self.failed.get() || self.thread.as_ref().is_some_and(JoinHandle::is_finished)
+    }
+
+    fn render_offscreen(
+        &mut self,
+        resource_manager: Arc<ResourceManager>,
+        width: u16,
+        height: u16,
+    ) -> Option<Screenshot> {
+        let (sender, receiver) = mpsc::channel();
+        self.send(Message::Offscreen(
+            Frame::new(self, resource_manager),
+            width,
+            height,
+            sender,
+        ));
+        receiver.recv().ok().flatten()
+    }
+
+    fn supports_subpixel_text(&self) -> bool {
+        self.state
+            .lock()
+            .map(|state| state.supports_subpixel_text)
+            .unwrap_or_default()
+    }
+
+    fn set_image_retention(&mut self, image_retention: ImageRetention) {
+        self.image_retention = Some(image_retention);
+    }
+
+    fn image_cache_stats(&self) -> ImageCacheStats {
+        self.state
+            .lock()
+            .map(|state| state.image_cache_stats)
+            .unwrap_or_default()
+    }
+}
+
+/// Draws the frames sent by the UI thread until it drops its renderer.
+fn run(renderer: &Rc<RefCell<dyn Renderer>>, messages: &Receiver<Message>, state: &Mutex<RenderThreadState>) {
+    // The text of the last frame, which is drawn again by screenshots.
+    let mut _texts = Vec::new();
+    for message in messages {
+        let mut renderer = renderer.borrow_mut();
+        match message {
+            Message::Frame(frame) => {
+                let resource_manager = frame.resource_manager.clone();
+                _texts = load_frame(&mut *renderer, frame);
+                let window = Rectangle::new(0.0, 0.0, renderer.surface_width(), renderer.surface_height());
+                renderer.prepare(resource_manager.clone(), window);
+                renderer.submit(resource_manager);
+            }
+            Message::Screenshot(reply) => {
+                reply.send(renderer.screenshot()).ok();
+            }
+            Message::RequestReadback => renderer.request_readback(),
+            Message::ReadPixel(x, y, reply) => {
+                reply.send(renderer.read_pixel(x, y)).ok();
+            }
+            Message::Offscreen(frame, width, height, reply) => {
+                let resource_manager = frame.resource_manager.clone();
+                _texts = load_frame(&mut *renderer, frame);
+                reply
+                    .send(renderer.render_offscreen(resource_manager, width, height))
+                    .ok();
+            }
+        }
+        if let Ok(mut state) = state.lock() {
+            state.supports_subpixel_text = renderer.supports_subpixel_text();
+            state.image_cache_stats = renderer.image_cache_stats();
+        }
+    }
+}
+
+/// Moves a frame into the renderer. Returns the text of its `DrawText` commands, which have to live until the
+/// next frame.
+fn load_frame(renderer: &mut dyn Renderer, frame: Frame) -> Vec<Rc<RefCell<TextSnapshot>>> {
+    if renderer.surface_width() != frame.width || renderer.surface_height() != frame.height {
+        renderer.resize_surface(frame.width, frame.height);
+    }
+    renderer.surface_set_clear_color(frame.clear_color);
+    if let Some(image_retention) = frame.image_retention {
+        renderer.set_image_retention(image_retention);
+    }
+    let mut render_list = frame.render_list.0;
+    let mut texts = Vec::with_capacity(frame.texts.len());
+    for (index, text_render) in frame.texts {
+        if let Some(RenderCommand::DrawText(text)) = render_list.commands.get_mut(index) {
+            let snapshot = Rc::new(RefCell::new(TextSnapshot(text_render)));
+            text.data = Rc::downgrade(&snapshot);
+            texts.push(snapshot);
+        }
+    }
+    *renderer.render_list_mut() = render_list;
+    texts
+}
+
+/// Wakes the render thread when the future it waits for can make progress.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Runs a future to completion on the current thread, to create the renderer on the render thread.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+        thread::park();
+    }
+}
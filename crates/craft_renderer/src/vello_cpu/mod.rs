@@ -23,11 +23,11 @@ use winit::window::Window;
 use craft_primitives::geometry::{Rectangle, TOLERANCE};
 use craft_resource_manager::ResourceManager as CraftResourceManager;
 use crate::helpers::{brush_to_paint, rgba_to_encoded_u32};
-use crate::render_command::{BoxShadowCmd, DrawCircleCmd, DrawCircleOutlineCmd, DrawRectCmd, DrawRectOutlineCmd, FillBezPathCmd, PushLayerCmd, StrokeBezPathCmd};
+use crate::render_command::{BoxShadowCmd, ColorFilterCmd, EffectLayerCmd, DrawCircleCmd, DrawCircleOutlineCmd, DrawRectCmd, DrawRectOutlineCmd, FillBezPathCmd, PushLayerCmd, StrokeBezPathCmd};
 use crate::render_list::RenderList;
 use crate::renderer::Renderer;
 use crate::resource_mapper::{RendererResourceId, ResourceMapper};
-use crate::screenshot::Screenshot;
+use crate::screenshot::{pixel_at, Screenshot};
 use crate::sort_commands::SortedCommands;
 use crate::RenderCommand;
 use text::draw_text;
@@ -107,6 +107,22 @@ fn push_layer(scene: &mut RenderContext, cmd: &PushLayerCmd) {
     };
 }
 
+fn push_effect_layer(scene: &mut RenderContext, cmd: &EffectLayerCmd) {
+    scene.set_transform(cmd.transform);
+    let clip_path = &cmd.rect.to_kurbo().into_path(0.1);
+    scene.push_layer(Some(clip_path), Some(cmd.blend_mode), None, None, None);
+}
+
+fn apply_color_filter(scene: &mut RenderContext, cmd: &ColorFilterCmd) {
+    scene.set_transform(cmd.transform);
+    for (blend_mode, color) in cmd.filter.fills() {
+        scene.set_blend_mode(blend_mode);
+        scene.set_paint(PaintType::from(color));
+        scene.fill_rect(&cmd.rect.to_kurbo());
+    }
+    scene.set_blend_mode(BlendMode::new(Mix::Normal, Compose::SrcOver));
+}
+
 fn pop_layer(scene: &mut RenderContext) {
     scene.pop_layer();
 }
@@ -177,6 +193,75 @@ fn draw_box_shadow(scene: &mut RenderContext, cmd: &BoxShadowCmd) {
     }
 }
 
+/// Draws the sorted commands of a render list into a scene, uploading the images they use.
+fn draw_render_list(
+    scene: &mut RenderContext,
+    render_list: &RenderList,
+    resources: &mut Resources,
+    resource_mapper: &mut ResourceMapper,
+    resources_seen: &mut HashSet<RendererResourceId>,
+    resource_manager: Arc<CraftResourceManager>,
+    window: &Rectangle,
+) {
+    SortedCommands::draw(render_list, &render_list.overlay, &mut |command: &RenderCommand| {
+        match command {
+            RenderCommand::DrawRect(cmd) => {
+                draw_rect(scene, cmd);
+            }
+            RenderCommand::DrawRectOutline(cmd) => {
+                draw_rect_outline(scene, cmd);
+            }
+            RenderCommand::DrawImage(cmd) => {
+                if let Some(resource_id) = upload_image(
+                    cmd,
+                    resource_manager.clone(),
+                    resources,
+                    resource_mapper,
+                ) {
+                    draw_image(cmd, scene, resource_manager.clone(), resource_id);
+                }
+
+                // Track the resources used.
+                if let Some(resource) = resource_mapper.get(&cmd.resource_id) {
+                    resources_seen.insert(resource);
+                }
+            }
+            RenderCommand::DrawText(cmd) => {
+                draw_text(cmd, scene, resources, window);
+            }
+            RenderCommand::PushLayer(cmd) => {
+                push_layer(scene, cmd);
+            }
+            RenderCommand::PopLayer => {
+                pop_layer(scene);
+            }
+            RenderCommand::FillBezPath(cmd) => {
+                draw_filled_bez_path(scene, cmd);
+            }
+            RenderCommand::StartOverlay => {}
+            RenderCommand::EndOverlay => {}
+            RenderCommand::BoxShadowCmd(cmd) => {
+                draw_box_shadow(scene, cmd)
+            },
+            RenderCommand::PushEffectLayer(cmd) => {
+                push_effect_layer(scene, cmd);
+            }
+            RenderCommand::ApplyColorFilter(cmd) => {
+                apply_color_filter(scene, cmd);
+            }
+            RenderCommand::DrawCircleOutline(cmd) => {
+                draw_circle_outline(scene, cmd);
+            }
+            RenderCommand::DrawCircle(cmd) => {
+                draw_circle(scene, cmd);
+            }
+            RenderCommand::StrokeBezPath(cmd) => {
+                draw_stroked_bez_path(scene, cmd);
+            }
+        }
+    });
+}
+
 impl VelloCpuRenderer {
     pub fn new(window: Arc<Window>) -> Self {
         let width = window.inner_size().width as u16;
@@ -249,6 +334,11 @@ impl Renderer for VelloCpuRenderer {
         self.clear_color = color;
     }
 
+    fn supports_subpixel_text(&self) -> bool {
+        // The per-channel blending needs an opaque destination.
+        self.clear_color.components[3] == 1.0
+    }
+
     fn render_list(&self) -> &RenderList {
         &self.render_list
     }
@@ -282,58 +372,15 @@ impl Renderer for VelloCpuRenderer {
         self.scene.set_fill_rule(Fill::NonZero);
         self.scene.set_transform(Affine::IDENTITY);
 
-        let render_list = &self.render_list;
-        SortedCommands::draw(&render_list, &render_list.overlay, &mut |command: &RenderCommand| {
-            match command {
-                RenderCommand::DrawRect(cmd) => {
-                    draw_rect(&mut self.scene, cmd);
-                }
-                RenderCommand::DrawRectOutline(cmd) => {
-                    draw_rect_outline(&mut self.scene, cmd);
-                }
-                RenderCommand::DrawImage(cmd) => {
-                    if let Some(resource_id) = upload_image(
-                        cmd,
-                        resource_manager.clone(),
-                        &mut self.resources,
-                        &mut self.resource_mapper,
-                    ) {
-                        draw_image(cmd, &mut self.scene, resource_manager.clone(), resource_id);
-                    }
-
-                    // Track the resources used.
-                    if let Some(resource) = self.resource_mapper.get(&cmd.resource_id) {
-                        self.resources_seen.insert(resource);
-                    }
-                }
-                RenderCommand::DrawText(cmd) => {
-                    draw_text(cmd, &mut self.scene, &mut self.resources, &window);
-                }
-                RenderCommand::PushLayer(cmd) => {
-                    push_layer(&mut self.scene, cmd);
-                }
-                RenderCommand::PopLayer => {
-                    pop_layer(&mut self.scene);
-                }
-                RenderCommand::FillBezPath(cmd) => {
-                    draw_filled_bez_path(&mut self.scene, cmd);
-                }
-                RenderCommand::StartOverlay => {}
-                RenderCommand::EndOverlay => {}
-                RenderCommand::BoxShadowCmd(cmd) => {
-                    draw_box_shadow(&mut self.scene, cmd)
-                },
-                RenderCommand::DrawCircleOutline(cmd) => {
-                    draw_circle_outline(&mut self.scene, cmd);
-                }
-                RenderCommand::DrawCircle(cmd) => {
-                    draw_circle(&mut self.scene, cmd);
-                }
-                RenderCommand::StrokeBezPath(cmd) => {
-                    draw_stroked_bez_path(&mut self.scene, cmd);
-                }
-            }
-        });
+        draw_render_list(
+            &mut self.scene,
+            &self.render_list,
+            &mut self.resources,
+            &mut self.resource_mapper,
+            &mut self.resources_seen,
+            resource_manager,
+            &window,
+        );
 
         self.delete_unseen_resources();
     }
@@ -353,6 +400,50 @@ impl Renderer for VelloCpuRenderer {
             pixels: self.pixmap.data_as_u8_slice().to_vec(),
         }
     }
+
+    fn render_offscreen(
+        &mut self,
+        resource_manager: Arc<CraftResourceManager>,
+        width: u16,
+        height: u16,
+    ) -> Option<Screenshot> {
+        let width = width.max(1);
+        let height = height.max(1);
+        // A scene of its own, so the frame being drawn to the window isn't touched.
+        let mut scene = RenderContext::new(width, height);
+        scene.set_paint(PaintType::Solid(Color::WHITE));
+        scene.set_fill_rule(Fill::NonZero);
+
+        draw_render_list(
+            &mut scene,
+            &self.render_list,
+            &mut self.resources,
+            &mut self.resource_mapper,
+            &mut self.resources_seen,
+            resource_manager,
+            &Rectangle::new(0.0, 0.0, width as f32, height as f32),
+        );
+
+        let mut pixmap = Pixmap::new(width, height);
+        scene.flush();
+        scene.render(&mut pixmap, &mut self.resources);
+        Some(Screenshot {
+            width,
+            height,
+            pixels: pixmap.data_as_u8_slice().to_vec(),
+        })
+    }
+
+    fn read_pixel(&self, x: u32, y: u32) -> Option<Color> {
+        // Reads the pixmap directly, instead of copying all of it into a screenshot.
+        pixel_at(
+            self.pixmap.data_as_u8_slice(),
+            self.pixmap.width() as u32,
+            self.pixmap.height() as u32,
+            x,
+            y,
+        )
+    }
 }
 
 impl VelloCpuRenderer {
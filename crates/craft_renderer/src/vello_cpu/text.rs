@@ -1,6 +1,7 @@
 use kurbo::{Affine, Stroke};
 use peniko::kurbo::Shape;
 
+use peniko::{BlendMode, Color, Compose, Mix};
 use vello_common::paint::PaintType;
 use vello_common::{kurbo, peniko};
 use glifo::Glyph;
@@ -9,7 +10,10 @@ use vello_cpu::{RenderContext, Resources};
 use craft_primitives::geometry::Rectangle;
 use crate::render_command::{DrawRectCmd, DrawTextCmd};
 use crate::vello_cpu::draw_rect;
-use crate::text_renderer_data::TextScroll;
+use crate::text_renderer_data::{TextAntialiasing, TextRenderItem, TextScroll, squiggle_path, stem_darkening, whitespace_mark_path};
+
+/// The horizontal offset of the red and blue subpixels from the center of a pixel.
+const SUBPIXEL_OFFSET: f64 = 1.0 / 3.0;
 
 pub(crate) fn draw_text(cmd: &DrawTextCmd, scene: &mut RenderContext, resources: &mut Resources, window: &Rectangle) {
     let text_container = Rectangle::from_kurbo(cmd.transform.transform_rect_bbox(cmd.rect.to_kurbo()));
@@ -75,39 +79,116 @@ pub(crate) fn draw_text(cmd: &DrawTextCmd, scene: &mut RenderContext, resources:
                 scene.stroke_path(&underline.line.to_path(0.1));
             }
 
-            scene.set_paint(PaintType::from(
-                text_render
-                    .override_brush
-                    .map(|b| b.color)
-                    .unwrap_or_else(|| item.brush.color),
-            ));
-
-            let glyph_run_builder = scene
-                .glyph_run(resources, &item.font)
-                //.atlas_cache(true)
-                .font_size(item.font_size);
-            glyph_run_builder.fill_glyphs(item.glyphs.iter().map(|glyph| Glyph {
-                id: glyph.id,
-                x: glyph.x,
-                y: glyph.y,
-            }));
+            let color = text_render
+                .override_brush
+                .map(|b| b.color)
+                .unwrap_or_else(|| item.brush.color);
+
+            match cmd.antialiasing {
+                TextAntialiasing::Grayscale => {
+                    scene.set_paint(PaintType::from(color));
+                    fill_glyphs(scene, resources, item, 0.0);
+
+                    let darkening = stem_darkening(item.font_size, text_render.contrast.unwrap_or(cmd.contrast));
+                    if darkening > 0.0 {
+                        fill_glyphs(scene, resources, item, darkening);
+                    }
+                }
+                TextAntialiasing::Subpixel => {
+                    draw_subpixel_glyphs(scene, resources, item, color, cmd.transform * text_transform);
+                }
+            }
+        }
+
+        for (squiggle, color) in &line.squiggles {
+            let (path, width) = squiggle_path(squiggle);
+            scene.set_transform(cmd.transform * text_transform);
+            scene.set_stroke(Stroke::new(width));
+            scene.set_paint(PaintType::from(*color));
+            scene.stroke_path(&path);
+        }
+
+        for (mark, rect, color) in &line.whitespace_marks {
+            let (path, width) = whitespace_mark_path(*mark, rect);
+            scene.set_transform(cmd.transform * text_transform);
+            scene.set_stroke(Stroke::new(width));
+            scene.set_paint(PaintType::from(*color));
+            scene.stroke_path(&path);
+        }
+
+        // Draw the selected text again in the selection text color, clipped to the selections.
+        if let Some(selection_text_color) = text_render.selection_text_color {
+            scene.set_transform(cmd.transform * text_transform);
+            for (selection, _) in &line.selections {
+                scene.push_layer(Some(&selection.to_kurbo().into_path(0.1)), None, None, None, None);
+                scene.set_paint(PaintType::from(selection_text_color));
+                for item in &line.items {
+                    fill_glyphs(scene, resources, item, 0.0);
+                }
+                scene.pop_layer();
+            }
         }
     }
 
-    // Draw the cursor
+    // Draw the cursors
     if cmd.show_cursor
         && let Some((cursor, cursor_color)) = &text_render.cursor
     {
-        let cursor_rect = Rectangle {
-            x: cursor.x + cmd.rect.x,
-            y: -scroll + cursor.y + cmd.rect.y,
-            width: cursor.width,
-            height: cursor.height,
-        };
-        draw_rect(scene, &DrawRectCmd {
-            rect: cursor_rect,
-            color: *cursor_color,
-            transform: cmd.transform
-        });
+        for cursor in std::iter::once(cursor).chain(&text_render.extra_cursors) {
+            let cursor_rect = Rectangle {
+                x: cursor.x + cmd.rect.x,
+                y: -scroll + cursor.y + cmd.rect.y,
+                width: cursor.width,
+                height: cursor.height,
+            };
+            draw_rect(scene, &DrawRectCmd {
+                rect: cursor_rect,
+                color: *cursor_color,
+                transform: cmd.transform
+            });
+        }
     }
-}
\ No newline at end of file
+}
+fn fill_glyphs(scene: &mut RenderContext, resources: &mut Resources, item: &TextRenderItem, x_offset: f32) {
+    let glyph_run_builder = scene
+        .glyph_run(resources, &item.font)
+        //.atlas_cache(true)
+        .font_size(item.font_size);
+    glyph_run_builder.fill_glyphs(item.glyphs.iter().map(|glyph| Glyph {
+        id: glyph.id,
+        x: glyph.x + x_offset,
+        y: glyph.y,
+    }));
+}
+
+/// Draws glyphs with per-channel coverage by sampling each color channel at its subpixel position.
+///
+/// For each channel, the destination is first attenuated by the coverage using a multiply blend, then the text color
+/// is added with a plus blend. This only produces correct results over an opaque destination.
+fn draw_subpixel_glyphs(
+    scene: &mut RenderContext,
+    resources: &mut Resources,
+    item: &TextRenderItem,
+    color: Color,
+    transform: Affine,
+) {
+    let [r, g, b, a] = color.components;
+    let channels = [
+        (-SUBPIXEL_OFFSET, Color::new([0.0, 1.0, 1.0, a]), Color::new([r, 0.0, 0.0, a])),
+        (0.0, Color::new([1.0, 0.0, 1.0, a]), Color::new([0.0, g, 0.0, a])),
+        (SUBPIXEL_OFFSET, Color::new([1.0, 1.0, 0.0, a]), Color::new([0.0, 0.0, b, a])),
+    ];
+
+    for (offset, mask, channel_color) in channels {
+        scene.set_transform(transform);
+        scene.set_blend_mode(BlendMode::new(Mix::Multiply, Compose::SrcOver));
+        scene.set_paint(PaintType::from(mask));
+        fill_glyphs(scene, resources, item, offset as f32);
+
+        scene.set_blend_mode(BlendMode::new(Mix::Normal, Compose::Plus));
+        scene.set_paint(PaintType::from(channel_color));
+        fill_glyphs(scene, resources, item, offset as f32);
+    }
+
+    scene.set_blend_mode(BlendMode::new(Mix::Normal, Compose::SrcOver));
+}
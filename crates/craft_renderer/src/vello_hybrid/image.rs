@@ -14,6 +14,7 @@ use craft_resource_manager::ResourceManager;
 use craft_resource_manager::image::ImageResource;
 use craft_resource_manager::resource::Resource;
 use craft_resource_manager::resource_type::ResourceType;
+use crate::image_cache::ImageCache;
 use crate::render_command::DrawImageCmd;
 use crate::resource_mapper::{RendererResourceId, ResourceMapper};
 use crate::vello_hybrid::render_context::DeviceHandle;
@@ -22,6 +23,7 @@ pub(crate) fn upload_image(
     cmd: &DrawImageCmd,
     resource_manager: Arc<ResourceManager>,
     resource_mapper: &mut ResourceMapper,
+    image_cache: &mut ImageCache,
     resources: &mut Resources,
     renderer: &mut VelloRenderer,
     encoder: &mut CommandEncoder,
@@ -32,6 +34,7 @@ pub(crate) fn upload_image(
 
     // TODO: Handle expired images
     let resource_id = if let Some(resource_id) = resource_mapper.get(&cmd.resource_id) {
+        image_cache.used(&resource_id);
         resource_id
     } else {
         let premul_data: Vec<PremulRgba8> = image
@@ -60,6 +63,7 @@ pub(crate) fn upload_image(
         let renderer_resource_id = RendererResourceId(image_id.as_u32() as u64);
 
         resource_mapper.add_mapping(cmd.resource_id.clone(), renderer_resource_id.clone());
+        image_cache.uploaded(renderer_resource_id.clone(), image.image.len());
 
         renderer_resource_id
     };
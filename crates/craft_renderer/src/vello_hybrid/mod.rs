@@ -1,9 +1,10 @@
+mod readback;
 mod render_context;
 pub mod image;
 pub mod text;
 
 use std::any::Any;
-use std::collections::HashSet;
+use std::cell::Cell;
 use std::sync::Arc;
 
 use kurbo::{Affine, Stroke};
@@ -24,11 +25,14 @@ use craft_primitives::geometry::{Rectangle, TOLERANCE};
 use craft_primitives::Color;
 use craft_resource_manager::ResourceManager;
 use crate::helpers::brush_to_paint;
-use crate::render_command::{BoxShadowCmd, DrawCircleCmd, DrawCircleOutlineCmd, DrawRectCmd, DrawRectOutlineCmd, FillBezPathCmd, PushLayerCmd, StrokeBezPathCmd};
+use crate::image_cache::{ImageCache, ImageCacheStats, ImageRetention};
+use crate::render_command::{BoxShadowCmd, ColorFilterCmd, EffectLayerCmd, DrawCircleCmd, DrawCircleOutlineCmd, DrawRectCmd, DrawRectOutlineCmd, FillBezPathCmd, PushLayerCmd, StrokeBezPathCmd};
 use crate::render_list::RenderList;
 use crate::renderer::Renderer;
 use crate::resource_mapper::{RendererResourceId, ResourceMapper};
+use crate::screenshot::Screenshot;
 use crate::sort_commands::SortedCommands;
+use readback::FrameReadback;
 use render_context::{create_vello_renderer, DeviceHandle, RenderContext, RenderSurface};
 use crate::RenderCommand;
 use image::{draw_image, upload_image};
@@ -67,13 +71,20 @@ pub struct VelloHybridRenderer {
 
     resources: Resources,
     resource_mapper: ResourceMapper,
-    resources_seen: HashSet<RendererResourceId>,
+    image_cache: ImageCache,
 
     window: Arc<Window>,
 
     texture_bindings: TextureBindings,
 
     render_list: RenderList,
+
+    /// Whether the frames are copied for screenshots, from a request until a copy is read.
+    readback_requested: Cell<bool>,
+    /// The buffer frames are copied to, kept for the next request.
+    readback: Option<FrameReadback>,
+    /// Whether `readback` holds the last frame.
+    readback_is_current: bool,
 }
 
 impl Renderer for VelloHybridRenderer {
@@ -119,6 +130,14 @@ impl Renderer for VelloHybridRenderer {
         self
     }
 
+    fn set_image_retention(&mut self, image_retention: ImageRetention) {
+        self.image_cache.set_retention(image_retention);
+    }
+
+    fn image_cache_stats(&self) -> ImageCacheStats {
+        self.image_cache.stats()
+    }
+
     fn prepare(
         &mut self,
         resource_manager: Arc<ResourceManager>,
@@ -129,7 +148,7 @@ impl Renderer for VelloHybridRenderer {
             _ => panic!("!!!"),
         };
 
-        self.resources_seen.clear();
+        self.image_cache.start_frame();
 
         // Get the RenderSurface (surface + config)
         let surface = &render_state.surface;
@@ -158,60 +177,28 @@ impl Renderer for VelloHybridRenderer {
                 label: Some("Blit Textures onto a Texture Atlas Encoder"),
             });
 
-        let render_list = &self.render_list;
-        SortedCommands::draw(&render_list, &render_list.overlay, &mut |command: &RenderCommand| {
-
-            match command {
-                RenderCommand::DrawCircle(cmd) => draw_circle(&mut self.scene, cmd),
-                RenderCommand::DrawCircleOutline(cmd) => draw_circle_outline(&mut self.scene, cmd),
-                RenderCommand::DrawRect(cmd) => draw_rect(&mut self.scene, cmd),
-                RenderCommand::DrawRectOutline(cmd) => draw_rect_outline(&mut self.scene, cmd),
-                RenderCommand::DrawImage(cmd) => {
-                    if let Some(resource_id) = upload_image(
-                        cmd,
-                        resource_manager.clone(),
-                        &mut self.resource_mapper,
-                        &mut self.resources,
-                        renderer,
-                        &mut encoder,
-                        device_handle,
-                    ) {
-                        draw_image(cmd, &mut self.scene, resource_manager.clone(), resource_id);
-                    }
-
-                    // Track the resources used.
-                    if let Some(resource) = self.resource_mapper.get(&cmd.resource_id) {
-                        self.resources_seen.insert(resource);
-                    }
-                }
-                RenderCommand::DrawText(cmd) => {
-                    draw_text(
-                        cmd,
-                        &mut self.scene,
-                        &mut self.resources,
-                        &window
-                    );
-                }
-                RenderCommand::PushLayer(cmd) => {
-                    push_layer(cmd, &mut self.scene);
-                }
-                RenderCommand::PopLayer => {
-                    pop_layer(&mut self.scene);
-                }
-                RenderCommand::FillBezPath(cmd) => {
-                    draw_filled_bez_path(cmd, &mut self.scene);
-                }
-                RenderCommand::StrokeBezPath(cmd) => {
-                    draw_stroked_bez_path(cmd, &mut self.scene);
-                }
-                RenderCommand::StartOverlay => {}
-                RenderCommand::EndOverlay => {}
-                RenderCommand::BoxShadowCmd(cmd) => draw_box_shadow(&mut self.scene, cmd),
-            }
-        });
+        // Upload the images drawn in this frame again, packed together, once evictions left the atlas full of gaps.
+        if self.image_cache.should_compact() {
+            let images = self.image_cache.take_all();
+            destroy_images(&images, renderer, &mut encoder, device_handle, &mut self.resources);
+            self.resource_mapper.resources.clear();
+        }
 
-        VelloHybridRenderer::delete_unseen_resources(
-            &mut self.resources_seen,
+        draw_render_list(
+            &mut self.scene,
+            &self.render_list,
+            resource_manager,
+            &mut self.resource_mapper,
+            &mut self.image_cache,
+            &mut self.resources,
+            renderer,
+            &mut encoder,
+            device_handle,
+            &window,
+        );
+
+        VelloHybridRenderer::evict_images(
+            &mut self.image_cache,
             renderer,
             &mut encoder,
             device_handle,
@@ -222,12 +209,117 @@ impl Renderer for VelloHybridRenderer {
         device_handle.queue.submit([encoder.finish()]);
     }
 
+    fn screenshot(&self) -> Screenshot {
+        let screenshot = match (&self.state, &self.readback) {
+            (RenderState::Active(render_state), Some(readback)) if self.readback_is_current => {
+                self.readback_requested.set(false);
+                let surface = &render_state.surface;
+                readback.read(&self.context.devices[surface.dev_id].device, surface.config.format)
+            }
+            _ => {
+                self.request_readback();
+                None
+            }
+        };
+        screenshot.unwrap_or(Screenshot {
+            width: 0,
+            height: 0,
+            pixels: Vec::new(),
+        })
+    }
+
+    fn render_offscreen(
+        &mut self,
+        resource_manager: Arc<ResourceManager>,
+        width: u16,
+        height: u16,
+    ) -> Option<Screenshot> {
+        let RenderState::Active(render_state) = &self.state else {
+            return None;
+        };
+        let surface = &render_state.surface;
+        let width = width.max(1);
+        let height = height.max(1);
+        let renderer = self.renderers[surface.dev_id].as_mut()?;
+        let device_handle = &self.context.devices[surface.dev_id];
+
+        // A scene of its own, so the frame being drawn to the window isn't touched.
+        let mut scene = Scene::new(width, height);
+        let mut encoder = device_handle
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Vello Render Offscreen pass"),
+            });
+        draw_render_list(
+            &mut scene,
+            &self.render_list,
+            resource_manager,
+            &mut self.resource_mapper,
+            &mut self.image_cache,
+            &mut self.resources,
+            renderer,
+            &mut encoder,
+            device_handle,
+            &Rectangle::new(0.0, 0.0, width as f32, height as f32),
+        );
+
+        // The pipelines of the renderer draw in the format of the surface, which the readback converts to RGBA.
+        let render_size = RenderSize {
+            width: width as u32,
+            height: height as u32,
+        };
+        let texture = device_handle.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Texture"),
+            size: wgpu::Extent3d {
+                width: render_size.width,
+                height: render_size.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: surface.config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        renderer
+            .render(
+                &scene,
+                &mut self.resources,
+                &device_handle.device,
+                &device_handle.queue,
+                &mut encoder,
+                &render_size,
+                &texture_view,
+                &self.texture_bindings,
+            )
+            .ok()?;
+
+        let readback = FrameReadback::new(&device_handle.device, render_size.width, render_size.height);
+        readback.copy_from(&mut encoder, &texture);
+        device_handle.queue.submit([encoder.finish()]);
+        readback.read(&device_handle.device, surface.config.format)
+    }
+
+    fn request_readback(&self) {
+        if !self.readback_requested.replace(true) {
+            self.window.request_redraw();
+        }
+    }
+
     fn submit(&mut self, _resource_manager: Arc<ResourceManager>) {
         let render_state = match &mut self.state {
             RenderState::Active(state) => state,
             _ => panic!("Todo: Handle a suspended render state."),
         };
 
+        // The frame is copied only while a screenshot waits for it, as copying every frame costs bandwidth.
+        let copy = self
+            .context
+            .set_copyable(&mut render_state.surface, self.readback_requested.get());
+        self.readback_is_current = false;
+
         // Get the RenderSurface (surface + config)
         let surface = &render_state.surface;
 
@@ -285,6 +377,16 @@ impl Renderer for VelloHybridRenderer {
             )
             .unwrap();
 
+        if copy {
+            let readback = match self.readback.take() {
+                Some(readback) if readback.has_size(render_size.width, render_size.height) => readback,
+                _ => FrameReadback::new(&device_handle.device, render_size.width, render_size.height),
+            };
+            readback.copy_from(&mut encoder, &surface_texture.texture);
+            self.readback = Some(readback);
+            self.readback_is_current = true;
+        }
+
         device_handle.queue.submit([encoder.finish()]);
 
         // Queue the texture to be presented on the surface
@@ -310,10 +412,13 @@ impl VelloHybridRenderer {
             surface_clear_color: Color::WHITE,
             resources: Resources::new(),
             resource_mapper: ResourceMapper::new(),
-            resources_seen: HashSet::with_capacity(20),
+            image_cache: ImageCache::default(),
             window: window.clone(),
             texture_bindings: Default::default(),
             render_list: Default::default(),
+            readback_requested: Cell::new(false),
+            readback: None,
+            readback_is_current: false,
         };
 
         let surface = vello_renderer
@@ -346,28 +451,96 @@ impl VelloHybridRenderer {
         vello_renderer
     }
 
-    pub(crate) fn delete_unseen_resources(resources_seen: &mut HashSet<RendererResourceId>,
-                                          renderer: &mut VelloRenderer,
-                                          encoder: &mut CommandEncoder,
-                                          device_handle: &DeviceHandle,
-                                          resources: &mut Resources,
-                                          resource_mapper: &mut ResourceMapper
+    /// Destroys the images that went unused for too long, or that don't fit in the byte budget.
+    pub(crate) fn evict_images(image_cache: &mut ImageCache,
+                               renderer: &mut VelloRenderer,
+                               encoder: &mut CommandEncoder,
+                               device_handle: &DeviceHandle,
+                               resources: &mut Resources,
+                               resource_mapper: &mut ResourceMapper
     ) {
-        resource_mapper.resources.retain(|_key, value| {
-            if resources_seen.contains(&value) {
-                true
-            } else {
-                renderer.destroy_image(
+        let evictions = image_cache.take_evictions();
+        if evictions.is_empty() {
+            return;
+        }
+        destroy_images(&evictions, renderer, encoder, device_handle, resources);
+        resource_mapper.resources.retain(|_key, value| !evictions.contains(value));
+    }
+}
+
+/// Draws the commands of a render list into a scene, uploading the images it draws.
+#[allow(clippy::too_many_arguments)]
+fn draw_render_list(
+    scene: &mut Scene,
+    render_list: &RenderList,
+    resource_manager: Arc<ResourceManager>,
+    resource_mapper: &mut ResourceMapper,
+    image_cache: &mut ImageCache,
+    resources: &mut Resources,
+    renderer: &mut VelloRenderer,
+    encoder: &mut CommandEncoder,
+    device_handle: &DeviceHandle,
+    window: &Rectangle,
+) {
+    SortedCommands::draw(render_list, &render_list.overlay, &mut |command: &RenderCommand| {
+        match command {
+            RenderCommand::DrawCircle(cmd) => draw_circle(scene, cmd),
+            RenderCommand::DrawCircleOutline(cmd) => draw_circle_outline(scene, cmd),
+            RenderCommand::DrawRect(cmd) => draw_rect(scene, cmd),
+            RenderCommand::DrawRectOutline(cmd) => draw_rect_outline(scene, cmd),
+            RenderCommand::DrawImage(cmd) => {
+                if let Some(resource_id) = upload_image(
+                    cmd,
+                    resource_manager.clone(),
+                    resource_mapper,
+                    image_cache,
                     resources,
-                    &device_handle.device,
-                    &device_handle.queue,
+                    renderer,
                     encoder,
-                    ImageId::new(value.0 as u32),
-                );
-
-                false
+                    device_handle,
+                ) {
+                    draw_image(cmd, scene, resource_manager.clone(), resource_id);
+                }
             }
-        });
+            RenderCommand::DrawText(cmd) => {
+                draw_text(cmd, scene, resources, window);
+            }
+            RenderCommand::PushLayer(cmd) => {
+                push_layer(cmd, scene);
+            }
+            RenderCommand::PopLayer => {
+                pop_layer(scene);
+            }
+            RenderCommand::FillBezPath(cmd) => {
+                draw_filled_bez_path(cmd, scene);
+            }
+            RenderCommand::StrokeBezPath(cmd) => {
+                draw_stroked_bez_path(cmd, scene);
+            }
+            RenderCommand::StartOverlay => {}
+            RenderCommand::EndOverlay => {}
+            RenderCommand::BoxShadowCmd(cmd) => draw_box_shadow(scene, cmd),
+            RenderCommand::PushEffectLayer(cmd) => push_effect_layer(cmd, scene),
+            RenderCommand::ApplyColorFilter(cmd) => apply_color_filter(cmd, scene),
+        }
+    });
+}
+
+fn destroy_images(
+    images: &[RendererResourceId],
+    renderer: &mut VelloRenderer,
+    encoder: &mut CommandEncoder,
+    device_handle: &DeviceHandle,
+    resources: &mut Resources,
+) {
+    for image in images {
+        renderer.destroy_image(
+            resources,
+            &device_handle.device,
+            &device_handle.queue,
+            encoder,
+            ImageId::new(image.0 as u32),
+        );
     }
 }
 
@@ -453,6 +626,22 @@ fn push_layer(cmd: &PushLayerCmd, scene: &mut Scene) {
    };
 }
 
+fn push_effect_layer(cmd: &EffectLayerCmd, scene: &mut Scene) {
+    scene.set_transform(cmd.transform);
+    let clip_path = &cmd.rect.to_kurbo().into_path(0.1);
+    scene.push_layer(Some(clip_path), Some(cmd.blend_mode), None, None, None);
+}
+
+fn apply_color_filter(cmd: &ColorFilterCmd, scene: &mut Scene) {
+    scene.set_transform(cmd.transform);
+    for (blend_mode, color) in cmd.filter.fills() {
+        scene.set_blend_mode(blend_mode);
+        scene.set_paint(PaintType::from(color));
+        scene.fill_rect(&cmd.rect.to_kurbo());
+    }
+    scene.set_blend_mode(BlendMode::new(Mix::Normal, Compose::SrcOver));
+}
+
 fn pop_layer(scene: &mut Scene) {
     scene.pop_layer();
 }
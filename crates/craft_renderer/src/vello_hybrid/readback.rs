@@ -0,0 +1,88 @@
+//! Copies frames into a buffer, so screenshots, the color picker and element captures can read them back.
+
+use std::sync::mpsc;
+
+use wgpu::{CommandEncoder, Device, Texture, TextureFormat};
+
+use crate::screenshot::Screenshot;
+
+pub(crate) struct FrameReadback {
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    /// Rows of the buffer are padded to the alignment wgpu copies with.
+    bytes_per_row: u32,
+}
+
+impl FrameReadback {
+    pub(crate) fn new(device: &Device, width: u32, height: u32) -> Self {
+        let bytes_per_row = (width * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Frame Readback Buffer"),
+            size: bytes_per_row as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        Self {
+            buffer,
+            width,
+            height,
+            bytes_per_row,
+        }
+    }
+
+    pub(crate) fn has_size(&self, width: u32, height: u32) -> bool {
+        self.width == width && self.height == height
+    }
+
+    /// Records a copy of the frame in `texture`, which must have this readback's size.
+    pub(crate) fn copy_from(&self, encoder: &mut CommandEncoder, texture: &Texture) {
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &self.buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    /// Waits for the last copied frame and returns it with RGBA8 pixels.
+    pub(crate) fn read(&self, device: &Device, format: TextureFormat) -> Option<Screenshot> {
+        let (sender, receiver) = mpsc::channel();
+        self.buffer.map_async(wgpu::MapMode::Read, .., move |result| {
+            sender.send(result).ok();
+        });
+        device.poll(wgpu::PollType::wait_indefinitely()).ok()?;
+        receiver.recv().ok()?.ok()?;
+
+        let mut pixels = Vec::with_capacity(self.width as usize * self.height as usize * 4);
+        {
+            let mapped = self.buffer.get_mapped_range(..);
+            for row in mapped.chunks_exact(self.bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..self.width as usize * 4]);
+            }
+        }
+        self.buffer.unmap();
+
+        if matches!(format, TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb) {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        Some(Screenshot {
+            width: self.width as u16,
+            height: self.height as u16,
+            pixels,
+        })
+    }
+}
@@ -30,6 +30,8 @@ pub(crate) struct RenderSurface<'s> {
     pub(crate) config: SurfaceConfiguration,
     /// The device id
     pub(crate) dev_id: usize,
+    /// Whether the platform lets frames be copied out of the surface, for screenshots.
+    pub(crate) can_copy: bool,
 }
 
 pub(crate) fn create_vello_renderer(render_cx: &RenderContext, surface: &RenderSurface) -> Renderer {
@@ -97,6 +99,18 @@ impl RenderContext {
     ) -> RenderSurface<'w> {
         let dev_id = self.device(Some(&surface)).await.expect("No compatible device");
 
+        // Prefer an alpha mode that composites with the content behind the window, so a transparent clear color
+        // lets translucent window materials show through. Opaque frames look the same in every mode.
+        let capabilities = surface.get_capabilities(&self.devices[dev_id].adapter);
+        let alpha_modes = capabilities.alpha_modes;
+        let alpha_mode = [wgpu::CompositeAlphaMode::PreMultiplied, wgpu::CompositeAlphaMode::PostMultiplied]
+            .into_iter()
+            .find(|mode| alpha_modes.contains(mode))
+            .unwrap_or(wgpu::CompositeAlphaMode::Auto);
+
+        // Frames are only copied out of the surface while a screenshot waits for one, see `set_copyable`.
+        let can_copy = capabilities.usages.contains(wgpu::TextureUsages::COPY_SRC);
+
         let config = SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format,
@@ -104,13 +118,14 @@ impl RenderContext {
             height,
             present_mode,
             desired_maximum_frame_latency: 2,
-            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            alpha_mode,
             view_formats: vec![],
         };
         let surface = RenderSurface {
             surface,
             config,
             dev_id,
+            can_copy,
         };
         self.configure_surface(&surface);
         surface
@@ -123,6 +138,16 @@ impl RenderContext {
         self.configure_surface(surface);
     }
 
+    /// Lets the frames of the surface be copied, or stops it. Returns true if they can be copied.
+    pub(crate) fn set_copyable(&self, surface: &mut RenderSurface<'_>, copyable: bool) -> bool {
+        let copyable = copyable && surface.can_copy;
+        if surface.config.usage.contains(wgpu::TextureUsages::COPY_SRC) != copyable {
+            surface.config.usage.set(wgpu::TextureUsages::COPY_SRC, copyable);
+            self.configure_surface(surface);
+        }
+        copyable
+    }
+
     pub(crate) fn configure_surface(&self, surface: &RenderSurface<'_>) {
         let device = &self.devices[surface.dev_id].device;
         surface.surface.configure(device, &surface.config);
@@ -8,7 +8,7 @@ use vello_hybrid::{Resources, Scene};
 
 use craft_primitives::geometry::Rectangle;
 use crate::render_command::{DrawRectCmd, DrawTextCmd};
-use crate::text_renderer_data::TextScroll;
+use crate::text_renderer_data::{TextScroll, squiggle_path, stem_darkening, whitespace_mark_path};
 use crate::vello_hybrid::draw_rect;
 
 pub(crate) fn draw_text(cmd: &DrawTextCmd, scene: &mut Scene, resources: &mut Resources, window: &Rectangle) {
@@ -82,32 +82,71 @@ pub(crate) fn draw_text(cmd: &DrawTextCmd, scene: &mut Scene, resources: &mut Re
                     .unwrap_or_else(|| item.brush.color),
             ));
 
-            let glyph_run_builder = scene
-                .glyph_run(resources, &item.font)
-                //.atlas_cache(true)
-                .font_size(item.font_size);
-            glyph_run_builder.fill_glyphs(item.glyphs.iter().map(|glyph| Glyph {
-                id: glyph.id,
-                x: glyph.x,
-                y: glyph.y,
-            }));
+            let darkening = stem_darkening(item.font_size, text_render.contrast.unwrap_or(cmd.contrast));
+            let passes: &[f32] = if darkening > 0.0 { &[0.0, darkening] } else { &[0.0] };
+            for x_offset in passes {
+                let glyph_run_builder = scene
+                    .glyph_run(resources, &item.font)
+                    //.atlas_cache(true)
+                    .font_size(item.font_size);
+                glyph_run_builder.fill_glyphs(item.glyphs.iter().map(|glyph| Glyph {
+                    id: glyph.id,
+                    x: glyph.x + x_offset,
+                    y: glyph.y,
+                }));
+            }
+        }
+
+        for (squiggle, color) in &line.squiggles {
+            let (path, width) = squiggle_path(squiggle);
+            scene.set_stroke(Stroke::new(width));
+            scene.set_paint(PaintType::from(*color));
+            scene.stroke_path(&path);
+        }
+
+        for (mark, rect, color) in &line.whitespace_marks {
+            let (path, width) = whitespace_mark_path(*mark, rect);
+            scene.set_stroke(Stroke::new(width));
+            scene.set_paint(PaintType::from(*color));
+            scene.stroke_path(&path);
+        }
+
+        // Draw the selected text again in the selection text color, clipped to the selections.
+        if let Some(selection_text_color) = text_render.selection_text_color {
+            for (selection, _) in &line.selections {
+                scene.push_layer(Some(&selection.to_kurbo().into_path(0.1)), None, None, None, None);
+                scene.set_paint(PaintType::from(selection_text_color));
+                for item in &line.items {
+                    scene
+                        .glyph_run(resources, &item.font)
+                        .font_size(item.font_size)
+                        .fill_glyphs(item.glyphs.iter().map(|glyph| Glyph {
+                            id: glyph.id,
+                            x: glyph.x,
+                            y: glyph.y,
+                        }));
+                }
+                scene.pop_layer();
+            }
         }
     }
 
-    // Draw the cursor
+    // Draw the cursors
     if cmd.show_cursor
         && let Some((cursor, cursor_color)) = &text_render.cursor
     {
-        let cursor_rect = Rectangle {
-            x: cursor.x + cmd.rect.x,
-            y: -scroll + cursor.y + cmd.rect.y,
-            width: cursor.width,
-            height: cursor.height,
-        };
-        draw_rect(scene, &DrawRectCmd {
-            rect: cursor_rect,
-            color: *cursor_color,
-            transform: cmd.transform
-        });
+        for cursor in std::iter::once(cursor).chain(&text_render.extra_cursors) {
+            let cursor_rect = Rectangle {
+                x: cursor.x + cmd.rect.x,
+                y: -scroll + cursor.y + cmd.rect.y,
+                width: cursor.width,
+                height: cursor.height,
+            };
+            draw_rect(scene, &DrawRectCmd {
+                rect: cursor_rect,
+                color: *cursor_color,
+                transform: cmd.transform
+            });
+        }
     }
 }
\ No newline at end of file
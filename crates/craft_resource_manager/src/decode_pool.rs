@@ -0,0 +1,178 @@
+//! Decodes downloaded resources on their own threads, so a large image doesn't hold up the runtime thread that also
+//! handles the futures of the app.
+//!
+//! At most a few resources are decoded at once, and the smallest waiting resource goes first, so icons and thumbnails
+//! show while a large photo is still decoding. Each resource is handed back as soon as it is decoded.
+
+use std::any::Any;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Mutex};
+
+type DecodeWork = Box<dyn FnOnce() + Send>;
+
+/// Turns the bytes of a resource into its data.
+pub type Decoder = fn(Vec<u8>) -> Box<dyn Any + Send>;
+
+/// A pool of threads that decode resources.
+#[derive(Clone)]
+pub struct DecodePool {
+    queue: Arc<Mutex<DecodeQueue>>,
+}
+
+struct DecodeQueue {
+    jobs: BinaryHeap<DecodeJob>,
+    next_sequence: u64,
+    /// The threads that are decoding. A thread stops when there is nothing left to decode.
+    workers: usize,
+    max_workers: usize,
+}
+
+struct DecodeJob {
+    /// The size of the encoded data, which is used to decode small resources first.
+    size: usize,
+    sequence: u64,
+    work: DecodeWork,
+}
+
+impl PartialEq for DecodeJob {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for DecodeJob {}
+
+impl PartialOrd for DecodeJob {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DecodeJob {
+    /// The greatest job is decoded first: the smallest one, and then the one that has waited the longest.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.size.cmp(&self.size).then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+impl Default for DecodePool {
+    /// A pool that leaves a core free for the GUI thread, with up to 4 threads.
+    fn default() -> Self {
+        let cores = std::thread::available_parallelism().map_or(2, |cores| cores.get());
+        Self::new(cores.saturating_sub(1).clamp(1, 4))
+    }
+}
+
+impl DecodePool {
+    /// Creates a pool that decodes at most `max_workers` resources at once.
+    pub fn new(max_workers: usize) -> Self {
+        Self {
+            queue: Arc::new(Mutex::new(DecodeQueue {
+                jobs: BinaryHeap::new(),
+                next_sequence: 0,
+                workers: 0,
+                max_workers: max_workers.max(1),
+            })),
+        }
+    }
+
+    /// Decodes `bytes` with `decoder` on the pool. Returns `None` if the decoder panicked.
+    ///
+    /// On the web, where there are no threads, the bytes are decoded right away.
+    pub async fn decode(&self, bytes: Vec<u8>, decoder: Decoder) -> Option<Box<dyn Any + Send>> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            Some(decoder(bytes))
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let (sender, receiver) = craft_runtime::sync::oneshot::channel();
+            self.submit(
+                bytes.len(),
+                Box::new(move || {
+                    sender.send(decoder(bytes)).ok();
+                }),
+            );
+            // The sender is dropped without sending if the decoder panicked.
+            receiver.await.ok()
+        }
+    }
+
+    /// Queues `work`, and starts a thread for it if fewer than the maximum are decoding.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn submit(&self, size: usize, work: DecodeWork) {
+        let mut queue = self.queue.lock().unwrap();
+        let sequence = queue.next_sequence;
+        queue.next_sequence += 1;
+        queue.jobs.push(DecodeJob { size, sequence, work });
+
+        if queue.workers < queue.max_workers {
+            queue.workers += 1;
+            let pool_queue = self.queue.clone();
+            std::thread::Builder::new()
+                .name("craft-decode".to_string())
+                .spawn(move || run_worker(pool_queue))
+                .expect("Failed to start a decode thread");
+        }
+    }
+}
+
+/// Decodes queued resources until there are none left.
+#[cfg(not(target_arch = "wasm32"))]
+fn run_worker(queue: Arc<Mutex<DecodeQueue>>) {
+    loop {
+        let job = {
+            let mut queue = queue.lock().unwrap();
+            match queue.jobs.pop() {
+                Some(job) => job,
+                None => {
+                    queue.workers -= 1;
+                    return;
+                }
+            }
+        };
+
+        // A resource that fails to decode shouldn't take the thread down with it, and the jobs queued behind it.
+        if std::panic::catch_unwind(std::panic::AssertUnwindSafe(job.work)).is_err() {
+            craft_logging::warn!("Failed to decode a resource of {} bytes", job.size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(size: usize, sequence: u64) -> DecodeJob {
+        DecodeJob {
+            size,
+            sequence,
+            work: Box::new(|| {}),
+        }
+    }
+
+    #[test]
+    fn small_resources_are_decoded_first() {
+        let mut jobs = BinaryHeap::from(vec![job(5000, 0), job(10, 1), job(200, 2), job(10, 3)]);
+        let order: Vec<(usize, u64)> = std::iter::from_fn(|| jobs.pop().map(|job| (job.size, job.sequence))).collect();
+        assert_eq!(order, vec![(10, 1), (10, 3), (200, 2), (5000, 0)]);
+    }
+
+    #[test]
+    fn decoded_resources_are_handed_back() {
+        let pool = DecodePool::new(2);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        for size in [3, 1, 2] {
+            let sender = sender.clone();
+            pool.submit(size, Box::new(move || sender.send(size).unwrap()));
+        }
+        pool.submit(4, Box::new(|| panic!("Bad data")));
+        pool.submit(5, Box::new(move || sender.send(5).unwrap()));
+
+        let mut decoded: Vec<usize> = receiver.iter().take(4).collect();
+        decoded.sort_unstable();
+        assert_eq!(decoded, vec![1, 2, 3, 5]);
+    }
+}
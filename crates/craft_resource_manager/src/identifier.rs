@@ -1,10 +1,11 @@
 use std::fmt::Display;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::{fmt, fs};
 
 #[cfg(feature = "http_client")]
 use crate::ResourceId::Url;
-use crate::ResourceId::{File, StaticBytes};
+use crate::ResourceId::{File, Generated, StaticBytes};
 
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
 pub enum ResourceId {
@@ -12,6 +13,9 @@ pub enum ResourceId {
     Url(String),
     File(PathBuf),
     StaticBytes(&'static [u8]),
+    /// A resource made by the app, such as a rendered page, which is inserted into the resource manager instead of
+    /// fetched. Use [`ResourceId::generate`] to get a unique id.
+    Generated(u64),
 }
 
 impl Display for ResourceId {
@@ -21,15 +25,32 @@ impl Display for ResourceId {
             Url(url) => write!(f, "URL: {url}"),
             File(file_path) => write!(f, "File: {:?}", file_path.as_os_str().to_str()),
             StaticBytes(bytes) => write!(f, "Static Bytes: {:?}", bytes.as_ptr()),
+            Generated(id) => write!(f, "Generated: {id}"),
         }
     }
 }
 
 static DUMMY_BYTES: [u8; 0] = [0u8; 0];
 
+static NEXT_GENERATED_ID: AtomicU64 = AtomicU64::new(0);
+
 impl ResourceId {
     pub const DUMMY: ResourceId = ResourceId::StaticBytes(&DUMMY_BYTES);
 
+    /// Returns a new [`ResourceId::Generated`] id that is different from all the ids generated before it.
+    pub fn generate() -> ResourceId {
+        Generated(NEXT_GENERATED_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Whether the resource is fetched over the network, so it can only be downloaded while the device is online.
+    pub fn is_remote(&self) -> bool {
+        match self {
+            #[cfg(feature = "http_client")]
+            Url(_) => true,
+            _ => false,
+        }
+    }
+
     pub async fn fetch_data_from_resource_id(&self) -> Option<Vec<u8>> {
         match self {
             #[cfg(feature = "http_client")]
@@ -58,6 +79,8 @@ impl ResourceId {
                 None
             }
             StaticBytes(bytes) => Some(bytes.to_vec()),
+            // Generated resources are inserted by whatever made them.
+            Generated(_) => None,
         }
     }
 }
@@ -1,3 +1,4 @@
+mod decode_pool;
 mod identifier;
 pub mod image;
 mod lock_free_map;
@@ -11,9 +12,11 @@ use std::any::Any;
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
 use craft_runtime::{CraftRuntimeHandle, Sender};
+pub use crate::decode_pool::{DecodePool, Decoder};
 use crate::decoders::{image_decoder, tinyvg_decoder};
 pub use crate::identifier::ResourceId;
 use crate::lock_free_map::LockFreeMap;
@@ -38,7 +41,11 @@ impl<T: From<ResourceEvent> + 'static> ResourceEventHandler for T {}
 pub struct ResourceManager {
     resources: LockFreeMap<ResourceId, Resource>,
     pub(crate) runtime: CraftRuntimeHandle,
-    decoders: HashMap<ResourceType, fn(Vec<u8>) -> Box<dyn Any + Send>>
+    decoders: HashMap<ResourceType, Decoder>,
+    /// Decodes downloaded resources off the runtime thread.
+    decode_pool: DecodePool,
+    /// The downloads that haven't finished, with a flag that is set to cancel them.
+    downloads: Mutex<HashMap<ResourceId, Arc<AtomicBool>>>,
 }
 
 impl ResourceManager {
@@ -48,10 +55,12 @@ impl ResourceManager {
             runtime: craft_runtime_handle,
             decoders: HashMap::from(
                 [
-                    (ResourceType::Image, image_decoder as fn(Vec<u8>) -> Box<dyn Any + Send + 'static>),
-                    (ResourceType::TinyVg, tinyvg_decoder as fn(Vec<u8>) -> Box<dyn Any + Send + 'static>)
+                    (ResourceType::Image, image_decoder as Decoder),
+                    (ResourceType::TinyVg, tinyvg_decoder as Decoder)
                 ]
             ),
+            decode_pool: DecodePool::default(),
+            downloads: Mutex::new(HashMap::new()),
         }
     }
 
@@ -67,12 +76,37 @@ impl ResourceManager {
         let resource_type = resource_type.clone();
         let decoder_fn =  *self.decoders.get(&resource_type).unwrap();
         let app_sender_copy = app_sender.clone();
+        let decode_pool = self.decode_pool.clone();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.downloads.lock().unwrap().insert(resource_id.clone(), cancelled.clone());
         let f = async move {
+            if cancelled.load(Ordering::Relaxed) {
+                return;
+            }
             let bytes = resource_id.fetch_data_from_resource_id().await;
+            // The download may have been cancelled while the data was fetched.
+            if cancelled.load(Ordering::Relaxed) {
+                return;
+            }
+            let Some(bytes) = bytes else {
+                app_sender_copy
+                    .send(ResourceEvent::Failed(resource_id_copy, resource_type).into())
+                    .await
+                    .expect("Failed to send failed resource event");
+                return;
+            };
+
+            let Some(data) = decode_pool.decode(bytes, decoder_fn).await else {
+                return;
+            };
+            // The download may have been cancelled while the data was decoded.
+            if cancelled.load(Ordering::Relaxed) {
+                return;
+            }
 
             let resource = Resource {
                 resource_type: resource_type.clone(),
-                data: decoder_fn(bytes.unwrap()),
+                data,
                 expiration_time: None,
             };
 
@@ -85,6 +119,28 @@ impl ResourceManager {
         self.runtime.spawn(f);
     }
 
+    /// Cancels a download started with [`Self::async_download_resource_and_send_message_on_finish`].
+    ///
+    /// A request that was already sent may still complete, but its data isn't decoded and no event is sent for it.
+    /// Returns false if the resource isn't being downloaded.
+    pub fn cancel_download(&self, resource_id: &ResourceId) -> bool {
+        let Some(cancelled) = self.downloads.lock().unwrap().remove(resource_id) else {
+            return false;
+        };
+        cancelled.store(true, Ordering::Relaxed);
+        true
+    }
+
+    /// Replaces the pool that decodes downloaded resources, e.g. to decode more at once.
+    pub fn set_decode_pool(&mut self, decode_pool: DecodePool) {
+        self.decode_pool = decode_pool;
+    }
+
+    /// Forgets a download that failed, so the resource can be downloaded again.
+    pub fn download_failed(&self, resource_id: &ResourceId) {
+        self.downloads.lock().unwrap().remove(resource_id);
+    }
+
     pub fn contains(&self, resource_id: &ResourceId) -> bool {
         self.resources.contains(resource_id)
     }
@@ -94,6 +150,7 @@ impl ResourceManager {
     }
 
     pub fn insert(&self, resource_id: ResourceId, resource: Arc<Resource>) {
+        self.downloads.lock().unwrap().remove(&resource_id);
         self.resources.insert(resource_id, resource);
     }
 }
@@ -7,4 +7,6 @@ pub enum ResourceEvent {
     Loaded(ResourceId, ResourceType, Resource),
     #[allow(dead_code)]
     UnLoaded(ResourceId),
+    /// The resource couldn't be fetched, e.g. because the network is down.
+    Failed(ResourceId, ResourceType),
 }
@@ -1,12 +1,19 @@
-use std::cell::RefCell;
+use std::any::Any;
+use std::cell::{Cell, RefCell};
 use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
 use std::rc::{Rc, Weak};
 use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time;
+#[cfg(target_arch = "wasm32")]
+use web_time as time;
 
 #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
 use accesskit::TreeUpdate;
 
-use craft_logging::info;
+use craft_logging::{error, info, warn};
 
 use craft_primitives::geometry::{Point, Size};
 
@@ -19,19 +26,38 @@ use craft_runtime::{CraftRuntimeHandle, Sender};
 use taffy::NodeId;
 
 use ui_events::keyboard::KeyboardEvent;
-use ui_events::pointer::{PointerButtonEvent, PointerScrollEvent, PointerUpdate};
+use ui_events::pointer::{PointerButton, PointerButtonEvent, PointerGestureEvent, PointerScrollEvent, PointerUpdate};
 
 use winit::event::{Ime, WindowEvent};
 use winit::event_loop::ActiveEventLoop;
-use winit::window::WindowId;
+use winit::window::{CursorIcon, Theme, WindowId};
 
 #[cfg(feature = "audio")]
 use crate::elements::{AudioInner, AUDIO_CONTEXT};
-use crate::elements::{ElementIdMap, ElementInternals, Window};
+use crate::attract_mode::{leave_attract_modes, poll_attract_mode};
+use crate::elements::{
+    clear_drop_targets, focused_ime_purpose, poll_file_dialogs, poll_log_views, ElementIdMap, ElementInternals, Window,
+};
 use crate::events::internal::InternalMessage;
-use crate::events::{Event, EventDispatcher, EventKind};
+use crate::idle::{note_user_activity, poll_idle};
+use crate::kiosk::{
+    blocks_shortcuts, note_kiosk_activity, note_render_failure, note_render_success, poll_kiosk,
+    restarts_on_render_failure,
+};
+use crate::network::{NetworkStatus, is_online, poll_network_status};
+use crate::power::{POWER_SAVING_FRAME_INTERVAL, is_power_saving, poll_power_status};
+use crate::document::deliver_mutations;
+use crate::events::{dispatch_queued_events, run_event_hooks, Event, EventDispatcher, EventKind};
 use crate::layout::TaffyTree;
+use crate::quality::{QualityFeature, is_degraded, record_frame};
+use crate::recent_files::poll_recent_files;
+use crate::scroll_animation::update_scroll_animations;
+use crate::shell::poll_jump_list;
+use crate::system_settings::{poll_system_settings, refresh_system_settings};
 use crate::text::text_context::TextContext;
+use crate::tree_updates::apply_tree_updates;
+#[cfg(all(feature = "update_check", not(target_arch = "wasm32")))]
+use crate::update::UpdateMessage;
 use crate::window_manager::WindowManager;
 use crate::CraftOptions;
 
@@ -39,19 +65,37 @@ thread_local! {
     pub(crate) static ELEMENTS: RefCell<ElementIdMap> = RefCell::new(ElementIdMap::new());
     pub(crate) static PENDING_RESOURCES: RefCell<VecDeque<(ResourceId, ResourceType)>> = const { RefCell::new(VecDeque::new()) };
     pub(crate) static IN_PROGRESS_RESOURCES: RefCell<VecDeque<(ResourceId, ResourceType)>> = const { RefCell::new(VecDeque::new()) };
+    /// The remote resources that failed to download, which are tried again when the device is back online.
+    static FAILED_RESOURCES: RefCell<Vec<(ResourceId, ResourceType)>> = const { RefCell::new(Vec::new()) };
     pub(crate) static FOCUS: RefCell<Option<Weak<RefCell<dyn ElementInternals>>>> = RefCell::new(None);
+    /// The element waiting for the color under the next click. See [`ElementInternals::pick_color`].
+    pub(crate) static COLOR_PICK: RefCell<Option<Weak<RefCell<dyn ElementInternals>>>> = RefCell::new(None);
+    /// The elements waiting to be drawn to an image, with their scale. See [`ElementInternals::capture`].
+    pub(crate) static CAPTURES: RefCell<Vec<(Weak<RefCell<dyn ElementInternals>>, f64)>> = const { RefCell::new(Vec::new()) };
     pub(crate) static WINDOW_MANAGER: RefCell<WindowManager> = RefCell::new(WindowManager::new());
     pub(crate) static TAFFY_TREE: RefCell<TaffyTree> = RefCell::new(TaffyTree::new());
     /// An event queue that users or elements can manipulate. Cleared at the start and end of every event dispatch.
     static EVENT_DISPATCH_QUEUE: RefCell<VecDeque<(Event, EventKind)>> = RefCell::new(VecDeque::with_capacity(10));
     /// An event queue for capturing window events not generated by winit.
-    static WINDOW_EVENT_DISPATCH_QUEUE: RefCell<VecDeque<(WindowId, WindowEvent)>> = RefCell::new(VecDeque::with_capacity(10));
+    static WINDOW_EVENT_DISPATCH_QUEUE: RefCell<VecDeque<(WindowId, QueuedWindowEvent)>> = RefCell::new(VecDeque::with_capacity(10));
+    /// Set while drawing when an element is animating and needs to be drawn again.
+    static ANIMATION_FRAME_REQUESTED: Cell<bool> = const { Cell::new(false) };
+    /// Set while drawing to the earliest time an element needs to be drawn again.
+    static ANIMATION_FRAME_TIME: Cell<Option<time::Instant>> = const { Cell::new(None) };
+    /// Whether the window being drawn has keyboard focus.
+    static DRAWING_WINDOW_ACTIVE: Cell<bool> = const { Cell::new(true) };
+    /// See [`CraftOptions::reduced_motion`].
+    static REDUCED_MOTION: Cell<bool> = const { Cell::new(false) };
 }
 
 pub struct App {
     pub(crate) event_dispatcher: EventDispatcher,
-    /// The text context is used to manage fonts and text rendering. It is only valid between resume and pause.
+    /// The text context is used to manage fonts and text rendering. It is only valid between resume and pause, once
+    /// the fonts are loaded. The windows aren't laid out or drawn, and ignore input, until then.
     pub(crate) text_context: Option<TextContext>,
+    /// The text context while the system fonts are loaded on the runtime, see [`App::setup_text_context`].
+    #[cfg(all(not(target_arch = "wasm32"), feature = "system_fonts"))]
+    pub(crate) loading_text_context: Option<craft_runtime::task::JoinHandle<TextContext>>,
     pub(crate) reload_fonts: bool,
     /// The resource manager is used to manage resources such as images and fonts.
     ///
@@ -67,6 +111,14 @@ pub struct App {
 
     /// True if the winit app is active.
     pub(crate) active: bool,
+
+    /// Files dropped onto a window since the last iteration of the event loop.
+    ///
+    /// Winit reports every dropped file separately, so they are collected and dispatched together.
+    pub(crate) dropped_files: Option<(Window, Vec<PathBuf>)>,
+
+    /// When the app started, for [`FrameStats::time_to_first_frame`](crate::frame_stats::FrameStats).
+    pub(crate) started_at: time::Instant,
 }
 
 impl App {
@@ -88,6 +140,13 @@ impl App {
     }
 
     pub fn on_about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if self.poll_text_context() {
+            // The windows draw their first frame once the fonts are loaded.
+            WINDOW_MANAGER.with_borrow_mut(|window_manager| {
+                window_manager.redraw_all(self);
+            });
+        }
+
         #[cfg(feature = "audio")]
         AUDIO_CONTEXT.with(|audio_context| {
             if let Some(ctx) = audio_context.get() {
@@ -102,6 +161,149 @@ impl App {
             }
         });
 
+        poll_log_views();
+
+        for idle_time in poll_idle() {
+            self.send_to_all_windows(EventKind::UserIdle(idle_time));
+        }
+
+        poll_attract_mode();
+
+        let kiosk_idle = poll_kiosk();
+        if kiosk_idle.hide_cursor {
+            set_cursors_visible(false);
+        }
+        if kiosk_idle.reset {
+            self.send_to_all_windows(EventKind::KioskReset());
+        }
+
+        if let Some(system_settings) = poll_system_settings() {
+            self.send_to_all_windows(EventKind::SystemSettingsChanged(system_settings));
+        }
+
+        if let Some(power_status) = poll_power_status() {
+            self.send_to_all_windows(EventKind::PowerStatusChanged(power_status));
+        }
+
+        if let Some(network_status) = poll_network_status() {
+            if network_status == NetworkStatus::Online {
+                retry_failed_resources();
+            }
+            // The windows are redrawn, which starts the downloads that waited for the network.
+            self.send_to_all_windows(EventKind::NetworkStatusChanged(network_status));
+        }
+
+        // The entry the app was started for waits until the windows are open.
+        if self.active {
+            for activation in poll_jump_list() {
+                self.send_to_all_windows(EventKind::JumpListActivated(activation));
+            }
+        }
+
+        if let Some(recent_files) = poll_recent_files() {
+            self.send_to_all_windows(EventKind::RecentFilesChanged(recent_files));
+        }
+
+        #[cfg(all(feature = "update_check", not(target_arch = "wasm32")))]
+        for message in crate::update::poll_updates() {
+            self.send_to_all_windows(match message {
+                UpdateMessage::Available(update) => EventKind::UpdateAvailable(update),
+                UpdateMessage::Downloaded(download) => EventKind::UpdateDownloaded(download),
+            });
+        }
+
+        if apply_tree_updates() {
+            WINDOW_MANAGER.with_borrow_mut(|window_manager| {
+                window_manager.redraw_all(self);
+            });
+        }
+
+        deliver_mutations();
+
+        #[cfg(feature = "speech")]
+        if crate::speech::poll_speech()
+            && let Some(text_context) = self.text_context.as_mut()
+        {
+            dispatch_queued_events(text_context);
+        }
+
+        #[cfg(all(feature = "websocket", not(target_arch = "wasm32")))]
+        if crate::websocket::poll_web_sockets()
+            && let Some(text_context) = self.text_context.as_mut()
+        {
+            dispatch_queued_events(text_context);
+            WINDOW_MANAGER.with_borrow_mut(|window_manager| {
+                window_manager.redraw_all(self);
+            });
+        }
+
+        #[cfg(all(feature = "file_watch", not(target_arch = "wasm32")))]
+        {
+            for (resource_id, resource_type) in crate::file_watch::poll_resource_changes() {
+                self.reload_resource(resource_id, resource_type);
+            }
+            if crate::file_watch::poll_file_watches()
+                && let Some(text_context) = self.text_context.as_mut()
+            {
+                dispatch_queued_events(text_context);
+                WINDOW_MANAGER.with_borrow_mut(|window_manager| {
+                    window_manager.redraw_all(self);
+                });
+            }
+        }
+
+        #[cfg(all(feature = "keyring", not(target_arch = "wasm32")))]
+        if crate::keyring::poll_keyring()
+            && let Some(text_context) = self.text_context.as_mut()
+        {
+            dispatch_queued_events(text_context);
+            WINDOW_MANAGER.with_borrow_mut(|window_manager| {
+                window_manager.redraw_all(self);
+            });
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if crate::process::poll_processes()
+            && let Some(text_context) = self.text_context.as_mut()
+        {
+            dispatch_queued_events(text_context);
+            WINDOW_MANAGER.with_borrow_mut(|window_manager| {
+                window_manager.redraw_all(self);
+            });
+        }
+
+        #[cfg(all(feature = "http_stream", not(target_arch = "wasm32")))]
+        if crate::http_stream::poll_http_streams()
+            && let Some(text_context) = self.text_context.as_mut()
+        {
+            dispatch_queued_events(text_context);
+            WINDOW_MANAGER.with_borrow_mut(|window_manager| {
+                window_manager.redraw_all(self);
+            });
+        }
+
+        if let Some(text_context) = self.text_context.as_mut()
+            && self.event_dispatcher.dispatch_hover_intents(text_context)
+        {
+            WINDOW_MANAGER.with_borrow_mut(|window_manager| {
+                window_manager.redraw_all(self);
+            });
+        }
+
+        if let Some((window, paths)) = self.dropped_files.take() {
+            clear_drop_targets();
+            self.dispatch_event(window, &EventKind::FilesDropped(paths));
+        }
+
+        if poll_file_dialogs()
+            && let Some(text_context) = self.text_context.as_mut()
+        {
+            dispatch_queued_events(text_context);
+            WINDOW_MANAGER.with_borrow_mut(|window_manager| {
+                window_manager.redraw_all(self);
+            });
+        }
+
         WINDOW_MANAGER.with_borrow_mut(|window_manager| {
             window_manager.on_about_to_wait(self, event_loop);
         });
@@ -145,14 +347,52 @@ impl App {
 
     pub fn on_move(&mut self, _window: Window) {}
 
+    pub fn on_window_focused(&mut self, window: Window, focused: bool) {
+        window.inner.borrow_mut().is_active = focused;
+        let winit_window = window.winit_window();
+        if let Some(winit_window) = &winit_window {
+            winit_window.request_redraw();
+        }
+        // The user may be coming back from the system settings.
+        if focused {
+            refresh_system_settings(winit_window.and_then(|winit_window| winit_window.theme()));
+        }
+    }
+
+    pub fn on_theme_changed(&mut self, theme: Theme) {
+        refresh_system_settings(Some(theme));
+    }
+
+    /// Sends an event to every window, and redraws them.
+    fn send_to_all_windows(&mut self, message: EventKind) {
+        let windows = WINDOW_MANAGER.with_borrow(|window_manager| window_manager.windows().to_vec());
+        for window in windows {
+            queue_event(Event::new(window.inner.clone()), message.clone());
+            if let Some(winit_window) = window.winit_window() {
+                winit_window.request_redraw();
+            }
+        }
+        if let Some(text_context) = self.text_context.as_mut() {
+            dispatch_queued_events(text_context);
+        }
+    }
+
     pub fn on_pointer_scroll(&mut self, window: Window, pointer_scroll_update: PointerScrollEvent) {
-        if window.inner.borrow_mut().maybe_zoom(&pointer_scroll_update) {
+        // Kiosks don't let the user zoom the window.
+        if !blocks_shortcuts() && window.inner.borrow_mut().maybe_zoom(&pointer_scroll_update) {
             return;
         }
         self.dispatch_event(window, &EventKind::PointerScroll(pointer_scroll_update));
     }
 
+    pub fn on_pointer_gesture(&mut self, window: Window, pointer_gesture: PointerGestureEvent) {
+        self.dispatch_event(window, &EventKind::PointerGesture(pointer_gesture));
+    }
+
     pub fn on_pointer_button(&mut self, window: Window, pointer_event: PointerButtonEvent, is_up: bool) {
+        if self.maybe_pick_color(&window, &pointer_event, is_up) {
+            return;
+        }
         let cursor_position = pointer_event.state.logical_point();
 
         let event = if is_up {
@@ -170,13 +410,29 @@ impl App {
         self.dispatch_event(window.clone(), &EventKind::PointerMovedEvent(mouse_moved));
     }
 
+    pub fn on_file_hovered(&mut self, window: Window, path: PathBuf) {
+        self.dispatch_event(window, &EventKind::FileHovered(path));
+    }
+
+    pub fn on_file_hover_cancelled(&mut self, window: Window) {
+        clear_drop_targets();
+        self.dispatch_event(window, &EventKind::FileHoverCancelled());
+    }
+
+    pub fn on_file_dropped(&mut self, window: Window, path: PathBuf) {
+        match &mut self.dropped_files {
+            Some((_, paths)) => paths.push(path),
+            None => self.dropped_files = Some((window, vec![path])),
+        }
+    }
+
     pub fn on_ime(&mut self, window: Window, ime: Ime) {
         self.dispatch_event(window.clone(), &EventKind::ImeEvent(ime));
     }
 
     pub fn on_keyboard_input(&mut self, window: Window, keyboard_input: KeyboardEvent) {
         window.inner.borrow_mut().update_modifiers(&keyboard_input);
-        if window.inner.borrow_mut().maybe_zoom_keyboard(&keyboard_input) {
+        if !blocks_shortcuts() && window.inner.borrow_mut().maybe_zoom_keyboard(&keyboard_input) {
             return;
         }
         self.dispatch_event(window.clone(), &EventKind::KeyboardInputEvent(keyboard_input));
@@ -185,9 +441,12 @@ impl App {
     pub fn on_resource_event(&mut self, resource_event: ResourceEvent) {
         match resource_event {
             ResourceEvent::Loaded(resource_id, resource_type, resource) => {
+                #[cfg(all(feature = "file_watch", not(target_arch = "wasm32")))]
+                crate::file_watch::watch_resource(&resource_id, &resource_type);
                 IN_PROGRESS_RESOURCES.with_borrow_mut(|in_progress| {
                     in_progress.retain_mut(|(resource, _resource_type)| *resource != resource_id);
                 });
+                WINDOW_MANAGER.with_borrow(|window_manager| window_manager.invalidate_resource_users(&resource_id));
                 if let Some(_text_context) = self.text_context.as_mut()
                     && resource_type == ResourceType::Font
                 {
@@ -203,15 +462,117 @@ impl App {
                 });
             }
             ResourceEvent::UnLoaded(_) => {}
+            ResourceEvent::Failed(resource_id, resource_type) => {
+                warn!("Failed to load the resource {resource_id}");
+                IN_PROGRESS_RESOURCES.with_borrow_mut(|in_progress| {
+                    in_progress.retain(|(resource, _resource_type)| *resource != resource_id);
+                });
+                self.resource_manager.download_failed(&resource_id);
+                if resource_id.is_remote() {
+                    FAILED_RESOURCES.with_borrow_mut(|failed| failed.push((resource_id, resource_type)));
+                }
+            }
         }
     }
 
     fn on_request_redraw_internal(&mut self, window: Window) {
+        // The window shows its splash color, if it has one, until the fonts are loaded.
+        if self.text_context.is_none() {
+            return;
+        }
+        let update_start = time::Instant::now();
         self.update_resources();
-        window.on_redraw(self.text_context.as_mut().unwrap(), self.resource_manager.clone());
+        update_scroll_animations();
+        let update = update_start.elapsed();
+
+        let unchanged = {
+            let window = window.inner.borrow();
+            window.frame_skipper.is_unchanged(window.frame_key())
+        };
+        if self.craft_options.skip_unchanged_frames && unchanged {
+            window.inner.borrow_mut().present_last_frame(self.resource_manager.clone());
+        } else {
+            self.redraw(&window);
+            // Changes made while drawing are part of this frame, but the events queued after it aren't.
+            let animating = ANIMATION_FRAME_REQUESTED.get() || ANIMATION_FRAME_TIME.get().is_some();
+            let mut window = window.inner.borrow_mut();
+            let key = window.frame_key();
+            window.frame_skipper.drawn(key, animating);
+        }
+        window.inner.borrow_mut().frame_stats.update = update;
+        let frame_stats = window.inner.borrow().frame_stats;
+        if !frame_stats.reused {
+            record_frame(frame_stats.total());
+        }
+        let started_at = self.started_at;
+        window.inner.borrow_mut().frame_stats.time_to_first_frame.get_or_insert_with(|| started_at.elapsed());
+        let ime_purpose = focused_ime_purpose(&Rc::downgrade(&window.inner));
+        window.inner.borrow_mut().set_ime_purpose(ime_purpose);
+        // Sends the images of captured elements.
+        dispatch_queued_events(self.text_context.as_mut().unwrap());
+
+        if ANIMATION_FRAME_REQUESTED.replace(false) {
+            if is_power_saving() {
+                // Animations run at a lower frame rate to save power.
+                request_animation_frame_at(time::Instant::now() + POWER_SAVING_FRAME_INTERVAL);
+            } else if let Some(winit_window) = window.winit_window() {
+                winit_window.request_redraw();
+            }
+        }
+        window.inner.borrow_mut().next_frame_time = ANIMATION_FRAME_TIME.take();
+    }
+
+    /// Lays out and draws the window. In kiosk mode a frame that panics, or a renderer that stopped drawing, like a
+    /// render thread whose renderer panicked, recreates the renderer and draws again.
+    fn redraw(&mut self, window: &Window) {
+        if !restarts_on_render_failure() {
+            window.on_redraw(self.text_context.as_mut().unwrap(), self.resource_manager.clone());
+            return;
+        }
+
+        loop {
+            let text_context = self.text_context.as_mut().unwrap();
+            let resource_manager = self.resource_manager.clone();
+            let result = panic::catch_unwind(AssertUnwindSafe(|| window.on_redraw(text_context, resource_manager)));
+            let renderer_failed = window.inner.borrow().renderer.borrow().has_failed();
+            match result {
+                Ok(()) if !renderer_failed => {
+                    note_render_success();
+                    return;
+                }
+                Ok(()) if note_render_failure() => {
+                    error!("The renderer stopped drawing, restarting it");
+                    window.inner.borrow_mut().restart_renderer(self);
+                }
+                Ok(()) => return,
+                Err(payload) if note_render_failure() => {
+                    error!("Drawing a frame failed, restarting the renderer: {}", panic_message(&*payload));
+                    window.inner.borrow_mut().restart_renderer(self);
+                }
+                Err(payload) => panic::resume_unwind(payload),
+            }
+        }
     }
 
     fn dispatch_event(&mut self, window: Window, message: &EventKind) {
+        // Nothing was drawn to act on before the fonts are loaded.
+        if self.text_context.is_none() {
+            return;
+        }
+        if note_user_activity() {
+            self.send_to_all_windows(EventKind::UserActive());
+        }
+        if note_kiosk_activity() {
+            set_cursors_visible(true);
+        }
+        // The input that ends attract mode only wakes the app.
+        if leave_attract_modes() {
+            return;
+        }
+        let Some(message) = run_event_hooks(&window, message) else {
+            return;
+        };
+        let message = message.as_ref();
         let mouse_pos = window.mouse_position();
         let binding = window.inner.borrow().renderer.clone();
         let render_list = &mut *binding.borrow_mut();
@@ -226,10 +587,60 @@ impl App {
         window.winit_window().unwrap().request_redraw();
     }
 
+    /// Reads the color under a click for the element waiting for it, if there is one.
+    ///
+    /// Returns true if the click was used to pick a color, so it shouldn't reach the elements under it.
+    fn maybe_pick_color(&mut self, window: &Window, pointer_event: &PointerButtonEvent, is_up: bool) -> bool {
+        if pointer_event.button != Some(PointerButton::Primary) || COLOR_PICK.with_borrow(Option::is_none) {
+            return false;
+        }
+        // The color is picked on release, so neither half of the click reaches the elements.
+        if !is_up {
+            window.inner.borrow().renderer.borrow().request_readback();
+            return true;
+        }
+
+        window.inner.borrow().set_cursor(CursorIcon::Default);
+        let Some(picker) = COLOR_PICK.take().and_then(|picker| picker.upgrade()) else {
+            return true;
+        };
+        let position = pointer_event.state.logical_point();
+        let scale_factor = window.effective_scale_factor();
+        let (x, y) = (position.x * scale_factor, position.y * scale_factor);
+        if x < 0.0 || y < 0.0 {
+            return true;
+        }
+        let color = window.inner.borrow().renderer.borrow().read_pixel(x as u32, y as u32);
+        let Some(color) = color else {
+            warn!("The renderer can't read back the color under the pointer.");
+            return true;
+        };
+
+        queue_event(Event::new(picker), EventKind::ColorPicked(color));
+        dispatch_queued_events(self.text_context.as_mut().unwrap());
+        true
+    }
+
+    /// Stops waiting for a click to pick a color. Returns false if no element was waiting for one.
+    pub(crate) fn cancel_color_pick(&mut self, window: &Window) -> bool {
+        if COLOR_PICK.take().is_none() {
+            return false;
+        }
+        window.inner.borrow().set_cursor(CursorIcon::Default);
+        true
+    }
+
     fn update_resources(&mut self) {
+        let online = is_online();
         PENDING_RESOURCES.with_borrow_mut(|pending_resources| {
             IN_PROGRESS_RESOURCES.with_borrow_mut(|in_progress| {
-                for (resource, resource_type) in pending_resources.drain(..) {
+                let resources: Vec<(ResourceId, ResourceType)> = pending_resources.drain(..).collect();
+                for (resource, resource_type) in resources {
+                    // Remote resources wait in the queue until the device is online.
+                    if !online && resource.is_remote() {
+                        pending_resources.push_back((resource, resource_type));
+                        continue;
+                    }
                     if self.resource_manager.contains(&resource)
                         || in_progress.contains(&(resource.clone(), resource_type.clone()))
                     {
@@ -247,41 +658,83 @@ impl App {
         });
     }
 
+    /// Loads a resource that was already loaded again, because its file changed. The old resource is drawn until the
+    /// new one replaces it.
+    #[cfg(all(feature = "file_watch", not(target_arch = "wasm32")))]
+    fn reload_resource(&mut self, resource_id: ResourceId, resource_type: ResourceType) {
+        IN_PROGRESS_RESOURCES.with_borrow_mut(|in_progress| {
+            let resource = (resource_id, resource_type);
+            if in_progress.contains(&resource) {
+                return;
+            }
+            self.resource_manager.async_download_resource_and_send_message_on_finish(
+                self.app_sender.clone(),
+                resource.0.clone(),
+                &resource.1,
+            );
+            in_progress.push_back(resource);
+        });
+    }
+
     /// Initialize any data needed to layout/render text.
+    ///
+    /// Loading the system fonts takes a while, so it runs on the runtime while the windows and their renderers are
+    /// created, and [`App::poll_text_context`] picks it up once it's done.
     fn setup_text_context(&mut self) {
+        #[cfg(all(not(target_arch = "wasm32"), feature = "system_fonts"))]
+        if self.text_context.is_none() && self.loading_text_context.is_none() {
+            self.loading_text_context = Some(self.runtime.spawn_blocking(TextContext::new));
+        }
+
+        #[cfg(any(target_arch = "wasm32", not(feature = "system_fonts")))]
         if self.text_context.is_none() {
-            #[cfg(any(target_arch = "wasm32", not(feature = "system_fonts")))]
             let mut text_context = TextContext::new();
-            #[cfg(all(not(target_arch = "wasm32"), feature = "system_fonts"))]
-            let text_context = TextContext::new();
-
-            #[cfg(any(target_arch = "wasm32", not(feature = "system_fonts")))]
-            {
-                let regular = include_bytes!("../../../fonts/Roboto-Regular.ttf");
-                let bold = include_bytes!("../../../fonts/Roboto-Bold.ttf");
-                let semi_bold = include_bytes!("../../../fonts/Roboto-SemiBold.ttf");
-                let medium = include_bytes!("../../../fonts/Roboto-Medium.ttf");
-
-                fn register_and_append(font_data: &'static [u8], text_context: &mut TextContext) {
-                    let blob = peniko::Blob::new(Arc::new(font_data));
-                    let fonts = text_context.font_context.collection.register_fonts(blob, None);
-
-                    // Register all the Roboto families under parley::GenericFamily::SystemUi.
-                    // This will become the fallback font for platforms like WASM.
-                    text_context
-                        .font_context
-                        .collection
-                        .append_generic_families(parley::GenericFamily::SystemUi, fonts.iter().map(|f| f.0));
-                }
 
-                register_and_append(regular, &mut text_context);
-                register_and_append(bold, &mut text_context);
-                register_and_append(semi_bold, &mut text_context);
-                register_and_append(medium, &mut text_context);
+            let regular = include_bytes!("../../../fonts/Roboto-Regular.ttf");
+            let bold = include_bytes!("../../../fonts/Roboto-Bold.ttf");
+            let semi_bold = include_bytes!("../../../fonts/Roboto-SemiBold.ttf");
+            let medium = include_bytes!("../../../fonts/Roboto-Medium.ttf");
+
+            fn register_and_append(font_data: &'static [u8], text_context: &mut TextContext) {
+                let blob = peniko::Blob::new(Arc::new(font_data));
+                let fonts = text_context.font_context.collection.register_fonts(blob, None);
+
+                // Register all the Roboto families under parley::GenericFamily::SystemUi.
+                // This will become the fallback font for platforms like WASM.
+                text_context
+                    .font_context
+                    .collection
+                    .append_generic_families(parley::GenericFamily::SystemUi, fonts.iter().map(|f| f.0));
             }
 
+            register_and_append(regular, &mut text_context);
+            register_and_append(bold, &mut text_context);
+            register_and_append(semi_bold, &mut text_context);
+            register_and_append(medium, &mut text_context);
+
+            self.text_context = Some(text_context);
+        }
+    }
+
+    /// Sets up the text context once the fonts that started loading in [`App::setup_text_context`] are loaded.
+    /// Returns true if it was set up.
+    ///
+    /// Called once per iteration of the event loop.
+    fn poll_text_context(&mut self) -> bool {
+        #[cfg(all(not(target_arch = "wasm32"), feature = "system_fonts"))]
+        if self.loading_text_context.as_ref().is_some_and(|loading| loading.is_finished()) {
+            let loading_text_context = self.loading_text_context.take().unwrap();
+            // The task is finished, so this doesn't block.
+            let text_context = self
+                .runtime
+                .clone()
+                .borrow_tokio_runtime()
+                .block_on(loading_text_context)
+                .expect("Failed to load the system fonts");
             self.text_context = Some(text_context);
+            return true;
         }
+        false
     }
 }
 
@@ -306,15 +759,111 @@ pub(crate) fn dequeue_event() -> Option<(Event, EventKind)> {
 /// Only user-registered event callbacks will be dispatched.
 pub fn queue_window_event(window_id: WindowId, event: WindowEvent) {
     WINDOW_EVENT_DISPATCH_QUEUE.with_borrow_mut(|event_queue| {
-        event_queue.push_back((window_id, event));
+        event_queue.push_back((window_id, QueuedWindowEvent::Window(event)));
     });
 }
 
+/// Enqueues a key press or release for a window, in order with the events of [`queue_window_event`].
+///
+/// Winit's key events can't be created outside of winit, so keys are queued after they would have been translated.
+pub fn queue_keyboard_event(window_id: WindowId, event: KeyboardEvent) {
+    WINDOW_EVENT_DISPATCH_QUEUE.with_borrow_mut(|event_queue| {
+        event_queue.push_back((window_id, QueuedWindowEvent::Keyboard(event)));
+    });
+}
+
+/// An event for a window that didn't come from winit.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum QueuedWindowEvent {
+    Window(WindowEvent),
+    Keyboard(KeyboardEvent),
+}
+
 /// Pops from the front of the event dispatch queue and returns the result.
-pub(crate) fn dequeue_window_event() -> Option<(WindowId, WindowEvent)> {
+pub(crate) fn dequeue_window_event() -> Option<(WindowId, QueuedWindowEvent)> {
     WINDOW_EVENT_DISPATCH_QUEUE.with_borrow_mut(|event_queue| event_queue.pop_front())
 }
 
+/// Stops loading a resource that was requested but hasn't loaded yet.
+///
+/// Only for resources that no other element is waiting for, such as map tiles that scrolled out of view.
+pub(crate) fn cancel_resource(resource_manager: &ResourceManager, resource_id: &ResourceId) {
+    PENDING_RESOURCES.with_borrow_mut(|pending_resources| {
+        pending_resources.retain(|(resource, _resource_type)| resource != resource_id);
+    });
+    IN_PROGRESS_RESOURCES.with_borrow_mut(|in_progress| {
+        in_progress.retain(|(resource, _resource_type)| resource != resource_id);
+    });
+    FAILED_RESOURCES.with_borrow_mut(|failed| failed.retain(|(resource, _resource_type)| resource != resource_id));
+    resource_manager.cancel_download(resource_id);
+}
+
+/// Shows or hides the pointer over all windows.
+fn set_cursors_visible(visible: bool) {
+    let windows = WINDOW_MANAGER.with_borrow(|window_manager| window_manager.windows().to_vec());
+    for window in windows {
+        if let Some(winit_window) = window.winit_window() {
+            winit_window.set_cursor_visible(visible);
+        }
+    }
+}
+
+/// The message of a panic, when it is a string.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown error")
+}
+
+/// Queues the remote resources that failed to download again, when the device is back online.
+fn retry_failed_resources() {
+    let failed = FAILED_RESOURCES.take();
+    PENDING_RESOURCES.with_borrow_mut(|pending_resources| pending_resources.extend(failed));
+}
+
+/// Requests that the window is drawn again after the current frame.
+///
+/// Elements call this while drawing an animation that hasn't finished. Unlike
+/// [`ElementInternals::request_window_redraw`], it is safe to call while the window is being drawn.
+pub fn request_animation_frame() {
+    ANIMATION_FRAME_REQUESTED.set(true);
+}
+
+/// Requests that the window is drawn again at `time`.
+///
+/// For animations that change in steps, like a blinking cursor, so the window isn't drawn every frame in between.
+/// Like [`request_animation_frame`], it is safe to call while the window is being drawn.
+pub fn request_animation_frame_at(time: time::Instant) {
+    let earliest = ANIMATION_FRAME_TIME.get().map_or(time, |earliest| earliest.min(time));
+    ANIMATION_FRAME_TIME.set(Some(earliest));
+}
+
+/// Whether the window being drawn has keyboard focus, e.g. to mute selections in inactive windows.
+///
+/// Only meaningful while elements are drawn.
+pub(crate) fn is_window_active() -> bool {
+    DRAWING_WINDOW_ACTIVE.get()
+}
+
+pub(crate) fn set_drawing_window_active(active: bool) {
+    DRAWING_WINDOW_ACTIVE.set(active);
+}
+
+/// Whether elements should keep animations to a minimum. See [`CraftOptions::reduced_motion`].
+///
+/// Also true while the device saves power, see [`is_power_saving`], and while the governor lowered the quality of
+/// animations, see [`crate::quality`].
+pub fn is_reduced_motion() -> bool {
+    REDUCED_MOTION.get() || is_power_saving() || is_degraded(QualityFeature::Animations)
+}
+
+/// Changes [`CraftOptions::reduced_motion`] while the app runs, e.g. when the system setting changes.
+pub fn set_reduced_motion(reduced_motion: bool) {
+    REDUCED_MOTION.set(reduced_motion);
+}
+
 #[inline]
 pub fn request_layout(taffy_node: NodeId) {
     TAFFY_TREE.with_borrow_mut(|taffy_tree| {
@@ -0,0 +1,123 @@
+//! Shows a designated element in a window, like a looping video or a "touch to start" screen, after the app was left
+//! alone for a while, and goes back to what the window showed before on the next input. Set with
+//! [`Window::attract_mode`].
+//!
+//! The children of the window are taken out of it while the attract element is shown and put back as they were, so
+//! their state, like the scroll positions, the text typed and the focus, is kept. The input that ends attract mode
+//! only wakes the app, it isn't sent to the elements.
+//!
+//! ```ignore
+//! Window::new("Museum guide")
+//!     .push(guide)
+//!     .attract_mode(Duration::from_secs(180), Video::new(loop_url));
+//! ```
+//!
+//! The idle time is the one of [`crate::idle`], so input in any window keeps all of them out of attract mode.
+
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+#[cfg(target_arch = "wasm32")]
+use web_time::Duration;
+
+use crate::app::{FOCUS, WINDOW_MANAGER};
+use crate::elements::internal_helpers::insert_child_to_element;
+use crate::elements::{ElementInternals, Window, WindowInternal};
+use crate::idle::user_idle_time;
+
+/// The attract element of a window, see [`Window::attract_mode`].
+pub(crate) struct AttractMode {
+    after: Duration,
+    attract: Rc<RefCell<dyn ElementInternals>>,
+    /// What the window showed before the attract element, while it is shown.
+    hidden: Option<HiddenTree>,
+}
+
+struct HiddenTree {
+    children: Vec<Rc<RefCell<dyn ElementInternals>>>,
+    focus: Option<Weak<RefCell<dyn ElementInternals>>>,
+}
+
+impl AttractMode {
+    pub(crate) fn new(after: Duration, attract: Rc<RefCell<dyn ElementInternals>>) -> Self {
+        Self {
+            after,
+            attract,
+            hidden: None,
+        }
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        self.hidden.is_some()
+    }
+}
+
+fn windows() -> Vec<Window> {
+    WINDOW_MANAGER.with_borrow(|window_manager| window_manager.windows().to_vec())
+}
+
+/// Shows the attract elements of the windows left alone for long enough. Called once per iteration of the event loop.
+pub(crate) fn poll_attract_mode() {
+    let idle_time = user_idle_time();
+    for window in windows() {
+        let mut window = window.inner.borrow_mut();
+        let is_due = window.attract_mode.as_ref().is_some_and(|mode| !mode.is_active() && idle_time >= mode.after);
+        if is_due {
+            enter_attract_mode(&mut window);
+        }
+    }
+}
+
+/// Puts back what the windows showed before their attract elements. Returns true if any window was in attract mode,
+/// so the input that woke it should go no further.
+pub(crate) fn leave_attract_modes() -> bool {
+    let mut left = false;
+    for window in windows() {
+        let focus = {
+            let mut window = window.inner.borrow_mut();
+            if !window.attract_mode.as_ref().is_some_and(AttractMode::is_active) {
+                continue;
+            }
+            left = true;
+            leave_attract_mode(&mut window)
+        };
+        // Focusing tells the previous element, which may be the window.
+        if let Some(focus) = focus.and_then(|focus| focus.upgrade()) {
+            focus.borrow_mut().focus();
+        }
+    }
+    left
+}
+
+fn enter_attract_mode(window: &mut WindowInternal) {
+    let Some(attract_mode) = window.attract_mode.take() else {
+        return;
+    };
+    let hidden = HiddenTree {
+        children: window.children().to_vec(),
+        focus: FOCUS.with_borrow(|focus| focus.clone()),
+    };
+    window.remove_all_children();
+    window.push(attract_mode.attract.clone());
+    window.attract_mode = Some(AttractMode {
+        hidden: Some(hidden),
+        ..attract_mode
+    });
+    window.request_redraw();
+}
+
+/// Returns the element that had the focus before.
+pub(crate) fn leave_attract_mode(window: &mut WindowInternal) -> Option<Weak<RefCell<dyn ElementInternals>>> {
+    let attract_mode = window.attract_mode.as_mut()?;
+    let hidden = attract_mode.hidden.take()?;
+    let attract = attract_mode.attract.clone();
+    // Children pushed while the attract element was shown stay, after the ones put back.
+    window.remove_child(attract).ok();
+    for (index, child) in hidden.children.into_iter().enumerate() {
+        insert_child_to_element(window, child, index);
+    }
+    window.request_redraw();
+    hidden.focus
+}
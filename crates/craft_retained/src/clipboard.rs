@@ -0,0 +1,305 @@
+//! The system clipboard, as plain text or as a table.
+//!
+//! Tables are written both as tab-separated text and as an HTML table, which is what spreadsheets put on the clipboard
+//! and read back, so cells copied from one can be pasted into the other. Reading a table prefers the HTML table and
+//! falls back to tab or comma-separated text.
+//!
+//! The clipboard is only available on Windows, macOS and Linux with the `clipboard` feature. Elsewhere nothing is
+//! written and nothing is read.
+
+/// A table of cells, by row.
+pub type Table = Vec<Vec<String>>;
+
+#[cfg(all(
+    any(target_os = "windows", target_os = "macos", target_os = "linux"),
+    feature = "clipboard"
+))]
+mod system {
+    use clipboard_rs::{Clipboard, ClipboardContent, ClipboardContext, ContentFormat};
+
+    pub(super) fn set(text: String, html: Option<String>) {
+        let Ok(clipboard) = ClipboardContext::new() else {
+            return;
+        };
+        let mut contents = vec![ClipboardContent::Text(text)];
+        contents.extend(html.map(ClipboardContent::Html));
+        clipboard.set(contents).ok();
+    }
+
+    pub(super) fn get_text() -> Option<String> {
+        ClipboardContext::new().ok()?.get_text().ok()
+    }
+
+    pub(super) fn get_html() -> Option<String> {
+        let clipboard = ClipboardContext::new().ok()?;
+        if !clipboard.has(ContentFormat::Html) {
+            return None;
+        }
+        clipboard.get_html().ok()
+    }
+}
+
+#[cfg(not(all(
+    any(target_os = "windows", target_os = "macos", target_os = "linux"),
+    feature = "clipboard"
+)))]
+mod system {
+    pub(super) fn set(_text: String, _html: Option<String>) {}
+
+    pub(super) fn get_text() -> Option<String> {
+        None
+    }
+
+    pub(super) fn get_html() -> Option<String> {
+        None
+    }
+}
+
+/// Puts text on the clipboard.
+pub fn set_text(text: &str) {
+    system::set(text.to_string(), None);
+}
+
+pub fn get_text() -> Option<String> {
+    system::get_text()
+}
+
+/// Puts a table on the clipboard, as tab-separated text and as an HTML table.
+pub fn set_table(table: &[Vec<String>]) {
+    system::set(format_delimited(table, '\t'), Some(format_html_table(table)));
+}
+
+/// Reads a table from the clipboard: an HTML table if there is one, or else the text split into rows at line breaks
+/// and into cells at tabs, or at commas if it has no tabs.
+pub fn get_table() -> Option<Table> {
+    if let Some(table) = system::get_html().and_then(|html| parse_html_table(&html)) {
+        return Some(table);
+    }
+    let text = system::get_text()?;
+    let delimiter = if text.contains('\t') { '\t' } else { ',' };
+    Some(parse_delimited(&text, delimiter))
+}
+
+/// Joins the cells with `delimiter` and the rows with line breaks. Cells with the delimiter, a quote or a line break
+/// are quoted, as in CSV.
+pub fn format_delimited(table: &[Vec<String>], delimiter: char) -> String {
+    let mut text = String::new();
+    for row in table {
+        for (index, cell) in row.iter().enumerate() {
+            if index > 0 {
+                text.push(delimiter);
+            }
+            if cell.contains([delimiter, '"', '\n', '\r']) {
+                text.push('"');
+                text.push_str(&cell.replace('"', "\"\""));
+                text.push('"');
+            } else {
+                text.push_str(cell);
+            }
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Splits text written by [`format_delimited`], or by a spreadsheet, into cells.
+pub fn parse_delimited(text: &str, delimiter: char) -> Table {
+    let mut table = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    let mut characters = text.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '"' if in_quotes => {
+                if characters.next_if_eq(&'"').is_some() {
+                    cell.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if cell.is_empty() => in_quotes = true,
+            _ if in_quotes => cell.push(character),
+            '\r' if characters.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                row.push(std::mem::take(&mut cell));
+                table.push(std::mem::take(&mut row));
+            }
+            _ if character == delimiter => row.push(std::mem::take(&mut cell)),
+            _ => cell.push(character),
+        }
+    }
+    // The last line break doesn't start another row.
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        table.push(row);
+    }
+    table
+}
+
+pub fn format_html_table(table: &[Vec<String>]) -> String {
+    let mut html = String::from("<table>");
+    for row in table {
+        html.push_str("<tr>");
+        for cell in row {
+            html.push_str("<td>");
+            html.push_str(&escape_html(cell));
+            html.push_str("</td>");
+        }
+        html.push_str("</tr>");
+    }
+    html.push_str("</table>");
+    html
+}
+
+/// The text of the cells of the first table in `html`, or `None` if it has no table rows.
+pub(crate) fn parse_html_table(html: &str) -> Option<Table> {
+    // ASCII lowercasing keeps the byte indices, so tags are found in `lower` and text is taken from `html`.
+    let lower = html.to_ascii_lowercase();
+    let end = lower.find("</table").unwrap_or(lower.len());
+    let mut table = Vec::new();
+    let mut position = 0;
+    while let Some(row_start) = find_tag(&lower[..end], position, "tr") {
+        let row_end = lower[row_start..end]
+            .find("</tr")
+            .map_or(end, |offset| row_start + offset);
+        let mut row = Vec::new();
+        let mut cell_position = row_start;
+        while let Some(cell_start) = find_tag(&lower[..row_end], cell_position, "td")
+            .into_iter()
+            .chain(find_tag(&lower[..row_end], cell_position, "th"))
+            .min()
+        {
+            let content_start = lower[cell_start..row_end]
+                .find('>')
+                .map_or(row_end, |offset| cell_start + offset + 1);
+            let content_end = ["</td", "</th", "<td", "<th"]
+                .iter()
+                .filter_map(|tag| lower[content_start..row_end].find(tag))
+                .min()
+                .map_or(row_end, |offset| content_start + offset);
+            row.push(html_text(&html[content_start..content_end]));
+            cell_position = content_end;
+        }
+        table.push(row);
+        position = row_end;
+    }
+    (!table.is_empty()).then_some(table)
+}
+
+/// The index of the next `<name>` or `<name ...>` tag at or after `from`.
+fn find_tag(lower: &str, from: usize, name: &str) -> Option<usize> {
+    let open = format!("<{name}");
+    let mut position = from;
+    while let Some(offset) = lower.get(position..)?.find(&open) {
+        let start = position + offset;
+        match lower[start + open.len()..].chars().next() {
+            Some('>' | ' ' | '\t' | '\n' | '\r' | '/') => return Some(start),
+            _ => position = start + open.len(),
+        }
+    }
+    None
+}
+
+/// The text of an HTML fragment, without its tags, with entities decoded and whitespace collapsed.
+fn html_text(fragment: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for character in fragment.chars() {
+        match character {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if in_tag => {}
+            _ => text.push(character),
+        }
+    }
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    decode_entities(&text)
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..].find(';').map(|end| &rest[1..end + 1]);
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{00A0}'),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        });
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(rows: &[&[&str]]) -> Table {
+        rows.iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn delimited_text_round_trips() {
+        let cells = table(&[
+            &["Name", "Note"],
+            &["Ada", "said \"hi\"\tand left"],
+            &["", "two\nlines"],
+        ]);
+        let text = format_delimited(&cells, '\t');
+        assert_eq!(
+            text,
+            "Name\tNote\nAda\t\"said \"\"hi\"\"\tand left\"\n\t\"two\nlines\"\n"
+        );
+        assert_eq!(parse_delimited(&text, '\t'), cells);
+
+        assert_eq!(parse_delimited("a,b\r\nc,d", ','), table(&[&["a", "b"], &["c", "d"]]));
+    }
+
+    #[test]
+    fn html_tables_are_read_from_spreadsheet_fragments() {
+        let html = "<html><body><!--StartFragment--><TABLE border=0><tr><th>Item</th><th>Price</th></tr>\
+                    <tr class=x><td><b>Tea</b> &amp; cake</td><td style='a'>3&#46;5</td></tr></TABLE></body></html>";
+        assert_eq!(
+            parse_html_table(html),
+            Some(table(&[&["Item", "Price"], &["Tea & cake", "3.5"]]))
+        );
+        assert_eq!(parse_html_table("<p>no table</p>"), None);
+
+        let cells = table(&[&["a<b", "c"]]);
+        assert_eq!(parse_html_table(&format_html_table(&cells)), Some(cells));
+    }
+}
@@ -4,11 +4,14 @@
 use std::time;
 use std::time::Instant;
 use craft_logging::info;
+#[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+use craft_logging::warn;
 
 use craft_primitives::geometry::Size;
 
 use craft_runtime::{CraftRuntimeHandle, Receiver, Sender, pop_gui_thread_work, Job, push_gui_thread_work};
 
+use ui_events::keyboard::{Key, KeyboardEvent, NamedKey};
 use ui_events::pointer::PointerEvent;
 
 use ui_events_winit::{WindowEventReducer, WindowEventTranslation};
@@ -21,9 +24,12 @@ use winit::event_loop::{ActiveEventLoop, ControlFlow};
 use winit::window::WindowId;
 
 use crate::CraftOptions;
-use crate::app::{App, WINDOW_MANAGER, dequeue_window_event};
+use crate::app::{App, QueuedWindowEvent, WINDOW_MANAGER, dequeue_window_event};
 use crate::elements::Window;
 use crate::events::internal::InternalMessage;
+use crate::kiosk::{blocks_shortcuts, keep_fullscreen};
+#[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+use crate::recording::{InputRecorder, InputRecording, InputReplayer, RecordedInput};
 #[cfg(target_arch = "wasm32")]
 use {crate::wasm_queue::WASM_QUEUE, crate::wasm_queue::WasmQueue};
 
@@ -44,6 +50,10 @@ pub struct CraftState {
     pub craft_options: CraftOptions,
     pub event_reducer: WindowEventReducer,
     pub craft_app: Box<App>,
+    #[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+    recorder: Option<InputRecorder>,
+    #[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+    replayer: Option<InputReplayer>,
 }
 
 pub(crate) struct CraftWinitState {
@@ -60,6 +70,47 @@ impl ApplicationHandler for CraftWinitState {
     }
 
     fn window_event(&mut self, event_loop: &ActiveEventLoop, window_id: WindowId, event: WindowEvent) {
+        // Kiosks can't be closed from the title bar or with the shortcut of the platform, but still with
+        // `Window::close`.
+        if matches!(event, WindowEvent::CloseRequested) && blocks_shortcuts() {
+            return;
+        }
+
+        #[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+        if let Some(input) = RecordedInput::from_window_event(&event) {
+            self.record_input(window_id, input);
+        }
+
+        self.on_window_event(event_loop, window_id, event);
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if event_loop.exiting() {
+            return;
+        }
+        self.craft_state.runtime.update_local_set();
+        #[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+        self.replay_input(event_loop);
+        self.process_non_winit_window_events(event_loop);
+        self.process_craft_messages();
+        self.process_external_work();
+        self.craft_state.craft_app.on_about_to_wait(event_loop);
+        self.maybe_exit(event_loop);
+        event_loop.set_control_flow(ControlFlow::WaitUntil(time::Instant::now() + WAIT_TIME));
+    }
+
+    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
+        self.craft_state.craft_app.on_suspended(event_loop);
+    }
+}
+
+impl CraftWinitState {
+    pub(crate) fn new(craft_state: CraftState) -> Self {
+        Self { craft_state }
+    }
+
+    /// Handles window events from winit and from [`queue_window_event`](crate::queue_window_event).
+    fn on_window_event(&mut self, event_loop: &ActiveEventLoop, window_id: WindowId, event: WindowEvent) {
         let window: Option<crate::elements::Window> =
             WINDOW_MANAGER.with_borrow_mut(|window_manager| window_manager.get_window_by_id(window_id));
 
@@ -84,12 +135,9 @@ impl ApplicationHandler for CraftWinitState {
                 .reduce(window.effective_scale_factor(), &event)
             {
                 Some(WindowEventTranslation::Keyboard(keyboard_event)) => {
-                    use ui_events::keyboard::{Key, NamedKey};
-                    if keyboard_event.state.is_down() && matches!(keyboard_event.key, Key::Named(NamedKey::Escape)) {
-                        event_loop.exit();
-                    } else {
-                        craft_state.craft_app.on_keyboard_input(window, keyboard_event);
-                    }
+                    #[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+                    self.record_input(window_id, RecordedInput::from_keyboard_event(&keyboard_event));
+                    self.on_keyboard_event(event_loop, window, keyboard_event);
                     return;
                 }
                 Some(WindowEventTranslation::Pointer(pointer_event)) => {
@@ -113,7 +161,9 @@ impl ApplicationHandler for CraftWinitState {
                         PointerEvent::Scroll(pointer_scroll_update) => {
                             craft_state.craft_app.on_pointer_scroll(window, pointer_scroll_update);
                         }
-                        PointerEvent::Gesture(_) => todo!(),
+                        PointerEvent::Gesture(pointer_gesture) => {
+                            craft_state.craft_app.on_pointer_gesture(window, pointer_gesture);
+                        }
                     }
                     return;
                 }
@@ -121,6 +171,12 @@ impl ApplicationHandler for CraftWinitState {
             }
         }
 
+        if matches!(event, WindowEvent::Resized(_) | WindowEvent::Focused(_))
+            && let Some(winit_window) = window.winit_window()
+        {
+            keep_fullscreen(&winit_window);
+        }
+
         match event {
             WindowEvent::CloseRequested => {
                 self.on_close_requested(&window);
@@ -135,6 +191,15 @@ impl ApplicationHandler for CraftWinitState {
                 };
                 craft_state.craft_app.on_resize(window, new_size);
             }
+            WindowEvent::HoveredFile(path) => {
+                craft_state.craft_app.on_file_hovered(window, path);
+            }
+            WindowEvent::HoveredFileCancelled => {
+                craft_state.craft_app.on_file_hover_cancelled(window);
+            }
+            WindowEvent::DroppedFile(path) => {
+                craft_state.craft_app.on_file_dropped(window, path);
+            }
             WindowEvent::Ime(ime) => {
                 craft_state.craft_app.on_ime(window, ime);
             }
@@ -144,31 +209,111 @@ impl ApplicationHandler for CraftWinitState {
             WindowEvent::Moved(_) => {
                 craft_state.craft_app.on_move(window);
             }
+            WindowEvent::Focused(focused) => {
+                craft_state.craft_app.on_window_focused(window, focused);
+            }
+            WindowEvent::ThemeChanged(theme) => {
+                craft_state.craft_app.on_theme_changed(theme);
+            }
             _ => (),
         }
     }
 
-    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
-        if event_loop.exiting() {
+    fn on_keyboard_event(&mut self, event_loop: &ActiveEventLoop, window: Window, keyboard_event: KeyboardEvent) {
+        let craft_app = &mut self.craft_state.craft_app;
+        let is_escape = keyboard_event.state.is_down() && matches!(keyboard_event.key, Key::Named(NamedKey::Escape));
+        // Escape cancels picking a color before it closes the app, unless the app is a kiosk.
+        if is_escape && craft_app.cancel_color_pick(&window) {
             return;
         }
-        self.craft_state.runtime.update_local_set();
-        self.process_non_winit_window_events(event_loop);
-        self.process_craft_messages();
-        self.process_external_work();
-        self.craft_state.craft_app.on_about_to_wait(event_loop);
-        self.maybe_exit(event_loop);
-        event_loop.set_control_flow(ControlFlow::WaitUntil(time::Instant::now() + WAIT_TIME));
+        if is_escape && !blocks_shortcuts() {
+            event_loop.exit();
+        } else {
+            craft_app.on_keyboard_input(window, keyboard_event);
+        }
     }
 
-    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
-        self.craft_state.craft_app.on_suspended(event_loop);
+    /// Writes the input of a window to the recording, after the size of the window the first time.
+    #[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+    fn record_input(&mut self, window_id: WindowId, input: RecordedInput) {
+        let Some(recorder) = &mut self.craft_state.recorder else {
+            return;
+        };
+        let Some((index, window)) = WINDOW_MANAGER.with_borrow(|window_manager| {
+            let index = window_manager.index_of(window_id)?;
+            Some((index, window_manager.get_window_by_index(index)?))
+        }) else {
+            return;
+        };
+
+        let mut inputs = Vec::new();
+        if recorder.first_sight(index)
+            && let Some(winit_window) = window.winit_window()
+        {
+            let size = winit_window.inner_size();
+            inputs.push(RecordedInput::Resized {
+                width: size.width,
+                height: size.height,
+            });
+            inputs.push(RecordedInput::ScaleFactorChanged {
+                scale_factor: winit_window.scale_factor(),
+            });
+        }
+        inputs.push(input);
+
+        for input in inputs {
+            if let Err(error) = recorder.record(index, input) {
+                warn!("Stopped recording the input: {error}");
+                self.craft_state.recorder = None;
+                return;
+            }
+        }
     }
-}
 
-impl CraftWinitState {
-    pub(crate) fn new(craft_state: CraftState) -> Self {
-        Self { craft_state }
+    /// Sends the recorded input that is due to the windows.
+    #[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+    fn replay_input(&mut self, event_loop: &ActiveEventLoop) {
+        if !self.craft_state.craft_app.active {
+            return;
+        }
+        let Some(replayer) = &mut self.craft_state.replayer else {
+            return;
+        };
+
+        for event in replayer.due_events() {
+            let Some(window) =
+                WINDOW_MANAGER.with_borrow(|window_manager| window_manager.get_window_by_index(event.window))
+            else {
+                continue;
+            };
+            let Some(winit_window) = window.winit_window() else {
+                continue;
+            };
+
+            match event.input {
+                keyboard @ RecordedInput::Keyboard { .. } => {
+                    if let Some(keyboard_event) = keyboard.to_keyboard_event() {
+                        self.on_keyboard_event(event_loop, window, keyboard_event);
+                    }
+                }
+                RecordedInput::ScaleFactorChanged { scale_factor } => {
+                    self.craft_state.craft_app.on_scale_factor_changed(window, scale_factor);
+                }
+                input => {
+                    if let RecordedInput::Resized { width, height } = input {
+                        let _ = winit_window.request_inner_size(winit::dpi::PhysicalSize::new(width, height));
+                    }
+                    if let Some(window_event) = input.to_window_event() {
+                        self.on_window_event(event_loop, winit_window.id(), window_event);
+                    }
+                }
+            }
+        }
+
+        if self.craft_state.replayer.as_ref().is_some_and(InputReplayer::is_finished) {
+            info!("Finished replaying the input recording");
+            self.craft_state.replayer = None;
+        }
     }
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -216,14 +361,15 @@ impl CraftWinitState {
 
         let mut timer_jobs: Vec<Job> = vec![];
         while let Some(mut work) = pop_gui_thread_work() {
-            if work.interval.is_none() || work.last_run.elapsed() >= work.interval.unwrap() {
+            let due = work.interval.is_none() || work.last_run.elapsed() >= work.interval.unwrap();
+            if due {
                 (work.callback)();
                 work.last_run = Instant::now();
 
                 work_done = true;
             }
 
-            if work.interval.is_some() {
+            if work.interval.is_some() && (work.repeat || !due) {
                 timer_jobs.push(work);
             }
         }
@@ -249,7 +395,16 @@ impl CraftWinitState {
 
     fn process_non_winit_window_events(&mut self, event_loop: &ActiveEventLoop) {
         while let Some((window_id, event)) = dequeue_window_event() {
-            self.window_event(event_loop, window_id, event);
+            match event {
+                QueuedWindowEvent::Window(event) => self.on_window_event(event_loop, window_id, event),
+                QueuedWindowEvent::Keyboard(keyboard_event) => {
+                    let window =
+                        WINDOW_MANAGER.with_borrow(|window_manager| window_manager.get_window_by_id(window_id));
+                    if let Some(window) = window {
+                        self.on_keyboard_event(event_loop, window, keyboard_event);
+                    }
+                }
+            }
         }
     }
 
@@ -272,6 +427,25 @@ impl CraftState {
         craft_options: CraftOptions,
         craft_app: Box<App>,
     ) -> Self {
+        #[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+        let (recorder, replayer) = match &craft_options.input_recording {
+            Some(InputRecording::Record(path)) => match InputRecorder::create(path) {
+                Ok(recorder) => (Some(recorder), None),
+                Err(error) => {
+                    warn!("Couldn't create the input recording {}: {error}", path.display());
+                    (None, None)
+                }
+            },
+            Some(InputRecording::Replay(path)) => match InputReplayer::open(path) {
+                Ok(replayer) => (None, Some(replayer)),
+                Err(error) => {
+                    warn!("Couldn't read the input recording {}: {error}", path.display());
+                    (None, None)
+                }
+            },
+            None => (None, None),
+        };
+
         Self {
             runtime,
             wait_cancelled: false,
@@ -281,6 +455,10 @@ impl CraftState {
             craft_options,
             event_reducer: Default::default(),
             craft_app,
+            #[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+            recorder,
+            #[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+            replayer,
         }
     }
 }
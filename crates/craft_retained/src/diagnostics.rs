@@ -0,0 +1,132 @@
+//! Reports how much memory element state takes, and finds state left behind by dropped elements.
+//!
+//! Every element registers itself by id when it is created and unregisters when it is dropped. An entry whose element
+//! is gone is a leak, usually from an element that was replaced without going through its drop.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+use craft_logging::info;
+
+use crate::app::ELEMENTS;
+use crate::elements::{ElementData, ElementIdMap, ElementInternals};
+
+/// A snapshot of the registered element state.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ElementStateStats {
+    /// The number of registered entries, including orphans.
+    pub entries: usize,
+    /// The number of entries whose element is still alive.
+    pub live: usize,
+    /// The approximate size of the live elements and the registry in bytes.
+    ///
+    /// This counts the elements themselves and their child lists, but not heap data they own, like text or images.
+    pub approximate_bytes: usize,
+    /// The ids of entries whose element was dropped.
+    pub orphans: Vec<u64>,
+    /// The ids of live elements that aren't in any window, and aren't kept by a parent either.
+    ///
+    /// These can be elements that are built but not shown yet, or elements kept alive by a handler after they were
+    /// removed.
+    pub detached: Vec<u64>,
+    /// Entries that were borrowed while the snapshot was taken, which aren't counted in the size.
+    pub skipped: usize,
+}
+
+impl fmt::Display for ElementStateStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} element state entries ({} live, about {} bytes), {} orphaned, {} detached",
+            self.entries,
+            self.live,
+            self.approximate_bytes,
+            self.orphans.len(),
+            self.detached.len()
+        )
+    }
+}
+
+/// Takes a snapshot of the element state of this thread.
+///
+/// Elements that are borrowed while this runs, like the window handling an event, are counted as live but not
+/// measured.
+pub fn element_state_stats() -> ElementStateStats {
+    ELEMENTS.with_borrow(stats_of)
+}
+
+/// Unregisters the entries whose element was dropped, and returns how many were removed.
+pub fn purge_orphaned_element_state() -> usize {
+    let purged = ELEMENTS.with_borrow_mut(|elements| elements.remove_dropped());
+    if !purged.is_empty() {
+        info!("Purged {} orphaned element state entries", purged.len());
+    }
+    purged.len()
+}
+
+fn stats_of(elements: &ElementIdMap) -> ElementStateStats {
+    let mut stats = ElementStateStats {
+        approximate_bytes: elements.capacity() * size_of::<(u64, Weak<RefCell<dyn ElementInternals>>)>(),
+        ..Default::default()
+    };
+
+    for (id, element) in elements.iter() {
+        stats.entries += 1;
+        let Some(element) = element.upgrade() else {
+            stats.orphans.push(id);
+            continue;
+        };
+        stats.live += 1;
+        let Ok(element) = element.try_borrow() else {
+            stats.skipped += 1;
+            continue;
+        };
+        let element_data = element.element_data();
+        stats.approximate_bytes += size_of_val(&*element)
+            + element_data.children.capacity() * size_of::<Rc<RefCell<dyn ElementInternals>>>();
+        if element_data.parent.is_none() && element_data.window.is_none() {
+            stats.detached.push(id);
+        }
+    }
+
+    stats.orphans.sort_unstable();
+    stats.detached.sort_unstable();
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::ContainerInner;
+
+    fn dropped() -> Weak<RefCell<dyn ElementInternals>> {
+        Weak::<RefCell<ContainerInner>>::new()
+    }
+
+    #[test]
+    fn dropped_elements_are_orphans() {
+        let mut elements = ElementIdMap::new();
+        elements.insert_id(7, dropped());
+        elements.insert_id(3, dropped());
+
+        let stats = stats_of(&elements);
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.live, 0);
+        assert_eq!(stats.orphans, vec![3, 7]);
+        assert!(stats.detached.is_empty());
+    }
+
+    #[test]
+    fn removing_dropped_entries_leaves_none() {
+        let mut elements = ElementIdMap::new();
+        elements.insert_id(1, dropped());
+        elements.insert_id(2, dropped());
+
+        let mut purged = elements.remove_dropped();
+        purged.sort_unstable();
+        assert_eq!(purged, vec![1, 2]);
+        assert_eq!(elements.len(), 0);
+        assert_eq!(stats_of(&elements).orphans, Vec::<u64>::new());
+    }
+}
@@ -0,0 +1,235 @@
+//! The document: the windows of the app and the elements in them, with queries over the whole tree and observers of
+//! its changes, for tools like devtools, tests and layers that sync the tree with other state.
+//!
+//! Changes are recorded as [`Mutation`]s while the tree changes and delivered to the observers together, once per
+//! iteration of the event loop, like DOM mutation observers. Observers can change the tree themselves, and those
+//! changes are delivered at the next iteration.
+//!
+//! ```ignore
+//! let observer = document().observe(|mutations| {
+//!     for mutation in mutations {
+//!         if let Mutation::ChildAdded { child, .. } = mutation {
+//!             println!("added {:?}", child);
+//!         }
+//!     }
+//! });
+//! // Later, to stop observing:
+//! observer.disconnect();
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+
+use crate::app::{ELEMENTS, WINDOW_MANAGER};
+use crate::elements::{DynElement, ElementInternals, Window};
+use crate::events::pointer_capture::PointerCapture;
+use crate::tree_updates::ElementHandle;
+
+/// What an attribute change changed. See [`Mutation::AttributeChanged`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Attribute {
+    /// The id set with [`Element::id`](crate::elements::Element::id).
+    Id,
+    /// The data set with [`Element::user_data`](crate::elements::Element::user_data).
+    UserData,
+}
+
+/// A change to the element tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mutation {
+    /// `child` was added to the children of `parent` at `index`.
+    ChildAdded {
+        parent: ElementHandle,
+        child: ElementHandle,
+        index: usize,
+    },
+    ChildRemoved {
+        parent: ElementHandle,
+        child: ElementHandle,
+    },
+    /// A style property of an element in a tree was set.
+    StyleChanged(ElementHandle),
+    /// An attribute of an element in a tree was set.
+    AttributeChanged(ElementHandle, Attribute),
+}
+
+type ObserverCallback = Rc<RefCell<dyn FnMut(&[Mutation])>>;
+
+thread_local! {
+    static OBSERVERS: RefCell<Vec<(u64, ObserverCallback)>> = const { RefCell::new(Vec::new()) };
+    static NEXT_OBSERVER_ID: Cell<u64> = const { Cell::new(0) };
+    /// The mutations since the observers were last called, only recorded while there are observers.
+    static PENDING_MUTATIONS: RefCell<Vec<Mutation>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Stops calling a callback passed to [`Document::observe`] when disconnected.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct MutationObserver(u64);
+
+impl MutationObserver {
+    pub fn disconnect(self) {
+        OBSERVERS.with_borrow_mut(|observers| observers.retain(|(id, _)| *id != self.0));
+        if OBSERVERS.with_borrow(Vec::is_empty) {
+            PENDING_MUTATIONS.with_borrow_mut(Vec::clear);
+        }
+    }
+}
+
+/// The windows of the app and the elements in them. See the [module docs](self).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Document {
+    _private: (),
+}
+
+/// Returns the document of the GUI thread.
+pub fn document() -> Document {
+    Document::default()
+}
+
+impl Document {
+    /// The windows, in the order they were created. Each is the root of a tree of elements.
+    pub fn windows(&self) -> Vec<Window> {
+        WINDOW_MANAGER.with_borrow(|window_manager| window_manager.windows().to_vec())
+    }
+
+    /// Returns the element a handle refers to, if it wasn't dropped.
+    pub fn element(&self, handle: ElementHandle) -> Option<DynElement> {
+        let element = ELEMENTS.with_borrow(|elements| elements.get(handle.0).and_then(Weak::upgrade))?;
+        Some(DynElement::new(element))
+    }
+
+    /// Returns the first element, in tree order, whose [`id`](crate::elements::Element::id) is `id`.
+    pub fn get_element_by_id(&self, id: &str) -> Option<DynElement> {
+        let mut found = self.query_all(|element| element.element_data().id.as_deref() == Some(id));
+        (!found.is_empty()).then(|| found.swap_remove(0))
+    }
+
+    /// Returns the elements of every window, in tree order, that `matches` accepts.
+    pub fn query_all(&self, mut matches: impl FnMut(&dyn ElementInternals) -> bool) -> Vec<DynElement> {
+        let mut found = Vec::new();
+        for window in self.windows() {
+            let root: Rc<RefCell<dyn ElementInternals>> = window.inner.clone();
+            query_below(&root, &mut matches, &mut found);
+        }
+        found
+    }
+
+    /// Calls `callback` with the changes to the element tree, once per iteration of the event loop in which it
+    /// changed, until the returned observer is disconnected.
+    pub fn observe(&self, callback: impl FnMut(&[Mutation]) + 'static) -> MutationObserver {
+        let id = NEXT_OBSERVER_ID.with(|next_id| next_id.replace(next_id.get() + 1));
+        OBSERVERS.with_borrow_mut(|observers| observers.push((id, Rc::new(RefCell::new(callback)))));
+        MutationObserver(id)
+    }
+}
+
+fn query_below(
+    element: &Rc<RefCell<dyn ElementInternals>>,
+    matches: &mut impl FnMut(&dyn ElementInternals) -> bool,
+    found: &mut Vec<DynElement>,
+) {
+    // Elements that are borrowed, like the one handling the current event, are skipped with their children.
+    let Ok(element_ref) = element.try_borrow() else {
+        return;
+    };
+    if matches(&*element_ref) {
+        found.push(DynElement::new(element.clone()));
+    }
+    for child in element_ref.children() {
+        query_below(child, matches, found);
+    }
+}
+
+/// Records a change to the element tree for the observers, if there are any.
+pub(crate) fn record_mutation(mutation: Mutation) {
+    if OBSERVERS.with_borrow(Vec::is_empty) {
+        return;
+    }
+    PENDING_MUTATIONS.with_borrow_mut(|pending| {
+        // Setting several style properties at once is one change.
+        if pending.last() != Some(&mutation) || !matches!(mutation, Mutation::StyleChanged(_)) {
+            pending.push(mutation);
+        }
+    });
+}
+
+/// Records a change to the style or an attribute of an element, unless the element isn't in a tree yet, like while
+/// it is built.
+pub(crate) fn record_element_change(element: &dyn ElementInternals, mutation: impl FnOnce(ElementHandle) -> Mutation) {
+    let element_data = element.element_data();
+    if element_data.parent.is_some() || element_data.window.is_some() {
+        record_mutation(mutation(ElementHandle(element_data.internal_id)));
+    }
+}
+
+/// Calls the observers with the changes recorded since they were last called. Returns true if there were any.
+///
+/// Called once per iteration of the event loop.
+pub(crate) fn deliver_mutations() -> bool {
+    let mutations = PENDING_MUTATIONS.with_borrow_mut(std::mem::take);
+    if mutations.is_empty() {
+        return false;
+    }
+    // The observers are cloned, so that they can observe, disconnect and change the tree.
+    let observers: Vec<ObserverCallback> =
+        OBSERVERS.with_borrow(|observers| observers.iter().map(|(_, callback)| callback.clone()).collect());
+    for observer in observers {
+        (*observer.borrow_mut())(&mutations);
+    }
+    true
+}
+
+/// Forgets an element that was removed from the tree, and the elements inside it, in the state of the document.
+pub(crate) fn remove_element_from_document(
+    node: &Rc<RefCell<dyn ElementInternals>>,
+    pointer_capture: &mut PointerCapture,
+) {
+    pointer_capture.remove_element(node);
+    for child in node.borrow().children() {
+        remove_element_from_document(child, pointer_capture);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::{AsElement, Container, Element, Text};
+    use crate::style::Display;
+
+    #[test]
+    fn observers_get_the_changes_since_they_were_last_called() {
+        let mutations = Rc::new(RefCell::new(Vec::new()));
+        let observed = mutations.clone();
+        let observer = document().observe(move |changes| observed.borrow_mut().extend_from_slice(changes));
+
+        let list = Container::new();
+        let label = Text::new("First");
+        let list = list.push(label.clone());
+        // Both style properties are one change.
+        let label = label.display(Display::Block).padding_all(crate::px(4));
+        list.borrow_mut().remove_child(label.as_element_rc()).unwrap();
+
+        assert!(deliver_mutations());
+        let (list, label) = (list.get_handle(), label.get_handle());
+        assert_eq!(
+            *mutations.borrow(),
+            vec![
+                Mutation::ChildAdded {
+                    parent: list,
+                    child: label,
+                    index: 0
+                },
+                Mutation::StyleChanged(label),
+                Mutation::ChildRemoved {
+                    parent: list,
+                    child: label
+                },
+            ]
+        );
+        assert!(!deliver_mutations());
+
+        observer.disconnect();
+        Container::new().push(Text::new("Unobserved"));
+        assert!(!deliver_mutations());
+    }
+}
@@ -197,6 +197,10 @@ impl ElementInternals for CheckboxInner {
         }
     }
 
+    fn supports_children(&self) -> bool {
+        true
+    }
+
     fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
         push_child_to_element(self, child);
     }
@@ -139,6 +139,10 @@ impl ElementInternals for CheckboxGroupInner {
         }
     }
 
+    fn supports_children(&self) -> bool {
+        true
+    }
+
     fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
         push_child_to_element(self, child);
     }
@@ -0,0 +1,260 @@
+//! The regions of a [`CodeEditor`](super::CodeEditor) that can be folded: the lines after a line that opens a block,
+//! hidden behind it while folded.
+//!
+//! Lines are counted from 0. A region is known by the line that stays visible, which is also how the folds are saved
+//! and restored, see [`CodeEditor::folded_lines`](super::CodeEditor::folded_lines).
+
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+/// How a [`CodeEditor`](super::CodeEditor) finds the regions that can be folded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FoldingStrategy {
+    /// A line followed by lines indented more than it, as in Python or YAML.
+    Indentation,
+    /// A line with an opening bracket, up to the line with the matching closing bracket, which stays visible, as in
+    /// Rust or JSON. Brackets in strings and line comments are ignored.
+    Brackets,
+}
+
+/// Lines that can be folded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FoldRegion {
+    /// The line that stays visible when the region is folded.
+    pub start_line: usize,
+    /// The last line hidden when the region is folded.
+    pub end_line: usize,
+}
+
+/// The byte index of the start of each line.
+pub(crate) fn line_starts(text: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+        .collect()
+}
+
+/// The regions of `text`, ordered by their first line, at most one per line.
+pub(crate) fn fold_regions(text: &str, strategy: FoldingStrategy) -> Vec<FoldRegion> {
+    match strategy {
+        FoldingStrategy::Indentation => indentation_regions(text),
+        FoldingStrategy::Brackets => bracket_regions(text),
+    }
+}
+
+/// The indentation of a line in columns, with tabs as 4, or `None` for a blank line.
+fn indentation(line: &str) -> Option<usize> {
+    let mut columns = 0;
+    for character in line.chars() {
+        match character {
+            ' ' => columns += 1,
+            '\t' => columns += 4,
+            '\r' => {}
+            _ => return Some(columns),
+        }
+    }
+    None
+}
+
+fn indentation_regions(text: &str) -> Vec<FoldRegion> {
+    let indents: Vec<Option<usize>> = text.split('\n').map(indentation).collect();
+    let mut regions = Vec::new();
+    for (start_line, indent) in indents.iter().enumerate() {
+        let Some(indent) = indent else {
+            continue;
+        };
+        // Blank lines inside the block belong to it, the ones after it don't.
+        let end_line = indents[start_line + 1..]
+            .iter()
+            .take_while(|inner| inner.is_none_or(|inner| inner > *indent))
+            .enumerate()
+            .filter(|(_, inner)| inner.is_some())
+            .last()
+            .map(|(offset, _)| start_line + 1 + offset);
+        if let Some(end_line) = end_line {
+            regions.push(FoldRegion {
+                start_line,
+                end_line,
+            });
+        }
+    }
+    regions
+}
+
+fn bracket_regions(text: &str) -> Vec<FoldRegion> {
+    let mut regions: Vec<FoldRegion> = Vec::new();
+    let mut open: Vec<(char, usize)> = Vec::new();
+    let mut line = 0;
+    let mut in_string = false;
+    let mut in_comment = false;
+    let mut characters = text.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '\n' => {
+                line += 1;
+                in_comment = false;
+            }
+            _ if in_comment => {}
+            '\\' if in_string => {
+                characters.next_if(|next| *next != '\n');
+            }
+            '"' => in_string = !in_string,
+            _ if in_string => {}
+            '/' if characters.peek() == Some(&'/') => in_comment = true,
+            '{' | '[' | '(' => open.push((character, line)),
+            '}' | ']' | ')' => {
+                let opening = match character {
+                    '}' => '{',
+                    ']' => '[',
+                    _ => '(',
+                };
+                let Some(index) = open.iter().rposition(|(bracket, _)| *bracket == opening) else {
+                    continue;
+                };
+                let (_, start_line) = open[index];
+                open.truncate(index);
+                if line > start_line + 1 {
+                    let end_line = line - 1;
+                    match regions.iter_mut().find(|region| region.start_line == start_line) {
+                        Some(region) => region.end_line = region.end_line.max(end_line),
+                        None => regions.push(FoldRegion {
+                            start_line,
+                            end_line,
+                        }),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    regions.sort_by_key(|region| region.start_line);
+    regions
+}
+
+/// Which of `line_count` lines the folded regions hide.
+pub(crate) fn hidden_lines(regions: &[FoldRegion], folded_lines: &BTreeSet<usize>, line_count: usize) -> Vec<bool> {
+    let mut hidden = vec![false; line_count];
+    for region in regions
+        .iter()
+        .filter(|region| folded_lines.contains(&region.start_line))
+    {
+        let end_line = region.end_line.min(line_count.saturating_sub(1));
+        for line in region.start_line + 1..=end_line {
+            hidden[line] = true;
+        }
+    }
+    hidden
+}
+
+/// The bytes of the hidden lines, with their line breaks, merged into ranges.
+pub(crate) fn hidden_byte_ranges(text: &str, line_starts: &[usize], hidden: &[bool]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for (line, _) in hidden.iter().enumerate().filter(|(_, hidden)| **hidden) {
+        let start = line_starts[line];
+        let end = line_starts.get(line + 1).copied().unwrap_or(text.len());
+        match ranges.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => ranges.push(start..end),
+        }
+    }
+    ranges
+}
+
+/// Moves the folded lines of `old_text` to where they are in `new_text`, for a single edit between them. Regions the
+/// edit changed hidden lines of are unfolded, so the change can be seen.
+pub(crate) fn remap_folded_lines(
+    old_text: &str,
+    new_text: &str,
+    regions: &[FoldRegion],
+    folded_lines: &BTreeSet<usize>,
+) -> BTreeSet<usize> {
+    let prefix = old_text
+        .bytes()
+        .zip(new_text.bytes())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let max_suffix = old_text.len().min(new_text.len()) - prefix;
+    let suffix = old_text
+        .bytes()
+        .rev()
+        .zip(new_text.bytes().rev())
+        .take(max_suffix)
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    let line_of = |text: &str, index: usize| text.as_bytes()[..index].iter().filter(|byte| **byte == b'\n').count();
+    let first_line = line_of(old_text, prefix);
+    let last_line = line_of(old_text, old_text.len() - suffix);
+    let delta = new_text.matches('\n').count() as isize - old_text.matches('\n').count() as isize;
+
+    folded_lines
+        .iter()
+        .filter_map(|line| {
+            let end_line = regions
+                .iter()
+                .find(|region| region.start_line == *line)
+                .map_or(*line, |region| region.end_line);
+            if end_line < first_line || (*line == first_line && last_line == first_line) {
+                Some(*line)
+            } else if *line > last_line {
+                line.checked_add_signed(delta)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(start_line: usize, end_line: usize) -> FoldRegion {
+        FoldRegion {
+            start_line,
+            end_line,
+        }
+    }
+
+    #[test]
+    fn regions_follow_indentation_or_brackets() {
+        let python = "def f():\n    a = 1\n\n    return a\n\nprint(f())";
+        assert_eq!(fold_regions(python, FoldingStrategy::Indentation), vec![region(0, 3)]);
+
+        let rust = "fn main() {\n    let s = \"{\";\n    if a {\n        b();\n    }\n}\n";
+        assert_eq!(
+            fold_regions(rust, FoldingStrategy::Brackets),
+            vec![region(0, 4), region(2, 3)]
+        );
+    }
+
+    #[test]
+    fn folded_regions_hide_the_lines_after_their_first() {
+        let text = "a {\n  b\n  c\n}\nd";
+        let regions = fold_regions(text, FoldingStrategy::Brackets);
+        let starts = line_starts(text);
+        let hidden = hidden_lines(&regions, &BTreeSet::from([0]), starts.len());
+        assert_eq!(hidden, vec![false, true, true, false, false]);
+        assert_eq!(hidden_byte_ranges(text, &starts, &hidden), vec![4..12]);
+    }
+
+    #[test]
+    fn folds_move_with_the_lines_before_them() {
+        let old_text = "x\na {\n  b\n}\nc {\n  d\n}\n";
+        let regions = fold_regions(old_text, FoldingStrategy::Brackets);
+        let folded = BTreeSet::from([1, 4]);
+
+        // A line added at the start moves both folds down.
+        let new_text = format!("y\n{old_text}");
+        assert_eq!(
+            remap_folded_lines(old_text, &new_text, &regions, &folded),
+            BTreeSet::from([2, 5])
+        );
+
+        // Editing a hidden line unfolds its region only.
+        let new_text = old_text.replace("  b", "  bb");
+        assert_eq!(
+            remap_folded_lines(old_text, &new_text, &regions, &folded),
+            BTreeSet::from([4])
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! The minimap of a [`CodeEditor`](super::CodeEditor): the highlighted text at a small scale, with a block per word
+//! in its color.
+
+use std::ops::Range;
+
+use crate::Color;
+use crate::style::TextStyleProperty;
+use crate::text::RangedStyles;
+
+/// The width of a column of the minimap, in logical pixels.
+pub(crate) const MINIMAP_COLUMN_WIDTH: f32 = 1.0;
+/// The height of a line of the minimap, in logical pixels.
+pub(crate) const MINIMAP_LINE_HEIGHT: f32 = 2.0;
+
+/// A run of characters of the same color on a line.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct MinimapBlock {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) length: usize,
+    pub(crate) color: Color,
+}
+
+/// The blocks of `text`, colored by the [`TextStyleProperty::Color`] styles and `default_color` elsewhere. Whitespace
+/// is left out, with tabs as 4 columns.
+pub(crate) fn minimap_blocks(text: &str, styles: &RangedStyles, default_color: Color) -> Vec<MinimapBlock> {
+    let mut colors: Vec<(Range<usize>, Color)> = styles
+        .styles
+        .iter()
+        .filter_map(|(range, style)| match style {
+            TextStyleProperty::Color(color) => Some((range.clone(), *color)),
+            _ => None,
+        })
+        .collect();
+    colors.sort_by_key(|(range, _)| range.start);
+    let color_at = |index: usize| {
+        let candidate = colors.partition_point(|(range, _)| range.start <= index);
+        colors[..candidate]
+            .last()
+            .filter(|(range, _)| index < range.end)
+            .map_or(default_color, |(_, color)| *color)
+    };
+
+    let mut blocks: Vec<MinimapBlock> = Vec::new();
+    let mut line_start = 0;
+    for (line, line_text) in text.split('\n').enumerate() {
+        let mut column = 0;
+        for (offset, character) in line_text.char_indices() {
+            let width = if character == '\t' { 4 } else { 1 };
+            if !character.is_whitespace() {
+                let color = color_at(line_start + offset);
+                match blocks.last_mut() {
+                    Some(last) if last.line == line && last.column + last.length == column && last.color == color => {
+                        last.length += 1;
+                    }
+                    _ => blocks.push(MinimapBlock {
+                        line,
+                        column,
+                        length: 1,
+                        color,
+                    }),
+                }
+            }
+            column += width;
+        }
+        line_start += line_text.len() + 1;
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_split_at_whitespace_and_color_changes() {
+        let red = Color::from_rgb8(255, 0, 0);
+        let styles = RangedStyles::new(vec![(0..2, TextStyleProperty::Color(red))]);
+        let blocks = minimap_blocks("fn main\n\tx", &styles, Color::WHITE);
+        let block = |line, column, length, color| MinimapBlock {
+            line,
+            column,
+            length,
+            color,
+        };
+        assert_eq!(
+            blocks,
+            vec![
+                block(0, 0, 2, red),
+                block(0, 3, 4, Color::WHITE),
+                block(1, 4, 1, Color::WHITE)
+            ]
+        );
+    }
+}
@@ -2,29 +2,50 @@
 
 use std::any::Any;
 use std::cell::{Ref, RefCell, RefMut};
+use std::collections::BTreeSet;
 use std::rc::{Rc, Weak};
 use std::sync::Arc;
 
-use craft_primitives::geometry::{Affine, Point, Rectangle};
+use craft_primitives::geometry::{Affine, BezPath, Point, Rectangle};
+use craft_renderer::Brush;
 use craft_renderer::renderer::Renderer;
 use craft_resource_manager::ResourceManager;
+use parley::{Affinity, Cursor};
+use ui_events::pointer::PointerButton;
 
 use crate::elements::element_data::ElementData;
 use crate::elements::internal_helpers::{apply_generic_container_layout, draw_generic_container, push_child_to_element};
 use crate::elements::traits::DeepClone;
 use crate::elements::{AsElement, Element, ElementInternals, resolve_clip_for_scrollable, TextInput};
+use crate::elements::codeeditor::folding::{fold_regions, hidden_byte_ranges, hidden_lines, line_starts, remap_folded_lines};
 use crate::elements::codeeditor::highlighter::compute_code_editor_style;
+use crate::elements::codeeditor::minimap::{MINIMAP_COLUMN_WIDTH, MINIMAP_LINE_HEIGHT, MinimapBlock, minimap_blocks};
 use crate::events::{Event, EventKind};
 use crate::layout::TaffyTree;
-use crate::style::Overflow;
+use crate::style::{Overflow, TextStyleProperty};
 use crate::text::text_context::TextContext;
+use crate::text::whitespace::ShowWhitespace;
+use crate::{Color, px};
+
+/// The width of the gutter with the fold markers, in logical pixels.
+const GUTTER_WIDTH: f32 = 16.0;
+/// The width of the minimap, in logical pixels.
+const MINIMAP_WIDTH: f32 = 80.0;
+/// The columns between tab stops, unless set with [`CodeEditor::tab_width`].
+const DEFAULT_TAB_WIDTH: u8 = 4;
+/// The font size of folded lines, small enough for them to take no room.
+const FOLDED_FONT_SIZE: f32 = 0.01;
 
 #[derive(Clone)]
 pub struct CodeEditor {
     pub inner: Rc<RefCell<CodeEditorInner>>,
 }
 
+mod folding;
 pub mod highlighter;
+mod minimap;
+
+pub use folding::{FoldRegion, FoldingStrategy};
 
 /// Stores one or more elements.
 ///
@@ -36,6 +57,20 @@ pub struct CodeEditorInner {
     theme: String,
     text_input: TextInput,
     // TODO: Retain syntax_set and theme set.
+    foreground_color: Color,
+
+    folding: Option<FoldingStrategy>,
+    fold_regions: Vec<FoldRegion>,
+    /// The first lines of the folded regions.
+    folded_lines: BTreeSet<usize>,
+    /// The text the regions were found in, to move the folds with the lines when it changes.
+    folded_text: String,
+    hidden_lines: Vec<bool>,
+
+    minimap: bool,
+    minimap_blocks: Vec<MinimapBlock>,
+
+    scale_factor: f64,
 }
 
 impl Default for CodeEditor {
@@ -91,6 +126,7 @@ impl ElementInternals for CodeEditorInner {
         clip_bounds: Option<Rectangle>,
         scale_factor: f64,
     ) {
+        self.scale_factor = scale_factor;
         apply_generic_container_layout(
             self,
             taffy_tree,
@@ -105,6 +141,13 @@ impl ElementInternals for CodeEditorInner {
 
     fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
         draw_generic_container(self, renderer, resource_manager, text_context, scale_factor);
+        if !self.is_visible() {
+            return;
+        }
+        self.draw_fold_markers(renderer, scale_factor);
+        if self.minimap {
+            self.draw_minimap(renderer, scale_factor);
+        }
     }
 
     fn on_event(
@@ -114,8 +157,19 @@ impl ElementInternals for CodeEditorInner {
         _event: &mut Event,
         _target: Option<Rc<RefCell<dyn ElementInternals>>>,
     ) {
-        if let EventKind::TextInputChanged(_) = message {
-            self.highlight();
+        match message {
+            EventKind::TextInputChanged(_) => self.highlight(),
+            EventKind::PointerButtonDown(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                let point = pointer_button.state.logical_point();
+                let clicked = self
+                    .fold_markers()
+                    .into_iter()
+                    .find(|(_, marker)| marker.contains(&point));
+                if let Some((line, _)) = clicked {
+                    self.toggle_fold(line);
+                }
+            }
+            _ => {}
         }
     }
 
@@ -128,6 +182,10 @@ impl ElementInternals for CodeEditorInner {
         }
     }
 
+    fn supports_children(&self) -> bool {
+        true
+    }
+
     fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
         push_child_to_element(self, child);
     }
@@ -144,13 +202,22 @@ impl ElementInternals for CodeEditorInner {
 impl CodeEditor {
     pub fn new(code: &str, extension: &str, theme: &str) -> Self {
         println!("Extension: {}", extension);
-        let text_input = TextInput::new(code);
+        let text_input = TextInput::new(code).multiple_carets(true).tab_width(Some(DEFAULT_TAB_WIDTH));
         let inner = Rc::new_cyclic(|me: &Weak<RefCell<CodeEditorInner>>| {
             RefCell::new(CodeEditorInner {
                 element_data: ElementData::new(me.clone(), true),
                 extension: extension.to_string(),
                 theme: theme.to_string(),
                 text_input: text_input.clone(),
+                foreground_color: Color::WHITE,
+                folding: None,
+                fold_regions: Vec::new(),
+                folded_lines: BTreeSet::new(),
+                folded_text: String::new(),
+                hidden_lines: Vec::new(),
+                minimap: false,
+                minimap_blocks: Vec::new(),
+                scale_factor: 1.0,
             })
         });
         let mut inner_mut = inner.borrow_mut();
@@ -160,17 +227,260 @@ impl CodeEditor {
         drop(inner_mut);
         Self { inner }
     }
+
+    /// Lets regions of the code be folded from toggles in a gutter on the left, found with `strategy`. `None`, the
+    /// default, turns folding off.
+    pub fn folding(self, strategy: Option<FoldingStrategy>) -> Self {
+        self.inner.borrow_mut().set_folding(strategy);
+        self
+    }
+
+    /// Shows the code at a small scale in a strip on the right.
+    pub fn minimap(self, minimap: bool) -> Self {
+        self.inner.borrow_mut().set_minimap(minimap);
+        self
+    }
+
+    /// Marks invisible characters and wrapped lines with faint glyphs.
+    pub fn show_whitespace(self, show_whitespace: ShowWhitespace) -> Self {
+        let text_input = self.inner.borrow().text_input.clone();
+        text_input.inner.borrow_mut().set_show_whitespace(show_whitespace);
+        self
+    }
+
+    /// Aligns tabs to a tab stop every `tab_width` columns. Defaults to 4.
+    pub fn tab_width(self, tab_width: Option<u8>) -> Self {
+        let text_input = self.inner.borrow().text_input.clone();
+        text_input.inner.borrow_mut().set_tab_width(tab_width);
+        self
+    }
+
+    /// Folds the regions starting at `lines`, like ones saved from [`CodeEditor::get_folded_lines`], and unfolds the
+    /// others. Lines that don't start a region are ignored.
+    pub fn folded_lines(self, lines: impl IntoIterator<Item = usize>) -> Self {
+        self.inner.borrow_mut().set_folded_lines(lines.into_iter().collect());
+        self
+    }
+
+    /// The first lines of the folded regions. They move with the lines as the code is edited.
+    pub fn get_folded_lines(&self) -> Vec<usize> {
+        self.inner.borrow().folded_lines.iter().copied().collect()
+    }
+
+    /// The regions that can be folded, ordered by their first line.
+    pub fn get_fold_regions(&self) -> Vec<FoldRegion> {
+        self.inner.borrow().fold_regions.clone()
+    }
+
+    /// Folds or unfolds the region starting at `line`.
+    pub fn toggle_fold(&self, line: usize) {
+        self.inner.borrow_mut().toggle_fold(line);
+    }
+
+    pub fn fold_all(&self) {
+        let mut inner = self.inner.borrow_mut();
+        let lines = inner.fold_regions.iter().map(|region| region.start_line).collect();
+        inner.set_folded_lines(lines);
+    }
+
+    pub fn unfold_all(&self) {
+        self.inner.borrow_mut().set_folded_lines(BTreeSet::new());
+    }
 }
 
 
 impl CodeEditorInner {
 
     fn highlight(&mut self) {
-        let mut text = self.text_input.inner.borrow_mut();
-        let code_editor = compute_code_editor_style(text.get_text(), None, None, &self.extension, &self.theme);
+        let text_input = self.text_input.inner.clone();
+        let mut text = text_input.borrow_mut();
+        let code = text.get_text().to_string();
+        let mut code_editor = compute_code_editor_style(&code, None, None, &self.extension, &self.theme);
+        self.foreground_color = code_editor.foreground_color;
+
+        self.update_folds(&code);
+        self.minimap_blocks = if self.minimap {
+            minimap_blocks(&code, &code_editor.ranged_styles, code_editor.foreground_color)
+        } else {
+            Vec::new()
+        };
+
+        // Folded lines stay in the text, at a size that takes no room.
+        let starts = line_starts(&code);
+        for range in hidden_byte_ranges(&code, &starts, &self.hidden_lines) {
+            code_editor
+                .ranged_styles
+                .styles
+                .push((range.clone(), TextStyleProperty::FontSize(FOLDED_FONT_SIZE)));
+            code_editor
+                .ranged_styles
+                .styles
+                .push((range, TextStyleProperty::Color(Color::TRANSPARENT)));
+        }
+
         text.set_ranged_styles(code_editor.ranged_styles);
         text.set_background_color(code_editor.background_color);
         text.set_color(code_editor.foreground_color);
     }
 
+    /// Finds the regions of `code`, and moves the folds to the lines they are on now.
+    fn update_folds(&mut self, code: &str) {
+        let Some(strategy) = self.folding else {
+            self.fold_regions.clear();
+            self.folded_lines.clear();
+            self.hidden_lines.clear();
+            return;
+        };
+        if self.folded_text != code {
+            self.folded_lines = remap_folded_lines(&self.folded_text, code, &self.fold_regions, &self.folded_lines);
+            self.folded_text = code.to_string();
+        }
+        self.fold_regions = fold_regions(code, strategy);
+        let regions = &self.fold_regions;
+        self.folded_lines
+            .retain(|line| regions.iter().any(|region| region.start_line == *line));
+        self.hidden_lines = hidden_lines(&self.fold_regions, &self.folded_lines, line_starts(code).len());
+    }
+
+    pub fn set_folding(&mut self, strategy: Option<FoldingStrategy>) -> &mut Self {
+        self.folding = strategy;
+        self.update_margins();
+        self.highlight();
+        self
+    }
+
+    pub fn set_minimap(&mut self, minimap: bool) -> &mut Self {
+        self.minimap = minimap;
+        self.update_margins();
+        self.highlight();
+        self
+    }
+
+    pub fn set_folded_lines(&mut self, folded_lines: BTreeSet<usize>) -> &mut Self {
+        self.folded_lines = folded_lines;
+        self.highlight();
+        self
+    }
+
+    pub fn toggle_fold(&mut self, line: usize) -> &mut Self {
+        if !self.folded_lines.remove(&line) {
+            self.folded_lines.insert(line);
+        }
+        self.highlight();
+        self
+    }
+
+    /// Makes room for the gutter and the minimap beside the text.
+    fn update_margins(&mut self) {
+        let left = if self.folding.is_some() { GUTTER_WIDTH } else { 0.0 };
+        let right = if self.minimap { MINIMAP_WIDTH } else { 0.0 };
+        self.text_input
+            .inner
+            .borrow_mut()
+            .set_margin(px(0), px(right), px(0), px(left));
+    }
+
+    /// The toggles of the regions whose first line is visible, by that line, in logical pixels.
+    fn fold_markers(&self) -> Vec<(usize, Rectangle)> {
+        if self.fold_regions.is_empty() {
+            return Vec::new();
+        }
+        let content_rectangle = self.element_data.layout.computed_box_transformed.content_rectangle();
+        let text_input = self.text_input.inner.borrow();
+        let Some(layout) = text_input.state.editor.try_layout() else {
+            return Vec::new();
+        };
+        let text_rectangle = text_input
+            .element_data
+            .layout
+            .computed_box_transformed
+            .content_rectangle();
+        let scroll_y = if text_input.element_data.is_scrollable() {
+            text_input.element_data.scroll().scroll_y()
+        } else {
+            0.0
+        };
+        let starts = line_starts(text_input.get_text());
+        let scale_factor = self.scale_factor as f32;
+
+        self.fold_regions
+            .iter()
+            .filter(|region| !self.hidden_lines.get(region.start_line).copied().unwrap_or(false))
+            .filter_map(|region| {
+                let start = *starts.get(region.start_line)?;
+                let line_box = Cursor::from_byte_index(layout, start, Affinity::Downstream).geometry(layout, 1.0);
+                let y = text_rectangle.y + (line_box.y0 as f32 - scroll_y) / scale_factor;
+                let height = (line_box.y1 - line_box.y0) as f32 / scale_factor;
+                Some((
+                    region.start_line,
+                    Rectangle::new(content_rectangle.x, y, GUTTER_WIDTH, height),
+                ))
+            })
+            .collect()
+    }
+
+    fn draw_fold_markers(&self, renderer: &mut dyn Renderer, scale_factor: f64) {
+        let color = self.foreground_color.multiply_alpha(0.6);
+        for (line, marker) in self.fold_markers() {
+            let marker = marker.scale(scale_factor);
+            let size = marker.width.min(marker.height) as f64 * 0.4;
+            let center_x = marker.x as f64 + marker.width as f64 / 2.0;
+            let center_y = marker.y as f64 + marker.height as f64 / 2.0;
+            let mut path = BezPath::new();
+            if self.folded_lines.contains(&line) {
+                // Pointing right, at the hidden lines.
+                path.move_to((center_x - size / 2.0, center_y - size / 2.0));
+                path.line_to((center_x + size / 2.0, center_y));
+                path.line_to((center_x - size / 2.0, center_y + size / 2.0));
+            } else {
+                path.move_to((center_x - size / 2.0, center_y - size / 4.0));
+                path.line_to((center_x + size / 2.0, center_y - size / 4.0));
+                path.line_to((center_x, center_y + size / 4.0));
+            }
+            path.close_path();
+            renderer.fill_bez_path(path, Brush::Color(color));
+        }
+    }
+
+    fn draw_minimap(&self, renderer: &mut dyn Renderer, scale_factor: f64) {
+        let content_rectangle = self.element_data.layout.computed_box_transformed.content_rectangle();
+        let minimap = Rectangle::new(
+            content_rectangle.right() - MINIMAP_WIDTH,
+            content_rectangle.y,
+            MINIMAP_WIDTH,
+            content_rectangle.height,
+        );
+        renderer.draw_rect(minimap.scale(scale_factor), self.foreground_color.multiply_alpha(0.05));
+
+        // The row of each line, leaving out the folded ones.
+        let mut row = 0;
+        let rows: Vec<Option<usize>> = (0..self.hidden_lines.len().max(1))
+            .map(|line| {
+                if self.hidden_lines.get(line).copied().unwrap_or(false) {
+                    return None;
+                }
+                row += 1;
+                Some(row - 1)
+            })
+            .collect();
+        let max_rows = (minimap.height / MINIMAP_LINE_HEIGHT) as usize;
+        let max_columns = (minimap.width / MINIMAP_COLUMN_WIDTH) as usize;
+
+        for block in &self.minimap_blocks {
+            let Some(row) = rows.get(block.line).copied().flatten() else {
+                continue;
+            };
+            if row >= max_rows || block.column >= max_columns {
+                continue;
+            }
+            let length = block.length.min(max_columns - block.column);
+            let rectangle = Rectangle::new(
+                minimap.x + block.column as f32 * MINIMAP_COLUMN_WIDTH,
+                minimap.y + row as f32 * MINIMAP_LINE_HEIGHT,
+                length as f32 * MINIMAP_COLUMN_WIDTH,
+                MINIMAP_LINE_HEIGHT * 0.75,
+            );
+            renderer.draw_rect(rectangle.scale(scale_factor), block.color.multiply_alpha(0.7));
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,287 @@
+//! Parses ANSI escape codes out of streamed console output.
+
+use peniko::Color;
+
+use crate::rgb;
+
+const ESCAPE: u8 = 0x1b;
+const BELL: u8 = 0x07;
+
+/// The 16 standard and bright colors, used by the `30`–`37`, `90`–`97` and matching background codes.
+const PALETTE: [Color; 16] = [
+    rgb(0, 0, 0),
+    rgb(205, 49, 49),
+    rgb(13, 188, 121),
+    rgb(229, 229, 16),
+    rgb(36, 114, 200),
+    rgb(188, 63, 188),
+    rgb(17, 168, 205),
+    rgb(229, 229, 229),
+    rgb(102, 102, 102),
+    rgb(241, 76, 76),
+    rgb(35, 209, 139),
+    rgb(245, 245, 67),
+    rgb(59, 142, 234),
+    rgb(214, 112, 214),
+    rgb(41, 184, 219),
+    rgb(255, 255, 255),
+];
+
+/// The text attributes set by SGR escape codes. `None` colors use the console's own colors.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct AnsiStyle {
+    pub(crate) foreground: Option<Color>,
+    pub(crate) background: Option<Color>,
+    pub(crate) bold: bool,
+    pub(crate) italic: bool,
+    pub(crate) underline: bool,
+}
+
+/// A piece of console output with the escape codes removed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AnsiSegment<'a> {
+    Text(&'a str),
+    LineFeed,
+    /// A carriage return that isn't part of a `\r\n` line ending, which moves back to the start of the line.
+    CarriageReturn,
+}
+
+/// Splits console output into text and line breaks, tracking the style set by SGR escape codes.
+///
+/// Output can be parsed in chunks that split escape codes; the incomplete end of a chunk is kept until the next one.
+/// Escape codes other than SGR, like cursor movement or window titles, are dropped.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AnsiParser {
+    style: AnsiStyle,
+    pending: String,
+}
+
+impl AnsiParser {
+    pub(crate) fn parse(&mut self, text: &str, mut on_segment: impl FnMut(AnsiSegment<'_>, AnsiStyle)) {
+        let joined;
+        let text = if self.pending.is_empty() {
+            text
+        } else {
+            joined = std::mem::take(&mut self.pending) + text;
+            joined.as_str()
+        };
+
+        let bytes = text.as_bytes();
+        let mut text_start = 0;
+        let mut index = 0;
+        while index < bytes.len() {
+            let byte = bytes[index];
+            if byte >= 0x20 || byte == b'\t' {
+                index += 1;
+                continue;
+            }
+
+            // Every byte we stop at is ASCII, so the text before it ends on a character boundary.
+            if text_start < index {
+                on_segment(AnsiSegment::Text(&text[text_start..index]), self.style);
+            }
+
+            let sequence_end = match byte {
+                ESCAPE => self.parse_escape(&bytes[index..]).map(|length| index + length),
+                b'\n' => {
+                    on_segment(AnsiSegment::LineFeed, self.style);
+                    Some(index + 1)
+                }
+                b'\r' => match bytes.get(index + 1) {
+                    Some(b'\n') => {
+                        on_segment(AnsiSegment::LineFeed, self.style);
+                        Some(index + 2)
+                    }
+                    Some(_) => {
+                        on_segment(AnsiSegment::CarriageReturn, self.style);
+                        Some(index + 1)
+                    }
+                    // Wait for the next chunk to know whether this is a `\r\n`.
+                    None => None,
+                },
+                // Other control characters, like the bell or backspace, aren't shown.
+                _ => Some(index + 1),
+            };
+
+            let Some(sequence_end) = sequence_end else {
+                self.pending = text[index..].to_string();
+                return;
+            };
+            index = sequence_end;
+            text_start = index;
+        }
+
+        if text_start < bytes.len() {
+            on_segment(AnsiSegment::Text(&text[text_start..]), self.style);
+        }
+    }
+
+    /// Applies the escape sequence at the start of `bytes`, returning its length or `None` if it is incomplete.
+    fn parse_escape(&mut self, bytes: &[u8]) -> Option<usize> {
+        match bytes.get(1)? {
+            // Control Sequence Introducer: parameter bytes followed by a final byte.
+            b'[' => {
+                let final_index = bytes[2..].iter().position(|byte| (0x40..=0x7e).contains(byte))? + 2;
+                if bytes[final_index] == b'm' {
+                    // The parameters are ASCII, as anything else would have ended the sequence.
+                    let parameters = std::str::from_utf8(&bytes[2..final_index]).unwrap_or_default();
+                    self.apply_sgr(parameters);
+                }
+                Some(final_index + 1)
+            }
+            // Operating System Command, ended by a bell or `ESC \`.
+            b']' => {
+                let mut index = 2;
+                loop {
+                    match bytes.get(index)? {
+                        &BELL => return Some(index + 1),
+                        &ESCAPE if *bytes.get(index + 1)? == b'\\' => return Some(index + 2),
+                        _ => index += 1,
+                    }
+                }
+            }
+            // Two character escapes, like `ESC c`.
+            byte if byte.is_ascii() => Some(2),
+            _ => Some(1),
+        }
+    }
+
+    /// Applies Select Graphic Rendition parameters such as `1;31`.
+    fn apply_sgr(&mut self, parameters: &str) {
+        // Some programs separate the parts of extended colors with colons instead of semicolons.
+        let codes: Vec<u16> = parameters
+            .split([';', ':'])
+            .map(|code| code.parse().unwrap_or(0))
+            .collect();
+
+        let mut codes = codes.iter().copied();
+        while let Some(code) = codes.next() {
+            match code {
+                0 => self.style = AnsiStyle::default(),
+                1 => self.style.bold = true,
+                3 => self.style.italic = true,
+                4 => self.style.underline = true,
+                22 => self.style.bold = false,
+                23 => self.style.italic = false,
+                24 => self.style.underline = false,
+                30..=37 => self.style.foreground = Some(PALETTE[code as usize - 30]),
+                38 => self.style.foreground = extended_color(&mut codes).or(self.style.foreground),
+                39 => self.style.foreground = None,
+                40..=47 => self.style.background = Some(PALETTE[code as usize - 40]),
+                48 => self.style.background = extended_color(&mut codes).or(self.style.background),
+                49 => self.style.background = None,
+                90..=97 => self.style.foreground = Some(PALETTE[code as usize - 90 + 8]),
+                100..=107 => self.style.background = Some(PALETTE[code as usize - 100 + 8]),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Reads the rest of a `38;5;n` or `38;2;r;g;b` color.
+fn extended_color(codes: &mut impl Iterator<Item = u16>) -> Option<Color> {
+    match codes.next()? {
+        5 => Some(indexed_color(codes.next()?.min(255) as u8)),
+        2 => {
+            let red = codes.next()?.min(255) as u8;
+            let green = codes.next()?.min(255) as u8;
+            let blue = codes.next()?.min(255) as u8;
+            Some(rgb(red, green, blue))
+        }
+        _ => None,
+    }
+}
+
+/// Returns one of the 256 indexed colors: the 16 palette colors, a 6×6×6 color cube and a gray ramp.
+fn indexed_color(index: u8) -> Color {
+    match index {
+        0..=15 => PALETTE[index as usize],
+        16..=231 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let index = index - 16;
+            rgb(level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            rgb(gray, gray, gray)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(parser: &mut AnsiParser, text: &str) -> Vec<(String, AnsiStyle)> {
+        let mut segments = Vec::new();
+        parser.parse(text, |segment, style| {
+            let text = match segment {
+                AnsiSegment::Text(text) => text.to_string(),
+                AnsiSegment::LineFeed => "\n".to_string(),
+                AnsiSegment::CarriageReturn => "\r".to_string(),
+            };
+            segments.push((text, style));
+        });
+        segments
+    }
+
+    #[test]
+    fn applies_and_resets_colors() {
+        let mut parser = AnsiParser::default();
+        let segments = parse(&mut parser, "\x1b[1;31merror\x1b[0m: failed");
+
+        let error_style = AnsiStyle {
+            foreground: Some(PALETTE[1]),
+            bold: true,
+            ..AnsiStyle::default()
+        };
+        assert_eq!(
+            segments,
+            vec![
+                ("error".to_string(), error_style),
+                (": failed".to_string(), AnsiStyle::default()),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_extended_colors() {
+        let mut parser = AnsiParser::default();
+        let segments = parse(&mut parser, "\x1b[38;2;10;20;30;48;5;196mx");
+
+        assert_eq!(segments[0].1.foreground, Some(rgb(10, 20, 30)));
+        assert_eq!(segments[0].1.background, Some(rgb(255, 0, 0)));
+    }
+
+    #[test]
+    fn keeps_escape_codes_split_across_chunks() {
+        let mut parser = AnsiParser::default();
+        assert_eq!(parse(&mut parser, "ok \x1b[3"), vec![("ok ".to_string(), AnsiStyle::default())]);
+
+        let segments = parse(&mut parser, "2mdone");
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].0, "done");
+        assert_eq!(segments[0].1.foreground, Some(PALETTE[2]));
+    }
+
+    #[test]
+    fn splits_line_endings() {
+        let mut parser = AnsiParser::default();
+        let segments: Vec<String> = parse(&mut parser, "a\r\nb\rc\n").into_iter().map(|(text, _)| text).collect();
+        assert_eq!(segments, vec!["a", "\n", "b", "\r", "c", "\n"]);
+
+        // A trailing `\r` waits for the next chunk.
+        assert!(parse(&mut parser, "d\r").iter().all(|(text, _)| text == "d"));
+        assert_eq!(parse(&mut parser, "\n")[0].0, "\n");
+    }
+
+    #[test]
+    fn drops_other_escape_codes() {
+        let mut parser = AnsiParser::default();
+        let text: String = parse(&mut parser, "\x1b]0;title\x07\x1b[2Kclean\x07")
+            .into_iter()
+            .map(|(text, _)| text)
+            .collect();
+        assert_eq!(text, "clean");
+    }
+}
@@ -0,0 +1,666 @@
+//! A scrolling view of streamed text output, such as build logs.
+
+mod ansi;
+mod scrollback;
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::ops::Range;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use craft_primitives::geometry::{Affine, Point, Rectangle};
+use craft_primitives::ColorBrush;
+use craft_renderer::renderer::Renderer;
+use craft_renderer::text_renderer_data::{TextData, TextRender};
+use craft_resource_manager::ResourceManager;
+use parley::{Affinity, Alignment, AlignmentOptions, BoundingBox, Cursor, Selection, StyleProperty, StyleSet};
+use peniko::Color;
+use ui_events::keyboard::{Key, KeyState, NamedKey};
+use ui_events::pointer::PointerButton;
+
+use crate::app::is_window_active;
+use crate::elements::console::ansi::AnsiParser;
+use crate::elements::console::scrollback::{LinePosition, Scrollback};
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, copy_to_clipboard, draw_generic_container, push_child_to_element};
+use crate::elements::traits::DeepClone;
+use crate::elements::{resolve_clip_for_scrollable, scrollable, AsElement, Container, Element, ElementInternals};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::{Display, FlexDirection, FontFamily, Overflow, Style};
+use crate::text::from_editor;
+use crate::text::text_context::TextContext;
+use crate::{px, rgb};
+
+const DEFAULT_MAX_LINES: usize = 10_000;
+
+#[derive(Clone)]
+pub struct Console {
+    pub inner: Rc<RefCell<ConsoleInner>>,
+}
+
+/// Shows streamed text output, keeping the most recent lines.
+///
+/// Text is added with [`Console::write`] and may contain ANSI escape codes, which set the color, background, weight,
+/// slant and underline of the text that follows. A carriage return replaces the current line, like progress bars in
+/// a terminal. Long lines are not wrapped.
+///
+/// Only the lines in view are laid out, so the scrollback can hold many lines. The console stays scrolled to the
+/// bottom as output arrives, until the user scrolls up; scrolling back to the bottom or pressing End resumes it.
+/// Text can be selected with the pointer and copied with Ctrl+C, or Cmd+C on macOS.
+#[derive(Clone)]
+pub struct ConsoleInner {
+    element_data: ElementData,
+    scrollback: Scrollback,
+    parser: AnsiParser,
+    /// Increased whenever the lines change, so the laid out lines are rebuilt.
+    revision: u64,
+    auto_scroll: bool,
+    /// Whether the console is scrolled to the bottom and should stay there as lines are added.
+    follow: bool,
+    /// The anchor and focus of the selected text.
+    selection: Option<(LinePosition, LinePosition)>,
+    is_selecting: bool,
+    /// Gives the console the height of all of its lines, so it scrolls like any other container.
+    spacer: Container,
+    view: Rc<RefCell<ConsoleView>>,
+}
+
+/// The laid out lines in view.
+#[derive(Clone, Default)]
+struct ConsoleView {
+    layout: Option<parley::Layout<ColorBrush>>,
+    text_render: Option<TextRender>,
+    key: Option<ViewKey>,
+    /// The byte range of each laid out line in the layout's text.
+    lines: Vec<Range<usize>>,
+}
+
+/// Everything the laid out lines depend on.
+#[derive(Clone, Copy, PartialEq)]
+struct ViewKey {
+    first_line: u64,
+    line_count: usize,
+    revision: u64,
+    scale_factor: f64,
+    font_size: f32,
+    color: Color,
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Element for Console {}
+
+impl Drop for ConsoleInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for Console {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for ConsoleInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl TextData for ConsoleView {
+    fn get_text_renderer(&self) -> Option<&TextRender> {
+        self.text_render.as_ref()
+    }
+}
+
+impl ElementInternals for ConsoleInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+
+        // The scroll range is only known after layout, so new lines are scrolled into view here.
+        let max_scroll_y = self.element_data.layout.max_scroll_y.max(0.0);
+        if self.follow && self.element_data.scroll().scroll_y() != max_scroll_y {
+            self.element_data.layout.scroll_state.set_scroll_y(max_scroll_y);
+            let node = self.element_data.layout.taffy_node_id.unwrap();
+            self.element_data.apply_scroll(taffy_tree.get_layout(node));
+            self.element_data.layout.scroll_state.mark_old();
+        }
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        draw_generic_container(self, renderer, resource_manager, text_context, scale_factor);
+    }
+
+    fn draw_children(&mut self, renderer: &mut dyn Renderer, _resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        let computed_box = self.element_data.layout.computed_box_transformed;
+        let padding_rectangle = computed_box.padding_rectangle();
+        let content_rectangle = computed_box.content_rectangle();
+        let line_height = self.line_height();
+
+        let lines_top = content_rectangle.y - self.element_data.scroll().scroll_y();
+        let visible = visible_lines(
+            padding_rectangle.y - lines_top,
+            padding_rectangle.bottom() - lines_top,
+            line_height,
+            self.scrollback.len(),
+        );
+        if visible.is_empty() {
+            return;
+        }
+
+        let first_line = self.scrollback.first_line() + visible.start as u64;
+        self.update_view(first_line, visible.len(), scale_factor, text_context);
+
+        let mut text_rectangle = Rectangle::new(
+            content_rectangle.x,
+            lines_top + visible.start as f32 * line_height,
+            content_rectangle.width,
+            visible.len() as f32 * line_height,
+        )
+        .scale(scale_factor);
+        if self.style().get_pixel_snapping() {
+            // Keep the text origin, and therefore the baselines, on physical pixels.
+            text_rectangle.x = text_rectangle.x.round();
+            text_rectangle.y = text_rectangle.y.round();
+        }
+
+        renderer.draw_text(Rc::downgrade(&self.view), text_rectangle, None, false);
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        _target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        let previous_scroll_y = self.element_data.scroll().scroll_y();
+        scrollable::handle_scroll_logic(self, message, event);
+        if self.element_data.scroll().scroll_y() != previous_scroll_y {
+            // Scrolling up pauses following the output, and scrolling back to the bottom resumes it.
+            self.follow = self.auto_scroll && self.is_scrolled_to_bottom();
+        }
+
+        match message {
+            EventKind::PointerButtonDown(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                let point = pointer_button.state.logical_point();
+                if self.element_data.layout.scroll_state.scroll_click.is_some()
+                    || self.element_data.layout.computed_scroll_track.contains(&point)
+                {
+                    return;
+                }
+
+                self.focus();
+                let position = self.position_at(point);
+                self.selection = Some((position, position));
+                self.is_selecting = true;
+                self.capture_pointer();
+                event.prevent_defaults();
+            }
+            EventKind::PointerMovedEvent(pointer_moved) if self.is_selecting => {
+                let position = self.position_at(pointer_moved.current.logical_point());
+                if let Some((_, focus)) = self.selection.as_mut() {
+                    *focus = position;
+                }
+            }
+            EventKind::PointerButtonUp(pointer_button)
+                if pointer_button.button == Some(PointerButton::Primary) && self.is_selecting =>
+            {
+                self.is_selecting = false;
+                self.release_pointer();
+            }
+            EventKind::KeyboardInputEvent(key) if key.state == KeyState::Down && self.is_focused() => {
+                let action_modifier = if cfg!(target_os = "macos") {
+                    key.modifiers.meta()
+                } else {
+                    key.modifiers.ctrl()
+                };
+
+                match &key.key {
+                    Key::Character(character) if action_modifier && character.eq_ignore_ascii_case("c") => {
+                        if let Some(text) = self.selected_text() {
+                            copy_to_clipboard(text);
+                        }
+                    }
+                    Key::Character(character) if action_modifier && character.eq_ignore_ascii_case("a") => {
+                        self.select_all();
+                    }
+                    Key::Named(NamedKey::End) => {
+                        self.follow = self.auto_scroll;
+                        scrollable::scroll_to_bottom(&mut self.element_data);
+                    }
+                    Key::Named(NamedKey::Home) => {
+                        self.follow = false;
+                        scrollable::scroll_to_top(&mut self.element_data);
+                    }
+                    _ => return,
+                }
+                event.prevent_defaults();
+            }
+            _ => {}
+        }
+    }
+
+    fn apply_clip(&mut self, clip_bounds: Option<Rectangle>) {
+        let overflow = self.style().get_overflow();
+        if overflow[0] == Overflow::Scroll || overflow[1] == Overflow::Scroll {
+            resolve_clip_for_scrollable(self, clip_bounds);
+        } else {
+            self.element_data.layout.apply_clip(clip_bounds);
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl Console {
+    pub fn new() -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<ConsoleInner>>| {
+            RefCell::new(ConsoleInner {
+                element_data: ElementData::new(me.clone(), true),
+                scrollback: Scrollback::new(DEFAULT_MAX_LINES),
+                parser: AnsiParser::default(),
+                revision: 0,
+                auto_scroll: true,
+                follow: true,
+                selection: None,
+                is_selecting: false,
+                spacer: Container::new().height(px(0)).flex_shrink(0.0),
+                view: Rc::new(RefCell::new(ConsoleView::default())),
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        inner_mut.set_display(Display::Flex);
+        inner_mut.set_flex_direction(FlexDirection::Column);
+        inner_mut.set_overflow_y(Overflow::Scroll);
+        inner_mut.set_padding(px(6), px(8), px(6), px(8));
+        inner_mut.set_background_color(rgb(30, 30, 30));
+        inner_mut.set_color(rgb(204, 204, 204));
+        inner_mut.set_font_family(FontFamily::new("monospace"));
+        inner_mut.set_font_size(13.0);
+
+        let spacer = inner_mut.spacer.clone();
+        inner_mut.push(spacer.inner);
+
+        drop(inner_mut);
+        Self { inner }
+    }
+
+    /// Set how many lines are kept. The oldest lines are dropped past this. Defaults to 10,000.
+    pub fn max_lines(self, max_lines: usize) -> Self {
+        self.inner.borrow_mut().set_max_lines(max_lines);
+        self
+    }
+
+    /// Set whether the console follows new output while scrolled to the bottom. Defaults to true.
+    pub fn auto_scroll(self, auto_scroll: bool) -> Self {
+        self.inner.borrow_mut().set_auto_scroll(auto_scroll);
+        self
+    }
+
+    /// Adds output to the console. Escape codes may be split across calls.
+    pub fn write(&self, text: &str) {
+        self.inner.borrow_mut().write(text);
+    }
+
+    pub fn clear(&self) {
+        self.inner.borrow_mut().clear();
+    }
+
+    /// Returns all kept lines without their escape codes.
+    pub fn get_text(&self) -> String {
+        self.inner.borrow().text()
+    }
+
+    pub fn get_selected_text(&self) -> Option<String> {
+        self.inner.borrow().selected_text()
+    }
+
+    /// Scrolls a line to the top of the console, counting from the oldest kept line. This pauses following the output.
+    pub fn scroll_to_line(&self, line: usize) {
+        self.inner.borrow_mut().scroll_to_line(line);
+    }
+}
+
+impl ConsoleInner {
+    pub fn write(&mut self, text: &str) {
+        let scrollback = &mut self.scrollback;
+        let mut dropped = 0;
+        self.parser
+            .parse(text, |segment, style| dropped += scrollback.push(segment, style));
+        self.on_lines_changed(dropped);
+    }
+
+    pub fn clear(&mut self) {
+        self.scrollback.clear();
+        self.selection = None;
+        self.is_selecting = false;
+        self.follow = self.auto_scroll;
+        self.on_lines_changed(0);
+    }
+
+    pub fn set_max_lines(&mut self, max_lines: usize) {
+        let dropped = self.scrollback.set_max_lines(max_lines);
+        self.on_lines_changed(dropped);
+    }
+
+    pub fn set_auto_scroll(&mut self, auto_scroll: bool) {
+        self.auto_scroll = auto_scroll;
+        self.follow = auto_scroll && self.is_scrolled_to_bottom();
+    }
+
+    pub fn scroll_to_line(&mut self, line: usize) {
+        self.follow = false;
+        let scroll_y = line.min(self.scrollback.len()) as f32 * self.line_height();
+        scrollable::scroll_to(&mut self.element_data, scroll_y.min(self.element_data.layout.max_scroll_y));
+    }
+
+    fn text(&self) -> String {
+        let start = LinePosition {
+            line: self.scrollback.first_line(),
+            index: 0,
+        };
+        let end = LinePosition {
+            line: self.scrollback.end_line(),
+            index: 0,
+        };
+        self.scrollback.text_between(start, end)
+    }
+
+    fn selected_text(&self) -> Option<String> {
+        let (anchor, focus) = self.selection?;
+        let text = self.scrollback.text_between(anchor, focus);
+        if text.is_empty() { None } else { Some(text) }
+    }
+
+    fn select_all(&mut self) {
+        let start = LinePosition {
+            line: self.scrollback.first_line(),
+            index: 0,
+        };
+        let end = LinePosition {
+            line: self.scrollback.end_line(),
+            index: 0,
+        };
+        self.selection = Some((start, end));
+    }
+
+    fn line_height(&self) -> f32 {
+        let style = self.style();
+        style.get_font_size() * style.get_line_height()
+    }
+
+    fn is_scrolled_to_bottom(&self) -> bool {
+        self.element_data.scroll().scroll_y() >= self.element_data.layout.max_scroll_y - 1.0
+    }
+
+    fn on_lines_changed(&mut self, dropped: usize) {
+        self.revision += 1;
+
+        let line_height = self.line_height();
+        if dropped > 0 && !self.follow {
+            // Keep the lines being read in place as the lines above them are dropped.
+            let scroll_y = self.element_data.scroll().scroll_y() - dropped as f32 * line_height;
+            self.element_data.layout.scroll_state.set_scroll_y(scroll_y.max(0.0));
+        }
+
+        let height = self.scrollback.len() as f32 * line_height;
+        self.spacer.clone().height(px(height));
+    }
+
+    /// Returns the position of the character under a point, clamped to the kept lines.
+    fn position_at(&self, point: Point) -> LinePosition {
+        let content_rectangle = self.element_data.layout.computed_box_transformed.content_rectangle();
+        let y = point.y as f32 - content_rectangle.y + self.element_data.scroll().scroll_y();
+        let row = (y / self.line_height()).max(0.0) as u64;
+        let line = (self.scrollback.first_line() + row).min(self.scrollback.end_line().saturating_sub(1));
+
+        let line_length = self.scrollback.line(line).map_or(0, |line| line.text.len());
+        let scale_factor = self.view.borrow().key.map_or(1.0, |key| key.scale_factor);
+        let x = (point.x as f32 - content_rectangle.x) * scale_factor as f32;
+        let index = self.view.borrow().index_at(line, x).unwrap_or(if x <= 0.0 { 0 } else { line_length });
+        LinePosition { line, index }
+    }
+
+    /// Lays out the lines in view again if they changed, and updates the selection.
+    fn update_view(&mut self, first_line: u64, line_count: usize, scale_factor: f64, text_context: &mut TextContext) {
+        let style = &self.element_data.style;
+        let key = ViewKey {
+            first_line,
+            line_count,
+            revision: self.revision,
+            scale_factor,
+            font_size: style.get_font_size(),
+            color: style.get_color(),
+        };
+
+        let mut view = self.view.borrow_mut();
+        if view.key != Some(key) {
+            view.rebuild(&self.scrollback, style, key, text_context);
+        }
+        let (selection_color, selection_text_color) = style.get_selection_colors(is_window_active());
+        view.update_selection(self.selection, selection_color);
+        if let Some(text_render) = view.text_render.as_mut() {
+            text_render.contrast = style.get_text_contrast();
+            text_render.selection_text_color = selection_text_color;
+        }
+    }
+}
+
+impl ConsoleView {
+    fn rebuild(&mut self, scrollback: &Scrollback, style: &Style, key: ViewKey, text_context: &mut TextContext) {
+        let mut text = String::new();
+        let mut spans = Vec::new();
+        self.lines.clear();
+        for number in key.first_line..key.first_line + key.line_count as u64 {
+            let Some(line) = scrollback.line(number) else {
+                break;
+            };
+            if number != key.first_line {
+                text.push('\n');
+            }
+            let start = text.len();
+            text.push_str(&line.text);
+            self.lines.push(start..text.len());
+            spans.extend(
+                line.spans
+                    .iter()
+                    .map(|(range, span_style)| (range.start + start..range.end + start, *span_style)),
+            );
+        }
+
+        let mut style_set = StyleSet::new(style.get_font_size());
+        style.add_styles_to_style_set(&mut style_set);
+
+        // Lines are laid out without quantization, so each one is exactly as tall as the scroll math expects.
+        let mut builder =
+            text_context
+                .layout_context
+                .ranged_builder(&mut text_context.font_context, &text, key.scale_factor as f32, false);
+        for property in style_set.inner().values() {
+            builder.push_default(property.to_owned());
+        }
+        for (range, span_style) in &spans {
+            if let Some(color) = span_style.foreground {
+                builder.push(StyleProperty::Brush(ColorBrush { color }), range.clone());
+            }
+            if span_style.bold {
+                builder.push(StyleProperty::FontWeight(parley::FontWeight::BOLD), range.clone());
+            }
+            if span_style.italic {
+                builder.push(StyleProperty::FontStyle(parley::FontStyle::Italic), range.clone());
+            }
+            if span_style.underline {
+                builder.push(StyleProperty::Underline(true), range.clone());
+            }
+        }
+
+        let mut layout = builder.build(&text);
+        layout.break_all_lines(None);
+        layout.align(Alignment::Start, AlignmentOptions::default());
+
+        let mut text_render = from_editor(&layout);
+        for (range, span_style) in &spans {
+            let Some(color) = span_style.background else {
+                continue;
+            };
+            byte_selection(&layout, range.clone()).geometry_with(&layout, |bounding_box, line| {
+                text_render.lines[line]
+                    .backgrounds
+                    .push((bounding_box_to_rect(bounding_box), color));
+            });
+        }
+
+        self.layout = Some(layout);
+        self.text_render = Some(text_render);
+        self.key = Some(key);
+    }
+
+    fn update_selection(&mut self, selection: Option<(LinePosition, LinePosition)>, selection_color: Color) {
+        let range = selection.and_then(|(anchor, focus)| self.byte_range(anchor, focus));
+        let (Some(layout), Some(text_render)) = (self.layout.as_ref(), self.text_render.as_mut()) else {
+            return;
+        };
+
+        for line in text_render.lines.iter_mut() {
+            line.selections.clear();
+        }
+        if let Some(range) = range {
+            byte_selection(layout, range).geometry_with(layout, |bounding_box, line| {
+                text_render.lines[line]
+                    .selections
+                    .push((bounding_box_to_rect(bounding_box), selection_color));
+            });
+        }
+    }
+
+    /// Returns the byte range of the layout's text covered by a selection, if any of it is in view.
+    fn byte_range(&self, anchor: LinePosition, focus: LinePosition) -> Option<Range<usize>> {
+        let first_line = self.key?.first_line;
+        let end_line = first_line + self.lines.len() as u64;
+        let (start, end) = if anchor <= focus { (anchor, focus) } else { (focus, anchor) };
+        if start == end || end.line < first_line || start.line >= end_line {
+            return None;
+        }
+
+        let offset = |position: LinePosition| {
+            let line = &self.lines[(position.line - first_line) as usize];
+            line.start + position.index.min(line.len())
+        };
+        let start = if start.line < first_line { 0 } else { offset(start) };
+        let end = if end.line >= end_line { self.lines.last()?.end } else { offset(end) };
+        Some(start..end)
+    }
+
+    /// Returns the byte index into a laid out line closest to `x`, in physical pixels.
+    fn index_at(&self, line: u64, x: f32) -> Option<usize> {
+        let layout = self.layout.as_ref()?;
+        let row = usize::try_from(line.checked_sub(self.key?.first_line)?).ok()?;
+        let line_range = self.lines.get(row)?;
+
+        let metrics = layout.lines().nth(row)?.metrics();
+        let y = (metrics.block_min_coord + metrics.block_max_coord) / 2.0;
+        let index = Cursor::from_point(layout, x, y).index();
+        Some(index.clamp(line_range.start, line_range.end) - line_range.start)
+    }
+}
+
+fn byte_selection(layout: &parley::Layout<ColorBrush>, range: Range<usize>) -> Selection {
+    Selection::new(
+        Cursor::from_byte_index(layout, range.start, Affinity::Downstream),
+        Cursor::from_byte_index(layout, range.end, Affinity::Downstream),
+    )
+}
+
+fn bounding_box_to_rect(bounding_box: BoundingBox) -> Rectangle {
+    Rectangle::new(
+        bounding_box.x0 as f32,
+        bounding_box.y0 as f32,
+        bounding_box.width() as f32,
+        bounding_box.height() as f32,
+    )
+}
+
+/// Returns the lines that overlap `top..bottom`, measured from the top of the first line.
+fn visible_lines(top: f32, bottom: f32, line_height: f32, line_count: usize) -> Range<usize> {
+    if line_height <= 0.0 {
+        return 0..0;
+    }
+    let first = (top / line_height).floor().max(0.0) as usize;
+    let end = (bottom / line_height).ceil().max(0.0) as usize;
+    first.min(line_count)..end.min(line_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::visible_lines;
+
+    #[test]
+    fn only_lines_overlapping_the_viewport_are_visible() {
+        assert_eq!(visible_lines(0.0, 100.0, 20.0, 50), 0..5);
+        assert_eq!(visible_lines(30.0, 130.0, 20.0, 50), 1..7);
+    }
+
+    #[test]
+    fn visible_lines_are_clamped_to_the_line_count() {
+        assert_eq!(visible_lines(-10.0, 100.0, 20.0, 3), 0..3);
+        assert_eq!(visible_lines(200.0, 300.0, 20.0, 3), 3..3);
+    }
+}
@@ -0,0 +1,262 @@
+//! The lines kept by a console.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use crate::elements::console::ansi::{AnsiSegment, AnsiStyle};
+
+/// A line of console output with the styles of its parts.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct ConsoleLine {
+    pub(crate) text: String,
+    /// Byte ranges of the text with a style other than the default.
+    pub(crate) spans: Vec<(Range<usize>, AnsiStyle)>,
+}
+
+impl ConsoleLine {
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, text: &str, style: AnsiStyle) {
+        let start = self.text.len();
+        self.text.push_str(text);
+        if style == AnsiStyle::default() {
+            return;
+        }
+
+        match self.spans.last_mut() {
+            Some((range, last_style)) if range.end == start && *last_style == style => range.end = self.text.len(),
+            _ => self.spans.push((start..self.text.len(), style)),
+        }
+    }
+
+    fn clear(&mut self) {
+        self.text.clear();
+        self.spans.clear();
+    }
+}
+
+/// A place in the scrollback, as a line number and a byte index into that line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct LinePosition {
+    pub(crate) line: u64,
+    pub(crate) index: usize,
+}
+
+/// A ring buffer of the most recent lines.
+///
+/// Lines are numbered from the first line ever written, so a number keeps pointing at the same line when older
+/// lines are dropped.
+#[derive(Clone, Debug)]
+pub(crate) struct Scrollback {
+    lines: VecDeque<ConsoleLine>,
+    max_lines: usize,
+    /// The number of lines dropped from the front.
+    dropped: u64,
+    /// Whether the last line was ended by a line feed, so the next text starts a new line.
+    line_ended: bool,
+    /// Whether the next text replaces the last line, after a carriage return.
+    overwrite: bool,
+}
+
+impl Scrollback {
+    pub(crate) fn new(max_lines: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            max_lines: max_lines.max(1),
+            dropped: 0,
+            line_ended: false,
+            overwrite: false,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// The number of the oldest line that is still kept.
+    pub(crate) fn first_line(&self) -> u64 {
+        self.dropped
+    }
+
+    /// The number of the line after the newest line.
+    pub(crate) fn end_line(&self) -> u64 {
+        self.dropped + self.lines.len() as u64
+    }
+
+    pub(crate) fn line(&self, line: u64) -> Option<&ConsoleLine> {
+        let offset = line.checked_sub(self.dropped)?;
+        self.lines.get(usize::try_from(offset).ok()?)
+    }
+
+    /// Adds a parsed segment of output, returning how many old lines were dropped to make room.
+    pub(crate) fn push(&mut self, segment: AnsiSegment<'_>, style: AnsiStyle) -> usize {
+        match segment {
+            AnsiSegment::Text(text) => {
+                let dropped = self.ensure_open_line();
+                let line = self.lines.back_mut().unwrap();
+                if self.overwrite {
+                    line.clear();
+                    self.overwrite = false;
+                }
+                line.push(text, style);
+                dropped
+            }
+            AnsiSegment::LineFeed => {
+                let dropped = self.ensure_open_line();
+                self.line_ended = true;
+                self.overwrite = false;
+                dropped
+            }
+            AnsiSegment::CarriageReturn => {
+                self.overwrite = !self.line_ended;
+                0
+            }
+        }
+    }
+
+    pub(crate) fn set_max_lines(&mut self, max_lines: usize) -> usize {
+        self.max_lines = max_lines.max(1);
+        self.drop_excess_lines()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.dropped += self.lines.len() as u64;
+        self.lines.clear();
+        self.line_ended = false;
+        self.overwrite = false;
+    }
+
+    /// Returns the text between two positions, with the lines joined by line feeds.
+    pub(crate) fn text_between(&self, start: LinePosition, end: LinePosition) -> String {
+        let (start, end) = if start <= end { (start, end) } else { (end, start) };
+        let start = start.max(LinePosition {
+            line: self.first_line(),
+            index: 0,
+        });
+
+        let mut text = String::new();
+        for number in start.line..=end.line {
+            let Some(line) = self.line(number) else {
+                break;
+            };
+            let from = if number == start.line { start.index.min(line.text.len()) } else { 0 };
+            let to = if number == end.line { end.index.min(line.text.len()) } else { line.text.len() };
+            if number != start.line {
+                text.push('\n');
+            }
+            text.push_str(line.text.get(from..to.max(from)).unwrap_or_default());
+        }
+        text
+    }
+
+    /// Starts a new line if there is none to write to.
+    fn ensure_open_line(&mut self) -> usize {
+        if !self.lines.is_empty() && !self.line_ended {
+            return 0;
+        }
+        self.lines.push_back(ConsoleLine::default());
+        self.line_ended = false;
+        self.drop_excess_lines()
+    }
+
+    fn drop_excess_lines(&mut self) -> usize {
+        let excess = self.lines.len().saturating_sub(self.max_lines);
+        for _ in 0..excess {
+            self.lines.pop_front();
+        }
+        self.dropped += excess as u64;
+        excess
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(scrollback: &mut Scrollback, segments: &[AnsiSegment<'_>]) {
+        for segment in segments {
+            scrollback.push(*segment, AnsiStyle::default());
+        }
+    }
+
+    fn lines(scrollback: &Scrollback) -> Vec<String> {
+        (scrollback.first_line()..scrollback.end_line())
+            .map(|number| scrollback.line(number).unwrap().text.clone())
+            .collect()
+    }
+
+    #[test]
+    fn line_feeds_start_new_lines_lazily() {
+        let mut scrollback = Scrollback::new(10);
+        write(
+            &mut scrollback,
+            &[AnsiSegment::Text("a"), AnsiSegment::LineFeed, AnsiSegment::LineFeed],
+        );
+        assert_eq!(lines(&scrollback), vec!["a", ""]);
+
+        write(&mut scrollback, &[AnsiSegment::Text("b")]);
+        assert_eq!(lines(&scrollback), vec!["a", "", "b"]);
+    }
+
+    #[test]
+    fn carriage_returns_replace_the_line() {
+        let mut scrollback = Scrollback::new(10);
+        write(
+            &mut scrollback,
+            &[
+                AnsiSegment::Text("10%"),
+                AnsiSegment::CarriageReturn,
+                AnsiSegment::Text("50%"),
+            ],
+        );
+        assert_eq!(lines(&scrollback), vec!["50%"]);
+    }
+
+    #[test]
+    fn drops_the_oldest_lines_past_the_limit() {
+        let mut scrollback = Scrollback::new(2);
+        let mut dropped = 0;
+        for text in ["a", "b", "c"] {
+            dropped += scrollback.push(AnsiSegment::Text(text), AnsiStyle::default());
+            dropped += scrollback.push(AnsiSegment::LineFeed, AnsiStyle::default());
+        }
+
+        assert_eq!(dropped, 1);
+        assert_eq!(lines(&scrollback), vec!["b", "c"]);
+        assert_eq!(scrollback.first_line(), 1);
+        assert_eq!(scrollback.line(0), None);
+    }
+
+    #[test]
+    fn merges_spans_with_the_same_style() {
+        let bold = AnsiStyle {
+            bold: true,
+            ..AnsiStyle::default()
+        };
+        let mut scrollback = Scrollback::new(10);
+        scrollback.push(AnsiSegment::Text("ab"), bold);
+        scrollback.push(AnsiSegment::Text("cd"), bold);
+        scrollback.push(AnsiSegment::Text("ef"), AnsiStyle::default());
+
+        assert_eq!(scrollback.line(0).unwrap().spans, vec![(0..4, bold)]);
+    }
+
+    #[test]
+    fn copies_text_between_positions() {
+        let mut scrollback = Scrollback::new(10);
+        write(
+            &mut scrollback,
+            &[
+                AnsiSegment::Text("hello"),
+                AnsiSegment::LineFeed,
+                AnsiSegment::Text("world"),
+            ],
+        );
+
+        let start = LinePosition { line: 0, index: 3 };
+        let end = LinePosition { line: 1, index: 2 };
+        assert_eq!(scrollback.text_between(end, start), "lo\nwo");
+    }
+}
@@ -0,0 +1,331 @@
+//! Elements implemented outside of craft.
+//!
+//! [`ElementInternals`] needs the layout tree and other state that is private to craft, so other crates implement
+//! [`CustomElement`] instead and wrap their element in a [`Custom`]. The wrapper lays the element out with the
+//! surrounding tree, draws its background, borders, children and scrollbar, and sends it its events.
+//!
+//! ```ignore
+//! #[derive(Clone)]
+//! struct Swatch {
+//!     color: Color,
+//! }
+//!
+//! impl CustomElement for Swatch {
+//!     fn measure(&mut self, _known: Size<Option<f32>>, _available: Size<AvailableSpace>, _: &mut TextContext)
+//!         -> Size<f32> {
+//!         Size { width: 24.0, height: 24.0 }
+//!     }
+//!
+//!     fn draw(&mut self, cx: &mut DrawContext) {
+//!         cx.renderer.draw_rect(cx.content_rectangle, self.color);
+//!     }
+//! }
+//!
+//! Window::new("Swatches").push(Custom::new(Swatch { color: Color::BLACK }));
+//! ```
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use craft_primitives::geometry::{Affine, ElementBox, Point, Rectangle};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use taffy::{AvailableSpace, Size};
+
+use crate::app::TAFFY_TREE;
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, push_child_to_element};
+use crate::elements::traits::DeepClone;
+use crate::elements::{resolve_clip_for_scrollable, AsElement, Element, ElementInternals};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::layout::layout_context::{LayoutContext, TaffyElementContext};
+use crate::style::Overflow;
+use crate::text::text_context::TextContext;
+
+/// What a [`CustomElement`] changed while handling an event.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CustomChange {
+    #[default]
+    Nothing,
+    /// The element draws differently, but keeps its size.
+    Paint,
+    /// The size of the element may have changed, so it is measured and laid out again.
+    Layout,
+}
+
+/// What a [`CustomElement`] draws with.
+pub struct DrawContext<'a> {
+    pub renderer: &'a mut dyn Renderer,
+    /// The content box of the element, in physical pixels.
+    pub content_rectangle: Rectangle,
+    pub scale_factor: f64,
+    pub text_context: &'a mut TextContext,
+}
+
+/// An element implemented outside of craft. See [`Custom`].
+///
+/// Sizes and boxes are in logical pixels, except when drawing.
+pub trait CustomElement: Clone + 'static {
+    /// The size of the content of the element, for elements without children. Elements with children are sized by
+    /// their children and style.
+    fn measure(
+        &mut self,
+        _known_dimensions: Size<Option<f32>>,
+        _available_space: Size<AvailableSpace>,
+        _text_context: &mut TextContext,
+    ) -> Size<f32> {
+        Size::ZERO
+    }
+
+    /// Called after the layout with the final box of the element.
+    fn layout(&mut self, _computed_box: ElementBox) {}
+
+    /// Draws the content of the element, above its background and borders and below its children.
+    fn draw(&mut self, _cx: &mut DrawContext) {}
+
+    /// Handles an event sent to the element or to one of its children, before the user's handlers run.
+    fn on_event(&mut self, _message: &EventKind, _event: &mut Event) -> CustomChange {
+        CustomChange::Nothing
+    }
+
+    /// Whether spatial navigation can move the focus to the element, see [`crate::spatial_navigation`].
+    fn is_focusable(&self) -> bool {
+        false
+    }
+
+    /// Whether the element uses the arrow keys while focused, so they don't move the focus with spatial navigation.
+    fn handles_arrow_keys(&self) -> bool {
+        false
+    }
+
+    /// Describes the element to assistive technologies, like its role and value. The bounds and children of the
+    /// node are filled in afterwards.
+    #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+    fn accessibility(&self, _node: &mut accesskit::Node) {}
+}
+
+/// Adds a [`CustomElement`] to a tree like any other element.
+#[derive(Clone)]
+pub struct Custom<T: CustomElement> {
+    pub inner: Rc<RefCell<CustomInner<T>>>,
+}
+
+#[derive(Clone)]
+pub struct CustomInner<T: CustomElement> {
+    element_data: ElementData,
+    custom: T,
+}
+
+impl<T: CustomElement> Element for Custom<T> {}
+
+impl<T: CustomElement> Drop for CustomInner<T> {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl<T: CustomElement> AsElement for Custom<T> {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl<T: CustomElement> crate::elements::ElementData for CustomInner<T> {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl<T: CustomElement> ElementInternals for CustomInner<T> {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        let clone = self.deep_clone_internal();
+        // The cloned layout node still measures this element.
+        let node = clone.borrow().element_data().layout.taffy_node_id;
+        if let Some(node) = node {
+            let layout_context = LayoutContext::Element(TaffyElementContext {
+                element: Rc::downgrade(&clone),
+            });
+            TAFFY_TREE.with_borrow_mut(|taffy_tree| taffy_tree.set_node_context(node, Some(layout_context)));
+        }
+        clone
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+        self.custom.layout(self.element_data.layout.computed_box);
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        if !self.is_visible() {
+            return;
+        }
+        self.maybe_start_overlay(renderer);
+        self.add_hit_testable(renderer, true, scale_factor);
+        self.maybe_start_effect_layer(renderer, scale_factor);
+        self.maybe_start_mask(renderer, scale_factor);
+        self.draw_borders(renderer, scale_factor);
+        self.maybe_start_layer(renderer, scale_factor);
+        let content_rectangle = self.get_computed_box_transformed().content_rectangle().scale(scale_factor);
+        self.custom.draw(&mut DrawContext {
+            renderer: &mut *renderer,
+            content_rectangle,
+            scale_factor,
+            text_context: &mut *text_context,
+        });
+        self.draw_children(renderer, resource_manager.clone(), scale_factor, text_context);
+        self.maybe_end_layer(renderer);
+        self.draw_scrollbar(renderer, scale_factor);
+        self.maybe_end_mask(renderer, resource_manager, scale_factor, text_context);
+        self.maybe_end_effect_layer(renderer, scale_factor);
+        self.maybe_end_overlay(renderer);
+    }
+
+    #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+    fn compute_accessibility_tree(
+        &mut self,
+        tree: &mut accesskit::TreeUpdate,
+        parent_index: Option<usize>,
+        scale_factor: f64,
+    ) {
+        let current_node_id = accesskit::NodeId(self.element_data.internal_id);
+        let mut current_node = accesskit::Node::new(accesskit::Role::GenericContainer);
+        self.custom.accessibility(&mut current_node);
+
+        crate::elements::internal_helpers::add_generic_accesskit_data(
+            &mut self.element_data,
+            current_node,
+            current_node_id,
+            tree,
+            parent_index,
+            scale_factor,
+        );
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        _target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        match self.custom.on_event(message, event) {
+            CustomChange::Nothing => {}
+            CustomChange::Paint => self.invalidate_layer_cache(),
+            CustomChange::Layout => self.mark_dirty(),
+        }
+    }
+
+    fn apply_clip(&mut self, clip_bounds: Option<Rectangle>) {
+        let overflow = self.style().get_overflow();
+        if overflow[0] == Overflow::Scroll || overflow[1] == Overflow::Scroll {
+            resolve_clip_for_scrollable(self, clip_bounds);
+        } else {
+            self.element_data.layout.apply_clip(clip_bounds);
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn measure(
+        &mut self,
+        known_dimensions: Size<Option<f32>>,
+        available_space: Size<AvailableSpace>,
+        text_context: &mut TextContext,
+    ) -> Size<f32> {
+        self.custom.measure(known_dimensions, available_space, text_context)
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.element_data.focusable || self.custom.is_focusable()
+    }
+
+    fn handles_arrow_keys(&self) -> bool {
+        self.custom.handles_arrow_keys()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl<T: CustomElement> Custom<T> {
+    pub fn new(custom: T) -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<CustomInner<T>>>| {
+            RefCell::new(CustomInner {
+                element_data: ElementData::new(me.clone(), true),
+                custom,
+            })
+        });
+        let element: Weak<RefCell<dyn ElementInternals>> = Rc::downgrade(&inner);
+        let layout_context = LayoutContext::Element(TaffyElementContext { element });
+        inner.borrow_mut().element_data.create_layout_node(Some(layout_context));
+        Self { inner }
+    }
+
+    pub fn get_custom(&self) -> Ref<'_, T> {
+        Ref::map(self.inner.borrow(), |inner| &inner.custom)
+    }
+
+    /// Changes the custom element, and measures or draws it again as `update` returns.
+    pub fn update(self, update: impl FnOnce(&mut T) -> CustomChange) -> Self {
+        self.inner.borrow_mut().update(update);
+        self
+    }
+}
+
+impl<T: CustomElement> CustomInner<T> {
+    pub fn custom(&self) -> &T {
+        &self.custom
+    }
+
+    pub fn update(&mut self, update: impl FnOnce(&mut T) -> CustomChange) {
+        match update(&mut self.custom) {
+            CustomChange::Nothing => {}
+            CustomChange::Paint => self.invalidate_layer_cache(),
+            CustomChange::Layout => self.mark_dirty(),
+        }
+    }
+}
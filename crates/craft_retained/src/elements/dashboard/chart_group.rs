@@ -0,0 +1,46 @@
+//! Charts that show the same crosshair and tooltip while one of them is hovered.
+
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+use super::SparklineInner;
+use crate::app::queue_event;
+use crate::events::{Event, EventKind};
+
+/// A set of [`Sparkline`](super::Sparkline)s sharing the same x-domain, like series measured at the same times.
+///
+/// While the pointer is over one chart of the group, every chart draws a crosshair and a tooltip at the value with
+/// the same index. The hovered chart tells the others in an [`EventKind::ElementMessage`].
+#[derive(Clone, Default)]
+pub struct ChartGroup {
+    charts: Rc<RefCell<Vec<Weak<RefCell<SparklineInner>>>>>,
+}
+
+/// Sent by a chart to the other charts of its group.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ChartGroupMessage {
+    /// The index of the value under the pointer, or `None` when the pointer left the chart.
+    Hover(Option<usize>),
+}
+
+impl ChartGroup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn add(&self, chart: Weak<RefCell<SparklineInner>>) {
+        let mut charts = self.charts.borrow_mut();
+        charts.retain(|chart| chart.strong_count() > 0);
+        charts.push(chart);
+    }
+
+    /// Sends a message to every chart of the group except `sender`.
+    pub(crate) fn send(&self, sender: &SparklineInner, message: ChartGroupMessage) {
+        for chart in self.charts.borrow().iter().filter_map(Weak::upgrade) {
+            if std::ptr::eq(chart.as_ptr(), sender) {
+                continue;
+            }
+            queue_event(Event::new(chart), EventKind::new_element_message(message));
+        }
+    }
+}
@@ -0,0 +1,428 @@
+//! A radial gauge.
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::f64::consts::PI;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+#[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+use accesskit::{Role, TreeUpdate};
+use craft_primitives::geometry::{Affine, BezPath, Point, Rectangle, Shape, TOLERANCE};
+use craft_renderer::Brush;
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use peniko::Color;
+use peniko::kurbo;
+
+use super::ValueAnimation;
+use super::time::{Duration, Instant};
+use crate::app::request_animation_frame;
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, push_child_to_element};
+use crate::elements::traits::DeepClone;
+use crate::elements::{AsElement, Element, ElementInternals, Text};
+use crate::events::{Event, EventKind};
+use crate::export::SvgDocument;
+use crate::layout::TaffyTree;
+use crate::style::{AlignItems, Display, JustifyContent, Unit};
+use crate::text::text_context::TextContext;
+use crate::rgb;
+
+#[derive(Clone)]
+pub struct Gauge {
+    pub inner: Rc<RefCell<GaugeInner>>,
+}
+
+/// An arc filled in proportion to a value between a minimum and a maximum, with the value written in its middle.
+///
+/// The arc is open at the bottom and spans 270° by default. When an animation duration is set, changes of the value
+/// sweep the arc to its new length.
+#[derive(Clone)]
+pub struct GaugeInner {
+    element_data: ElementData,
+    value: f32,
+    min: f32,
+    max: f32,
+    animation: ValueAnimation,
+    thickness: f32,
+    sweep_angle: f32,
+    decimals: usize,
+    suffix: String,
+    track_color: Color,
+    value_color: Color,
+    pub label: Text,
+}
+
+impl Gauge {
+    pub fn new(value: f32) -> Self {
+        Self {
+            inner: GaugeInner::new(value),
+        }
+    }
+
+    pub fn value(self, value: f32) -> Self {
+        self.inner.borrow_mut().set_value(value);
+        self
+    }
+
+    pub fn get_value(&self) -> f32 {
+        self.inner.borrow().get_value()
+    }
+
+    /// Set the values of an empty and a full arc. Defaults to 0 and 100.
+    pub fn range(self, min: f32, max: f32) -> Self {
+        self.inner.borrow_mut().set_range(min, max);
+        self
+    }
+
+    pub fn get_range(&self) -> (f32, f32) {
+        self.inner.borrow().get_range()
+    }
+
+    /// Set the width and height of the gauge. Defaults to 120.
+    pub fn size(self, size: f32) -> Self {
+        self.inner.borrow_mut().set_size(size);
+        self
+    }
+
+    /// Set the width of the arc. Defaults to 12.
+    pub fn thickness(self, thickness: f32) -> Self {
+        self.inner.borrow_mut().thickness = thickness;
+        self
+    }
+
+    /// Set how far around the arc goes, in degrees. Defaults to 270.
+    pub fn sweep_angle(self, degrees: f32) -> Self {
+        self.inner.borrow_mut().sweep_angle = degrees.clamp(0.0, 360.0);
+        self
+    }
+
+    /// Set the number of decimals of the value in the middle. Defaults to 0.
+    pub fn decimals(self, decimals: usize) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.decimals = decimals;
+        inner.update_label();
+        drop(inner);
+        self
+    }
+
+    /// Set the text written after the value, such as `%`.
+    pub fn suffix(self, suffix: &str) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.suffix = suffix.to_string();
+        inner.update_label();
+        drop(inner);
+        self
+    }
+
+    pub fn track_color(self, color: Color) -> Self {
+        self.inner.borrow_mut().track_color = color;
+        self
+    }
+
+    pub fn value_color(self, color: Color) -> Self {
+        self.inner.borrow_mut().value_color = color;
+        self
+    }
+
+    /// Set how long the arc takes to reach a new value. Defaults to zero, which shows new values at once.
+    pub fn animation_duration(self, duration: Duration) -> Self {
+        self.inner.borrow_mut().animation.set_duration(duration);
+        self
+    }
+
+    pub fn get_animation_duration(&self) -> Duration {
+        self.inner.borrow().animation.get_duration()
+    }
+
+    /// The gauge with its current value as an SVG document of its laid-out size, without borders or background. See
+    /// [`crate::export`].
+    pub fn to_svg(&self) -> String {
+        self.inner.borrow().to_svg()
+    }
+
+    /// Draws the gauge to an image after the next frame, at its laid-out size times `scale`, and sends it to the gauge
+    /// in a [`EventKind::ElementCaptured`] event. See [`crate::export`].
+    pub fn to_png(&self, scale: f64) {
+        self.capture(scale);
+    }
+}
+
+impl GaugeInner {
+    pub fn new(value: f32) -> Rc<RefCell<Self>> {
+        let me = Rc::new_cyclic(|me: &Weak<RefCell<Self>>| {
+            RefCell::new(Self {
+                element_data: ElementData::new(me.clone(), false),
+                value: 0.0,
+                min: 0.0,
+                max: 100.0,
+                animation: ValueAnimation::default(),
+                thickness: 12.0,
+                sweep_angle: 270.0,
+                decimals: 0,
+                suffix: String::new(),
+                track_color: rgb(229, 231, 235),
+                value_color: rgb(37, 99, 235),
+                label: Text::new("").selectable(false).font_size(22.0),
+            })
+        });
+
+        let mut inner_mut = me.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+        inner_mut.set_display(Display::Flex);
+        inner_mut.set_justify_content(Some(JustifyContent::Center));
+        inner_mut.set_align_items(Some(AlignItems::Center));
+        let label = inner_mut.label.clone();
+        inner_mut.push(label.inner);
+        inner_mut.set_size(120.0);
+        inner_mut.set_value(value);
+        drop(inner_mut);
+
+        me
+    }
+
+    /// Set the value, clamped to the range.
+    pub fn set_value(&mut self, value: f32) {
+        self.value = value.clamp(self.min, self.max);
+        self.animation.set_values(vec![self.value]);
+        self.update_label();
+        self.request_window_redraw();
+    }
+
+    pub fn get_value(&self) -> f32 {
+        self.value
+    }
+
+    pub fn set_range(&mut self, min: f32, max: f32) {
+        self.min = min.min(max);
+        self.max = max.max(min);
+        self.set_value(self.value);
+    }
+
+    pub fn get_range(&self) -> (f32, f32) {
+        (self.min, self.max)
+    }
+
+    pub fn set_size(&mut self, size: f32) {
+        self.set_width(Unit::Px(size));
+        self.set_height(Unit::Px(size));
+    }
+
+    fn to_svg(&self) -> String {
+        let computed_box = &self.element_data.layout.computed_box;
+        let border_rectangle = computed_box.border_rectangle();
+        let mut rectangle = computed_box.content_rectangle();
+        rectangle.x -= border_rectangle.x;
+        rectangle.y -= border_rectangle.y;
+
+        let mut document = SvgDocument::new(border_rectangle.width, border_rectangle.height);
+        for (path, color) in self.arcs(self.value, rectangle, 1.0) {
+            document.fill_path(&path, color);
+        }
+        let center = (
+            (rectangle.x + rectangle.width / 2.0) as f64,
+            (rectangle.y + rectangle.height / 2.0) as f64,
+        );
+        let label = self.label.borrow();
+        document.text(
+            center,
+            &self.label.get_text(),
+            label.style().get_font_size(),
+            label.style().get_color(),
+        );
+        drop(label);
+        document.finish()
+    }
+
+    /// The track and the filled part of the arc in the rectangle, with their colors.
+    fn arcs(&self, value: f32, rectangle: Rectangle, scale_factor: f64) -> Vec<(BezPath, Color)> {
+        let thickness = self.thickness as f64 * scale_factor;
+        let sweep_angle = self.sweep_angle as f64;
+        let mut arcs = vec![(arc_path(rectangle, thickness, sweep_angle, 1.0), self.track_color)];
+
+        let fraction = gauge_fraction(value, self.min, self.max) as f64;
+        if fraction > 0.0 {
+            arcs.push((arc_path(rectangle, thickness, sweep_angle, fraction), self.value_color));
+        }
+        arcs
+    }
+
+    fn update_label(&self) {
+        let text = format!("{:.*}{}", self.decimals, self.value, self.suffix);
+        self.label.clone().text(&text);
+    }
+}
+
+/// How much of the arc a value fills, from 0 to 1.
+fn gauge_fraction(value: f32, min: f32, max: f32) -> f32 {
+    if max <= min {
+        return 0.0;
+    }
+    ((value - min) / (max - min)).clamp(0.0, 1.0)
+}
+
+/// The outline of an arc of `thickness` with rounded ends, centered on the circle fitting in the rectangle.
+///
+/// The full arc is open at the bottom and spans `sweep_angle` degrees, of which the first `fraction` is returned.
+fn arc_path(rectangle: Rectangle, thickness: f64, sweep_angle: f64, fraction: f64) -> BezPath {
+    let center = (
+        rectangle.x as f64 + rectangle.width as f64 / 2.0,
+        rectangle.y as f64 + rectangle.height as f64 / 2.0,
+    );
+    let radius = (rectangle.width.min(rectangle.height) as f64 - thickness).max(0.0) / 2.0;
+    let sweep = sweep_angle.to_radians();
+    // Angles grow clockwise from the right, so the arc is centered on -90°, the top.
+    let start = -PI / 2.0 - sweep / 2.0;
+
+    let arc = kurbo::Arc::new(center, (radius, radius), start, sweep * fraction, 0.0);
+    let style = kurbo::Stroke::new(thickness).with_caps(kurbo::Cap::Round);
+    kurbo::stroke(
+        arc.path_elements(TOLERANCE),
+        &style,
+        &kurbo::StrokeOpts::default(),
+        TOLERANCE,
+    )
+}
+
+impl Element for Gauge {}
+
+impl Drop for GaugeInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for Gauge {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for GaugeInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for GaugeInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        if !self.is_visible() {
+            return;
+        }
+
+        self.add_hit_testable(renderer, true, scale_factor);
+        self.draw_borders(renderer, scale_factor);
+        self.maybe_start_layer(renderer, scale_factor);
+
+        let now = Instant::now();
+        let value = self.animation.values_at(now).first().copied().unwrap_or(self.value);
+        if self.animation.is_running(now) {
+            request_animation_frame();
+        }
+
+        let content_rectangle = self.element_data.layout.computed_box_transformed.content_rectangle();
+        let rectangle = content_rectangle.scale(scale_factor);
+        for (path, color) in self.arcs(value, rectangle, scale_factor) {
+            renderer.fill_bez_path(path, Brush::Color(color));
+        }
+
+        self.draw_children(renderer, resource_manager, scale_factor, text_context);
+        self.maybe_end_layer(renderer);
+    }
+
+    #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+    fn compute_accessibility_tree(&mut self, tree: &mut TreeUpdate, parent_index: Option<usize>, scale_factor: f64) {
+        let current_node_id = accesskit::NodeId(self.element_data().internal_id);
+        let mut current_node = accesskit::Node::new(Role::Meter);
+        current_node.set_numeric_value(self.value as f64);
+        current_node.set_min_numeric_value(self.min as f64);
+        current_node.set_max_numeric_value(self.max as f64);
+
+        crate::elements::internal_helpers::add_generic_accesskit_data(
+            &mut self.element_data,
+            current_node,
+            current_node_id,
+            tree,
+            parent_index,
+            scale_factor,
+        )
+    }
+
+    fn on_event(
+        &mut self,
+        _message: &EventKind,
+        _text_context: &mut TextContext,
+        _event: &mut Event,
+        _target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::gauge_fraction;
+
+    #[test]
+    fn fraction_is_relative_to_the_range() {
+        assert_eq!(gauge_fraction(50.0, 0.0, 100.0), 0.5);
+        assert_eq!(gauge_fraction(15.0, 10.0, 20.0), 0.5);
+        assert_eq!(gauge_fraction(30.0, 10.0, 20.0), 1.0);
+        assert_eq!(gauge_fraction(5.0, 10.0, 20.0), 0.0);
+        assert_eq!(gauge_fraction(5.0, 10.0, 10.0), 0.0);
+    }
+}
@@ -0,0 +1,118 @@
+//! Small elements for showing numbers at a glance: [`Gauge`], [`Sparkline`] and [`StatCard`].
+
+mod chart_group;
+mod gauge;
+mod sparkline;
+mod stat_card;
+mod viewport;
+
+pub use chart_group::ChartGroup;
+pub use gauge::{Gauge, GaugeInner};
+pub use sparkline::{Sparkline, SparklineInner};
+pub use stat_card::{StatCard, StatCardInner};
+pub use viewport::ChartViewport;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time;
+#[cfg(target_arch = "wasm32")]
+use web_time as time;
+
+use time::{Duration, Instant};
+
+/// Moves drawn values from their old to their new values when they change.
+///
+/// With a duration of zero, or when the number of values changes, the new values are drawn at once.
+#[derive(Clone, Default)]
+pub(crate) struct ValueAnimation {
+    duration: Duration,
+    from: Vec<f32>,
+    to: Vec<f32>,
+    start: Option<Instant>,
+}
+
+impl ValueAnimation {
+    pub(crate) fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+    }
+
+    pub(crate) fn get_duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// Animates from the values drawn now to `values`.
+    pub(crate) fn set_values(&mut self, values: Vec<f32>) {
+        let now = Instant::now();
+        self.from = self.values_at(now);
+        self.start = (!self.duration.is_zero() && self.from.len() == values.len()).then_some(now);
+        self.to = values;
+    }
+
+    /// The values once the animation has finished.
+    pub(crate) fn get_values(&self) -> &[f32] {
+        &self.to
+    }
+
+    pub(crate) fn values_at(&self, now: Instant) -> Vec<f32> {
+        let Some(start) = self.start else {
+            return self.to.clone();
+        };
+        let progress = ease_out_cubic(progress(now.saturating_duration_since(start), self.duration));
+        self.from.iter().zip(&self.to).map(|(from, to)| from + (to - from) * progress).collect()
+    }
+
+    pub(crate) fn is_running(&self, now: Instant) -> bool {
+        self.start.is_some_and(|start| now.saturating_duration_since(start) < self.duration)
+    }
+}
+
+/// How far through an animation of `duration` it is after `elapsed`, from 0 to 1.
+fn progress(elapsed: Duration, duration: Duration) -> f32 {
+    if duration.is_zero() {
+        return 1.0;
+    }
+    (elapsed.as_secs_f32() / duration.as_secs_f32()).min(1.0)
+}
+
+/// Starts quickly and slows down towards the end.
+fn ease_out_cubic(t: f32) -> f32 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn easing_starts_and_ends_at_the_bounds() {
+        assert_eq!(ease_out_cubic(0.0), 0.0);
+        assert_eq!(ease_out_cubic(1.0), 1.0);
+        assert!(ease_out_cubic(0.5) > 0.5);
+    }
+
+    #[test]
+    fn progress_is_clamped() {
+        assert_eq!(progress(Duration::from_millis(100), Duration::from_millis(400)), 0.25);
+        assert_eq!(progress(Duration::from_millis(800), Duration::from_millis(400)), 1.0);
+        assert_eq!(progress(Duration::from_millis(100), Duration::ZERO), 1.0);
+    }
+
+    #[test]
+    fn animates_between_values_of_the_same_length() {
+        let mut animation = ValueAnimation::default();
+        animation.set_duration(Duration::from_millis(400));
+        animation.set_values(vec![0.0, 10.0]);
+        // The first values have nothing to animate from.
+        assert!(animation.start.is_none());
+
+        animation.set_values(vec![10.0, 0.0]);
+        let start = animation.start.unwrap();
+        assert_eq!(animation.values_at(start), vec![0.0, 10.0]);
+        assert_eq!(animation.values_at(start + Duration::from_millis(400)), vec![10.0, 0.0]);
+        assert!(animation.is_running(start + Duration::from_millis(200)));
+        assert!(!animation.is_running(start + Duration::from_millis(400)));
+
+        animation.set_values(vec![1.0, 2.0, 3.0]);
+        assert!(animation.start.is_none());
+        assert_eq!(animation.get_values(), &[1.0, 2.0, 3.0]);
+    }
+}
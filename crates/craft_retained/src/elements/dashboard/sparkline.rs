@@ -0,0 +1,815 @@
+//! A small line chart without axes, which can be zoomed and panned, and grouped with other charts.
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use craft_primitives::geometry::{Affine, BezPath, Point, Rectangle, Shape, TOLERANCE};
+use craft_renderer::Brush;
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use peniko::Color;
+use peniko::kurbo;
+use ui_events::ScrollDelta;
+use ui_events::pointer::PointerButton;
+
+use super::chart_group::ChartGroupMessage;
+use super::time::{Duration, Instant};
+use super::{ChartGroup, ChartViewport, ValueAnimation};
+use crate::app::{queue_event, request_animation_frame};
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, push_child_to_element};
+use crate::elements::traits::DeepClone;
+use crate::elements::{AsElement, Element, ElementInternals, Text};
+use crate::events::{Event, EventKind};
+use crate::export::SvgDocument;
+use crate::layout::TaffyTree;
+use crate::style::{Display, Position, Unit};
+use crate::text::text_context::TextContext;
+use crate::{auto, pct, px, rgb, rgba};
+
+/// How much one line of the mouse wheel zooms.
+const WHEEL_ZOOM_STEP: f64 = 1.2;
+
+/// How many pixels of a precise scroll count as one line of the mouse wheel.
+const PIXELS_PER_LINE: f64 = 40.0;
+
+/// The longest time between the clicks of a double click.
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(300);
+
+/// The fewest values between the ends of a zoomed viewport.
+const MIN_VIEWPORT_WIDTH: f64 = 1.0;
+
+/// How far the pointer has to be dragged, in logical pixels, to zoom to the dragged box.
+const MIN_BOX_WIDTH: f64 = 3.0;
+
+/// The size of the reset button, in logical pixels.
+const RESET_BUTTON_SIZE: f32 = 16.0;
+
+const BOX_ZOOM_COLOR: Color = rgba(37, 99, 235, 40);
+const RESET_BUTTON_BACKGROUND_COLOR: Color = rgba(243, 244, 246, 230);
+const RESET_BUTTON_ICON_COLOR: Color = rgb(75, 85, 99);
+const CROSSHAIR_COLOR: Color = rgba(0, 0, 0, 90);
+const TOOLTIP_BORDER_COLOR: Color = rgba(0, 0, 0, 40);
+
+/// What dragging over an interactive sparkline does.
+#[derive(Clone, Copy)]
+enum ChartDrag {
+    /// Moves the viewport, from the last pointer x.
+    Pan(f64),
+    /// Selects the range between two pointer x positions to zoom to.
+    Box { start: f64, current: f64 },
+}
+
+#[derive(Clone)]
+pub struct Sparkline {
+    pub inner: Rc<RefCell<SparklineInner>>,
+}
+
+/// A line through a series of values, scaled to fill the element, for showing a trend next to text.
+///
+/// The values are spread evenly from left to right, and the smallest and largest values touch the bottom and top.
+/// When an animation duration is set, the line moves to new values of the same length.
+///
+/// An interactive sparkline is zoomed with the mouse wheel, panned by dragging, and zoomed to a range by dragging
+/// with Shift held. The y-axis is rescaled to the values in view. Double clicking, or the reset button shown while
+/// zoomed, shows all values again. Each change the user makes is sent in a [`EventKind::ViewportChanged`] event.
+///
+/// Interactive sparklines and sparklines in a [`ChartGroup`] show a crosshair and a tooltip with the value under the
+/// pointer, on every chart of the group at once.
+#[derive(Clone)]
+pub struct SparklineInner {
+    element_data: ElementData,
+    animation: ValueAnimation,
+    line_width: f32,
+    line_color: Color,
+    fill: bool,
+    show_last_point: bool,
+    interactive: bool,
+    /// The zoomed part of the values, or `None` when all values are shown.
+    viewport: Option<ChartViewport>,
+    drag: Option<ChartDrag>,
+    last_click: Option<Instant>,
+    group: Option<ChartGroup>,
+    /// The index of the value under the crosshair.
+    hover: Option<usize>,
+    pub tooltip: Text,
+}
+
+impl Sparkline {
+    pub fn new(values: &[f32]) -> Self {
+        Self {
+            inner: SparklineInner::new(values),
+        }
+    }
+
+    pub fn values(self, values: &[f32]) -> Self {
+        self.inner.borrow_mut().set_values(values);
+        self
+    }
+
+    pub fn get_values(&self) -> Vec<f32> {
+        self.inner.borrow().get_values().to_vec()
+    }
+
+    /// Set the width of the line. Defaults to 1.5.
+    pub fn line_width(self, line_width: f32) -> Self {
+        self.inner.borrow_mut().line_width = line_width;
+        self
+    }
+
+    pub fn line_color(self, color: Color) -> Self {
+        self.inner.borrow_mut().line_color = color;
+        self
+    }
+
+    /// Shade the area under the line with a faint line color. Defaults to true.
+    pub fn fill(self, fill: bool) -> Self {
+        self.inner.borrow_mut().fill = fill;
+        self
+    }
+
+    /// Mark the last value with a dot. Defaults to true.
+    pub fn show_last_point(self, show_last_point: bool) -> Self {
+        self.inner.borrow_mut().show_last_point = show_last_point;
+        self
+    }
+
+    /// Set how long the line takes to reach new values. Defaults to zero, which shows new values at once.
+    pub fn animation_duration(self, duration: Duration) -> Self {
+        self.inner.borrow_mut().animation.set_duration(duration);
+        self
+    }
+
+    pub fn get_animation_duration(&self) -> Duration {
+        self.inner.borrow().animation.get_duration()
+    }
+
+    /// Let the user zoom and pan the sparkline with the pointer. Defaults to false.
+    pub fn interactive(self, interactive: bool) -> Self {
+        self.inner.borrow_mut().interactive = interactive;
+        self
+    }
+
+    /// Show part of the values, or all of them with `None`.
+    pub fn viewport(self, viewport: Option<ChartViewport>) -> Self {
+        self.inner.borrow_mut().set_viewport(viewport);
+        self
+    }
+
+    pub fn get_viewport(&self) -> ChartViewport {
+        self.inner.borrow().get_viewport()
+    }
+
+    /// Show all values again.
+    pub fn reset_zoom(&self) {
+        self.inner.borrow_mut().set_viewport(None);
+    }
+
+    /// Show the crosshair and tooltip of the other charts in `group`, and show this sparkline's to them.
+    pub fn group(self, group: &ChartGroup) -> Self {
+        group.add(Rc::downgrade(&self.inner));
+        self.inner.borrow_mut().group = Some(group.clone());
+        self
+    }
+
+    /// The index of the value under the crosshair, if the pointer is over this sparkline or another of its group.
+    pub fn get_hovered_index(&self) -> Option<usize> {
+        self.inner.borrow().hover
+    }
+
+    /// The sparkline with its current values as an SVG document of its laid-out size, without borders or background.
+    /// See [`crate::export`].
+    pub fn to_svg(&self) -> String {
+        self.inner.borrow().to_svg()
+    }
+
+    /// Draws the sparkline to an image after the next frame, at its laid-out size times `scale`, and sends it to the
+    /// sparkline in a [`EventKind::ElementCaptured`] event. See [`crate::export`].
+    pub fn to_png(&self, scale: f64) {
+        self.capture(scale);
+    }
+}
+
+impl SparklineInner {
+    pub fn new(values: &[f32]) -> Rc<RefCell<Self>> {
+        let me = Rc::new_cyclic(|me: &Weak<RefCell<Self>>| {
+            RefCell::new(Self {
+                element_data: ElementData::new(me.clone(), false),
+                animation: ValueAnimation::default(),
+                line_width: 1.5,
+                line_color: rgb(37, 99, 235),
+                fill: true,
+                show_last_point: true,
+                interactive: false,
+                viewport: None,
+                drag: None,
+                last_click: None,
+                group: None,
+                hover: None,
+                tooltip: Text::new("")
+                    .selectable(false)
+                    .font_size(12.0)
+                    .display(Display::None)
+                    .position(Position::Absolute)
+                    .padding(px(2), px(6), px(2), px(6))
+                    .margin(px(0), px(0), px(4), px(0))
+                    .background_color(Color::WHITE)
+                    .border_width_all(px(1))
+                    .border_color_all(TOOLTIP_BORDER_COLOR)
+                    .border_radius_all((4.0, 4.0))
+                    .elevation(4.0),
+            })
+        });
+
+        let mut inner_mut = me.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+        inner_mut.set_width(Unit::Px(100.0));
+        inner_mut.set_height(Unit::Px(24.0));
+        let tooltip = inner_mut.tooltip.clone();
+        inner_mut.push(tooltip.inner);
+        inner_mut.set_values(values);
+        drop(inner_mut);
+
+        me
+    }
+
+    /// Set the values, ignoring values that aren't finite.
+    pub fn set_values(&mut self, values: &[f32]) {
+        let values = values.iter().copied().filter(|value| value.is_finite()).collect();
+        self.animation.set_values(values);
+        // Keeps the zoom, moving it back inside the values if there are fewer of them.
+        self.set_viewport(self.viewport);
+        self.update_tooltip();
+    }
+
+    pub fn get_values(&self) -> &[f32] {
+        self.animation.get_values()
+    }
+
+    /// Show part of the values, or all of them with `None`. The viewport is moved inside the values.
+    pub fn set_viewport(&mut self, viewport: Option<ChartViewport>) {
+        let limits = ChartViewport::full(self.get_values().len());
+        self.viewport = viewport
+            .map(|viewport| viewport.clamp(limits))
+            .filter(|viewport| *viewport != limits);
+        self.update_tooltip();
+        self.request_window_redraw();
+    }
+
+    /// The part of the values shown.
+    pub fn get_viewport(&self) -> ChartViewport {
+        self.viewport
+            .unwrap_or_else(|| ChartViewport::full(self.get_values().len()))
+    }
+
+    /// Sets the viewport and sends a [`EventKind::ViewportChanged`] event, if it changed.
+    fn change_viewport(&mut self, viewport: Option<ChartViewport>) {
+        let old_viewport = self.get_viewport();
+        self.set_viewport(viewport);
+        let new_viewport = self.get_viewport();
+        if new_viewport != old_viewport {
+            let new_event = Event::new(self.element_data.me.upgrade().unwrap());
+            queue_event(new_event, EventKind::ViewportChanged(new_viewport));
+        }
+    }
+
+    /// Zooms by `factor`, keeping the value under the pointer x in place.
+    fn zoom_at(&mut self, factor: f64, x: f64) {
+        let limits = ChartViewport::full(self.get_values().len());
+        let anchor = self.index_at(x);
+        let viewport = self.get_viewport().zoom_at(factor, anchor, limits, MIN_VIEWPORT_WIDTH);
+        self.change_viewport(Some(viewport));
+    }
+
+    /// Moves the crosshair to a value, or hides it with `None`.
+    fn set_hover(&mut self, hover: Option<usize>) {
+        if self.hover != hover {
+            self.hover = hover;
+            self.update_tooltip();
+            self.request_window_redraw();
+        }
+    }
+
+    /// Moves the crosshair to the value under the pointer, and tells the other charts of the group.
+    fn hover_at(&mut self, x: Option<f64>) {
+        let count = self.get_values().len();
+        let hover = x
+            .filter(|_| count > 0)
+            .map(|x| (self.index_at(x).round() as usize).min(count - 1));
+        if hover != self.hover {
+            self.set_hover(hover);
+            if let Some(group) = &self.group {
+                group.send(self, ChartGroupMessage::Hover(hover));
+            }
+        }
+    }
+
+    /// Shows the hovered value in the tooltip above the crosshair, or hides it if no value in view is hovered.
+    fn update_tooltip(&self) {
+        let viewport = self.get_viewport();
+        let hovered = self
+            .hover
+            .and_then(|index| Some((index, *self.get_values().get(index)?)))
+            .filter(|(index, _)| (0.0..=1.0).contains(&viewport.fraction(*index as f64)));
+        let Some((index, value)) = hovered else {
+            self.tooltip.clone().display(Display::None);
+            return;
+        };
+
+        let padding_rectangle = self.element_data.layout.computed_box.padding_rectangle();
+        let plot_rectangle = inset_rectangle(
+            self.element_data.layout.computed_box.content_rectangle(),
+            self.line_width / 2.0,
+        );
+        let x = plot_rectangle.x - padding_rectangle.x + plot_rectangle.width * viewport.fraction(index as f64) as f32;
+        self.tooltip
+            .clone()
+            .text(&format_value(value))
+            .display(Display::Block)
+            .inset(auto(), auto(), pct(100), px(x));
+    }
+
+    /// The logical rectangle the line is drawn in.
+    fn plot_rectangle(&self) -> Rectangle {
+        let rectangle = self.element_data.layout.computed_box_transformed.content_rectangle();
+        inset_rectangle(rectangle, self.line_width / 2.0)
+    }
+
+    /// The value index under a logical pointer x, which may be between two values.
+    fn index_at(&self, x: f64) -> f64 {
+        let rectangle = self.plot_rectangle();
+        let fraction = if rectangle.width > 0.0 {
+            ((x - rectangle.x as f64) / rectangle.width as f64).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        self.get_viewport().index(fraction)
+    }
+
+    /// The logical rectangle of the reset button, in the top right corner while the sparkline is zoomed.
+    fn reset_button(&self) -> Option<Rectangle> {
+        if !self.interactive || self.viewport.is_none() {
+            return None;
+        }
+        let rectangle = self.element_data.layout.computed_box_transformed.content_rectangle();
+        let size = RESET_BUTTON_SIZE.min(rectangle.width).min(rectangle.height);
+        Some(Rectangle::new(rectangle.right() - size, rectangle.y, size, size))
+    }
+
+    fn to_svg(&self) -> String {
+        let computed_box = &self.element_data.layout.computed_box;
+        let border_rectangle = computed_box.border_rectangle();
+        let mut rectangle = computed_box.content_rectangle();
+        rectangle.x -= border_rectangle.x;
+        rectangle.y -= border_rectangle.y;
+
+        let mut document = SvgDocument::new(border_rectangle.width, border_rectangle.height);
+        for (path, color) in self.paths(self.get_values(), rectangle, 1.0) {
+            document.fill_path(&path, color);
+        }
+        document.finish()
+    }
+
+    /// The crosshair at the hovered value in the rectangle: a vertical line and a dot on the value.
+    fn crosshair_paths(&self, values: &[f32], mut rectangle: Rectangle, scale_factor: f64) -> Vec<(BezPath, Color)> {
+        let viewport = self.get_viewport();
+        let range = visible_range(values.len(), viewport);
+        let Some(index) = self.hover.filter(|index| !values.is_empty() && range.contains(index)) else {
+            return Vec::new();
+        };
+
+        let line_width = self.line_width as f64 * scale_factor;
+        rectangle = inset_rectangle(rectangle, (line_width / 2.0) as f32);
+        let (x, y) = sparkline_points(values, viewport, rectangle)[index - range.start()];
+
+        let crosshair_width = scale_factor;
+        let line = kurbo::Rect::new(
+            x - crosshair_width / 2.0,
+            rectangle.y as f64,
+            x + crosshair_width / 2.0,
+            rectangle.bottom() as f64,
+        );
+        let dot = kurbo::Circle::new((x, y), line_width * 1.5);
+        vec![
+            (line.to_path(TOLERANCE), CROSSHAIR_COLOR),
+            (dot.to_path(TOLERANCE), self.line_color),
+        ]
+    }
+
+    /// The shapes of the sparkline in the rectangle, with their colors: the shaded area, the line and the dot.
+    fn paths(&self, values: &[f32], mut rectangle: Rectangle, scale_factor: f64) -> Vec<(BezPath, Color)> {
+        if values.is_empty() {
+            return Vec::new();
+        }
+
+        // Inset by half the line, so the line isn't cut off at the edges.
+        let line_width = self.line_width as f64 * scale_factor;
+        rectangle = inset_rectangle(rectangle, (line_width / 2.0) as f32);
+        let viewport = self.get_viewport();
+        let points = sparkline_points(values, viewport, rectangle);
+
+        let mut line = BezPath::new();
+        line.move_to(points[0]);
+        for point in &points[1..] {
+            line.line_to(*point);
+        }
+
+        let mut paths = Vec::new();
+        if self.fill && points.len() > 1 {
+            let bottom = (rectangle.y + rectangle.height) as f64;
+            let mut area = line.clone();
+            area.line_to((points[points.len() - 1].0, bottom));
+            area.line_to((points[0].0, bottom));
+            area.close_path();
+            paths.push((area, self.line_color.multiply_alpha(0.15)));
+        }
+
+        let style = kurbo::Stroke::new(line_width)
+            .with_caps(kurbo::Cap::Round)
+            .with_join(kurbo::Join::Round);
+        let outline = kurbo::stroke(line, &style, &kurbo::StrokeOpts::default(), TOLERANCE);
+        paths.push((outline, self.line_color));
+
+        if self.show_last_point && *visible_range(values.len(), viewport).end() == values.len() - 1 {
+            let (x, y) = points[points.len() - 1];
+            let dot = kurbo::Circle::new((x, y), line_width * 1.5);
+            paths.push((dot.to_path(TOLERANCE), self.line_color));
+        }
+        paths
+    }
+}
+
+fn inset_rectangle(mut rectangle: Rectangle, inset: f32) -> Rectangle {
+    rectangle.x += inset;
+    rectangle.y += inset;
+    rectangle.width = (rectangle.width - inset * 2.0).max(0.0);
+    rectangle.height = (rectangle.height - inset * 2.0).max(0.0);
+    rectangle
+}
+
+/// The indices of the values drawn in a viewport: the ones inside it, and the nearest one outside each end so the
+/// line reaches the edges.
+fn visible_range(count: usize, viewport: ChartViewport) -> std::ops::RangeInclusive<usize> {
+    let last = count.saturating_sub(1);
+    let first = (viewport.start.floor().max(0.0) as usize).min(last);
+    first..=(viewport.end.ceil().max(0.0) as usize).clamp(first, last)
+}
+
+/// Where the values in the viewport are drawn in the rectangle, from left to right. The smallest and largest of them
+/// touch the bottom and top.
+fn sparkline_points(values: &[f32], viewport: ChartViewport, rectangle: Rectangle) -> Vec<(f64, f64)> {
+    let range = visible_range(values.len(), viewport);
+    let first = *range.start();
+    let visible = &values[range];
+    let min = visible.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = visible.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+    visible
+        .iter()
+        .enumerate()
+        .map(|(offset, value)| {
+            let fraction = viewport.fraction((first + offset) as f64);
+            let x = rectangle.x as f64 + rectangle.width as f64 * fraction;
+            // A flat line is drawn through the middle.
+            let height = if max > min { (value - min) / (max - min) } else { 0.5 };
+            let y = rectangle.y + rectangle.height * (1.0 - height);
+            (x, y as f64)
+        })
+        .collect()
+}
+
+/// A value with at most two decimals, without trailing zeros.
+fn format_value(value: f32) -> String {
+    let text = format!("{value:.2}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/// The reset button in a physical rectangle: a background and a circular arrow.
+fn reset_button_paths(rectangle: Rectangle, scale_factor: f64) -> Vec<(BezPath, Color)> {
+    let bounds = rectangle.to_kurbo();
+    let background = kurbo::RoundedRect::from_rect(bounds, 3.0 * scale_factor);
+    let center = bounds.center();
+    let radius = bounds.width() * 0.25;
+
+    // Clockwise from the top to the left, where the arrow head points up.
+    let arc = kurbo::Arc::new(
+        center,
+        (radius, radius),
+        -std::f64::consts::FRAC_PI_2,
+        1.5 * std::f64::consts::PI,
+        0.0,
+    );
+    let style = kurbo::Stroke::new(1.5 * scale_factor);
+    let arc = kurbo::stroke(
+        arc.path_elements(TOLERANCE),
+        &style,
+        &kurbo::StrokeOpts::default(),
+        TOLERANCE,
+    );
+
+    let head = 2.5 * scale_factor;
+    let mut arrow_head = BezPath::new();
+    arrow_head.move_to((center.x - radius - head, center.y));
+    arrow_head.line_to((center.x - radius + head, center.y));
+    arrow_head.line_to((center.x - radius, center.y - head * 1.2));
+    arrow_head.close_path();
+
+    vec![
+        (background.to_path(TOLERANCE), RESET_BUTTON_BACKGROUND_COLOR),
+        (arc, RESET_BUTTON_ICON_COLOR),
+        (arrow_head, RESET_BUTTON_ICON_COLOR),
+    ]
+}
+
+impl Element for Sparkline {}
+
+impl Drop for SparklineInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for Sparkline {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for SparklineInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for SparklineInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        if !self.is_visible() {
+            return;
+        }
+
+        self.add_hit_testable(renderer, true, scale_factor);
+        self.draw_borders(renderer, scale_factor);
+
+        let now = Instant::now();
+        let values = self.animation.values_at(now);
+        if self.animation.is_running(now) {
+            request_animation_frame();
+        }
+
+        let rectangle = self
+            .element_data
+            .layout
+            .computed_box_transformed
+            .content_rectangle()
+            .scale(scale_factor);
+        // A zoomed line goes on past the edges.
+        if self.viewport.is_some() {
+            renderer.push_layer(rectangle);
+        }
+        let mut paths = self.paths(&values, rectangle, scale_factor);
+        paths.extend(self.crosshair_paths(&values, rectangle, scale_factor));
+        for (path, color) in paths {
+            renderer.fill_bez_path(path, Brush::Color(color));
+        }
+        if self.viewport.is_some() {
+            renderer.pop_layer();
+        }
+
+        if let Some(ChartDrag::Box { start, current }) = self.drag {
+            let left = start.min(current).max(rectangle.x as f64 / scale_factor);
+            let right = start.max(current).min(rectangle.right() as f64 / scale_factor);
+            let selection = Rectangle::new(
+                (left * scale_factor) as f32,
+                rectangle.y,
+                ((right - left).max(0.0) * scale_factor) as f32,
+                rectangle.height,
+            );
+            renderer.draw_rect(selection, BOX_ZOOM_COLOR);
+        }
+
+        if let Some(reset_button) = self.reset_button() {
+            for (path, color) in reset_button_paths(reset_button.scale(scale_factor), scale_factor) {
+                renderer.fill_bez_path(path, Brush::Color(color));
+            }
+        }
+
+        self.draw_children(renderer, resource_manager, scale_factor, text_context);
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        _target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        if let EventKind::ElementMessage(message) = message
+            && let Some(ChartGroupMessage::Hover(hover)) = message.as_any().downcast_ref::<ChartGroupMessage>()
+        {
+            self.set_hover(*hover);
+            return;
+        }
+
+        let shows_crosshair = self.interactive || self.group.is_some();
+        match message {
+            EventKind::PointerScroll(scroll) if self.interactive => {
+                let lines = match scroll.delta {
+                    ScrollDelta::LineDelta(_x, y) => y as f64,
+                    ScrollDelta::PixelDelta(physical) => physical.y / PIXELS_PER_LINE,
+                    ScrollDelta::PageDelta(_x, y) => y as f64,
+                };
+                self.zoom_at(WHEEL_ZOOM_STEP.powf(lines), scroll.state.logical_point().x);
+                event.prevent_propagate();
+                event.prevent_defaults();
+            }
+            EventKind::PointerButtonDown(pointer_button)
+                if self.interactive && pointer_button.button == Some(PointerButton::Primary) =>
+            {
+                let pointer_position = pointer_button.state.logical_point();
+                if self
+                    .reset_button()
+                    .is_some_and(|button| button.contains(&pointer_position))
+                {
+                    self.change_viewport(None);
+                    return;
+                }
+
+                let now = Instant::now();
+                if self
+                    .last_click
+                    .take()
+                    .is_some_and(|last_click| now.duration_since(last_click) < DOUBLE_CLICK_TIME)
+                {
+                    self.change_viewport(None);
+                    return;
+                }
+                self.last_click = Some(now);
+
+                let x = pointer_position.x;
+                self.drag = Some(if pointer_button.state.modifiers.shift() {
+                    ChartDrag::Box {
+                        start: x,
+                        current: x,
+                    }
+                } else {
+                    ChartDrag::Pan(x)
+                });
+                self.capture_pointer();
+            }
+            EventKind::PointerMovedEvent(pointer_update) => {
+                let x = pointer_update.current.logical_point().x;
+                match self.drag {
+                    Some(ChartDrag::Pan(last_x)) => {
+                        self.drag = Some(ChartDrag::Pan(x));
+                        let plot_width = self.plot_rectangle().width as f64;
+                        if plot_width > 0.0 {
+                            let viewport = self.get_viewport();
+                            let limits = ChartViewport::full(self.get_values().len());
+                            let delta = (last_x - x) / plot_width * viewport.width();
+                            self.change_viewport(Some(viewport.pan(delta, limits)));
+                        }
+                    }
+                    Some(ChartDrag::Box { start, .. }) => {
+                        self.drag = Some(ChartDrag::Box { start, current: x });
+                        self.request_window_redraw();
+                    }
+                    None if shows_crosshair => self.hover_at(Some(x)),
+                    None => {}
+                }
+            }
+            EventKind::PointerLeave() if shows_crosshair => {
+                self.hover_at(None);
+            }
+            EventKind::PointerButtonUp(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                let Some(drag) = self.drag.take() else {
+                    return;
+                };
+                self.release_pointer();
+                if let ChartDrag::Box { start, current } = drag {
+                    self.request_window_redraw();
+                    if (current - start).abs() >= MIN_BOX_WIDTH {
+                        let limits = ChartViewport::full(self.get_values().len());
+                        let selection = ChartViewport::new(self.index_at(start), self.index_at(current));
+                        let center = selection.index(0.5);
+                        self.change_viewport(Some(selection.zoom_at(1.0, center, limits, MIN_VIEWPORT_WIDTH)));
+                    }
+                }
+            }
+            EventKind::LostPointerCapture() => {
+                self.drag = None;
+                self.request_window_redraw();
+            }
+            _ => {}
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_fill_the_rectangle() {
+        let points = sparkline_points(
+            &[1.0, 3.0, 2.0],
+            ChartViewport::full(3),
+            Rectangle::new(10.0, 0.0, 100.0, 20.0),
+        );
+
+        assert_eq!(points, vec![(10.0, 20.0), (60.0, 0.0), (110.0, 10.0)]);
+    }
+
+    #[test]
+    fn equal_values_are_drawn_through_the_middle() {
+        let points = sparkline_points(
+            &[4.0, 4.0],
+            ChartViewport::full(2),
+            Rectangle::new(0.0, 0.0, 50.0, 20.0),
+        );
+
+        assert_eq!(points, vec![(0.0, 10.0), (50.0, 10.0)]);
+    }
+
+    #[test]
+    fn zoomed_values_are_rescaled_to_the_viewport() {
+        let values = [0.0, 10.0, 2.0, 4.0, 100.0];
+        assert_eq!(visible_range(values.len(), ChartViewport::new(1.5, 2.5)), 1..=3);
+
+        // The line reaches past the edges, and is scaled to the values drawn.
+        let points = sparkline_points(
+            &values,
+            ChartViewport::new(1.5, 2.5),
+            Rectangle::new(0.0, 0.0, 100.0, 20.0),
+        );
+        assert_eq!(points, vec![(-50.0, 0.0), (50.0, 20.0), (150.0, 15.0)]);
+    }
+
+    #[test]
+    fn values_are_formatted_with_at_most_two_decimals() {
+        assert_eq!(format_value(12.0), "12");
+        assert_eq!(format_value(1.23456), "1.23");
+        assert_eq!(format_value(0.5), "0.5");
+        assert_eq!(format_value(-0.001), "0");
+    }
+}
@@ -0,0 +1,273 @@
+//! A card showing a key figure.
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use craft_primitives::geometry::{Affine, Point, Rectangle};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use peniko::Color;
+
+use super::Sparkline;
+use super::time::Duration;
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, draw_generic_container, push_child_to_element};
+use crate::elements::traits::DeepClone;
+use crate::elements::{AsElement, Container, Element, ElementInternals, Text};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::{AlignItems, Display, FlexDirection, FontWeight};
+use crate::text::text_context::TextContext;
+use crate::{pct, px, rgb};
+
+const TITLE_COLOR: Color = rgb(107, 114, 128);
+const INCREASE_COLOR: Color = rgb(22, 163, 74);
+const DECREASE_COLOR: Color = rgb(220, 38, 38);
+
+#[derive(Clone)]
+pub struct StatCard {
+    pub inner: Rc<RefCell<StatCardInner>>,
+}
+
+/// A card with a title, a value, its change in percent and a [`Sparkline`] of its recent values.
+///
+/// The change and the sparkline are hidden until they are set. Increases are green and decreases red, unless
+/// [`StatCard::invert_delta_colors`] is set for values where less is better.
+#[derive(Clone)]
+pub struct StatCardInner {
+    element_data: ElementData,
+    delta: Option<f32>,
+    invert_delta_colors: bool,
+    pub title: Text,
+    pub value: Text,
+    pub delta_label: Text,
+    pub sparkline: Sparkline,
+}
+
+impl Default for StatCard {
+    fn default() -> Self {
+        Self::new("", "")
+    }
+}
+
+impl StatCard {
+    pub fn new(title: &str, value: &str) -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<StatCardInner>>| {
+            RefCell::new(StatCardInner {
+                element_data: ElementData::new(me.clone(), false),
+                delta: None,
+                invert_delta_colors: false,
+                title: Text::new(title).selectable(false).font_size(13.0).color(TITLE_COLOR),
+                value: Text::new(value).selectable(false).font_size(28.0).font_weight(FontWeight::BOLD),
+                delta_label: Text::new("").selectable(false).font_size(13.0).display(Display::None),
+                sparkline: Sparkline::new(&[]).width(pct(100)).height(px(32)).display(Display::None),
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        inner_mut.set_display(Display::Flex);
+        inner_mut.set_flex_direction(FlexDirection::Column);
+        inner_mut.set_gap(px(4), px(4));
+        inner_mut.set_padding_all(px(16));
+        inner_mut.set_background_color(Color::WHITE);
+        inner_mut.set_border_width_all(px(1));
+        inner_mut.set_border_color_all(rgb(229, 231, 235));
+        inner_mut.set_border_radius_all(px(8).into());
+
+        let value_row = Container::new()
+            .display(Display::Flex)
+            .flex_direction(FlexDirection::Row)
+            .align_items(Some(AlignItems::Baseline))
+            .gap(px(8), px(8))
+            .push(inner_mut.value.clone())
+            .push(inner_mut.delta_label.clone());
+        let title = inner_mut.title.clone();
+        let sparkline = inner_mut.sparkline.clone();
+        inner_mut.push(title.inner);
+        inner_mut.push(value_row.inner);
+        inner_mut.push(sparkline.inner);
+
+        drop(inner_mut);
+        Self { inner }
+    }
+
+    pub fn title(self, title: &str) -> Self {
+        self.inner.borrow().title.clone().text(title);
+        self
+    }
+
+    pub fn value(self, value: &str) -> Self {
+        self.inner.borrow().value.clone().text(value);
+        self
+    }
+
+    /// Set the change of the value in percent, or hide it with `None`.
+    pub fn delta(self, delta: Option<f32>) -> Self {
+        self.inner.borrow_mut().set_delta(delta);
+        self
+    }
+
+    pub fn get_delta(&self) -> Option<f32> {
+        self.inner.borrow().delta
+    }
+
+    /// Show decreases in green and increases in red.
+    pub fn invert_delta_colors(self, invert_delta_colors: bool) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.invert_delta_colors = invert_delta_colors;
+        let delta = inner.delta;
+        inner.set_delta(delta);
+        drop(inner);
+        self
+    }
+
+    /// Set the recent values drawn in the sparkline, hiding it when empty.
+    pub fn trend(self, values: &[f32]) -> Self {
+        let display = if values.is_empty() { Display::None } else { Display::Flex };
+        self.inner.borrow().sparkline.clone().values(values).display(display);
+        self
+    }
+
+    /// Set how long the sparkline takes to reach new values.
+    pub fn animation_duration(self, duration: Duration) -> Self {
+        self.inner.borrow().sparkline.clone().animation_duration(duration);
+        self
+    }
+}
+
+impl StatCardInner {
+    pub fn set_delta(&mut self, delta: Option<f32>) {
+        self.delta = delta.filter(|delta| delta.is_finite());
+        let label = self.delta_label.clone();
+        let Some(delta) = self.delta else {
+            label.display(Display::None);
+            return;
+        };
+
+        let is_good = (delta >= 0.0) != self.invert_delta_colors;
+        let color = if delta == 0.0 {
+            TITLE_COLOR
+        } else if is_good {
+            INCREASE_COLOR
+        } else {
+            DECREASE_COLOR
+        };
+        label.text(&format_delta(delta)).color(color).display(Display::Flex);
+    }
+}
+
+/// Formats a change in percent with an arrow showing its direction, such as `▲ 3.2%`.
+fn format_delta(delta: f32) -> String {
+    let arrow = if delta > 0.0 {
+        "▲ "
+    } else if delta < 0.0 {
+        "▼ "
+    } else {
+        ""
+    };
+    format!("{arrow}{:.1}%", delta.abs())
+}
+
+impl Element for StatCard {}
+
+impl Drop for StatCardInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for StatCard {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for StatCardInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for StatCardInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        draw_generic_container(self, renderer, resource_manager, text_context, scale_factor);
+    }
+
+    fn on_event(
+        &mut self,
+        _message: &EventKind,
+        _text_context: &mut TextContext,
+        _event: &mut Event,
+        _target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_delta;
+
+    #[test]
+    fn deltas_show_their_direction() {
+        assert_eq!(format_delta(3.24), "▲ 3.2%");
+        assert_eq!(format_delta(-12.0), "▼ 12.0%");
+        assert_eq!(format_delta(0.0), "0.0%");
+    }
+}
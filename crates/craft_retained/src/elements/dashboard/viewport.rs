@@ -0,0 +1,87 @@
+//! The part of a chart's values that is shown, after the user zoomed or panned.
+
+/// The visible range of a chart's x-axis, in value indices: `0.0` is the first value and `1.0` the second.
+///
+/// The ends don't have to be whole numbers, so a chart can be zoomed to show part of the way between two values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChartViewport {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl ChartViewport {
+    pub fn new(start: f64, end: f64) -> Self {
+        Self {
+            start: start.min(end),
+            end: start.max(end),
+        }
+    }
+
+    /// The viewport showing all of `count` values.
+    pub fn full(count: usize) -> Self {
+        Self::new(0.0, count.saturating_sub(1) as f64)
+    }
+
+    pub fn width(&self) -> f64 {
+        self.end - self.start
+    }
+
+    /// Where an index is in the viewport, from 0 at the start to 1 at the end.
+    pub(crate) fn fraction(&self, index: f64) -> f64 {
+        if self.width() > 0.0 {
+            (index - self.start) / self.width()
+        } else {
+            0.0
+        }
+    }
+
+    /// The index at a fraction of the viewport, from 0 at the start to 1 at the end.
+    pub(crate) fn index(&self, fraction: f64) -> f64 {
+        self.start + self.width() * fraction
+    }
+
+    /// Divides the width by `factor`, keeping the index at `anchor` in place.
+    pub(crate) fn zoom_at(self, factor: f64, anchor: f64, limits: Self, min_width: f64) -> Self {
+        let width = (self.width() / factor).clamp(min_width.min(limits.width()), limits.width());
+        let start = anchor - (anchor - self.start) * width / self.width().max(f64::EPSILON);
+        Self::new(start, start + width).clamp(limits)
+    }
+
+    /// Moves the viewport by `delta` indices.
+    pub(crate) fn pan(self, delta: f64, limits: Self) -> Self {
+        Self::new(self.start + delta, self.end + delta).clamp(limits)
+    }
+
+    /// Moves the viewport inside `limits`, shrinking it if it is wider.
+    pub(crate) fn clamp(self, limits: Self) -> Self {
+        let width = self.width().min(limits.width());
+        let start = self.start.max(limits.start).min(limits.end - width);
+        Self::new(start, start + width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zooming_keeps_the_anchor_in_place() {
+        let limits = ChartViewport::full(11);
+        let zoomed = limits.zoom_at(2.0, 2.0, limits, 1.0);
+        assert_eq!(zoomed, ChartViewport::new(1.0, 6.0));
+        assert_eq!(zoomed.fraction(2.0), limits.fraction(2.0));
+
+        // Zooming in stops at the smallest width, and zooming out at the limits.
+        assert_eq!(zoomed.zoom_at(100.0, 1.0, limits, 1.0), ChartViewport::new(1.0, 2.0));
+        assert_eq!(zoomed.zoom_at(0.01, 2.0, limits, 1.0), limits);
+    }
+
+    #[test]
+    fn panning_stops_at_the_limits() {
+        let limits = ChartViewport::full(11);
+        let viewport = ChartViewport::new(2.0, 6.0);
+        assert_eq!(viewport.pan(3.0, limits), ChartViewport::new(5.0, 9.0));
+        assert_eq!(viewport.pan(-5.0, limits), ChartViewport::new(0.0, 4.0));
+        assert_eq!(viewport.pan(10.0, limits), ChartViewport::new(6.0, 10.0));
+    }
+}
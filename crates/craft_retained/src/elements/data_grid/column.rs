@@ -0,0 +1,125 @@
+/// The value of a cell in a [`DataGrid`](crate::elements::DataGrid).
+#[derive(Clone, Debug, PartialEq)]
+pub enum CellValue {
+    Text(String),
+    Number(f64),
+    /// The index of the selected option of a dropdown column.
+    Choice(usize),
+}
+
+/// How the cells of a column are shown and edited.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColumnKind {
+    /// Edited with a text input.
+    Text,
+    /// Edited with a text input accepting numbers.
+    Number,
+    /// Edited with a dropdown of the options.
+    Dropdown(Vec<String>),
+}
+
+/// A column of a [`DataGrid`](crate::elements::DataGrid).
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataGridColumn {
+    pub title: String,
+    pub kind: ColumnKind,
+    /// The width of the column in logical pixels.
+    pub width: f32,
+    pub editable: bool,
+}
+
+impl DataGridColumn {
+    fn new(title: &str, kind: ColumnKind) -> Self {
+        Self {
+            title: title.to_string(),
+            kind,
+            width: 120.0,
+            editable: true,
+        }
+    }
+
+    pub fn text(title: &str) -> Self {
+        Self::new(title, ColumnKind::Text)
+    }
+
+    pub fn number(title: &str) -> Self {
+        Self::new(title, ColumnKind::Number)
+    }
+
+    pub fn dropdown(title: &str, options: &[&str]) -> Self {
+        Self::new(
+            title,
+            ColumnKind::Dropdown(options.iter().map(|option| option.to_string()).collect()),
+        )
+    }
+
+    /// Set the width of the column in logical pixels. Columns are 120 pixels wide by default.
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Stops the cells of the column from being edited.
+    pub fn read_only(mut self) -> Self {
+        self.editable = false;
+        self
+    }
+
+    /// The value of an empty cell of the column.
+    pub(crate) fn default_value(&self) -> CellValue {
+        match self.kind {
+            ColumnKind::Text => CellValue::Text(String::new()),
+            ColumnKind::Number => CellValue::Number(0.0),
+            ColumnKind::Dropdown(_) => CellValue::Choice(0),
+        }
+    }
+
+    /// The text a cell of the column shows for `value`.
+    pub(crate) fn format(&self, value: &CellValue) -> String {
+        match (value, &self.kind) {
+            (CellValue::Choice(index), ColumnKind::Dropdown(options)) => {
+                options.get(*index).cloned().unwrap_or_default()
+            }
+            (CellValue::Choice(index), _) => index.to_string(),
+            (CellValue::Text(text), _) => text.clone(),
+            (CellValue::Number(number), _) => number.to_string(),
+        }
+    }
+
+    /// Parses the text of a cell of the column, returning `None` if it isn't a number in a number column or one of the
+    /// options in a dropdown column.
+    pub(crate) fn parse(&self, text: &str) -> Option<CellValue> {
+        match &self.kind {
+            ColumnKind::Text => Some(CellValue::Text(text.to_string())),
+            ColumnKind::Number => text
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|number| number.is_finite())
+                .map(CellValue::Number),
+            ColumnKind::Dropdown(options) => options
+                .iter()
+                .position(|option| option == text.trim())
+                .map(CellValue::Choice),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_are_parsed_by_the_kind_of_their_column() {
+        let number = DataGridColumn::number("Price");
+        assert_eq!(number.parse(" 2.5 "), Some(CellValue::Number(2.5)));
+        assert_eq!(number.parse("inf"), None);
+        assert_eq!(number.parse("abc"), None);
+
+        let dropdown = DataGridColumn::dropdown("Size", &["Small", "Large"]);
+        assert_eq!(dropdown.parse("Large"), Some(CellValue::Choice(1)));
+        assert_eq!(dropdown.parse("Medium"), None);
+        assert_eq!(dropdown.format(&CellValue::Choice(0)), "Small");
+        assert_eq!(dropdown.format(&CellValue::Choice(5)), "");
+    }
+}
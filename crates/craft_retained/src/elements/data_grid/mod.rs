@@ -0,0 +1,715 @@
+//! Rows of values in typed columns, edited in place.
+
+mod column;
+mod navigation;
+mod selection;
+
+pub use column::{CellValue, ColumnKind, DataGridColumn};
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::ops::Range;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use craft_primitives::geometry::{Affine, Point, Rectangle};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use peniko::Color;
+use ui_events::keyboard::{Key, KeyState, NamedKey};
+use ui_events::pointer::PointerButton;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time;
+#[cfg(target_arch = "wasm32")]
+use web_time as time;
+
+use time::{Duration, Instant};
+
+use crate::app::queue_event;
+use crate::elements::data_grid::navigation::{CellMove, move_cell};
+use crate::elements::data_grid::selection::{cell_range, pasted_values};
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, draw_generic_container, is_target_within, push_child_to_element};
+use crate::elements::traits::DeepClone;
+use crate::elements::{AsElement, Container, Dropdown, Element, ElementInternals, KeyBindings, KeyChord, Text, TextInput, resolve_clip_for_scrollable};
+use crate::events::{CellEdited, Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::{Display, FlexDirection, FontWeight, Overflow};
+use crate::text::text_context::TextContext;
+use crate::{clipboard, pct, px, rgb};
+
+/// The longest time between two clicks on a cell that starts editing it.
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(500);
+const GRID_LINE_COLOR: Color = rgb(220, 220, 226);
+const HEADER_BACKGROUND_COLOR: Color = rgb(244, 244, 246);
+const ACTIVE_CELL_COLOR: Color = rgb(191, 219, 254);
+const SELECTED_CELL_COLOR: Color = rgb(219, 234, 254);
+const INVALID_BORDER_COLOR: Color = rgb(220, 38, 38);
+
+/// A cell and the label showing its value while it isn't edited.
+#[derive(Clone)]
+struct GridCell {
+    container: Container,
+    label: Text,
+}
+
+/// The control editing a cell.
+#[derive(Clone)]
+enum CellEditor {
+    Input(TextInput),
+    Dropdown(Dropdown),
+}
+
+impl CellEditor {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        match self {
+            CellEditor::Input(input) => input.as_element_rc(),
+            CellEditor::Dropdown(dropdown) => dropdown.as_element_rc(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct CellEditing {
+    row: usize,
+    column: usize,
+    editor: CellEditor,
+}
+
+#[derive(Clone)]
+pub struct DataGrid {
+    pub inner: Rc<RefCell<DataGridInner>>,
+}
+
+/// A grid of rows with a value per column, shown as text and edited in place.
+///
+/// Double-clicking a cell or pressing F2 edits the active cell with a control for the type of its column: a text input
+/// for text and numbers, or a dropdown of the column's options. Enter keeps the new value and Escape discards it. Tab
+/// and Shift+Tab keep it and edit the next or previous cell, while the arrow keys move between cells when no cell is
+/// edited.
+///
+/// Shift-clicking a cell, or Shift with the arrow keys, selects the cells between it and the active one. Ctrl+C, or
+/// Cmd+C on macOS, copies them as a table that spreadsheets can paste, and Ctrl+V pastes a table copied from a
+/// spreadsheet, or tab or comma-separated text, from the first selected cell, see [`crate::clipboard`].
+///
+/// A [`EventKind::CellEdited`] event is sent with the row, column and new value whenever the user changes a cell. A
+/// number input shows a red border, and stays open, while its text isn't a number.
+#[derive(Clone)]
+pub struct DataGridInner {
+    element_data: ElementData,
+    columns: Vec<DataGridColumn>,
+    rows: Vec<Vec<CellValue>>,
+    cells: Vec<Vec<GridCell>>,
+    /// The row and column of the cell the keyboard acts on.
+    active: Option<(usize, usize)>,
+    /// The other corner of the selected cells, with `active`.
+    anchor: Option<(usize, usize)>,
+    editing: Option<CellEditing>,
+    last_click: Option<(Instant, (usize, usize))>,
+}
+
+impl Default for DataGrid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Element for DataGrid {}
+
+impl Drop for DataGridInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for DataGrid {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for DataGridInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for DataGridInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(
+        &mut self,
+        renderer: &mut dyn Renderer,
+        resource_manager: Arc<ResourceManager>,
+        scale_factor: f64,
+        text_context: &mut TextContext,
+    ) {
+        draw_generic_container(self, renderer, resource_manager, text_context, scale_factor);
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        _event: &mut Event,
+        target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        match message {
+            EventKind::PointerButtonDown(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                if let Some(cell) = target.and_then(|target| self.cell_at(&target)) {
+                    self.click_cell(cell, pointer_button.state.modifiers.shift());
+                }
+            }
+            EventKind::DropdownItemSelected(selected) => {
+                let is_editor = matches!(
+                    (&self.editing, &target),
+                    (Some(CellEditing { editor: CellEditor::Dropdown(dropdown), .. }), Some(target))
+                        if is_target_within(target, dropdown)
+                );
+                // Selections reach `on_event` twice, so the second one finds the editor closed.
+                if is_editor {
+                    self.finish_edit(Some(CellValue::Choice(*selected)));
+                }
+            }
+            EventKind::KeyboardInputEvent(key) if key.state == KeyState::Down => {
+                if self.editing.is_some() {
+                    match &key.key {
+                        Key::Named(NamedKey::Enter) => {
+                            self.commit_edit();
+                        }
+                        Key::Named(NamedKey::Escape) => self.finish_edit(None),
+                        Key::Named(NamedKey::Tab) => {
+                            let movement = if key.modifiers.shift() {
+                                CellMove::Previous
+                            } else {
+                                CellMove::Next
+                            };
+                            let previous = self.active;
+                            if self.commit_edit() {
+                                self.move_active(movement, false);
+                                if let Some(active) = self.active
+                                    && self.active != previous
+                                {
+                                    self.begin_edit(active);
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                    return;
+                }
+                if !self.is_focused() {
+                    return;
+                }
+
+                let action_modifier = if cfg!(target_os = "macos") {
+                    key.modifiers.meta()
+                } else {
+                    key.modifiers.ctrl()
+                };
+                let shift = key.modifiers.shift();
+                let (movement, extend) = match &key.key {
+                    Key::Named(NamedKey::ArrowUp) => (CellMove::Up, shift),
+                    Key::Named(NamedKey::ArrowDown) => (CellMove::Down, shift),
+                    Key::Named(NamedKey::ArrowLeft) => (CellMove::Left, shift),
+                    Key::Named(NamedKey::ArrowRight) => (CellMove::Right, shift),
+                    Key::Named(NamedKey::Tab) if shift => (CellMove::Previous, false),
+                    Key::Named(NamedKey::Tab) => (CellMove::Next, false),
+                    Key::Named(NamedKey::F2) => {
+                        if let Some(active) = self.active {
+                            self.begin_edit(active);
+                        }
+                        return;
+                    }
+                    Key::Character(character) if action_modifier && character.eq_ignore_ascii_case("c") => {
+                        if let Some(table) = self.selected_table() {
+                            clipboard::set_table(&table);
+                        }
+                        return;
+                    }
+                    Key::Character(character) if action_modifier && character.eq_ignore_ascii_case("v") => {
+                        if let Some(table) = clipboard::get_table() {
+                            self.paste(&table);
+                        }
+                        return;
+                    }
+                    _ => return,
+                };
+                self.move_active(movement, extend);
+            }
+            _ => {}
+        }
+    }
+
+    fn apply_clip(&mut self, clip_bounds: Option<Rectangle>) {
+        let overflow = self.style().get_overflow();
+        if overflow[0] == Overflow::Scroll || overflow[1] == Overflow::Scroll {
+            resolve_clip_for_scrollable(self, clip_bounds);
+        } else {
+            self.element_data.layout.apply_clip(clip_bounds);
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn handles_arrow_keys(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl DataGrid {
+    pub fn new() -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<DataGridInner>>| {
+            RefCell::new(DataGridInner {
+                element_data: ElementData::new(me.clone(), true),
+                columns: Vec::new(),
+                rows: Vec::new(),
+                cells: Vec::new(),
+                active: None,
+                anchor: None,
+                editing: None,
+                last_click: None,
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        inner_mut.set_display(Display::Flex);
+        inner_mut.set_flex_direction(FlexDirection::Column);
+        inner_mut.set_border_width(px(1), px(0), px(0), px(1));
+        inner_mut.set_border_color(GRID_LINE_COLOR, GRID_LINE_COLOR, GRID_LINE_COLOR, GRID_LINE_COLOR);
+
+        drop(inner_mut);
+        Self { inner }
+    }
+
+    /// Adds a column to the right of the grid. Existing rows get the column's empty value.
+    pub fn column(self, column: DataGridColumn) -> Self {
+        self.inner.borrow_mut().add_column(column);
+        self
+    }
+
+    /// Adds a row to the bottom of the grid, with a value per column.
+    pub fn row(self, values: Vec<CellValue>) -> Self {
+        self.inner.borrow_mut().add_row(values);
+        self
+    }
+
+    pub fn get_value(&self, row: usize, column: usize) -> Option<CellValue> {
+        self.inner.borrow().rows.get(row)?.get(column).cloned()
+    }
+
+    pub fn get_rows(&self) -> Vec<Vec<CellValue>> {
+        self.inner.borrow().rows.clone()
+    }
+
+    pub fn get_columns(&self) -> Vec<DataGridColumn> {
+        self.inner.borrow().columns.clone()
+    }
+
+    /// The row and column of the cell the keyboard acts on.
+    pub fn get_active_cell(&self) -> Option<(usize, usize)> {
+        self.inner.borrow().active
+    }
+
+    /// The rows and columns of the selected cells.
+    pub fn get_selection(&self) -> Option<(Range<usize>, Range<usize>)> {
+        self.inner.borrow().selection()
+    }
+
+    /// The column titles and the rows as CSV, with the values as the cells show them. See [`crate::export`].
+    pub fn to_csv(&self) -> String {
+        let inner = self.inner.borrow();
+        let header = inner.columns.iter().map(|column| column.title.clone()).collect();
+        let rows = inner.rows.iter().map(|values| {
+            inner
+                .columns
+                .iter()
+                .zip(values)
+                .map(|(column, value)| column.format(value))
+                .collect()
+        });
+        let table: Vec<Vec<String>> = std::iter::once(header).chain(rows).collect();
+        clipboard::format_delimited(&table, ',')
+    }
+}
+
+impl DataGridInner {
+    pub fn add_column(&mut self, column: DataGridColumn) {
+        for row in &mut self.rows {
+            row.push(column.default_value());
+        }
+        self.columns.push(column);
+        self.rebuild();
+    }
+
+    /// Adds a row, filling missing values with the empty value of their column and dropping extra ones.
+    pub fn add_row(&mut self, mut values: Vec<CellValue>) {
+        values.truncate(self.columns.len());
+        for column in &self.columns[values.len()..] {
+            values.push(column.default_value());
+        }
+        self.push_row(&values);
+        self.rows.push(values);
+    }
+
+    /// Changes the value of a cell without sending an event.
+    pub fn set_value(&mut self, row: usize, column: usize, value: CellValue) {
+        let Some(cell) = self.cells.get(row).and_then(|cells| cells.get(column)) else {
+            return;
+        };
+        cell.label
+            .inner
+            .borrow_mut()
+            .set_text(&self.columns[column].format(&value));
+        self.rows[row][column] = value;
+    }
+
+    /// Recreates the header and the rows, after the columns changed.
+    fn rebuild(&mut self) {
+        self.finish_edit(None);
+        self.remove_all_children();
+        self.cells.clear();
+        self.active = None;
+        self.anchor = None;
+
+        let header = self.columns.iter().fold(
+            Container::new()
+                .display(Display::Flex)
+                .flex_direction(FlexDirection::Row)
+                .background_color(HEADER_BACKGROUND_COLOR),
+            |header, column| {
+                header.push(
+                    cell_container(column.width)
+                        .push(Text::new(&column.title).selectable(false).font_weight(FontWeight::BOLD)),
+                )
+            },
+        );
+        self.push(header.inner);
+
+        for values in self.rows.clone() {
+            self.push_row(&values);
+        }
+    }
+
+    fn push_row(&mut self, values: &[CellValue]) {
+        let cells: Vec<GridCell> = self
+            .columns
+            .iter()
+            .zip(values)
+            .map(|(column, value)| {
+                let label = Text::new(&column.format(value)).selectable(false);
+                GridCell {
+                    container: cell_container(column.width).push(label.clone()),
+                    label,
+                }
+            })
+            .collect();
+        let row = cells.iter().fold(
+            Container::new()
+                .display(Display::Flex)
+                .flex_direction(FlexDirection::Row),
+            |row, cell| row.push(cell.container.clone()),
+        );
+
+        self.push(row.inner);
+        self.cells.push(cells);
+    }
+
+    /// The row and column of the cell the target is in.
+    fn cell_at(&self, target: &Rc<RefCell<dyn ElementInternals>>) -> Option<(usize, usize)> {
+        self.cells.iter().enumerate().find_map(|(row, cells)| {
+            cells
+                .iter()
+                .position(|cell| is_target_within(target, &cell.container))
+                .map(|column| (row, column))
+        })
+    }
+
+    /// Activates a clicked cell, and edits it if it was clicked twice in a row. With `extend`, selects the cells
+    /// between it and the active cell instead.
+    fn click_cell(&mut self, cell: (usize, usize), extend: bool) {
+        if self
+            .editing
+            .as_ref()
+            .is_some_and(|editing| (editing.row, editing.column) == cell)
+        {
+            return;
+        }
+        if self.editing.is_some() && !self.commit_edit() {
+            return;
+        }
+
+        let now = Instant::now();
+        let is_double_click = self
+            .last_click
+            .is_some_and(|(time, last)| last == cell && now.duration_since(time) < DOUBLE_CLICK_TIME);
+        self.last_click = Some((now, cell));
+
+        self.focus();
+        self.set_active(Some(cell), extend);
+        if is_double_click && !extend {
+            self.last_click = None;
+            self.begin_edit(cell);
+        }
+    }
+
+    fn selection(&self) -> Option<(Range<usize>, Range<usize>)> {
+        self.active
+            .map(|active| cell_range(self.anchor.unwrap_or(active), active))
+    }
+
+    /// Moves the active cell, keeping the anchor of the selection if `extend` is true.
+    fn set_active(&mut self, active: Option<(usize, usize)>, extend: bool) {
+        let old_selection = self.selection();
+        self.anchor = if extend { self.anchor.or(self.active) } else { active };
+        self.active = active;
+        let selection = self.selection();
+
+        for (rows, columns) in [old_selection, selection.clone()].into_iter().flatten() {
+            for row in rows {
+                for column in columns.clone() {
+                    let color = if Some((row, column)) == active {
+                        ACTIVE_CELL_COLOR
+                    } else if selection
+                        .as_ref()
+                        .is_some_and(|(rows, columns)| rows.contains(&row) && columns.contains(&column))
+                    {
+                        SELECTED_CELL_COLOR
+                    } else {
+                        Color::TRANSPARENT
+                    };
+                    if let Some(cell) = self.cells.get(row).and_then(|cells| cells.get(column)) {
+                        cell.container.clone().background_color(color);
+                    }
+                }
+            }
+        }
+    }
+
+    fn move_active(&mut self, movement: CellMove, extend: bool) {
+        let active = match self.active {
+            Some(active) => move_cell(active, movement, self.rows.len(), self.columns.len()),
+            None => (0, 0),
+        };
+        if active.0 < self.rows.len() && active.1 < self.columns.len() {
+            self.set_active(Some(active), extend);
+        }
+    }
+
+    /// The text of the selected cells.
+    fn selected_table(&self) -> Option<Vec<Vec<String>>> {
+        let (rows, columns) = self.selection()?;
+        let table = self.rows[rows]
+            .iter()
+            .map(|values| {
+                columns
+                    .clone()
+                    .map(|column| self.columns[column].format(&values[column]))
+                    .collect()
+            })
+            .collect();
+        Some(table)
+    }
+
+    /// Pastes a table from the first selected cell, and selects the cells it covers.
+    fn paste(&mut self, table: &[Vec<String>]) {
+        let Some((rows, columns)) = self.selection() else {
+            return;
+        };
+        let first = (rows.start, columns.start);
+        for (row, column, value) in pasted_values(&self.columns, self.rows.len(), first, table) {
+            self.edit_value(row, column, value);
+        }
+
+        let width = table.iter().map(Vec::len).max().unwrap_or(0);
+        if table.is_empty() || width == 0 {
+            return;
+        }
+        let last = (
+            (first.0 + table.len()).min(self.rows.len()) - 1,
+            (first.1 + width).min(self.columns.len()) - 1,
+        );
+        self.set_active(Some(first), false);
+        self.set_active(Some(last), true);
+    }
+
+    /// Replaces the label of a cell with an editor, if its column is editable.
+    fn begin_edit(&mut self, (row, column): (usize, usize)) {
+        let (Some(cell), Some(value)) = (
+            self.cells.get(row).and_then(|cells| cells.get(column)),
+            self.rows.get(row).and_then(|values| values.get(column)),
+        ) else {
+            return;
+        };
+        let column_info = &self.columns[column];
+        if !column_info.editable {
+            return;
+        }
+
+        let editor = match &column_info.kind {
+            ColumnKind::Dropdown(options) if options.is_empty() => return,
+            ColumnKind::Dropdown(options) => {
+                let selected = match value {
+                    CellValue::Choice(selected) => (*selected).min(options.len() - 1),
+                    _ => 0,
+                };
+                let dropdown = options
+                    .iter()
+                    .fold(Dropdown::new(), |dropdown, option| dropdown.push(Text::new(option)))
+                    .selected_item(selected);
+                CellEditor::Dropdown(dropdown)
+            }
+            ColumnKind::Text | ColumnKind::Number => {
+                // Enter keeps the value rather than starting a new line.
+                let key_bindings = KeyBindings::platform().unbind(&KeyChord::named(NamedKey::Enter));
+                CellEditor::Input(
+                    TextInput::new(&column_info.format(value))
+                        .key_bindings(key_bindings)
+                        .width(pct(100)),
+                )
+            }
+        };
+
+        let mut container = cell.container.inner.borrow_mut();
+        container.remove_child(cell.label.as_element_rc()).unwrap();
+        container.push(editor.as_element_rc());
+        drop(container);
+
+        match &editor {
+            CellEditor::Input(input) => input.inner.borrow_mut().focus(),
+            CellEditor::Dropdown(_) => self.focus(),
+        }
+        self.set_active(Some((row, column)), false);
+        self.editing = Some(CellEditing {
+            row,
+            column,
+            editor,
+        });
+    }
+
+    /// Closes the editor with the value it holds. Returns false, leaving the editor open with a red border, if the
+    /// text of a number input isn't a number.
+    fn commit_edit(&mut self) -> bool {
+        let Some(editing) = &self.editing else {
+            return true;
+        };
+        let value = match &editing.editor {
+            CellEditor::Input(input) => {
+                let value = self.columns[editing.column].parse(&input.get_text());
+                if value.is_none() {
+                    input.clone().border_color_all(INVALID_BORDER_COLOR);
+                    return false;
+                }
+                value
+            }
+            CellEditor::Dropdown(dropdown) => Some(CellValue::Choice(dropdown.clone().get_selected_item())),
+        };
+        self.finish_edit(value);
+        true
+    }
+
+    /// Puts the label of the edited cell back, and changes its value to `value` if there is one.
+    fn finish_edit(&mut self, value: Option<CellValue>) {
+        let Some(CellEditing {
+            row,
+            column,
+            editor,
+        }) = self.editing.take()
+        else {
+            return;
+        };
+        let cell = &self.cells[row][column];
+        let mut container = cell.container.inner.borrow_mut();
+        container.remove_child(editor.as_element_rc()).unwrap();
+        container.push(cell.label.as_element_rc());
+        drop(container);
+        self.focus();
+
+        if let Some(value) = value {
+            self.edit_value(row, column, value);
+        }
+    }
+
+    /// Changes the value of a cell and sends a [`EventKind::CellEdited`] event, if the value is new.
+    fn edit_value(&mut self, row: usize, column: usize, value: CellValue) {
+        if self.rows[row][column] == value {
+            return;
+        }
+        self.set_value(row, column, value.clone());
+        let new_event = Event::new(self.element_data.me.upgrade().unwrap());
+        queue_event(
+            new_event,
+            EventKind::CellEdited(CellEdited {
+                row,
+                col: column,
+                value,
+            }),
+        );
+    }
+}
+
+fn cell_container(width: f32) -> Container {
+    Container::new()
+        .width(px(width))
+        .flex_shrink(0.0)
+        .padding_horizontal(px(6))
+        .padding_vertical(px(4))
+        .border_width(px(0), px(1), px(1), px(0))
+        .border_color_all(GRID_LINE_COLOR)
+}
@@ -0,0 +1,58 @@
+//! Moving the active cell of a [`DataGrid`](super::DataGrid) with the keyboard.
+
+/// Where the active cell moves to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CellMove {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// The cell to the right, or the first cell of the next row. Moved to with Tab.
+    Next,
+    /// The cell to the left, or the last cell of the previous row. Moved to with Shift+Tab.
+    Previous,
+}
+
+/// The cell `movement` leads to from `cell`, as a row and column, staying in a grid of `row_count` rows and
+/// `column_count` columns.
+pub(crate) fn move_cell(
+    (row, column): (usize, usize),
+    movement: CellMove,
+    row_count: usize,
+    column_count: usize,
+) -> (usize, usize) {
+    if row_count == 0 || column_count == 0 {
+        return (row, column);
+    }
+    let (last_row, last_column) = (row_count - 1, column_count - 1);
+    let (row, column) = (row.min(last_row), column.min(last_column));
+
+    match movement {
+        CellMove::Up => (row.saturating_sub(1), column),
+        CellMove::Down => ((row + 1).min(last_row), column),
+        CellMove::Left => (row, column.saturating_sub(1)),
+        CellMove::Right => (row, (column + 1).min(last_column)),
+        CellMove::Next if column < last_column => (row, column + 1),
+        CellMove::Next if row < last_row => (row + 1, 0),
+        CellMove::Previous if column > 0 => (row, column - 1),
+        CellMove::Previous if row > 0 => (row - 1, last_column),
+        CellMove::Next | CellMove::Previous => (row, column),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tab_wraps_between_rows_and_arrows_stop_at_the_edges() {
+        assert_eq!(move_cell((0, 2), CellMove::Next, 2, 3), (1, 0));
+        assert_eq!(move_cell((1, 2), CellMove::Next, 2, 3), (1, 2));
+        assert_eq!(move_cell((1, 0), CellMove::Previous, 2, 3), (0, 2));
+        assert_eq!(move_cell((0, 0), CellMove::Previous, 2, 3), (0, 0));
+
+        assert_eq!(move_cell((0, 2), CellMove::Right, 2, 3), (0, 2));
+        assert_eq!(move_cell((0, 0), CellMove::Up, 2, 3), (0, 0));
+        assert_eq!(move_cell((0, 1), CellMove::Down, 2, 3), (1, 1));
+    }
+}
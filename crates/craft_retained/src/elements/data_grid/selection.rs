@@ -0,0 +1,75 @@
+//! The selected cells of a [`DataGrid`](super::DataGrid), and the values of tables pasted into it.
+
+use std::ops::Range;
+
+use crate::elements::data_grid::column::{CellValue, DataGridColumn};
+
+/// The rows and columns of the cells between two cells, both included.
+pub(crate) fn cell_range(
+    (anchor_row, anchor_column): (usize, usize),
+    (row, column): (usize, usize),
+) -> (Range<usize>, Range<usize>) {
+    (
+        anchor_row.min(row)..anchor_row.max(row) + 1,
+        anchor_column.min(column)..anchor_column.max(column) + 1,
+    )
+}
+
+/// The values a table pasted with its first cell at `(row, column)` gives the cells it covers, as rows, columns and
+/// values. Cells outside the grid or in read-only columns are left out, and so are values their column can't parse.
+pub(crate) fn pasted_values(
+    columns: &[DataGridColumn],
+    row_count: usize,
+    (first_row, first_column): (usize, usize),
+    table: &[Vec<String>],
+) -> Vec<(usize, usize, CellValue)> {
+    let mut values = Vec::new();
+    for (row, cells) in (first_row..row_count).zip(table) {
+        for ((column, column_info), text) in columns.iter().enumerate().skip(first_column).zip(cells) {
+            if !column_info.editable {
+                continue;
+            }
+            if let Some(value) = column_info.parse(text) {
+                values.push((row, column, value));
+            }
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_include_both_cells() {
+        assert_eq!(cell_range((3, 1), (1, 2)), (1..4, 1..3));
+        assert_eq!(cell_range((0, 0), (0, 0)), (0..1, 0..1));
+    }
+
+    #[test]
+    fn pasted_tables_are_cut_to_the_grid_and_parsed() {
+        let columns = [
+            DataGridColumn::text("Name"),
+            DataGridColumn::number("Price"),
+            DataGridColumn::text("Id").read_only(),
+        ];
+        let table: Vec<Vec<String>> = [["Tea", "3.5", "9"], ["Cake", "free", "9"], ["Pie", "2", "9"]]
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+            .collect();
+
+        assert_eq!(
+            pasted_values(&columns, 2, (0, 0), &table),
+            vec![
+                (0, 0, CellValue::Text("Tea".to_string())),
+                (0, 1, CellValue::Number(3.5)),
+                (1, 0, CellValue::Text("Cake".to_string())),
+            ]
+        );
+        assert_eq!(
+            pasted_values(&columns, 2, (1, 1), &table),
+            vec![(1, 1, CellValue::Number(3.5))]
+        );
+    }
+}
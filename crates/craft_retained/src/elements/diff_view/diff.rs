@@ -0,0 +1,370 @@
+//! Line and word diffs, and the text shown for them.
+
+use std::ops::Range;
+
+/// Sequences with more pairs of differing items than this are not compared item by item, but shown as removed and
+/// added as a whole, to bound the time and memory of a diff.
+const MAX_COMPARISONS: usize = 4_000_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Edit {
+    /// The indices of an item in the old and new sequence.
+    Equal(usize, usize),
+    Removed(usize),
+    Added(usize),
+}
+
+/// Finds the edits that turn `old` into `new`, keeping the longest common subsequence.
+///
+/// Removals come before additions where both are possible, so a changed run is shown as removed then added.
+fn diff<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Edit> {
+    let prefix = old.iter().zip(new).take_while(|(old, new)| old == new).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|index| Edit::Equal(index, index)).collect();
+
+    let (rows, columns) = (old_middle.len(), new_middle.len());
+    if rows.saturating_mul(columns) > MAX_COMPARISONS {
+        edits.extend((0..rows).map(|index| Edit::Removed(prefix + index)));
+        edits.extend((0..columns).map(|index| Edit::Added(prefix + index)));
+    } else {
+        // The length of the longest common subsequence of the suffixes starting at each pair of indices.
+        let width = columns + 1;
+        let mut lengths = vec![0u32; (rows + 1) * width];
+        for row in (0..rows).rev() {
+            for column in (0..columns).rev() {
+                lengths[row * width + column] = if old_middle[row] == new_middle[column] {
+                    lengths[(row + 1) * width + column + 1] + 1
+                } else {
+                    lengths[(row + 1) * width + column].max(lengths[row * width + column + 1])
+                };
+            }
+        }
+
+        let (mut row, mut column) = (0, 0);
+        while row < rows || column < columns {
+            if row < rows && column < columns && old_middle[row] == new_middle[column] {
+                edits.push(Edit::Equal(prefix + row, prefix + column));
+                row += 1;
+                column += 1;
+            } else if row < rows
+                && (column == columns || lengths[(row + 1) * width + column] >= lengths[row * width + column + 1])
+            {
+                edits.push(Edit::Removed(prefix + row));
+                row += 1;
+            } else {
+                edits.push(Edit::Added(prefix + column));
+                column += 1;
+            }
+        }
+    }
+
+    edits.extend((0..suffix).map(|index| Edit::Equal(old.len() - suffix + index, new.len() - suffix + index)));
+    edits
+}
+
+/// A row of a side-by-side diff: a line of the old text, of the new text, or of both.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct DiffRow {
+    pub(crate) old: Option<usize>,
+    pub(crate) new: Option<usize>,
+    /// Whether the line is the same in both texts.
+    pub(crate) equal: bool,
+    /// The byte ranges of the changed words in the old line.
+    pub(crate) old_words: Vec<Range<usize>>,
+    /// The byte ranges of the changed words in the new line.
+    pub(crate) new_words: Vec<Range<usize>>,
+}
+
+/// The lines of two texts, lined up by a line diff.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct LineDiff {
+    pub(crate) old_lines: Vec<String>,
+    pub(crate) new_lines: Vec<String>,
+    pub(crate) rows: Vec<DiffRow>,
+}
+
+impl LineDiff {
+    pub(crate) fn new(old: &str, new: &str) -> Self {
+        let old_lines: Vec<String> = old.lines().map(str::to_string).collect();
+        let new_lines: Vec<String> = new.lines().map(str::to_string).collect();
+
+        let mut rows = Vec::new();
+        let mut removed = Vec::new();
+        let mut added = Vec::new();
+        for edit in diff(&old_lines, &new_lines) {
+            match edit {
+                Edit::Removed(old) => removed.push(old),
+                Edit::Added(new) => added.push(new),
+                Edit::Equal(old, new) => {
+                    push_changed_rows(&mut rows, &mut removed, &mut added, &old_lines, &new_lines);
+                    rows.push(DiffRow {
+                        old: Some(old),
+                        new: Some(new),
+                        equal: true,
+                        ..DiffRow::default()
+                    });
+                }
+            }
+        }
+        push_changed_rows(&mut rows, &mut removed, &mut added, &old_lines, &new_lines);
+
+        Self {
+            old_lines,
+            new_lines,
+            rows,
+        }
+    }
+
+    /// The number of added and removed lines.
+    pub(crate) fn counts(&self) -> (usize, usize) {
+        let changed = self.rows.iter().filter(|row| !row.equal);
+        let added = changed.clone().filter(|row| row.new.is_some()).count();
+        let removed = changed.filter(|row| row.old.is_some()).count();
+        (added, removed)
+    }
+}
+
+/// Pairs up a run of removed and added lines, comparing the words of each pair.
+fn push_changed_rows(
+    rows: &mut Vec<DiffRow>,
+    removed: &mut Vec<usize>,
+    added: &mut Vec<usize>,
+    old_lines: &[String],
+    new_lines: &[String],
+) {
+    for index in 0..removed.len().max(added.len()) {
+        let old = removed.get(index).copied();
+        let new = added.get(index).copied();
+        let (old_words, new_words) = match (old, new) {
+            (Some(old), Some(new)) => word_diff(&old_lines[old], &new_lines[new]),
+            _ => (Vec::new(), Vec::new()),
+        };
+        rows.push(DiffRow {
+            old,
+            new,
+            equal: false,
+            old_words,
+            new_words,
+        });
+    }
+    removed.clear();
+    added.clear();
+}
+
+/// Splits a line into words, runs of whitespace and single other characters.
+fn tokenize(line: &str) -> Vec<Range<usize>> {
+    let mut tokens: Vec<Range<usize>> = Vec::new();
+    let mut last_class = None;
+    for (index, character) in line.char_indices() {
+        let class = if character.is_alphanumeric() || character == '_' {
+            Some(0)
+        } else if character.is_whitespace() {
+            Some(1)
+        } else {
+            None
+        };
+
+        let end = index + character.len_utf8();
+        match tokens.last_mut() {
+            Some(token) if class.is_some() && class == last_class => token.end = end,
+            _ => tokens.push(index..end),
+        }
+        last_class = class;
+    }
+    tokens
+}
+
+/// Returns the byte ranges of the words that differ between two lines.
+///
+/// Lines without any word in common are left without word highlights, as the whole line changed.
+fn word_diff(old: &str, new: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let old_tokens = tokenize(old);
+    let new_tokens = tokenize(new);
+    let old_words: Vec<&str> = old_tokens.iter().map(|range| &old[range.clone()]).collect();
+    let new_words: Vec<&str> = new_tokens.iter().map(|range| &new[range.clone()]).collect();
+
+    let edits = diff(&old_words, &new_words);
+    let shares_a_word = edits
+        .iter()
+        .any(|edit| matches!(edit, Edit::Equal(index, _) if !old_words[*index].trim().is_empty()));
+    if !shares_a_word {
+        return (Vec::new(), Vec::new());
+    }
+
+    let mut old_changes = Vec::new();
+    let mut new_changes = Vec::new();
+    for edit in edits {
+        match edit {
+            Edit::Removed(index) => push_merged(&mut old_changes, old_tokens[index].clone()),
+            Edit::Added(index) => push_merged(&mut new_changes, new_tokens[index].clone()),
+            Edit::Equal(..) => {}
+        }
+    }
+    (old_changes, new_changes)
+}
+
+fn push_merged(ranges: &mut Vec<Range<usize>>, range: Range<usize>) {
+    match ranges.last_mut() {
+        Some(last) if last.end == range.start => last.end = range.end,
+        _ => ranges.push(range),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum LineKind {
+    Unchanged,
+    Removed,
+    Added,
+    /// An empty line across from a removed or added line, so the rows of both sides stay lined up.
+    Filler,
+}
+
+/// The text shown for a diff, with the byte ranges of its lines and changed words.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct DiffDocument {
+    pub(crate) text: String,
+    pub(crate) lines: Vec<(Range<usize>, LineKind)>,
+    pub(crate) words: Vec<(Range<usize>, LineKind)>,
+}
+
+impl DiffDocument {
+    fn push_line(&mut self, prefix: &str, line: &str, kind: LineKind, words: &[Range<usize>]) {
+        if !self.lines.is_empty() {
+            self.text.push('\n');
+        }
+
+        let start = self.text.len();
+        self.text.push_str(prefix);
+        let line_start = self.text.len();
+        self.text.push_str(line);
+        self.lines.push((start..self.text.len(), kind));
+        self.words.extend(
+            words
+                .iter()
+                .map(|range| (line_start + range.start..line_start + range.end, kind)),
+        );
+    }
+
+    /// The old text on the left and the new text on the right, with filler lines across from added and removed
+    /// lines.
+    pub(crate) fn side_by_side(diff: &LineDiff) -> (Self, Self) {
+        let mut old = Self::default();
+        let mut new = Self::default();
+        for row in &diff.rows {
+            let (old_kind, new_kind) = if row.equal {
+                (LineKind::Unchanged, LineKind::Unchanged)
+            } else {
+                (LineKind::Removed, LineKind::Added)
+            };
+
+            match row.old {
+                Some(line) => old.push_line("", &diff.old_lines[line], old_kind, &row.old_words),
+                None => old.push_line("", "", LineKind::Filler, &[]),
+            }
+            match row.new {
+                Some(line) => new.push_line("", &diff.new_lines[line], new_kind, &row.new_words),
+                None => new.push_line("", "", LineKind::Filler, &[]),
+            }
+        }
+        (old, new)
+    }
+
+    /// Both texts in one column, with each run of removed lines followed by the lines added in their place.
+    pub(crate) fn inline(diff: &LineDiff) -> Self {
+        let mut document = Self::default();
+        let mut rows = diff.rows.iter().peekable();
+        while let Some(row) = rows.next() {
+            if row.equal {
+                document.push_line("  ", &diff.old_lines[row.old.unwrap()], LineKind::Unchanged, &[]);
+                continue;
+            }
+
+            let mut changed = vec![row];
+            while let Some(row) = rows.next_if(|row| !row.equal) {
+                changed.push(row);
+            }
+            for row in &changed {
+                if let Some(line) = row.old {
+                    document.push_line("- ", &diff.old_lines[line], LineKind::Removed, &row.old_words);
+                }
+            }
+            for row in &changed {
+                if let Some(line) = row.new {
+                    document.push_line("+ ", &diff.new_lines[line], LineKind::Added, &row.new_words);
+                }
+            }
+        }
+        document
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diffs_keep_the_common_lines() {
+        let edits = diff(&["a", "b", "c", "d"], &["a", "c", "x", "d"]);
+        assert_eq!(
+            edits,
+            vec![
+                Edit::Equal(0, 0),
+                Edit::Removed(1),
+                Edit::Equal(2, 1),
+                Edit::Added(2),
+                Edit::Equal(3, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn removals_come_before_additions() {
+        assert_eq!(diff(&["a"], &["b"]), vec![Edit::Removed(0), Edit::Added(0)]);
+    }
+
+    #[test]
+    fn changed_lines_are_paired() {
+        let diff = LineDiff::new("let a = 1;\nend", "let b = 1;\nmore\nend");
+
+        assert_eq!(diff.rows.len(), 3);
+        assert_eq!((diff.rows[0].old, diff.rows[0].new), (Some(0), Some(0)));
+        assert_eq!(diff.rows[0].old_words, vec![4..5]);
+        assert_eq!(diff.rows[0].new_words, vec![4..5]);
+        assert_eq!((diff.rows[1].old, diff.rows[1].new), (None, Some(1)));
+        assert!(diff.rows[2].equal);
+        assert_eq!(diff.counts(), (2, 1));
+    }
+
+    #[test]
+    fn unrelated_lines_have_no_word_highlights() {
+        assert_eq!(word_diff("hello world", "goodbye moon"), (Vec::new(), Vec::new()));
+    }
+
+    #[test]
+    fn side_by_side_lines_stay_lined_up() {
+        let diff = LineDiff::new("a\nb", "a\nc\nd");
+        let (old, new) = DiffDocument::side_by_side(&diff);
+
+        assert_eq!(old.text, "a\nb\n");
+        assert_eq!(new.text, "a\nc\nd");
+        assert_eq!(old.lines[2], (4..4, LineKind::Filler));
+        assert_eq!(new.lines[2], (4..5, LineKind::Added));
+    }
+
+    #[test]
+    fn inline_documents_prefix_lines() {
+        let diff = LineDiff::new("keep\nold value", "keep\nnew value");
+        let document = DiffDocument::inline(&diff);
+
+        assert_eq!(document.text, "  keep\n- old value\n+ new value");
+        assert_eq!(document.words, vec![(9..12, LineKind::Removed), (21..24, LineKind::Added)]);
+    }
+}
@@ -0,0 +1,324 @@
+//! Shows the differences between two texts.
+
+mod diff;
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use craft_primitives::geometry::{Affine, Point, Rectangle};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use peniko::Color;
+
+use crate::elements::diff_view::diff::{DiffDocument, LineDiff, LineKind};
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, draw_generic_container, push_child_to_element};
+use crate::elements::traits::DeepClone;
+use crate::elements::{resolve_clip_for_scrollable, AsElement, Container, Element, ElementInternals, TextInput};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::{Display, FlexDirection, FontFamily, Overflow, TextStyleProperty};
+use crate::text::text_context::TextContext;
+use crate::text::RangedStyles;
+use crate::{px, rgb};
+
+const BORDER_COLOR: Color = rgb(199, 199, 206);
+const REMOVED_LINE_COLOR: Color = rgb(255, 235, 233);
+const REMOVED_WORD_COLOR: Color = rgb(255, 192, 188);
+const ADDED_LINE_COLOR: Color = rgb(230, 255, 236);
+const ADDED_WORD_COLOR: Color = rgb(171, 242, 188);
+const FILLER_COLOR: Color = rgb(240, 241, 243);
+
+/// How a [`DiffView`] lays out the two texts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DiffMode {
+    /// The old text on the left and the new text on the right, scrolling together.
+    #[default]
+    SideBySide,
+    /// One column with removed lines marked `-` and added lines marked `+`.
+    Inline,
+}
+
+#[derive(Clone)]
+pub struct DiffView {
+    pub inner: Rc<RefCell<DiffViewInner>>,
+}
+
+/// Compares two texts line by line and highlights the removed and added lines.
+///
+/// A removed line paired with an added line is also compared word by word, and the changed words get a stronger
+/// highlight. Lines are not wrapped, so the rows of both sides stay lined up in side-by-side mode.
+#[derive(Clone)]
+pub struct DiffViewInner {
+    element_data: ElementData,
+    mode: DiffMode,
+    diff: LineDiff,
+    /// The scroll position both panes were last moved to, to tell which of them the user scrolled.
+    synced_scroll_y: f32,
+    pub old_pane: Container,
+    pub old_text: TextInput,
+    pub new_pane: Container,
+    pub new_text: TextInput,
+}
+
+impl Default for DiffView {
+    fn default() -> Self {
+        Self::new("", "")
+    }
+}
+
+impl Element for DiffView {}
+
+impl Drop for DiffViewInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for DiffView {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for DiffViewInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for DiffViewInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        // The panes are scrolled by events before the layout, so the other pane follows before either is laid out.
+        if self.mode == DiffMode::SideBySide {
+            self.sync_scroll();
+        }
+
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+
+        // The layout clamps the scroll position to the height of the panes.
+        self.synced_scroll_y = scroll_y(&self.old_pane);
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        draw_generic_container(self, renderer, resource_manager, text_context, scale_factor);
+    }
+
+    fn on_event(
+        &mut self,
+        _message: &EventKind,
+        _text_context: &mut TextContext,
+        _event: &mut Event,
+        _target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+    }
+
+    fn apply_clip(&mut self, clip_bounds: Option<Rectangle>) {
+        let overflow = self.style().get_overflow();
+        if overflow[0] == Overflow::Scroll || overflow[1] == Overflow::Scroll {
+            resolve_clip_for_scrollable(self, clip_bounds);
+        } else {
+            self.element_data.layout.apply_clip(clip_bounds);
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl DiffView {
+    pub fn new(old: &str, new: &str) -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<DiffViewInner>>| {
+            RefCell::new(DiffViewInner {
+                element_data: ElementData::new(me.clone(), false),
+                mode: DiffMode::default(),
+                diff: LineDiff::default(),
+                synced_scroll_y: 0.0,
+                old_pane: diff_pane(),
+                old_text: diff_text(),
+                new_pane: diff_pane(),
+                new_text: diff_text(),
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        inner_mut.set_display(Display::Flex);
+        inner_mut.set_flex_direction(FlexDirection::Row);
+        inner_mut.set_gap(px(4), px(4));
+
+        let old_pane = inner_mut.old_pane.clone().push(inner_mut.old_text.clone());
+        let new_pane = inner_mut.new_pane.clone().push(inner_mut.new_text.clone());
+        inner_mut.push(old_pane.inner);
+        inner_mut.push(new_pane.inner);
+        inner_mut.set_texts(old, new);
+
+        drop(inner_mut);
+        Self { inner }
+    }
+
+    pub fn mode(self, mode: DiffMode) -> Self {
+        self.inner.borrow_mut().set_mode(mode);
+        self
+    }
+
+    pub fn get_mode(&self) -> DiffMode {
+        self.inner.borrow().mode
+    }
+
+    /// Set the texts to compare.
+    pub fn set_texts(self, old: &str, new: &str) -> Self {
+        self.inner.borrow_mut().set_texts(old, new);
+        self
+    }
+
+    /// Returns the number of added and removed lines.
+    pub fn get_line_counts(&self) -> (usize, usize) {
+        self.inner.borrow().diff.counts()
+    }
+}
+
+impl DiffViewInner {
+    pub fn set_mode(&mut self, mode: DiffMode) {
+        self.mode = mode;
+        self.new_pane.clone().display(match mode {
+            DiffMode::SideBySide => Display::Flex,
+            DiffMode::Inline => Display::None,
+        });
+        self.show_diff();
+    }
+
+    pub fn set_texts(&mut self, old: &str, new: &str) {
+        self.diff = LineDiff::new(old, new);
+        self.show_diff();
+    }
+
+    fn show_diff(&mut self) {
+        match self.mode {
+            DiffMode::SideBySide => {
+                let (old, new) = DiffDocument::side_by_side(&self.diff);
+                show_document(&self.old_text, old);
+                show_document(&self.new_text, new);
+            }
+            DiffMode::Inline => {
+                show_document(&self.old_text, DiffDocument::inline(&self.diff));
+                show_document(&self.new_text, DiffDocument::default());
+            }
+        }
+    }
+
+    /// Moves both panes to the scroll position of the pane that was scrolled.
+    fn sync_scroll(&mut self) {
+        let old_scroll_y = scroll_y(&self.old_pane);
+        let new_scroll_y = scroll_y(&self.new_pane);
+        let target = if old_scroll_y != self.synced_scroll_y { old_scroll_y } else { new_scroll_y };
+
+        for pane in [&self.old_pane, &self.new_pane] {
+            pane.borrow_mut()
+                .element_data_mut()
+                .layout
+                .scroll_state
+                .set_scroll_y(target);
+        }
+        self.synced_scroll_y = target;
+    }
+}
+
+fn diff_pane() -> Container {
+    Container::new()
+        .display(Display::Flex)
+        .flex_direction(FlexDirection::Row)
+        .flex_grow(1.0)
+        .flex_basis(px(0))
+        .min_width(px(0))
+        .min_height(px(0))
+        .overflow(Overflow::Hidden, Overflow::Scroll)
+        .border_width_all(px(1))
+        .border_color_all(BORDER_COLOR)
+}
+
+fn diff_text() -> TextInput {
+    // Not shrinking the text keeps it at its widest, so its lines are not wrapped.
+    TextInput::new("")
+        .disable()
+        .flex_shrink(0.0)
+        .border_width_all(px(0))
+        .font_family(FontFamily::new("monospace"))
+        .font_size(13.0)
+}
+
+fn scroll_y(pane: &Container) -> f32 {
+    pane.borrow().element_data().scroll().scroll_y()
+}
+
+fn show_document(text_input: &TextInput, document: DiffDocument) {
+    let line_styles = document.lines.into_iter().filter_map(|(range, kind)| {
+        let color = match kind {
+            LineKind::Unchanged => return None,
+            LineKind::Removed => REMOVED_LINE_COLOR,
+            LineKind::Added => ADDED_LINE_COLOR,
+            LineKind::Filler => FILLER_COLOR,
+        };
+        Some((range, TextStyleProperty::BackgroundColor(color)))
+    });
+    // Word backgrounds come after the line backgrounds, so they are drawn over them.
+    let word_styles = document.words.into_iter().map(|(range, kind)| {
+        let color = if kind == LineKind::Removed { REMOVED_WORD_COLOR } else { ADDED_WORD_COLOR };
+        (range, TextStyleProperty::BackgroundColor(color))
+    });
+
+    text_input
+        .inner
+        .borrow_mut()
+        .set_text(&document.text)
+        .set_ranged_styles(RangedStyles::new(line_styles.chain(word_styles).collect()));
+}
@@ -0,0 +1,759 @@
+//! Vertical lists whose items can be reordered by dragging, within a list and between the lists of a group.
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+#[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+use accesskit::{Role, TreeUpdate};
+use craft_primitives::geometry::{Affine, Point, Rectangle};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::image::ImageResource;
+use craft_resource_manager::resource::Resource;
+use craft_resource_manager::resource_type::ResourceType;
+use craft_resource_manager::{ResourceId, ResourceManager};
+use image::RgbaImage;
+use peniko::Color;
+use ui_events::pointer::PointerButton;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time;
+#[cfg(target_arch = "wasm32")]
+use web_time as time;
+
+use time::Instant;
+
+use crate::app::{queue_event, request_animation_frame};
+use crate::elements::element_data::ElementData;
+#[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+use crate::elements::internal_helpers::add_generic_accesskit_data;
+use crate::elements::internal_helpers::{
+    apply_generic_container_layout, capture_element, draw_generic_container, insert_child_to_element,
+    push_child_to_element,
+};
+use crate::elements::traits::DeepClone;
+use crate::elements::{resolve_clip_for_scrollable, scrollable, AsElement, Element, ElementInternals};
+use crate::events::{DragListMove, Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::{Display, FlexDirection, Overflow, Unit};
+use crate::text::text_context::TextContext;
+
+/// How far, in logical pixels, the pointer must move before a press on an item starts dragging it.
+const DRAG_THRESHOLD: f64 = 4.0;
+
+/// How quickly the items slide into their new positions, in 1/seconds.
+const REFLOW_SPEED: f32 = 20.0;
+
+/// How a [`DragList`] draws the item being dragged under the pointer.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DragGhost {
+    /// Draw an image of the item, taken when the drag starts. Otherwise, or if the renderer can't draw offscreen, a
+    /// rectangle of the item's size is drawn.
+    pub snapshot: bool,
+    /// The opacity of the ghost, from 0 to 1.
+    pub opacity: f32,
+    /// The size of the ghost relative to the item, around the pointer.
+    pub scale: f32,
+    /// The fill of the rectangle drawn instead of an image.
+    pub fallback_color: Color,
+    /// The outline of the rectangle drawn instead of an image.
+    pub fallback_outline_color: Color,
+}
+
+impl Default for DragGhost {
+    fn default() -> Self {
+        Self {
+            snapshot: true,
+            opacity: 0.85,
+            scale: 1.0,
+            fallback_color: Color::from_rgb8(245, 245, 245),
+            fallback_outline_color: Color::from_rgba8(0, 0, 0, 60),
+        }
+    }
+}
+
+/// A set of [`DragList`]s that items can be dragged between.
+///
+/// Lists are numbered in the order they are created with the group, starting at 0. These numbers identify the
+/// lists in [`DragListMove`].
+#[derive(Clone, Default)]
+pub struct DragListGroup {
+    inner: Rc<RefCell<DragListGroupState>>,
+}
+
+impl DragListGroup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Default)]
+struct DragListGroupState {
+    lists: Vec<DragListEntry>,
+    drag: Option<Drag>,
+}
+
+/// The geometry of a list from its last layout, so drop targets can be found without borrowing the other lists.
+struct DragListEntry {
+    list: Weak<RefCell<dyn ElementInternals>>,
+    bounds: Rectangle,
+    items: Vec<Rectangle>,
+}
+
+/// An item being dragged.
+struct Drag {
+    source_list: usize,
+    source_index: usize,
+    item: Rc<RefCell<dyn ElementInternals>>,
+    item_rectangle: Rectangle,
+    start: Point,
+    pointer: Point,
+    /// False until the pointer moves past [`DRAG_THRESHOLD`], so clicks on items still work.
+    is_active: bool,
+    target: Option<DropTarget>,
+    /// Drawn under the pointer, once the drag is active.
+    ghost: Option<GhostImage>,
+}
+
+/// What is drawn for a dragged item.
+enum GhostImage {
+    Snapshot(ResourceId),
+    Fallback,
+}
+
+/// Where a dragged item would be dropped: the list and the index among the items that aren't dragged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct DropTarget {
+    list: usize,
+    index: usize,
+}
+
+impl Drag {
+    /// The distance the item has been dragged.
+    fn delta(&self) -> (f64, f64) {
+        (self.pointer.x - self.start.x, self.pointer.y - self.start.y)
+    }
+}
+
+impl DragListGroupState {
+    /// Returns the list under the pointer and the index the dragged item would be inserted at.
+    fn drop_target(&self) -> Option<DropTarget> {
+        let drag = self.drag.as_ref()?;
+        let list = self.lists.iter().position(|entry| entry.bounds.contains(&drag.pointer))?;
+
+        let center_y = drag.item_rectangle.y + drag.item_rectangle.height / 2.0 + drag.delta().1 as f32;
+        let skip = (list == drag.source_list).then_some(drag.source_index);
+        let index = insertion_index(&self.lists[list].items, skip, center_y);
+
+        Some(DropTarget { list, index })
+    }
+}
+
+/// A vertical list whose items can be dragged to reorder them, or to move them to another list of the same
+/// [`DragListGroup`], e.g. the columns of a kanban board.
+///
+/// While an item is dragged, the other items slide apart to open a gap where it would be dropped. Dropping the item
+/// moves its element and sends a [`EventKind::DragListItemMoved`] event to it, which bubbles up through the
+/// destination list.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::rc::Rc;
+/// use craft_retained::elements::{Container, DragList, DragListGroup, Element, Text, Window};
+/// use craft_retained::{CraftOptions, craft_main};
+///
+/// fn main() {
+///     let group = DragListGroup::new();
+///     Window::new("Board").push(
+///         Container::new()
+///             .push(DragList::new(&group).push(Text::new("Write docs").selectable(false)))
+///             .push(DragList::new(&group).push(Text::new("Fix bug").selectable(false)))
+///             .on_drag_list_item_moved(Rc::new(|_event, moved| println!("{moved:?}"))),
+///     );
+///     craft_main(CraftOptions::basic("Board"));
+/// }
+/// ```
+#[derive(Clone)]
+pub struct DragList {
+    pub inner: Rc<RefCell<DragListInner>>,
+}
+
+#[derive(Clone)]
+pub struct DragListInner {
+    element_data: ElementData,
+    group: DragListGroup,
+    list_index: usize,
+    /// The current vertical offset of each item, animated towards the offsets that open the drop gap.
+    offsets: Vec<f32>,
+    last_frame: Option<Instant>,
+    placeholder_color: Option<Color>,
+    ghost: DragGhost,
+}
+
+impl Element for DragList {}
+
+impl Drop for DragListInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for DragList {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for DragListInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for DragListInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        let clone = self.deep_clone_internal();
+
+        // The clone is another list of the group, so it needs its own number.
+        let mut group = self.group.inner.borrow_mut();
+        let list_index = group.lists.len();
+        group.lists.push(DragListEntry {
+            list: Rc::downgrade(&clone),
+            bounds: Rectangle::default(),
+            items: Vec::new(),
+        });
+        if let Some(list) = clone.borrow_mut().as_any_mut().downcast_mut::<DragListInner>() {
+            list.list_index = list_index;
+            list.offsets.clear();
+        }
+        drop(group);
+
+        clone
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+
+        let bounds = self.element_data.layout.computed_box_transformed.border_rectangle();
+        let items = self.item_rectangles();
+        if let Some(entry) = self.group.inner.borrow_mut().lists.get_mut(self.list_index) {
+            entry.bounds = bounds;
+            entry.items = items;
+        }
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        draw_generic_container(self, renderer, resource_manager, text_context, scale_factor);
+    }
+
+    fn draw_children(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        self.update_ghost(renderer, resource_manager.clone(), scale_factor, text_context);
+
+        let group = self.group.inner.clone();
+        let group = group.borrow();
+        let drag = group.drag.as_ref().filter(|drag| drag.is_active);
+        let item_count = self.element_data.children.len();
+
+        let source = drag.filter(|drag| drag.source_list == self.list_index).map(|drag| drag.source_index);
+        let target = drag
+            .and_then(|drag| drag.target)
+            .filter(|target| target.list == self.list_index);
+        let shift = drag.map_or(0.0, |drag| drag.item_rectangle.height + self.row_gap());
+        self.animate_offsets(&target_offsets(item_count, source, target.map(|target| target.index), shift));
+
+        if let Some(target) = target
+            && let Some(placeholder_color) = self.placeholder_color
+        {
+            let placeholder = self.placeholder_rectangle(source, target.index, shift, drag.unwrap().item_rectangle.height);
+            renderer.draw_rect(placeholder.scale(scale_factor), placeholder_color);
+        }
+
+        let old_transform = renderer.get_transform();
+        for (index, child) in self.children().iter().enumerate() {
+            if source == Some(index) {
+                continue;
+            }
+
+            let mut child = child.borrow_mut();
+            if child.element_data().is_mask || child.is_outside_viewport(renderer, scale_factor) {
+                continue;
+            }
+
+            let offset = self.offsets.get(index).copied().unwrap_or(0.0) as f64;
+            renderer.set_transform(Affine::translate((0.0, offset * scale_factor)) * old_transform);
+            child.draw(renderer, resource_manager.clone(), scale_factor, text_context);
+        }
+
+        renderer.set_transform(old_transform);
+
+        // Draw the dragged item under the pointer, above every list.
+        if let Some(drag) = drag
+            && source.is_some()
+        {
+            let ghost_rectangle =
+                ghost_rectangle(drag.item_rectangle, drag.delta(), drag.pointer, self.ghost.scale).scale(scale_factor);
+            renderer.start_overlay();
+            match &drag.ghost {
+                Some(GhostImage::Snapshot(resource_id)) => renderer.draw_image(ghost_rectangle, resource_id.clone()),
+                _ => {
+                    let opacity = self.ghost.opacity;
+                    renderer.draw_rect(ghost_rectangle, self.ghost.fallback_color.multiply_alpha(opacity));
+                    renderer.draw_rect_outline(
+                        ghost_rectangle,
+                        self.ghost.fallback_outline_color.multiply_alpha(opacity),
+                        scale_factor,
+                    );
+                }
+            }
+            renderer.end_overlay();
+        }
+    }
+
+    #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+    fn compute_accessibility_tree(&mut self, tree: &mut TreeUpdate, parent_index: Option<usize>, scale_factor: f64) {
+        let current_node_id = accesskit::NodeId(self.element_data().internal_id);
+        let current_node = accesskit::Node::new(Role::List);
+
+        add_generic_accesskit_data(
+            &mut self.element_data,
+            current_node,
+            current_node_id,
+            tree,
+            parent_index,
+            scale_factor,
+        );
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        _target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        scrollable::handle_scroll_logic(self, message, event);
+
+        match message {
+            EventKind::PointerButtonDown(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                let pointer_position = pointer_button.state.logical_point();
+                let items = self.item_rectangles();
+                let Some(index) = items.iter().position(|item| item.contains(&pointer_position)) else {
+                    return;
+                };
+
+                self.group.inner.borrow_mut().drag = Some(Drag {
+                    source_list: self.list_index,
+                    source_index: index,
+                    item: self.element_data.children[index].clone(),
+                    item_rectangle: items[index],
+                    start: pointer_position,
+                    pointer: pointer_position,
+                    is_active: false,
+                    target: None,
+                    ghost: None,
+                });
+            }
+            EventKind::PointerMovedEvent(pointer_update) => {
+                let mut group = self.group.inner.borrow_mut();
+                let Some(drag) = group.drag.as_mut().filter(|drag| drag.source_list == self.list_index) else {
+                    return;
+                };
+
+                drag.pointer = pointer_update.current.logical_point();
+                let (dx, dy) = drag.delta();
+                if !drag.is_active {
+                    if dx.hypot(dy) < DRAG_THRESHOLD {
+                        return;
+                    }
+
+                    // Capture the pointer only once dragging starts, so that clicks still reach the items.
+                    drag.is_active = true;
+                    self.capture_pointer();
+                }
+
+                let target = group.drop_target();
+                group.drag.as_mut().unwrap().target = target;
+            }
+            EventKind::PointerButtonUp(_) => {
+                let drag = {
+                    let mut group = self.group.inner.borrow_mut();
+                    if group.drag.as_ref().is_none_or(|drag| drag.source_list != self.list_index) {
+                        return;
+                    }
+                    group.drag.take().unwrap()
+                };
+                self.release_pointer();
+                self.offsets.clear();
+
+                if drag.is_active
+                    && let Some(target) = drag.target
+                {
+                    self.move_item(drag, target);
+                }
+            }
+            EventKind::LostPointerCapture() => {
+                let mut group = self.group.inner.borrow_mut();
+                if group.drag.as_ref().is_some_and(|drag| drag.source_list == self.list_index) {
+                    group.drag = None;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn apply_clip(&mut self, clip_bounds: Option<Rectangle>) {
+        let overflow = self.style().get_overflow();
+        if overflow[0] == Overflow::Scroll || overflow[1] == Overflow::Scroll {
+            resolve_clip_for_scrollable(self, clip_bounds);
+        } else {
+            self.element_data.layout.apply_clip(clip_bounds);
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl DragListInner {
+    fn item_rectangles(&self) -> Vec<Rectangle> {
+        self.children()
+            .iter()
+            .map(|child| child.borrow().element_data().layout.computed_box_transformed.border_rectangle())
+            .collect()
+    }
+
+    fn row_gap(&self) -> f32 {
+        match self.style().get_gap()[1] {
+            Unit::Px(gap) => gap,
+            _ => 0.0,
+        }
+    }
+
+    /// Moves the current offsets towards `targets`, requesting more frames until they are reached.
+    fn animate_offsets(&mut self, targets: &[f32]) {
+        let now = Instant::now();
+        let elapsed = self
+            .last_frame
+            .map_or(0.0, |last_frame| now.duration_since(last_frame).as_secs_f32().min(0.1));
+        self.last_frame = Some(now);
+
+        self.offsets.resize(targets.len(), 0.0);
+        let progress = 1.0 - (-elapsed * REFLOW_SPEED).exp();
+        for (offset, target) in self.offsets.iter_mut().zip(targets) {
+            if (*target - *offset).abs() < 0.5 {
+                *offset = *target;
+            } else {
+                *offset += (*target - *offset) * progress;
+                request_animation_frame();
+            }
+        }
+    }
+
+    /// The gap the dragged item would be dropped into, at `index` among the items that aren't dragged.
+    fn placeholder_rectangle(&self, source: Option<usize>, index: usize, shift: f32, height: f32) -> Rectangle {
+        let content = self.element_data.layout.computed_box_transformed.content_rectangle();
+
+        // Where the remaining items rest once the dragged item has left its slot.
+        let resting: Vec<Rectangle> = self
+            .item_rectangles()
+            .into_iter()
+            .enumerate()
+            .filter(|(item, _)| Some(*item) != source)
+            .map(|(item, mut rectangle)| {
+                if source.is_some_and(|source| item > source) {
+                    rectangle.y -= shift;
+                }
+                rectangle
+            })
+            .collect();
+
+        let y = match (resting.get(index), resting.last()) {
+            (Some(next), _) => next.y,
+            (None, Some(last)) => last.y + last.height + self.row_gap(),
+            (None, None) => content.y,
+        };
+
+        Rectangle::new(content.x, y, content.width, height)
+    }
+
+    /// Takes the image of the item dragged from this list once the drag starts.
+    fn update_ghost(
+        &self,
+        renderer: &mut dyn Renderer,
+        resource_manager: Arc<ResourceManager>,
+        scale_factor: f64,
+        text_context: &mut TextContext,
+    ) {
+        let item = match &self.group.inner.borrow().drag {
+            Some(drag) if drag.is_active && drag.source_list == self.list_index && drag.ghost.is_none() => {
+                drag.item.clone()
+            }
+            _ => return,
+        };
+
+        let snapshot = if self.ghost.snapshot {
+            // Taken at the ghost's size, so it stays sharp when scaled up.
+            let scale_factor = scale_factor * self.ghost.scale as f64;
+            capture_element(&mut *item.borrow_mut(), renderer, resource_manager.clone(), scale_factor, text_context)
+        } else {
+            None
+        };
+        let ghost = match snapshot {
+            Some(mut image) => {
+                fade(&mut image, self.ghost.opacity);
+                let resource_id = ResourceId::generate();
+                let resource = Resource {
+                    resource_type: ResourceType::Image,
+                    data: Box::new(ImageResource { image }),
+                    expiration_time: None,
+                };
+                resource_manager.insert(resource_id.clone(), Arc::new(resource));
+                GhostImage::Snapshot(resource_id)
+            }
+            None => GhostImage::Fallback,
+        };
+
+        if let Some(drag) = self.group.inner.borrow_mut().drag.as_mut() {
+            drag.ghost = Some(ghost);
+        }
+    }
+
+    /// Moves the dragged item from this list to the drop target, and notifies the item.
+    fn move_item(&mut self, drag: Drag, target: DropTarget) {
+        if target.list == self.list_index && target.index == drag.source_index {
+            return;
+        }
+
+        let Ok(item) = self.remove_child(drag.item.clone()) else {
+            return;
+        };
+
+        if target.list == self.list_index {
+            insert_child_to_element(self, item.clone(), target.index);
+        } else {
+            let destination = self.group.inner.borrow().lists[target.list].list.upgrade();
+            let Some(destination) = destination else {
+                return;
+            };
+            let mut destination = destination.borrow_mut();
+            if let Some(list) = destination.as_any_mut().downcast_mut::<DragListInner>() {
+                list.offsets.clear();
+            }
+            insert_child_to_element(&mut *destination, item.clone(), target.index);
+        }
+
+        queue_event(
+            Event::new(item),
+            EventKind::DragListItemMoved(DragListMove {
+                source_list: drag.source_list,
+                source_index: drag.source_index,
+                destination_list: target.list,
+                destination_index: target.index,
+            }),
+        );
+    }
+}
+
+impl DragList {
+    /// Creates a list that exchanges items with the other lists of `group`.
+    pub fn new(group: &DragListGroup) -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<DragListInner>>| {
+            let mut group_state = group.inner.borrow_mut();
+            let list_index = group_state.lists.len();
+            group_state.lists.push(DragListEntry {
+                list: me.clone(),
+                bounds: Rectangle::default(),
+                items: Vec::new(),
+            });
+
+            RefCell::new(DragListInner {
+                element_data: ElementData::new(me.clone(), true),
+                group: group.clone(),
+                list_index,
+                offsets: Vec::new(),
+                last_frame: None,
+                placeholder_color: Some(Color::from_rgba8(0, 0, 0, 20)),
+                ghost: DragGhost::default(),
+            })
+        });
+
+        inner.borrow_mut().element_data.create_layout_node(None);
+        inner.borrow_mut().element_data.style.set_display(Display::Flex);
+        inner
+            .borrow_mut()
+            .element_data
+            .style
+            .set_flex_direction(FlexDirection::Column);
+
+        Self { inner }
+    }
+
+    /// The color of the gap where the dragged item would be dropped, or `None` to leave it empty.
+    pub fn placeholder_color(self, placeholder_color: Option<Color>) -> Self {
+        self.inner.borrow_mut().placeholder_color = placeholder_color;
+        self
+    }
+
+    /// How the item dragged from this list is drawn under the pointer.
+    pub fn drag_ghost(self, drag_ghost: DragGhost) -> Self {
+        self.inner.borrow_mut().ghost = drag_ghost;
+        self
+    }
+
+    pub fn get_drag_ghost(&self) -> DragGhost {
+        self.inner.borrow().ghost
+    }
+
+    /// The number of this list in its group.
+    pub fn get_list_index(&self) -> usize {
+        self.inner.borrow().list_index
+    }
+}
+
+/// Returns the index, among the items other than `skip`, before which an item centered at `center_y` is inserted.
+fn insertion_index(items: &[Rectangle], skip: Option<usize>, center_y: f32) -> usize {
+    items
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| Some(*index) != skip)
+        .filter(|(_, item)| item.y + item.height / 2.0 < center_y)
+        .count()
+}
+
+/// Returns where the ghost of an item dragged by `delta` is drawn, scaled by `scale` around the pointer.
+fn ghost_rectangle(item: Rectangle, delta: (f64, f64), pointer: Point, scale: f32) -> Rectangle {
+    let (pointer_x, pointer_y) = (pointer.x as f32, pointer.y as f32);
+    let x = item.x + delta.0 as f32;
+    let y = item.y + delta.1 as f32;
+    Rectangle::new(
+        pointer_x + (x - pointer_x) * scale,
+        pointer_y + (y - pointer_y) * scale,
+        item.width * scale,
+        item.height * scale,
+    )
+}
+
+/// Multiplies the alpha of an image by `opacity`.
+fn fade(image: &mut RgbaImage, opacity: f32) {
+    let opacity = opacity.clamp(0.0, 1.0);
+    for pixel in image.pixels_mut() {
+        pixel.0[3] = (pixel.0[3] as f32 * opacity).round() as u8;
+    }
+}
+
+/// Returns the vertical offset of each of `len` items that closes the slot of the dragged item at `source`, and
+/// opens a gap of `shift` at `insertion`, an index among the other items.
+fn target_offsets(len: usize, source: Option<usize>, insertion: Option<usize>, shift: f32) -> Vec<f32> {
+    let mut remaining = 0;
+    (0..len)
+        .map(|index| {
+            if source == Some(index) {
+                return 0.0;
+            }
+
+            let mut offset = 0.0;
+            if source.is_some_and(|source| index > source) {
+                offset -= shift;
+            }
+            if insertion.is_some_and(|insertion| remaining >= insertion) {
+                offset += shift;
+            }
+            remaining += 1;
+            offset
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(count: usize) -> Vec<Rectangle> {
+        (0..count).map(|row| Rectangle::new(0.0, row as f32 * 10.0, 100.0, 10.0)).collect()
+    }
+
+    #[test]
+    fn insertion_index_skips_the_dragged_item() {
+        let items = rows(4);
+
+        assert_eq!(insertion_index(&items, None, 0.0), 0);
+        assert_eq!(insertion_index(&items, None, 16.0), 2);
+        assert_eq!(insertion_index(&items, Some(0), 16.0), 1);
+        assert_eq!(insertion_index(&items, Some(3), 100.0), 3);
+    }
+
+    #[test]
+    fn ghosts_scale_around_the_pointer() {
+        let item = Rectangle::new(0.0, 0.0, 100.0, 10.0);
+
+        assert_eq!(
+            ghost_rectangle(item, (5.0, 20.0), Point::new(10.0, 25.0), 1.0),
+            Rectangle::new(5.0, 20.0, 100.0, 10.0)
+        );
+        assert_eq!(
+            ghost_rectangle(item, (0.0, 0.0), Point::new(10.0, 5.0), 2.0),
+            Rectangle::new(-10.0, -5.0, 200.0, 20.0)
+        );
+    }
+
+    #[test]
+    fn offsets_open_a_gap_in_the_destination() {
+        assert_eq!(target_offsets(3, None, Some(1), 10.0), vec![0.0, 10.0, 10.0]);
+        assert_eq!(target_offsets(3, None, None, 10.0), vec![0.0; 3]);
+    }
+
+    #[test]
+    fn offsets_close_the_slot_of_the_dragged_item() {
+        // Dragging the first item out of the list.
+        assert_eq!(target_offsets(3, Some(0), None, 10.0), vec![0.0, -10.0, -10.0]);
+        // Moving the first item to the end of the same list.
+        assert_eq!(target_offsets(3, Some(0), Some(2), 10.0), vec![0.0, -10.0, -10.0]);
+        // Moving the last item to the start of the same list.
+        assert_eq!(target_offsets(3, Some(2), Some(0), 10.0), vec![10.0, 10.0, 0.0]);
+    }
+}
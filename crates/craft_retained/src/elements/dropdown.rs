@@ -4,7 +4,7 @@ use std::any::Any;
 use std::cell::{Ref, RefCell, RefMut};
 use std::rc::{Rc, Weak};
 use std::sync::Arc;
-use craft_primitives::geometry::{BezPath, Rectangle, TrblRectangle};
+use craft_primitives::geometry::{BezPath, Rectangle, Size, TrblRectangle};
 
 use craft_renderer::Brush;
 
@@ -19,12 +19,12 @@ use crate::elements::traits::DeepClone;
 use crate::elements::{resolve_clip_for_scrollable, AsElement, Element, ElementData, ElementInternals};
 use crate::events::{Event, EventKind};
 use crate::layout::layout::Layout;
+use crate::layout::popup::{layout_viewport, position_popup, PopupOptions};
 use crate::layout::TaffyTree;
-use crate::style::{AlignItems, BoxShadow, Display, FlexDirection, Overflow, Position, Style, Unit};
+use crate::style::{AlignItems, CornerRadius, Display, FlexDirection, Overflow, Position, Style, Unit};
 use crate::text::text_context::TextContext;
 use crate::{auto, px, rgba};
 use craft_renderer::renderer::Renderer;
-use ui_events::pointer::PointerId;
 use craft_resource_manager::ResourceManager;
 
 /// An element to select a single item from a collapsable vertical list of options.
@@ -71,6 +71,8 @@ pub struct DropdownInner {
     selected_element_index: Option<usize>,
     currently_hovered_element: Option<usize>,
     hovered_bg_color: Option<Color>,
+    /// How the list is positioned relative to the dropdown.
+    popup_options: PopupOptions,
 }
 
 impl Default for Dropdown {
@@ -156,11 +158,7 @@ impl ElementInternals for DropdownInner {
             taffy_tree.mark_seen(selected_element.borrow().element_data().layout.taffy_node_id());
         }
 
-        // Position the floating window below the dropdown with a gap.
-        let floating_window_position = Point::new(
-            self.element_data.layout.computed_box.position.x,
-            self.element_data.layout.computed_box.position.y + self.element_data.layout.computed_box.size.height as f64,
-        );
+        let floating_window_position = self.floating_window_position(taffy_tree, transform);
 
         self.floating_window.apply_simple_layout(
             taffy_tree,
@@ -312,9 +310,9 @@ impl ElementInternals for DropdownInner {
             request_apply_layout(self.element_data.layout.taffy_node_id.unwrap());
         }
         if result.set_pointer_capture {
-            self.set_pointer_capture(PointerId::new(1).unwrap())
+            self.capture_pointer()
         } else if result.release_pointer_capture {
-            self.release_pointer_capture(PointerId::new(1).unwrap());
+            self.release_pointer();
         }
     }
 
@@ -322,6 +320,10 @@ impl ElementInternals for DropdownInner {
         resolve_clip_for_scrollable(self, clip_bounds);
     }
 
+    fn supports_children(&self) -> bool {
+        true
+    }
+
     fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
         let me: Weak<RefCell<dyn ElementInternals>> = self.element_data.me.clone();
         let me_window = self.element_data.window.clone();
@@ -379,6 +381,10 @@ impl ElementInternals for DropdownInner {
         }
     }
 
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -427,11 +433,19 @@ impl Shape {
             // Refactor START
             let current_style = &self.style;
             let has_border = current_style.has_border();
-            let border_radius = current_style.get_border_radius();
+            let border_rectangle = self.layout.computed_box_transformed.border_rectangle();
+            let border_radius = current_style.get_resolved_border_radius(border_rectangle.width, border_rectangle.height);
             let border_color = &current_style.get_border_color();
-            let box_shadows = current_style.get_box_shadows();
-            self.layout
-                .apply_borders(has_border, border_radius, scale_factor, *border_color, box_shadows.to_vec());
+            let box_shadows = current_style.get_computed_box_shadows();
+            let pixel_snapping = current_style.get_pixel_snapping();
+            self.layout.apply_borders(
+                has_border,
+                border_radius,
+                scale_factor,
+                *border_color,
+                box_shadows,
+                pixel_snapping,
+            );
             // Refactor END
 
             // For scroll changes from taffy;
@@ -462,12 +476,13 @@ impl Dropdown {
                 selected_element_index: None,
                 currently_hovered_element: None,
                 hovered_bg_color: Some(Color::from_rgba8(213, 213, 215, 255)),
+                popup_options: PopupOptions::default(),
             })
         });
 
         let border_color = rgba(0, 0, 0, 64);
         let border_width = px(1.0);
-        let border_radius = [(5.0, 5.0); 4];
+        let border_radius = [CornerRadius::from((5.0, 5.0)); 4];
 
         inner.borrow_mut().element_data.create_layout_node(None);
         inner.borrow_mut().element_data.style.set_display(Display::Flex);
@@ -514,7 +529,7 @@ impl Dropdown {
             .borrow_mut()
             .floating_window
             .style
-            .set_box_shadows(vec![BoxShadow::new(false, 0.0, 4.0, 8.0, 1.0, rgba(0, 0, 0, 255))]);
+            .set_elevation(8.0);
         inner
             .borrow_mut()
             .floating_window
@@ -577,12 +592,40 @@ impl Dropdown {
         self
     }
 
+    /// Sets how the list is positioned relative to the dropdown. By default it opens below, and flips above when
+    /// there is more room there.
+    pub fn popup_options(self, popup_options: PopupOptions) -> Self {
+        self.inner.borrow_mut().popup_options = popup_options;
+        self
+    }
+
     pub fn get_selected_item(self) -> usize {
         self.inner.borrow().selected_element_index.unwrap()
     }
 }
 
 impl DropdownInner {
+    /// Positions the floating window below the dropdown, or where the popup options say, while keeping it in the
+    /// window.
+    fn floating_window_position(&self, taffy_tree: &TaffyTree, transform: Affine) -> Point {
+        let computed_box = &self.element_data.layout.computed_box;
+        let below = Point::new(computed_box.position.x, computed_box.position.y + computed_box.size.height as f64);
+        let Some(viewport) = layout_viewport() else {
+            return below;
+        };
+
+        let floating_window_layout = taffy_tree.get_layout(self.floating_window.layout.taffy_node_id());
+        let size = Size {
+            width: floating_window_layout.size.width,
+            height: floating_window_layout.size.height,
+        };
+        // The viewport is in window coordinates, so the popup is positioned around the transformed (e.g. scrolled)
+        // dropdown, and then moved back into its untransformed coordinates.
+        let anchor = self.element_data.layout.computed_box_transformed.border_rectangle();
+        let popup = position_popup(anchor, size, viewport, &self.popup_options);
+        transform.inverse() * popup.position
+    }
+
     fn draw_selected_element(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, text_context: &mut TextContext, scale_factor: f64) {
         if let Some(selected_element) = &self.selected_element {
             let mut binding = selected_element.borrow_mut();
@@ -649,8 +692,9 @@ impl DropdownInner {
                     None
                 });
 
-                if let Some(hovered_child) = hovered_child {
-                    self.currently_hovered_element = Some(hovered_child);
+                if hovered_child.is_some() && hovered_child != self.currently_hovered_element {
+                    self.currently_hovered_element = hovered_child;
+                    self.invalidate_layer_cache();
                 }
             }
         }
@@ -662,7 +706,7 @@ impl DropdownInner {
             self.currently_hovered_element = self.selected_element_index;
 
             if self.is_floating_window_hidden {
-                self.release_pointer_capture(PointerId::new(1).unwrap());
+                self.release_pointer();
             }
         }
     }
@@ -694,7 +738,7 @@ impl DropdownInner {
                 if contains {
                     should_hide_window = true;
                     self.set_selected_element(child_index);
-                    self.release_pointer_capture(PointerId::new(1).unwrap());
+                    self.release_pointer();
 
                     let new_event = Event::new(event.target.clone());
                     queue_event(new_event, EventKind::DropdownItemSelected(child_index));
@@ -1,13 +1,17 @@
+use std::any::Any;
 use std::cell::RefCell;
 use std::rc::{Rc, Weak};
 
+use craft_resource_manager::ResourceId;
 use smol_str::SmolStr;
 
 use crate::app::{ELEMENTS, TAFFY_TREE};
 use crate::elements::element_id::create_unique_element_id;
+use crate::elements::layer_cache::LayerCache;
 use crate::elements::scrollable::{ScrollState, apply_scroll_layout};
 use crate::elements::{ElementInternals, WindowInternal};
-use crate::events::{CheckboxToggledHandler, DropdownItemSelectedHandler, KeyboardInputHandler, PointerCaptureHandler, PointerEnterHandler, PointerEventHandler, PointerLeaveHandler, PointerUpdateHandler, RadioValueChangedHandler, ScrollHandler, SliderValueChangedHandler, TextInputChangedHandler};
+use crate::events::{CellEditedHandler, CheckboxToggledHandler, ColorPickedHandler, DragListItemMovedHandler, DropdownItemSelectedHandler, DurationChangedHandler, ElementCapturedHandler, FilesChangedHandler, FilesDroppedHandler, FilesSelectedHandler, HoverIntent, HoverIntentEndedHandler, HoverIntentHandler, HttpStreamHandler, ImageCropChangedHandler, JumpListActivatedHandler, KeyboardInputHandler, KeyringHandler, KioskResetHandler, ListBoxSelectionChangedHandler, MapClickedHandler, NetworkStatusChangedHandler, PdfPageChangedHandler, PointerCaptureHandler, PointerEnterHandler, PointerEventHandler, PointerLeaveHandler, PointerUpdateHandler, PowerStatusChangedHandler, ProcessHandler, PropertyChangedHandler, RadioValueChangedHandler, RatingChangedHandler, RecentFilesChangedHandler, ScrollHandler, SegmentedInputChangedHandler, SelectionAreaChangedHandler, SliderValueChangedHandler, SpeechFinishedHandler, SpellingSuggestionsHandler, StepperFinishedHandler, StepperStepChangedHandler, SystemSettingsChangedHandler, TagsChangedHandler, TextInputChangedHandler, TimeChangedHandler, UpdateAvailableHandler, UpdateDownloadedHandler, UserActiveHandler, UserIdleHandler, ViewportChangedHandler, WebSocketHandler};
+use crate::layout::custom_layout::CustomLayoutState;
 use crate::layout::layout::Layout;
 use crate::layout::layout_context::LayoutContext;
 use crate::style::{Overflow, Style};
@@ -39,11 +43,72 @@ pub struct ElementData {
     /// A unique id for this element. Within a craft app the id will be unique even across windows.
     pub(crate) internal_id: u64,
 
+    /// The offscreen layer the element and its subtree are drawn into, if the element is cached as a layer. See
+    /// [`Element::cache_as_layer`](crate::elements::Element::cache_as_layer).
+    pub(crate) layer_cache: Option<LayerCache>,
+
+    /// An image whose alpha masks the element.
+    pub(crate) mask_image: Option<ResourceId>,
+
+    /// Whether the element is the mask of its parent rather than a regular child.
+    pub(crate) is_mask: bool,
+
+    /// When the pointer counts as resting on the element, for hover intent events.
+    pub(crate) hover_intent: HoverIntent,
+
+    /// Data the app associates with the element, like the id of the record a row shows.
+    pub(crate) user_data: Option<Rc<dyn Any>>,
+
+    /// Whether the element can be focused with spatial navigation, see [`crate::spatial_navigation`].
+    pub(crate) focusable: bool,
+
+    /// Places the children instead of taffy, see [`crate::layout::custom_layout`].
+    pub(crate) custom_layout: Option<CustomLayoutState>,
+
     // Events:
     pub on_dropdown_item_selected: Vec<DropdownItemSelectedHandler>,
+    pub on_drag_list_item_moved: Vec<DragListItemMovedHandler>,
+    pub on_list_box_selection_changed: Vec<ListBoxSelectionChangedHandler>,
+    pub on_selection_area_changed: Vec<SelectionAreaChangedHandler>,
     pub on_slider_value_changed: Vec<SliderValueChangedHandler>,
+    pub on_stepper_step_changed: Vec<StepperStepChangedHandler>,
+    pub on_stepper_finished: Vec<StepperFinishedHandler>,
+    pub on_property_changed: Vec<PropertyChangedHandler>,
+    pub on_cell_edited: Vec<CellEditedHandler>,
+    pub on_viewport_changed: Vec<ViewportChangedHandler>,
+    pub on_image_crop_changed: Vec<ImageCropChangedHandler>,
+    pub on_color_picked: Vec<ColorPickedHandler>,
+    pub on_map_clicked: Vec<MapClickedHandler>,
+    pub on_pdf_page_changed: Vec<PdfPageChangedHandler>,
+    pub on_element_captured: Vec<ElementCapturedHandler>,
+    pub on_rating_changed: Vec<RatingChangedHandler>,
+    pub on_tags_changed: Vec<TagsChangedHandler>,
+    pub on_segmented_input_changed: Vec<SegmentedInputChangedHandler>,
+    pub on_time_changed: Vec<TimeChangedHandler>,
+    pub on_duration_changed: Vec<DurationChangedHandler>,
+    pub on_files_dropped: Vec<FilesDroppedHandler>,
+    pub on_files_selected: Vec<FilesSelectedHandler>,
+    pub on_spelling_suggestions: Vec<SpellingSuggestionsHandler>,
+    pub on_speech_finished: Vec<SpeechFinishedHandler>,
+    pub on_web_socket: Vec<WebSocketHandler>,
+    pub on_files_changed: Vec<FilesChangedHandler>,
+    pub on_process: Vec<ProcessHandler>,
+    pub on_keyring: Vec<KeyringHandler>,
+    pub on_http_stream: Vec<HttpStreamHandler>,
+    pub on_system_settings_changed: Vec<SystemSettingsChangedHandler>,
+    pub on_user_idle: Vec<UserIdleHandler>,
+    pub on_user_active: Vec<UserActiveHandler>,
+    pub on_kiosk_reset: Vec<KioskResetHandler>,
+    pub on_power_status_changed: Vec<PowerStatusChangedHandler>,
+    pub on_network_status_changed: Vec<NetworkStatusChangedHandler>,
+    pub on_jump_list_activated: Vec<JumpListActivatedHandler>,
+    pub on_recent_files_changed: Vec<RecentFilesChangedHandler>,
+    pub on_update_available: Vec<UpdateAvailableHandler>,
+    pub on_update_downloaded: Vec<UpdateDownloadedHandler>,
     pub on_pointer_enter: Vec<PointerEnterHandler>,
     pub on_pointer_leave: Vec<PointerLeaveHandler>,
+    pub on_hover_intent: Vec<HoverIntentHandler>,
+    pub on_hover_intent_ended: Vec<HoverIntentEndedHandler>,
     pub on_got_pointer_capture: Vec<PointerCaptureHandler>,
     pub on_lost_pointer_capture: Vec<PointerCaptureHandler>,
     pub on_pointer_button_down: Vec<PointerEventHandler>,
@@ -67,10 +132,56 @@ impl ElementData {
             children: Default::default(),
             id: None,
             internal_id: create_unique_element_id(),
+            layer_cache: None,
+            mask_image: None,
+            is_mask: false,
+            hover_intent: HoverIntent::default(),
+            user_data: None,
+            focusable: false,
+            custom_layout: None,
             on_dropdown_item_selected: Vec::new(),
+            on_drag_list_item_moved: Vec::new(),
+            on_list_box_selection_changed: Vec::new(),
+            on_selection_area_changed: Vec::new(),
             on_slider_value_changed: Vec::new(),
+            on_stepper_step_changed: Vec::new(),
+            on_stepper_finished: Vec::new(),
+            on_property_changed: Vec::new(),
+            on_cell_edited: Vec::new(),
+            on_viewport_changed: Vec::new(),
+            on_image_crop_changed: Vec::new(),
+            on_color_picked: Vec::new(),
+            on_map_clicked: Vec::new(),
+            on_pdf_page_changed: Vec::new(),
+            on_element_captured: Vec::new(),
+            on_rating_changed: Vec::new(),
+            on_tags_changed: Vec::new(),
+            on_segmented_input_changed: Vec::new(),
+            on_time_changed: Vec::new(),
+            on_duration_changed: Vec::new(),
+            on_files_dropped: Vec::new(),
+            on_files_selected: Vec::new(),
+            on_spelling_suggestions: Vec::new(),
+            on_speech_finished: Vec::new(),
+            on_web_socket: Vec::new(),
+            on_files_changed: Vec::new(),
+            on_process: Vec::new(),
+            on_keyring: Vec::new(),
+            on_http_stream: Vec::new(),
+            on_system_settings_changed: Vec::new(),
+            on_user_idle: Vec::new(),
+            on_user_active: Vec::new(),
+            on_kiosk_reset: Vec::new(),
+            on_power_status_changed: Vec::new(),
+            on_network_status_changed: Vec::new(),
+            on_jump_list_activated: Vec::new(),
+            on_recent_files_changed: Vec::new(),
+            on_update_available: Vec::new(),
+            on_update_downloaded: Vec::new(),
             on_pointer_enter: Vec::new(),
             on_pointer_leave: Vec::new(),
+            on_hover_intent: Vec::new(),
+            on_hover_intent_ended: Vec::new(),
             on_got_pointer_capture: Vec::new(),
             on_lost_pointer_capture: Vec::new(),
             on_pointer_button_down: Vec::new(),
@@ -106,11 +217,19 @@ impl ElementData {
     pub fn apply_borders(&mut self, scale_factor: f64) {
         let current_style = self.style();
         let has_border = current_style.has_border();
-        let border_radius = current_style.get_border_radius();
+        let border_rectangle = self.layout.computed_box_transformed.border_rectangle();
+        let border_radius = current_style.get_resolved_border_radius(border_rectangle.width, border_rectangle.height);
         let border_color = current_style.get_border_color();
-        let box_shadows = current_style.get_box_shadows();
-        self.layout
-            .apply_borders(has_border, border_radius, scale_factor, border_color, box_shadows.to_vec());
+        let box_shadows = current_style.get_computed_box_shadows();
+        let pixel_snapping = current_style.get_pixel_snapping();
+        self.layout.apply_borders(
+            has_border,
+            border_radius,
+            scale_factor,
+            border_color,
+            box_shadows,
+            pixel_snapping,
+        );
     }
 
     /// Computes the scrollbar's tack and thumb layout.
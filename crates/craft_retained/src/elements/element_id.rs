@@ -1,4 +1,9 @@
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use smol_str::SmolStr;
+
+use crate::elements::ElementInternals;
 
 thread_local! {
     static THREAD_LOCAL_ELEMENT_ID: Cell<u64> = const { Cell::new(0) };
@@ -11,3 +16,126 @@ pub fn create_unique_element_id() -> u64 {
         id
     })
 }
+
+/// One step of the path from the root of a tree down to an element.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PathSegment {
+    /// The id set with [`Element::id`](crate::elements::Element::id).
+    Key(SmolStr),
+    /// The index among the children of the parent, for elements without an id.
+    Index(usize),
+}
+
+impl PathSegment {
+    pub(crate) fn of(element: &dyn ElementInternals, index: usize) -> Self {
+        match &element.element_data().id {
+            Some(id) => PathSegment::Key(id.clone()),
+            None => PathSegment::Index(index),
+        }
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Hashes paths with FNV-1a, which, unlike the hasher of the standard library, gives the same hash on every run,
+/// platform and Rust version.
+#[derive(Clone, Copy)]
+pub(crate) struct StableIdHasher(u64);
+
+impl Default for StableIdHasher {
+    fn default() -> Self {
+        Self(FNV_OFFSET_BASIS)
+    }
+}
+
+impl StableIdHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    pub(crate) fn push(&mut self, segment: &PathSegment) {
+        // The tag keeps the key "0" apart from the index 0.
+        match segment {
+            PathSegment::Key(key) => {
+                self.write(&[0]);
+                self.write(&(key.len() as u64).to_le_bytes());
+                self.write(key.as_bytes());
+            }
+            PathSegment::Index(index) => {
+                self.write(&[1]);
+                self.write(&(*index as u64).to_le_bytes());
+            }
+        }
+    }
+
+    pub(crate) fn finish(self) -> u64 {
+        self.0
+    }
+}
+
+/// Hashes a path from the root of a tree down to an element.
+pub(crate) fn stable_id_of_path(path: &[PathSegment]) -> u64 {
+    let mut hasher = StableIdHasher::default();
+    for segment in path {
+        hasher.push(segment);
+    }
+    hasher.finish()
+}
+
+/// Finds the element below `root`, or `root` itself, whose
+/// [`stable_id`](crate::elements::ElementData::stable_id) is `stable_id`.
+pub(crate) fn find_by_stable_id(
+    root: &Rc<RefCell<dyn ElementInternals>>,
+    stable_id: u64,
+) -> Option<Rc<RefCell<dyn ElementInternals>>> {
+    let mut hasher = StableIdHasher::default();
+    hasher.push(&PathSegment::of(&*root.try_borrow().ok()?, 0));
+    find_below(root, hasher, stable_id)
+}
+
+fn find_below(
+    element: &Rc<RefCell<dyn ElementInternals>>,
+    hasher: StableIdHasher,
+    stable_id: u64,
+) -> Option<Rc<RefCell<dyn ElementInternals>>> {
+    if hasher.finish() == stable_id {
+        return Some(element.clone());
+    }
+    let element_ref = element.try_borrow().ok()?;
+    for (index, child) in element_ref.children().iter().enumerate() {
+        let Ok(child_ref) = child.try_borrow() else {
+            continue;
+        };
+        let mut child_hasher = hasher;
+        child_hasher.push(&PathSegment::of(&*child_ref, index));
+        drop(child_ref);
+        if let Some(found) = find_below(child, child_hasher, stable_id) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_ids_depend_only_on_the_path() {
+        // The hash must not change between runs or releases, or saved ids would stop matching.
+        assert_eq!(stable_id_of_path(&[]), FNV_OFFSET_BASIS);
+        assert_eq!(stable_id_of_path(&[PathSegment::Index(0)]), 0x529a_2cdc_8ff5_33ac);
+        assert_ne!(
+            stable_id_of_path(&[PathSegment::Key("0".into())]),
+            stable_id_of_path(&[PathSegment::Index(0)])
+        );
+        assert_ne!(
+            stable_id_of_path(&[PathSegment::Key("ab".into()), PathSegment::Key("c".into())]),
+            stable_id_of_path(&[PathSegment::Key("a".into()), PathSegment::Key("bc".into())])
+        );
+    }
+}
@@ -41,4 +41,33 @@ impl ElementIdMap {
     pub fn get(&self, id: u64) -> Option<&Weak<RefCell<dyn ElementInternals>>> {
         self.map.get(&id)
     }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.map.capacity()
+    }
+
+    /// Removes the entries whose element was dropped, and returns their ids.
+    pub fn remove_dropped(&mut self) -> Vec<u64> {
+        let mut removed = Vec::new();
+        self.map.retain(|id, element| {
+            let alive = element.strong_count() > 0;
+            if !alive {
+                removed.push(*id);
+            }
+            alive
+        });
+        removed
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &Weak<RefCell<dyn ElementInternals>>)> {
+        self.map.iter().map(|(id, element)| (*id, element))
+    }
 }
@@ -0,0 +1,399 @@
+//! A file path picker.
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use craft_primitives::geometry::{Affine, Point, Rectangle};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use peniko::Color;
+use ui_events::pointer::PointerButton;
+
+use crate::app::queue_event;
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, draw_generic_container, is_target_within, push_child_to_element};
+use crate::elements::traits::DeepClone;
+use crate::elements::{resolve_clip_for_scrollable, AsElement, Container, Element, ElementInternals, Text};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::{AlignItems, Display, FlexDirection, JustifyContent, Overflow};
+use crate::text::text_context::TextContext;
+use crate::{px, rgb};
+
+const BORDER_COLOR: Color = rgb(199, 199, 206);
+const DROP_TARGET_BORDER_COLOR: Color = rgb(37, 99, 235);
+const PLACEHOLDER_COLOR: Color = rgb(120, 120, 120);
+const HAS_FILE_DIALOG: bool = cfg!(all(feature = "file_dialog", any(target_os = "windows", target_os = "macos", target_os = "linux")));
+
+thread_local! {
+    /// The pickers highlighted as drop targets, so the highlight is cleared wherever the drag ends.
+    static DROP_TARGETS: RefCell<Vec<Weak<RefCell<dyn ElementInternals>>>> = const { RefCell::new(Vec::new()) };
+    /// The files picked in dialogs that were closed since the last poll, with the picker that opened them.
+    static PICKED_FILES: RefCell<Vec<(Weak<RefCell<dyn ElementInternals>>, Vec<PathBuf>)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A named group of file extensions accepted by a [`FilePicker`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileFilter {
+    pub name: String,
+    /// Extensions without the leading dot, such as `png`.
+    pub extensions: Vec<String>,
+}
+
+impl FileFilter {
+    pub fn new(name: &str, extensions: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            extensions: extensions.iter().map(|extension| extension.to_string()).collect(),
+        }
+    }
+
+    /// Returns true if the path has one of the extensions, ignoring case.
+    pub fn accepts(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                self.extensions
+                    .iter()
+                    .any(|accepted| accepted.eq_ignore_ascii_case(extension))
+            })
+    }
+}
+
+#[derive(Clone)]
+pub struct FilePicker {
+    pub inner: Rc<RefCell<FilePickerInner>>,
+}
+
+/// Shows the picked file paths with a button that opens the native file dialog.
+///
+/// Files dropped from the OS onto the picker are picked too. Only files accepted by one of the filters can be
+/// picked, unless there are no filters. A [`EventKind::FilesSelected`] event is sent when files are picked.
+///
+/// The browse button requires the `file_dialog` feature; without it files can only be dropped.
+#[derive(Clone)]
+pub struct FilePickerInner {
+    element_data: ElementData,
+    paths: Vec<PathBuf>,
+    filters: Vec<FileFilter>,
+    multiple: bool,
+    placeholder: String,
+    pub path_text: Text,
+    pub browse_button: Container,
+}
+
+impl Default for FilePicker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Element for FilePicker {}
+
+impl Drop for FilePickerInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for FilePicker {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for FilePickerInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for FilePickerInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        draw_generic_container(self, renderer, resource_manager, text_context, scale_factor);
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        let picked = match message {
+            EventKind::PointerButtonUp(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                if target.is_some_and(|target| is_target_within(&target, &self.browse_button)) {
+                    self.browse();
+                }
+                return;
+            }
+            EventKind::FileHovered(path) => {
+                self.set_drop_target_active(self.accepts(path));
+                return;
+            }
+            EventKind::FileHoverCancelled() => {
+                self.set_drop_target_active(false);
+                return;
+            }
+            EventKind::FilesDropped(paths) => {
+                self.set_drop_target_active(false);
+                paths.iter().filter(|path| self.accepts(path)).cloned().collect()
+            }
+            _ => return,
+        };
+
+        self.pick(picked, event.target.clone());
+    }
+
+    fn apply_clip(&mut self, clip_bounds: Option<Rectangle>) {
+        let overflow = self.style().get_overflow();
+        if overflow[0] == Overflow::Scroll || overflow[1] == Overflow::Scroll {
+            resolve_clip_for_scrollable(self, clip_bounds);
+        } else {
+            self.element_data.layout.apply_clip(clip_bounds);
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl FilePicker {
+    pub fn new() -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<FilePickerInner>>| {
+            RefCell::new(FilePickerInner {
+                element_data: ElementData::new(me.clone(), true),
+                paths: Vec::new(),
+                filters: Vec::new(),
+                multiple: false,
+                placeholder: "No file selected".to_string(),
+                path_text: Text::new("").selectable(false).flex_grow(1.0),
+                browse_button: Container::new()
+                    .display(if HAS_FILE_DIALOG { Display::Flex } else { Display::None })
+                    .justify_content(Some(JustifyContent::Center))
+                    .align_items(Some(AlignItems::Center))
+                    .padding(px(4), px(12), px(4), px(12))
+                    .border_radius_all(px(4))
+                    .background_color(rgb(230, 230, 235))
+                    .push(Text::new("Browse…").selectable(false)),
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        inner_mut.set_display(Display::Flex);
+        inner_mut.set_flex_direction(FlexDirection::Row);
+        inner_mut.set_align_items(Some(AlignItems::Center));
+        inner_mut.set_gap(px(8), px(8));
+        inner_mut.set_padding(px(4), px(4), px(4), px(8));
+        inner_mut.set_border_width_all(px(1));
+        inner_mut.set_border_radius_all(px(5).into());
+        inner_mut.set_border_color_all(BORDER_COLOR);
+
+        let path_text = inner_mut.path_text.clone();
+        inner_mut.push(path_text.inner);
+        let browse_button = inner_mut.browse_button.clone();
+        inner_mut.push(browse_button.inner);
+        inner_mut.update_path_text();
+
+        drop(inner_mut);
+        Self { inner }
+    }
+
+    /// Only accept files with one of the extensions. Can be called several times to add more filters.
+    pub fn filter(self, name: &str, extensions: &[&str]) -> Self {
+        self.inner.borrow_mut().filters.push(FileFilter::new(name, extensions));
+        self
+    }
+
+    /// Allow picking several files at once. Defaults to false.
+    pub fn multiple(self, multiple: bool) -> Self {
+        self.inner.borrow_mut().multiple = multiple;
+        self
+    }
+
+    /// Set the text shown when no file is picked.
+    pub fn placeholder(self, placeholder: &str) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.placeholder = placeholder.to_string();
+        inner.update_path_text();
+        drop(inner);
+        self
+    }
+
+    pub fn paths(self, paths: Vec<PathBuf>) -> Self {
+        self.inner.borrow_mut().set_paths(paths);
+        self
+    }
+
+    pub fn get_paths(&self) -> Vec<PathBuf> {
+        self.inner.borrow().paths.clone()
+    }
+}
+
+impl FilePickerInner {
+    pub fn set_paths(&mut self, paths: Vec<PathBuf>) {
+        self.paths = paths;
+        self.update_path_text();
+    }
+
+    fn accepts(&self, path: &Path) -> bool {
+        self.filters.is_empty() || self.filters.iter().any(|filter| filter.accepts(path))
+    }
+
+    /// Picks the files, if there are any, and sends a [`EventKind::FilesSelected`] event to `target`.
+    fn pick(&mut self, picked: Vec<PathBuf>, target: Rc<RefCell<dyn ElementInternals>>) {
+        let picked = if self.multiple { picked } else { picked.into_iter().take(1).collect() };
+        if picked.is_empty() {
+            return;
+        }
+        self.set_paths(picked);
+
+        queue_event(Event::new(target), EventKind::FilesSelected(self.paths.clone()));
+    }
+
+    fn set_drop_target_active(&mut self, is_active: bool) {
+        self.set_border_color_all(if is_active { DROP_TARGET_BORDER_COLOR } else { BORDER_COLOR });
+        if is_active {
+            let me = self.element_data.me.clone();
+            DROP_TARGETS.with_borrow_mut(|drop_targets| drop_targets.push(me));
+        }
+    }
+
+    fn update_path_text(&mut self) {
+        let (text, color) = if self.paths.is_empty() {
+            (self.placeholder.clone(), PLACEHOLDER_COLOR)
+        } else {
+            let names: Vec<String> = self.paths.iter().map(|path| path.display().to_string()).collect();
+            (names.join(", "), Color::BLACK)
+        };
+        self.path_text.clone().text(&text).color(color);
+    }
+
+    /// Opens the native file dialog. The event loop keeps running while it is open, and the files are picked by
+    /// [`poll_file_dialogs`] once it is closed.
+    #[cfg(all(feature = "file_dialog", any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    fn browse(&self) {
+        let mut dialog = rfd::AsyncFileDialog::new();
+        for filter in &self.filters {
+            dialog = dialog.add_filter(&filter.name, &filter.extensions);
+        }
+        if let Some(directory) = self.paths.first().and_then(|path| path.parent()) {
+            dialog = dialog.set_directory(directory);
+        }
+
+        let picker = self.element_data.me.clone();
+        let multiple = self.multiple;
+        craft_runtime::spawn_local(async move {
+            let files = if multiple {
+                dialog.pick_files().await.unwrap_or_default()
+            } else {
+                dialog.pick_file().await.into_iter().collect()
+            };
+            let paths = files.iter().map(|file| file.path().to_path_buf()).collect();
+            PICKED_FILES.with_borrow_mut(|picked_files| picked_files.push((picker, paths)));
+        });
+    }
+
+    #[cfg(not(all(feature = "file_dialog", any(target_os = "windows", target_os = "macos", target_os = "linux"))))]
+    fn browse(&self) {}
+}
+
+/// Picks the files of the dialogs that were closed. Returns true if any were picked.
+///
+/// Called once per iteration of the event loop.
+pub(crate) fn poll_file_dialogs() -> bool {
+    let picked_files = PICKED_FILES.take();
+    let mut picked_any = false;
+    for (picker, paths) in picked_files {
+        let Some(picker) = picker.upgrade() else {
+            continue;
+        };
+        if let Some(picker_inner) = picker.borrow_mut().as_any_mut().downcast_mut::<FilePickerInner>() {
+            picked_any |= !paths.is_empty();
+            picker_inner.pick(paths, picker.clone());
+        }
+    }
+    picked_any
+}
+
+/// Clears the highlight of the pickers files were dragged over. Called when the drag leaves a window or the files
+/// are dropped, wherever that happens.
+pub(crate) fn clear_drop_targets() {
+    for drop_target in DROP_TARGETS.take() {
+        if let Some(drop_target) = drop_target.upgrade()
+            && let Some(picker) = drop_target.borrow_mut().as_any_mut().downcast_mut::<FilePickerInner>()
+        {
+            picker.set_drop_target_active(false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_match_extensions_ignoring_case() {
+        let filter = FileFilter::new("Images", &["png", "jpg"]);
+
+        assert!(filter.accepts(Path::new("photo.JPG")));
+        assert!(filter.accepts(Path::new("/tmp/icon.png")));
+        assert!(!filter.accepts(Path::new("notes.txt")));
+        assert!(!filter.accepts(Path::new("png")));
+    }
+}
@@ -70,6 +70,10 @@ impl ElementInternals for ImageInner {
         self.deep_clone_internal()
     }
 
+    fn uses_resource(&self, resource_id: &ResourceId) -> bool {
+        self.resource_id == *resource_id || self.element_data.mask_image.as_ref() == Some(resource_id)
+    }
+
     fn apply_layout(
         &mut self,
         taffy_tree: &mut TaffyTree,
@@ -96,6 +100,9 @@ impl ElementInternals for ImageInner {
             return;
         }
 
+        self.maybe_start_effect_layer(_renderer, _scale_factor);
+        self.maybe_start_mask(_renderer, _scale_factor);
+
         // We draw the borders before we start any layers, so that we don't clip the borders.
         self.draw_borders(_renderer, _scale_factor);
 
@@ -104,6 +111,8 @@ impl ElementInternals for ImageInner {
         self.draw_borders(_renderer, _scale_factor);
 
         _renderer.draw_image(content_rectangle.scale(_scale_factor), self.resource_id.clone());
+        self.maybe_end_mask(_renderer, _resource_manager, _scale_factor, _text_context);
+        self.maybe_end_effect_layer(_renderer, _scale_factor);
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -0,0 +1,86 @@
+//! Shapes drawn over the image of an [`ImageCrop`](crate::elements::ImageCrop).
+
+use craft_primitives::geometry::{Point, Rectangle, Vec2};
+use peniko::Color;
+
+use crate::rgb;
+
+/// A shape, with its position in image pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnnotationShape {
+    Arrow { from: Point, to: Point },
+    Rectangle(Rectangle),
+    /// Text whose top left corner is at the position.
+    Text { position: Point, text: String },
+}
+
+/// A shape drawn over an image.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Annotation {
+    pub shape: AnnotationShape,
+    pub color: Color,
+}
+
+impl Annotation {
+    fn new(shape: AnnotationShape) -> Self {
+        Self {
+            shape,
+            color: rgb(239, 68, 68),
+        }
+    }
+
+    pub fn arrow(from: Point, to: Point) -> Self {
+        Self::new(AnnotationShape::Arrow { from, to })
+    }
+
+    /// The outline of a rectangle.
+    pub fn rectangle(rectangle: Rectangle) -> Self {
+        Self::new(AnnotationShape::Rectangle(rectangle))
+    }
+
+    pub fn text(position: Point, text: &str) -> Self {
+        Self::new(AnnotationShape::Text {
+            position,
+            text: text.to_string(),
+        })
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+/// The ends of the two lines of an arrow head of `size` at `to`, or `None` if the arrow has no length.
+pub(crate) fn arrow_head(from: Point, to: Point, size: f64) -> Option<(Point, Point)> {
+    let direction = to - from;
+    let length = direction.hypot();
+    if length == 0.0 {
+        return None;
+    }
+    let back = direction / length * -size;
+    // The lines are 30° either side of the shaft.
+    let (sin, cos) = (30f64.to_radians().sin(), 30f64.to_radians().cos());
+    let rotate = |v: Vec2, sin: f64| Vec2::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos);
+    Some((to + rotate(back, sin), to + rotate(back, -sin)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrow_heads_point_back_along_the_shaft() {
+        let (left, right) = arrow_head(Point::new(0.0, 0.0), Point::new(10.0, 0.0), 2.0).unwrap();
+
+        assert!((left.x - (10.0 - 3f64.sqrt())).abs() < 1e-9);
+        assert!((right.x - left.x).abs() < 1e-9);
+        assert!((left.y + right.y).abs() < 1e-9);
+        assert!((left.y.abs() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn arrows_without_length_have_no_head() {
+        assert_eq!(arrow_head(Point::new(1.0, 1.0), Point::new(1.0, 1.0), 2.0), None);
+    }
+}
@@ -0,0 +1,236 @@
+//! Moving and resizing the crop rectangle, in image pixels.
+
+use craft_primitives::geometry::{Point, Rectangle};
+
+/// The part of the crop rectangle the pointer grabbed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Handle {
+    Move,
+    Top,
+    Bottom,
+    Left,
+    Right,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Handle {
+    fn moves_left(self) -> bool {
+        matches!(self, Handle::Left | Handle::TopLeft | Handle::BottomLeft)
+    }
+
+    fn moves_right(self) -> bool {
+        matches!(self, Handle::Right | Handle::TopRight | Handle::BottomRight)
+    }
+
+    fn moves_top(self) -> bool {
+        matches!(self, Handle::Top | Handle::TopLeft | Handle::TopRight)
+    }
+
+    fn moves_bottom(self) -> bool {
+        matches!(self, Handle::Bottom | Handle::BottomLeft | Handle::BottomRight)
+    }
+}
+
+/// Returns the handle under a point, preferring corners, then edges, then the inside of the crop.
+pub(crate) fn handle_at(crop: Rectangle, point: Point, tolerance: f32) -> Option<Handle> {
+    let (x, y) = (point.x as f32, point.y as f32);
+    let near = |a: f32, b: f32| (a - b).abs() <= tolerance;
+    let within_x = x >= crop.left() - tolerance && x <= crop.right() + tolerance;
+    let within_y = y >= crop.top() - tolerance && y <= crop.bottom() + tolerance;
+    if !within_x || !within_y {
+        return None;
+    }
+
+    let (left, right) = (near(x, crop.left()), near(x, crop.right()));
+    let (top, bottom) = (near(y, crop.top()), near(y, crop.bottom()));
+    Some(match (left, right, top, bottom) {
+        (true, _, true, _) => Handle::TopLeft,
+        (_, true, true, _) => Handle::TopRight,
+        (true, _, _, true) => Handle::BottomLeft,
+        (_, true, _, true) => Handle::BottomRight,
+        (true, ..) => Handle::Left,
+        (_, true, ..) => Handle::Right,
+        (_, _, true, _) => Handle::Top,
+        (.., true) => Handle::Bottom,
+        _ => Handle::Move,
+    })
+}
+
+/// The largest rectangle with the aspect ratio (width / height) centered in `rectangle`.
+pub(crate) fn fit_aspect_ratio(rectangle: Rectangle, aspect_ratio: f32) -> Rectangle {
+    let (mut width, mut height) = (rectangle.width, rectangle.height);
+    if width / height > aspect_ratio {
+        width = height * aspect_ratio;
+    } else {
+        height = width / aspect_ratio;
+    }
+    Rectangle::new(
+        rectangle.x + (rectangle.width - width) / 2.0,
+        rectangle.y + (rectangle.height - height) / 2.0,
+        width,
+        height,
+    )
+}
+
+/// The crop after dragging a handle of `start` by `(dx, dy)` within an image of `image_width` by `image_height`.
+///
+/// The crop stays within the image and at least `min_size` wide and high. With an aspect ratio, the edge or corner
+/// opposite to the handle stays in place, and edges keep their middle in place.
+pub(crate) fn drag_handle(
+    start: Rectangle,
+    handle: Handle,
+    (dx, dy): (f32, f32),
+    (image_width, image_height): (f32, f32),
+    aspect_ratio: Option<f32>,
+    min_size: f32,
+) -> Rectangle {
+    if handle == Handle::Move {
+        let x = (start.x + dx).clamp(0.0, (image_width - start.width).max(0.0));
+        let y = (start.y + dy).clamp(0.0, (image_height - start.height).max(0.0));
+        return Rectangle::new(x, y, start.width, start.height);
+    }
+
+    let min_width = min_size.min(image_width);
+    let min_height = min_size.min(image_height);
+    let (mut left, mut top, mut right, mut bottom) = (start.left(), start.top(), start.right(), start.bottom());
+    if handle.moves_left() {
+        left = (left + dx).clamp(0.0, right - min_width);
+    }
+    if handle.moves_right() {
+        right = (right + dx).clamp(left + min_width, image_width);
+    }
+    if handle.moves_top() {
+        top = (top + dy).clamp(0.0, bottom - min_height);
+    }
+    if handle.moves_bottom() {
+        bottom = (bottom + dy).clamp(top + min_height, image_height);
+    }
+
+    let Some(aspect_ratio) = aspect_ratio else {
+        return Rectangle::new(left, top, right - left, bottom - top);
+    };
+
+    // Where the crop is anchored, and how far it may grow from there in each direction.
+    let resizes_x = handle.moves_left() || handle.moves_right();
+    let resizes_y = handle.moves_top() || handle.moves_bottom();
+    let (anchor_x, max_width) = if handle.moves_left() {
+        (start.right(), start.right())
+    } else if handle.moves_right() {
+        (start.left(), image_width - start.left())
+    } else {
+        let center = start.x + start.width / 2.0;
+        (center, 2.0 * center.min(image_width - center))
+    };
+    let (anchor_y, max_height) = if handle.moves_top() {
+        (start.bottom(), start.bottom())
+    } else if handle.moves_bottom() {
+        (start.top(), image_height - start.top())
+    } else {
+        let center = start.y + start.height / 2.0;
+        (center, 2.0 * center.min(image_height - center))
+    };
+
+    let (mut width, mut height) = (right - left, bottom - top);
+    if resizes_x && resizes_y {
+        if width / height > aspect_ratio {
+            width = height * aspect_ratio;
+        } else {
+            height = width / aspect_ratio;
+        }
+    } else if resizes_x {
+        height = width / aspect_ratio;
+    } else {
+        width = height * aspect_ratio;
+    }
+    if width > max_width {
+        width = max_width;
+        height = width / aspect_ratio;
+    }
+    if height > max_height {
+        height = max_height;
+        width = height * aspect_ratio;
+    }
+
+    let x = if handle.moves_left() {
+        anchor_x - width
+    } else if handle.moves_right() {
+        anchor_x
+    } else {
+        anchor_x - width / 2.0
+    };
+    let y = if handle.moves_top() {
+        anchor_y - height
+    } else if handle.moves_bottom() {
+        anchor_y
+    } else {
+        anchor_y - height / 2.0
+    };
+    Rectangle::new(x, y, width, height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IMAGE: (f32, f32) = (400.0, 300.0);
+
+    #[test]
+    fn finds_handles_near_the_edges() {
+        let crop = Rectangle::new(100.0, 100.0, 200.0, 100.0);
+
+        assert_eq!(handle_at(crop, Point::new(102.0, 98.0), 6.0), Some(Handle::TopLeft));
+        assert_eq!(handle_at(crop, Point::new(200.0, 201.0), 6.0), Some(Handle::Bottom));
+        assert_eq!(handle_at(crop, Point::new(297.0, 150.0), 6.0), Some(Handle::Right));
+        assert_eq!(handle_at(crop, Point::new(200.0, 150.0), 6.0), Some(Handle::Move));
+        assert_eq!(handle_at(crop, Point::new(50.0, 150.0), 6.0), None);
+    }
+
+    #[test]
+    fn moving_stays_within_the_image() {
+        let crop = Rectangle::new(100.0, 100.0, 200.0, 100.0);
+
+        let moved = drag_handle(crop, Handle::Move, (500.0, -500.0), IMAGE, None, 10.0);
+
+        assert_eq!(moved, Rectangle::new(200.0, 0.0, 200.0, 100.0));
+    }
+
+    #[test]
+    fn resizing_keeps_the_minimum_size() {
+        let crop = Rectangle::new(100.0, 100.0, 200.0, 100.0);
+
+        let resized = drag_handle(crop, Handle::TopLeft, (500.0, -500.0), IMAGE, None, 10.0);
+
+        assert_eq!(resized, Rectangle::new(290.0, 0.0, 10.0, 200.0));
+    }
+
+    #[test]
+    fn corners_keep_the_aspect_ratio() {
+        let crop = Rectangle::new(100.0, 100.0, 100.0, 100.0);
+
+        let resized = drag_handle(crop, Handle::BottomRight, (50.0, 10.0), IMAGE, Some(1.0), 10.0);
+        assert_eq!(resized, Rectangle::new(100.0, 100.0, 110.0, 110.0));
+
+        // Limited by the bottom of the image.
+        let resized = drag_handle(crop, Handle::BottomRight, (500.0, 500.0), IMAGE, Some(1.0), 10.0);
+        assert_eq!(resized, Rectangle::new(100.0, 100.0, 200.0, 200.0));
+    }
+
+    #[test]
+    fn edges_keep_the_aspect_ratio_around_their_middle() {
+        let crop = Rectangle::new(100.0, 100.0, 100.0, 50.0);
+
+        let resized = drag_handle(crop, Handle::Bottom, (0.0, 50.0), IMAGE, Some(2.0), 10.0);
+
+        assert_eq!(resized, Rectangle::new(50.0, 100.0, 200.0, 100.0));
+    }
+
+    #[test]
+    fn fits_aspect_ratios() {
+        let fitted = fit_aspect_ratio(Rectangle::new(0.0, 0.0, 400.0, 300.0), 1.0);
+
+        assert_eq!(fitted, Rectangle::new(50.0, 0.0, 300.0, 300.0));
+    }
+}
@@ -0,0 +1,526 @@
+//! Crops an image and draws annotations over it.
+
+mod annotation;
+mod crop;
+
+pub use annotation::{Annotation, AnnotationShape};
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use craft_primitives::geometry::{Affine, BezPath, Point, Rectangle, TOLERANCE};
+use craft_renderer::Brush;
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::image::ImageResource;
+use craft_resource_manager::{ResourceId, ResourceManager};
+use peniko::Color;
+use peniko::kurbo;
+use ui_events::pointer::PointerButton;
+
+use crate::app::queue_event;
+use crate::elements::element_data::ElementData;
+use crate::elements::image_crop::annotation::arrow_head;
+use crate::elements::image_crop::crop::{drag_handle, fit_aspect_ratio, handle_at, Handle};
+use crate::elements::internal_helpers::{apply_generic_container_layout, push_child_to_element};
+use crate::elements::traits::DeepClone;
+use crate::elements::{AsElement, Element, ElementInternals, Image, Text};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::Position;
+use crate::text::text_context::TextContext;
+use crate::{auto, pct, px, rgba};
+
+/// How close, in logical pixels, the pointer must be to an edge of the crop to resize it.
+const HANDLE_TOLERANCE: f32 = 8.0;
+
+/// The size of the squares marking the corners and edges of the crop, in logical pixels.
+const HANDLE_SIZE: f32 = 8.0;
+
+/// The smallest width and height of the crop, in image pixels.
+const MIN_CROP_SIZE: f32 = 8.0;
+
+const SHADE_COLOR: Color = rgba(0, 0, 0, 128);
+const GUIDE_COLOR: Color = rgba(255, 255, 255, 140);
+
+#[derive(Clone)]
+pub struct ImageCrop {
+    pub inner: Rc<RefCell<ImageCropInner>>,
+}
+
+/// A crop being dragged.
+#[derive(Clone)]
+struct CropDrag {
+    handle: Handle,
+    start: Point,
+    start_crop: Rectangle,
+}
+
+/// Shows an [`Image`] with a crop rectangle that can be moved and resized by dragging, and annotations drawn over it.
+///
+/// The crop and the annotations are in image pixels, so they don't depend on the size the image is shown at. The
+/// crop can be locked to an aspect ratio, and rule-of-thirds guides are drawn inside it. A
+/// [`EventKind::ImageCropChanged`] event is sent whenever the user changes the crop.
+#[derive(Clone)]
+pub struct ImageCropInner {
+    element_data: ElementData,
+    /// The size of the image in pixels, once it has loaded.
+    image_size: Option<(f32, f32)>,
+    /// The crop, or `None` to crop the whole image.
+    crop: Option<Rectangle>,
+    aspect_ratio: Option<f32>,
+    show_guides: bool,
+    drag: Option<CropDrag>,
+    annotations: Vec<Annotation>,
+    /// The texts of the text annotations, in the order of the annotations.
+    annotation_labels: Vec<Text>,
+    pub image: Image,
+}
+
+impl Element for ImageCrop {}
+
+impl Drop for ImageCropInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for ImageCrop {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for ImageCropInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for ImageCropInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        if !self.is_visible() {
+            return;
+        }
+
+        if let Some(resource) = resource_manager.get(self.image.inner.borrow().get_resource_id())
+            && let Some(image_data) = resource.data.downcast_ref::<ImageResource>()
+        {
+            self.image_size = Some((image_data.image.width() as f32, image_data.image.height() as f32));
+        }
+
+        self.add_hit_testable(renderer, true, scale_factor);
+        self.draw_borders(renderer, scale_factor);
+        self.image
+            .inner
+            .borrow_mut()
+            .draw(renderer, resource_manager.clone(), scale_factor, text_context);
+
+        if self.image_size.is_none() {
+            return;
+        }
+        self.draw_annotations(renderer, resource_manager, scale_factor, text_context);
+        self.draw_crop(renderer, scale_factor);
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        _event: &mut Event,
+        _target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        let Some(crop) = self.get_crop() else {
+            return;
+        };
+
+        match message {
+            EventKind::PointerButtonDown(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                let point = self.to_image(pointer_button.state.logical_point());
+                let tolerance = HANDLE_TOLERANCE / self.display_scale();
+                let Some(handle) = handle_at(crop, point, tolerance) else {
+                    return;
+                };
+
+                self.drag = Some(CropDrag {
+                    handle,
+                    start: point,
+                    start_crop: crop,
+                });
+                self.capture_pointer();
+            }
+            EventKind::PointerMovedEvent(pointer_update) => {
+                let (Some(drag), Some(image_size)) = (&self.drag, self.image_size) else {
+                    return;
+                };
+                let point = self.to_image(pointer_update.current.logical_point());
+                let delta = ((point.x - drag.start.x) as f32, (point.y - drag.start.y) as f32);
+                let new_crop = drag_handle(
+                    drag.start_crop,
+                    drag.handle,
+                    delta,
+                    image_size,
+                    self.aspect_ratio,
+                    MIN_CROP_SIZE,
+                );
+                if new_crop != crop {
+                    self.crop = Some(new_crop);
+                    self.notify_crop_changed();
+                }
+            }
+            EventKind::PointerButtonUp(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                if self.drag.take().is_some() {
+                    self.release_pointer();
+                }
+            }
+            EventKind::LostPointerCapture() => {
+                self.drag = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl ImageCrop {
+    pub fn new(resource_id: ResourceId) -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<ImageCropInner>>| {
+            RefCell::new(ImageCropInner {
+                element_data: ElementData::new(me.clone(), false),
+                image_size: None,
+                crop: None,
+                aspect_ratio: None,
+                show_guides: true,
+                drag: None,
+                annotations: Vec::new(),
+                annotation_labels: Vec::new(),
+                image: Image::new(resource_id).width(pct(100)),
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        let image = inner_mut.image.clone();
+        inner_mut.push(image.inner);
+
+        drop(inner_mut);
+        Self { inner }
+    }
+
+    /// Set the crop in image pixels. It is clamped to the image once the image has loaded.
+    pub fn crop(self, crop: Rectangle) -> Self {
+        self.inner.borrow_mut().set_crop(Some(crop));
+        self
+    }
+
+    /// Returns the crop in image pixels, or `None` until the image has loaded.
+    pub fn get_crop(&self) -> Option<Rectangle> {
+        self.inner.borrow().get_crop()
+    }
+
+    /// Lock the crop to an aspect ratio (width / height), or unlock it with `None`.
+    pub fn aspect_ratio(self, aspect_ratio: Option<f32>) -> Self {
+        self.inner.borrow_mut().set_aspect_ratio(aspect_ratio);
+        self
+    }
+
+    pub fn get_aspect_ratio(&self) -> Option<f32> {
+        self.inner.borrow().aspect_ratio
+    }
+
+    /// Draw the rule-of-thirds guides inside the crop. Defaults to true.
+    pub fn show_guides(self, show_guides: bool) -> Self {
+        self.inner.borrow_mut().show_guides = show_guides;
+        self
+    }
+
+    pub fn annotation(self, annotation: Annotation) -> Self {
+        self.inner.borrow_mut().add_annotation(annotation);
+        self
+    }
+
+    pub fn get_annotations(&self) -> Vec<Annotation> {
+        self.inner.borrow().annotations.clone()
+    }
+
+    pub fn clear_annotations(&self) {
+        self.inner.borrow_mut().clear_annotations();
+    }
+}
+
+impl ImageCropInner {
+    pub fn set_crop(&mut self, crop: Option<Rectangle>) {
+        self.crop = crop;
+        self.request_window_redraw();
+    }
+
+    pub fn get_crop(&self) -> Option<Rectangle> {
+        let (width, height) = self.image_size?;
+        let whole_image = Rectangle::new(0.0, 0.0, width, height);
+        let crop = match (self.crop, self.aspect_ratio) {
+            (Some(crop), _) => clamp_to_image(crop, width, height),
+            (None, Some(aspect_ratio)) => fit_aspect_ratio(whole_image, aspect_ratio),
+            (None, None) => whole_image,
+        };
+        Some(crop)
+    }
+
+    /// Locks the aspect ratio, shrinking the crop around its middle to match it.
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: Option<f32>) {
+        self.aspect_ratio = aspect_ratio.filter(|aspect_ratio| aspect_ratio.is_finite() && *aspect_ratio > 0.0);
+        if let Some(aspect_ratio) = self.aspect_ratio
+            && let Some(crop) = self.crop
+        {
+            self.crop = Some(fit_aspect_ratio(crop, aspect_ratio));
+        }
+        self.request_window_redraw();
+    }
+
+    pub fn add_annotation(&mut self, annotation: Annotation) {
+        if let AnnotationShape::Text { text, .. } = &annotation.shape {
+            // Laid out at the top left corner, and moved to the annotation when drawn.
+            let label = Text::new(text)
+                .selectable(false)
+                .color(annotation.color)
+                .position(Position::Absolute)
+                .inset(px(0), auto(), auto(), px(0));
+            self.push(label.inner.clone());
+            self.annotation_labels.push(label);
+        }
+        self.annotations.push(annotation);
+        self.request_window_redraw();
+    }
+
+    pub fn clear_annotations(&mut self) {
+        for label in std::mem::take(&mut self.annotation_labels) {
+            self.remove_child(label.inner).unwrap();
+        }
+        self.annotations.clear();
+        self.request_window_redraw();
+    }
+
+    fn notify_crop_changed(&mut self) {
+        self.request_window_redraw();
+        let Some(crop) = self.get_crop() else {
+            return;
+        };
+        let new_event = Event::new(self.element_data.me.upgrade().unwrap());
+        queue_event(new_event, EventKind::ImageCropChanged(crop));
+    }
+
+    fn image_rectangle(&self) -> Rectangle {
+        self.image
+            .borrow()
+            .element_data()
+            .layout
+            .computed_box_transformed
+            .content_rectangle()
+    }
+
+    /// How many logical pixels an image pixel is shown as.
+    fn display_scale(&self) -> f32 {
+        let Some((width, _)) = self.image_size.filter(|(width, _)| *width > 0.0) else {
+            return 1.0;
+        };
+        self.image_rectangle().width / width
+    }
+
+    fn to_image(&self, point: Point) -> Point {
+        let image_rectangle = self.image_rectangle();
+        let scale = self.display_scale() as f64;
+        Point::new(
+            (point.x - image_rectangle.x as f64) / scale,
+            (point.y - image_rectangle.y as f64) / scale,
+        )
+    }
+
+    /// Converts a point in image pixels to physical pixels.
+    fn to_physical(&self, point: Point, scale_factor: f64) -> Point {
+        let image_rectangle = self.image_rectangle();
+        let scale = self.display_scale() as f64;
+        Point::new(
+            (image_rectangle.x as f64 + point.x * scale) * scale_factor,
+            (image_rectangle.y as f64 + point.y * scale) * scale_factor,
+        )
+    }
+
+    fn rectangle_to_physical(&self, rectangle: Rectangle, scale_factor: f64) -> Rectangle {
+        let origin = self.to_physical(Point::new(rectangle.x as f64, rectangle.y as f64), scale_factor);
+        let scale = self.display_scale() as f64 * scale_factor;
+        Rectangle::new(
+            origin.x as f32,
+            origin.y as f32,
+            (rectangle.width as f64 * scale) as f32,
+            (rectangle.height as f64 * scale) as f32,
+        )
+    }
+
+    fn draw_annotations(
+        &mut self,
+        renderer: &mut dyn Renderer,
+        resource_manager: Arc<ResourceManager>,
+        scale_factor: f64,
+        text_context: &mut TextContext,
+    ) {
+        let line_width = 2.0 * scale_factor;
+        let mut labels = self.annotation_labels.iter();
+        for annotation in &self.annotations {
+            match &annotation.shape {
+                AnnotationShape::Arrow { from, to } => {
+                    let from = self.to_physical(*from, scale_factor);
+                    let to = self.to_physical(*to, scale_factor);
+                    let mut path = BezPath::new();
+                    path.move_to(from);
+                    path.line_to(to);
+                    if let Some((left, right)) = arrow_head(from, to, 12.0 * scale_factor) {
+                        path.move_to(left);
+                        path.line_to(to);
+                        path.line_to(right);
+                    }
+                    let style = kurbo::Stroke::new(line_width)
+                        .with_caps(kurbo::Cap::Round)
+                        .with_join(kurbo::Join::Round);
+                    let outline = kurbo::stroke(path, &style, &kurbo::StrokeOpts::default(), TOLERANCE);
+                    renderer.fill_bez_path(outline, Brush::Color(annotation.color));
+                }
+                AnnotationShape::Rectangle(rectangle) => {
+                    let rectangle = self.rectangle_to_physical(*rectangle, scale_factor);
+                    renderer.draw_rect_outline(rectangle, annotation.color, line_width);
+                }
+                AnnotationShape::Text { position, .. } => {
+                    let Some(label) = labels.next() else {
+                        continue;
+                    };
+                    let target = self.to_physical(*position, scale_factor);
+                    let laid_out = label.borrow().element_data().layout.computed_box_transformed.border_rectangle();
+                    let offset = (
+                        target.x - laid_out.x as f64 * scale_factor,
+                        target.y - laid_out.y as f64 * scale_factor,
+                    );
+
+                    let old_transform = renderer.get_transform();
+                    renderer.set_transform(Affine::translate(offset) * old_transform);
+                    label
+                        .inner
+                        .borrow_mut()
+                        .draw(renderer, resource_manager.clone(), scale_factor, text_context);
+                    renderer.set_transform(old_transform);
+                }
+            }
+        }
+    }
+
+    fn draw_crop(&self, renderer: &mut dyn Renderer, scale_factor: f64) {
+        let Some(crop) = self.get_crop() else {
+            return;
+        };
+        let image = self.image_rectangle().scale(scale_factor);
+        let crop = self.rectangle_to_physical(crop, scale_factor);
+
+        // Shade the image outside the crop.
+        let shades = [
+            Rectangle::new(image.x, image.y, image.width, crop.y - image.y),
+            Rectangle::new(image.x, crop.bottom(), image.width, image.bottom() - crop.bottom()),
+            Rectangle::new(image.x, crop.y, crop.x - image.x, crop.height),
+            Rectangle::new(crop.right(), crop.y, image.right() - crop.right(), crop.height),
+        ];
+        for shade in shades {
+            if shade.width > 0.0 && shade.height > 0.0 {
+                renderer.draw_rect(shade, SHADE_COLOR);
+            }
+        }
+
+        let line_width = scale_factor as f32;
+        if self.show_guides {
+            for third in [1.0, 2.0] {
+                let x = crop.x + crop.width * third / 3.0;
+                let y = crop.y + crop.height * third / 3.0;
+                renderer.draw_rect(Rectangle::new(x, crop.y, line_width, crop.height), GUIDE_COLOR);
+                renderer.draw_rect(Rectangle::new(crop.x, y, crop.width, line_width), GUIDE_COLOR);
+            }
+        }
+        renderer.draw_rect_outline(crop, Color::WHITE, line_width as f64);
+
+        let handle_size = HANDLE_SIZE * scale_factor as f32;
+        let (middle_x, middle_y) = (crop.x + crop.width / 2.0, crop.y + crop.height / 2.0);
+        for (x, y) in [
+            (crop.x, crop.y),
+            (middle_x, crop.y),
+            (crop.right(), crop.y),
+            (crop.x, middle_y),
+            (crop.right(), middle_y),
+            (crop.x, crop.bottom()),
+            (middle_x, crop.bottom()),
+            (crop.right(), crop.bottom()),
+        ] {
+            let handle = Rectangle::new(x - handle_size / 2.0, y - handle_size / 2.0, handle_size, handle_size);
+            renderer.draw_rect(handle, Color::WHITE);
+        }
+    }
+}
+
+/// Moves and shrinks the crop to fit in the image.
+fn clamp_to_image(crop: Rectangle, width: f32, height: f32) -> Rectangle {
+    let crop_width = crop.width.clamp(0.0, width);
+    let crop_height = crop.height.clamp(0.0, height);
+    Rectangle::new(
+        crop.x.clamp(0.0, width - crop_width),
+        crop.y.clamp(0.0, height - crop_height),
+        crop_width,
+        crop_height,
+    )
+}
@@ -0,0 +1,389 @@
+//! Shows an image that can be zoomed, panned and rotated.
+
+mod view;
+
+pub use view::ZoomMode;
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time;
+#[cfg(target_arch = "wasm32")]
+use web_time as time;
+
+use craft_primitives::geometry::{Affine, Point, Rectangle, Size};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::{ResourceId, ResourceManager};
+use time::{Duration, Instant};
+use ui_events::ScrollDelta;
+use ui_events::pointer::{PointerButton, PointerGesture};
+
+use crate::elements::element_data::ElementData;
+use crate::elements::image_viewer::view::View;
+use crate::elements::internal_helpers::{apply_generic_container_layout, push_child_to_element};
+use crate::elements::traits::DeepClone;
+use crate::elements::{AsElement, Element, ElementInternals, Image};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::{Overflow, Position};
+use crate::text::text_context::TextContext;
+use crate::{auto, px};
+
+/// How much one line of the mouse wheel zooms.
+const WHEEL_ZOOM_STEP: f64 = 1.2;
+
+/// How many pixels of a precise scroll count as one line of the mouse wheel.
+const PIXELS_PER_LINE: f64 = 40.0;
+
+/// The longest time between the clicks of a double click.
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(300);
+
+#[derive(Clone)]
+pub struct ImageViewer {
+    pub inner: Rc<RefCell<ImageViewerInner>>,
+}
+
+/// Shows an [`Image`] that can be zoomed with the mouse wheel or a pinch, panned by dragging and rotated.
+///
+/// The image is fitted to the viewer until the user zooms or pans. Zooming keeps the point under the pointer in
+/// place, and double clicking switches between fitting the image and showing it at its actual size.
+#[derive(Clone)]
+pub struct ImageViewerInner {
+    element_data: ElementData,
+    view: View,
+    /// The last pointer position while the image is dragged.
+    drag: Option<Point>,
+    last_click: Option<Instant>,
+    pub image: Image,
+}
+
+impl Element for ImageViewer {}
+
+impl Drop for ImageViewerInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for ImageViewer {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for ImageViewerInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for ImageViewerInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+
+        // The image is refitted when it loads or the viewer is resized.
+        let (image_size, viewport) = (self.image_size(), self.viewport_size());
+        self.view.apply_mode(image_size, viewport);
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        if !self.is_visible() {
+            return;
+        }
+
+        self.add_hit_testable(renderer, true, scale_factor);
+        self.draw_borders(renderer, scale_factor);
+
+        let content_rectangle = self.element_data.layout.computed_box_transformed.content_rectangle();
+        let image_rectangle = self.image_rectangle();
+        // The image draws itself at its layout position, in physical pixels, so it is moved to the image's origin
+        // before the view is applied.
+        let view_transform = Affine::scale(scale_factor)
+            * Affine::translate((content_rectangle.x as f64, content_rectangle.y as f64))
+            * self.view.transform(self.viewport_size())
+            * Affine::translate((-image_rectangle.x as f64, -image_rectangle.y as f64))
+            * Affine::scale(1.0 / scale_factor);
+
+        renderer.push_layer(content_rectangle.scale(scale_factor));
+        let old_transform = renderer.get_transform();
+        renderer.set_transform(old_transform * view_transform);
+        self.draw_children(renderer, resource_manager, scale_factor, text_context);
+        renderer.set_transform(old_transform);
+        renderer.pop_layer();
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        _target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        match message {
+            EventKind::PointerScroll(scroll) => {
+                let lines = match scroll.delta {
+                    ScrollDelta::LineDelta(_x, y) => y as f64,
+                    ScrollDelta::PixelDelta(physical) => physical.y / PIXELS_PER_LINE,
+                    ScrollDelta::PageDelta(_x, y) => y as f64,
+                };
+                let anchor = self.viewport_point(scroll.state.logical_point());
+                self.zoom_at(WHEEL_ZOOM_STEP.powf(lines), anchor);
+                event.prevent_propagate();
+                event.prevent_defaults();
+            }
+            EventKind::PointerGesture(gesture) => {
+                if let PointerGesture::Pinch(delta) = gesture.gesture {
+                    let anchor = self.viewport_point(gesture.state.logical_point());
+                    self.zoom_at(1.0 + delta as f64, anchor);
+                    event.prevent_propagate();
+                    event.prevent_defaults();
+                }
+            }
+            EventKind::PointerButtonDown(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                let pointer_position = pointer_button.state.logical_point();
+                let now = Instant::now();
+                if self
+                    .last_click
+                    .take()
+                    .is_some_and(|last_click| now.duration_since(last_click) < DOUBLE_CLICK_TIME)
+                {
+                    self.toggle_actual_size(self.viewport_point(pointer_position));
+                    return;
+                }
+                self.last_click = Some(now);
+
+                self.drag = Some(pointer_position);
+                self.capture_pointer();
+            }
+            EventKind::PointerMovedEvent(pointer_update) => {
+                let Some(last_position) = self.drag else {
+                    return;
+                };
+                let pointer_position = pointer_update.current.logical_point();
+                self.drag = Some(pointer_position);
+                let image_size = self.image_size();
+                self.view.pan(pointer_position - last_position, image_size);
+                self.request_window_redraw();
+            }
+            EventKind::PointerButtonUp(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                if self.drag.take().is_some() {
+                    self.release_pointer();
+                }
+            }
+            EventKind::LostPointerCapture() => {
+                self.drag = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl ImageViewer {
+    pub fn new(resource_id: ResourceId) -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<ImageViewerInner>>| {
+            RefCell::new(ImageViewerInner {
+                element_data: ElementData::new(me.clone(), false),
+                view: View::default(),
+                drag: None,
+                last_click: None,
+                // Without a size, the image is laid out at its actual size, which the view then scales.
+                image: Image::new(resource_id)
+                    .position(Position::Absolute)
+                    .inset(px(0), auto(), auto(), px(0)),
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        inner_mut.set_overflow(Overflow::Hidden, Overflow::Hidden);
+        let image = inner_mut.image.clone();
+        inner_mut.push(image.inner);
+
+        drop(inner_mut);
+        Self { inner }
+    }
+
+    pub fn resource_id(self, resource_id: ResourceId) -> Self {
+        self.inner.borrow_mut().set_image(resource_id);
+        self
+    }
+
+    pub fn get_resource_id(&self) -> ResourceId {
+        self.inner.borrow().image.get_resource_id()
+    }
+
+    /// Fit, fill or show the image at its actual size, and center it.
+    pub fn zoom_mode(self, mode: ZoomMode) -> Self {
+        self.inner.borrow_mut().set_zoom_mode(mode);
+        self
+    }
+
+    /// Returns the zoom mode, or `None` once the user zoomed or panned the image.
+    pub fn get_zoom_mode(&self) -> Option<ZoomMode> {
+        self.inner.borrow().view.mode
+    }
+
+    /// Set the zoom, keeping the middle of the viewer in place. A zoom of 1 shows the image at its actual size.
+    pub fn zoom(self, zoom: f64) -> Self {
+        self.inner.borrow_mut().set_zoom(zoom);
+        self
+    }
+
+    pub fn get_zoom(&self) -> f64 {
+        self.inner.borrow().view.zoom
+    }
+
+    /// Set the smallest and largest zoom. Defaults to 0.05 and 32.
+    pub fn zoom_limits(self, min: f64, max: f64) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.view.min_zoom = min.min(max);
+        inner.view.max_zoom = max.max(min);
+        let zoom = inner.view.zoom;
+        inner.set_zoom(zoom);
+        drop(inner);
+        self
+    }
+
+    /// Set the clockwise rotation of the image in degrees.
+    pub fn rotation(self, degrees: f64) -> Self {
+        self.inner.borrow_mut().set_rotation(degrees);
+        self
+    }
+
+    pub fn get_rotation(&self) -> f64 {
+        self.inner.borrow().get_rotation()
+    }
+}
+
+impl ImageViewerInner {
+    pub fn set_image(&mut self, resource_id: ResourceId) {
+        self.image.inner.borrow_mut().set_image(resource_id);
+        self.view.mode = Some(self.view.mode.unwrap_or_default());
+    }
+
+    pub fn set_zoom_mode(&mut self, mode: ZoomMode) {
+        self.view.mode = Some(mode);
+        self.apply_view_mode();
+    }
+
+    pub fn set_zoom(&mut self, zoom: f64) {
+        let viewport = self.viewport_size();
+        let middle = Point::new(viewport.width / 2.0, viewport.height / 2.0);
+        let zoom = zoom.clamp(self.view.min_zoom, self.view.max_zoom);
+        let image_size = self.image_size();
+        self.view.set_zoom_at(zoom, middle, image_size, viewport);
+        self.request_window_redraw();
+    }
+
+    pub fn set_rotation(&mut self, degrees: f64) {
+        self.view.rotation = degrees.rem_euclid(360.0).to_radians();
+        self.apply_view_mode();
+    }
+
+    pub fn get_rotation(&self) -> f64 {
+        self.view.rotation.to_degrees()
+    }
+
+    fn apply_view_mode(&mut self) {
+        let (image_size, viewport) = (self.image_size(), self.viewport_size());
+        self.view.apply_mode(image_size, viewport);
+        self.request_window_redraw();
+    }
+
+    fn zoom_at(&mut self, factor: f64, anchor: Point) {
+        let (image_size, viewport) = (self.image_size(), self.viewport_size());
+        self.view.zoom_at(factor, anchor, image_size, viewport);
+        self.request_window_redraw();
+    }
+
+    /// Shows the image at its actual size around the anchor, or fits it if it already is.
+    fn toggle_actual_size(&mut self, anchor: Point) {
+        let (image_size, viewport) = (self.image_size(), self.viewport_size());
+        let fit_zoom = self.view.mode_zoom(ZoomMode::Fit, image_size, viewport);
+        if self.view.mode == Some(ZoomMode::Fit) && fit_zoom != 1.0 {
+            self.view.set_zoom_at(1.0, anchor, image_size, viewport);
+        } else {
+            self.view.mode = Some(ZoomMode::Fit);
+            self.view.apply_mode(image_size, viewport);
+        }
+        self.request_window_redraw();
+    }
+
+    fn image_rectangle(&self) -> Rectangle {
+        self.image
+            .borrow()
+            .element_data()
+            .layout
+            .computed_box_transformed
+            .content_rectangle()
+    }
+
+    fn image_size(&self) -> Size<f64> {
+        let image_rectangle = self.image_rectangle();
+        Size::new(image_rectangle.width as f64, image_rectangle.height as f64)
+    }
+
+    fn viewport_size(&self) -> Size<f64> {
+        let content_rectangle = self.element_data.layout.computed_box_transformed.content_rectangle();
+        Size::new(content_rectangle.width as f64, content_rectangle.height as f64)
+    }
+
+    /// Converts a window point to a point relative to the top left of the viewer's content.
+    fn viewport_point(&self, point: Point) -> Point {
+        let content_rectangle = self.element_data.layout.computed_box_transformed.content_rectangle();
+        Point::new(point.x - content_rectangle.x as f64, point.y - content_rectangle.y as f64)
+    }
+}
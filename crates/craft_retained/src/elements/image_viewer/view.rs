@@ -0,0 +1,194 @@
+//! Where the image is shown in the viewer.
+
+use craft_primitives::geometry::{Affine, Point, Size, Vec2};
+
+/// How an [`ImageViewer`](crate::elements::ImageViewer) sizes its image.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ZoomMode {
+    /// Shows the whole image, as large as the viewer allows.
+    #[default]
+    Fit,
+    /// Covers the whole viewer, cutting off the overflowing sides of the image.
+    Fill,
+    /// Shows one image pixel per logical pixel.
+    ActualSize,
+}
+
+/// The zoom, rotation and position of the image, in logical pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct View {
+    pub(crate) zoom: f64,
+    /// The clockwise rotation in radians.
+    pub(crate) rotation: f64,
+    /// The point of the image shown in the middle of the viewport.
+    pub(crate) center: Point,
+    /// Keeps the zoom following the size of the viewport until the user zooms or pans.
+    pub(crate) mode: Option<ZoomMode>,
+    pub(crate) min_zoom: f64,
+    pub(crate) max_zoom: f64,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self {
+            zoom: 1.0,
+            rotation: 0.0,
+            center: Point::ZERO,
+            mode: Some(ZoomMode::Fit),
+            min_zoom: 0.05,
+            max_zoom: 32.0,
+        }
+    }
+}
+
+impl View {
+    /// The zoom of a mode, for an image in a viewport.
+    pub(crate) fn mode_zoom(&self, mode: ZoomMode, image: Size<f64>, viewport: Size<f64>) -> f64 {
+        let (width, height) = rotated_size(image, self.rotation);
+        if width <= 0.0 || height <= 0.0 {
+            return 1.0;
+        }
+        match mode {
+            ZoomMode::Fit => (viewport.width / width).min(viewport.height / height),
+            ZoomMode::Fill => (viewport.width / width).max(viewport.height / height),
+            ZoomMode::ActualSize => 1.0,
+        }
+    }
+
+    /// Centers the image and applies the zoom of the mode, if there is one.
+    pub(crate) fn apply_mode(&mut self, image: Size<f64>, viewport: Size<f64>) {
+        if let Some(mode) = self.mode {
+            self.zoom = self.mode_zoom(mode, image, viewport);
+            self.center = Point::new(image.width / 2.0, image.height / 2.0);
+        }
+    }
+
+    /// Maps a point of the image to the viewport.
+    pub(crate) fn transform(&self, viewport: Size<f64>) -> Affine {
+        Affine::translate((viewport.width / 2.0, viewport.height / 2.0))
+            * Affine::rotate(self.rotation)
+            * Affine::scale(self.zoom)
+            * Affine::translate(-self.center.to_vec2())
+    }
+
+    /// Returns the point of the image under a point of the viewport.
+    pub(crate) fn image_point(&self, point: Point, viewport: Size<f64>) -> Point {
+        self.transform(viewport).inverse() * point
+    }
+
+    /// Multiplies the zoom by `factor`, keeping the image point under `anchor` in place.
+    pub(crate) fn zoom_at(&mut self, factor: f64, anchor: Point, image: Size<f64>, viewport: Size<f64>) {
+        let zoom = (self.zoom * factor).clamp(self.min_zoom, self.max_zoom);
+        self.set_zoom_at(zoom, anchor, image, viewport);
+    }
+
+    pub(crate) fn set_zoom_at(&mut self, zoom: f64, anchor: Point, image: Size<f64>, viewport: Size<f64>) {
+        let image_anchor = self.image_point(anchor, viewport);
+        self.zoom = zoom;
+        self.mode = None;
+        // Moves the center so the anchor maps to the same viewport point at the new zoom.
+        let moved = self.image_point(anchor, viewport);
+        self.center += image_anchor - moved;
+        self.clamp_center(image);
+    }
+
+    /// Moves the image by a distance in the viewport.
+    pub(crate) fn pan(&mut self, delta: Vec2, image: Size<f64>) {
+        let image_delta = Affine::rotate(-self.rotation) * delta.to_point();
+        self.center -= image_delta.to_vec2() / self.zoom;
+        self.mode = None;
+        self.clamp_center(image);
+    }
+
+    /// Keeps some of the image in the middle of the viewport.
+    fn clamp_center(&mut self, image: Size<f64>) {
+        self.center.x = self.center.x.clamp(0.0, image.width.max(0.0));
+        self.center.y = self.center.y.clamp(0.0, image.height.max(0.0));
+    }
+}
+
+/// The size of the box around an image rotated by `rotation` radians.
+fn rotated_size(image: Size<f64>, rotation: f64) -> (f64, f64) {
+    let (sin, cos) = (rotation.sin().abs(), rotation.cos().abs());
+    (
+        image.width * cos + image.height * sin,
+        image.width * sin + image.height * cos,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use super::*;
+
+    const IMAGE: Size<f64> = Size {
+        width: 400.0,
+        height: 200.0,
+    };
+    const VIEWPORT: Size<f64> = Size {
+        width: 200.0,
+        height: 200.0,
+    };
+
+    fn assert_near(a: Point, b: Point) {
+        assert!((a - b).hypot() < 1e-9, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn modes_fit_the_rotated_image() {
+        let mut view = View::default();
+        assert_eq!(view.mode_zoom(ZoomMode::Fit, IMAGE, VIEWPORT), 0.5);
+        assert_eq!(view.mode_zoom(ZoomMode::Fill, IMAGE, VIEWPORT), 1.0);
+        assert_eq!(view.mode_zoom(ZoomMode::ActualSize, IMAGE, VIEWPORT), 1.0);
+
+        view.rotation = FRAC_PI_2;
+        assert!((view.mode_zoom(ZoomMode::Fit, IMAGE, VIEWPORT) - 0.5).abs() < 1e-9);
+        assert!((view.mode_zoom(ZoomMode::Fill, Size::new(400.0, 100.0), VIEWPORT) - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn the_center_of_the_image_is_shown_in_the_middle() {
+        let mut view = View::default();
+        view.apply_mode(IMAGE, VIEWPORT);
+
+        assert_near(view.transform(VIEWPORT) * Point::new(200.0, 100.0), Point::new(100.0, 100.0));
+        assert_near(view.transform(VIEWPORT) * Point::new(0.0, 0.0), Point::new(0.0, 50.0));
+    }
+
+    #[test]
+    fn zooming_keeps_the_anchor_in_place() {
+        let mut view = View::default();
+        view.rotation = FRAC_PI_2;
+        view.apply_mode(IMAGE, VIEWPORT);
+        let anchor = Point::new(150.0, 40.0);
+        let before = view.image_point(anchor, VIEWPORT);
+
+        view.zoom_at(2.0, anchor, IMAGE, VIEWPORT);
+
+        assert_eq!(view.zoom, 1.0);
+        assert_eq!(view.mode, None);
+        assert_near(view.image_point(anchor, VIEWPORT), before);
+    }
+
+    #[test]
+    fn panning_follows_the_pointer() {
+        let mut view = View::default();
+        view.apply_mode(IMAGE, VIEWPORT);
+        let before = view.transform(VIEWPORT) * Point::new(100.0, 100.0);
+
+        view.pan(Vec2::new(10.0, -5.0), IMAGE);
+
+        assert_near(view.transform(VIEWPORT) * Point::new(100.0, 100.0), before + Vec2::new(10.0, -5.0));
+    }
+
+    #[test]
+    fn panning_keeps_the_image_in_view() {
+        let mut view = View::default();
+        view.apply_mode(IMAGE, VIEWPORT);
+
+        view.pan(Vec2::new(10_000.0, 0.0), IMAGE);
+
+        assert_eq!(view.center, Point::new(0.0, 100.0));
+    }
+}
@@ -1,7 +1,10 @@
 use crate::app::TAFFY_TREE;
-use crate::elements::ElementInternals;
+use crate::document::{record_mutation, remove_element_from_document, Mutation};
+use crate::elements::{AsElement, ElementInternals};
 use crate::layout::TaffyTree;
 use crate::text::text_context::TextContext;
+use crate::tree_updates::ElementHandle;
+use crate::CraftError;
 
 use craft_primitives::geometry::{Affine, Point, Rectangle};
 
@@ -12,7 +15,10 @@ use crate::elements::element_data::ElementData;
 #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
 use accesskit::{Node, NodeId, TreeUpdate};
 use craft_renderer::renderer::Renderer;
+use craft_renderer::text_renderer_data::TextAntialiasing;
+use craft_renderer::TargetItem;
 use craft_resource_manager::ResourceManager;
+use image::RgbaImage;
 
 /// A helper to push children.
 pub fn push_child_to_element(parent: &mut dyn ElementInternals, child: Rc<RefCell<dyn ElementInternals>>) {
@@ -22,7 +28,13 @@ pub fn push_child_to_element(parent: &mut dyn ElementInternals, child: Rc<RefCel
     child.borrow_mut().element_data_mut().parent = Some(me);
     child.borrow_mut().element_data_mut().window = me_window;
     child.borrow_mut().propagate_window_down();
+    child.borrow_mut().inherit_direction(element_data.style.get_direction());
     element_data.children.push(child.clone());
+    record_mutation(Mutation::ChildAdded {
+        parent: ElementHandle(element_data.internal_id),
+        child: ElementHandle(child.borrow().element_data().internal_id),
+        index: element_data.children.len() - 1,
+    });
 
     // Add the children's taffy node.
     TAFFY_TREE.with_borrow_mut(|taffy_tree| {
@@ -35,6 +47,158 @@ pub fn push_child_to_element(parent: &mut dyn ElementInternals, child: Rc<RefCel
     })
 }
 
+/// Returns true if the event target is the element or one of its direct children, such as the label of a button.
+///
+/// Composite elements are mutably borrowed while handling the events of their parts,
+/// so elements that can't be borrowed are skipped.
+/// A helper to swap two children, see [`ElementInternals::swap_child`].
+pub fn swap_children_of_element<E: ElementInternals + ?Sized>(
+    parent: &mut E,
+    child_1: Rc<RefCell<dyn ElementInternals>>,
+    child_2: Rc<RefCell<dyn ElementInternals>>,
+) -> Result<(), CraftError> {
+    let children = &mut parent.element_data_mut().children;
+    let position_1 = children
+        .iter()
+        .position(|x| Rc::ptr_eq(x, &child_1))
+        .ok_or(CraftError::ElementNotFound)?;
+
+    let position_2 = children
+        .iter()
+        .position(|x| Rc::ptr_eq(x, &child_2))
+        .ok_or(CraftError::ElementNotFound)?;
+
+    // Swap the children.
+    parent.element_data_mut().children.swap(position_1, position_2);
+
+    // Swap the children's taffy nodes.
+    TAFFY_TREE.with_borrow_mut(|taffy_tree| {
+        let parent_id = parent.element_data().layout.taffy_node_id;
+        let child_1_id = child_1.borrow().element_data().layout.taffy_node_id;
+        let child_2_id = child_2.borrow().element_data().layout.taffy_node_id;
+
+        if let Some(parent_id) = parent_id
+            && let Some(child_1_id) = child_1_id
+            && let Some(child_2_id) = child_2_id
+        {
+            // There isn't a swap API in the taffy tree. Instead swap the children and call set_children.
+            let mut tchildren = taffy_tree.children(parent_id).to_vec();
+
+            let i1 = tchildren
+                .iter()
+                .position(|x| *x == child_1_id)
+                .ok_or(CraftError::ElementNotFound)
+                .expect("Failed to find taffy child");
+            let i2 = tchildren
+                .iter()
+                .position(|x| *x == child_2_id)
+                .ok_or(CraftError::ElementNotFound)
+                .expect("Failed to find taffy child");
+
+            tchildren.swap(i1, i2);
+
+            taffy_tree.set_children(parent_id, &tchildren);
+            taffy_tree.mark_dirty(parent_id);
+            taffy_tree.request_layout();
+        }
+    });
+
+    Ok(())
+}
+
+/// A helper to remove a child, see [`ElementInternals::remove_child`].
+pub fn remove_child_from_element<E: ElementInternals + ?Sized>(
+    parent: &mut E,
+    child: Rc<RefCell<dyn ElementInternals>>,
+) -> Result<Rc<RefCell<dyn ElementInternals>>, CraftError> {
+    // Find the node.
+    let children = &mut parent.element_data_mut().children;
+    let position = children
+        .iter()
+        .position(|x| Rc::ptr_eq(x, &child))
+        .ok_or(CraftError::ElementNotFound)?;
+
+    let child = children[position].clone();
+
+    // Remove the node from the element.
+
+    children.remove(position);
+
+    // Remove the parent reference.
+    child.borrow_mut().element_data_mut().parent = None;
+    //child.borrow_mut().element_data_mut().window = None;
+    child.borrow_mut().propagate_window_down();
+
+    TAFFY_TREE.with_borrow_mut(|taffy_tree| {
+        let child_id = child.borrow().element_data().layout.taffy_node_id;
+
+        if let Some(child_id) = child_id {
+            taffy_tree.unparent_node(child_id);
+        }
+
+        let parent_id = parent.element_data().layout.taffy_node_id;
+        taffy_tree.mark_dirty(parent_id.unwrap());
+    });
+
+    if let Some(pointer_capture) = parent.pointer_capture() {
+        remove_element_from_document(&child, &mut pointer_capture.borrow_mut());
+    }
+
+    child.borrow_mut().unfocus();
+
+    let parent = ElementHandle(parent.element_data().internal_id);
+    let removed = ElementHandle(child.borrow().element_data().internal_id);
+    record_mutation(Mutation::ChildRemoved { parent, child: removed });
+
+    Ok(child)
+}
+
+pub fn is_target_within(target: &Rc<RefCell<dyn ElementInternals>>, element: &impl AsElement) -> bool {
+    let element_id = element.borrow().element_data().internal_id;
+    let Ok(target) = target.try_borrow() else {
+        return false;
+    };
+    if target.element_data().internal_id == element_id {
+        return true;
+    }
+    target
+        .parent()
+        .and_then(|parent| parent.upgrade())
+        .is_some_and(|parent| parent.try_borrow().is_ok_and(|parent| parent.element_data().internal_id == element_id))
+}
+
+/// Puts text on the system clipboard. Does nothing without the `clipboard` feature.
+pub fn copy_to_clipboard(text: String) {
+    crate::clipboard::set_text(&text);
+}
+
+/// Inserts `child` into `parent` at `index`, shifting the children after it.
+pub fn insert_child_to_element(parent: &mut dyn ElementInternals, child: Rc<RefCell<dyn ElementInternals>>, index: usize) {
+    let element_data = parent.element_data_mut();
+    let me: Weak<RefCell<dyn ElementInternals>> = element_data.me.clone();
+    let me_window = element_data.window.clone();
+    child.borrow_mut().element_data_mut().parent = Some(me);
+    child.borrow_mut().element_data_mut().window = me_window;
+    child.borrow_mut().propagate_window_down();
+    let index = index.min(element_data.children.len());
+    element_data.children.insert(index, child.clone());
+    record_mutation(Mutation::ChildAdded {
+        parent: ElementHandle(element_data.internal_id),
+        child: ElementHandle(child.borrow().element_data().internal_id),
+        index,
+    });
+
+    // Insert the child's taffy node at the same index.
+    TAFFY_TREE.with_borrow_mut(|taffy_tree| {
+        let parent_id = element_data.layout.taffy_node_id.unwrap();
+        let child_id = child.borrow().element_data().layout.taffy_node_id;
+        if let Some(child_id) = child_id {
+            taffy_tree.add_child_at_index(parent_id, child_id, index);
+        }
+        child.borrow_mut().on_post_add_layout_tree(taffy_tree);
+    })
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn apply_generic_container_layout(
     element: &mut dyn ElementInternals,
@@ -194,6 +358,9 @@ pub fn add_generic_accesskit_data(
     tree.nodes.push((current_node_id, current_node));
 
     for child in element.children.iter_mut() {
+        if child.borrow().element_data().is_mask {
+            continue;
+        }
         child
             .borrow_mut()
             .compute_accessibility_tree(tree, Some(current_index), scale_factor);
@@ -210,10 +377,65 @@ pub fn draw_generic_container(
     if !element.is_visible() {
         return;
     }
+    element.maybe_start_overlay(renderer);
     element.add_hit_testable(renderer, true, scale_factor);
+    element.maybe_start_effect_layer(renderer, scale_factor);
+    element.maybe_start_mask(renderer, scale_factor);
     element.draw_borders(renderer, scale_factor);
     element.maybe_start_layer(renderer, scale_factor);
     element.draw_children(renderer, resource_manager.clone(), scale_factor, text_context);
     element.maybe_end_layer(renderer);
     element.draw_scrollbar(renderer, scale_factor);
+    element.maybe_end_mask(renderer, resource_manager, scale_factor, text_context);
+    element.maybe_end_effect_layer(renderer, scale_factor);
+    element.maybe_end_overlay(renderer);
+}
+
+/// Draws an element and its children to an image of its border box, in physical pixels at `scale_factor`.
+///
+/// Can be called while drawing a frame, as the frame's render list is put back afterwards. Returns `None` if the
+/// renderer can't render offscreen.
+pub fn capture_element(
+    element: &mut dyn ElementInternals,
+    renderer: &mut dyn Renderer,
+    resource_manager: Arc<ResourceManager>,
+    scale_factor: f64,
+    text_context: &mut TextContext,
+) -> Option<RgbaImage> {
+    let bounds = element.element_data().layout.computed_box_transformed.border_rectangle().scale(scale_factor);
+    draw_element_offscreen(element, renderer, resource_manager, scale_factor, text_context, bounds)
+        .map(|(image, _targets)| image)
+}
+
+/// Draws an element and its children to an image of `bounds`, in physical pixels at `scale_factor`, and returns it
+/// with the hit targets the element and its children pushed.
+///
+/// Can be called while drawing a frame, as the frame's render list is put back afterwards. Returns `None` if the
+/// renderer can't render offscreen.
+pub(crate) fn draw_element_offscreen(
+    element: &mut dyn ElementInternals,
+    renderer: &mut dyn Renderer,
+    resource_manager: Arc<ResourceManager>,
+    scale_factor: f64,
+    text_context: &mut TextContext,
+    bounds: Rectangle,
+) -> Option<(RgbaImage, Vec<TargetItem>)> {
+    let width = bounds.width.ceil().clamp(1.0, u16::MAX as f32) as u16;
+    let height = bounds.height.ceil().clamp(1.0, u16::MAX as f32) as u16;
+
+    // Hit testing uses the window's render list until the next frame.
+    let window_render_list = std::mem::take(renderer.render_list_mut());
+    // Subpixel text needs an opaque background.
+    renderer.set_text_antialiasing(TextAntialiasing::Grayscale);
+    renderer.set_text_contrast(window_render_list.text_contrast);
+    renderer.set_transform(Affine::translate((-bounds.x as f64, -bounds.y as f64)));
+    renderer.set_cull(Some(Rectangle::new(0.0, 0.0, width as f32, height as f32)));
+
+    element.draw(renderer, resource_manager.clone(), scale_factor, text_context);
+    renderer.sort_render_list();
+    let screenshot = renderer.render_offscreen(resource_manager, width, height);
+    let element_render_list = std::mem::replace(renderer.render_list_mut(), window_render_list);
+
+    let image = RgbaImage::from_raw(width as u32, height as u32, screenshot?.to_unpremultiplied_rgba8())?;
+    Some((image, element_render_list.targets))
 }
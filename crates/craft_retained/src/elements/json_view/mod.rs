@@ -0,0 +1,464 @@
+//! An expandable tree of JSON data.
+
+mod tree;
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use craft_primitives::geometry::{Affine, Point, Rectangle};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use peniko::Color;
+use serde_json::Value;
+use ui_events::keyboard::{Key, KeyState, NamedKey};
+use ui_events::pointer::PointerButton;
+
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, copy_to_clipboard, draw_generic_container, is_target_within, push_child_to_element};
+use crate::elements::json_view::tree::{flatten, search, visible_nodes, JsonNode, ValueKind};
+use crate::elements::traits::DeepClone;
+use crate::elements::{resolve_clip_for_scrollable, AsElement, Container, Element, ElementInternals, Text, TextInput};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::{AlignItems, Display, FlexDirection, FontFamily, Overflow, Position};
+use crate::text::text_context::TextContext;
+use crate::{auto, px, rgb};
+
+const KEY_COLOR: Color = rgb(136, 19, 145);
+const STRING_COLOR: Color = rgb(196, 26, 22);
+const NUMBER_COLOR: Color = rgb(28, 0, 207);
+const KEYWORD_COLOR: Color = rgb(13, 34, 170);
+const SUMMARY_COLOR: Color = rgb(110, 110, 110);
+const SELECTED_COLOR: Color = rgb(219, 234, 254);
+const MATCH_COLOR: Color = rgb(254, 243, 199);
+const INDENT: f32 = 16.0;
+
+/// The elements of a node's row.
+#[derive(Clone)]
+struct JsonRow {
+    row: Container,
+    toggle: Text,
+}
+
+#[derive(Clone)]
+pub struct JsonView {
+    pub inner: Rc<RefCell<JsonViewInner>>,
+}
+
+/// Shows JSON data as a tree of keys and values, colored by their type.
+///
+/// Clicking an object or array expands or collapses it. Typing in the search box shows only the keys and values
+/// containing the text and the objects and arrays around them. A right click on a row opens a menu to copy the
+/// path of its value, such as `$.users[0].name`, or the value itself. With a row selected, Ctrl+C copies its value
+/// and Ctrl+Shift+C its path, or Cmd on macOS.
+#[derive(Clone)]
+pub struct JsonViewInner {
+    element_data: ElementData,
+    value: Value,
+    nodes: Vec<JsonNode>,
+    expanded: Vec<bool>,
+    rows: Vec<JsonRow>,
+    /// The depth up to which nodes are expanded when the value is set.
+    expand_depth: usize,
+    query: String,
+    selected: Option<usize>,
+    /// The node the menu was opened for.
+    menu_node: Option<usize>,
+    pub search_input: TextInput,
+    pub tree: Container,
+    pub menu: Container,
+    pub copy_path_item: Container,
+    pub copy_value_item: Container,
+}
+
+impl Default for JsonView {
+    fn default() -> Self {
+        Self::new(Value::Null)
+    }
+}
+
+impl Element for JsonView {}
+
+impl Drop for JsonViewInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for JsonView {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for JsonViewInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for JsonViewInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        draw_generic_container(self, renderer, resource_manager, text_context, scale_factor);
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        match message {
+            // Text changes reach `on_event` twice, so the rows are only updated the first time.
+            EventKind::TextInputChanged(changed) if changed.value != self.query => {
+                self.query = changed.value.clone();
+                self.update_rows();
+            }
+            EventKind::PointerButtonUp(pointer_button) => {
+                let Some(target) = target else {
+                    return;
+                };
+
+                if let Some(node) = self.menu_node {
+                    if is_target_within(&target, &self.copy_path_item) {
+                        copy_to_clipboard(self.nodes[node].path.clone());
+                    } else if is_target_within(&target, &self.copy_value_item) {
+                        copy_to_clipboard(self.value_text(node));
+                    }
+                    self.close_menu();
+                    if is_target_within(&target, &self.menu) {
+                        return;
+                    }
+                }
+
+                let Some(node) = self.rows.iter().position(|row| is_target_within(&target, &row.row)) else {
+                    return;
+                };
+                self.focus();
+                self.selected = Some(node);
+                match pointer_button.button {
+                    Some(PointerButton::Primary) if self.nodes[node].subtree_end > node + 1 => {
+                        self.expanded[node] = !self.expanded[node];
+                    }
+                    Some(PointerButton::Secondary) => {
+                        self.open_menu(node, pointer_button.state.logical_point());
+                        event.prevent_defaults();
+                    }
+                    _ => {}
+                }
+                self.update_rows();
+            }
+            EventKind::KeyboardInputEvent(key) if key.state == KeyState::Down && self.is_focused() => {
+                let action_modifier = if cfg!(target_os = "macos") {
+                    key.modifiers.meta()
+                } else {
+                    key.modifiers.ctrl()
+                };
+
+                match &key.key {
+                    Key::Character(character) if action_modifier && character.eq_ignore_ascii_case("c") => {
+                        if let Some(node) = self.selected {
+                            copy_to_clipboard(if key.modifiers.shift() {
+                                self.nodes[node].path.clone()
+                            } else {
+                                self.value_text(node)
+                            });
+                        }
+                    }
+                    Key::Named(NamedKey::Escape) => self.close_menu(),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn apply_clip(&mut self, clip_bounds: Option<Rectangle>) {
+        let overflow = self.style().get_overflow();
+        if overflow[0] == Overflow::Scroll || overflow[1] == Overflow::Scroll {
+            resolve_clip_for_scrollable(self, clip_bounds);
+        } else {
+            self.element_data.layout.apply_clip(clip_bounds);
+        }
+    }
+
+    fn on_direction_changed(&mut self) {
+        self.update_rows();
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl JsonView {
+    pub fn new(value: Value) -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<JsonViewInner>>| {
+            RefCell::new(JsonViewInner {
+                element_data: ElementData::new(me.clone(), false),
+                value: Value::Null,
+                nodes: Vec::new(),
+                expanded: Vec::new(),
+                rows: Vec::new(),
+                expand_depth: 1,
+                query: String::new(),
+                selected: None,
+                menu_node: None,
+                search_input: TextInput::new(""),
+                tree: Container::new()
+                    .display(Display::Flex)
+                    .flex_direction(FlexDirection::Column)
+                    .overflow_y(Overflow::Scroll)
+                    .flex_grow(1.0)
+                    .min_height(px(0))
+                    .font_family(FontFamily::new("monospace"))
+                    .font_size(13.0),
+                menu: Container::new()
+                    .display(Display::None)
+                    .flex_direction(FlexDirection::Column)
+                    .position(Position::Absolute)
+                    .padding(px(4), px(0), px(4), px(0))
+                    .border_width_all(px(1))
+                    .border_color_all(rgb(199, 199, 206))
+                    .border_radius_all(px(4))
+                    .background_color(Color::WHITE),
+                copy_path_item: menu_item("Copy path"),
+                copy_value_item: menu_item("Copy value"),
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        inner_mut.set_display(Display::Flex);
+        inner_mut.set_flex_direction(FlexDirection::Column);
+        inner_mut.set_gap(px(4), px(4));
+
+        let search_input = inner_mut.search_input.clone();
+        let tree = inner_mut.tree.clone();
+        let menu = inner_mut
+            .menu
+            .clone()
+            .push(inner_mut.copy_path_item.clone())
+            .push(inner_mut.copy_value_item.clone());
+        inner_mut.push(search_input.inner);
+        inner_mut.push(tree.inner);
+        inner_mut.push(menu.inner);
+        inner_mut.set_value(value);
+
+        drop(inner_mut);
+        Self { inner }
+    }
+
+    /// Creates a view of any serializable data, as it would be serialized to JSON.
+    pub fn from_serializable<T: serde::Serialize + ?Sized>(data: &T) -> Result<Self, serde_json::Error> {
+        Ok(Self::new(serde_json::to_value(data)?))
+    }
+
+    /// Set the depth up to which objects and arrays start expanded. Defaults to 1, showing the root's children.
+    pub fn expand_depth(self, expand_depth: usize) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.expand_depth = expand_depth;
+        let value = inner.value.take();
+        inner.set_value(value);
+        drop(inner);
+        self
+    }
+
+    pub fn set_value(&self, value: Value) {
+        self.inner.borrow_mut().set_value(value);
+    }
+
+    pub fn get_value(&self) -> Value {
+        self.inner.borrow().value.clone()
+    }
+
+    pub fn search(self, query: &str) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.query = query.to_string();
+        inner.search_input.inner.borrow_mut().set_text(query);
+        inner.update_rows();
+        drop(inner);
+        self
+    }
+
+    pub fn expand_all(&self) {
+        self.inner.borrow_mut().set_all_expanded(true);
+    }
+
+    pub fn collapse_all(&self) {
+        self.inner.borrow_mut().set_all_expanded(false);
+    }
+
+    /// Returns the path of the selected value, such as `$.users[0].name`.
+    pub fn get_selected_path(&self) -> Option<String> {
+        let inner = self.inner.borrow();
+        inner.selected.map(|node| inner.nodes[node].path.clone())
+    }
+}
+
+impl JsonViewInner {
+    pub fn set_value(&mut self, value: Value) {
+        self.nodes = flatten(&value);
+        self.value = value;
+        self.expanded = self.nodes.iter().map(|node| node.depth < self.expand_depth).collect();
+        self.selected = None;
+        self.close_menu();
+
+        self.tree.remove_all_children();
+        self.rows = self.nodes.iter().map(node_row).collect();
+        for row in &self.rows {
+            self.tree.clone().push(row.row.clone());
+        }
+        self.update_rows();
+    }
+
+    pub fn set_all_expanded(&mut self, expanded: bool) {
+        self.expanded.fill(expanded);
+        self.update_rows();
+    }
+
+    /// Shows the rows of the visible nodes, with their toggles and highlights.
+    fn update_rows(&mut self) {
+        let matches = (!self.query.is_empty()).then(|| search(&self.nodes, &self.query));
+        let visible = visible_nodes(&self.nodes, &self.expanded, matches.as_deref());
+
+        for (index, row) in self.rows.iter().enumerate() {
+            let node = &self.nodes[index];
+            let toggle = if node.subtree_end == index + 1 {
+                " "
+            } else if self.expanded[index] || matches.is_some() {
+                "▾"
+            } else if self.style().is_mirrored() {
+                "◂"
+            } else {
+                "▸"
+            };
+            row.toggle.clone().text(toggle);
+
+            let background = if self.selected == Some(index) {
+                SELECTED_COLOR
+            } else if matches.as_ref().is_some_and(|matches| matches[index]) {
+                MATCH_COLOR
+            } else {
+                Color::TRANSPARENT
+            };
+            row.row
+                .clone()
+                .display(if visible[index] { Display::Flex } else { Display::None })
+                .background_color(background);
+        }
+    }
+
+    /// The text copied for a value: strings without quotes, and other values as pretty printed JSON.
+    fn value_text(&self, node: usize) -> String {
+        match self.value.pointer(&self.nodes[node].pointer) {
+            Some(Value::String(string)) => string.clone(),
+            Some(value) => serde_json::to_string_pretty(value).unwrap_or_default(),
+            None => String::new(),
+        }
+    }
+
+    fn open_menu(&mut self, node: usize, point: Point) {
+        self.menu_node = Some(node);
+        // Absolutely positioned elements are placed from the padding box of their parent.
+        let padding_rectangle = self.element_data.layout.computed_box_transformed.padding_rectangle();
+        self.menu
+            .clone()
+            .display(Display::Flex)
+            .inset(
+                px(point.y as f32 - padding_rectangle.y),
+                auto(),
+                auto(),
+                px(point.x as f32 - padding_rectangle.x),
+            );
+    }
+
+    fn close_menu(&mut self) {
+        self.menu_node = None;
+        self.menu.clone().display(Display::None);
+    }
+}
+
+fn menu_item(label: &str) -> Container {
+    Container::new()
+        .padding(px(4), px(12), px(4), px(12))
+        .push(Text::new(label).selectable(false))
+}
+
+fn node_row(node: &JsonNode) -> JsonRow {
+    let toggle = Text::new(" ").selectable(false).width(px(INDENT)).flex_shrink(0.0);
+    let row = Container::new()
+        .display(Display::Flex)
+        .flex_direction(FlexDirection::Row)
+        .align_items(Some(AlignItems::Center))
+        .padding(px(1), px(8), px(1), px(4.0 + node.depth as f32 * INDENT))
+        .push(toggle.clone());
+
+    let row = if node.key.is_empty() {
+        row
+    } else {
+        row.push(Text::new(&format!("{}: ", node.key)).selectable(false).color(KEY_COLOR))
+    };
+    let value_color = match node.kind {
+        ValueKind::Object | ValueKind::Array => SUMMARY_COLOR,
+        ValueKind::String => STRING_COLOR,
+        ValueKind::Number => NUMBER_COLOR,
+        ValueKind::Bool | ValueKind::Null => KEYWORD_COLOR,
+    };
+    let row = row.push(Text::new(&node.text).selectable(false).color(value_color));
+
+    JsonRow { row, toggle }
+}
@@ -0,0 +1,190 @@
+//! The nodes of a JSON value, flattened in the order they are shown.
+
+use serde_json::Value;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ValueKind {
+    Object,
+    Array,
+    String,
+    Number,
+    Bool,
+    Null,
+}
+
+/// A value in the tree, with where it is and how it is shown.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct JsonNode {
+    pub(crate) depth: usize,
+    pub(crate) parent: Option<usize>,
+    /// The index of the first node after the node's descendants.
+    pub(crate) subtree_end: usize,
+    /// The object key or array index of the node, empty for the root.
+    pub(crate) key: String,
+    /// The path of the node in JavaScript syntax, such as `$.users[0].name`.
+    pub(crate) path: String,
+    /// The JSON pointer of the node, used to find its value.
+    pub(crate) pointer: String,
+    pub(crate) kind: ValueKind,
+    /// The value for scalars, or the number of children for objects and arrays.
+    pub(crate) text: String,
+}
+
+impl JsonNode {
+    pub(crate) fn is_container(&self) -> bool {
+        matches!(self.kind, ValueKind::Object | ValueKind::Array)
+    }
+}
+
+/// Flattens a value into its nodes, each followed by its descendants.
+pub(crate) fn flatten(value: &Value) -> Vec<JsonNode> {
+    let mut nodes = Vec::new();
+    push_node(&mut nodes, value, None, String::new(), "$".to_string(), String::new());
+    nodes
+}
+
+fn push_node(
+    nodes: &mut Vec<JsonNode>,
+    value: &Value,
+    parent: Option<usize>,
+    key: String,
+    path: String,
+    pointer: String,
+) {
+    let index = nodes.len();
+    let (kind, text) = match value {
+        Value::Object(object) => (ValueKind::Object, format!("{{{}}}", object.len())),
+        Value::Array(array) => (ValueKind::Array, format!("[{}]", array.len())),
+        Value::String(_) => (ValueKind::String, value.to_string()),
+        Value::Number(number) => (ValueKind::Number, number.to_string()),
+        Value::Bool(boolean) => (ValueKind::Bool, boolean.to_string()),
+        Value::Null => (ValueKind::Null, "null".to_string()),
+    };
+    nodes.push(JsonNode {
+        depth: parent.map_or(0, |parent| nodes[parent].depth + 1),
+        parent,
+        subtree_end: index + 1,
+        key,
+        path,
+        pointer,
+        kind,
+        text,
+    });
+
+    match value {
+        Value::Object(object) => {
+            for (key, child) in object {
+                let path = format!("{}{}", nodes[index].path, key_path(key));
+                let pointer = format!("{}/{}", nodes[index].pointer, key.replace('~', "~0").replace('/', "~1"));
+                push_node(nodes, child, Some(index), key.clone(), path, pointer);
+            }
+        }
+        Value::Array(array) => {
+            for (child_index, child) in array.iter().enumerate() {
+                let path = format!("{}[{child_index}]", nodes[index].path);
+                let pointer = format!("{}/{child_index}", nodes[index].pointer);
+                push_node(nodes, child, Some(index), child_index.to_string(), path, pointer);
+            }
+        }
+        _ => {}
+    }
+    nodes[index].subtree_end = nodes.len();
+}
+
+/// Returns `.key` for keys that are identifiers, and `["key"]` for other keys.
+fn key_path(key: &str) -> String {
+    let mut characters = key.chars();
+    let is_identifier = characters
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_' || first == '$')
+        && characters.all(|character| character.is_alphanumeric() || character == '_' || character == '$');
+
+    if is_identifier {
+        format!(".{key}")
+    } else {
+        format!("[{}]", Value::String(key.to_string()))
+    }
+}
+
+/// Returns which nodes contain the query in their key or value, ignoring case.
+pub(crate) fn search(nodes: &[JsonNode], query: &str) -> Vec<bool> {
+    let query = query.to_lowercase();
+    nodes
+        .iter()
+        .map(|node| {
+            node.key.to_lowercase().contains(&query) || (!node.is_container() && node.text.to_lowercase().contains(&query))
+        })
+        .collect()
+}
+
+/// Returns which nodes are shown.
+///
+/// Without a search, a node is shown when all its ancestors are expanded. With a search, the matching nodes and their
+/// ancestors are shown, whether they are expanded or not.
+pub(crate) fn visible_nodes(nodes: &[JsonNode], expanded: &[bool], matches: Option<&[bool]>) -> Vec<bool> {
+    let mut visible = vec![false; nodes.len()];
+    match matches {
+        None => {
+            for (index, node) in nodes.iter().enumerate() {
+                visible[index] = node.parent.is_none_or(|parent| visible[parent] && expanded[parent]);
+            }
+        }
+        Some(matches) => {
+            for index in (0..nodes.len()).rev() {
+                if matches[index] || visible[index] {
+                    visible[index] = true;
+                    if let Some(parent) = nodes[index].parent {
+                        visible[parent] = true;
+                    }
+                }
+            }
+        }
+    }
+    visible
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn flattens_values_in_order() {
+        let nodes = flatten(&json!({ "name": "craft", "tags": ["ui", 1] }));
+        let paths: Vec<&str> = nodes.iter().map(|node| node.path.as_str()).collect();
+
+        assert_eq!(paths, vec!["$", "$.name", "$.tags", "$.tags[0]", "$.tags[1]"]);
+        assert_eq!(nodes[0].subtree_end, 5);
+        assert_eq!(nodes[2].subtree_end, 5);
+        assert_eq!(nodes[2].text, "[2]");
+        assert_eq!(nodes[1].text, "\"craft\"");
+        assert_eq!(nodes[4].depth, 2);
+    }
+
+    #[test]
+    fn paths_and_pointers_escape_keys() {
+        let nodes = flatten(&json!({ "a/b c": { "~x": true } }));
+
+        assert_eq!(nodes[1].path, "$[\"a/b c\"]");
+        assert_eq!(nodes[2].path, "$[\"a/b c\"][\"~x\"]");
+        assert_eq!(nodes[2].pointer, "/a~1b c/~0x");
+    }
+
+    #[test]
+    fn collapsed_nodes_hide_their_descendants() {
+        let nodes = flatten(&json!({ "a": { "b": 1 }, "c": 2 }));
+        let expanded = vec![true, false, false, false];
+
+        assert_eq!(visible_nodes(&nodes, &expanded, None), vec![true, true, false, true]);
+    }
+
+    #[test]
+    fn searches_show_the_ancestors_of_matches() {
+        let nodes = flatten(&json!({ "a": { "b": "Needle" }, "c": 2 }));
+        let matches = search(&nodes, "needle");
+        let expanded = vec![false; nodes.len()];
+
+        assert_eq!(matches, vec![false, false, true, false]);
+        assert_eq!(visible_nodes(&nodes, &expanded, Some(&matches)), vec![true, true, true, false]);
+    }
+}
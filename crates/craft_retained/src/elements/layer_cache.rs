@@ -0,0 +1,223 @@
+//! Draws a subtree into an offscreen layer, and composites the layer every frame until the subtree changes.
+//!
+//! A subtree is only drawn into a layer once it went a frame without changing, so a subtree that changes every frame
+//! is drawn directly instead of into a new layer each frame. Each layer is stored as a new image resource, and the
+//! resource manager keeps its resources for the lifetime of the app, so layers suit subtrees that rarely change.
+//!
+//! Text in a layer is antialiased in grayscale, as the layer is transparent.
+
+use std::cell::{Cell, RefCell};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use craft_primitives::geometry::Rectangle;
+use craft_renderer::TargetItem;
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::image::ImageResource;
+use craft_resource_manager::resource::Resource;
+use craft_resource_manager::resource_type::ResourceType;
+use craft_resource_manager::{ResourceId, ResourceManager};
+
+use crate::elements::ElementInternals;
+use crate::elements::internal_helpers::draw_element_offscreen;
+use crate::scene::scene_generation;
+use crate::text::text_context::TextContext;
+
+/// Subtrees larger than this in either direction, in physical pixels, are drawn directly instead of into a layer.
+const MAX_LAYER_SIZE: f32 = 4096.0;
+
+/// The layer of an element drawn with [`Element::cache_as_layer`](crate::elements::Element::cache_as_layer).
+///
+/// The layer is drawn again when the subtree is laid out differently, when an element of the subtree is marked dirty,
+/// has its style changed, requests a redraw, or handles an event that acts on it, like a click, a key or a drag.
+/// Elements that change what they draw on other events must request a redraw.
+#[derive(Default)]
+pub(crate) struct LayerCache {
+    /// Cleared when the subtree changes. A cell, so that elements can invalidate it while only borrowed.
+    is_valid: Cell<bool>,
+    /// What the subtree was last drawn from.
+    key: Option<LayerKey>,
+    layer: Option<Layer>,
+    /// Set once the renderer couldn't draw a layer, after which the subtree is always drawn directly.
+    is_unsupported: bool,
+}
+
+/// Everything outside the element's own changes that affects its layer.
+#[derive(Clone, Copy, PartialEq)]
+struct LayerKey {
+    /// The bounds of the layer relative to the element's border box. Includes where the element sits within a
+    /// physical pixel, as layers are drawn at whole pixels.
+    bounds: Rectangle,
+    scale_factor: f64,
+    /// A hash of where the descendants are relative to the element, which changes when they move, resize or scroll.
+    layout: u64,
+}
+
+struct Layer {
+    resource_id: ResourceId,
+    /// The hit targets of the subtree, relative to the element's border box.
+    targets: Vec<TargetItem>,
+}
+
+impl Clone for LayerCache {
+    /// Cloned elements start without a layer, as the layer shows the original element.
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl LayerCache {
+    pub(crate) fn invalidate(&self) {
+        self.is_valid.set(false);
+    }
+}
+
+/// Draws an element, compositing its layer if nothing affecting the layer has changed.
+pub(crate) fn draw_with_layer_cache(
+    element: &mut dyn ElementInternals,
+    renderer: &mut dyn Renderer,
+    resource_manager: Arc<ResourceManager>,
+    scale_factor: f64,
+    text_context: &mut TextContext,
+) {
+    let border_rectangle = element.element_data().layout.computed_box_transformed.border_rectangle();
+    let origin = border_rectangle.scale(scale_factor);
+    let subtree_bounds = element.subtree_bounds().scale(scale_factor);
+    let left = subtree_bounds.x.floor();
+    let top = subtree_bounds.y.floor();
+    let bounds = Rectangle::new(
+        left,
+        top,
+        (subtree_bounds.x + subtree_bounds.width - left).ceil(),
+        (subtree_bounds.y + subtree_bounds.height - top).ceil(),
+    );
+
+    // A layer is drawn at whole pixels, so it would blur once scaled or rotated.
+    let [a, b, c, d, _, _] = renderer.get_transform().as_coeffs();
+    let is_translation = a == 1.0 && b == 0.0 && c == 0.0 && d == 1.0;
+    let draws_directly = element.element_data().layer_cache.as_ref().is_none_or(|cache| cache.is_unsupported);
+    if draws_directly || !is_translation || bounds.width > MAX_LAYER_SIZE || bounds.height > MAX_LAYER_SIZE {
+        element.draw(renderer, resource_manager, scale_factor, text_context);
+        return;
+    }
+
+    let key = LayerKey {
+        bounds: Rectangle::new(bounds.x - origin.x, bounds.y - origin.y, bounds.width, bounds.height),
+        scale_factor,
+        layout: layout_hash(element, border_rectangle),
+    };
+    let layer_cache = element.element_data().layer_cache.as_ref().unwrap();
+    let is_unchanged = layer_cache.is_valid.get() && layer_cache.key == Some(key);
+    if is_unchanged && let Some(layer) = &layer_cache.layer {
+        composite(renderer, layer, key.bounds, origin);
+        return;
+    }
+
+    let generation = scene_generation();
+    let mut layer = None;
+    let mut is_unsupported = false;
+    if is_unchanged {
+        match draw_element_offscreen(element, renderer, resource_manager.clone(), scale_factor, text_context, bounds) {
+            Some((image, targets)) => {
+                let resource_id = ResourceId::generate();
+                let resource = Resource {
+                    resource_type: ResourceType::Image,
+                    data: Box::new(ImageResource { image }),
+                    expiration_time: None,
+                };
+                resource_manager.insert(resource_id.clone(), Arc::new(resource));
+                let targets = targets
+                    .into_iter()
+                    .map(|mut target| {
+                        target.rectangle.x -= origin.x;
+                        target.rectangle.y -= origin.y;
+                        target
+                    })
+                    .collect();
+                let drawn_layer = Layer { resource_id, targets };
+                composite(renderer, &drawn_layer, key.bounds, origin);
+                layer = Some(drawn_layer);
+            }
+            None => {
+                is_unsupported = true;
+                element.draw(renderer, resource_manager, scale_factor, text_context);
+            }
+        }
+    } else {
+        element.draw(renderer, resource_manager, scale_factor, text_context);
+    }
+
+    // A subtree that changed while it was drawn, e.g. because it animates, is drawn directly again next frame.
+    let is_stable = scene_generation() == generation;
+    if let Some(layer_cache) = &mut element.element_data_mut().layer_cache {
+        layer_cache.is_valid.set(is_stable);
+        layer_cache.key = Some(key);
+        layer_cache.layer = layer.filter(|_| is_stable);
+        layer_cache.is_unsupported = is_unsupported;
+    }
+}
+
+/// Draws a layer at the element's border box origin, and pushes the hit targets of its subtree.
+fn composite(renderer: &mut dyn Renderer, layer: &Layer, bounds: Rectangle, origin: Rectangle) {
+    let x = origin.x;
+    let y = origin.y;
+    renderer.draw_image(
+        Rectangle::new(bounds.x + x, bounds.y + y, bounds.width, bounds.height),
+        layer.resource_id.clone(),
+    );
+
+    let overlay_depth = renderer.render_list().current_overlay_depth;
+    for target in &layer.targets {
+        let rectangle = Rectangle::new(
+            target.rectangle.x + x,
+            target.rectangle.y + y,
+            target.rectangle.width,
+            target.rectangle.height,
+        );
+        if !renderer.is_culled(&rectangle) {
+            renderer.render_list_mut().targets.push(TargetItem::new(
+                target.custom_id,
+                rectangle,
+                target.overlay_depth + overlay_depth,
+            ));
+        }
+    }
+}
+
+/// Hashes the boxes of the element's descendants relative to the element's border box.
+fn layout_hash(element: &dyn ElementInternals, border_rectangle: Rectangle) -> u64 {
+    fn hash_children(element: &dyn ElementInternals, border_rectangle: Rectangle, hasher: &mut DefaultHasher) {
+        for child in element.children() {
+            let child = child.borrow();
+            let computed_box = child.element_data().layout.computed_box_transformed;
+            for rectangle in [computed_box.border_rectangle(), computed_box.content_rectangle()] {
+                (rectangle.x - border_rectangle.x).to_bits().hash(hasher);
+                (rectangle.y - border_rectangle.y).to_bits().hash(hasher);
+                rectangle.width.to_bits().hash(hasher);
+                rectangle.height.to_bits().hash(hasher);
+            }
+            hash_children(&*child, border_rectangle, hasher);
+        }
+    }
+
+    let mut hasher = DefaultHasher::new();
+    border_rectangle.width.to_bits().hash(&mut hasher);
+    border_rectangle.height.to_bits().hash(&mut hasher);
+    hash_children(element, border_rectangle, &mut hasher);
+    hasher.finish()
+}
+
+/// Invalidates the layers of the elements in the subtree that draw `resource_id`, as they were drawn before the
+/// resource finished loading.
+pub(crate) fn invalidate_resource_users(root: Rc<RefCell<dyn ElementInternals>>, resource_id: &ResourceId) {
+    let mut stack = vec![root];
+    while let Some(element) = stack.pop() {
+        let borrowed = element.borrow();
+        stack.extend(borrowed.children().iter().cloned());
+        if borrowed.uses_resource(resource_id) {
+            borrowed.invalidate_layer_cache();
+        }
+    }
+}
@@ -0,0 +1,660 @@
+//! A vertical list of items that can be selected with the pointer or the keyboard.
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::BTreeSet;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+#[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+use accesskit::{Role, TreeUpdate};
+use craft_primitives::geometry::{Affine, Point, Rectangle};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use peniko::Color;
+use ui_events::keyboard::{Code, Key, KeyState, Modifiers};
+use ui_events::pointer::PointerButton;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time;
+#[cfg(target_arch = "wasm32")]
+use web_time as time;
+
+use time::{Duration, Instant};
+
+use crate::app::queue_event;
+use crate::elements::element_data::ElementData;
+#[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+use crate::elements::internal_helpers::add_generic_accesskit_data;
+use crate::elements::internal_helpers::{
+    apply_generic_container_layout, draw_generic_container, push_child_to_element, remove_child_from_element,
+    swap_children_of_element,
+};
+use crate::elements::layer_cache::draw_with_layer_cache;
+use crate::elements::traits::DeepClone;
+use crate::elements::{resolve_clip_for_scrollable, scrollable, AsElement, Element, ElementInternals, TextInner};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::{Display, FlexDirection, Overflow};
+use crate::text::text_context::TextContext;
+use crate::CraftError;
+
+/// How long the typed characters are remembered when jumping to an item by typing its name.
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// How many items can be selected at once in a [`ListBox`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SelectionMode {
+    /// At most one item is selected.
+    #[default]
+    Single,
+    /// Any number of items can be selected with Ctrl-click, Shift-click, and Shift with the arrow keys.
+    Multiple,
+}
+
+/// A vertical list of items that can be selected with the pointer or the keyboard.
+///
+/// Each child is an item. Typing the start of an item's text jumps to it, if the item is a [`Text`](crate::elements::Text).
+/// A [`EventKind::ListBoxSelectionChanged`] event with the selected indices is sent whenever the selection changes.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::rc::Rc;
+/// use craft_retained::elements::{Element, ListBox, SelectionMode, Text, Window};
+/// use craft_retained::{CraftOptions, craft_main};
+///
+/// fn main() {
+///     Window::new("List Box").push(
+///         ListBox::new()
+///             .selection_mode(SelectionMode::Multiple)
+///             .push(Text::new("Apple").selectable(false))
+///             .push(Text::new("Banana").selectable(false))
+///             .push(Text::new("Cherry").selectable(false))
+///             .on_list_box_selection_changed(Rc::new(|_event, selected| println!("{selected:?}"))),
+///     );
+///     craft_main(CraftOptions::basic("List Box"));
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ListBox {
+    pub inner: Rc<RefCell<ListBoxInner>>,
+}
+
+#[derive(Clone)]
+pub struct ListBoxInner {
+    element_data: ElementData,
+    selection: ListSelection,
+    type_ahead: String,
+    last_type_ahead: Option<Instant>,
+    selected_background_color: Color,
+    active_outline_color: Color,
+}
+
+impl Default for ListBox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Element for ListBox {}
+
+impl Drop for ListBoxInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for ListBox {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for ListBoxInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for ListBoxInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        draw_generic_container(self, renderer, resource_manager, text_context, scale_factor);
+    }
+
+    fn draw_children(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        let is_focused = self.is_focused();
+        let content_rectangle = self.element_data.layout.computed_box_transformed.content_rectangle();
+
+        for (index, child) in self.children().iter().enumerate() {
+            let mut child = child.borrow_mut();
+            if child.element_data().is_mask || child.is_outside_viewport(renderer, scale_factor) {
+                continue;
+            }
+
+            // Highlight the whole row, not just the item's content.
+            let mut row = child.element_data().layout.computed_box_transformed.border_rectangle();
+            row.x = content_rectangle.x;
+            row.width = content_rectangle.width;
+
+            if self.selection.selected.contains(&index) {
+                renderer.draw_rect(row.scale(scale_factor), self.selected_background_color);
+            }
+            if is_focused && self.selection.active == Some(index) {
+                renderer.draw_rect_outline(row.scale(scale_factor), self.active_outline_color, scale_factor);
+            }
+
+            if child.element_data().layer_cache.is_some() {
+                draw_with_layer_cache(&mut *child, renderer, resource_manager.clone(), scale_factor, text_context);
+            } else {
+                child.draw(renderer, resource_manager.clone(), scale_factor, text_context);
+            }
+        }
+    }
+
+    #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+    fn compute_accessibility_tree(&mut self, tree: &mut TreeUpdate, parent_index: Option<usize>, scale_factor: f64) {
+        let current_node_id = accesskit::NodeId(self.element_data().internal_id);
+
+        let mut current_node = accesskit::Node::new(Role::ListBox);
+        if self.selection.mode == SelectionMode::Multiple {
+            current_node.set_multiselectable();
+        }
+
+        add_generic_accesskit_data(
+            &mut self.element_data,
+            current_node,
+            current_node_id,
+            tree,
+            parent_index,
+            scale_factor,
+        );
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        _target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        scrollable::handle_scroll_logic(self, message, event);
+
+        let changed = match message {
+            EventKind::PointerButtonDown(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                self.focus();
+
+                let pointer_position = pointer_button.state.logical_point();
+                let modifiers = pointer_button.state.modifiers;
+                let len = self.children().len();
+                match self.item_at(&pointer_position) {
+                    Some(index) => self.selection.click(index, len, is_toggle_modifier(modifiers), modifiers.shift()),
+                    None => false,
+                }
+            }
+            EventKind::KeyboardInputEvent(key) => {
+                if key.state != KeyState::Down || !self.is_focused() {
+                    return;
+                }
+                self.on_key_down(key.code, &key.key, key.modifiers)
+            }
+            _ => false,
+        };
+
+        if changed {
+            let new_event = Event::new(event.target.clone());
+            queue_event(new_event, EventKind::ListBoxSelectionChanged(self.selection.selected.clone()));
+        }
+    }
+
+    fn apply_clip(&mut self, clip_bounds: Option<Rectangle>) {
+        let overflow = self.style().get_overflow();
+        if overflow[0] == Overflow::Scroll || overflow[1] == Overflow::Scroll {
+            resolve_clip_for_scrollable(self, clip_bounds);
+        } else {
+            self.element_data.layout.apply_clip(clip_bounds);
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn remove_child(
+        &mut self,
+        child: Rc<RefCell<dyn ElementInternals>>,
+    ) -> Result<Rc<RefCell<dyn ElementInternals>>, CraftError> {
+        let index = self.item_index(&child);
+        let child = remove_child_from_element(self, child)?;
+        if let Some(index) = index {
+            self.selection.remove_item(index);
+        }
+        Ok(child)
+    }
+
+    fn swap_child(
+        &mut self,
+        child_1: Rc<RefCell<dyn ElementInternals>>,
+        child_2: Rc<RefCell<dyn ElementInternals>>,
+    ) -> Result<(), CraftError> {
+        let indices = self.item_index(&child_1).zip(self.item_index(&child_2));
+        swap_children_of_element(self, child_1, child_2)?;
+        if let Some((index_1, index_2)) = indices {
+            self.selection.swap_items(index_1, index_2);
+        }
+        Ok(())
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn handles_arrow_keys(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl ListBoxInner {
+    /// Returns the index of an item.
+    fn item_index(&self, child: &Rc<RefCell<dyn ElementInternals>>) -> Option<usize> {
+        self.children().iter().position(|item| Rc::ptr_eq(item, child))
+    }
+
+    /// Returns the index of the item under the pointer.
+    fn item_at(&self, point: &Point) -> Option<usize> {
+        let content_rectangle = self.element_data.layout.computed_box_transformed.content_rectangle();
+
+        self.children().iter().position(|child| {
+            let row = child.borrow().element_data().layout.computed_box_transformed.border_rectangle();
+            point.y >= row.y as f64
+                && point.y < (row.y + row.height) as f64
+                && point.x >= content_rectangle.x as f64
+                && point.x < (content_rectangle.x + content_rectangle.width) as f64
+        })
+    }
+
+    /// Returns the text of each item, or an empty string for items that aren't text.
+    fn item_labels(&self) -> Vec<String> {
+        self.children()
+            .iter()
+            .map(|child| {
+                child
+                    .borrow()
+                    .as_any()
+                    .downcast_ref::<TextInner>()
+                    .map(|text| text.get_text().to_string())
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    /// Handles a key press and returns true if the selection changed.
+    fn on_key_down(&mut self, code: Code, key: &Key, modifiers: Modifiers) -> bool {
+        let len = self.children().len();
+        if len == 0 {
+            return false;
+        }
+
+        let active = self.selection.active;
+        let target = match code {
+            Code::ArrowUp => Some(active.map_or(0, |active| active.saturating_sub(1))),
+            Code::ArrowDown => Some(active.map_or(0, |active| (active + 1).min(len - 1))),
+            Code::Home => Some(0),
+            Code::End => Some(len - 1),
+            _ => None,
+        };
+        if let Some(target) = target {
+            return self.selection.click(target, len, false, modifiers.shift());
+        }
+
+        match code {
+            Code::Space => {
+                return active
+                    .is_some_and(|active| self.selection.click(active, len, is_toggle_modifier(modifiers), false));
+            }
+            Code::KeyA if is_toggle_modifier(modifiers) => return self.selection.select_all(len),
+            _ => {}
+        }
+
+        if let Key::Character(characters) = key
+            && !is_toggle_modifier(modifiers)
+        {
+            return self.type_ahead(characters);
+        }
+
+        false
+    }
+
+    /// Jumps to the next item whose text starts with the recently typed characters.
+    fn type_ahead(&mut self, characters: &str) -> bool {
+        let now = Instant::now();
+        let expired = self
+            .last_type_ahead
+            .is_none_or(|last| now.duration_since(last) > TYPE_AHEAD_TIMEOUT);
+        if expired {
+            self.type_ahead.clear();
+        }
+        self.last_type_ahead = Some(now);
+        self.type_ahead.push_str(&characters.to_lowercase());
+
+        // Typing more characters refines the current match, while a new search starts after the active item.
+        let start = match self.selection.active {
+            Some(active) if self.type_ahead.chars().count() > 1 => active,
+            Some(active) => active + 1,
+            None => 0,
+        };
+
+        let labels = self.item_labels();
+        match find_type_ahead_match(&labels, start, &self.type_ahead) {
+            Some(index) => self.selection.click(index, labels.len(), false, false),
+            None => false,
+        }
+    }
+}
+
+impl ListBox {
+    pub fn new() -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<ListBoxInner>>| {
+            RefCell::new(ListBoxInner {
+                element_data: ElementData::new(me.clone(), true),
+                selection: ListSelection::default(),
+                type_ahead: String::new(),
+                last_type_ahead: None,
+                selected_background_color: Color::from_rgb8(204, 228, 247),
+                active_outline_color: Color::from_rgb8(0, 120, 215),
+            })
+        });
+
+        inner.borrow_mut().element_data.create_layout_node(None);
+        inner.borrow_mut().element_data.style.set_display(Display::Flex);
+        inner
+            .borrow_mut()
+            .element_data
+            .style
+            .set_flex_direction(FlexDirection::Column);
+
+        Self { inner }
+    }
+
+    pub fn selection_mode(self, selection_mode: SelectionMode) -> Self {
+        self.inner.borrow_mut().selection.set_mode(selection_mode);
+        self
+    }
+
+    pub fn get_selection_mode(&self) -> SelectionMode {
+        self.inner.borrow().selection.mode
+    }
+
+    /// Replaces the selected items, without sending a selection change event.
+    ///
+    /// Only the first item is kept in [`SelectionMode::Single`], and indices past the last item are ignored.
+    pub fn selected_items(self, indices: impl IntoIterator<Item = usize>) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        let len = inner.children().len();
+        inner.selection.set_selected(indices, len);
+        drop(inner);
+        self
+    }
+
+    pub fn get_selected_items(&self) -> BTreeSet<usize> {
+        self.inner.borrow().selection.selected.clone()
+    }
+
+    pub fn selected_background_color(self, color: Color) -> Self {
+        self.inner.borrow_mut().selected_background_color = color;
+        self
+    }
+}
+
+/// Returns true if the modifiers toggle single items, i.e. Ctrl, or Cmd on macOS.
+fn is_toggle_modifier(modifiers: Modifiers) -> bool {
+    modifiers.ctrl() || modifiers.meta()
+}
+
+/// Returns the first item at or after `start`, wrapping around, whose label starts with `prefix`, ignoring case.
+fn find_type_ahead_match(labels: &[String], start: usize, prefix: &str) -> Option<usize> {
+    let len = labels.len();
+    (0..len)
+        .map(|offset| (start + offset) % len)
+        .find(|&index| labels[index].to_lowercase().starts_with(prefix))
+}
+
+/// The selected items of a list, along with the anchor of range selections and the item with keyboard focus.
+#[derive(Clone, Debug, Default)]
+struct ListSelection {
+    mode: SelectionMode,
+    selected: BTreeSet<usize>,
+    /// The item a Shift-click range starts from.
+    anchor: Option<usize>,
+    /// The item moved by the arrow keys.
+    active: Option<usize>,
+}
+
+impl ListSelection {
+    fn set_mode(&mut self, mode: SelectionMode) {
+        self.mode = mode;
+        if mode == SelectionMode::Single
+            && let Some(first) = self.selected.first().copied()
+        {
+            self.selected = BTreeSet::from([first]);
+        }
+    }
+
+    fn set_selected(&mut self, indices: impl IntoIterator<Item = usize>, len: usize) {
+        let mut indices = indices.into_iter().filter(|&index| index < len);
+        self.selected = match self.mode {
+            SelectionMode::Single => indices.next().into_iter().collect(),
+            SelectionMode::Multiple => indices.collect(),
+        };
+        self.anchor = self.selected.first().copied();
+        self.active = self.anchor;
+    }
+
+    /// Selects an item of a list with `len` items as if it was clicked, and returns true if the selection changed.
+    ///
+    /// `toggle` (Ctrl-click) adds or removes the item, and `extend` (Shift-click) selects the range from the anchor.
+    /// Both only apply in [`SelectionMode::Multiple`].
+    fn click(&mut self, index: usize, len: usize, toggle: bool, extend: bool) -> bool {
+        if index >= len {
+            return false;
+        }
+
+        let previous = self.selected.clone();
+        self.active = Some(index);
+
+        match (self.mode, self.anchor) {
+            (SelectionMode::Multiple, Some(anchor)) if extend => {
+                if !toggle {
+                    self.selected.clear();
+                }
+                self.selected.extend(anchor.min(index)..=anchor.max(index));
+            }
+            (SelectionMode::Multiple, _) if toggle => {
+                if !self.selected.remove(&index) {
+                    self.selected.insert(index);
+                }
+                self.anchor = Some(index);
+            }
+            _ => {
+                self.selected = BTreeSet::from([index]);
+                self.anchor = Some(index);
+            }
+        }
+
+        self.selected != previous
+    }
+
+    fn select_all(&mut self, len: usize) -> bool {
+        if self.mode != SelectionMode::Multiple {
+            return false;
+        }
+
+        let previous = self.selected.len();
+        self.selected = (0..len).collect();
+        self.selected.len() != previous
+    }
+
+    /// Forgets an item that was removed, and moves the items after it up.
+    fn remove_item(&mut self, index: usize) {
+        let shift = |item: usize| match item.cmp(&index) {
+            std::cmp::Ordering::Less => Some(item),
+            std::cmp::Ordering::Equal => None,
+            std::cmp::Ordering::Greater => Some(item - 1),
+        };
+        self.selected = self.selected.iter().filter_map(|&item| shift(item)).collect();
+        self.anchor = self.anchor.and_then(shift);
+        self.active = self.active.and_then(shift);
+    }
+
+    /// Keeps the selection with the items when two of them trade places.
+    fn swap_items(&mut self, index_1: usize, index_2: usize) {
+        let swap = |item: usize| {
+            if item == index_1 {
+                index_2
+            } else if item == index_2 {
+                index_1
+            } else {
+                item
+            }
+        };
+        self.selected = self.selected.iter().map(|&item| swap(item)).collect();
+        self.anchor = self.anchor.map(swap);
+        self.active = self.active.map(swap);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection(mode: SelectionMode) -> ListSelection {
+        ListSelection {
+            mode,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn single_mode_ignores_modifiers() {
+        let mut selection = selection(SelectionMode::Single);
+        selection.click(2, 10, false, false);
+        selection.click(5, 10, true, true);
+
+        assert_eq!(selection.selected, BTreeSet::from([5]));
+    }
+
+    #[test]
+    fn shift_click_selects_range_from_anchor() {
+        let mut selection = selection(SelectionMode::Multiple);
+        selection.click(2, 10, false, false);
+        selection.click(5, 10, false, true);
+        assert_eq!(selection.selected, BTreeSet::from([2, 3, 4, 5]));
+
+        // The anchor stays put, so the range shrinks and flips around it.
+        selection.click(0, 10, false, true);
+        assert_eq!(selection.selected, BTreeSet::from([0, 1, 2]));
+    }
+
+    #[test]
+    fn ctrl_click_toggles_and_moves_anchor() {
+        let mut selection = selection(SelectionMode::Multiple);
+        selection.click(1, 10, false, false);
+        selection.click(4, 10, true, false);
+        assert_eq!(selection.selected, BTreeSet::from([1, 4]));
+
+        assert!(selection.click(1, 10, true, false));
+        assert_eq!(selection.selected, BTreeSet::from([4]));
+        assert_eq!(selection.anchor, Some(1));
+
+        // Ctrl-Shift-click adds the range to the selection.
+        selection.click(6, 10, true, true);
+        assert_eq!(selection.selected, BTreeSet::from([1, 2, 3, 4, 5, 6]));
+    }
+
+    #[test]
+    fn clicking_the_selection_again_is_not_a_change() {
+        let mut selection = selection(SelectionMode::Single);
+
+        assert!(selection.click(3, 10, false, false));
+        assert!(!selection.click(3, 10, false, false));
+        assert!(!selection.click(10, 10, false, false));
+    }
+
+    #[test]
+    fn type_ahead_wraps_and_ignores_case() {
+        let labels = ["Apple", "banana", "Blueberry", "Cherry"].map(String::from);
+
+        assert_eq!(find_type_ahead_match(&labels, 0, "b"), Some(1));
+        assert_eq!(find_type_ahead_match(&labels, 2, "b"), Some(2));
+        assert_eq!(find_type_ahead_match(&labels, 3, "a"), Some(0));
+        assert_eq!(find_type_ahead_match(&labels, 0, "bl"), Some(2));
+        assert_eq!(find_type_ahead_match(&labels, 0, "z"), None);
+    }
+
+    #[test]
+    fn removing_an_item_moves_the_selection_with_the_items_after_it() {
+        let mut selection = selection(SelectionMode::Multiple);
+        selection.click(2, 10, false, false);
+        selection.click(4, 10, false, true);
+        selection.click(7, 10, true, false);
+
+        selection.remove_item(3);
+        assert_eq!(selection.selected, BTreeSet::from([2, 3, 6]));
+        assert_eq!(selection.anchor, Some(6));
+
+        selection.remove_item(6);
+        assert_eq!(selection.selected, BTreeSet::from([2, 3]));
+        assert_eq!(selection.anchor, None);
+        assert_eq!(selection.active, None);
+    }
+}
@@ -0,0 +1,531 @@
+//! A log viewer with level badges and filtering.
+
+mod record;
+#[cfg(feature = "tracing")]
+mod tracing_layer;
+
+pub use record::{LogLevel, LogRecord, LogSink};
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time;
+#[cfg(target_arch = "wasm32")]
+use web_time as time;
+
+use craft_primitives::geometry::{Affine, Point, Rectangle};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use peniko::Color;
+use time::SystemTime;
+use ui_events::pointer::PointerButton;
+
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, draw_generic_container, is_target_within, push_child_to_element};
+use crate::elements::log_view::record::{format_time, sanitize_message};
+use crate::elements::traits::DeepClone;
+use crate::elements::{resolve_clip_for_scrollable, AsElement, Console, Container, Element, ElementInternals, Text, TextInput};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::{AlignItems, Display, FlexDirection, Overflow};
+use crate::text::text_context::TextContext;
+use crate::{px, rgb};
+
+const DEFAULT_MAX_RECORDS: usize = 10_000;
+const BORDER_COLOR: Color = rgb(199, 199, 206);
+const INVALID_BORDER_COLOR: Color = rgb(220, 38, 38);
+const TOGGLE_COLOR: Color = rgb(230, 230, 235);
+const TOGGLE_ACTIVE_COLOR: Color = rgb(191, 219, 254);
+const HAS_REGEX: bool = cfg!(feature = "regex");
+
+thread_local! {
+    /// Every log view, so records collected by their sinks on other threads can be moved in.
+    static LOG_VIEWS: RefCell<Vec<Weak<RefCell<LogViewInner>>>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Clone)]
+pub struct LogView {
+    pub inner: Rc<RefCell<LogViewInner>>,
+}
+
+/// Shows log records in a [`Console`], each with its time, a badge with its level and its target.
+///
+/// Records are added with [`LogView::push`] or through a [`LogSink`], which can be fed from any thread and, with
+/// the `tracing` feature, by `tracing`. Records from a sink are shown the next time the event loop wakes up.
+///
+/// The filter box above the records hides records that don't contain its text, ignoring case. With the `regex`
+/// feature, a toggle next to it treats the filter as a regular expression instead. A record that repeats the one
+/// before it is collapsed into that line with a count.
+#[derive(Clone)]
+pub struct LogViewInner {
+    element_data: ElementData,
+    records: VecDeque<LogRecord>,
+    max_records: usize,
+    sinks: Vec<LogSink>,
+    collapse_repeats: bool,
+    filter_text: String,
+    use_regex: bool,
+    filter: LogFilter,
+    shown_lines: ShownLines,
+    pub filter_input: TextInput,
+    pub regex_toggle: Container,
+    pub console: Console,
+}
+
+/// Which records are shown.
+#[derive(Clone)]
+enum LogFilter {
+    All,
+    /// Lowercase text found in the target or message of the shown records.
+    Substring(String),
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+/// Tracks the lines written to the console.
+#[derive(Clone, Default)]
+struct ShownLines {
+    /// The time of each line, to find lines by time.
+    times: VecDeque<SystemTime>,
+    /// The record on the last line and how many times it was repeated.
+    last: Option<(LogRecord, usize)>,
+}
+
+impl Default for LogView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Element for LogView {}
+
+impl Drop for LogViewInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for LogView {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for LogViewInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for LogViewInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        draw_generic_container(self, renderer, resource_manager, text_context, scale_factor);
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        _event: &mut Event,
+        target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        match message {
+            // Text changes reach `on_event` twice, so the records are only filtered again the first time.
+            EventKind::TextInputChanged(changed) if changed.value != self.filter_text => {
+                self.filter_text = changed.value.clone();
+                self.update_filter();
+            }
+            EventKind::PointerButtonUp(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                if let Some(target) = target
+                    && is_target_within(&target, &self.regex_toggle)
+                {
+                    self.set_use_regex(!self.use_regex);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn apply_clip(&mut self, clip_bounds: Option<Rectangle>) {
+        let overflow = self.style().get_overflow();
+        if overflow[0] == Overflow::Scroll || overflow[1] == Overflow::Scroll {
+            resolve_clip_for_scrollable(self, clip_bounds);
+        } else {
+            self.element_data.layout.apply_clip(clip_bounds);
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl LogView {
+    pub fn new() -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<LogViewInner>>| {
+            RefCell::new(LogViewInner {
+                element_data: ElementData::new(me.clone(), false),
+                records: VecDeque::new(),
+                max_records: DEFAULT_MAX_RECORDS,
+                sinks: Vec::new(),
+                collapse_repeats: true,
+                filter_text: String::new(),
+                use_regex: false,
+                filter: LogFilter::All,
+                shown_lines: ShownLines::default(),
+                filter_input: TextInput::new("").flex_grow(1.0),
+                regex_toggle: Container::new()
+                    .display(if HAS_REGEX { Display::Flex } else { Display::None })
+                    .align_items(Some(AlignItems::Center))
+                    .padding(px(4), px(8), px(4), px(8))
+                    .border_radius_all(px(4))
+                    .background_color(TOGGLE_COLOR)
+                    .push(Text::new(".*").selectable(false)),
+                console: Console::new()
+                    .max_lines(DEFAULT_MAX_RECORDS)
+                    .flex_grow(1.0)
+                    .min_height(px(0)),
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        inner_mut.set_display(Display::Flex);
+        inner_mut.set_flex_direction(FlexDirection::Column);
+        inner_mut.set_gap(px(4), px(4));
+
+        let toolbar = Container::new()
+            .display(Display::Flex)
+            .flex_direction(FlexDirection::Row)
+            .align_items(Some(AlignItems::Center))
+            .gap(px(4), px(4))
+            .push(inner_mut.filter_input.clone())
+            .push(inner_mut.regex_toggle.clone());
+        inner_mut.push(toolbar.inner);
+        let console = inner_mut.console.clone();
+        inner_mut.push(console.inner);
+
+        drop(inner_mut);
+        LOG_VIEWS.with_borrow_mut(|log_views| log_views.push(Rc::downgrade(&inner)));
+        Self { inner }
+    }
+
+    /// Show the records collected by a sink. Can be called several times to read from more sinks.
+    pub fn sink(self, sink: LogSink) -> Self {
+        self.inner.borrow_mut().sinks.push(sink);
+        self
+    }
+
+    /// Set how many records are kept. The oldest records are dropped past this. Defaults to 10,000.
+    pub fn max_records(self, max_records: usize) -> Self {
+        self.inner.borrow_mut().set_max_records(max_records);
+        self
+    }
+
+    /// Set whether a record repeating the one before it is collapsed into its line. Defaults to true.
+    pub fn collapse_repeats(self, collapse_repeats: bool) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.collapse_repeats = collapse_repeats;
+        inner.refresh();
+        drop(inner);
+        self
+    }
+
+    pub fn filter(self, filter: &str) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.filter_text = filter.to_string();
+        inner.filter_input.inner.borrow_mut().set_text(filter);
+        inner.update_filter();
+        drop(inner);
+        self
+    }
+
+    pub fn get_filter(&self) -> String {
+        self.inner.borrow().filter_text.clone()
+    }
+
+    pub fn push(&self, record: LogRecord) {
+        self.inner.borrow_mut().append(vec![record]);
+    }
+
+    pub fn clear(&self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.records.clear();
+        inner.refresh();
+    }
+
+    /// Scrolls to the first shown record logged at or after the time.
+    pub fn jump_to(&self, timestamp: SystemTime) {
+        let inner = self.inner.borrow();
+        let line = inner.shown_lines.times.partition_point(|time| *time < timestamp);
+        inner.console.scroll_to_line(line);
+    }
+}
+
+impl LogViewInner {
+    pub fn set_max_records(&mut self, max_records: usize) {
+        self.max_records = max_records.max(1);
+        while self.records.len() > self.max_records {
+            self.records.pop_front();
+        }
+        self.console.clone().max_lines(self.max_records);
+        self.refresh();
+    }
+
+    pub fn set_use_regex(&mut self, use_regex: bool) {
+        self.use_regex = use_regex && HAS_REGEX;
+        self.regex_toggle
+            .clone()
+            .background_color(if self.use_regex { TOGGLE_ACTIVE_COLOR } else { TOGGLE_COLOR });
+        self.update_filter();
+    }
+
+    fn update_filter(&mut self) {
+        let filter = LogFilter::new(&self.filter_text, self.use_regex);
+        self.filter_input
+            .clone()
+            .border_color_all(if filter.is_some() { BORDER_COLOR } else { INVALID_BORDER_COLOR });
+
+        // An invalid regular expression keeps the previous filter while it is being typed.
+        if let Some(filter) = filter {
+            self.filter = filter;
+            self.refresh();
+        }
+    }
+
+    fn append(&mut self, records: Vec<LogRecord>) {
+        let mut output = String::new();
+        for record in records {
+            if self.records.len() >= self.max_records {
+                self.records.pop_front();
+            }
+            if self.filter.matches(&record) {
+                self.shown_lines
+                    .show(&record, self.collapse_repeats, self.max_records, &mut output);
+            }
+            self.records.push_back(record);
+        }
+
+        if !output.is_empty() {
+            self.console.write(&output);
+        }
+    }
+
+    /// Writes the shown records to the console again.
+    fn refresh(&mut self) {
+        self.console.clear();
+        self.shown_lines = ShownLines::default();
+
+        let mut output = String::new();
+        for record in self.records.iter().filter(|record| self.filter.matches(record)) {
+            self.shown_lines
+                .show(record, self.collapse_repeats, self.max_records, &mut output);
+        }
+        self.console.write(&output);
+    }
+
+    fn poll_sinks(&mut self) {
+        let mut records: Vec<LogRecord> = self.sinks.iter().flat_map(LogSink::take).collect();
+        if records.is_empty() {
+            return;
+        }
+        records.sort_by_key(|record| record.timestamp);
+        self.append(records);
+        self.request_window_redraw();
+    }
+}
+
+/// Moves the records collected by sinks into their log views. Called once per iteration of the event loop.
+pub(crate) fn poll_log_views() {
+    let log_views = LOG_VIEWS.with_borrow_mut(|log_views| {
+        log_views.retain(|log_view| log_view.strong_count() > 0);
+        log_views.clone()
+    });
+
+    for log_view in log_views.iter().filter_map(Weak::upgrade) {
+        if let Ok(mut log_view) = log_view.try_borrow_mut() {
+            log_view.poll_sinks();
+        }
+    }
+}
+
+impl LogFilter {
+    /// Returns `None` if the text is an invalid regular expression.
+    fn new(text: &str, use_regex: bool) -> Option<Self> {
+        if text.is_empty() {
+            return Some(LogFilter::All);
+        }
+
+        #[cfg(feature = "regex")]
+        if use_regex {
+            return regex::RegexBuilder::new(text)
+                .case_insensitive(true)
+                .build()
+                .ok()
+                .map(LogFilter::Regex);
+        }
+        #[cfg(not(feature = "regex"))]
+        let _ = use_regex;
+
+        Some(LogFilter::Substring(text.to_lowercase()))
+    }
+
+    fn matches(&self, record: &LogRecord) -> bool {
+        match self {
+            LogFilter::All => true,
+            LogFilter::Substring(text) => {
+                record.message.to_lowercase().contains(text) || record.target.to_lowercase().contains(text)
+            }
+            #[cfg(feature = "regex")]
+            LogFilter::Regex(regex) => regex.is_match(&record.message) || regex.is_match(&record.target),
+        }
+    }
+}
+
+impl ShownLines {
+    /// Writes a record to the output, or updates the count on the last line if it repeats the record there.
+    fn show(&mut self, record: &LogRecord, collapse_repeats: bool, max_lines: usize, output: &mut String) {
+        if collapse_repeats
+            && let Some((last, count)) = self.last.as_mut()
+            && last.is_repeat_of(record)
+        {
+            *count += 1;
+            // The last line is still open, so a carriage return replaces it.
+            output.push('\r');
+            write_line(output, last, *count);
+            return;
+        }
+
+        if !self.times.is_empty() {
+            output.push('\n');
+        }
+        write_line(output, record, 1);
+
+        self.times.push_back(record.timestamp);
+        if self.times.len() > max_lines {
+            self.times.pop_front();
+        }
+        self.last = Some((record.clone(), 1));
+    }
+}
+
+/// Writes a record as a line with ANSI colors: the time, the level badge, the target and the message.
+fn write_line(output: &mut String, record: &LogRecord, count: usize) {
+    let _ = write!(
+        output,
+        "\x1b[90m{}\x1b[0m \x1b[1;{}m {:<5} \x1b[0m \x1b[90m{}:\x1b[0m {}",
+        format_time(record.timestamp),
+        record.level.badge_codes(),
+        record.level,
+        record.target,
+        sanitize_message(&record.message),
+    );
+    if count > 1 {
+        let _ = write!(output, " \x1b[90m(×{count})\x1b[0m");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(level: LogLevel, message: &str) -> LogRecord {
+        LogRecord::new(level, "app", message)
+    }
+
+    #[test]
+    fn substring_filters_ignore_case() {
+        let filter = LogFilter::new("CONNECT", false).unwrap();
+
+        assert!(filter.matches(&record(LogLevel::Info, "Connected to server")));
+        assert!(!filter.matches(&record(LogLevel::Info, "Loaded config")));
+    }
+
+    #[test]
+    fn filters_match_targets() {
+        let filter = LogFilter::new("app", false).unwrap();
+        assert!(filter.matches(&record(LogLevel::Warn, "anything")));
+    }
+
+    #[test]
+    fn repeated_records_replace_the_last_line() {
+        let mut shown_lines = ShownLines::default();
+        let mut output = String::new();
+        let first = record(LogLevel::Warn, "retrying");
+        shown_lines.show(&first, true, 10, &mut output);
+        shown_lines.show(&first, true, 10, &mut output);
+        shown_lines.show(&record(LogLevel::Info, "done"), true, 10, &mut output);
+
+        assert_eq!(shown_lines.times.len(), 2);
+        assert_eq!(output.matches('\r').count(), 1);
+        assert_eq!(output.matches('\n').count(), 1);
+        assert!(output.contains("(×2)"));
+    }
+
+    #[test]
+    fn repeats_are_kept_when_not_collapsing() {
+        let mut shown_lines = ShownLines::default();
+        let mut output = String::new();
+        let line = record(LogLevel::Error, "failed");
+        shown_lines.show(&line, false, 10, &mut output);
+        shown_lines.show(&line, false, 10, &mut output);
+
+        assert_eq!(shown_lines.times.len(), 2);
+        assert!(!output.contains('\r'));
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time;
+#[cfg(target_arch = "wasm32")]
+use web_time as time;
+
+use time::{SystemTime, UNIX_EPOCH};
+
+/// How many records a [`LogSink`] keeps while no log view takes them.
+const MAX_PENDING_RECORDS: usize = 10_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    /// The ANSI codes of the level's badge: its text and background colors.
+    pub(crate) fn badge_codes(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "97;100",
+            LogLevel::Debug => "97;44",
+            LogLevel::Info => "30;42",
+            LogLevel::Warn => "30;43",
+            LogLevel::Error => "97;41",
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        };
+        f.pad(name)
+    }
+}
+
+/// A log message shown by a [`LogView`](crate::elements::LogView).
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogRecord {
+    pub timestamp: SystemTime,
+    pub level: LogLevel,
+    /// Where the message comes from, usually a module path.
+    pub target: String,
+    pub message: String,
+}
+
+impl LogRecord {
+    /// Creates a record with the current time.
+    pub fn new(level: LogLevel, target: &str, message: &str) -> Self {
+        Self {
+            timestamp: SystemTime::now(),
+            level,
+            target: target.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Returns true if the record says the same as `other`, ignoring the time.
+    pub(crate) fn is_repeat_of(&self, other: &LogRecord) -> bool {
+        self.level == other.level && self.target == other.target && self.message == other.message
+    }
+}
+
+/// Collects log records from any thread for the log views reading from it.
+///
+/// With the `tracing` feature the sink is also a `tracing_subscriber` layer, so an app can show its own logs:
+///
+/// ```ignore
+/// let sink = LogSink::new();
+/// tracing_subscriber::registry().with(sink.clone()).init();
+/// let log_view = LogView::new().sink(sink);
+/// ```
+#[derive(Clone, Default)]
+pub struct LogSink {
+    pending: Arc<Mutex<VecDeque<LogRecord>>>,
+}
+
+impl LogSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a record. The oldest records are dropped if no log view takes them.
+    pub fn push(&self, record: LogRecord) {
+        let mut pending = self.pending.lock().unwrap();
+        if pending.len() >= MAX_PENDING_RECORDS {
+            pending.pop_front();
+        }
+        pending.push_back(record);
+    }
+
+    pub(crate) fn take(&self) -> Vec<LogRecord> {
+        self.pending.lock().unwrap().drain(..).collect()
+    }
+}
+
+/// Formats the time of day in UTC as `HH:MM:SS.mmm`.
+pub(crate) fn format_time(timestamp: SystemTime) -> String {
+    let since_epoch = timestamp.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs() % 86_400;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Replaces line breaks and other control characters, so a message stays on one line and can't add escape codes.
+pub(crate) fn sanitize_message(message: &str) -> String {
+    message
+        .trim_end()
+        .chars()
+        .map(|character| if character.is_control() { ' ' } else { character })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn formats_the_time_of_day() {
+        let timestamp = UNIX_EPOCH + Duration::from_millis(((3 * 24 + 13) * 3600 + 5 * 60 + 9) * 1000 + 42);
+        assert_eq!(format_time(timestamp), "13:05:09.042");
+    }
+
+    #[test]
+    fn level_names_pad_to_the_badge_width() {
+        assert_eq!(format!("{:<5}", LogLevel::Info), "INFO ");
+        assert_eq!(format!("{:<5}", LogLevel::Error), "ERROR");
+    }
+
+    #[test]
+    fn messages_stay_on_one_line() {
+        assert_eq!(sanitize_message("first\nsecond\x1b[31m\n"), "first second [31m");
+    }
+
+    #[test]
+    fn sinks_drop_the_oldest_records_when_full() {
+        let sink = LogSink::new();
+        for index in 0..MAX_PENDING_RECORDS + 1 {
+            sink.push(LogRecord::new(LogLevel::Info, "app", &index.to_string()));
+        }
+
+        let records = sink.take();
+        assert_eq!(records.len(), MAX_PENDING_RECORDS);
+        assert_eq!(records[0].message, "1");
+        assert!(sink.take().is_empty());
+    }
+}
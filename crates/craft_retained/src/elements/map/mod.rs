@@ -0,0 +1,653 @@
+//! A slippy map showing raster tiles, with markers and lines on top.
+
+mod overlay;
+mod projection;
+
+pub use overlay::{MapMarker, MapPolyline};
+pub use projection::{LatLng, TileId};
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::HashSet;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time;
+#[cfg(target_arch = "wasm32")]
+use web_time as time;
+
+use craft_primitives::geometry::{Affine, BezPath, Circle, Point, Rectangle, Size, TOLERANCE};
+use craft_renderer::Brush;
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::resource_type::ResourceType;
+use craft_resource_manager::{ResourceId, ResourceManager};
+use peniko::Color;
+use peniko::kurbo;
+use time::{Duration, Instant};
+use ui_events::ScrollDelta;
+use ui_events::pointer::{PointerButton, PointerGesture};
+
+use crate::app::{PENDING_RESOURCES, cancel_resource, queue_event, request_animation_frame};
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, push_child_to_element};
+use crate::elements::map::overlay::distance_to_segment;
+use crate::elements::map::projection::{MapView, from_world, to_world};
+use crate::elements::traits::DeepClone;
+use crate::elements::{AsElement, Element, ElementInternals, Text};
+use crate::events::{Event, EventKind, MapClick};
+use crate::layout::TaffyTree;
+use crate::style::{Display, Overflow, Position};
+use crate::text::text_context::TextContext;
+use crate::{auto, pct, px, rgb, rgba};
+
+/// Returns the resource of a map tile, such as the URL of the tile on a tile server.
+pub type TileSource = Rc<dyn Fn(TileId) -> ResourceId>;
+
+/// How many zoom levels one line of the mouse wheel zooms.
+const WHEEL_ZOOM_STEP: f64 = 0.5;
+
+/// How many pixels of a precise scroll count as one line of the mouse wheel.
+const PIXELS_PER_LINE: f64 = 40.0;
+
+/// The longest time between the clicks of a double click.
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(300);
+
+/// How far the pointer may move, in logical pixels, before a click becomes a drag.
+const CLICK_SLOP: f64 = 4.0;
+
+/// How many zoom levels lower to look for a loaded tile to show until a tile loads.
+const FALLBACK_LEVELS: u8 = 4;
+
+/// The radius of a marker in logical pixels.
+const MARKER_RADIUS: f32 = 7.0;
+
+/// How far from a marker or line, in logical pixels, a click still hits it.
+const HIT_TOLERANCE: f64 = 4.0;
+
+#[derive(Clone)]
+pub struct Map {
+    pub inner: Rc<RefCell<MapInner>>,
+}
+
+/// A pointer pressed on the map.
+#[derive(Clone)]
+struct MapDrag {
+    start: Point,
+    last: Point,
+    /// Set once the pointer moved far enough to pan instead of click.
+    panning: bool,
+}
+
+/// Shows a map from raster tiles, which can be panned by dragging and zoomed with the mouse wheel, a pinch or a double
+/// click.
+///
+/// Tiles are loaded through the resource manager, which caches them. Tiles that scroll out of view before they load
+/// are cancelled, and lower zoom tiles are scaled up in their place while they load. Markers and lines are drawn over
+/// the tiles, and clicking the map sends a [`EventKind::MapClicked`] event.
+#[derive(Clone)]
+pub struct MapInner {
+    element_data: ElementData,
+    view: MapView,
+    tile_source: Option<TileSource>,
+    max_tile_zoom: u8,
+    /// The tiles requested from the resource manager that haven't loaded yet.
+    requested_tiles: HashSet<ResourceId>,
+    markers: Vec<MapMarker>,
+    polylines: Vec<MapPolyline>,
+    drag: Option<MapDrag>,
+    last_click: Option<Instant>,
+    pub attribution: Text,
+}
+
+impl Element for Map {}
+
+impl Drop for MapInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for Map {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for MapInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for MapInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn uses_resource(&self, resource_id: &ResourceId) -> bool {
+        self.requested_tiles.contains(resource_id) || self.element_data.mask_image.as_ref() == Some(resource_id)
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        if !self.is_visible() {
+            return;
+        }
+
+        self.add_hit_testable(renderer, true, scale_factor);
+        self.draw_borders(renderer, scale_factor);
+
+        renderer.push_layer(self.content_rectangle().scale(scale_factor));
+        self.draw_tiles(renderer, &resource_manager, scale_factor);
+        self.draw_polylines(renderer, scale_factor);
+        self.draw_markers(renderer, scale_factor);
+        renderer.pop_layer();
+
+        self.draw_children(renderer, resource_manager, scale_factor, text_context);
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        _target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        match message {
+            EventKind::PointerScroll(scroll) => {
+                let lines = match scroll.delta {
+                    ScrollDelta::LineDelta(_x, y) => y as f64,
+                    ScrollDelta::PixelDelta(physical) => physical.y / PIXELS_PER_LINE,
+                    ScrollDelta::PageDelta(_x, y) => y as f64,
+                };
+                let anchor = self.viewport_point(scroll.state.logical_point());
+                self.zoom_at(self.view.zoom + lines * WHEEL_ZOOM_STEP, anchor);
+                event.prevent_propagate();
+                event.prevent_defaults();
+            }
+            EventKind::PointerGesture(gesture) => {
+                if let PointerGesture::Pinch(delta) = gesture.gesture {
+                    let anchor = self.viewport_point(gesture.state.logical_point());
+                    let zoom = self.view.zoom + (1.0 + delta as f64).max(0.01).log2();
+                    self.zoom_at(zoom, anchor);
+                    event.prevent_propagate();
+                    event.prevent_defaults();
+                }
+            }
+            EventKind::PointerButtonDown(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                let pointer_position = pointer_button.state.logical_point();
+                let now = Instant::now();
+                if self
+                    .last_click
+                    .take()
+                    .is_some_and(|last_click| now.duration_since(last_click) < DOUBLE_CLICK_TIME)
+                {
+                    self.zoom_at(self.view.zoom.floor() + 1.0, self.viewport_point(pointer_position));
+                    return;
+                }
+                self.last_click = Some(now);
+
+                self.drag = Some(MapDrag {
+                    start: pointer_position,
+                    last: pointer_position,
+                    panning: false,
+                });
+                self.capture_pointer();
+            }
+            EventKind::PointerMovedEvent(pointer_update) => {
+                let Some(drag) = &mut self.drag else {
+                    return;
+                };
+                let pointer_position = pointer_update.current.logical_point();
+                drag.panning |= (pointer_position - drag.start).hypot() > CLICK_SLOP;
+                if !drag.panning {
+                    return;
+                }
+                let delta = pointer_position - drag.last;
+                drag.last = pointer_position;
+                self.view.pan(delta);
+                self.request_window_redraw();
+            }
+            EventKind::PointerButtonUp(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                let Some(drag) = self.drag.take() else {
+                    return;
+                };
+                self.release_pointer();
+                if !drag.panning {
+                    let click = self.click_at(self.viewport_point(pointer_button.state.logical_point()));
+                    let new_event = Event::new(self.element_data.me.upgrade().unwrap());
+                    queue_event(new_event, EventKind::MapClicked(click));
+                }
+            }
+            EventKind::LostPointerCapture() => {
+                self.drag = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl Map {
+    pub fn new() -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<MapInner>>| {
+            RefCell::new(MapInner {
+                element_data: ElementData::new(me.clone(), false),
+                view: MapView::default(),
+                tile_source: None,
+                max_tile_zoom: 19,
+                requested_tiles: HashSet::new(),
+                markers: Vec::new(),
+                polylines: Vec::new(),
+                drag: None,
+                last_click: None,
+                attribution: Text::new("")
+                    .selectable(false)
+                    .display(Display::None)
+                    .position(Position::Absolute)
+                    .inset(auto(), px(0), px(0), auto())
+                    .padding(px(1), px(4), px(1), px(4))
+                    .font_size(11.0)
+                    .color(rgb(51, 51, 51))
+                    .background_color(rgba(255, 255, 255, 200)),
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        inner_mut.set_width(pct(100));
+        inner_mut.set_height(px(300));
+        inner_mut.set_overflow(Overflow::Hidden, Overflow::Hidden);
+        inner_mut.set_background_color(rgb(229, 227, 223));
+        let attribution = inner_mut.attribution.clone();
+        inner_mut.push(attribution.inner);
+
+        drop(inner_mut);
+        Self { inner }
+    }
+
+    /// Set where the tiles are loaded from.
+    ///
+    /// Tiles that aren't loaded yet are shown as the background color of the map.
+    pub fn tile_source(self, tile_source: impl Fn(TileId) -> ResourceId + 'static) -> Self {
+        self.inner.borrow_mut().set_tile_source(Rc::new(tile_source));
+        self
+    }
+
+    /// Load the tiles from a tile server, with a URL where `{z}`, `{x}` and `{y}` are replaced by the zoom and the
+    /// coordinates of the tile. For example, `https://tile.example.com/{z}/{x}/{y}.png`.
+    #[cfg(feature = "http_client")]
+    pub fn tile_url(self, template: &str) -> Self {
+        let template = template.to_string();
+        self.tile_source(move |tile| {
+            ResourceId::Url(
+                template
+                    .replace("{z}", &tile.zoom.to_string())
+                    .replace("{x}", &tile.x.to_string())
+                    .replace("{y}", &tile.y.to_string()),
+            )
+        })
+    }
+
+    /// Set the highest zoom the tile source has tiles for. Further zooming scales those tiles up. Defaults to 19.
+    pub fn max_tile_zoom(self, max_tile_zoom: u8) -> Self {
+        self.inner.borrow_mut().max_tile_zoom = max_tile_zoom;
+        self
+    }
+
+    /// Set the text crediting the map data, shown in the bottom right corner.
+    pub fn attribution(self, attribution: &str) -> Self {
+        self.inner.borrow_mut().set_attribution(attribution);
+        self
+    }
+
+    /// Show a position in the middle of the map.
+    pub fn center(self, center: LatLng) -> Self {
+        self.inner.borrow_mut().set_center(center);
+        self
+    }
+
+    pub fn get_center(&self) -> LatLng {
+        self.inner.borrow().get_center()
+    }
+
+    /// Set the zoom, where the whole world is one tile wide at zoom 0, and each level doubles the size of the map.
+    pub fn zoom(self, zoom: f64) -> Self {
+        self.inner.borrow_mut().set_zoom(zoom);
+        self
+    }
+
+    pub fn get_zoom(&self) -> f64 {
+        self.inner.borrow().view.zoom
+    }
+
+    /// Set the lowest and highest zoom. Defaults to 0 and 19.
+    pub fn zoom_limits(self, min: f64, max: f64) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.view.min_zoom = min.min(max);
+        inner.view.max_zoom = max.max(min);
+        let zoom = inner.view.zoom;
+        inner.set_zoom(zoom);
+        drop(inner);
+        self
+    }
+
+    pub fn marker(self, marker: MapMarker) -> Self {
+        self.inner.borrow_mut().markers.push(marker);
+        self
+    }
+
+    /// Replace the markers.
+    pub fn markers(self, markers: Vec<MapMarker>) -> Self {
+        self.inner.borrow_mut().set_markers(markers);
+        self
+    }
+
+    pub fn get_markers(&self) -> Vec<MapMarker> {
+        self.inner.borrow().markers.clone()
+    }
+
+    pub fn polyline(self, polyline: MapPolyline) -> Self {
+        self.inner.borrow_mut().polylines.push(polyline);
+        self
+    }
+
+    /// Replace the lines.
+    pub fn polylines(self, polylines: Vec<MapPolyline>) -> Self {
+        self.inner.borrow_mut().set_polylines(polylines);
+        self
+    }
+
+    pub fn get_polylines(&self) -> Vec<MapPolyline> {
+        self.inner.borrow().polylines.clone()
+    }
+}
+
+impl Default for Map {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MapInner {
+    pub fn set_tile_source(&mut self, tile_source: TileSource) {
+        // Tiles of the previous source are cancelled when the map is drawn again.
+        self.tile_source = Some(tile_source);
+        self.request_window_redraw();
+    }
+
+    pub fn set_attribution(&mut self, attribution: &str) {
+        let mut label = self.attribution.inner.borrow_mut();
+        label.set_text(attribution);
+        label.set_display(if attribution.is_empty() { Display::None } else { Display::Flex });
+    }
+
+    pub fn set_center(&mut self, center: LatLng) {
+        self.view.set_center(to_world(center));
+        self.request_window_redraw();
+    }
+
+    pub fn get_center(&self) -> LatLng {
+        from_world(self.view.center)
+    }
+
+    pub fn set_zoom(&mut self, zoom: f64) {
+        self.view.zoom = zoom.clamp(self.view.min_zoom, self.view.max_zoom);
+        self.request_window_redraw();
+    }
+
+    pub fn set_markers(&mut self, markers: Vec<MapMarker>) {
+        self.markers = markers;
+        self.request_window_redraw();
+    }
+
+    pub fn set_polylines(&mut self, polylines: Vec<MapPolyline>) {
+        self.polylines = polylines;
+        self.request_window_redraw();
+    }
+
+    fn zoom_at(&mut self, zoom: f64, anchor: Point) {
+        let viewport = self.viewport_size();
+        self.view.zoom_at(zoom, anchor, viewport);
+        self.request_window_redraw();
+    }
+
+    fn content_rectangle(&self) -> Rectangle {
+        self.element_data.layout.computed_box_transformed.content_rectangle()
+    }
+
+    fn viewport_size(&self) -> Size<f64> {
+        let content_rectangle = self.content_rectangle();
+        Size::new(content_rectangle.width as f64, content_rectangle.height as f64)
+    }
+
+    /// Converts a window point to a point relative to the top left of the map's content.
+    fn viewport_point(&self, point: Point) -> Point {
+        let content_rectangle = self.content_rectangle();
+        Point::new(point.x - content_rectangle.x as f64, point.y - content_rectangle.y as f64)
+    }
+
+    /// Converts a point relative to the map's content to physical pixels in the window.
+    fn to_physical(&self, point: Point, scale_factor: f64) -> Point {
+        let content_rectangle = self.content_rectangle();
+        Point::new(
+            (content_rectangle.x as f64 + point.x) * scale_factor,
+            (content_rectangle.y as f64 + point.y) * scale_factor,
+        )
+    }
+
+    /// The points of a line in the viewport, using the copy of the world nearest to the middle for its first point.
+    fn polyline_points(&self, polyline: &MapPolyline, viewport: Size<f64>) -> Vec<Point> {
+        let Some(first) = polyline.points.first() else {
+            return Vec::new();
+        };
+        let first = to_world(*first);
+        let shift = self.view.nearest_copy(first).x - first.x;
+        polyline
+            .points
+            .iter()
+            .map(|point| {
+                let world = to_world(*point);
+                self.view.to_viewport(Point::new(world.x + shift, world.y), viewport)
+            })
+            .collect()
+    }
+
+    fn marker_point(&self, marker: &MapMarker, viewport: Size<f64>) -> Point {
+        self.view.to_viewport(self.view.nearest_copy(to_world(marker.location)), viewport)
+    }
+
+    /// Finds the location, marker and line under a point of the viewport.
+    fn click_at(&self, point: Point) -> MapClick {
+        let viewport = self.viewport_size();
+        let location = from_world(self.view.to_world(point, viewport));
+
+        // The markers drawn last are on top.
+        let marker = self.markers.iter().rposition(|marker| {
+            (self.marker_point(marker, viewport) - point).hypot() <= MARKER_RADIUS as f64 + HIT_TOLERANCE
+        });
+        let polyline = if marker.is_some() {
+            None
+        } else {
+            self.polylines.iter().rposition(|polyline| {
+                let points = self.polyline_points(polyline, viewport);
+                let reach = polyline.width as f64 / 2.0 + HIT_TOLERANCE;
+                points
+                    .windows(2)
+                    .any(|segment| distance_to_segment(point, segment[0], segment[1]) <= reach)
+            })
+        };
+
+        MapClick {
+            location,
+            marker,
+            polyline,
+        }
+    }
+
+    fn draw_tiles(&mut self, renderer: &mut dyn Renderer, resource_manager: &ResourceManager, scale_factor: f64) {
+        let Some(tile_source) = self.tile_source.clone() else {
+            return;
+        };
+        let viewport = self.viewport_size();
+        let content_rectangle = self.content_rectangle();
+
+        let mut loading = HashSet::new();
+        let mut requested = false;
+        for (tile, rectangle) in self.view.visible_tiles(viewport, self.max_tile_zoom) {
+            // Rounded to physical pixels, so neighboring tiles don't leave gaps between them.
+            let left = ((content_rectangle.x + rectangle.x) as f64 * scale_factor).round();
+            let top = ((content_rectangle.y + rectangle.y) as f64 * scale_factor).round();
+            let right = ((content_rectangle.x + rectangle.right()) as f64 * scale_factor).round();
+            let bottom = ((content_rectangle.y + rectangle.bottom()) as f64 * scale_factor).round();
+            let rectangle = Rectangle::new(left as f32, top as f32, (right - left) as f32, (bottom - top) as f32);
+
+            let resource_id = tile_source(tile);
+            if resource_manager.contains(&resource_id) {
+                renderer.draw_image(rectangle, resource_id);
+                continue;
+            }
+
+            if self.requested_tiles.insert(resource_id.clone()) {
+                PENDING_RESOURCES.with_borrow_mut(|pending_resources| {
+                    pending_resources.push_back((resource_id.clone(), ResourceType::Image));
+                });
+                requested = true;
+            }
+            loading.insert(resource_id);
+            draw_fallback_tile(renderer, resource_manager, &tile_source, tile, rectangle);
+        }
+
+        // Tiles that scrolled out of view before they loaded aren't needed anymore.
+        self.requested_tiles.retain(|resource_id| {
+            if loading.contains(resource_id) {
+                return true;
+            }
+            if !resource_manager.contains(resource_id) {
+                cancel_resource(resource_manager, resource_id);
+            }
+            false
+        });
+
+        // The requested tiles start loading before the next frame.
+        if requested {
+            request_animation_frame();
+        }
+    }
+
+    fn draw_polylines(&self, renderer: &mut dyn Renderer, scale_factor: f64) {
+        let viewport = self.viewport_size();
+        for polyline in &self.polylines {
+            let points = self.polyline_points(polyline, viewport);
+            let Some((first, rest)) = points.split_first() else {
+                continue;
+            };
+            let mut path = BezPath::new();
+            path.move_to(self.to_physical(*first, scale_factor));
+            for point in rest {
+                path.line_to(self.to_physical(*point, scale_factor));
+            }
+
+            let style = kurbo::Stroke::new(polyline.width as f64 * scale_factor)
+                .with_caps(kurbo::Cap::Round)
+                .with_join(kurbo::Join::Round);
+            let outline = kurbo::stroke(path, &style, &kurbo::StrokeOpts::default(), TOLERANCE);
+            renderer.fill_bez_path(outline, Brush::Color(polyline.color));
+        }
+    }
+
+    fn draw_markers(&self, renderer: &mut dyn Renderer, scale_factor: f64) {
+        let viewport = self.viewport_size();
+        for marker in &self.markers {
+            let center = self.to_physical(self.marker_point(marker, viewport), scale_factor);
+            let radius = MARKER_RADIUS * scale_factor as f32;
+            renderer.draw_circle(Circle::new(center.x as f32, center.y as f32, radius), Color::WHITE);
+            renderer.draw_circle(
+                Circle::new(center.x as f32, center.y as f32, radius - 2.0 * scale_factor as f32),
+                marker.color,
+            );
+        }
+    }
+}
+
+/// Draws the part of a loaded lower zoom tile covering a tile that is still loading.
+fn draw_fallback_tile(
+    renderer: &mut dyn Renderer,
+    resource_manager: &ResourceManager,
+    tile_source: &TileSource,
+    tile: TileId,
+    rectangle: Rectangle,
+) {
+    for levels in 1..=FALLBACK_LEVELS {
+        let Some(parent) = tile.parent(levels) else {
+            return;
+        };
+        let resource_id = tile_source(parent);
+        if !resource_manager.contains(&resource_id) {
+            continue;
+        }
+
+        let tiles_per_side = 1u32 << levels;
+        let column = (tile.x % tiles_per_side) as f32;
+        let row = (tile.y % tiles_per_side) as f32;
+        let parent_rectangle = Rectangle::new(
+            rectangle.x - column * rectangle.width,
+            rectangle.y - row * rectangle.height,
+            rectangle.width * tiles_per_side as f32,
+            rectangle.height * tiles_per_side as f32,
+        );
+        renderer.push_layer(rectangle);
+        renderer.draw_image(parent_rectangle, resource_id);
+        renderer.pop_layer();
+        return;
+    }
+}
@@ -0,0 +1,84 @@
+//! Markers and lines drawn over a [`Map`](crate::elements::Map).
+
+use craft_primitives::geometry::Point;
+use peniko::Color;
+
+use crate::elements::map::LatLng;
+use crate::rgb;
+
+const DEFAULT_COLOR: Color = rgb(37, 99, 235);
+
+/// A point marked on a map.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapMarker {
+    pub location: LatLng,
+    pub color: Color,
+}
+
+impl MapMarker {
+    pub fn new(location: LatLng) -> Self {
+        Self {
+            location,
+            color: DEFAULT_COLOR,
+        }
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+/// A line through points on a map, such as a route.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapPolyline {
+    pub points: Vec<LatLng>,
+    pub color: Color,
+    /// The width of the line in logical pixels.
+    pub width: f32,
+}
+
+impl MapPolyline {
+    pub fn new(points: Vec<LatLng>) -> Self {
+        Self {
+            points,
+            color: DEFAULT_COLOR,
+            width: 3.0,
+        }
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width;
+        self
+    }
+}
+
+/// The distance from `point` to the nearest point of the segment from `a` to `b`.
+pub(crate) fn distance_to_segment(point: Point, a: Point, b: Point) -> f64 {
+    let segment = b - a;
+    let length_squared = segment.hypot2();
+    if length_squared == 0.0 {
+        return (point - a).hypot();
+    }
+    let t = ((point - a).dot(segment) / length_squared).clamp(0.0, 1.0);
+    (point - (a + segment * t)).hypot()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_the_distance_to_segments() {
+        let (a, b) = (Point::new(0.0, 0.0), Point::new(10.0, 0.0));
+
+        assert_eq!(distance_to_segment(Point::new(5.0, 3.0), a, b), 3.0);
+        assert_eq!(distance_to_segment(Point::new(13.0, 4.0), a, b), 5.0);
+        assert_eq!(distance_to_segment(Point::new(3.0, 4.0), a, a), 5.0);
+    }
+}
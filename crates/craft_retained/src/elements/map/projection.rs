@@ -0,0 +1,240 @@
+//! The Web Mercator projection, and the tiles covering the part of the world in view.
+
+use std::f64::consts::PI;
+
+use craft_primitives::geometry::{Point, Rectangle, Size, Vec2};
+
+/// The size of a tile in logical pixels.
+pub(crate) const TILE_SIZE: f64 = 256.0;
+
+/// The latitude of the top and bottom edges of the map, where the projected world is square.
+const MAX_LATITUDE: f64 = 85.051_128_779_806_59;
+
+/// A position on Earth in degrees.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LatLng {
+    pub lat: f64,
+    pub lng: f64,
+}
+
+impl LatLng {
+    pub const fn new(lat: f64, lng: f64) -> Self {
+        Self { lat, lng }
+    }
+}
+
+/// A map tile, numbered the way slippy map tile servers number them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TileId {
+    pub zoom: u8,
+    pub x: u32,
+    pub y: u32,
+}
+
+impl TileId {
+    /// The tile `levels` zoom levels lower that covers this one, or `None` below zoom 0.
+    pub fn parent(self, levels: u8) -> Option<TileId> {
+        let zoom = self.zoom.checked_sub(levels)?;
+        Some(TileId {
+            zoom,
+            x: self.x >> levels,
+            y: self.y >> levels,
+        })
+    }
+}
+
+/// Projects a position to world coordinates, from (0, 0) at the north west corner to (1, 1) at the south east.
+pub(crate) fn to_world(location: LatLng) -> Point {
+    let lat = location.lat.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+    Point::new(
+        (location.lng + 180.0) / 360.0,
+        (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / PI) / 2.0,
+    )
+}
+
+/// The position at world coordinates, with the longitude wrapped to -180..180.
+pub(crate) fn from_world(point: Point) -> LatLng {
+    let lng = point.x.rem_euclid(1.0) * 360.0 - 180.0;
+    let lat = (PI * (1.0 - 2.0 * point.y)).sinh().atan().to_degrees();
+    LatLng::new(lat, lng)
+}
+
+/// The part of the world shown in the map.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct MapView {
+    /// The world coordinates shown in the middle of the viewport.
+    pub(crate) center: Point,
+    pub(crate) zoom: f64,
+    pub(crate) min_zoom: f64,
+    pub(crate) max_zoom: f64,
+}
+
+impl Default for MapView {
+    fn default() -> Self {
+        Self {
+            center: Point::new(0.5, 0.5),
+            zoom: 2.0,
+            min_zoom: 0.0,
+            max_zoom: 19.0,
+        }
+    }
+}
+
+impl MapView {
+    /// The width of the world in logical pixels.
+    fn world_size(&self) -> f64 {
+        TILE_SIZE * self.zoom.exp2()
+    }
+
+    /// Maps world coordinates to the viewport, with the origin at its top left corner.
+    pub(crate) fn to_viewport(&self, world: Point, viewport: Size<f64>) -> Point {
+        let offset = (world - self.center) * self.world_size();
+        Point::new(viewport.width / 2.0 + offset.x, viewport.height / 2.0 + offset.y)
+    }
+
+    /// Returns the world coordinates under a point of the viewport.
+    pub(crate) fn to_world(&self, point: Point, viewport: Size<f64>) -> Point {
+        let offset = Vec2::new(point.x - viewport.width / 2.0, point.y - viewport.height / 2.0);
+        self.center + offset / self.world_size()
+    }
+
+    /// The world repeats horizontally. Returns the copy of a point nearest to the middle of the viewport.
+    pub(crate) fn nearest_copy(&self, world: Point) -> Point {
+        Point::new(world.x - (world.x - self.center.x).round(), world.y)
+    }
+
+    /// Sets the zoom, keeping the world point under `anchor` in place.
+    pub(crate) fn zoom_at(&mut self, zoom: f64, anchor: Point, viewport: Size<f64>) {
+        let world_anchor = self.to_world(anchor, viewport);
+        self.zoom = zoom.clamp(self.min_zoom, self.max_zoom);
+        self.center += world_anchor - self.to_world(anchor, viewport);
+        self.wrap_center();
+    }
+
+    /// Moves the map by a distance in logical pixels.
+    pub(crate) fn pan(&mut self, delta: Vec2) {
+        self.center -= delta / self.world_size();
+        self.wrap_center();
+    }
+
+    pub(crate) fn set_center(&mut self, world: Point) {
+        self.center = world;
+        self.wrap_center();
+    }
+
+    fn wrap_center(&mut self) {
+        self.center.x = self.center.x.rem_euclid(1.0);
+        self.center.y = self.center.y.clamp(0.0, 1.0);
+    }
+
+    /// The zoom of the tiles drawn at the current zoom.
+    pub(crate) fn tile_zoom(&self, max_tile_zoom: u8) -> u8 {
+        self.zoom.round().clamp(0.0, max_tile_zoom as f64) as u8
+    }
+
+    /// Returns the tiles covering the viewport and where they are in it.
+    pub(crate) fn visible_tiles(&self, viewport: Size<f64>, max_tile_zoom: u8) -> Vec<(TileId, Rectangle)> {
+        let zoom = self.tile_zoom(max_tile_zoom);
+        let tiles_per_side = 1u64 << zoom;
+        let count = tiles_per_side as f64;
+        let tile_size = self.world_size() / count;
+
+        let top_left = self.to_world(Point::ZERO, viewport);
+        let bottom_right = self.to_world(Point::new(viewport.width, viewport.height), viewport);
+        let columns = (top_left.x * count).floor() as i64..(bottom_right.x * count).ceil() as i64;
+        let rows = (top_left.y * count).floor().max(0.0) as i64..(bottom_right.y * count).ceil().min(count) as i64;
+
+        let mut tiles = Vec::new();
+        for row in rows {
+            for column in columns.clone() {
+                let origin = self.to_viewport(Point::new(column as f64 / count, row as f64 / count), viewport);
+                let tile = TileId {
+                    zoom,
+                    x: column.rem_euclid(tiles_per_side as i64) as u32,
+                    y: row as u32,
+                };
+                let rectangle = Rectangle::new(origin.x as f32, origin.y as f32, tile_size as f32, tile_size as f32);
+                tiles.push((tile, rectangle));
+            }
+        }
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIEWPORT: Size<f64> = Size {
+        width: 256.0,
+        height: 256.0,
+    };
+
+    fn assert_near(a: Point, b: Point) {
+        assert!((a - b).hypot() < 1e-9, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn projects_to_world_coordinates_and_back() {
+        assert_near(to_world(LatLng::new(0.0, 0.0)), Point::new(0.5, 0.5));
+        assert_near(to_world(LatLng::new(MAX_LATITUDE, -180.0)), Point::new(0.0, 0.0));
+
+        let london = LatLng::new(51.5074, -0.1278);
+        let round_trip = from_world(to_world(london));
+        assert!((round_trip.lat - london.lat).abs() < 1e-9);
+        assert!((round_trip.lng - london.lng).abs() < 1e-9);
+    }
+
+    #[test]
+    fn zoom_zero_shows_one_tile() {
+        let view = MapView {
+            zoom: 0.0,
+            ..MapView::default()
+        };
+
+        let tiles = view.visible_tiles(VIEWPORT, 19);
+
+        assert_eq!(
+            tiles,
+            vec![(TileId { zoom: 0, x: 0, y: 0 }, Rectangle::new(0.0, 0.0, 256.0, 256.0))]
+        );
+    }
+
+    #[test]
+    fn tiles_wrap_around_the_antimeridian() {
+        let mut view = MapView {
+            zoom: 1.0,
+            ..MapView::default()
+        };
+        view.set_center(Point::new(0.0, 0.5));
+
+        let mut tiles: Vec<(u32, u32)> = view
+            .visible_tiles(VIEWPORT, 19)
+            .iter()
+            .map(|(tile, _)| (tile.x, tile.y))
+            .collect();
+        tiles.sort();
+
+        assert_eq!(tiles, vec![(0, 0), (0, 1), (1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn zooming_keeps_the_anchor_in_place() {
+        let mut view = MapView::default();
+        let anchor = Point::new(40.0, 200.0);
+        let before = view.to_world(anchor, VIEWPORT);
+
+        view.zoom_at(3.5, anchor, VIEWPORT);
+
+        assert_eq!(view.zoom, 3.5);
+        assert_near(view.to_world(anchor, VIEWPORT), before);
+    }
+
+    #[test]
+    fn parents_cover_their_children() {
+        let tile = TileId { zoom: 3, x: 5, y: 2 };
+
+        assert_eq!(tile.parent(2), Some(TileId { zoom: 1, x: 1, y: 0 }));
+        assert_eq!(tile.parent(4), None);
+    }
+}
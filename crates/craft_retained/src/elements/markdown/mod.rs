@@ -5,7 +5,8 @@ use craft_resource_manager::ResourceId;
 
 use pulldown_cmark::{Event, HeadingLevel, Tag, TagEnd};
 
-use crate::elements::{AsElement, CodeEditor, Container, DynElement, Element, Image, Text, TextInput};
+use crate::elements::{AsElement, CodeEditor, Container, DynElement, Element, Image, TextInput};
+use crate::layout::custom_layout::FlowLayout;
 use crate::style::{Display, FlexDirection, FontStyle, FontWeight, TextStyleProperty, Unit};
 use crate::text::RangedStyles;
 use crate::{Color, px, rgb, pct};
@@ -33,6 +34,8 @@ struct MarkdownRenderer<'a> {
     italic: Option<usize>,
     link: Option<(usize, String)>,
     code_block_kind: Option<pulldown_cmark::CodeBlockKind<'a>>,
+    /// The paragraph being rendered, once it has an image. Its text and images wrap together in a [`FlowLayout`].
+    flow: Option<DynElement>,
 }
 
 impl<'a> MarkdownRenderer<'a> {
@@ -49,6 +52,7 @@ impl<'a> MarkdownRenderer<'a> {
             italic: None,
             link: None,
             code_block_kind: None,
+            flow: None,
         }
     }
 
@@ -89,6 +93,9 @@ impl<'a> MarkdownRenderer<'a> {
     }
 
     pub fn push_rich_text(&mut self, text_input: Option<TextInput>) {
+        if self.pop_flow() {
+            return;
+        }
         if self.styled_text.text.is_empty() {
             return;
         }
@@ -107,6 +114,82 @@ impl<'a> MarkdownRenderer<'a> {
         self.styled_text = StyledText::new();
     }
 
+    /// Adds an image to the paragraph being rendered, inline with its text.
+    pub fn push_inline_image(&mut self, image: DynElement) {
+        if self.flow.is_none() {
+            let flow = Container::new()
+                .display(Display::Block)
+                .custom_layout(FlowLayout::new().gap(4.0, 0.0));
+            self.flow = Some(flow.as_dyn_element());
+        }
+        self.push_words();
+        if let Some(flow) = self.flow.clone() {
+            flow.push(image);
+        }
+    }
+
+    /// Adds the text so far to the paragraph being rendered, one element per word so that the words wrap around its
+    /// images.
+    fn push_words(&mut self) {
+        let Some(flow) = self.flow.clone() else {
+            return;
+        };
+
+        // Styles that are still open end with these words, and start again with the text after them.
+        let (bold, italic, link) = (self.bold.is_some(), self.italic.is_some(), self.link.clone());
+        self.pop_bold();
+        self.pop_italic();
+        self.pop_link();
+        let styled_text = std::mem::replace(&mut self.styled_text, StyledText::new());
+        if bold {
+            self.push_bold();
+        }
+        if italic {
+            self.push_italic();
+        }
+        if let Some((_, url)) = link {
+            self.push_link(url);
+        }
+
+        let mut start = 0;
+        for word in styled_text.text.split_inclusive(char::is_whitespace) {
+            let word_start = start;
+            start += word.len();
+            let word = word.trim_end();
+            if word.is_empty() {
+                continue;
+            }
+            let word_end = word_start + word.len();
+            let styles = styled_text
+                .style
+                .styles
+                .iter()
+                .filter(|(range, _)| range.start < word_end && range.end > word_start)
+                .map(|(range, style)| {
+                    let range = range.start.max(word_start) - word_start..range.end.min(word_end) - word_start;
+                    (range, style.clone())
+                })
+                .collect();
+            let word = TextInput::new(word)
+                .border_width_all(px(0))
+                .padding_all(px(0))
+                .disable()
+                .ranged_styles(RangedStyles::new(styles));
+            flow.clone().push(word);
+        }
+    }
+
+    /// Adds the paragraph being rendered, if it has images.
+    fn pop_flow(&mut self) -> bool {
+        if self.flow.is_none() {
+            return false;
+        }
+        self.push_words();
+        let flow = self.flow.take().expect("The paragraph should have a flow container");
+        self.push(flow);
+        true
+    }
+
     pub fn push_link(&mut self, url: String) {
         self.link = Some((self.styled_text.text.len(), url));
     }
@@ -215,11 +298,8 @@ pub fn render_markdown(markdown: &str) -> DynElement {
                     } else {
                         ResourceId::File(PathBuf::from_str(&dest_url).expect("Invalid file path for image"))
                     };
-                    renderer.push(
-                        Container::new()
-                            .push(Image::new(resource).width(Unit::Auto).height(Unit::Auto))
-                            .as_dyn_element(),
-                    )
+                    let image = Image::new(resource).width(Unit::Auto).height(Unit::Auto);
+                    renderer.push_inline_image(image.as_dyn_element());
                 }
                 _ => {}
             },
@@ -295,9 +375,7 @@ pub fn render_markdown(markdown: &str) -> DynElement {
                         renderer.pop_link();
                     }
                     TagEnd::Image => {
-                        let text = &renderer.styled_text.text;
-                        let text = Text::new(text);
-                        renderer.push(text.as_dyn_element());
+                        // The alt text of the image isn't shown, as the image is.
                         renderer.styled_text = StyledText::new();
                     }
                     TagEnd::MetadataBlock(_) => {}
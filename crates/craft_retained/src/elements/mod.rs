@@ -4,45 +4,97 @@ pub use crate::elements::calendar::{Calendar, CalendarInner};
 pub use crate::elements::checkbox::{Checkbox, CheckboxInner};
 pub use crate::elements::checkboxgroup::{CheckboxGroup, CheckboxGroupInner};
 #[cfg(feature = "code_highlighting")]
-pub use crate::elements::codeeditor::CodeEditor;
+pub use crate::elements::codeeditor::{CodeEditor, FoldRegion, FoldingStrategy};
+pub use crate::elements::console::{Console, ConsoleInner};
 pub use crate::elements::container::{Container, ContainerInner};
+pub use crate::elements::custom::{Custom, CustomChange, CustomElement, CustomInner, DrawContext};
+pub use crate::elements::dashboard::{ChartGroup, ChartViewport, Gauge, GaugeInner, Sparkline, SparklineInner, StatCard, StatCardInner};
+pub use crate::elements::data_grid::{CellValue, ColumnKind, DataGrid, DataGridColumn, DataGridInner};
+pub use crate::elements::diff_view::{DiffMode, DiffView, DiffViewInner};
+pub use crate::elements::drag_list::{DragGhost, DragList, DragListGroup, DragListInner};
 pub use crate::elements::dropdown::{Dropdown, DropdownInner};
 pub use crate::elements::dyn_element::DynElement;
 pub use crate::elements::element_id_map::ElementIdMap;
+pub use crate::elements::file_picker::{FileFilter, FilePicker, FilePickerInner};
 pub use crate::elements::image::{Image, ImageInner};
+pub use crate::elements::image_crop::{Annotation, AnnotationShape, ImageCrop, ImageCropInner};
+pub use crate::elements::image_viewer::{ImageViewer, ImageViewerInner, ZoomMode};
+#[cfg(feature = "json")]
+pub use crate::elements::json_view::{JsonView, JsonViewInner};
+pub use crate::elements::list_box::{ListBox, ListBoxInner, SelectionMode};
+pub use crate::elements::log_view::{LogLevel, LogRecord, LogSink, LogView, LogViewInner};
+pub use crate::elements::map::{LatLng, Map, MapInner, MapMarker, MapPolyline, TileId, TileSource};
 #[cfg(feature = "markdown")]
 pub use crate::elements::markdown::render_markdown;
+pub use crate::elements::particles::{ParticleEmitter, ParticleShape, Particles, ParticlesInner};
+#[cfg(all(feature = "pdf", not(target_arch = "wasm32")))]
+pub use crate::elements::pdf_view::{PdfSource, PdfView, PdfViewInner};
+pub use crate::elements::property_grid::{Property, PropertyGrid, PropertyGridInner, PropertyValue};
 pub use crate::elements::radio::{Radio, RadioInner};
 pub use crate::elements::radiogroup::{RadioGroup, RadioGroupInner};
+pub use crate::elements::rating::{Rating, RatingInner};
 pub use crate::elements::scrollable::{ScrollOptions, ScrollState, ScrollToBox};
+pub use crate::elements::segmented_input::{
+    Segment, SegmentCharacters, SegmentedInput, SegmentedInputInner, SegmentedValue,
+};
+pub use crate::elements::selection_area::{SelectionArea, SelectionAreaInner};
 pub use crate::elements::slider::{Slider, SliderDirection, SliderInner};
+pub use crate::elements::stepper::{Stepper, StepperInner, StepperValidator};
+pub use crate::elements::tag_input::{TagInput, TagInputInner};
 pub use crate::elements::text::{Text, TextInner};
-pub use crate::elements::text_input::{TextInput, TextInputInner};
+pub use crate::elements::text_input::{
+    InputPurpose, KeyBindings, KeyChord, TextEditAction, TextInput, TextInputInner, TextMotion,
+};
+pub use crate::elements::time_picker::{DurationPicker, DurationPickerInner, HourCycle, TimeOfDay, TimePicker, TimePickerInner};
 pub use crate::elements::tinyvg::{TinyVg, TinyVgInner};
 pub use crate::elements::traits::{resolve_clip_for_scrollable, AsElement, Element, ElementData, ElementInternals};
 pub use crate::elements::window::{Window, WindowInternal};
 
 #[cfg(feature = "audio")]
 pub(crate) use crate::elements::audio::AUDIO_CONTEXT;
+pub(crate) use crate::elements::file_picker::{clear_drop_targets, poll_file_dialogs};
+pub(crate) use crate::elements::log_view::poll_log_views;
+pub(crate) use crate::elements::text_input::focused_ime_purpose;
 
 pub(crate) mod internal_helpers;
+pub(crate) mod layer_cache;
 pub(crate) mod scrollable;
 
 mod calendar;
 mod checkbox;
 mod checkboxgroup;
+mod console;
 mod container;
+mod custom;
+mod dashboard;
+mod data_grid;
+mod diff_view;
+mod drag_list;
 mod dropdown;
 mod dyn_element;
 mod element_data;
 mod element_id;
 mod element_id_map;
+mod file_picker;
 mod image;
+mod image_crop;
+mod image_viewer;
+mod list_box;
+mod log_view;
+mod map;
+mod particles;
+mod property_grid;
 mod radio;
 mod radiogroup;
+mod rating;
+mod segmented_input;
+mod selection_area;
 mod slider;
+mod stepper;
+mod tag_input;
 mod text;
 mod text_input;
+mod time_picker;
 mod tinyvg;
 mod traits;
 mod window;
@@ -52,3 +104,7 @@ mod markdown;
 mod codeeditor;
 #[cfg(feature = "audio")]
 mod audio;
+#[cfg(feature = "json")]
+mod json_view;
+#[cfg(all(feature = "pdf", not(target_arch = "wasm32")))]
+mod pdf_view;
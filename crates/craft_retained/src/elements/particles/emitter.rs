@@ -0,0 +1,368 @@
+//! Where particles start and how they move, and the simulation that moves them.
+
+use std::f64::consts::PI;
+use std::ops::RangeInclusive;
+
+use craft_primitives::geometry::{Point, Size, Vec2};
+use peniko::Color;
+
+use crate::rgb;
+use crate::utils::random::Random;
+
+/// The shape drawn for a particle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParticleShape {
+    /// A strip of paper, like confetti.
+    Rectangle,
+    Circle,
+    /// A four pointed star that grows and shrinks, like a sparkle.
+    Star,
+}
+
+/// Configures how particles are emitted and how they move.
+///
+/// Positions are in fractions of the element's size, and distances in logical pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParticleEmitter {
+    /// Where particles start, from (0, 0) at the top left of the element to (1, 1) at its bottom right.
+    pub origin: Point,
+    /// How far from the origin particles may start, as the width and height of the area around it.
+    pub spread: Size<f64>,
+    /// Particles emitted per second while emitting. With 0, particles are only emitted in bursts.
+    pub rate: f32,
+    /// Particles emitted at once when the element is created and when it bursts.
+    pub burst: usize,
+    /// The direction particles move in, in degrees clockwise from the right.
+    pub direction: f32,
+    /// How many degrees the direction may differ either side.
+    pub direction_spread: f32,
+    /// The speed particles start with, in logical pixels per second.
+    pub speed: RangeInclusive<f32>,
+    /// How long particles live, in seconds.
+    pub lifetime: RangeInclusive<f32>,
+    /// The size of particles in logical pixels.
+    pub size: RangeInclusive<f32>,
+    /// How fast particles spin, in degrees per second.
+    pub spin: RangeInclusive<f32>,
+    /// The acceleration of particles, in logical pixels per second squared.
+    pub gravity: Vec2,
+    /// The fraction of their speed particles lose each second, like air resistance.
+    pub drag: f32,
+    /// How far particles sway from side to side, in logical pixels per second, like falling snow.
+    pub sway: f32,
+    pub colors: Vec<Color>,
+    pub shapes: Vec<ParticleShape>,
+    /// Stops emitting while this many particles are alive.
+    pub max_particles: usize,
+}
+
+impl ParticleEmitter {
+    /// A burst of paper strips shot up from the bottom of the element, which then flutter down.
+    pub fn confetti() -> Self {
+        Self {
+            origin: Point::new(0.5, 1.0),
+            spread: Size::new(0.2, 0.0),
+            rate: 0.0,
+            burst: 150,
+            direction: -90.0,
+            direction_spread: 35.0,
+            speed: 450.0..=900.0,
+            lifetime: 2.5..=4.0,
+            size: 6.0..=11.0,
+            spin: -540.0..=540.0,
+            gravity: Vec2::new(0.0, 700.0),
+            drag: 0.9,
+            sway: 30.0,
+            colors: vec![
+                rgb(239, 68, 68),
+                rgb(249, 115, 22),
+                rgb(234, 179, 8),
+                rgb(34, 197, 94),
+                rgb(59, 130, 246),
+                rgb(168, 85, 247),
+                rgb(236, 72, 153),
+            ],
+            shapes: vec![ParticleShape::Rectangle, ParticleShape::Rectangle, ParticleShape::Circle],
+            max_particles: 500,
+        }
+    }
+
+    /// Stars twinkling all over the element.
+    pub fn sparkles() -> Self {
+        Self {
+            origin: Point::new(0.5, 0.5),
+            spread: Size::new(1.0, 1.0),
+            rate: 12.0,
+            burst: 0,
+            direction: -90.0,
+            direction_spread: 180.0,
+            speed: 2.0..=12.0,
+            lifetime: 0.6..=1.4,
+            size: 6.0..=14.0,
+            spin: -90.0..=90.0,
+            gravity: Vec2::ZERO,
+            drag: 0.0,
+            sway: 0.0,
+            colors: vec![rgb(255, 255, 255), rgb(253, 230, 138), rgb(250, 204, 21)],
+            shapes: vec![ParticleShape::Star],
+            max_particles: 100,
+        }
+    }
+
+    /// Snowflakes falling from the top of the element.
+    pub fn snow() -> Self {
+        Self {
+            origin: Point::new(0.5, 0.0),
+            spread: Size::new(1.0, 0.0),
+            rate: 25.0,
+            burst: 0,
+            direction: 90.0,
+            direction_spread: 15.0,
+            speed: 25.0..=60.0,
+            lifetime: 20.0..=20.0,
+            size: 2.0..=6.0,
+            spin: 0.0..=0.0,
+            gravity: Vec2::new(0.0, 5.0),
+            drag: 0.0,
+            sway: 20.0,
+            colors: vec![rgb(255, 255, 255), rgb(226, 232, 240)],
+            shapes: vec![ParticleShape::Circle],
+            max_particles: 400,
+        }
+    }
+
+    pub fn origin(mut self, origin: Point) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    pub fn spread(mut self, spread: Size<f64>) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    pub fn rate(mut self, rate: f32) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn burst(mut self, burst: usize) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    pub fn direction(mut self, direction: f32, direction_spread: f32) -> Self {
+        self.direction = direction;
+        self.direction_spread = direction_spread;
+        self
+    }
+
+    pub fn speed(mut self, speed: RangeInclusive<f32>) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn lifetime(mut self, lifetime: RangeInclusive<f32>) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    pub fn size(mut self, size: RangeInclusive<f32>) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn gravity(mut self, gravity: Vec2) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    pub fn colors(mut self, colors: Vec<Color>) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    pub fn shapes(mut self, shapes: Vec<ParticleShape>) -> Self {
+        self.shapes = shapes;
+        self
+    }
+
+    pub fn max_particles(mut self, max_particles: usize) -> Self {
+        self.max_particles = max_particles;
+        self
+    }
+}
+
+/// A particle, positioned in logical pixels from the top left of the element.
+#[derive(Clone, Debug)]
+pub(crate) struct Particle {
+    pub(crate) position: Point,
+    velocity: Vec2,
+    /// In radians.
+    pub(crate) rotation: f64,
+    /// In radians per second.
+    spin: f64,
+    pub(crate) size: f32,
+    pub(crate) color: Color,
+    pub(crate) shape: ParticleShape,
+    age: f32,
+    lifetime: f32,
+    /// Where the particle is in its sway, so particles don't sway together.
+    sway_phase: f32,
+}
+
+impl Particle {
+    /// How far through its life the particle is, from 0 to 1.
+    pub(crate) fn progress(&self) -> f32 {
+        (self.age / self.lifetime).min(1.0)
+    }
+
+    /// The opacity of the particle, which fades out over the last fifth of its life.
+    pub(crate) fn opacity(&self) -> f32 {
+        ((1.0 - self.progress()) * 5.0).min(1.0)
+    }
+}
+
+/// Moves particles, and emits and removes them.
+#[derive(Clone, Debug)]
+pub(crate) struct ParticleSystem {
+    particles: Vec<Particle>,
+    random: Random,
+    /// The fraction of a particle that was due but not emitted yet.
+    pending_emission: f32,
+}
+
+impl ParticleSystem {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self {
+            particles: Vec::new(),
+            random: Random::new(seed),
+            pending_emission: 0.0,
+        }
+    }
+
+    pub(crate) fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.particles.clear();
+        self.pending_emission = 0.0;
+    }
+
+    /// Emits `count` particles at once, within the emitter's limit.
+    pub(crate) fn burst(&mut self, emitter: &ParticleEmitter, count: usize, area: Size<f64>) {
+        let count = count.min(emitter.max_particles.saturating_sub(self.particles.len()));
+        for _ in 0..count {
+            let particle = self.spawn(emitter, area);
+            self.particles.push(particle);
+        }
+    }
+
+    /// Advances the simulation by `delta` seconds, emitting particles at the emitter's rate if `emitting`.
+    pub(crate) fn step(&mut self, emitter: &ParticleEmitter, delta: f32, area: Size<f64>, emitting: bool) {
+        let delta_f64 = delta as f64;
+        let damping = (1.0 - emitter.drag as f64 * delta_f64).max(0.0);
+        for particle in &mut self.particles {
+            particle.age += delta;
+            particle.velocity = (particle.velocity + emitter.gravity * delta_f64) * damping;
+            let sway = (particle.age * 2.0 + particle.sway_phase).sin() * emitter.sway;
+            particle.position += particle.velocity * delta_f64 + Vec2::new(sway as f64 * delta_f64, 0.0);
+            particle.rotation += particle.spin * delta_f64;
+        }
+
+        // Particles also die once they fall below the element, as they won't come back.
+        let bottom = area.height + 50.0;
+        self.particles
+            .retain(|particle| particle.age < particle.lifetime && particle.position.y < bottom);
+
+        if emitting && emitter.rate > 0.0 {
+            self.pending_emission += emitter.rate * delta;
+            let count = self.pending_emission.floor();
+            self.pending_emission -= count;
+            self.burst(emitter, count as usize, area);
+        }
+    }
+
+    fn spawn(&mut self, emitter: &ParticleEmitter, area: Size<f64>) -> Particle {
+        let random = &mut self.random;
+        let x = emitter.origin.x + emitter.spread.width * (random.next_f64() - 0.5);
+        let y = emitter.origin.y + emitter.spread.height * (random.next_f64() - 0.5);
+        let direction = (emitter.direction + emitter.direction_spread * (random.next_f32() * 2.0 - 1.0)) as f64;
+        let speed = random.in_range(&emitter.speed) as f64;
+        let direction = direction.to_radians();
+
+        Particle {
+            position: Point::new(x * area.width, y * area.height),
+            velocity: Vec2::new(direction.cos(), direction.sin()) * speed,
+            rotation: random.next_f64() * 2.0 * PI,
+            spin: (random.in_range(&emitter.spin) as f64).to_radians(),
+            size: random.in_range(&emitter.size),
+            color: random.pick(&emitter.colors).copied().unwrap_or(Color::WHITE),
+            shape: random.pick(&emitter.shapes).copied().unwrap_or(ParticleShape::Circle),
+            age: 0.0,
+            lifetime: random.in_range(&emitter.lifetime).max(0.01),
+            sway_phase: random.next_f32() * 2.0 * PI as f32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Size<f64> = Size {
+        width: 400.0,
+        height: 300.0,
+    };
+
+    #[test]
+    fn bursts_respect_the_particle_limit() {
+        let emitter = ParticleEmitter::confetti().max_particles(40);
+        let mut system = ParticleSystem::new(7);
+
+        system.burst(&emitter, 30, AREA);
+        system.burst(&emitter, 30, AREA);
+
+        assert_eq!(system.particles().len(), 40);
+    }
+
+    #[test]
+    fn emits_at_the_rate_over_time() {
+        let emitter = ParticleEmitter::snow().rate(10.0);
+        let mut system = ParticleSystem::new(7);
+
+        for _ in 0..20 {
+            system.step(&emitter, 0.05, AREA, true);
+        }
+
+        assert_eq!(system.particles().len(), 10);
+    }
+
+    #[test]
+    fn particles_die_after_their_lifetime() {
+        let emitter = ParticleEmitter::sparkles().lifetime(0.5..=0.5);
+        let mut system = ParticleSystem::new(7);
+        system.burst(&emitter, 5, AREA);
+
+        system.step(&emitter, 0.45, AREA, false);
+        assert_eq!(system.particles().len(), 5);
+        assert!(system.particles()[0].opacity() < 1.0);
+
+        system.step(&emitter, 0.1, AREA, false);
+        assert!(system.particles().is_empty());
+    }
+
+    #[test]
+    fn gravity_pulls_particles_down() {
+        let emitter = ParticleEmitter::confetti().speed(0.0..=0.0).size(8.0..=8.0);
+        let mut system = ParticleSystem::new(7);
+        system.burst(&emitter, 1, AREA);
+        let start = system.particles()[0].position;
+
+        system.step(&emitter, 0.1, AREA, false);
+
+        assert!(system.particles()[0].position.y > start.y);
+        assert_eq!(system.particles()[0].size, 8.0);
+    }
+}
@@ -0,0 +1,331 @@
+//! Particle effects such as confetti, sparkles and snow.
+
+mod emitter;
+
+pub use emitter::{ParticleEmitter, ParticleShape};
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::f64::consts::PI;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time;
+#[cfg(target_arch = "wasm32")]
+use web_time as time;
+
+use craft_primitives::geometry::{Affine, BezPath, Point, Rectangle, Shape, Size};
+use craft_renderer::Brush;
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use peniko::{Color, kurbo};
+use time::{Duration, Instant};
+
+use crate::app::request_animation_frame;
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, push_child_to_element};
+use crate::elements::particles::emitter::{Particle, ParticleSystem};
+use crate::elements::traits::DeepClone;
+use crate::elements::{AsElement, Element, ElementInternals};
+use crate::layout::TaffyTree;
+use crate::pct;
+use crate::text::text_context::TextContext;
+
+/// The longest step of the simulation, so particles don't jump after the window was hidden.
+const MAX_STEP: Duration = Duration::from_millis(100);
+
+/// How many opacities particles fade through. Particles with the same color and opacity are drawn together.
+const OPACITY_LEVELS: f32 = 8.0;
+
+#[derive(Clone)]
+pub struct Particles {
+    pub inner: Rc<RefCell<ParticlesInner>>,
+}
+
+/// Draws particles from a [`ParticleEmitter`] over its children, such as confetti after a task is done, or snow.
+///
+/// The particles move on each animation frame while any are alive, and the element draws nothing and doesn't redraw
+/// once they are gone. Particles of the same color are filled as one path, so thousands of them stay cheap to draw.
+///
+/// Particles don't take pointer events, so the element can cover other elements.
+#[derive(Clone)]
+pub struct ParticlesInner {
+    element_data: ElementData,
+    emitter: ParticleEmitter,
+    system: ParticleSystem,
+    emitting: bool,
+    /// Particles to burst once the element has a size.
+    pending_burst: usize,
+    last_tick: Option<Instant>,
+}
+
+impl Element for Particles {}
+
+impl Drop for ParticlesInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for Particles {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for ParticlesInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for ParticlesInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        if !self.is_visible() {
+            return;
+        }
+
+        self.draw_borders(renderer, scale_factor);
+        self.maybe_start_layer(renderer, scale_factor);
+        self.draw_children(renderer, resource_manager, scale_factor, text_context);
+
+        self.tick();
+        self.draw_particles(renderer, scale_factor);
+        self.maybe_end_layer(renderer);
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl Particles {
+    pub fn new(emitter: ParticleEmitter) -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<ParticlesInner>>| {
+            // Each element gets different particles.
+            let seed = me.as_ptr() as u64;
+            RefCell::new(ParticlesInner {
+                element_data: ElementData::new(me.clone(), false),
+                pending_burst: emitter.burst,
+                emitter,
+                system: ParticleSystem::new(seed),
+                emitting: true,
+                last_tick: None,
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        inner_mut.set_width(pct(100));
+        inner_mut.set_height(pct(100));
+
+        drop(inner_mut);
+        Self { inner }
+    }
+
+    /// Replace the emitter. Particles that are alive keep moving the way they were.
+    pub fn emitter(self, emitter: ParticleEmitter) -> Self {
+        self.inner.borrow_mut().set_emitter(emitter);
+        self
+    }
+
+    pub fn get_emitter(&self) -> ParticleEmitter {
+        self.inner.borrow().emitter.clone()
+    }
+
+    /// Start or stop emitting particles at the emitter's rate. Particles that are alive finish their lives.
+    pub fn emitting(self, emitting: bool) -> Self {
+        self.inner.borrow_mut().set_emitting(emitting);
+        self
+    }
+
+    pub fn is_emitting(&self) -> bool {
+        self.inner.borrow().emitting
+    }
+
+    /// Emit the emitter's burst of particles again, like firing another round of confetti.
+    pub fn burst(&self) {
+        let mut inner = self.inner.borrow_mut();
+        let burst = inner.emitter.burst;
+        inner.burst(burst);
+    }
+
+    /// Returns the number of particles alive.
+    pub fn get_particle_count(&self) -> usize {
+        self.inner.borrow().system.particles().len()
+    }
+}
+
+impl ParticlesInner {
+    pub fn set_emitter(&mut self, emitter: ParticleEmitter) {
+        self.emitter = emitter;
+        self.request_window_redraw();
+    }
+
+    pub fn set_emitting(&mut self, emitting: bool) {
+        self.emitting = emitting;
+        self.request_window_redraw();
+    }
+
+    /// Emit `count` particles at once.
+    pub fn burst(&mut self, count: usize) {
+        self.pending_burst += count;
+        self.request_window_redraw();
+    }
+
+    /// Remove all the particles.
+    pub fn clear(&mut self) {
+        self.pending_burst = 0;
+        self.system.clear();
+        self.request_window_redraw();
+    }
+
+    fn is_active(&self) -> bool {
+        !self.system.particles().is_empty() || self.pending_burst > 0 || (self.emitting && self.emitter.rate > 0.0)
+    }
+
+    /// Moves the simulation to the current time.
+    fn tick(&mut self) {
+        if !self.is_active() {
+            self.last_tick = None;
+            return;
+        }
+
+        let content_rectangle = self.element_data.layout.computed_box_transformed.content_rectangle();
+        let area = Size::new(content_rectangle.width as f64, content_rectangle.height as f64);
+        let now = Instant::now();
+        let delta = self.last_tick.map_or(Duration::ZERO, |last_tick| now.saturating_duration_since(last_tick));
+        self.last_tick = Some(now);
+
+        let burst = std::mem::take(&mut self.pending_burst);
+        self.system.burst(&self.emitter, burst, area);
+        self.system.step(&self.emitter, delta.min(MAX_STEP).as_secs_f32(), area, self.emitting);
+
+        request_animation_frame();
+    }
+
+    fn draw_particles(&self, renderer: &mut dyn Renderer, scale_factor: f64) {
+        let content_rectangle = self.element_data.layout.computed_box_transformed.content_rectangle();
+        let origin =
+            Affine::scale(scale_factor) * Affine::translate((content_rectangle.x as f64, content_rectangle.y as f64));
+
+        let shapes = UnitShapes::new();
+        let mut batches: Vec<(Color, BezPath)> = Vec::new();
+        for particle in self.system.particles() {
+            let opacity = (particle.opacity() * OPACITY_LEVELS).ceil() / OPACITY_LEVELS;
+            if opacity <= 0.0 {
+                continue;
+            }
+            let color = particle.color.with_alpha(particle.color.components[3] * opacity);
+            let path = origin * particle_transform(particle) * shapes.get(particle.shape);
+
+            match batches.iter_mut().find(|(batch_color, _path)| *batch_color == color) {
+                Some((_color, batch)) => batch.extend(path),
+                None => batches.push((color, path)),
+            }
+        }
+
+        for (color, path) in batches {
+            renderer.fill_bez_path(path, Brush::Color(color));
+        }
+    }
+}
+
+/// Places a particle's shape, one logical pixel across, at its position, rotation and size.
+fn particle_transform(particle: &Particle) -> Affine {
+    let mut size = particle.size as f64;
+    // Stars grow and then shrink again.
+    if particle.shape == ParticleShape::Star {
+        size *= (particle.progress() as f64 * PI).sin();
+    }
+    Affine::translate(particle.position.to_vec2()) * Affine::rotate(particle.rotation) * Affine::scale(size)
+}
+
+/// The shapes of particles, one logical pixel across and centered on the origin.
+struct UnitShapes {
+    rectangle: BezPath,
+    circle: BezPath,
+    star: BezPath,
+}
+
+impl UnitShapes {
+    fn new() -> Self {
+        let mut star = BezPath::new();
+        for point in 0..8 {
+            let angle = point as f64 * PI / 4.0 - PI / 2.0;
+            let radius = if point % 2 == 0 { 0.5 } else { 0.12 };
+            let vertex = Point::new(angle.cos() * radius, angle.sin() * radius);
+            if point == 0 {
+                star.move_to(vertex);
+            } else {
+                star.line_to(vertex);
+            }
+        }
+        star.close_path();
+
+        Self {
+            rectangle: kurbo::Rect::new(-0.5, -0.3, 0.5, 0.3).to_path(0.01),
+            circle: kurbo::Circle::new((0.0, 0.0), 0.5).to_path(0.01),
+            star,
+        }
+    }
+
+    fn get(&self, shape: ParticleShape) -> &BezPath {
+        match shape {
+            ParticleShape::Rectangle => &self.rectangle,
+            ParticleShape::Circle => &self.circle,
+            ParticleShape::Star => &self.star,
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! Opens PDF documents and renders their pages with pdfium.
+//!
+//! Pdfium is slow and can only be loaded once per process, so one thread owns it and works for every [`PdfView`]
+//! through a channel.
+//!
+//! [`PdfView`]: crate::elements::PdfView
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use craft_logging::warn;
+use craft_primitives::geometry::{Rectangle, Size};
+use image::RgbaImage;
+use pdfium_render::prelude::{PdfDocument, PdfPageIndex, PdfRenderConfig, Pdfium};
+
+use crate::elements::pdf_view::text::PdfChar;
+
+/// The largest width or height of a rendered page in pixels.
+const MAX_RENDER_SIZE: f32 = 8192.0;
+
+/// Where a PDF document is read from.
+#[derive(Clone, Debug)]
+pub enum PdfSource {
+    File(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+/// Sent by the viewers to the pdfium thread.
+enum PdfCommand {
+    Open {
+        document: u64,
+        source: PdfSource,
+        password: Option<String>,
+        responses: Sender<PdfResponse>,
+    },
+    /// Render a page at the scale of a [`render_step`].
+    Render { document: u64, page: usize, step: i32 },
+    Close { document: u64 },
+}
+
+/// Sent by the pdfium thread to the viewer of a document.
+pub(crate) enum PdfResponse {
+    /// The document opened, with the size of each page in points.
+    Opened(Vec<Size<f32>>),
+    Failed(String),
+    Rendered { page: usize, step: i32, image: RgbaImage },
+    /// The characters of a page, sent the first time the page is rendered.
+    Text { page: usize, chars: Vec<PdfChar> },
+}
+
+/// A document opened on the pdfium thread, which is closed when this is dropped.
+pub(crate) struct PdfDocumentHandle {
+    id: u64,
+    responses: Receiver<PdfResponse>,
+}
+
+impl PdfDocumentHandle {
+    pub(crate) fn open(source: PdfSource, password: Option<String>) -> Self {
+        static NEXT_DOCUMENT_ID: AtomicU64 = AtomicU64::new(0);
+
+        let id = NEXT_DOCUMENT_ID.fetch_add(1, Ordering::Relaxed);
+        let (responses_sender, responses) = mpsc::channel();
+        send(PdfCommand::Open {
+            document: id,
+            source,
+            password,
+            responses: responses_sender,
+        });
+        Self { id, responses }
+    }
+
+    pub(crate) fn render(&self, page: usize, step: i32) {
+        send(PdfCommand::Render {
+            document: self.id,
+            page,
+            step,
+        });
+    }
+
+    /// Returns the next response without waiting for one.
+    pub(crate) fn try_receive(&self) -> Option<PdfResponse> {
+        self.responses.try_recv().ok()
+    }
+}
+
+impl Drop for PdfDocumentHandle {
+    fn drop(&mut self) {
+        send(PdfCommand::Close { document: self.id });
+    }
+}
+
+/// Returns the step to render a page at for a zoom in pixels per point.
+///
+/// Pages are rendered at half steps of powers of two, so small zoom changes reuse the rendered pages.
+pub(crate) fn render_step(pixels_per_point: f64, page_size: Size<f32>) -> i32 {
+    let largest_scale = MAX_RENDER_SIZE / page_size.width.max(page_size.height).max(1.0);
+    let step = (pixels_per_point.max(0.01).log2() * 2.0).ceil() as i32;
+    step.min((largest_scale.log2() * 2.0).floor() as i32)
+}
+
+/// The pixels per point of a render step.
+pub(crate) fn step_scale(step: i32) -> f32 {
+    (step as f32 / 2.0).exp2()
+}
+
+fn send(command: PdfCommand) {
+    static COMMANDS: OnceLock<Sender<PdfCommand>> = OnceLock::new();
+
+    let commands = COMMANDS.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel();
+        thread::Builder::new()
+            .name("craft pdfium".to_string())
+            .spawn(move || run(receiver))
+            .expect("Failed to start the pdfium thread");
+        sender
+    });
+    commands.send(command).ok();
+}
+
+fn bind_pdfium() -> Result<Pdfium, String> {
+    let bindings = Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path("./"))
+        .or_else(|_| Pdfium::bind_to_system_library())
+        .map_err(|error| format!("Couldn't load the pdfium library: {error}"))?;
+    Ok(Pdfium::new(bindings))
+}
+
+/// An open document on the pdfium thread.
+struct OpenDocument<'a> {
+    document: PdfDocument<'a>,
+    responses: Sender<PdfResponse>,
+    /// The pages whose text was sent.
+    text_sent: HashSet<usize>,
+}
+
+fn run(commands: Receiver<PdfCommand>) {
+    let pdfium = bind_pdfium();
+    let mut documents: HashMap<u64, OpenDocument> = HashMap::new();
+
+    while let Ok(command) = commands.recv() {
+        match command {
+            PdfCommand::Open {
+                document: id,
+                source,
+                password,
+                responses,
+            } => {
+                let pdfium = match &pdfium {
+                    Ok(pdfium) => pdfium,
+                    Err(error) => {
+                        responses.send(PdfResponse::Failed(error.clone())).ok();
+                        continue;
+                    }
+                };
+                let bytes = match source {
+                    PdfSource::File(path) => fs::read(path),
+                    PdfSource::Bytes(bytes) => Ok(bytes),
+                };
+                let document = bytes
+                    .map_err(|error| error.to_string())
+                    .and_then(|bytes| {
+                        pdfium
+                            .load_pdf_from_byte_vec(bytes, password.as_deref())
+                            .map_err(|error| error.to_string())
+                    });
+                let document = match document {
+                    Ok(document) => document,
+                    Err(error) => {
+                        responses.send(PdfResponse::Failed(format!("Couldn't open the document: {error}"))).ok();
+                        continue;
+                    }
+                };
+                let page_sizes = document
+                    .pages()
+                    .page_sizes()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|rect| Size::new(rect.width().value, rect.height().value))
+                    .collect();
+                responses.send(PdfResponse::Opened(page_sizes)).ok();
+                documents.insert(
+                    id,
+                    OpenDocument {
+                        document,
+                        responses,
+                        text_sent: HashSet::new(),
+                    },
+                );
+            }
+            PdfCommand::Render { document, page, step } => {
+                if let Some(document) = documents.get_mut(&document) {
+                    render_page(document, page, step);
+                }
+            }
+            PdfCommand::Close { document } => {
+                documents.remove(&document);
+            }
+        }
+    }
+}
+
+fn render_page(document: &mut OpenDocument, page_index: usize, step: i32) {
+    let Ok(page) = document.document.pages().get(page_index as PdfPageIndex) else {
+        return;
+    };
+
+    match page.render_with_config(&PdfRenderConfig::new().scale_page_by_factor(step_scale(step))) {
+        Ok(bitmap) => {
+            let rendered = PdfResponse::Rendered {
+                page: page_index,
+                step,
+                image: bitmap.as_image().to_rgba8(),
+            };
+            document.responses.send(rendered).ok();
+        }
+        Err(error) => warn!("Failed to render page {page_index} of a PDF: {error}"),
+    }
+
+    if !document.text_sent.insert(page_index) {
+        return;
+    }
+    let page_height = page.height().value;
+    let chars = match page.text() {
+        Ok(text) => text
+            .chars()
+            .iter()
+            .filter_map(|text_char| {
+                let character = text_char.unicode_char()?;
+                let bounds = text_char.loose_bounds().ok()?;
+                // Pdfium measures from the bottom left of the page, and the viewer from the top left.
+                Some(PdfChar {
+                    character,
+                    bounds: Rectangle::new(
+                        bounds.left().value,
+                        page_height - bounds.top().value,
+                        bounds.width().value,
+                        bounds.height().value,
+                    ),
+                })
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    document.responses.send(PdfResponse::Text { page: page_index, chars }).ok();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_render_at_half_steps() {
+        let letter = Size::new(612.0, 792.0);
+
+        assert_eq!(render_step(1.0, letter), 0);
+        assert_eq!(render_step(1.2, letter), 1);
+        assert_eq!(render_step(2.0, letter), 2);
+        assert!((step_scale(1) - std::f32::consts::SQRT_2).abs() < 1e-6);
+        // Pages aren't rendered larger than MAX_RENDER_SIZE.
+        assert_eq!(render_step(64.0, letter), 6);
+    }
+}
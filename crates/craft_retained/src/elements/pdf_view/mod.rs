@@ -0,0 +1,684 @@
+//! Shows the pages of a PDF document.
+
+mod document;
+mod text;
+
+pub use document::PdfSource;
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::{HashMap, HashSet};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use craft_primitives::geometry::{Affine, Point, Rectangle, Size, Vec2};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::image::ImageResource;
+use craft_resource_manager::resource::Resource;
+use craft_resource_manager::resource_type::ResourceType;
+use craft_resource_manager::{ResourceId, ResourceManager};
+use peniko::Color;
+use ui_events::ScrollDelta;
+use ui_events::keyboard::{Code, KeyState, Modifiers};
+use ui_events::pointer::{PointerButton, PointerGesture};
+
+use crate::app::{queue_event, request_animation_frame};
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, copy_to_clipboard, push_child_to_element};
+use crate::elements::pdf_view::document::{PdfDocumentHandle, PdfResponse, render_step};
+use crate::elements::pdf_view::text::{PdfChar, char_at, selected_text, selection_rectangles};
+use crate::elements::traits::DeepClone;
+use crate::elements::{AsElement, Element, ElementInternals, Text};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::{Display, Overflow, Position};
+use crate::text::text_context::TextContext;
+use crate::{auto, pct, px, rgb, rgba};
+
+/// Logical pixels per point at a zoom of 1, which shows pages at their printed size on a 96 DPI screen.
+const PIXELS_PER_POINT: f64 = 96.0 / 72.0;
+
+/// The space around the page in logical pixels.
+const PAGE_MARGIN: f64 = 16.0;
+
+/// How many logical pixels one line of the mouse wheel or an arrow key scrolls.
+const LINE_HEIGHT: f64 = 40.0;
+
+/// How much one line of the mouse wheel zooms while Ctrl is held.
+const WHEEL_ZOOM_STEP: f64 = 1.1;
+
+#[derive(Clone)]
+pub struct PdfView {
+    pub inner: Rc<RefCell<PdfViewInner>>,
+}
+
+/// Shows a PDF document one page at a time.
+///
+/// Pages are rendered by pdfium on a background thread and drawn from the resource manager. The page is fitted to
+/// the width of the viewer until the user zooms with Ctrl and the mouse wheel or a pinch. Scrolling past the end of a
+/// page, the Page Up and Page Down keys and [`PdfViewInner::set_page`] change the page, which sends an
+/// [`EventKind::PdfPageChanged`] event when the user did it.
+///
+/// The text of the page can be selected by dragging, and copied with Ctrl+C, if the document has text.
+///
+/// Rendered pages stay in the resource manager, which can't remove resources, so each page is rendered at most once
+/// per zoom step.
+#[derive(Clone)]
+pub struct PdfViewInner {
+    element_data: ElementData,
+    document: Option<Rc<PdfDocumentHandle>>,
+    /// True until the document opens or fails to open.
+    loading: bool,
+    /// The size of each page in points.
+    page_sizes: Vec<Size<f32>>,
+    page: usize,
+    /// `None` fits the width of the page to the viewer.
+    zoom: Option<f64>,
+    min_zoom: f64,
+    max_zoom: f64,
+    /// How far the page is scrolled in logical pixels.
+    scroll: Vec2,
+    /// The rendered pages, by page and render step.
+    renders: HashMap<(usize, i32), ResourceId>,
+    pending_renders: HashSet<(usize, i32)>,
+    texts: HashMap<usize, Vec<PdfChar>>,
+    /// The characters where the selection on the current page starts and ends.
+    selection: Option<(usize, usize)>,
+    selecting: bool,
+    selection_color: Color,
+    /// Shows that the document is loading, or why it couldn't be opened.
+    pub status: Text,
+}
+
+impl Element for PdfView {}
+
+impl Drop for PdfViewInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for PdfView {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for PdfViewInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for PdfViewInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+
+        // The viewer may have been resized.
+        self.clamp_scroll();
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        if !self.is_visible() {
+            return;
+        }
+
+        self.receive_responses(&resource_manager);
+
+        self.add_hit_testable(renderer, true, scale_factor);
+        self.draw_borders(renderer, scale_factor);
+
+        let content_rectangle = self.content_rectangle();
+        renderer.push_layer(content_rectangle.scale(scale_factor));
+        if let (Some(page_size), Some(page_rectangle)) = (self.page_size(), self.page_rectangle()) {
+            let page_rectangle = Rectangle::new(
+                content_rectangle.x + page_rectangle.x,
+                content_rectangle.y + page_rectangle.y,
+                page_rectangle.width,
+                page_rectangle.height,
+            );
+            let physical_page = page_rectangle.scale(scale_factor).snap_to_pixels();
+            renderer.draw_rect(physical_page, Color::WHITE);
+
+            let step = render_step(self.pixels_per_point() * scale_factor, page_size);
+            match self.renders.get(&(self.page, step)) {
+                Some(resource_id) => renderer.draw_image(physical_page, resource_id.clone()),
+                None => {
+                    if self.pending_renders.insert((self.page, step))
+                        && let Some(document) = &self.document
+                    {
+                        document.render(self.page, step);
+                    }
+                    // Show the page at another zoom until it is rendered at this one.
+                    let nearest = self
+                        .renders
+                        .iter()
+                        .filter(|((page, _step), _resource_id)| *page == self.page)
+                        .min_by_key(|((_page, other_step), _resource_id)| (other_step - step).abs());
+                    if let Some((_, resource_id)) = nearest {
+                        renderer.draw_image(physical_page, resource_id.clone());
+                    }
+                }
+            }
+
+            if let (Some(range), Some(chars)) = (self.selection_range(), self.texts.get(&self.page)) {
+                let pixels_per_point = self.pixels_per_point() as f32;
+                for rectangle in selection_rectangles(chars, range) {
+                    let rectangle = Rectangle::new(
+                        page_rectangle.x + rectangle.x * pixels_per_point,
+                        page_rectangle.y + rectangle.y * pixels_per_point,
+                        rectangle.width * pixels_per_point,
+                        rectangle.height * pixels_per_point,
+                    );
+                    renderer.draw_rect(rectangle.scale(scale_factor), self.selection_color);
+                }
+            }
+        }
+        renderer.pop_layer();
+
+        self.draw_children(renderer, resource_manager, scale_factor, text_context);
+
+        // Keep drawing until pdfium answers.
+        if self.loading || !self.pending_renders.is_empty() {
+            request_animation_frame();
+        }
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        _target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        match message {
+            EventKind::PointerScroll(scroll) => {
+                let viewport = self.viewport_size();
+                let delta = match scroll.delta {
+                    ScrollDelta::LineDelta(x, y) => Vec2::new(x as f64, y as f64) * LINE_HEIGHT,
+                    ScrollDelta::PixelDelta(physical) => Vec2::new(physical.x, physical.y),
+                    ScrollDelta::PageDelta(x, y) => Vec2::new(x as f64 * viewport.width, y as f64 * viewport.height),
+                };
+                let modifiers = scroll.state.modifiers;
+                if is_action_modifier(modifiers) {
+                    let anchor = self.viewport_point(scroll.state.logical_point());
+                    self.zoom_at(self.effective_zoom() * WHEEL_ZOOM_STEP.powf(delta.y / LINE_HEIGHT), anchor);
+                } else if modifiers.shift() {
+                    self.scroll_by(Vec2::new(-delta.y, 0.0));
+                } else {
+                    self.scroll_by(-delta);
+                }
+                event.prevent_propagate();
+                event.prevent_defaults();
+            }
+            EventKind::PointerGesture(gesture) => {
+                if let PointerGesture::Pinch(delta) = gesture.gesture {
+                    let anchor = self.viewport_point(gesture.state.logical_point());
+                    self.zoom_at(self.effective_zoom() * (1.0 + delta as f64), anchor);
+                    event.prevent_propagate();
+                    event.prevent_defaults();
+                }
+            }
+            EventKind::PointerButtonDown(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                self.focus();
+                let index = self.char_at(pointer_button.state.logical_point());
+                self.selection = index.map(|index| (index, index));
+                if index.is_some() {
+                    self.selecting = true;
+                    self.capture_pointer();
+                }
+                self.request_window_redraw();
+            }
+            EventKind::PointerMovedEvent(pointer_update) => {
+                if !self.selecting {
+                    return;
+                }
+                if let (Some((anchor, _)), Some(index)) =
+                    (self.selection, self.char_at(pointer_update.current.logical_point()))
+                {
+                    self.selection = Some((anchor, index));
+                    self.request_window_redraw();
+                }
+            }
+            EventKind::PointerButtonUp(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                if self.selecting {
+                    self.selecting = false;
+                    self.release_pointer();
+                }
+            }
+            EventKind::LostPointerCapture() => {
+                self.selecting = false;
+            }
+            EventKind::KeyboardInputEvent(key) => {
+                if key.state != KeyState::Down || !self.is_focused() {
+                    return;
+                }
+                if self.on_key_down(key.code, key.modifiers) {
+                    event.prevent_propagate();
+                    event.prevent_defaults();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn handles_arrow_keys(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl PdfView {
+    pub fn new(source: PdfSource) -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<PdfViewInner>>| {
+            RefCell::new(PdfViewInner {
+                element_data: ElementData::new(me.clone(), false),
+                document: None,
+                loading: false,
+                page_sizes: Vec::new(),
+                page: 0,
+                zoom: None,
+                min_zoom: 0.1,
+                max_zoom: 8.0,
+                scroll: Vec2::ZERO,
+                renders: HashMap::new(),
+                pending_renders: HashSet::new(),
+                texts: HashMap::new(),
+                selection: None,
+                selecting: false,
+                selection_color: rgba(0, 120, 215, 90),
+                status: Text::new("")
+                    .selectable(false)
+                    .position(Position::Absolute)
+                    .inset(px(12), auto(), auto(), px(12))
+                    .color(rgb(80, 80, 80)),
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        inner_mut.set_width(pct(100));
+        inner_mut.set_height(px(600));
+        inner_mut.set_overflow(Overflow::Hidden, Overflow::Hidden);
+        inner_mut.set_background_color(rgb(82, 86, 89));
+        let status = inner_mut.status.clone();
+        inner_mut.push(status.inner);
+        inner_mut.set_document(source, None);
+
+        drop(inner_mut);
+        Self { inner }
+    }
+
+    /// Open another document.
+    pub fn source(self, source: PdfSource) -> Self {
+        self.inner.borrow_mut().set_document(source, None);
+        self
+    }
+
+    /// Open an encrypted document.
+    pub fn password_protected_source(self, source: PdfSource, password: &str) -> Self {
+        self.inner.borrow_mut().set_document(source, Some(password.to_string()));
+        self
+    }
+
+    /// Show a page, by its index from 0.
+    pub fn page(self, page: usize) -> Self {
+        self.inner.borrow_mut().set_page(page);
+        self
+    }
+
+    pub fn get_page(&self) -> usize {
+        self.inner.borrow().page
+    }
+
+    /// Returns the number of pages, or 0 until the document is open.
+    pub fn get_page_count(&self) -> usize {
+        self.inner.borrow().page_sizes.len()
+    }
+
+    /// Set the zoom, where 1 shows pages at their printed size. `None` fits the width of the page to the viewer,
+    /// which is the default.
+    pub fn zoom(self, zoom: Option<f64>) -> Self {
+        self.inner.borrow_mut().set_zoom(zoom);
+        self
+    }
+
+    pub fn get_zoom(&self) -> Option<f64> {
+        self.inner.borrow().zoom
+    }
+
+    /// Set the smallest and largest zoom. Defaults to 0.1 and 8.
+    pub fn zoom_limits(self, min: f64, max: f64) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.min_zoom = min.min(max);
+        inner.max_zoom = max.max(min);
+        let zoom = inner.zoom;
+        inner.set_zoom(zoom);
+        drop(inner);
+        self
+    }
+
+    pub fn selection_color(self, color: Color) -> Self {
+        self.inner.borrow_mut().selection_color = color;
+        self
+    }
+
+    /// Returns the selected text of the current page.
+    pub fn get_selected_text(&self) -> String {
+        self.inner.borrow().selected_text()
+    }
+}
+
+impl PdfViewInner {
+    pub fn set_document(&mut self, source: PdfSource, password: Option<String>) {
+        self.document = Some(Rc::new(PdfDocumentHandle::open(source, password)));
+        self.loading = true;
+        self.page_sizes.clear();
+        self.page = 0;
+        self.scroll = Vec2::ZERO;
+        self.renders.clear();
+        self.pending_renders.clear();
+        self.texts.clear();
+        self.selection = None;
+        self.set_status("Loading…");
+        self.request_window_redraw();
+    }
+
+    /// Show a page, by its index from 0. Pages past the end show the last page once the document is open.
+    pub fn set_page(&mut self, page: usize) {
+        let page = match self.page_sizes.len() {
+            0 => page,
+            count => page.min(count - 1),
+        };
+        if page == self.page {
+            return;
+        }
+        self.page = page;
+        self.scroll = Vec2::ZERO;
+        self.selection = None;
+        self.request_window_redraw();
+    }
+
+    pub fn set_zoom(&mut self, zoom: Option<f64>) {
+        self.zoom = zoom.map(|zoom| zoom.clamp(self.min_zoom, self.max_zoom));
+        self.clamp_scroll();
+        self.request_window_redraw();
+    }
+
+    fn set_status(&mut self, status: &str) {
+        let mut label = self.status.inner.borrow_mut();
+        label.set_text(status);
+        label.set_display(if status.is_empty() { Display::None } else { Display::Flex });
+    }
+
+    /// Handles the responses of the pdfium thread.
+    fn receive_responses(&mut self, resource_manager: &ResourceManager) {
+        let Some(document) = self.document.clone() else {
+            return;
+        };
+        while let Some(response) = document.try_receive() {
+            match response {
+                PdfResponse::Opened(page_sizes) => {
+                    self.loading = false;
+                    self.set_status(if page_sizes.is_empty() { "The document has no pages." } else { "" });
+                    self.page = self.page.min(page_sizes.len().saturating_sub(1));
+                    self.page_sizes = page_sizes;
+                }
+                PdfResponse::Failed(error) => {
+                    self.loading = false;
+                    self.set_status(&error);
+                }
+                PdfResponse::Rendered { page, step, image } => {
+                    let resource_id = ResourceId::generate();
+                    let resource = Resource {
+                        resource_type: ResourceType::Image,
+                        data: Box::new(ImageResource { image }),
+                        expiration_time: None,
+                    };
+                    resource_manager.insert(resource_id.clone(), Arc::new(resource));
+                    self.renders.insert((page, step), resource_id);
+                    self.pending_renders.remove(&(page, step));
+                }
+                PdfResponse::Text { page, chars } => {
+                    self.texts.insert(page, chars);
+                }
+            }
+        }
+    }
+
+    /// Changes the page for the user, and sends an event.
+    fn go_to_page(&mut self, page: usize) {
+        let previous_page = self.page;
+        self.set_page(page);
+        if self.page != previous_page {
+            let new_event = Event::new(self.element_data.me.upgrade().unwrap());
+            queue_event(new_event, EventKind::PdfPageChanged(self.page));
+        }
+    }
+
+    /// Handles a key press and returns true if it was used.
+    fn on_key_down(&mut self, code: Code, modifiers: Modifiers) -> bool {
+        let last_page = self.page_sizes.len().saturating_sub(1);
+        match code {
+            Code::PageDown | Code::ArrowRight => self.go_to_page((self.page + 1).min(last_page)),
+            Code::PageUp | Code::ArrowLeft => self.go_to_page(self.page.saturating_sub(1)),
+            Code::Home => self.go_to_page(0),
+            Code::End => self.go_to_page(last_page),
+            Code::ArrowDown => self.scroll_by(Vec2::new(0.0, LINE_HEIGHT)),
+            Code::ArrowUp => self.scroll_by(Vec2::new(0.0, -LINE_HEIGHT)),
+            Code::Equal if is_action_modifier(modifiers) => self.set_zoom(Some(self.effective_zoom() * 1.25)),
+            Code::Minus if is_action_modifier(modifiers) => self.set_zoom(Some(self.effective_zoom() / 1.25)),
+            Code::Digit0 if is_action_modifier(modifiers) => self.set_zoom(None),
+            Code::KeyA if is_action_modifier(modifiers) => {
+                let count = self.texts.get(&self.page).map_or(0, Vec::len);
+                self.selection = count.checked_sub(1).map(|last| (0, last));
+                self.request_window_redraw();
+            }
+            Code::KeyC if is_action_modifier(modifiers) => {
+                let text = self.selected_text();
+                if text.is_empty() {
+                    return false;
+                }
+                copy_to_clipboard(text);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn content_rectangle(&self) -> Rectangle {
+        self.element_data.layout.computed_box_transformed.content_rectangle()
+    }
+
+    fn viewport_size(&self) -> Size<f64> {
+        let content_rectangle = self.content_rectangle();
+        Size::new(content_rectangle.width as f64, content_rectangle.height as f64)
+    }
+
+    /// Converts a window point to a point relative to the top left of the viewer's content.
+    fn viewport_point(&self, point: Point) -> Point {
+        let content_rectangle = self.content_rectangle();
+        Point::new(point.x - content_rectangle.x as f64, point.y - content_rectangle.y as f64)
+    }
+
+    fn page_size(&self) -> Option<Size<f32>> {
+        self.page_sizes.get(self.page).copied()
+    }
+
+    /// The zoom, including the zoom that fits the page to the viewer.
+    fn effective_zoom(&self) -> f64 {
+        if let Some(zoom) = self.zoom {
+            return zoom;
+        }
+        let page_width = self.page_size().map_or(1.0, |page_size| page_size.width.max(1.0) as f64);
+        let fit = (self.viewport_size().width - 2.0 * PAGE_MARGIN).max(1.0) / (page_width * PIXELS_PER_POINT);
+        fit.clamp(self.min_zoom, self.max_zoom)
+    }
+
+    /// Logical pixels per point of the page.
+    fn pixels_per_point(&self) -> f64 {
+        PIXELS_PER_POINT * self.effective_zoom()
+    }
+
+    /// The size of the page in logical pixels.
+    fn page_display_size(&self) -> Option<Size<f64>> {
+        let page_size = self.page_size()?;
+        let pixels_per_point = self.pixels_per_point();
+        Some(Size::new(
+            page_size.width as f64 * pixels_per_point,
+            page_size.height as f64 * pixels_per_point,
+        ))
+    }
+
+    /// Where the page is, relative to the viewer's content. Pages smaller than the viewer are centered.
+    fn page_rectangle(&self) -> Option<Rectangle> {
+        let size = self.page_display_size()?;
+        let viewport = self.viewport_size();
+        let place = |length: f64, viewport_length: f64, scroll: f64| {
+            if length + 2.0 * PAGE_MARGIN <= viewport_length {
+                (viewport_length - length) / 2.0
+            } else {
+                PAGE_MARGIN - scroll
+            }
+        };
+        Some(Rectangle::new(
+            place(size.width, viewport.width, self.scroll.x) as f32,
+            place(size.height, viewport.height, self.scroll.y) as f32,
+            size.width as f32,
+            size.height as f32,
+        ))
+    }
+
+    fn max_scroll(&self) -> Vec2 {
+        let Some(size) = self.page_display_size() else {
+            return Vec2::ZERO;
+        };
+        let viewport = self.viewport_size();
+        Vec2::new(
+            (size.width + 2.0 * PAGE_MARGIN - viewport.width).max(0.0),
+            (size.height + 2.0 * PAGE_MARGIN - viewport.height).max(0.0),
+        )
+    }
+
+    fn clamp_scroll(&mut self) {
+        let max_scroll = self.max_scroll();
+        self.scroll = Vec2::new(self.scroll.x.clamp(0.0, max_scroll.x), self.scroll.y.clamp(0.0, max_scroll.y));
+    }
+
+    /// Scrolls the page, or goes to the next or previous page when it is already scrolled to its end.
+    fn scroll_by(&mut self, delta: Vec2) {
+        let max_scroll = self.max_scroll();
+        if delta.y > 0.0 && self.scroll.y >= max_scroll.y && self.page + 1 < self.page_sizes.len() {
+            self.go_to_page(self.page + 1);
+            return;
+        }
+        if delta.y < 0.0 && self.scroll.y <= 0.0 && self.page > 0 {
+            self.go_to_page(self.page - 1);
+            self.scroll.y = self.max_scroll().y;
+            return;
+        }
+        self.scroll += delta;
+        self.clamp_scroll();
+        self.request_window_redraw();
+    }
+
+    /// Sets the zoom, keeping the point of the page under `anchor` in place.
+    fn zoom_at(&mut self, zoom: f64, anchor: Point) {
+        let Some(page_rectangle) = self.page_rectangle() else {
+            return;
+        };
+        let old_pixels_per_point = self.pixels_per_point();
+        let page_point = Vec2::new(
+            (anchor.x - page_rectangle.x as f64) / old_pixels_per_point,
+            (anchor.y - page_rectangle.y as f64) / old_pixels_per_point,
+        );
+        self.zoom = Some(zoom.clamp(self.min_zoom, self.max_zoom));
+        let new_origin = anchor.to_vec2() - page_point * self.pixels_per_point();
+        self.scroll = Vec2::new(PAGE_MARGIN, PAGE_MARGIN) - new_origin;
+        self.clamp_scroll();
+        self.request_window_redraw();
+    }
+
+    /// Returns the character under a window point.
+    fn char_at(&self, point: Point) -> Option<usize> {
+        let chars = self.texts.get(&self.page)?;
+        let page_rectangle = self.page_rectangle()?;
+        let point = self.viewport_point(point);
+        let pixels_per_point = self.pixels_per_point();
+        let page_point = Point::new(
+            (point.x - page_rectangle.x as f64) / pixels_per_point,
+            (point.y - page_rectangle.y as f64) / pixels_per_point,
+        );
+        char_at(chars, page_point)
+    }
+
+    fn selection_range(&self) -> Option<std::ops::RangeInclusive<usize>> {
+        let (anchor, focus) = self.selection?;
+        Some(anchor.min(focus)..=anchor.max(focus))
+    }
+
+    fn selected_text(&self) -> String {
+        match (self.selection_range(), self.texts.get(&self.page)) {
+            (Some(range), Some(chars)) => selected_text(chars, range),
+            _ => String::new(),
+        }
+    }
+}
+
+/// Returns true if the modifiers are the ones used for shortcuts, i.e. Ctrl, or Cmd on macOS.
+fn is_action_modifier(modifiers: Modifiers) -> bool {
+    modifiers.ctrl() || modifiers.meta()
+}
@@ -0,0 +1,116 @@
+//! The text of PDF pages, for selecting and copying it.
+
+use std::ops::RangeInclusive;
+
+use craft_primitives::geometry::{Point, Rectangle};
+
+/// A character of a page, with its bounds in points from the top left of the page.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct PdfChar {
+    pub(crate) character: char,
+    pub(crate) bounds: Rectangle,
+}
+
+impl PdfChar {
+    /// Returns true if `other` is on a later line, i.e. it starts below the middle of this character.
+    fn is_above(&self, other: &PdfChar) -> bool {
+        other.bounds.y > self.bounds.y + self.bounds.height / 2.0
+    }
+}
+
+/// Returns the index of the character under a point of the page, or the nearest one.
+pub(crate) fn char_at(chars: &[PdfChar], point: Point) -> Option<usize> {
+    let distance = |bounds: &Rectangle| {
+        let dx = (bounds.x as f64 - point.x).max(point.x - bounds.right() as f64).max(0.0);
+        let dy = (bounds.y as f64 - point.y).max(point.y - bounds.bottom() as f64).max(0.0);
+        // Points beside a line are nearer to it than points between lines.
+        dx + dy * 4.0
+    };
+    chars
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| distance(&a.bounds).total_cmp(&distance(&b.bounds)))
+        .map(|(index, _)| index)
+}
+
+/// Returns the text of a range of characters, with a line break between lines.
+pub(crate) fn selected_text(chars: &[PdfChar], range: RangeInclusive<usize>) -> String {
+    let mut text = String::new();
+    let mut previous: Option<&PdfChar> = None;
+    for pdf_char in chars.get(range).unwrap_or_default() {
+        if let Some(previous) = previous
+            && previous.is_above(pdf_char)
+            && !previous.character.is_whitespace()
+            && !pdf_char.character.is_whitespace()
+        {
+            text.push('\n');
+        }
+        text.push(pdf_char.character);
+        previous = Some(pdf_char);
+    }
+    text
+}
+
+/// Returns the rectangles to highlight for a range of characters, one for each line.
+pub(crate) fn selection_rectangles(chars: &[PdfChar], range: RangeInclusive<usize>) -> Vec<Rectangle> {
+    let mut rectangles: Vec<Rectangle> = Vec::new();
+    let mut previous: Option<&PdfChar> = None;
+    for pdf_char in chars.get(range).unwrap_or_default() {
+        if pdf_char.bounds.width <= 0.0 || pdf_char.bounds.height <= 0.0 {
+            continue;
+        }
+        match (rectangles.last_mut(), previous) {
+            (Some(line), Some(previous)) if !previous.is_above(pdf_char) && pdf_char.bounds.x >= line.x => {
+                *line = Rectangle::from_kurbo(line.to_kurbo().union(pdf_char.bounds.to_kurbo()));
+            }
+            _ => rectangles.push(pdf_char.bounds),
+        }
+        previous = Some(pdf_char);
+    }
+    rectangles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lays out lines of 10 by 12 point characters, 20 points apart.
+    fn chars(lines: &[&str]) -> Vec<PdfChar> {
+        let mut chars = Vec::new();
+        for (line, text) in lines.iter().enumerate() {
+            for (column, character) in text.chars().enumerate() {
+                chars.push(PdfChar {
+                    character,
+                    bounds: Rectangle::new(column as f32 * 10.0, line as f32 * 20.0, 10.0, 12.0),
+                });
+            }
+        }
+        chars
+    }
+
+    #[test]
+    fn finds_the_nearest_character() {
+        let chars = chars(&["ab", "cd"]);
+
+        assert_eq!(char_at(&chars, Point::new(15.0, 5.0)), Some(1));
+        assert_eq!(char_at(&chars, Point::new(100.0, 22.0)), Some(3));
+        assert_eq!(char_at(&[], Point::ZERO), None);
+    }
+
+    #[test]
+    fn copies_lines_with_line_breaks() {
+        let chars = chars(&["one", "two"]);
+
+        assert_eq!(selected_text(&chars, 1..=4), "ne\ntw");
+    }
+
+    #[test]
+    fn highlights_one_rectangle_per_line() {
+        let chars = chars(&["one", "two"]);
+
+        assert_eq!(
+            selection_rectangles(&chars, 1..=4),
+            vec![Rectangle::new(10.0, 0.0, 20.0, 12.0), Rectangle::new(0.0, 20.0, 20.0, 12.0)]
+        );
+    }
+}
@@ -0,0 +1,414 @@
+//! Named properties edited with controls for their types.
+
+mod property;
+
+pub use property::{Property, PropertyValue};
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use craft_primitives::geometry::{Affine, Point, Rectangle};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use peniko::Color;
+use ui_events::pointer::PointerButton;
+
+use crate::app::queue_event;
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, draw_generic_container, is_target_within, push_child_to_element};
+use crate::elements::property_grid::property::{format_hex_color, parse_hex_color};
+use crate::elements::traits::DeepClone;
+use crate::elements::{resolve_clip_for_scrollable, AsElement, Checkbox, Container, Dropdown, Element, ElementInternals, Text, TextInput};
+use crate::events::{Event, EventKind, PropertyChanged};
+use crate::layout::TaffyTree;
+use crate::style::{AlignItems, Display, FlexDirection, Overflow};
+use crate::text::text_context::TextContext;
+use crate::{pct, px, rgb};
+
+const BORDER_COLOR: Color = rgb(199, 199, 206);
+const INVALID_BORDER_COLOR: Color = rgb(220, 38, 38);
+const LABEL_COLOR: Color = rgb(80, 80, 80);
+const SWATCH_SIZE: f32 = 22.0;
+const PALETTE: [Color; 12] = [
+    rgb(0, 0, 0),
+    rgb(255, 255, 255),
+    rgb(107, 114, 128),
+    rgb(220, 38, 38),
+    rgb(234, 88, 12),
+    rgb(234, 179, 8),
+    rgb(22, 163, 74),
+    rgb(13, 148, 136),
+    rgb(37, 99, 235),
+    rgb(79, 70, 229),
+    rgb(147, 51, 234),
+    rgb(219, 39, 119),
+];
+
+/// The control editing a property.
+#[derive(Clone)]
+enum PropertyEditor {
+    Bool(Checkbox),
+    Number(TextInput),
+    Text(TextInput),
+    Color {
+        swatch: Container,
+        input: TextInput,
+        palette: Container,
+        presets: Vec<Container>,
+    },
+    Enum(Dropdown),
+}
+
+#[derive(Clone)]
+pub struct PropertyGrid {
+    pub inner: Rc<RefCell<PropertyGridInner>>,
+}
+
+/// Lists named properties, each with a control for its type: a checkbox for bools, text inputs for numbers and
+/// text, a hex input with a palette for colors and a dropdown for enums.
+///
+/// A [`EventKind::PropertyChanged`] event is sent with the property's name and new value whenever the user changes
+/// it. Number and color inputs show a red border while their text isn't a valid value, and keep the last valid one.
+#[derive(Clone)]
+pub struct PropertyGridInner {
+    element_data: ElementData,
+    properties: Vec<Property>,
+    editors: Vec<PropertyEditor>,
+}
+
+impl Default for PropertyGrid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Element for PropertyGrid {}
+
+impl Drop for PropertyGridInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for PropertyGrid {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for PropertyGridInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for PropertyGridInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        draw_generic_container(self, renderer, resource_manager, text_context, scale_factor);
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        let Some(target) = target else {
+            return;
+        };
+
+        let change = match message {
+            EventKind::CheckboxToggled(toggled) => self
+                .editors
+                .iter()
+                .position(
+                    |editor| matches!(editor, PropertyEditor::Bool(checkbox) if is_target_within(&target, checkbox)),
+                )
+                .map(|index| (index, PropertyValue::Bool(toggled.status))),
+            EventKind::TextInputChanged(changed) => self.text_input_change(&target, &changed.value),
+            EventKind::DropdownItemSelected(selected) => self
+                .editors
+                .iter()
+                .position(
+                    |editor| matches!(editor, PropertyEditor::Enum(dropdown) if is_target_within(&target, dropdown)),
+                )
+                .map(|index| (index, PropertyValue::Enum(*selected))),
+            EventKind::PointerButtonUp(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                self.palette_click(&target)
+            }
+            _ => None,
+        };
+
+        // Changes from the editors reach `on_event` twice, so only the first call sends an event.
+        if let Some((index, value)) = change
+            && self.properties[index].value != value
+        {
+            self.properties[index].value = value.clone();
+            if let (PropertyValue::Color(color), PropertyEditor::Color { swatch, .. }) = (&value, &self.editors[index])
+            {
+                swatch.clone().background_color(*color);
+            }
+
+            let new_event = Event::new(event.target.clone());
+            queue_event(
+                new_event,
+                EventKind::PropertyChanged(PropertyChanged {
+                    name: self.properties[index].name.clone(),
+                    value,
+                }),
+            );
+        }
+    }
+
+    fn apply_clip(&mut self, clip_bounds: Option<Rectangle>) {
+        let overflow = self.style().get_overflow();
+        if overflow[0] == Overflow::Scroll || overflow[1] == Overflow::Scroll {
+            resolve_clip_for_scrollable(self, clip_bounds);
+        } else {
+            self.element_data.layout.apply_clip(clip_bounds);
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl PropertyGrid {
+    pub fn new() -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<PropertyGridInner>>| {
+            RefCell::new(PropertyGridInner {
+                element_data: ElementData::new(me.clone(), true),
+                properties: Vec::new(),
+                editors: Vec::new(),
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        inner_mut.set_display(Display::Flex);
+        inner_mut.set_flex_direction(FlexDirection::Column);
+        inner_mut.set_gap(px(6), px(6));
+
+        drop(inner_mut);
+        Self { inner }
+    }
+
+    /// Adds a property to the end of the grid.
+    pub fn property(self, property: Property) -> Self {
+        self.inner.borrow_mut().add_property(property);
+        self
+    }
+
+    pub fn get_value(&self, name: &str) -> Option<PropertyValue> {
+        let inner = self.inner.borrow();
+        inner
+            .properties
+            .iter()
+            .find(|property| property.name == name)
+            .map(|property| property.value.clone())
+    }
+
+    pub fn get_properties(&self) -> Vec<Property> {
+        self.inner.borrow().properties.clone()
+    }
+}
+
+impl PropertyGridInner {
+    pub fn add_property(&mut self, property: Property) {
+        let editor = property_editor(&property);
+        let editor_element = match &editor {
+            PropertyEditor::Bool(checkbox) => checkbox.as_element_rc(),
+            PropertyEditor::Number(input) | PropertyEditor::Text(input) => input.as_element_rc(),
+            PropertyEditor::Color { swatch, input, palette, .. } => Container::new()
+                .display(Display::Flex)
+                .flex_direction(FlexDirection::Column)
+                .gap(px(4), px(4))
+                .push(
+                    Container::new()
+                        .display(Display::Flex)
+                        .align_items(Some(AlignItems::Center))
+                        .gap(px(6), px(6))
+                        .push(swatch.clone())
+                        .push(input.clone().flex_grow(1.0)),
+                )
+                .push(palette.clone())
+                .as_element_rc(),
+            PropertyEditor::Enum(dropdown) => dropdown.as_element_rc(),
+        };
+
+        let label = Text::new(&property.name)
+            .selectable(false)
+            .color(LABEL_COLOR)
+            .width(pct(40))
+            .flex_shrink(0.0);
+        let editor_cell = Container::new().flex_grow(1.0).min_width(px(0));
+        editor_cell.inner.borrow_mut().push(editor_element);
+        let row = Container::new()
+            .display(Display::Flex)
+            .flex_direction(FlexDirection::Row)
+            .align_items(Some(AlignItems::Center))
+            .gap(px(8), px(8))
+            .push(label)
+            .push(editor_cell);
+
+        self.push(row.inner);
+        self.properties.push(property);
+        self.editors.push(editor);
+    }
+
+    /// Parses the text of the number, text or color input the event came from.
+    fn text_input_change(&self, target: &Rc<RefCell<dyn ElementInternals>>, text: &str) -> Option<(usize, PropertyValue)> {
+        for (index, editor) in self.editors.iter().enumerate() {
+            let (input, value) = match editor {
+                PropertyEditor::Number(input) => (input, self.properties[index].parse_number(text).map(PropertyValue::Number)),
+                PropertyEditor::Text(input) => (input, Some(PropertyValue::Text(text.to_string()))),
+                PropertyEditor::Color { input, .. } => (input, parse_hex_color(text).map(PropertyValue::Color)),
+                _ => continue,
+            };
+            if !is_target_within(target, input) {
+                continue;
+            }
+
+            input
+                .clone()
+                .border_color_all(if value.is_some() { BORDER_COLOR } else { INVALID_BORDER_COLOR });
+            return value.map(|value| (index, value));
+        }
+        None
+    }
+
+    /// Opens or closes a palette when its swatch is clicked, and picks the color of a clicked preset.
+    fn palette_click(&self, target: &Rc<RefCell<dyn ElementInternals>>) -> Option<(usize, PropertyValue)> {
+        for (index, editor) in self.editors.iter().enumerate() {
+            let PropertyEditor::Color {
+                swatch,
+                input,
+                palette,
+                presets,
+            } = editor
+            else {
+                continue;
+            };
+
+            if is_target_within(target, swatch) {
+                let is_open = palette.borrow().style().get_display() != Display::None;
+                palette
+                    .clone()
+                    .display(if is_open { Display::None } else { Display::Flex });
+                return None;
+            }
+            if let Some(preset) = presets.iter().position(|preset| is_target_within(target, preset)) {
+                let color = PALETTE[preset];
+                input.inner.borrow_mut().set_text(&format_hex_color(color));
+                input.clone().border_color_all(BORDER_COLOR);
+                palette.clone().display(Display::None);
+                return Some((index, PropertyValue::Color(color)));
+            }
+        }
+        None
+    }
+}
+
+fn property_editor(property: &Property) -> PropertyEditor {
+    match &property.value {
+        PropertyValue::Bool(value) => PropertyEditor::Bool(Checkbox::new("", *value)),
+        PropertyValue::Number(value) => PropertyEditor::Number(TextInput::new(&value.to_string())),
+        PropertyValue::Text(value) => PropertyEditor::Text(TextInput::new(value)),
+        PropertyValue::Color(color) => {
+            let presets: Vec<Container> = PALETTE
+                .iter()
+                .map(|color| swatch(*color).width(px(SWATCH_SIZE - 6.0)).height(px(SWATCH_SIZE - 6.0)))
+                .collect();
+            let palette = presets.iter().fold(
+                Container::new()
+                    .display(Display::None)
+                    .flex_direction(FlexDirection::Row)
+                    .gap(px(4), px(4)),
+                |palette, preset| palette.push(preset.clone()),
+            );
+            PropertyEditor::Color {
+                swatch: swatch(*color),
+                input: TextInput::new(&format_hex_color(*color)),
+                palette,
+                presets,
+            }
+        }
+        PropertyValue::Enum(selected) => {
+            let dropdown = property
+                .options
+                .iter()
+                .fold(Dropdown::new(), |dropdown, option| dropdown.push(Text::new(option)));
+            let dropdown = if property.options.is_empty() {
+                dropdown
+            } else {
+                dropdown.selected_item(*selected)
+            };
+            PropertyEditor::Enum(dropdown)
+        }
+    }
+}
+
+fn swatch(color: Color) -> Container {
+    Container::new()
+        .width(px(SWATCH_SIZE))
+        .height(px(SWATCH_SIZE))
+        .flex_shrink(0.0)
+        .border_width_all(px(1))
+        .border_color_all(BORDER_COLOR)
+        .border_radius_all(px(4))
+        .background_color(color)
+}
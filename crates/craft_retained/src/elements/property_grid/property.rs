@@ -0,0 +1,136 @@
+use peniko::Color;
+
+/// The value of a property in a [`PropertyGrid`](crate::elements::PropertyGrid).
+#[derive(Clone, Debug, PartialEq)]
+pub enum PropertyValue {
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    Color(Color),
+    /// The index of the selected option.
+    Enum(usize),
+}
+
+/// A named value edited in a [`PropertyGrid`](crate::elements::PropertyGrid).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Property {
+    pub name: String,
+    pub value: PropertyValue,
+    /// The names of the options of an enum property.
+    pub options: Vec<String>,
+    /// The smallest and largest value of a number property.
+    pub range: Option<(f64, f64)>,
+}
+
+impl Property {
+    fn new(name: &str, value: PropertyValue) -> Self {
+        Self {
+            name: name.to_string(),
+            value,
+            options: Vec::new(),
+            range: None,
+        }
+    }
+
+    /// A property edited with a checkbox.
+    pub fn bool(name: &str, value: bool) -> Self {
+        Self::new(name, PropertyValue::Bool(value))
+    }
+
+    /// A property edited with a text input accepting numbers.
+    pub fn number(name: &str, value: f64) -> Self {
+        Self::new(name, PropertyValue::Number(value))
+    }
+
+    pub fn text(name: &str, value: &str) -> Self {
+        Self::new(name, PropertyValue::Text(value.to_string()))
+    }
+
+    /// A property edited with a hex code or picked from a palette.
+    pub fn color(name: &str, value: Color) -> Self {
+        Self::new(name, PropertyValue::Color(value))
+    }
+
+    /// A property edited with a dropdown of the options.
+    pub fn enumeration(name: &str, options: &[&str], selected: usize) -> Self {
+        Self {
+            options: options.iter().map(|option| option.to_string()).collect(),
+            ..Self::new(name, PropertyValue::Enum(selected.min(options.len().saturating_sub(1))))
+        }
+    }
+
+    /// Set the smallest and largest value of a number property.
+    pub fn range(mut self, min: f64, max: f64) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    /// Parses the text of a number property, returning `None` if it isn't a number in range.
+    pub(crate) fn parse_number(&self, text: &str) -> Option<f64> {
+        let number = text.trim().parse::<f64>().ok().filter(|number| number.is_finite())?;
+        match self.range {
+            Some((min, max)) if number < min || number > max => None,
+            _ => Some(number),
+        }
+    }
+}
+
+/// Parses colors written as `#rgb`, `#rrggbb` or `#rrggbbaa`. The `#` is optional.
+pub(crate) fn parse_hex_color(text: &str) -> Option<Color> {
+    let hex = text.trim().trim_start_matches('#');
+    if !hex.is_ascii() {
+        return None;
+    }
+    let channel = |index: usize, width: usize| {
+        let digits = hex.get(index * width..(index + 1) * width)?;
+        let value = u8::from_str_radix(digits, 16).ok()?;
+        Some(if width == 1 { value * 17 } else { value })
+    };
+
+    match hex.len() {
+        3 => Some(Color::from_rgb8(channel(0, 1)?, channel(1, 1)?, channel(2, 1)?)),
+        6 => Some(Color::from_rgb8(channel(0, 2)?, channel(1, 2)?, channel(2, 2)?)),
+        8 => Some(Color::from_rgba8(channel(0, 2)?, channel(1, 2)?, channel(2, 2)?, channel(3, 2)?)),
+        _ => None,
+    }
+}
+
+/// Formats a color as `#rrggbb`, or `#rrggbbaa` if it is translucent.
+pub(crate) fn format_hex_color(color: Color) -> String {
+    let [red, green, blue, alpha] = color.to_rgba8().to_u8_array();
+    if alpha == u8::MAX {
+        format!("#{red:02x}{green:02x}{blue:02x}")
+    } else {
+        format!("#{red:02x}{green:02x}{blue:02x}{alpha:02x}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!(parse_hex_color("#f80"), Some(Color::from_rgb8(255, 136, 0)));
+        assert_eq!(parse_hex_color("2563eb"), Some(Color::from_rgb8(37, 99, 235)));
+        assert_eq!(parse_hex_color("#00000080"), Some(Color::from_rgba8(0, 0, 0, 128)));
+        assert_eq!(parse_hex_color("#12345"), None);
+        assert_eq!(parse_hex_color("#gggggg"), None);
+    }
+
+    #[test]
+    fn formats_hex_colors() {
+        assert_eq!(format_hex_color(Color::from_rgb8(37, 99, 235)), "#2563eb");
+        assert_eq!(format_hex_color(Color::from_rgba8(0, 0, 0, 128)), "#00000080");
+    }
+
+    #[test]
+    fn numbers_must_be_in_range() {
+        let property = Property::number("Opacity", 1.0).range(0.0, 1.0);
+
+        assert_eq!(property.parse_number(" 0.5 "), Some(0.5));
+        assert_eq!(property.parse_number("2"), None);
+        assert_eq!(property.parse_number("NaN"), None);
+        assert_eq!(property.parse_number("abc"), None);
+    }
+}
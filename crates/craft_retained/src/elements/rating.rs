@@ -0,0 +1,448 @@
+//! A star rating.
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::f64::consts::PI;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+#[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+use accesskit::{Action, Role, TreeUpdate};
+use craft_primitives::geometry::{Affine, BezPath, Point, Rectangle};
+use craft_renderer::Brush;
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use peniko::Color;
+use ui_events::keyboard::{Code, KeyState};
+use ui_events::pointer::PointerButton;
+
+use crate::app::queue_event;
+use crate::elements::element_data::ElementData;
+use crate::elements::traits::DeepClone;
+use crate::elements::{AsElement, Element, ElementInternals};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::Unit;
+use crate::text::text_context::TextContext;
+use crate::rgb;
+
+#[derive(Clone)]
+pub struct Rating {
+    pub inner: Rc<RefCell<RatingInner>>,
+}
+
+/// A row of stars showing a value between 0 and the number of stars.
+///
+/// Fractional values partially fill a star. While the pointer is over the element the value under the pointer is
+/// previewed, and clicking sends a [`EventKind::RatingChanged`] event.
+#[derive(Clone)]
+pub struct RatingInner {
+    element_data: ElementData,
+    value: f32,
+    hover_value: Option<f32>,
+    max: u32,
+    step: f32,
+    star_size: f32,
+    gap: f32,
+    read_only: bool,
+    filled_color: Color,
+    empty_color: Color,
+}
+
+impl Rating {
+    pub fn new(value: f32) -> Self {
+        Self {
+            inner: RatingInner::new(value),
+        }
+    }
+
+    pub fn value(self, value: f32) -> Self {
+        self.inner.borrow_mut().set_value(value);
+        self
+    }
+
+    pub fn get_value(&self) -> f32 {
+        self.inner.borrow().get_value()
+    }
+
+    /// Set the number of stars. Defaults to 5.
+    pub fn max(self, max: u32) -> Self {
+        self.inner.borrow_mut().set_max(max);
+        self
+    }
+
+    pub fn get_max(&self) -> u32 {
+        self.inner.borrow().get_max()
+    }
+
+    /// Set the smallest change of the value when picking it with the pointer or keyboard. Defaults to 1.
+    pub fn step(self, step: f32) -> Self {
+        self.inner.borrow_mut().set_step(step);
+        self
+    }
+
+    pub fn get_step(&self) -> f32 {
+        self.inner.borrow().get_step()
+    }
+
+    pub fn star_size(self, star_size: f32) -> Self {
+        self.inner.borrow_mut().set_star_size(star_size);
+        self
+    }
+
+    pub fn get_star_size(&self) -> f32 {
+        self.inner.borrow().get_star_size()
+    }
+
+    /// Only display the value, ignoring the pointer and keyboard.
+    pub fn read_only(self, read_only: bool) -> Self {
+        self.inner.borrow_mut().read_only = read_only;
+        self
+    }
+
+    pub fn filled_color(self, color: Color) -> Self {
+        self.inner.borrow_mut().filled_color = color;
+        self
+    }
+
+    pub fn empty_color(self, color: Color) -> Self {
+        self.inner.borrow_mut().empty_color = color;
+        self
+    }
+}
+
+impl RatingInner {
+    pub fn new(value: f32) -> Rc<RefCell<Self>> {
+        let me = Rc::new_cyclic(|me: &Weak<RefCell<Self>>| {
+            RefCell::new(Self {
+                element_data: ElementData::new(me.clone(), false),
+                value: 0.0,
+                hover_value: None,
+                max: 5,
+                step: 1.0,
+                star_size: 20.0,
+                gap: 4.0,
+                read_only: false,
+                filled_color: rgb(255, 180, 0),
+                empty_color: rgb(210, 210, 210),
+            })
+        });
+
+        me.borrow_mut().element_data.create_layout_node(None);
+        me.borrow_mut().set_value(value);
+        me.borrow_mut().update_size();
+
+        me
+    }
+
+    pub fn set_value(&mut self, value: f32) {
+        self.value = value.clamp(0.0, self.max as f32);
+    }
+
+    pub fn get_value(&self) -> f32 {
+        self.value
+    }
+
+    pub fn set_max(&mut self, max: u32) {
+        self.max = max;
+        self.value = self.value.min(max as f32);
+        self.update_size();
+    }
+
+    pub fn get_max(&self) -> u32 {
+        self.max
+    }
+
+    pub fn set_step(&mut self, step: f32) {
+        self.step = step;
+    }
+
+    pub fn get_step(&self) -> f32 {
+        self.step
+    }
+
+    pub fn set_star_size(&mut self, star_size: f32) {
+        self.star_size = star_size;
+        self.update_size();
+    }
+
+    pub fn get_star_size(&self) -> f32 {
+        self.star_size
+    }
+
+    fn update_size(&mut self) {
+        let width = self.max as f32 * self.star_size + self.max.saturating_sub(1) as f32 * self.gap;
+        self.set_width(Unit::Px(width));
+        self.set_height(Unit::Px(self.star_size));
+    }
+
+    fn star_rectangle(&self, index: u32) -> Rectangle {
+        let content_rectangle = self.element_data.layout.computed_box_transformed.content_rectangle();
+        Rectangle::new(
+            content_rectangle.x + index as f32 * (self.star_size + self.gap),
+            content_rectangle.y,
+            self.star_size,
+            self.star_size,
+        )
+    }
+
+    fn value_at(&self, point: &Point) -> f32 {
+        let content_rectangle = self.element_data.layout.computed_box_transformed.content_rectangle();
+        rating_at(
+            point.x as f32 - content_rectangle.x,
+            self.star_size,
+            self.gap,
+            self.max,
+            self.step,
+        )
+    }
+
+    /// Previews the value the pointer is over. Hovering doesn't otherwise redraw, so the layers the rating is drawn
+    /// into are invalidated.
+    fn set_hover_value(&mut self, hover_value: Option<f32>) {
+        if hover_value != self.hover_value {
+            self.hover_value = hover_value;
+            self.invalidate_layer_cache();
+        }
+    }
+
+    fn change_value(&mut self, value: f32, event: &Event) {
+        let value = value.clamp(0.0, self.max as f32);
+        if value == self.value {
+            return;
+        }
+        self.value = value;
+
+        let new_event = Event::new(event.target.clone());
+        queue_event(new_event, EventKind::RatingChanged(self.value));
+    }
+}
+
+/// Returns the value picked by a pointer `x` pixels from the start of the first star, rounded up to the step.
+fn rating_at(x: f32, star_size: f32, gap: f32, max: u32, step: f32) -> f32 {
+    let stride = star_size + gap;
+    let index = (x / stride).floor().max(0.0);
+    let within_star = ((x - index * stride) / star_size).clamp(0.0, 1.0);
+    let raw = index + within_star;
+    let stepped = if step > 0.0 { (raw / step).ceil() * step } else { raw };
+    stepped.clamp(step.min(max as f32), max as f32)
+}
+
+/// A five-pointed star fitting in the rectangle.
+fn star_path(rectangle: Rectangle) -> BezPath {
+    let center_x = rectangle.x as f64 + rectangle.width as f64 / 2.0;
+    let center_y = rectangle.y as f64 + rectangle.height as f64 / 2.0;
+    let outer_radius = rectangle.width.min(rectangle.height) as f64 / 2.0;
+    let inner_radius = outer_radius * 0.4;
+
+    let mut path = BezPath::new();
+    for i in 0..10 {
+        let radius = if i % 2 == 0 { outer_radius } else { inner_radius };
+        let angle = -PI / 2.0 + i as f64 * PI / 5.0;
+        let point = (center_x + radius * angle.cos(), center_y + radius * angle.sin());
+        if i == 0 {
+            path.move_to(point);
+        } else {
+            path.line_to(point);
+        }
+    }
+    path.close_path();
+    path
+}
+
+impl Element for Rating {}
+
+impl Drop for RatingInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for Rating {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for RatingInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for RatingInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        _text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        let node = self.element_data.layout.taffy_node_id.unwrap();
+        let layout = taffy_tree.get_layout(node);
+        let has_new_layout = taffy_tree.has_new_layout(node);
+
+        let dirty = has_new_layout
+            || transform != self.element_data.layout.get_transform()
+            || position != self.element_data.layout.position
+            || clip_bounds != self.element_data.layout.parent_clip;
+        self.element_data.layout.has_new_layout = has_new_layout;
+
+        if dirty {
+            self.resolve_box(position, transform, layout, z_index);
+
+            self.apply_borders(scale_factor);
+            self.apply_clip(clip_bounds);
+            self.element_data.layout.parent_clip = clip_bounds;
+        }
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, _resource_manager: Arc<ResourceManager>, scale_factor: f64, _text_context: &mut TextContext) {
+        if !self.is_visible() {
+            return;
+        }
+
+        self.add_hit_testable(renderer, true, scale_factor);
+        self.draw_borders(renderer, scale_factor);
+
+        let value = self.hover_value.unwrap_or(self.value);
+        for index in 0..self.max {
+            let star = self.star_rectangle(index).scale(scale_factor);
+            renderer.fill_bez_path(star_path(star), Brush::Color(self.empty_color));
+
+            let fill = (value - index as f32).clamp(0.0, 1.0);
+            if fill > 0.0 {
+                let mut filled_part = star;
+                filled_part.width *= fill;
+                renderer.push_layer(filled_part);
+                renderer.fill_bez_path(star_path(star), Brush::Color(self.filled_color));
+                renderer.pop_layer();
+            }
+        }
+    }
+
+    #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+    fn compute_accessibility_tree(&mut self, tree: &mut TreeUpdate, parent_index: Option<usize>, scale_factor: f64) {
+        let current_node_id = accesskit::NodeId(self.element_data().internal_id);
+        let mut current_node = accesskit::Node::new(Role::Slider);
+        current_node.set_numeric_value(self.value as f64);
+        current_node.set_min_numeric_value(0.0);
+        current_node.set_max_numeric_value(self.max as f64);
+        current_node.set_numeric_value_step(self.step as f64);
+        if !self.read_only {
+            current_node.add_action(Action::Increment);
+            current_node.add_action(Action::Decrement);
+        }
+
+        crate::elements::internal_helpers::add_generic_accesskit_data(
+            &mut self.element_data,
+            current_node,
+            current_node_id,
+            tree,
+            parent_index,
+            scale_factor,
+        )
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        _target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        if self.read_only {
+            return;
+        }
+
+        match message {
+            EventKind::PointerMovedEvent(pointer_update) => {
+                self.set_hover_value(Some(self.value_at(&pointer_update.current.logical_point())));
+            }
+            EventKind::PointerLeave() => {
+                self.set_hover_value(None);
+            }
+            EventKind::PointerButtonUp(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                self.focus();
+                let value = self.value_at(&pointer_button.state.logical_point());
+                self.change_value(value, event);
+            }
+            EventKind::KeyboardInputEvent(key) => {
+                if key.state != KeyState::Down || !self.is_focused() {
+                    return;
+                }
+
+                let new_value = match key.code {
+                    Code::ArrowUp | Code::ArrowRight => Some(self.value + self.step),
+                    Code::ArrowDown | Code::ArrowLeft => Some(self.value - self.step),
+                    Code::Home => Some(0.0),
+                    Code::End => Some(self.max as f32),
+                    _ => None,
+                };
+                if let Some(new_value) = new_value {
+                    self.change_value(new_value, event);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn handles_arrow_keys(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::rating_at;
+
+    #[test]
+    fn whole_steps_round_up_to_the_star_under_the_pointer() {
+        assert_eq!(rating_at(5.0, 20.0, 4.0, 5, 1.0), 1.0);
+        assert_eq!(rating_at(30.0, 20.0, 4.0, 5, 1.0), 2.0);
+    }
+
+    #[test]
+    fn half_steps_use_the_position_within_the_star() {
+        assert_eq!(rating_at(5.0, 20.0, 4.0, 5, 0.5), 0.5);
+        assert_eq!(rating_at(15.0, 20.0, 4.0, 5, 0.5), 1.0);
+        assert_eq!(rating_at(29.0, 20.0, 4.0, 5, 0.5), 1.5);
+    }
+
+    #[test]
+    fn clamps_to_the_range() {
+        assert_eq!(rating_at(-10.0, 20.0, 4.0, 5, 1.0), 1.0);
+        assert_eq!(rating_at(500.0, 20.0, 4.0, 5, 1.0), 5.0);
+    }
+}
@@ -4,7 +4,7 @@ use std::rc::Rc;
 
 use craft_primitives::geometry::{Point, Vec2};
 
-use ui_events::pointer::{PointerId, PointerType};
+use ui_events::pointer::PointerType;
 use ui_events::ScrollDelta;
 
 use crate::app::{queue_event, request_apply_layout};
@@ -250,11 +250,11 @@ pub(crate) fn handle_scroll_logic(element: &mut dyn ElementInternals, message: &
     }
 
     if result.set_pointer_capture {
-        element.set_pointer_capture(PointerId::new(1).unwrap())
+        element.capture_pointer()
     }
 
     if result.release_pointer_capture {
-        element.release_pointer_capture(PointerId::new(1).unwrap());
+        element.release_pointer();
     }
 }
 
@@ -320,9 +320,6 @@ pub(crate) fn handle_scroll_logic_advance(
                         pointer_button.state.logical_point().y,
                     ));
 
-                    // FIXME: Turn pointer capture on with the correct device id.
-                    //element.set_pointer_capture(PointerId::new(1).unwrap());
-
                     event.prevent_propagate();
                     event.prevent_defaults();
 
@@ -0,0 +1,438 @@
+//! An input split into fixed-format segments, like the day, month and year of a date or the digits of a one-time
+//! code.
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::ops::RangeInclusive;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use craft_primitives::geometry::{Affine, Point, Rectangle};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use peniko::Color;
+use ui_events::keyboard::{Key, KeyState, NamedKey};
+
+use crate::app::queue_event;
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, draw_generic_container, push_child_to_element};
+use crate::elements::traits::DeepClone;
+use crate::elements::{resolve_clip_for_scrollable, AsElement, Element, ElementInternals, InputPurpose, Text, TextInput};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::{AlignItems, Display, FlexDirection, Overflow, TextAlign};
+use crate::text::text_context::TextContext;
+use crate::{px, rgb};
+
+/// The border of a segment, and of one whose value is out of its range.
+const BORDER_COLOR: Color = Color::from_rgb8(199, 199, 206);
+const INVALID_BORDER_COLOR: Color = Color::from_rgb8(229, 57, 53);
+
+/// The characters a segment accepts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SegmentCharacters {
+    #[default]
+    Digits,
+    Letters,
+    Alphanumeric,
+}
+
+impl SegmentCharacters {
+    fn accepts(self, character: char) -> bool {
+        match self {
+            SegmentCharacters::Digits => character.is_ascii_digit(),
+            SegmentCharacters::Letters => character.is_alphabetic(),
+            SegmentCharacters::Alphanumeric => character.is_alphanumeric(),
+        }
+    }
+}
+
+/// One fixed-length part of a segmented input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Segment {
+    /// How many characters the segment holds. Typing the last one moves to the next segment.
+    pub length: usize,
+    pub characters: SegmentCharacters,
+    /// The values a segment of digits may have, like `1..=12` for a month.
+    pub range: Option<RangeInclusive<u32>>,
+    /// The text shown before the segment, like the `/` between the parts of a date.
+    pub prefix: String,
+}
+
+impl Segment {
+    pub fn digits(length: usize) -> Self {
+        Self {
+            length,
+            characters: SegmentCharacters::Digits,
+            range: None,
+            prefix: String::new(),
+        }
+    }
+
+    pub fn alphanumeric(length: usize) -> Self {
+        Self {
+            characters: SegmentCharacters::Alphanumeric,
+            ..Self::digits(length)
+        }
+    }
+
+    pub fn range(mut self, range: RangeInclusive<u32>) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Keeps the characters of `text` the segment accepts, up to its length.
+    pub fn accept(&self, text: &str) -> String {
+        text.chars()
+            .filter(|&character| self.characters.accepts(character))
+            .take(self.length)
+            .collect()
+    }
+
+    /// Whether nothing more can be typed into the segment, because it is full or because another digit would put it
+    /// out of its range, like a 4 as the first digit of a day.
+    pub fn is_finished(&self, text: &str) -> bool {
+        let length = text.chars().count();
+        if length >= self.length {
+            return true;
+        }
+        match (&self.range, text.parse::<u32>()) {
+            (Some(range), Ok(value)) if length > 0 => value.saturating_mul(10) > *range.end(),
+            _ => false,
+        }
+    }
+
+    /// Fills a finished segment of digits with leading zeros, like `4` to `04`.
+    pub fn pad(&self, text: &str) -> String {
+        if self.characters != SegmentCharacters::Digits || text.is_empty() {
+            return text.to_string();
+        }
+        format!("{text:0>width$}", width = self.length)
+    }
+
+    /// Whether `text` fills the segment and is in its range.
+    pub fn is_valid(&self, text: &str) -> bool {
+        if text.chars().count() != self.length {
+            return false;
+        }
+        match &self.range {
+            Some(range) => text.parse::<u32>().is_ok_and(|value| range.contains(&value)),
+            None => true,
+        }
+    }
+}
+
+/// The value of a segmented input, sent in [`EventKind::SegmentedInputChanged`] events.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SegmentedValue {
+    /// The text of each segment.
+    pub segments: Vec<String>,
+    /// The segments joined with their prefixes, like `24/12/2025`.
+    pub text: String,
+    /// Whether every segment is filled in and in its range.
+    pub complete: bool,
+}
+
+#[derive(Clone)]
+pub struct SegmentedInput {
+    pub inner: Rc<RefCell<SegmentedInputInner>>,
+}
+
+/// A row of text inputs, one per [`Segment`], that read as one value.
+///
+/// Typing moves to the next segment once a segment is finished, and Backspace in an empty segment moves back to the
+/// previous one. Characters a segment doesn't accept are dropped. A [`EventKind::SegmentedInputChanged`] event is
+/// sent whenever the value changes.
+#[derive(Clone)]
+pub struct SegmentedInputInner {
+    element_data: ElementData,
+    fields: Vec<SegmentField>,
+    /// The text of each segment before the key being handled, as the inputs handle keys before their parent.
+    texts: Vec<String>,
+}
+
+#[derive(Clone)]
+struct SegmentField {
+    segment: Segment,
+    input: TextInput,
+}
+
+impl Element for SegmentedInput {}
+
+impl Drop for SegmentedInputInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for SegmentedInput {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for SegmentedInputInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for SegmentedInputInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        draw_generic_container(self, renderer, resource_manager, text_context, scale_factor);
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        let EventKind::KeyboardInputEvent(key) = message else {
+            return;
+        };
+        if key.state != KeyState::Down {
+            return;
+        }
+        let Some(index) = target.and_then(|target| self.segment_index_of(&target)) else {
+            return;
+        };
+
+        let field = &self.fields[index];
+        let mut text = field.segment.accept(&field.input.get_text());
+        let previous = self.texts[index].clone();
+        match &key.key {
+            Key::Named(NamedKey::Backspace) if previous.is_empty() && index > 0 => {
+                self.fields[index - 1].input.inner.borrow_mut().focus();
+            }
+            Key::Character(_) if text != previous && field.segment.is_finished(&text) => {
+                text = field.segment.pad(&text);
+                if let Some(next) = self.fields.get(index + 1) {
+                    next.input.inner.borrow_mut().focus();
+                }
+            }
+            _ => {}
+        }
+
+        if text != self.fields[index].input.get_text() {
+            self.fields[index].input.inner.borrow_mut().set_text(&text);
+        }
+        if text != previous {
+            self.texts[index] = text;
+            self.show_validity(index);
+            let new_event = Event::new(event.target.clone());
+            queue_event(new_event, EventKind::SegmentedInputChanged(self.value()));
+        }
+    }
+
+    fn apply_clip(&mut self, clip_bounds: Option<Rectangle>) {
+        let overflow = self.style().get_overflow();
+        if overflow[0] == Overflow::Scroll || overflow[1] == Overflow::Scroll {
+            resolve_clip_for_scrollable(self, clip_bounds);
+        } else {
+            self.element_data.layout.apply_clip(clip_bounds);
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl SegmentedInput {
+    pub fn new(segments: Vec<Segment>) -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<SegmentedInputInner>>| {
+            RefCell::new(SegmentedInputInner {
+                element_data: ElementData::new(me.clone(), true),
+                fields: Vec::new(),
+                texts: vec![String::new(); segments.len()],
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        inner_mut.set_display(Display::Flex);
+        inner_mut.set_flex_direction(FlexDirection::Row);
+        inner_mut.set_align_items(Some(AlignItems::Center));
+        inner_mut.set_gap(px(4), px(4));
+
+        for segment in segments {
+            if !segment.prefix.is_empty() {
+                let prefix = Text::new(&segment.prefix).selectable(false).color(rgb(90, 90, 90));
+                inner_mut.push(prefix.as_element_rc());
+            }
+            let purpose = match segment.characters {
+                SegmentCharacters::Digits => InputPurpose::Number,
+                SegmentCharacters::Letters | SegmentCharacters::Alphanumeric => InputPurpose::Text,
+            };
+            let input = TextInput::new("")
+                .input_purpose(purpose)
+                .width(px(12.0 * segment.length as f32 + 16.0))
+                .text_align(TextAlign::Center);
+            inner_mut.push(input.as_element_rc());
+            inner_mut.fields.push(SegmentField { segment, input });
+        }
+
+        drop(inner_mut);
+        Self { inner }
+    }
+
+    /// A date written as day, month and year, like `24/12/2025`.
+    pub fn date() -> Self {
+        Self::new(vec![
+            Segment::digits(2).range(1..=31),
+            Segment::digits(2).range(1..=12).prefix("/"),
+            Segment::digits(4).range(1..=9999).prefix("/"),
+        ])
+    }
+
+    /// A North American phone number, like `(555) 123-4567`.
+    pub fn phone() -> Self {
+        Self::new(vec![
+            Segment::digits(3).prefix("("),
+            Segment::digits(3).prefix(")"),
+            Segment::digits(4).prefix("-"),
+        ])
+        .segment_purpose(InputPurpose::Telephone)
+    }
+
+    /// One box per digit of a one-time code.
+    pub fn one_time_code(digits: usize) -> Self {
+        Self::new(vec![Segment::digits(1); digits]).segment_purpose(InputPurpose::OneTimeCode)
+    }
+
+    /// Sets the text of each segment. Characters a segment doesn't accept are dropped.
+    pub fn segments(self, texts: Vec<String>) -> Self {
+        self.inner.borrow_mut().set_segments(texts);
+        self
+    }
+
+    pub fn get_value(&self) -> SegmentedValue {
+        self.inner.borrow().value()
+    }
+
+    fn segment_purpose(self, input_purpose: InputPurpose) -> Self {
+        for field in &self.inner.borrow().fields {
+            field.input.inner.borrow_mut().set_input_purpose(input_purpose);
+        }
+        self
+    }
+}
+
+impl SegmentedInputInner {
+    pub fn set_segments(&mut self, texts: Vec<String>) {
+        for (index, text) in texts.iter().enumerate().take(self.fields.len()) {
+            let text = self.fields[index].segment.accept(text);
+            self.fields[index].input.inner.borrow_mut().set_text(&text);
+            self.texts[index] = text;
+            self.show_validity(index);
+        }
+    }
+
+    pub fn value(&self) -> SegmentedValue {
+        let mut text = String::new();
+        for (field, segment_text) in self.fields.iter().zip(&self.texts) {
+            text.push_str(&field.segment.prefix);
+            text.push_str(segment_text);
+        }
+        SegmentedValue {
+            segments: self.texts.clone(),
+            text,
+            complete: self.fields.iter().zip(&self.texts).all(|(field, text)| field.segment.is_valid(text)),
+        }
+    }
+
+    /// Outlines a filled segment that is out of its range.
+    fn show_validity(&mut self, index: usize) {
+        let field = &self.fields[index];
+        let text = &self.texts[index];
+        let invalid = text.chars().count() == field.segment.length && !field.segment.is_valid(text);
+        let color = if invalid { INVALID_BORDER_COLOR } else { BORDER_COLOR };
+        field.input.inner.borrow_mut().set_border_color_all(color);
+    }
+
+    fn segment_index_of(&self, target: &Rc<RefCell<dyn ElementInternals>>) -> Option<usize> {
+        let target_id = target.try_borrow().ok()?.element_data().internal_id;
+        self.fields
+            .iter()
+            .position(|field| field.input.borrow().element_data().internal_id == target_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_accept_their_characters_up_to_their_length() {
+        let month = Segment::digits(2).range(1..=12);
+        assert_eq!(month.accept("1a23"), "12");
+        assert!(!month.is_finished("1"));
+        assert!(month.is_finished("2"));
+        assert_eq!(month.pad("2"), "02");
+        assert!(month.is_valid("02"));
+        assert!(!month.is_valid("13"));
+        assert!(!month.is_valid("1"));
+        assert_eq!(Segment::alphanumeric(3).accept("a-b c"), "abc");
+    }
+}
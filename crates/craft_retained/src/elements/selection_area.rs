@@ -0,0 +1,372 @@
+//! A container whose children can be selected by dragging a rectangle around them.
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::BTreeSet;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use craft_primitives::geometry::{Affine, Point, Rectangle};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use peniko::Color;
+use ui_events::pointer::PointerButton;
+
+use crate::app::queue_event;
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, draw_generic_container, push_child_to_element};
+use crate::elements::traits::DeepClone;
+use crate::elements::{resolve_clip_for_scrollable, scrollable, AsElement, Element, ElementInternals};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::spatial::visible_rectangle;
+use crate::style::Overflow;
+use crate::text::text_context::TextContext;
+
+/// How far, in logical pixels, the pointer must move before a press on empty space starts a marquee.
+const DRAG_THRESHOLD: f64 = 4.0;
+
+/// A container whose children can be selected with a marquee, like the icons of a file manager or the shapes of a
+/// diagram editor.
+///
+/// Dragging from empty space, where no child is, draws a selection rectangle. The children drawn under the
+/// rectangle are selected as it grows and shrinks, and a [`EventKind::SelectionAreaChanged`] event with the indices
+/// of the selected children is sent whenever the selection changes. Holding Shift when the drag starts adds to the
+/// selection instead of replacing it, and clicking empty space clears it.
+///
+/// The area doesn't draw the selected children differently, the app styles them from the event.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::rc::Rc;
+/// use craft_retained::elements::{Element, SelectionArea, Text, Window};
+/// use craft_retained::{CraftOptions, craft_main, pct};
+///
+/// fn main() {
+///     Window::new("Files").push(
+///         SelectionArea::new()
+///             .width(pct(100))
+///             .height(pct(100))
+///             .push(Text::new("notes.txt").selectable(false))
+///             .push(Text::new("photo.png").selectable(false))
+///             .on_selection_area_changed(Rc::new(|_event, selected| println!("{selected:?}"))),
+///     );
+///     craft_main(CraftOptions::basic("Files"));
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SelectionArea {
+    pub inner: Rc<RefCell<SelectionAreaInner>>,
+}
+
+#[derive(Clone)]
+pub struct SelectionAreaInner {
+    element_data: ElementData,
+    selected: BTreeSet<usize>,
+    marquee: Option<Marquee>,
+    fill_color: Color,
+    outline_color: Color,
+}
+
+/// A drag from empty space, which becomes a marquee once the pointer moved past the threshold.
+#[derive(Clone, Debug)]
+struct Marquee {
+    start: Point,
+    pointer: Point,
+    is_active: bool,
+    /// The selection kept when the drag started with Shift held.
+    kept: BTreeSet<usize>,
+}
+
+impl Element for SelectionArea {}
+
+impl Drop for SelectionAreaInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for SelectionArea {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for SelectionAreaInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for SelectionAreaInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        draw_generic_container(self, renderer, resource_manager, text_context, scale_factor);
+    }
+
+    fn draw_children(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        for child in self.children() {
+            let mut child = child.borrow_mut();
+            if child.element_data().is_mask || child.is_outside_viewport(renderer, scale_factor) {
+                continue;
+            }
+            child.draw(renderer, resource_manager.clone(), scale_factor, text_context);
+        }
+
+        // The marquee is drawn above the children, within the area.
+        let bounds = self.element_data.layout.computed_box_transformed.border_rectangle();
+        if let Some(marquee) = self.marquee.as_ref().filter(|marquee| marquee.is_active)
+            && let Some(rectangle) = marquee_rectangle(marquee.start, marquee.pointer).intersection(&bounds)
+        {
+            let rectangle = rectangle.scale(scale_factor);
+            renderer.draw_rect(rectangle, self.fill_color);
+            renderer.draw_rect_outline(rectangle, self.outline_color, scale_factor);
+        }
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        scrollable::handle_scroll_logic(self, message, event);
+
+        let changed = match message {
+            EventKind::PointerButtonDown(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                // Presses on a child are the child's, only presses on the area itself start a marquee.
+                if target.is_none_or(|target| !Rc::ptr_eq(&target, &self.to_rc())) {
+                    return;
+                }
+
+                let pointer_position = pointer_button.state.logical_point();
+                let kept = if pointer_button.state.modifiers.shift() {
+                    self.selected.clone()
+                } else {
+                    BTreeSet::new()
+                };
+                self.marquee = Some(Marquee {
+                    start: pointer_position,
+                    pointer: pointer_position,
+                    is_active: false,
+                    kept,
+                });
+                false
+            }
+            EventKind::PointerMovedEvent(pointer_update) => {
+                let Some(marquee) = self.marquee.as_mut() else {
+                    return;
+                };
+
+                marquee.pointer = pointer_update.current.logical_point();
+                if !marquee.is_active {
+                    if (marquee.pointer - marquee.start).hypot() < DRAG_THRESHOLD {
+                        return;
+                    }
+
+                    marquee.is_active = true;
+                    self.capture_pointer();
+                }
+                self.update_selection()
+            }
+            EventKind::PointerButtonUp(_) => {
+                let Some(marquee) = self.marquee.take() else {
+                    return;
+                };
+
+                if marquee.is_active {
+                    self.release_pointer();
+                    false
+                } else {
+                    // A click on empty space.
+                    let changed = self.selected != marquee.kept;
+                    self.selected = marquee.kept;
+                    changed
+                }
+            }
+            EventKind::LostPointerCapture() => {
+                self.marquee = None;
+                false
+            }
+            _ => false,
+        };
+
+        if changed {
+            let new_event = Event::new(self.to_rc());
+            queue_event(new_event, EventKind::SelectionAreaChanged(self.selected.clone()));
+        }
+    }
+
+    fn apply_clip(&mut self, clip_bounds: Option<Rectangle>) {
+        let overflow = self.style().get_overflow();
+        if overflow[0] == Overflow::Scroll || overflow[1] == Overflow::Scroll {
+            resolve_clip_for_scrollable(self, clip_bounds);
+        } else {
+            self.element_data.layout.apply_clip(clip_bounds);
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl SelectionAreaInner {
+    /// Selects the children under the marquee, with the kept selection. Returns true if the selection changed.
+    fn update_selection(&mut self) -> bool {
+        let Some(marquee) = &self.marquee else {
+            return false;
+        };
+
+        // The children as they were drawn, like the elements of the spatial queries.
+        let children: Vec<Option<Rectangle>> =
+            self.children().iter().map(|child| visible_rectangle(&*child.borrow())).collect();
+        let mut selected = children_in_rectangle(&children, marquee_rectangle(marquee.start, marquee.pointer));
+        selected.extend(marquee.kept.iter().copied());
+
+        if selected == self.selected {
+            return false;
+        }
+        self.selected = selected;
+        true
+    }
+}
+
+impl SelectionArea {
+    pub fn new() -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<SelectionAreaInner>>| {
+            RefCell::new(SelectionAreaInner {
+                element_data: ElementData::new(me.clone(), true),
+                selected: BTreeSet::new(),
+                marquee: None,
+                fill_color: Color::from_rgba8(59, 130, 246, 40),
+                outline_color: Color::from_rgba8(59, 130, 246, 200),
+            })
+        });
+
+        inner.borrow_mut().element_data.create_layout_node(None);
+
+        Self { inner }
+    }
+
+    /// The fill and outline of the selection rectangle.
+    pub fn marquee_color(self, fill_color: Color, outline_color: Color) -> Self {
+        {
+            let mut inner = self.inner.borrow_mut();
+            inner.fill_color = fill_color;
+            inner.outline_color = outline_color;
+        }
+        self
+    }
+
+    /// The indices of the selected children.
+    pub fn get_selected(&self) -> BTreeSet<usize> {
+        self.inner.borrow().selected.clone()
+    }
+
+    /// Selects the children at `selected`, without sending an event.
+    pub fn selected(self, selected: BTreeSet<usize>) -> Self {
+        self.inner.borrow_mut().selected = selected;
+        self
+    }
+}
+
+impl Default for SelectionArea {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The rectangle between two corners, in any order.
+fn marquee_rectangle(start: Point, end: Point) -> Rectangle {
+    Rectangle::new(
+        start.x.min(end.x) as f32,
+        start.y.min(end.y) as f32,
+        (start.x - end.x).abs() as f32,
+        (start.y - end.y).abs() as f32,
+    )
+}
+
+/// The indices of the drawn children that `rectangle` touches.
+fn children_in_rectangle(children: &[Option<Rectangle>], rectangle: Rectangle) -> BTreeSet<usize> {
+    children
+        .iter()
+        .enumerate()
+        .filter(|(_, child)| child.is_some_and(|child| child.intersects(&rectangle)))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_marquee_selects_the_children_it_touches_whichever_way_it_is_dragged() {
+        let children = [
+            Some(Rectangle::new(0.0, 0.0, 10.0, 10.0)),
+            Some(Rectangle::new(20.0, 0.0, 10.0, 10.0)),
+            // Clipped out of view.
+            None,
+            Some(Rectangle::new(0.0, 20.0, 10.0, 10.0)),
+        ];
+
+        let marquee = marquee_rectangle(Point::new(25.0, 5.0), Point::new(5.0, 25.0));
+        assert_eq!(marquee, Rectangle::new(5.0, 5.0, 20.0, 20.0));
+        assert_eq!(children_in_rectangle(&children, marquee), BTreeSet::from([0, 1, 3]));
+
+        let marquee = marquee_rectangle(Point::new(15.0, 15.0), Point::new(18.0, 18.0));
+        assert!(children_in_rectangle(&children, marquee).is_empty());
+    }
+}
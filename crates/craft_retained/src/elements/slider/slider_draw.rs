@@ -42,7 +42,11 @@ impl SliderInner {
             let track_radii = if let Some(br) = self.get_track_border_radius() {
                 border_radius_to_vec_radius(br)
             } else {
-                border_radius_to_vec_radius(self.style().get_border_radius())
+                let border_rectangle = self.get_computed_box_transformed().border_rectangle();
+                border_radius_to_vec_radius(
+                    self.style()
+                        .get_resolved_border_radius(border_rectangle.width, border_rectangle.height),
+                )
             };
 
             let css_rounded_rect = CssRoundedRect::new(
@@ -7,7 +7,6 @@ use craft_primitives::geometry::{Affine, Point, Rectangle};
 use peniko::Color;
 
 use ui_events::keyboard::{Code, KeyState};
-use ui_events::pointer::PointerId;
 use craft_renderer::renderer::Renderer;
 use craft_resource_manager::ResourceManager;
 use crate::app::queue_event;
@@ -416,8 +415,7 @@ impl ElementInternals for SliderInner {
             EventKind::PointerButtonUp(pointer_button_update) => {
                 self.focus();
                 self.dragging = false;
-                // FIXME: Turn pointer capture on with the correct device id.
-                self.release_pointer_capture(PointerId::new(1).unwrap());
+                self.release_pointer();
 
                 let value = self.compute_slider_value(&pointer_button_update.state.logical_point());
                 self.value = value;
@@ -427,8 +425,7 @@ impl ElementInternals for SliderInner {
             }
             EventKind::PointerButtonDown(pointer_button_update) => {
                 self.dragging = true;
-                // FIXME: Turn pointer capture on with the correct device id.
-                self.set_pointer_capture(PointerId::new(1).unwrap());
+                self.capture_pointer();
 
                 let value = self.compute_slider_value(&pointer_button_update.state.logical_point());
                 self.value = value;
@@ -480,6 +477,14 @@ impl ElementInternals for SliderInner {
         }
     }
 
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn handles_arrow_keys(&self) -> bool {
+        true
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -0,0 +1,499 @@
+//! A multi-step flow, such as an onboarding or installer wizard.
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use craft_primitives::geometry::{Affine, Point, Rectangle};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use peniko::Color;
+use ui_events::pointer::PointerButton;
+
+use crate::app::queue_event;
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, draw_generic_container, is_target_within, push_child_to_element};
+use crate::elements::traits::DeepClone;
+use crate::elements::{resolve_clip_for_scrollable, AsElement, Container, DynElement, Element, ElementInternals, Text};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::{AlignItems, Display, FlexDirection, FontWeight, JustifyContent, Overflow};
+use crate::text::text_context::TextContext;
+use crate::{px, rgb};
+
+/// Decides whether the stepper may move from the first step index to the second.
+///
+/// Called for both forward and backward moves, before the step changes.
+pub type StepperValidator = Rc<dyn Fn(usize, usize) -> bool>;
+
+#[derive(Clone)]
+pub struct Stepper {
+    pub inner: Rc<RefCell<StepperInner>>,
+}
+
+/// A multi-step flow with a step indicator and back/next buttons.
+///
+/// The content of every step stays in the tree and is only hidden while another step is shown,
+/// so the state of its elements is kept when going back and forth.
+#[derive(Clone)]
+pub struct StepperInner {
+    element_data: ElementData,
+    steps: Vec<Step>,
+    navigation: StepNavigation,
+    validator: Option<StepperValidator>,
+    pub indicator: Container,
+    pub content: Container,
+    pub nav: Container,
+    pub back_button: Container,
+    pub next_button: Container,
+    back_label: Text,
+    next_label: Text,
+    pub accent_color: Color,
+}
+
+#[derive(Clone)]
+struct Step {
+    content: DynElement,
+    marker: Container,
+    marker_label: Text,
+    title: Text,
+}
+
+const MARKER_SIZE: f32 = 28.0;
+const INACTIVE_COLOR: Color = rgb(220, 220, 220);
+const INACTIVE_TEXT_COLOR: Color = rgb(120, 120, 120);
+
+impl Default for Stepper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Element for Stepper {}
+
+impl Drop for StepperInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for Stepper {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for StepperInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for StepperInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        draw_generic_container(self, renderer, resource_manager, text_context, scale_factor);
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        let EventKind::PointerButtonUp(pointer_button) = message else {
+            return;
+        };
+        if pointer_button.button != Some(PointerButton::Primary) {
+            return;
+        }
+        let Some(target) = target else {
+            return;
+        };
+
+        let previous_step = self.navigation.current;
+        let was_finished = self.navigation.finished;
+        if is_target_within(&target, &self.next_button) {
+            self.next();
+        } else if is_target_within(&target, &self.back_button) {
+            self.back();
+        } else {
+            return;
+        }
+
+        if self.navigation.current != previous_step {
+            let new_event = Event::new(event.target.clone());
+            queue_event(new_event, EventKind::StepperStepChanged(self.navigation.current));
+        }
+        if self.navigation.finished && !was_finished {
+            let new_event = Event::new(event.target.clone());
+            queue_event(new_event, EventKind::StepperFinished());
+        }
+    }
+
+    fn apply_clip(&mut self, clip_bounds: Option<Rectangle>) {
+        let overflow = self.style().get_overflow();
+        if overflow[0] == Overflow::Scroll || overflow[1] == Overflow::Scroll {
+            resolve_clip_for_scrollable(self, clip_bounds);
+        } else {
+            self.element_data.layout.apply_clip(clip_bounds);
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl Stepper {
+    pub fn new() -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<StepperInner>>| {
+            RefCell::new(StepperInner {
+                element_data: ElementData::new(me.clone(), true),
+                steps: Vec::new(),
+                navigation: StepNavigation::default(),
+                validator: None,
+                indicator: Container::new()
+                    .display(Display::Flex)
+                    .flex_direction(FlexDirection::Row)
+                    .align_items(Some(AlignItems::Center))
+                    .gap(px(16), px(16)),
+                content: Container::new()
+                    .display(Display::Flex)
+                    .flex_direction(FlexDirection::Column)
+                    .flex_grow(1.0),
+                nav: Container::new()
+                    .display(Display::Flex)
+                    .flex_direction(FlexDirection::Row)
+                    .justify_content(Some(JustifyContent::SpaceBetween)),
+                back_button: nav_button(),
+                next_button: nav_button(),
+                back_label: Text::new("Back").selectable(false),
+                next_label: Text::new("Next").selectable(false),
+                accent_color: rgb(37, 99, 235),
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        inner_mut.set_display(Display::Flex);
+        inner_mut.set_flex_direction(FlexDirection::Column);
+        inner_mut.set_gap(px(16), px(16));
+
+        let back_label = inner_mut.back_label.clone();
+        inner_mut.back_button.clone().push(back_label);
+        let next_label = inner_mut.next_label.clone();
+        inner_mut.next_button.clone().push(next_label);
+        inner_mut
+            .nav
+            .clone()
+            .push(inner_mut.back_button.clone())
+            .push(inner_mut.next_button.clone());
+
+        let indicator = inner_mut.indicator.clone();
+        inner_mut.push(indicator.inner);
+        let content = inner_mut.content.clone();
+        inner_mut.push(content.inner);
+        let nav = inner_mut.nav.clone();
+        inner_mut.push(nav.inner);
+        inner_mut.update_steps();
+
+        drop(inner_mut);
+        Self { inner }
+    }
+
+    /// Appends a step with the given title to the flow.
+    pub fn step(self, title: &str, content: impl AsElement) -> Self {
+        self.inner.borrow_mut().add_step(title, DynElement::new(content.as_element_rc()));
+        self
+    }
+
+    /// Sets the hook deciding whether the user may leave the current step.
+    pub fn validate(self, validator: StepperValidator) -> Self {
+        self.inner.borrow_mut().validator = Some(validator);
+        self
+    }
+
+    /// Shows the step at `index` without running the validator.
+    pub fn current_step(self, index: usize) -> Self {
+        self.inner.borrow_mut().set_current_step(index);
+        self
+    }
+
+    pub fn accent_color(self, color: Color) -> Self {
+        self.inner.borrow_mut().accent_color = color;
+        self.inner.borrow_mut().update_steps();
+        self
+    }
+
+    pub fn get_current_step(&self) -> usize {
+        self.inner.borrow().navigation.current
+    }
+
+    /// Returns true once the user confirmed the last step.
+    pub fn is_finished(&self) -> bool {
+        self.inner.borrow().navigation.finished
+    }
+
+    /// Moves to the next step, or finishes the flow on the last step, if the validator allows it.
+    pub fn next(&self) {
+        self.inner.borrow_mut().next();
+    }
+
+    /// Moves to the previous step if the validator allows it.
+    pub fn back(&self) {
+        self.inner.borrow_mut().back();
+    }
+}
+
+impl StepperInner {
+    fn add_step(&mut self, title: &str, content: DynElement) {
+        let index = self.steps.len();
+        let marker_label = Text::new(&(index + 1).to_string()).selectable(false);
+        let marker = Container::new()
+            .display(Display::Flex)
+            .justify_content(Some(JustifyContent::Center))
+            .align_items(Some(AlignItems::Center))
+            .width(px(MARKER_SIZE))
+            .height(px(MARKER_SIZE))
+            .border_radius_all(px(MARKER_SIZE / 2.0))
+            .push(marker_label.clone());
+        let title = Text::new(title).selectable(false);
+        self.indicator.clone().push(
+            Container::new()
+                .display(Display::Flex)
+                .flex_direction(FlexDirection::Row)
+                .align_items(Some(AlignItems::Center))
+                .gap(px(8), px(8))
+                .push(marker.clone())
+                .push(title.clone()),
+        );
+        self.content.clone().push(content.clone());
+
+        self.steps.push(Step {
+            content,
+            marker,
+            marker_label,
+            title,
+        });
+        self.navigation.step_count = self.steps.len();
+        self.update_steps();
+    }
+
+    fn set_current_step(&mut self, index: usize) {
+        self.navigation.go_to(index);
+        self.update_steps();
+    }
+
+    fn next(&mut self) {
+        let target = self.navigation.next_target();
+        if self.is_allowed(target) {
+            self.navigation.next();
+            self.update_steps();
+        }
+    }
+
+    fn back(&mut self) {
+        let Some(target) = self.navigation.back_target() else {
+            return;
+        };
+        if self.is_allowed(target) {
+            self.navigation.back();
+            self.update_steps();
+        }
+    }
+
+    fn is_allowed(&self, target: usize) -> bool {
+        self.validator
+            .as_ref()
+            .is_none_or(|validator| validator(self.navigation.current, target))
+    }
+
+    fn update_steps(&mut self) {
+        let current = self.navigation.current;
+        for (index, step) in self.steps.iter().enumerate() {
+            let is_reached = index <= current || self.navigation.finished;
+            let (background, text_color) = if is_reached {
+                (self.accent_color, Color::WHITE)
+            } else {
+                (INACTIVE_COLOR, INACTIVE_TEXT_COLOR)
+            };
+            step.marker.clone().background_color(background);
+            step.marker_label.clone().color(text_color);
+            step.title
+                .clone()
+                .color(if index == current { rgb(0, 0, 0) } else { INACTIVE_TEXT_COLOR })
+                .font_weight(if index == current { FontWeight::SEMIBOLD } else { FontWeight::NORMAL });
+            step.content
+                .clone()
+                .display(if index == current { Display::Flex } else { Display::None });
+        }
+
+        self.back_label.clone().color(if current == 0 { INACTIVE_TEXT_COLOR } else { rgb(0, 0, 0) });
+        self.next_label
+            .clone()
+            .text(if self.navigation.is_last() { "Finish" } else { "Next" })
+            .color(Color::WHITE);
+        self.next_button.clone().background_color(self.accent_color);
+    }
+}
+
+fn nav_button() -> Container {
+    Container::new()
+        .display(Display::Flex)
+        .justify_content(Some(JustifyContent::Center))
+        .align_items(Some(AlignItems::Center))
+        .padding(px(6), px(16), px(6), px(16))
+        .border_radius_all(px(4))
+}
+
+/// The position of a stepper in its flow.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct StepNavigation {
+    current: usize,
+    step_count: usize,
+    finished: bool,
+}
+
+impl StepNavigation {
+    fn is_last(&self) -> bool {
+        self.current + 1 >= self.step_count
+    }
+
+    /// The step `next` moves to, which is the current step when it finishes the flow.
+    fn next_target(&self) -> usize {
+        if self.is_last() { self.current } else { self.current + 1 }
+    }
+
+    fn back_target(&self) -> Option<usize> {
+        self.current.checked_sub(1)
+    }
+
+    fn next(&mut self) {
+        if self.is_last() {
+            self.finished = self.step_count > 0;
+        } else {
+            self.current += 1;
+        }
+    }
+
+    fn back(&mut self) {
+        if let Some(target) = self.back_target() {
+            self.current = target;
+            self.finished = false;
+        }
+    }
+
+    fn go_to(&mut self, index: usize) {
+        self.current = index.min(self.step_count.saturating_sub(1));
+        self.finished = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn navigation(step_count: usize) -> StepNavigation {
+        StepNavigation {
+            step_count,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn next_finishes_on_the_last_step() {
+        let mut navigation = navigation(2);
+
+        navigation.next();
+        assert_eq!(navigation.current, 1);
+        assert!(!navigation.finished);
+
+        navigation.next();
+        assert_eq!(navigation.current, 1);
+        assert!(navigation.finished);
+    }
+
+    #[test]
+    fn back_stops_at_the_first_step() {
+        let mut navigation = navigation(3);
+        navigation.go_to(2);
+
+        navigation.back();
+        navigation.back();
+        navigation.back();
+
+        assert_eq!(navigation.current, 0);
+        assert_eq!(navigation.back_target(), None);
+    }
+
+    #[test]
+    fn go_to_clamps_to_the_last_step() {
+        let mut navigation = navigation(3);
+
+        navigation.go_to(10);
+
+        assert_eq!(navigation.current, 2);
+        assert!(navigation.is_last());
+    }
+
+    #[test]
+    fn empty_flow_never_finishes() {
+        let mut navigation = navigation(0);
+
+        navigation.next();
+
+        assert!(!navigation.finished);
+    }
+}
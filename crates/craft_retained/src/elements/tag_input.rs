@@ -0,0 +1,310 @@
+//! A text input that turns entries into removable tags.
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use craft_primitives::geometry::{Affine, Point, Rectangle};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use peniko::Color;
+use ui_events::keyboard::{Key, KeyState, NamedKey};
+use ui_events::pointer::PointerButton;
+
+use crate::app::queue_event;
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, draw_generic_container, insert_child_to_element, push_child_to_element};
+use crate::elements::traits::DeepClone;
+use crate::elements::{resolve_clip_for_scrollable, AsElement, Container, Element, ElementInternals, Text, TextInput};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::{AlignItems, Display, FlexDirection, FlexWrap, Overflow};
+use crate::text::text_context::TextContext;
+use crate::{px, rgb};
+
+#[derive(Clone)]
+pub struct TagInput {
+    pub inner: Rc<RefCell<TagInputInner>>,
+}
+
+/// A list of tags followed by a text input.
+///
+/// Typing Enter or a comma turns the typed text into a tag, and Backspace in an empty input removes the last tag.
+/// Tags wrap onto new lines when they don't fit. A [`EventKind::TagsChanged`] event is sent whenever the tags change.
+#[derive(Clone)]
+pub struct TagInputInner {
+    element_data: ElementData,
+    tags: Vec<String>,
+    chips: Vec<TagChip>,
+    pub input: TextInput,
+    /// Whether the input was empty before the key being handled, as the input handles keys before its parent.
+    input_was_empty: bool,
+    pub tag_background_color: Color,
+}
+
+#[derive(Clone)]
+struct TagChip {
+    chip: Container,
+    remove_button: Text,
+}
+
+impl Default for TagInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Element for TagInput {}
+
+impl Drop for TagInputInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for TagInput {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for TagInputInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for TagInputInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        draw_generic_container(self, renderer, resource_manager, text_context, scale_factor);
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        let changed = match message {
+            EventKind::TextInputChanged(changed) => {
+                self.input_was_empty = changed.value.is_empty();
+                false
+            }
+            EventKind::KeyboardInputEvent(key) if key.state == KeyState::Down => match &key.key {
+                Key::Named(NamedKey::Enter) => self.commit_input(),
+                Key::Character(character) if character == "," => self.commit_input(),
+                Key::Named(NamedKey::Backspace) if self.input_was_empty => self.remove_tag(self.tags.len().wrapping_sub(1)),
+                _ => false,
+            },
+            EventKind::PointerButtonUp(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
+                let removed = target.and_then(|target| self.chip_index_of_remove_button(&target));
+                match removed {
+                    Some(index) => self.remove_tag(index),
+                    None => {
+                        self.input.inner.borrow_mut().focus();
+                        false
+                    }
+                }
+            }
+            _ => false,
+        };
+
+        if changed {
+            let new_event = Event::new(event.target.clone());
+            queue_event(new_event, EventKind::TagsChanged(self.tags.clone()));
+        }
+    }
+
+    fn apply_clip(&mut self, clip_bounds: Option<Rectangle>) {
+        let overflow = self.style().get_overflow();
+        if overflow[0] == Overflow::Scroll || overflow[1] == Overflow::Scroll {
+            resolve_clip_for_scrollable(self, clip_bounds);
+        } else {
+            self.element_data.layout.apply_clip(clip_bounds);
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl TagInput {
+    pub fn new() -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<TagInputInner>>| {
+            RefCell::new(TagInputInner {
+                element_data: ElementData::new(me.clone(), true),
+                tags: Vec::new(),
+                chips: Vec::new(),
+                input: TextInput::new("").min_width(px(80)).flex_grow(1.0),
+                input_was_empty: true,
+                tag_background_color: rgb(225, 230, 240),
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        inner_mut.set_display(Display::Flex);
+        inner_mut.set_flex_direction(FlexDirection::Row);
+        inner_mut.set_wrap(FlexWrap::Wrap);
+        inner_mut.set_align_items(Some(AlignItems::Center));
+        inner_mut.set_gap(px(4), px(4));
+
+        let input = inner_mut.input.clone();
+        inner_mut.push(input.inner);
+
+        drop(inner_mut);
+        Self { inner }
+    }
+
+    pub fn tags(self, tags: Vec<String>) -> Self {
+        self.inner.borrow_mut().set_tags(tags);
+        self
+    }
+
+    pub fn get_tags(&self) -> Vec<String> {
+        self.inner.borrow().tags.clone()
+    }
+}
+
+impl TagInputInner {
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        while !self.tags.is_empty() {
+            self.remove_tag(self.tags.len() - 1);
+        }
+        for tag in tags {
+            self.add_tag(tag);
+        }
+    }
+
+    /// Adds a tag unless it is empty or already present, returning whether it was added.
+    fn add_tag(&mut self, tag: String) -> bool {
+        if tag.is_empty() || self.tags.contains(&tag) {
+            return false;
+        }
+
+        let remove_button = Text::new("×").selectable(false).color(rgb(90, 90, 90));
+        let chip = Container::new()
+            .display(Display::Flex)
+            .flex_direction(FlexDirection::Row)
+            .align_items(Some(AlignItems::Center))
+            .gap(px(4), px(4))
+            .padding(px(2), px(8), px(2), px(8))
+            .border_radius_all(px(4))
+            .background_color(self.tag_background_color)
+            .push(Text::new(&tag).selectable(false))
+            .push(remove_button.clone());
+
+        // Tags go before the input, which is always the last child.
+        let index = self.chips.len();
+        insert_child_to_element(self, chip.as_element_rc(), index);
+        self.chips.push(TagChip { chip, remove_button });
+        self.tags.push(tag);
+        true
+    }
+
+    fn remove_tag(&mut self, index: usize) -> bool {
+        if index >= self.tags.len() {
+            return false;
+        }
+        self.tags.remove(index);
+        let chip = self.chips.remove(index);
+        self.remove_child(chip.chip.as_element_rc()).unwrap();
+        true
+    }
+
+    /// Turns the text typed so far into tags and clears the input.
+    fn commit_input(&mut self) -> bool {
+        let text = self.input.get_text();
+        let mut changed = false;
+        for tag in split_tags(&text) {
+            changed |= self.add_tag(tag);
+        }
+        self.input.inner.borrow_mut().set_text("");
+        self.input_was_empty = true;
+        changed
+    }
+
+    fn chip_index_of_remove_button(&self, target: &Rc<RefCell<dyn ElementInternals>>) -> Option<usize> {
+        let target_id = target.try_borrow().ok()?.element_data().internal_id;
+        self.chips
+            .iter()
+            .position(|chip| chip.remove_button.borrow().element_data().internal_id == target_id)
+    }
+}
+
+/// Splits typed text on commas and line breaks into trimmed, non-empty tags.
+fn split_tags(text: &str) -> Vec<String> {
+    text.split([',', '\n'])
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_tags;
+
+    #[test]
+    fn splits_on_commas_and_line_breaks() {
+        assert_eq!(split_tags("rust, gui,\nui\n"), vec!["rust", "gui", "ui"]);
+    }
+
+    #[test]
+    fn skips_empty_entries() {
+        assert!(split_tags(" ,\n, ").is_empty());
+    }
+}
@@ -25,7 +25,7 @@ use taffy::{AvailableSpace, Size};
 
 use time::{Duration, Instant};
 
-use ui_events::pointer::{PointerButton, PointerId};
+use ui_events::pointer::PointerButton;
 
 #[cfg(target_arch = "wasm32")]
 use web_time as time;
@@ -33,6 +33,7 @@ use web_time as time;
 use winit::dpi;
 use craft_renderer::renderer::Renderer;
 use craft_resource_manager::ResourceManager;
+use crate::app::is_window_active;
 use crate::elements::element_data::ElementData;
 #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
 use crate::elements::element_id::create_unique_element_id;
@@ -208,13 +209,31 @@ impl ElementInternals for TextInner {
         let computed_box_transformed = self.get_computed_box_transformed();
         let content_rectangle = computed_box_transformed.content_rectangle();
 
+        self.maybe_start_effect_layer(_renderer, _scale_factor);
+        self.maybe_start_mask(_renderer, _scale_factor);
         self.draw_borders(_renderer, _scale_factor);
 
         /*if self.element_data.layout_item.has_new_layout {
             renderer.draw_rect_outline(self.element_data.layout_item.computed_box_transformed.padding_rectangle(), rgba(255, 0, 0, 100), 1.0);
         }*/
 
-        _renderer.draw_text(self.me.clone(), content_rectangle.scale(_scale_factor), None, false);
+        let text_contrast = self.style().get_text_contrast();
+        let (selection_color, selection_text_color) = self.style().get_selection_colors(is_window_active());
+        if let Some(text_render) = self.state.text_render.as_mut() {
+            text_render.contrast = text_contrast;
+            text_render.set_selection_colors(selection_color, selection_text_color);
+        }
+
+        let mut text_rectangle = content_rectangle.scale(_scale_factor);
+        if self.style().get_pixel_snapping() {
+            // Keep the text origin, and therefore the baselines, on physical pixels.
+            text_rectangle.x = text_rectangle.x.round();
+            text_rectangle.y = text_rectangle.y.round();
+        }
+
+        _renderer.draw_text(self.me.clone(), text_rectangle, None, false);
+        self.maybe_end_mask(_renderer, _resource_manager, _scale_factor, _text_context);
+        self.maybe_end_effect_layer(_renderer, _scale_factor);
     }
 
     #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
@@ -316,7 +335,7 @@ impl ElementInternals for TextInner {
                         _ => state.move_to_point(cursor_pos),
                     }
                     if click_count == 1 {
-                        self.set_pointer_capture(PointerId::new(1).unwrap());
+                        self.capture_pointer();
                     }
                     event.prevent_defaults();
                 }
@@ -324,7 +343,7 @@ impl ElementInternals for TextInner {
                     state.update_text_selection(self.element_data.style.get_selection_color());
                     state.pointer_down = false;
                     state.cursor_reset();
-                    self.release_pointer_capture(PointerId::new(1).unwrap());
+                    self.release_pointer();
                     event.prevent_defaults();
                 }
                 EventKind::PointerMovedEvent(pointer_moved) => {
@@ -354,6 +373,10 @@ impl ElementInternals for TextInner {
         self.state.scale_factor = scale_factor;
     }
 
+    fn text_baseline(&self) -> Option<f32> {
+        self.state.first_baseline()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -500,6 +523,12 @@ impl TextState {
         self.layout(known_dimensions, available_space)
     }
 
+    /// The baseline of the first line from the top of the text, in logical pixels.
+    pub(crate) fn first_baseline(&self) -> Option<f32> {
+        let line = self.layout.as_ref()?.lines().next()?;
+        Some(line.metrics().baseline / self.scale_factor as f32)
+    }
+
     pub fn layout(&mut self, known_dimensions: Size<Option<f32>>, available_space: Size<AvailableSpace>) -> Size<f32> {
         let key = TextHashKey::new(known_dimensions, available_space);
 
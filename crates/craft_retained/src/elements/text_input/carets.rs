@@ -0,0 +1,179 @@
+//! The carets of a text input with multiple carets, see [`TextInput::multiple_carets`].
+//!
+//! Carets are byte indices into the text, so they stay valid while the layout changes. The main caret is the
+//! selection of the editor, the others are kept next to it and edited one at a time.
+//!
+//! [`TextInput::multiple_carets`]: super::TextInput::multiple_carets
+
+use std::ops::Range;
+
+/// A caret and the text it selects, as byte indices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Caret {
+    pub(crate) anchor: usize,
+    pub(crate) focus: usize,
+}
+
+impl Caret {
+    pub(crate) fn new(anchor: usize, focus: usize) -> Self {
+        Self { anchor, focus }
+    }
+
+    pub(crate) fn start(&self) -> usize {
+        self.anchor.min(self.focus)
+    }
+
+    pub(crate) fn end(&self) -> usize {
+        self.anchor.max(self.focus)
+    }
+
+    pub(crate) fn range(&self) -> Range<usize> {
+        self.start()..self.end()
+    }
+
+    pub(crate) fn is_collapsed(&self) -> bool {
+        self.anchor == self.focus
+    }
+
+    /// The caret moved by `delta` bytes, for an edit before it.
+    pub(crate) fn shifted(self, delta: isize) -> Self {
+        Self::new(
+            self.anchor.saturating_add_signed(delta),
+            self.focus.saturating_add_signed(delta),
+        )
+    }
+}
+
+/// Sorts the carets and merges the ones that overlap, or that are at the same place. Returns the carets and the index
+/// of the one `main` became part of.
+pub(crate) fn merge_carets(carets: Vec<Caret>, main: usize) -> (Vec<Caret>, usize) {
+    let mut indexed: Vec<(usize, Caret)> = carets.into_iter().enumerate().collect();
+    indexed.sort_by_key(|(_, caret)| (caret.start(), caret.end()));
+
+    let mut merged: Vec<Caret> = Vec::with_capacity(indexed.len());
+    let mut merged_main = 0;
+    for (index, caret) in indexed {
+        let overlaps = merged.last().is_some_and(|last: &Caret| {
+            caret.start() < last.end() || (caret.start() == last.end() && (caret.is_collapsed() || last.is_collapsed()))
+        });
+        if overlaps {
+            let last = merged.last_mut().unwrap();
+            let (start, end) = (last.start(), last.end().max(caret.end()));
+            *last = if last.anchor <= last.focus {
+                Caret::new(start, end)
+            } else {
+                Caret::new(end, start)
+            };
+        } else {
+            merged.push(caret);
+        }
+        if index == main {
+            merged_main = merged.len() - 1;
+        }
+    }
+    (merged, merged_main)
+}
+
+/// The word around a byte index, of letters, digits and underscores, or an empty range if there is none.
+pub(crate) fn word_at(text: &str, index: usize) -> Range<usize> {
+    let is_word = |character: char| character.is_alphanumeric() || character == '_';
+    let start = text[..index]
+        .char_indices()
+        .rev()
+        .take_while(|(_, character)| is_word(*character))
+        .last()
+        .map_or(index, |(start, _)| start);
+    let end = text[index..]
+        .char_indices()
+        .find(|(_, character)| !is_word(*character))
+        .map_or(text.len(), |(end, _)| index + end);
+    start..end
+}
+
+/// The first occurrence of `needle` after `after` that none of `carets` selects already, wrapping around to the start
+/// of the text.
+pub(crate) fn next_occurrence(text: &str, needle: &str, after: usize, carets: &[Caret]) -> Option<Range<usize>> {
+    if needle.is_empty() {
+        return None;
+    }
+    let is_free = |range: &Range<usize>| {
+        !carets
+            .iter()
+            .any(|caret| caret.start() < range.end && range.start < caret.end())
+    };
+    let occurrences = text.match_indices(needle).map(|(start, _)| start..start + needle.len());
+    let (after, before): (Vec<_>, Vec<_>) = occurrences.partition(|range| range.start >= after);
+    after.into_iter().chain(before).find(is_free)
+}
+
+/// The carets of a block selection from `anchor` to `focus`: one per line between them, from the column of `anchor` to
+/// the column of `focus`, in characters, cut short at the end of the line. Returns the carets from the top down, and
+/// the index of the one on the line of `focus`.
+pub(crate) fn block_carets(text: &str, anchor: usize, focus: usize) -> (Vec<Caret>, usize) {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+        .collect();
+    let line_of = |index: usize| line_starts.partition_point(|start| *start <= index) - 1;
+    let column_of = |index: usize| text[line_starts[line_of(index)]..index].chars().count();
+
+    let (anchor_line, focus_line) = (line_of(anchor), line_of(focus));
+    let (anchor_column, focus_column) = (column_of(anchor), column_of(focus));
+
+    let carets = (anchor_line.min(focus_line)..=anchor_line.max(focus_line))
+        .map(|line| {
+            let start = line_starts[line];
+            let end = line_starts.get(line + 1).map_or(text.len(), |next| next - 1);
+            let line_text = &text[start..end];
+            let at_column = |column: usize| {
+                line_text
+                    .char_indices()
+                    .nth(column)
+                    .map_or(end, |(offset, _)| start + offset)
+            };
+            Caret::new(at_column(anchor_column), at_column(focus_column))
+        })
+        .collect();
+    let main = focus_line - anchor_line.min(focus_line);
+    (carets, main)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_carets_merge_and_keep_the_main_one() {
+        let carets = vec![
+            Caret::new(10, 10),
+            Caret::new(2, 6),
+            Caret::new(4, 8),
+            Caret::new(10, 12),
+        ];
+        let (merged, main) = merge_carets(carets, 2);
+        assert_eq!(merged, vec![Caret::new(2, 8), Caret::new(10, 12)]);
+        assert_eq!(main, 0);
+    }
+
+    #[test]
+    fn the_next_occurrence_wraps_around_and_skips_selected_ones() {
+        let text = "let a = b; let c = a; let";
+        let first = Caret::new(0, 3);
+        assert_eq!(next_occurrence(text, "let", 3, &[first]), Some(11..14));
+        let carets = [first, Caret::new(11, 14), Caret::new(22, 25)];
+        assert_eq!(next_occurrence(text, "let", 25, &carets), None);
+        assert_eq!(word_at(text, 16), 15..16);
+        assert_eq!(word_at(text, 7), 7..7);
+    }
+
+    #[test]
+    fn block_selections_have_a_caret_per_line() {
+        let text = "abcdef\nab\nabcdef";
+        // From column 1 of the first line to column 4 of the last one.
+        let (carets, main) = block_carets(text, 1, 14);
+        assert_eq!(carets, vec![Caret::new(1, 4), Caret::new(8, 9), Caret::new(11, 14)]);
+        assert_eq!(main, 2);
+        // Upwards, from the last line.
+        let (_, main) = block_carets(text, 14, 1);
+        assert_eq!(main, 0);
+    }
+}
@@ -0,0 +1,371 @@
+use std::rc::Rc;
+
+use ui_events::keyboard::{Key, KeyboardEvent, Modifiers, NamedKey};
+
+thread_local! {
+    static PLATFORM_KEY_BINDINGS: Rc<KeyBindings> = Rc::new(KeyBindings::platform());
+}
+
+/// Where the cursor of a text input moves to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextMotion {
+    Left,
+    Right,
+    Up,
+    Down,
+    WordLeft,
+    WordRight,
+    /// The start of the visual line, which may be a wrapped part of a paragraph.
+    LineStart,
+    LineEnd,
+    /// The start of the paragraph, or of the previous one if the cursor is at the start already.
+    ParagraphStart,
+    /// The end of the paragraph, or of the next one if the cursor is at the end already.
+    ParagraphEnd,
+    TextStart,
+    TextEnd,
+}
+
+/// What a key does in a text input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextEditAction {
+    Move(TextMotion),
+    /// Moves the focus of the selection, keeping its anchor.
+    Select(TextMotion),
+    /// Deletes the selection, or the text between the cursor and where the motion would move it.
+    Delete(TextMotion),
+    /// Like [`TextEditAction::Delete`], but cuts the text to the clipboard, like the kill commands of Emacs.
+    Kill(TextMotion),
+    SelectAll,
+    CollapseSelection,
+    /// Selects the word at the cursor, or adds a caret selecting the next occurrence of the selected text. Only does
+    /// something in inputs with [multiple carets](super::TextInput::multiple_carets).
+    SelectNextOccurrence,
+    Undo,
+    Redo,
+    Copy,
+    Cut,
+    Paste,
+    InsertNewline,
+}
+
+/// A key, and the modifiers held with it. Other modifiers, like caps lock, are ignored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyChord {
+    pub key: Key,
+    pub modifiers: Modifiers,
+}
+
+impl KeyChord {
+    pub fn new(key: Key) -> Self {
+        Self {
+            key,
+            modifiers: Modifiers::empty(),
+        }
+    }
+
+    pub fn named(key: NamedKey) -> Self {
+        Self::new(Key::Named(key))
+    }
+
+    /// A character key, which matches regardless of case.
+    pub fn character(character: &str) -> Self {
+        Self::new(Key::Character(character.to_lowercase()))
+    }
+
+    pub fn ctrl(mut self) -> Self {
+        self.modifiers |= Modifiers::CONTROL;
+        self
+    }
+
+    pub fn alt(mut self) -> Self {
+        self.modifiers |= Modifiers::ALT;
+        self
+    }
+
+    pub fn shift(mut self) -> Self {
+        self.modifiers |= Modifiers::SHIFT;
+        self
+    }
+
+    /// Command on macOS, the Windows key on Windows.
+    pub fn meta(mut self) -> Self {
+        self.modifiers |= Modifiers::META;
+        self
+    }
+
+    pub fn matches(&self, keyboard_event: &KeyboardEvent) -> bool {
+        let chord_modifiers = Modifiers::CONTROL | Modifiers::ALT | Modifiers::SHIFT | Modifiers::META;
+        if keyboard_event.modifiers & chord_modifiers != self.modifiers {
+            return false;
+        }
+        match (&self.key, &keyboard_event.key) {
+            (Key::Character(key), Key::Character(pressed)) => *key == pressed.to_lowercase(),
+            (key, pressed) => key == pressed,
+        }
+    }
+}
+
+/// The keys that edit the text of a [`TextInput`](crate::elements::TextInput), like the keys that move the cursor by
+/// word or delete to the end of the line.
+///
+/// Starts from a preset, and bindings can be added or replaced with [`KeyBindings::bind`]:
+///
+/// ```ignore
+/// let bindings = KeyBindings::readline().bind(KeyChord::character("l").ctrl(), TextEditAction::SelectAll);
+/// TextInput::new("").key_bindings(bindings);
+/// ```
+///
+/// Characters that aren't bound are typed into the input.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyBindings {
+    bindings: Vec<(KeyChord, TextEditAction)>,
+}
+
+impl KeyBindings {
+    /// No bindings at all, for building a table from scratch.
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// The preset of the platform the app runs on.
+    pub fn platform() -> Self {
+        if cfg!(target_os = "macos") {
+            Self::mac()
+        } else if cfg!(target_os = "windows") {
+            Self::windows()
+        } else {
+            Self::linux()
+        }
+    }
+
+    /// The keys of text fields on macOS: Command for actions and line ends, Option for words, and the Emacs-style
+    /// Control keys of Cocoa.
+    pub fn mac() -> Self {
+        use TextEditAction::*;
+        use TextMotion::*;
+
+        let mut bindings = Self::empty()
+            .common()
+            .actions(|chord| chord.meta())
+            .navigate(KeyChord::named(NamedKey::ArrowLeft).alt(), WordLeft)
+            .navigate(KeyChord::named(NamedKey::ArrowRight).alt(), WordRight)
+            .navigate(KeyChord::named(NamedKey::ArrowLeft).meta(), LineStart)
+            .navigate(KeyChord::named(NamedKey::ArrowRight).meta(), LineEnd)
+            .navigate(KeyChord::named(NamedKey::ArrowUp).alt(), ParagraphStart)
+            .navigate(KeyChord::named(NamedKey::ArrowDown).alt(), ParagraphEnd)
+            .navigate(KeyChord::named(NamedKey::ArrowUp).meta(), TextStart)
+            .navigate(KeyChord::named(NamedKey::ArrowDown).meta(), TextEnd)
+            .navigate(KeyChord::named(NamedKey::Home).meta(), TextStart)
+            .navigate(KeyChord::named(NamedKey::End).meta(), TextEnd)
+            .edit(KeyChord::named(NamedKey::Backspace).alt(), Delete(WordLeft))
+            .edit(KeyChord::named(NamedKey::Delete).alt(), Delete(WordRight))
+            .edit(KeyChord::named(NamedKey::Backspace).meta(), Delete(LineStart));
+        for (character, action) in [
+            ("a", Move(LineStart)),
+            ("e", Move(LineEnd)),
+            ("b", Move(Left)),
+            ("f", Move(Right)),
+            ("p", Move(Up)),
+            ("n", Move(Down)),
+            ("d", Delete(Right)),
+            ("h", Delete(Left)),
+            ("k", Kill(LineEnd)),
+        ] {
+            bindings = bindings.bind(KeyChord::character(character).ctrl(), action);
+        }
+        bindings
+    }
+
+    /// The keys of text fields on Windows: Control for actions and words.
+    pub fn windows() -> Self {
+        Self::linux().bind(KeyChord::character("y").ctrl(), TextEditAction::Redo)
+    }
+
+    /// The keys of GTK and Qt text fields: Control for actions and words.
+    pub fn linux() -> Self {
+        use TextEditAction::*;
+        use TextMotion::*;
+
+        Self::empty()
+            .common()
+            .actions(|chord| chord.ctrl())
+            .navigate(KeyChord::named(NamedKey::ArrowLeft).ctrl(), WordLeft)
+            .navigate(KeyChord::named(NamedKey::ArrowRight).ctrl(), WordRight)
+            .navigate(KeyChord::named(NamedKey::ArrowUp).ctrl(), ParagraphStart)
+            .navigate(KeyChord::named(NamedKey::ArrowDown).ctrl(), ParagraphEnd)
+            .navigate(KeyChord::named(NamedKey::Home).ctrl(), TextStart)
+            .navigate(KeyChord::named(NamedKey::End).ctrl(), TextEnd)
+            .edit(KeyChord::named(NamedKey::Backspace).ctrl(), Delete(WordLeft))
+            .edit(KeyChord::named(NamedKey::Delete).ctrl(), Delete(WordRight))
+    }
+
+    /// The platform preset with the editing keys of GNU Readline, as in most shells. Killed text is cut to the
+    /// clipboard, and `Ctrl+Y` pastes it back.
+    pub fn readline() -> Self {
+        use TextEditAction::*;
+        use TextMotion::*;
+
+        let mut bindings = Self::platform();
+        for (chord, action) in [
+            (KeyChord::character("a").ctrl(), Move(LineStart)),
+            (KeyChord::character("e").ctrl(), Move(LineEnd)),
+            (KeyChord::character("b").ctrl(), Move(Left)),
+            (KeyChord::character("f").ctrl(), Move(Right)),
+            (KeyChord::character("b").alt(), Move(WordLeft)),
+            (KeyChord::character("f").alt(), Move(WordRight)),
+            (KeyChord::character("d").ctrl(), Delete(Right)),
+            (KeyChord::character("h").ctrl(), Delete(Left)),
+            (KeyChord::character("k").ctrl(), Kill(LineEnd)),
+            (KeyChord::character("u").ctrl(), Kill(LineStart)),
+            (KeyChord::character("w").ctrl(), Kill(WordLeft)),
+            (KeyChord::character("d").alt(), Kill(WordRight)),
+            (KeyChord::named(NamedKey::Backspace).alt(), Kill(WordLeft)),
+            (KeyChord::character("y").ctrl(), Paste),
+        ] {
+            bindings = bindings.bind(chord, action);
+        }
+        bindings
+    }
+
+    /// The Readline preset with the line, undo and buffer keys of Emacs. `Ctrl+W` cuts the selection, as in Emacs.
+    pub fn emacs() -> Self {
+        use TextEditAction::*;
+        use TextMotion::*;
+
+        let mut bindings = Self::readline();
+        for (chord, action) in [
+            (KeyChord::character("p").ctrl(), Move(Up)),
+            (KeyChord::character("n").ctrl(), Move(Down)),
+            (KeyChord::character("<").alt().shift(), Move(TextStart)),
+            (KeyChord::character(">").alt().shift(), Move(TextEnd)),
+            (KeyChord::character("w").ctrl(), Cut),
+            (KeyChord::character("w").alt(), Copy),
+            (KeyChord::character("/").ctrl(), Undo),
+            (KeyChord::character("g").ctrl(), CollapseSelection),
+        ] {
+            bindings = bindings.bind(chord, action);
+        }
+        bindings
+    }
+
+    /// Binds a chord to an action, replacing what the chord was bound to.
+    pub fn bind(mut self, chord: KeyChord, action: TextEditAction) -> Self {
+        self.bindings.retain(|(bound, _)| *bound != chord);
+        self.bindings.push((chord, action));
+        self
+    }
+
+    /// Removes the binding of a chord. An unbound character is typed into the input.
+    pub fn unbind(mut self, chord: &KeyChord) -> Self {
+        self.bindings.retain(|(bound, _)| bound != chord);
+        self
+    }
+
+    /// Returns the action the key of `keyboard_event` is bound to.
+    pub fn action_for(&self, keyboard_event: &KeyboardEvent) -> Option<TextEditAction> {
+        self.bindings
+            .iter()
+            .find(|(chord, _)| chord.matches(keyboard_event))
+            .map(|(_, action)| *action)
+    }
+
+    /// The bindings every platform shares: the arrow keys, Home and End, and the keys that delete and break lines.
+    fn common(self) -> Self {
+        use TextEditAction::*;
+        use TextMotion::*;
+
+        self.navigate(KeyChord::named(NamedKey::ArrowLeft), Left)
+            .navigate(KeyChord::named(NamedKey::ArrowRight), Right)
+            .navigate(KeyChord::named(NamedKey::ArrowUp), Up)
+            .navigate(KeyChord::named(NamedKey::ArrowDown), Down)
+            .navigate(KeyChord::named(NamedKey::Home), LineStart)
+            .navigate(KeyChord::named(NamedKey::End), LineEnd)
+            .edit(KeyChord::named(NamedKey::Backspace), Delete(Left))
+            .edit(KeyChord::named(NamedKey::Delete), Delete(Right))
+            .edit(KeyChord::named(NamedKey::Enter), InsertNewline)
+    }
+
+    /// The undo, clipboard and select all shortcuts, with the modifier `modifier` adds.
+    fn actions(self, modifier: impl Fn(KeyChord) -> KeyChord) -> Self {
+        use TextEditAction::*;
+
+        self.bind(modifier(KeyChord::character("z")), Undo)
+            .bind(modifier(KeyChord::character("z")).shift(), Redo)
+            .bind(modifier(KeyChord::character("c")), Copy)
+            .bind(modifier(KeyChord::character("x")), Cut)
+            .bind(modifier(KeyChord::character("v")), Paste)
+            .bind(modifier(KeyChord::character("a")), SelectAll)
+            .bind(modifier(KeyChord::character("a")).shift(), CollapseSelection)
+            .bind(modifier(KeyChord::character("d")), SelectNextOccurrence)
+    }
+
+    /// Binds `chord` to move the cursor, and `chord` with Shift to extend the selection.
+    fn navigate(self, chord: KeyChord, motion: TextMotion) -> Self {
+        self.bind(chord.clone().shift(), TextEditAction::Select(motion))
+            .bind(chord, TextEditAction::Move(motion))
+    }
+
+    /// Binds `chord` with and without Shift to the same action.
+    fn edit(self, chord: KeyChord, action: TextEditAction) -> Self {
+        self.bind(chord.clone().shift(), action).bind(chord, action)
+    }
+}
+
+/// The preset of the platform, shared by the text inputs that don't set their own bindings.
+pub(crate) fn platform_key_bindings() -> Rc<KeyBindings> {
+    PLATFORM_KEY_BINDINGS.with(Rc::clone)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(key: Key, modifiers: Modifiers) -> KeyboardEvent {
+        KeyboardEvent {
+            key,
+            modifiers,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn presets_follow_the_platform_conventions() {
+        let word_left = press(Key::Named(NamedKey::ArrowLeft), Modifiers::CONTROL | Modifiers::SHIFT);
+        assert_eq!(
+            KeyBindings::linux().action_for(&word_left),
+            Some(TextEditAction::Select(TextMotion::WordLeft))
+        );
+        assert_eq!(KeyBindings::mac().action_for(&word_left), None);
+
+        let line_start = press(Key::Named(NamedKey::ArrowLeft), Modifiers::META);
+        assert_eq!(
+            KeyBindings::mac().action_for(&line_start),
+            Some(TextEditAction::Move(TextMotion::LineStart))
+        );
+        let redo = press(Key::Character("Y".to_string()), Modifiers::CONTROL);
+        assert_eq!(KeyBindings::windows().action_for(&redo), Some(TextEditAction::Redo));
+        assert_eq!(KeyBindings::linux().action_for(&redo), None);
+    }
+
+    #[test]
+    fn bindings_can_be_overridden() {
+        let ctrl_a = press(Key::Character("a".to_string()), Modifiers::CONTROL);
+        assert_eq!(KeyBindings::linux().action_for(&ctrl_a), Some(TextEditAction::SelectAll));
+        let ctrl_d = press(Key::Character("d".to_string()), Modifiers::CONTROL);
+        assert_eq!(KeyBindings::linux().action_for(&ctrl_d), Some(TextEditAction::SelectNextOccurrence));
+        assert_eq!(
+            KeyBindings::readline().action_for(&ctrl_d),
+            Some(TextEditAction::Delete(TextMotion::Right))
+        );
+        assert_eq!(
+            KeyBindings::readline().action_for(&ctrl_a),
+            Some(TextEditAction::Move(TextMotion::LineStart))
+        );
+
+        let bindings = KeyBindings::linux().unbind(&KeyChord::character("a").ctrl());
+        assert_eq!(bindings.action_for(&ctrl_a), None);
+        let bindings = bindings.bind(KeyChord::character("a").ctrl(), TextEditAction::Kill(TextMotion::LineEnd));
+        assert_eq!(bindings.action_for(&ctrl_a), Some(TextEditAction::Kill(TextMotion::LineEnd)));
+    }
+}
@@ -1,8 +1,10 @@
+mod carets;
+mod key_bindings;
 mod text_input_state;
 
 use std::any::Any;
 use std::cell::{Ref, RefCell, RefMut};
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 use std::rc::{Rc, Weak};
 use std::sync::Arc;
 use craft_primitives::Color;
@@ -12,27 +14,36 @@ use craft_renderer::text_renderer_data::{TextData, TextScroll};
 
 use parley::BoundingBox;
 
-use ui_events::pointer::{PointerButton, PointerId};
+use ui_events::pointer::PointerButton;
 
 use winit::event::Ime;
+use winit::window::ImePurpose;
 use craft_renderer::renderer::Renderer;
 use craft_resource_manager::ResourceManager;
-use crate::app::{ELEMENTS};
+use crate::app::{ELEMENTS, FOCUS, is_window_active, queue_event};
 use crate::elements::element_data::ElementData;
 #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
 use crate::elements::element_id::create_unique_element_id;
+use crate::elements::text_input::key_bindings::platform_key_bindings;
 use crate::elements::text_input::text_input_state::TextInputState;
 use crate::elements::traits::DeepClone;
-use crate::elements::{AsElement, Element, ElementInternals, resolve_clip_for_scrollable, scrollable};
+use crate::elements::{
+    AsElement, Element, ElementInternals, WindowInternal, resolve_clip_for_scrollable, scrollable,
+};
 use crate::events::{Event, EventKind};
 use crate::layout::TaffyTree;
 use crate::layout::layout_context::{LayoutContext, TaffyTextInputContext};
-use crate::style::{Display, Overflow, Style, Unit};
+use crate::style::{CornerRadius, Display, Overflow, Style, Unit};
 use crate::text::RangedStyles;
+use crate::text::number_format::NumberFormat;
+use crate::text::spell_check::{SpellChecker, SpellingSuggestions};
 use crate::text::text_context::TextContext;
 use crate::text::text_render_data::TextRender;
+use crate::text::whitespace::ShowWhitespace;
 use crate::utils::cloneable_any::CloneableAny;
 
+pub use crate::elements::text_input::key_bindings::{KeyBindings, KeyChord, TextEditAction, TextMotion};
+
 #[derive(Clone)]
 pub struct TextInput {
     pub inner: Rc<RefCell<TextInputInner>>,
@@ -48,10 +59,78 @@ pub struct TextInputInner {
     pub text: Option<String>,
     pub ranged_styles: Option<RangedStyles>,
     pub disabled: bool,
+    /// Marks the misspelled words, if set.
+    pub spell_checker: Option<Rc<dyn SpellChecker>>,
+    pub input_purpose: InputPurpose,
+    /// The keys that edit the text. The preset of the platform by default.
+    pub key_bindings: Rc<KeyBindings>,
+    /// Writes the text as a number with the separators of a locale, if set.
+    pub number_format: Option<NumberFormat>,
     pub(crate) state: TextInputState,
     pub(crate) me: Weak<RefCell<Self>>,
 }
 
+/// What a text input is for, so password managers can fill it in and on-screen keyboards can show the right keys.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InputPurpose {
+    #[default]
+    Text,
+    Username,
+    /// The current password of an account.
+    Password,
+    /// A password being picked, which password managers offer to generate.
+    NewPassword,
+    Email,
+    /// A code sent by text message or email, or from an authenticator app.
+    OneTimeCode,
+    Url,
+    Search,
+    Telephone,
+    Number,
+}
+
+impl InputPurpose {
+    /// The value of the HTML `autocomplete` attribute, for hosts that mirror the input in the DOM.
+    pub fn autocomplete_token(&self) -> Option<&'static str> {
+        match self {
+            InputPurpose::Text | InputPurpose::Search | InputPurpose::Number => None,
+            InputPurpose::Username => Some("username"),
+            InputPurpose::Password => Some("current-password"),
+            InputPurpose::NewPassword => Some("new-password"),
+            InputPurpose::Email => Some("email"),
+            InputPurpose::OneTimeCode => Some("one-time-code"),
+            InputPurpose::Url => Some("url"),
+            InputPurpose::Telephone => Some("tel"),
+        }
+    }
+
+    /// Whether the text is secret, like a password.
+    pub fn is_secret(&self) -> bool {
+        matches!(self, InputPurpose::Password | InputPurpose::NewPassword)
+    }
+
+    pub(crate) fn ime_purpose(&self) -> ImePurpose {
+        if self.is_secret() {
+            ImePurpose::Password
+        } else {
+            ImePurpose::Normal
+        }
+    }
+
+    #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+    fn accessibility_role(&self) -> accesskit::Role {
+        match self {
+            InputPurpose::Password | InputPurpose::NewPassword => accesskit::Role::PasswordInput,
+            InputPurpose::Email => accesskit::Role::EmailInput,
+            InputPurpose::Url => accesskit::Role::UrlInput,
+            InputPurpose::Search => accesskit::Role::SearchInput,
+            InputPurpose::Telephone => accesskit::Role::PhoneNumberInput,
+            InputPurpose::Number => accesskit::Role::NumberInput,
+            InputPurpose::Text | InputPurpose::Username | InputPurpose::OneTimeCode => accesskit::Role::TextInput,
+        }
+    }
+}
+
 #[allow(dead_code)]
 /// An external message that allows others to command the TextInput.
 pub enum TextInputMessage {
@@ -101,6 +180,64 @@ impl TextInput {
         self.inner.borrow_mut().set_ranged_styles(ranged_styles);
         self
     }
+
+    /// Underlines the words `spell_checker` doesn't know. Right clicking one sends an
+    /// [`EventKind::SpellingSuggestions`] event.
+    pub fn spell_checker(self, spell_checker: Option<Rc<dyn SpellChecker>>) -> Self {
+        self.inner.borrow_mut().set_spell_checker(spell_checker);
+        self
+    }
+
+    /// Tells the platform what the input is for, so password managers and on-screen keyboards can help fill it in.
+    pub fn input_purpose(self, input_purpose: InputPurpose) -> Self {
+        self.inner.borrow_mut().set_input_purpose(input_purpose);
+        self
+    }
+
+    /// Lets the user edit at several places at once: Ctrl+click adds a caret, Alt+drag selects a block with a caret per
+    /// line, and [`TextEditAction::SelectNextOccurrence`] adds a caret at the next occurrence of the selected text.
+    /// Typing, deleting and pasting then happen at every caret.
+    pub fn multiple_carets(self, multiple_carets: bool) -> Self {
+        self.inner.borrow_mut().set_multiple_carets(multiple_carets);
+        self
+    }
+
+    /// Marks invisible characters and wrapped lines with faint glyphs.
+    pub fn show_whitespace(self, show_whitespace: ShowWhitespace) -> Self {
+        self.inner.borrow_mut().set_show_whitespace(show_whitespace);
+        self
+    }
+
+    /// Aligns tabs to a tab stop every `tab_width` columns, the width of a space each. `None`, the default, leaves
+    /// tabs as wide as the font makes them.
+    pub fn tab_width(self, tab_width: Option<u8>) -> Self {
+        self.inner.borrow_mut().set_tab_width(tab_width);
+        self
+    }
+
+    /// Sets the keys that edit the text, like [`KeyBindings::emacs`] or a table of the app's own.
+    pub fn key_bindings(self, key_bindings: KeyBindings) -> Self {
+        self.inner.borrow_mut().set_key_bindings(key_bindings);
+        self
+    }
+
+    /// Makes the input a number input. The number is shown with the separators of `number_format` and rounded to its
+    /// fraction digits while the input isn't focused, and with all of its digits while it is.
+    pub fn number_format(self, number_format: NumberFormat) -> Self {
+        self.inner.borrow_mut().set_number_format(Some(number_format));
+        self
+    }
+
+    /// Returns the number typed, if the input has a number format and the text is a number.
+    pub fn get_number(&self) -> Option<f64> {
+        self.inner.borrow().get_number()
+    }
+
+    /// Replaces a byte range of the text, like a misspelled word with one of its suggestions.
+    pub fn replace_range(self, range: Range<usize>, text: &str) -> Self {
+        self.inner.borrow_mut().replace_range(range, text);
+        self
+    }
 }
 
 impl Element for TextInput {}
@@ -196,6 +333,7 @@ impl ElementInternals for TextInputInner {
             true,
         );
 
+        self.state.check_spelling(self.spell_checker.as_deref());
         self.state
             .render_text(self.is_focused(), self.element_data.style());
     }
@@ -227,12 +365,20 @@ impl ElementInternals for TextInputInner {
             None
         };
 
+        let (selection_color, selection_text_color) =
+            self.element_data.style().get_selection_colors(self.is_focused() && is_window_active());
+        if let Some(text_render) = self.state.text_render.as_mut() {
+            text_render.set_selection_colors(selection_color, selection_text_color);
+        }
+
         if self.state.text_render.as_ref().is_some() {
+            let show_cursor =
+                self.is_focused() && self.state.cursor_blink(self.element_data.style().get_cursor_blink_interval());
             _renderer.draw_text(
                 self.me.clone(),
                 content_rectangle.scale(_scale_factor),
                 text_scroll,
-                self.is_focused(),
+                show_cursor,
             );
         }
 
@@ -256,7 +402,7 @@ impl ElementInternals for TextInputInner {
 
         let current_node_id = accesskit::NodeId(self.element_data.internal_id);
 
-        let mut current_node = accesskit::Node::new(accesskit::Role::TextInput);
+        let mut current_node = accesskit::Node::new(self.input_purpose.accessibility_role());
         let padding_box = self
             .element_data
             .layout
@@ -318,6 +464,7 @@ impl ElementInternals for TextInputInner {
                         return;
                     }
                     self.state.paste(text_context);
+                    self.state.regroup_number(text_context, self.number_format.as_ref());
                     self.mark_dirty();
                     //generate_text_changed_event(&mut self.state.editor);
                 }
@@ -326,6 +473,7 @@ impl ElementInternals for TextInputInner {
                         return;
                     }
                     self.state.cut(text_context);
+                    self.state.regroup_number(text_context, self.number_format.as_ref());
                     self.mark_dirty();
                 }
             }
@@ -336,12 +484,21 @@ impl ElementInternals for TextInputInner {
                 if self.disabled || !keyboard_event.state.is_down() || !focused {
                     return;
                 }
-                self.state.key_press(text_context, keyboard_event, &mut self.element_data);
+                self.state.key_press(
+                    text_context,
+                    keyboard_event,
+                    &self.key_bindings,
+                    self.number_format.as_ref(),
+                    &mut self.element_data,
+                );
             }
             EventKind::PointerButtonDown(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
                 self.focus();
-                self.set_pointer_capture(PointerId::new(1).unwrap());
-                self.state.pointer_down(text_context);
+                self.capture_pointer();
+                self.state.pointer_down(text_context, pointer_button.state.modifiers);
+            }
+            EventKind::PointerButtonDown(pointer_button) if pointer_button.button == Some(PointerButton::Secondary) => {
+                self.show_spelling_suggestions(text_context);
             }
             EventKind::PointerButtonUp(pointer_button) if pointer_button.button == Some(PointerButton::Primary) => {
                 self.state.pointer_up();
@@ -354,6 +511,7 @@ impl ElementInternals for TextInputInner {
             }
             EventKind::ImeEvent(Ime::Commit(text)) => {
                 self.state.insert_or_replace_selection(text_context, text);
+                self.state.regroup_number(text_context, self.number_format.as_ref());
             }
             EventKind::ImeEvent(Ime::Preedit(text, cursor)) => {
                 self.state.ime_pre_edit(text_context, text, cursor);
@@ -366,6 +524,10 @@ impl ElementInternals for TextInputInner {
         }
     }
 
+    fn on_focus_changed(&mut self, focused: bool) {
+        self.rewrite_number(focused);
+    }
+
     fn apply_clip(&mut self, clip_bounds: Option<Rectangle>) {
         let overflow = self.style().get_overflow();
         if overflow[0] == Overflow::Scroll || overflow[1] == Overflow::Scroll {
@@ -386,7 +548,7 @@ impl ElementInternals for TextInputInner {
         const BORDER_COLOR: Color = Color::from_rgb8(199, 199, 206);
         style.set_border_color(TrblRectangle::new_all(BORDER_COLOR));
         style.set_border_width(TrblRectangle::new_all(Unit::Px(1.0)));
-        style.set_border_radius([(5.0, 5.0); 4]);
+        style.set_border_radius([CornerRadius::from((5.0, 5.0)); 4]);
 
         let padding = Unit::Px(4.0);
         style.set_padding(TrblRectangle::new_all(padding));
@@ -400,6 +562,18 @@ impl ElementInternals for TextInputInner {
         self.mark_dirty();
     }
 
+    fn text_baseline(&self) -> Option<f32> {
+        self.state.first_baseline()
+    }
+
+    fn is_focusable(&self) -> bool {
+        !self.disabled
+    }
+
+    fn handles_arrow_keys(&self) -> bool {
+        true
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -423,6 +597,10 @@ impl TextInputInner {
                 use_text_value_on_update: true,
                 ranged_styles: Some(RangedStyles::new(vec![])),
                 disabled: false,
+                spell_checker: None,
+                input_purpose: InputPurpose::default(),
+                key_bindings: platform_key_bindings(),
+                number_format: None,
                 state: text_input_state,
                 me: me.clone(),
             })
@@ -482,6 +660,130 @@ impl TextInputInner {
         self.mark_dirty();
         self
     }
+
+    pub fn set_spell_checker(&mut self, spell_checker: Option<Rc<dyn SpellChecker>>) -> &mut Self {
+        self.spell_checker = spell_checker;
+        self.mark_dirty();
+        self
+    }
+
+    pub fn set_input_purpose(&mut self, input_purpose: InputPurpose) -> &mut Self {
+        self.input_purpose = input_purpose;
+        self
+    }
+
+    pub fn set_multiple_carets(&mut self, multiple_carets: bool) -> &mut Self {
+        self.state.set_multiple_carets(multiple_carets);
+        self.mark_dirty();
+        self
+    }
+
+    pub fn set_show_whitespace(&mut self, show_whitespace: ShowWhitespace) -> &mut Self {
+        self.state.show_whitespace = show_whitespace;
+        self.mark_dirty();
+        self
+    }
+
+    pub fn set_tab_width(&mut self, tab_width: Option<u8>) -> &mut Self {
+        self.state.set_tab_width(tab_width);
+        self.mark_dirty();
+        self
+    }
+
+    pub fn set_key_bindings(&mut self, key_bindings: KeyBindings) -> &mut Self {
+        self.key_bindings = Rc::new(key_bindings);
+        self
+    }
+
+    pub fn set_number_format(&mut self, number_format: Option<NumberFormat>) -> &mut Self {
+        if number_format.is_some() {
+            self.input_purpose = InputPurpose::Number;
+        }
+        self.number_format = number_format;
+        let focused = self.is_focused();
+        self.rewrite_number(focused);
+        self
+    }
+
+    pub fn get_number(&self) -> Option<f64> {
+        self.number_format.as_ref()?.parse(self.get_text())
+    }
+
+    /// Writes the number for editing while the input is focused, and formatted while it isn't. Text that isn't a
+    /// number is left as typed.
+    fn rewrite_number(&mut self, focused: bool) {
+        let Some(number_format) = &self.number_format else {
+            return;
+        };
+        let Some(number) = number_format.parse(self.get_text()) else {
+            return;
+        };
+        let text = if focused {
+            number_format.edit_text(number)
+        } else {
+            number_format.format(number)
+        };
+        if text != self.get_text() {
+            self.set_text(&text);
+        }
+    }
+
+    /// Replaces a byte range of the text. Does nothing if the range isn't on character boundaries.
+    pub fn replace_range(&mut self, range: Range<usize>, text: &str) -> &mut Self {
+        let current = self.get_text();
+        if range.end > current.len() || !current.is_char_boundary(range.start) || !current.is_char_boundary(range.end)
+        {
+            return self;
+        }
+        let mut new_text = current.to_string();
+        new_text.replace_range(range, text);
+        self.set_text(&new_text)
+    }
+
+    /// Selects the misspelled word under the pointer and sends its suggestions.
+    fn show_spelling_suggestions(&mut self, text_context: &mut TextContext) {
+        let Some(spell_checker) = self.spell_checker.clone() else {
+            return;
+        };
+        let Some(range) = self.state.index_at_pointer().and_then(|index| self.state.misspelling_at(index)) else {
+            return;
+        };
+        self.state.driver(text_context).select_byte_range(range.start, range.end);
+
+        let word = self.get_text()[range.clone()].to_string();
+        let suggestions = SpellingSuggestions {
+            suggestions: spell_checker.suggestions(&word),
+            range,
+            word,
+        };
+        queue_event(
+            Event::new(self.me.upgrade().unwrap()),
+            EventKind::SpellingSuggestions(suggestions),
+        );
+    }
+}
+
+/// Returns the IME purpose of the focused text input in `window`.
+pub(crate) fn focused_ime_purpose(window: &Weak<RefCell<WindowInternal>>) -> ImePurpose {
+    let Some(focused) = FOCUS.with_borrow(|focus| focus.as_ref().and_then(Weak::upgrade)) else {
+        return ImePurpose::Normal;
+    };
+    let Ok(focused) = focused.try_borrow() else {
+        return ImePurpose::Normal;
+    };
+    let Some(text_input) = focused.as_any().downcast_ref::<TextInputInner>() else {
+        return ImePurpose::Normal;
+    };
+    let in_window = text_input
+        .element_data
+        .window
+        .as_ref()
+        .is_some_and(|text_input_window| Weak::ptr_eq(text_input_window, window));
+    if in_window {
+        text_input.input_purpose.ime_purpose()
+    } else {
+        ImePurpose::Normal
+    }
 }
 
 impl TextData for TextInputInner {
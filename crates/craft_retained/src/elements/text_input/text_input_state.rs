@@ -10,23 +10,34 @@ use craft_renderer::text_renderer_data::TextRender;
 use parley::{Affinity, ContentWidths, Cursor, Selection};
 use peniko::Color;
 use taffy::{AvailableSpace, NodeId};
-use ui_events::keyboard::{Key, KeyboardEvent, Modifiers, NamedKey};
+use ui_events::keyboard::{Key, KeyboardEvent, Modifiers};
 use ui_events::pointer::PointerUpdate;
 #[cfg(target_arch = "wasm32")]
 use web_time::{Duration, Instant};
 use winit::dpi;
 
-use crate::app::{TAFFY_TREE, request_apply_layout, queue_event};
+use crate::app::{TAFFY_TREE, is_reduced_motion, queue_event, request_animation_frame_at, request_apply_layout};
 use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::copy_to_clipboard;
+use crate::elements::text_input::carets::{Caret, block_carets, merge_carets, next_occurrence, word_at};
+use crate::elements::text_input::key_bindings::{KeyBindings, TextEditAction, TextMotion};
 use crate::elements::text_input::parley_box_to_rect;
 use crate::elements::{ElementInternals, TextInputInner};
 use crate::events::{Event, EventKind, TextInputChanged};
 use crate::layout::layout_context::TextHashKey;
 use crate::style::{Style, TextStyleProperty};
+use crate::text::number_format::NumberFormat;
 use crate::text::parley_editor::{PlainEditor, PlainEditorDriver};
+use crate::text::spell_check::{SpellChecker, misspelled_ranges};
 use crate::text::text_context::TextContext;
+use crate::text::whitespace::{ShowWhitespace, WhitespaceMark, whitespace_marks};
 use crate::text::{RangedStyles, text_render_data};
 
+/// The color of the wavy line under misspelled words.
+const MISSPELLING_COLOR: Color = Color::from_rgb8(229, 57, 53);
+/// The opacity of the whitespace marks, relative to the color of the text.
+const WHITESPACE_MARK_ALPHA: f32 = 0.35;
+
 #[derive(Clone)]
 pub struct TextInputState {
     pub(crate) taffy_id: Option<NodeId>,
@@ -57,7 +68,22 @@ pub struct TextInputState {
     cursor_visible: bool,
     modifiers: Option<Modifiers>,
     start_time: Option<Instant>,
-    blink_period: Duration,
+
+    /// The byte ranges of the misspelled words, as of `spell_checked_text`.
+    misspellings: Vec<Range<usize>>,
+    spell_checked_text: Option<String>,
+
+    /// Whether Ctrl+click, Alt+drag and [`TextEditAction::SelectNextOccurrence`] add carets.
+    multiple_carets: bool,
+    /// The carets besides the one of the editor, from the first to the last.
+    extra_carets: Vec<Caret>,
+    /// Where an Alt+drag started, while it makes a block selection.
+    block_anchor: Option<usize>,
+
+    pub(crate) show_whitespace: ShowWhitespace,
+
+    /// Whether the input was focused at the last layout, to notice when it gains or loses the focus.
+    pub(crate) was_focused: bool,
 
     /// True if the node needs laid-out.
     pub is_layout_dirty: bool,
@@ -90,7 +116,13 @@ impl Default for TextInputState {
             cursor_visible: false,
             modifiers: None,
             start_time: None,
-            blink_period: Default::default(),
+            misspellings: Vec::new(),
+            spell_checked_text: None,
+            multiple_carets: false,
+            extra_carets: Vec::new(),
+            block_anchor: None,
+            show_whitespace: ShowWhitespace::default(),
+            was_focused: false,
             is_layout_dirty: true,
         }
     }
@@ -126,8 +158,15 @@ impl TextInputState {
         if self.is_pointer_down() && prev_pos != self.cursor_pos() && !self.editor.is_composing() {
             self.reset_blink();
             let cursor_pos = self.cursor_pos();
-            self.driver(text_context)
-                .extend_selection_to_point(cursor_pos.x as f32, cursor_pos.y as f32);
+            if let Some(anchor) = self.block_anchor
+                && let Some(focus) = self.index_at_pointer()
+            {
+                let (carets, main) = block_carets(self.editor.raw_text(), anchor, focus);
+                self.set_carets(text_context, carets, main);
+            } else {
+                self.driver(text_context)
+                    .extend_selection_to_point(cursor_pos.x as f32, cursor_pos.y as f32);
+            }
             if let Some(taffy_id) = self.taffy_id {
                 request_apply_layout(taffy_id);
             }
@@ -299,37 +338,27 @@ impl TextInputState {
         None
     }
 
-    /// Resets the cursor blink.
+    /// Resets the cursor blink, so the cursor is shown right after it moves.
     pub fn reset_blink(&mut self) {
         self.start_time = Some(Instant::now());
-        // TODO: for real world use, this should be reading from the system settings
-        self.blink_period = Duration::from_millis(500);
         self.cursor_visible = true;
     }
 
-    #[allow(dead_code)]
-    pub fn disable_blink(&mut self) {
-        self.start_time = None;
-    }
-
-    #[allow(dead_code)]
-    pub fn next_blink_time(&self) -> Option<Instant> {
-        self.start_time.map(|start_time| {
-            let phase = Instant::now().duration_since(start_time);
-
-            start_time
-                + Duration::from_nanos(
-                    ((phase.as_nanos() / self.blink_period.as_nanos() + 1) * self.blink_period.as_nanos()) as u64,
-                )
-        })
-    }
+    /// Updates whether the blinking cursor is shown, and asks for the window to be drawn again when that changes.
+    ///
+    /// The cursor stays shown when `interval` is zero or with reduced motion.
+    pub fn cursor_blink(&mut self, interval: Duration) -> bool {
+        if interval.is_zero() || is_reduced_motion() {
+            self.cursor_visible = true;
+            return true;
+        }
 
-    #[allow(dead_code)]
-    pub fn cursor_blink(&mut self) {
-        self.cursor_visible = self.start_time.is_some_and(|start_time| {
-            let elapsed = Instant::now().duration_since(start_time);
-            (elapsed.as_millis() / self.blink_period.as_millis()).is_multiple_of(2)
-        });
+        let now = Instant::now();
+        let start_time = *self.start_time.get_or_insert(now);
+        let phases = now.duration_since(start_time).as_nanos() / interval.as_nanos();
+        self.cursor_visible = phases.is_multiple_of(2);
+        request_animation_frame_at(start_time + Duration::from_nanos(((phases + 1) * interval.as_nanos()) as u64));
+        self.cursor_visible
     }
 
     pub(crate) fn driver<'a>(&'a mut self, text_context: &'a mut TextContext) -> PlainEditorDriver<'a> {
@@ -337,6 +366,12 @@ impl TextInputState {
             .driver(&mut text_context.font_context, &mut text_context.layout_context)
     }
 
+    /// The baseline of the first line from the top of the text, in logical pixels.
+    pub(crate) fn first_baseline(&self) -> Option<f32> {
+        let line = self.editor.try_layout()?.lines().next()?;
+        Some(line.metrics().baseline / self.scale_factor as f32)
+    }
+
     /// Set's the scale factor.
     pub fn set_scale_factor(&mut self, scale_factor: f64) {
         self.scale_factor = scale_factor;
@@ -344,10 +379,32 @@ impl TextInputState {
         self.clear_cache();
     }
 
-    pub fn pointer_down(&mut self, text_context: &mut TextContext) {
+    /// Moves the caret to the pointer, or selects the word or line under it for double and triple clicks.
+    ///
+    /// With multiple carets, `modifiers` with Control add a caret at the pointer instead, and with Alt start a block
+    /// selection.
+    pub fn pointer_down(&mut self, text_context: &mut TextContext, modifiers: Modifiers) {
         self.cursor_visible = true;
         self.pointer_down = true;
         self.reset_blink();
+        self.block_anchor = None;
+        let adds_carets = modifiers.ctrl() || modifiers.alt();
+        if self.multiple_carets && adds_carets && !self.editor.is_composing() {
+            let previous = self.main_caret();
+            let cursor_pos = self.cursor_pos;
+            self.driver(text_context).move_to_point(cursor_pos.x as f32, cursor_pos.y as f32);
+            if modifiers.ctrl() {
+                let mut carets = self.carets();
+                carets.push(previous);
+                self.set_carets(text_context, carets, self.extra_carets.len());
+            } else {
+                self.extra_carets.clear();
+                self.block_anchor = Some(self.main_caret().focus);
+            }
+            self.last_click_time = None;
+            return;
+        }
+        self.extra_carets.clear();
         if !self.editor.is_composing() {
             let now = Instant::now();
             if let Some(last) = self.last_click_time.take() {
@@ -384,6 +441,7 @@ impl TextInputState {
 
     pub fn pointer_up(&mut self) {
         self.pointer_down = false;
+        self.block_anchor = None;
         self.reset_blink();
     }
 
@@ -404,14 +462,136 @@ impl TextInputState {
     ///
     /// This requires a relayout.
     pub fn insert_or_replace_selection(&mut self, text_context: &mut TextContext, text: &str) {
-        self.driver(text_context).insert_or_replace_selection(text, true);
+        self.for_each_caret(text_context, |driver| driver.insert_or_replace_selection(text, true));
+        self.clear_cache();
+    }
+
+    pub(crate) fn set_tab_width(&mut self, tab_width: Option<u8>) {
+        self.editor.set_tab_width(tab_width);
         self.clear_cache();
     }
 
+    pub(crate) fn set_multiple_carets(&mut self, multiple_carets: bool) {
+        self.multiple_carets = multiple_carets;
+        if !multiple_carets {
+            self.extra_carets.clear();
+            self.block_anchor = None;
+        }
+    }
+
+    /// The selection of the editor as a caret.
+    fn main_caret(&self) -> Caret {
+        let selection = self.editor.raw_selection();
+        Caret::new(selection.anchor().index(), selection.focus().index())
+    }
+
+    /// All the carets, the main one last.
+    fn carets(&self) -> Vec<Caret> {
+        let mut carets = self.extra_carets.clone();
+        carets.push(self.main_caret());
+        carets
+    }
+
+    /// Replaces the carets, merging the ones that overlap. The caret at `main` becomes the selection of the editor.
+    fn set_carets(&mut self, text_context: &mut TextContext, carets: Vec<Caret>, main: usize) {
+        let text_length = self.editor.raw_text().len();
+        let carets = carets
+            .into_iter()
+            .map(|caret| Caret::new(caret.anchor.min(text_length), caret.focus.min(text_length)))
+            .collect();
+        let (mut carets, main) = merge_carets(carets, main);
+        let main = carets.remove(main);
+        self.extra_carets = carets;
+        self.driver(text_context).select_byte_range(main.anchor, main.focus);
+    }
+
+    /// Does `edit` at every caret, from the last to the first so an edit only moves the carets already done.
+    fn for_each_caret(&mut self, text_context: &mut TextContext, mut edit: impl FnMut(&mut PlainEditorDriver)) {
+        if self.extra_carets.is_empty() {
+            edit(&mut self.driver(text_context));
+            return;
+        }
+
+        let mut carets = self.carets();
+        let main = carets.len() - 1;
+        let mut order: Vec<usize> = (0..carets.len()).collect();
+        order.sort_by_key(|index| std::cmp::Reverse(carets[*index].start()));
+
+        let mut driver = self.driver(text_context);
+        for (done, index) in order.iter().enumerate() {
+            let length = driver.editor.raw_text().len();
+            driver.select_byte_range(carets[*index].anchor, carets[*index].focus);
+            edit(&mut driver);
+            let delta = driver.editor.raw_text().len() as isize - length as isize;
+            for later in &order[..done] {
+                carets[*later] = carets[*later].shifted(delta);
+            }
+            let selection = driver.editor.raw_selection();
+            carets[*index] = Caret::new(selection.anchor().index(), selection.focus().index());
+        }
+        self.set_carets(text_context, carets, main);
+    }
+
+    /// The texts the carets select, from the first caret to the last.
+    fn selected_texts(&self) -> Vec<&str> {
+        let mut carets = self.carets();
+        carets.sort_by_key(Caret::start);
+        let text = self.editor.raw_text();
+        carets
+            .iter()
+            .filter(|caret| !caret.is_collapsed())
+            .map(|caret| &text[caret.range()])
+            .collect()
+    }
+
+    /// Selects the word at the caret, or adds a caret at the next occurrence of the selected text.
+    fn select_next_occurrence(&mut self, text_context: &mut TextContext) {
+        let main = self.main_caret();
+        let text = self.editor.raw_text();
+        if main.is_collapsed() {
+            let word = word_at(text, main.focus);
+            self.driver(text_context).select_byte_range(word.start, word.end);
+            return;
+        }
+        let needle = &text[main.range()];
+        let Some(next) = next_occurrence(text, needle, main.end(), &self.carets()) else {
+            return;
+        };
+        let mut carets = self.carets();
+        carets.push(Caret::new(next.start, next.end));
+        self.set_carets(text_context, carets, self.extra_carets.len() + 1);
+    }
+
     pub fn is_pointer_down(&self) -> bool {
         self.pointer_down
     }
 
+    /// Regroups the number, if the input has a number format, and sends the new text.
+    fn text_changed(
+        &mut self,
+        text_context: &mut TextContext,
+        number_format: Option<&NumberFormat>,
+        element_data: &ElementData,
+    ) {
+        self.clear_cache();
+        self.regroup_number(text_context, number_format);
+        self.generate_text_changed_event(element_data);
+    }
+
+    /// Puts the group separators of the number where they belong after it was edited, keeping the cursor after the
+    /// same digit.
+    pub(crate) fn regroup_number(&mut self, text_context: &mut TextContext, number_format: Option<&NumberFormat>) {
+        let Some(number_format) = number_format else {
+            return;
+        };
+        let cursor = self.editor.raw_selection().focus().index();
+        let (text, cursor) = number_format.regroup(self.editor.raw_text(), cursor);
+        if text != self.editor.raw_text() {
+            self.set_text(&text);
+            self.driver(text_context).move_to_byte(cursor);
+        }
+    }
+
     fn generate_text_changed_event(&self, element_data: &ElementData) {
         let new_event = Event::new(element_data.me.upgrade().unwrap());
         queue_event(new_event, EventKind::TextInputChanged(TextInputChanged {
@@ -423,239 +603,127 @@ impl TextInputState {
         &mut self,
         text_context: &mut TextContext,
         keyboard_event: &KeyboardEvent,
+        key_bindings: &KeyBindings,
+        number_format: Option<&NumberFormat>,
         element_data: &mut ElementData,
     ) {
         // TODO: self.reset_blink();
 
         self.modifiers = Some(keyboard_event.modifiers);
 
-        const IS_MAC: bool = cfg!(target_os = "macos");
-
-        let (shift, action_mod, word_mod) = self
-            .modifiers
-            .map(|mods| {
-                if IS_MAC {
-                    // mac: cmd for actions, alt for words
-                    (mods.shift(), mods.meta(), mods.alt())
-                } else {
-                    // windows/linux: Ctrl for both
-                    (mods.shift(), mods.ctrl(), mods.ctrl())
-                }
-            })
-            .unwrap_or_default();
-
-        let mut driver = self.driver(text_context);
+        if let Some(action) = key_bindings.action_for(keyboard_event) {
+            self.apply_edit_action(text_context, action, number_format, element_data);
+        } else if let Key::Character(character) = &keyboard_event.key {
+            self.for_each_caret(text_context, |driver| driver.insert_or_replace_selection(character, true));
+            self.text_changed(text_context, number_format, element_data);
+        }
+    }
 
-        match &keyboard_event.key {
-            #[cfg(target_os = "windows")]
-            Key::Character(c) if action_mod && c.to_lowercase() == "y" => {
-                driver.redo();
-                self.clear_cache();
-                self.generate_text_changed_event(element_data);
-            }
-            Key::Character(c) if action_mod && c.to_lowercase() == "z" => {
-                if shift {
-                    driver.redo();
-                } else {
-                    driver.undo();
-                }
-                self.clear_cache();
-                self.generate_text_changed_event(element_data);
+    /// Does what a key is bound to.
+    pub fn apply_edit_action(
+        &mut self,
+        text_context: &mut TextContext,
+        action: TextEditAction,
+        number_format: Option<&NumberFormat>,
+        element_data: &mut ElementData,
+    ) {
+        let changed = match action {
+            TextEditAction::Move(motion) => {
+                self.for_each_caret(text_context, |driver| move_by(driver, motion));
+                false
             }
-            Key::Character(c) if action_mod && matches!(c.as_str(), "c" | "x" | "v") => {
-                match c.to_lowercase().as_str() {
-                    "c" => copy(&mut driver),
-                    "x" => {
-                        cut(&mut driver);
-                        self.clear_cache();
-                        self.generate_text_changed_event(element_data);
-                    }
-                    "v" => {
-                        paste(&mut driver);
-                        self.clear_cache();
-                        self.generate_text_changed_event(element_data);
-                    }
-                    _ => (),
-                }
+            TextEditAction::Select(motion) => {
+                self.for_each_caret(text_context, |driver| select_by(driver, motion));
+                false
             }
-            Key::Character(c) if action_mod && matches!(c.to_lowercase().as_str(), "a") => {
-                if shift {
-                    driver.collapse_selection();
-                } else {
-                    driver.select_all();
-                }
+            TextEditAction::Delete(motion) => {
+                self.for_each_caret(text_context, |driver| delete_by(driver, motion));
+                true
             }
-            Key::Named(NamedKey::ArrowLeft) => {
-                if IS_MAC && action_mod {
-                    // mac: Cmd + Left = Line Start
-                    if shift {
-                        driver.select_to_line_start();
-                    } else {
-                        driver.move_to_line_start();
-                    }
-                } else if word_mod {
-                    // windows: ctrl + left | mac: alt + left = word left
-                    if shift {
-                        driver.select_word_left();
-                    } else {
-                        driver.move_word_left();
+            TextEditAction::Kill(motion) => {
+                self.for_each_caret(text_context, |driver| {
+                    if driver.editor.raw_selection().is_collapsed() {
+                        select_by(driver, motion);
                     }
-                } else if shift {
-                    driver.select_left();
-                } else {
-                    driver.move_left();
-                }
-                self.maybe_scroll_to_cursor(element_data);
+                });
+                self.cut(text_context);
+                true
             }
-            Key::Named(NamedKey::ArrowRight) => {
-                if IS_MAC && action_mod {
-                    // mac: cmd + right = end of line
-                    if shift {
-                        driver.select_to_line_end();
-                    } else {
-                        driver.move_to_line_end();
-                    }
-                } else if word_mod {
-                    // windows/linux: ctrl + right | mac: alt + right = word right
-                    if shift {
-                        driver.select_word_right();
-                    } else {
-                        driver.move_word_right();
-                    }
-                } else if shift {
-                    driver.select_right();
-                } else {
-                    driver.move_right();
-                }
-                self.maybe_scroll_to_cursor(element_data);
+            TextEditAction::SelectAll => {
+                self.extra_carets.clear();
+                self.driver(text_context).select_all();
+                false
             }
-            Key::Named(NamedKey::ArrowUp) => {
-                if IS_MAC && action_mod {
-                    // mac: cmd + up = document start
-                    if shift {
-                        driver.select_to_text_start();
-                    } else {
-                        driver.move_to_text_start();
-                    }
-                } else if word_mod {
-                    if shift {
-                        driver.select_left();
-                        driver.select_to_hard_line_start();
-                    } else {
-                        driver.move_left();
-                        driver.move_to_hard_line_start();
-                    }
-                } else if shift {
-                    driver.select_up();
-                } else {
-                    driver.move_up();
+            TextEditAction::CollapseSelection => {
+                // The extra carets go first, then the selection.
+                if self.extra_carets.is_empty() {
+                    self.driver(text_context).collapse_selection();
                 }
-                self.maybe_scroll_to_cursor(element_data);
+                self.extra_carets.clear();
+                false
             }
-            Key::Named(NamedKey::ArrowDown) => {
-                if IS_MAC && action_mod {
-                    // mac: cmd + down = end of document
-                    if shift {
-                        driver.select_to_text_end();
-                    } else {
-                        driver.move_to_text_end();
-                    }
-                } else if word_mod {
-                    if shift {
-                        driver.select_to_hard_line_end();
-                        driver.select_right();
-                    } else {
-                        driver.move_to_hard_line_end();
-                        driver.move_right();
-                    }
-                } else if shift {
-                    driver.select_down();
-                } else {
-                    driver.move_down();
+            TextEditAction::SelectNextOccurrence => {
+                if self.multiple_carets {
+                    self.select_next_occurrence(text_context);
                 }
-                self.maybe_scroll_to_cursor(element_data);
+                false
             }
-            Key::Named(NamedKey::Home) => {
-                if action_mod {
-                    if shift {
-                        driver.select_to_text_start();
-                    } else {
-                        driver.move_to_text_start();
-                    }
-                } else if shift {
-                    driver.select_to_line_start();
-                } else {
-                    driver.move_to_line_start();
-                }
-                self.maybe_scroll_to_cursor(element_data);
+            TextEditAction::Undo => {
+                self.extra_carets.clear();
+                self.driver(text_context).undo();
+                true
             }
-            Key::Named(NamedKey::End) => {
-                let mut drv = self.driver(text_context);
-
-                if action_mod {
-                    if shift {
-                        drv.select_to_text_end();
-                    } else {
-                        drv.move_to_text_end();
-                    }
-                } else if shift {
-                    drv.select_to_line_end();
-                } else {
-                    drv.move_to_line_end();
-                }
-                self.maybe_scroll_to_cursor(element_data);
+            TextEditAction::Redo => {
+                self.extra_carets.clear();
+                self.driver(text_context).redo();
+                true
             }
-            Key::Named(NamedKey::Delete) => {
-                if word_mod {
-                    driver.delete_word(true);
-                } else {
-                    driver.delete(true);
-                }
-                self.clear_cache();
-                self.generate_text_changed_event(element_data);
+            TextEditAction::Copy => {
+                self.copy(text_context);
+                false
             }
-            Key::Named(NamedKey::Backspace) => {
-                if IS_MAC && action_mod {
-                    driver.move_anchor_to_line_start();
-                    driver.insert_or_replace_selection("", true);
-                } else if word_mod {
-                    if IS_MAC {
-                        driver.move_anchor_word_left();
-                        self.insert_or_replace_selection(text_context, "");
-                    } else {
-                        driver.backdelete_word(true);
-                    }
-                } else {
-                    driver.backdelete(true);
-                }
-
-                self.clear_cache();
-                self.generate_text_changed_event(element_data);
+            TextEditAction::Cut => {
+                self.cut(text_context);
+                true
             }
-            Key::Named(NamedKey::Enter) => {
-                driver.insert_or_replace_selection("\n", true);
-                self.clear_cache();
-                self.generate_text_changed_event(element_data);
+            TextEditAction::Paste => {
+                self.paste(text_context);
+                true
             }
-            Key::Character(character) => {
-                driver.insert_or_replace_selection(character, true);
-                self.clear_cache();
-                self.generate_text_changed_event(element_data);
+            TextEditAction::InsertNewline => {
+                self.for_each_caret(text_context, |driver| driver.insert_or_replace_selection("\n", true));
+                true
             }
-            _ => (),
+        };
+
+        if changed {
+            self.text_changed(text_context, number_format, element_data);
+        }
+        if matches!(action, TextEditAction::Move(_) | TextEditAction::Select(_)) {
+            self.maybe_scroll_to_cursor(element_data);
         }
     }
 
+    /// Copies the selected text, the texts of all carets on their own lines with multiple carets.
     pub fn copy(&mut self, text_context: &mut TextContext) {
-        copy(&mut self.driver(text_context));
+        if self.extra_carets.is_empty() {
+            copy(&mut self.driver(text_context));
+        } else {
+            copy_to_clipboard(self.selected_texts().join("\n"));
+        }
     }
 
     pub fn paste(&mut self, text_context: &mut TextContext) {
-        paste(&mut self.driver(text_context));
+        self.for_each_caret(text_context, paste);
     }
 
     pub fn cut(&mut self, text_context: &mut TextContext) {
-        cut(&mut self.driver(text_context));
+        if self.extra_carets.is_empty() {
+            cut(&mut self.driver(text_context));
+        } else {
+            copy_to_clipboard(self.selected_texts().join("\n"));
+            self.for_each_caret(text_context, |driver| driver.delete_selection(true));
+        }
         self.clear_cache();
     }
 
@@ -678,6 +746,7 @@ impl TextInputState {
     }
 
     pub fn set_text(&mut self, text: &str) {
+        self.extra_carets.clear();
         self.editor.set_text(text);
         self.clear_cache();
     }
@@ -687,6 +756,37 @@ impl TextInputState {
         self.clear_cache();
     }
 
+    /// Checks the spelling of the text again if it changed since the last check, or forgets the misspellings if there
+    /// is no spell checker.
+    pub(crate) fn check_spelling(&mut self, spell_checker: Option<&dyn SpellChecker>) {
+        let Some(spell_checker) = spell_checker else {
+            self.misspellings.clear();
+            self.spell_checked_text = None;
+            return;
+        };
+        let text = self.editor.raw_text();
+        if self.spell_checked_text.as_deref() == Some(text) {
+            return;
+        }
+        self.misspellings = misspelled_ranges(spell_checker, text);
+        self.spell_checked_text = Some(text.to_string());
+    }
+
+    /// Returns the misspelled word at a byte index of the text.
+    pub(crate) fn misspelling_at(&self, index: usize) -> Option<Range<usize>> {
+        self.misspellings
+            .iter()
+            .find(|range| range.start <= index && index <= range.end)
+            .cloned()
+    }
+
+    /// Returns the byte index of the text under the last known pointer position.
+    pub(crate) fn index_at_pointer(&self) -> Option<usize> {
+        let layout = self.editor.try_layout()?;
+        let cursor_pos = self.cursor_pos();
+        Some(Cursor::from_point(layout, cursor_pos.x as f32, cursor_pos.y as f32).index())
+    }
+
     pub fn render_text(&mut self, focused: bool, style: &Style) {
         let backgrounds: Vec<(Range<usize>, Color)> = self
             .editor()
@@ -702,22 +802,50 @@ impl TextInputState {
             })
             .collect();
 
+        let mut squiggles: Vec<(Range<usize>, Color)> = self
+            .editor()
+            .ranged_styles
+            .styles
+            .iter()
+            .filter_map(|(range, style)| {
+                if let TextStyleProperty::SquigglyUnderline(color) = style {
+                    Some((range.clone(), *color))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        // The word being typed isn't marked until the cursor leaves it.
+        let typing_at = (focused && self.editor.raw_selection().is_collapsed())
+            .then(|| self.editor.raw_selection().focus().index());
+        squiggles.extend(
+            self.misspellings
+                .iter()
+                .filter(|range| typing_at.is_none_or(|index| index < range.start || index > range.end))
+                .map(|range| (range.clone(), MISSPELLING_COLOR)),
+        );
+
         let layout = self.editor.try_layout().unwrap();
+        let to_selection = |range: &Range<usize>| {
+            Selection::new(
+                Cursor::from_byte_index(layout, range.start, Affinity::Downstream),
+                Cursor::from_byte_index(layout, range.end, Affinity::Downstream),
+            )
+        };
         let backgrounds: Vec<(Selection, Color)> = backgrounds
             .iter()
-            .map(|(range, color)| {
-                (
-                    Selection::new(
-                        Cursor::from_byte_index(layout, range.start, Affinity::Downstream),
-                        Cursor::from_byte_index(layout, range.end, Affinity::Downstream),
-                    ),
-                    *color,
-                )
-            })
+            .map(|(range, color)| (to_selection(range), *color))
             .collect();
+        let squiggles: Vec<(Selection, Color)> = squiggles
+            .iter()
+            .map(|(range, color)| (to_selection(range), *color))
+            .collect();
+        let extra_selections: Vec<Selection> =
+            self.extra_carets.iter().map(|caret| to_selection(&caret.range())).collect();
         let text_renderer = self.text_render.as_mut().unwrap();
         for line in text_renderer.lines.iter_mut() {
             line.backgrounds.clear();
+            line.squiggles.clear();
         }
         for (selection, color) in backgrounds.iter() {
             selection.geometry_with(layout, |rect, line| {
@@ -732,6 +860,40 @@ impl TextInputState {
                 ));
             });
         }
+        for (selection, color) in squiggles.iter() {
+            selection.geometry_with(layout, |rect, line| {
+                text_renderer.lines[line].squiggles.push((parley_box_to_rect(rect), *color));
+            });
+        }
+
+        for line in text_renderer.lines.iter_mut() {
+            line.whitespace_marks.clear();
+        }
+        if !self.show_whitespace.is_empty() {
+            let color = style.get_color().multiply_alpha(WHITESPACE_MARK_ALPHA);
+            let text = self.editor.raw_text();
+            for (range, mark) in whitespace_marks(text, self.show_whitespace) {
+                to_selection(&range).geometry_with(layout, |rect, line| {
+                    text_renderer.lines[line]
+                        .whitespace_marks
+                        .push((mark, parley_box_to_rect(rect), color));
+                });
+            }
+            // A line ending without a line break was wrapped.
+            let wrapped_lines = layout.lines().enumerate().filter(|(_, line)| {
+                let end = line.text_range().end;
+                self.show_whitespace.wraps && end < text.len() && !text[..end].ends_with('\n')
+            });
+            for (index, line) in wrapped_lines {
+                let end = Cursor::from_byte_index(layout, line.text_range().end, Affinity::Upstream);
+                let caret = end.geometry(layout, 1.0);
+                let height = (caret.y1 - caret.y0) as f32;
+                let mark_rect = Rectangle::new(caret.x0 as f32, caret.y0 as f32, height / 2.0, height);
+                text_renderer.lines[index]
+                    .whitespace_marks
+                    .push((WhitespaceMark::Wrap, mark_rect, color));
+            }
+        }
 
         for line in text_renderer.lines.iter_mut() {
             line.selections.clear();
@@ -741,10 +903,27 @@ impl TextInputState {
                 .selections
                 .push((parley_box_to_rect(rect), style.get_selection_color()));
         });
+        for selection in extra_selections.iter() {
+            selection.geometry_with(layout, |rect, line| {
+                text_renderer.lines[line]
+                    .selections
+                    .push((parley_box_to_rect(rect), style.get_selection_color()));
+            });
+        }
 
+        text_renderer.extra_cursors.clear();
         if focused {
             let color = style.get_cursor_color().unwrap_or(style.get_color());
-            text_renderer.cursor = self.editor.cursor_geometry(1.0).map(|r| (parley_box_to_rect(r), color));
+            let width = style.get_cursor_width() * self.scale_factor as f32;
+            text_renderer.cursor = self.editor.cursor_geometry(width).map(|r| (parley_box_to_rect(r), color));
+            text_renderer.extra_cursors = self
+                .extra_carets
+                .iter()
+                .map(|caret| {
+                    let cursor = Cursor::from_byte_index(layout, caret.focus, Affinity::Downstream);
+                    parley_box_to_rect(cursor.geometry(layout, width))
+                })
+                .collect();
         } else {
             text_renderer.cursor = None;
         }
@@ -764,6 +943,68 @@ impl TextInputState {
     }
 }
 
+fn move_by(driver: &mut PlainEditorDriver, motion: TextMotion) {
+    match motion {
+        TextMotion::Left => driver.move_left(),
+        TextMotion::Right => driver.move_right(),
+        TextMotion::Up => driver.move_up(),
+        TextMotion::Down => driver.move_down(),
+        TextMotion::WordLeft => driver.move_word_left(),
+        TextMotion::WordRight => driver.move_word_right(),
+        TextMotion::LineStart => driver.move_to_line_start(),
+        TextMotion::LineEnd => driver.move_to_line_end(),
+        TextMotion::ParagraphStart => {
+            driver.move_left();
+            driver.move_to_hard_line_start();
+        }
+        TextMotion::ParagraphEnd => {
+            driver.move_to_hard_line_end();
+            driver.move_right();
+        }
+        TextMotion::TextStart => driver.move_to_text_start(),
+        TextMotion::TextEnd => driver.move_to_text_end(),
+    }
+}
+
+fn select_by(driver: &mut PlainEditorDriver, motion: TextMotion) {
+    match motion {
+        TextMotion::Left => driver.select_left(),
+        TextMotion::Right => driver.select_right(),
+        TextMotion::Up => driver.select_up(),
+        TextMotion::Down => driver.select_down(),
+        TextMotion::WordLeft => driver.select_word_left(),
+        TextMotion::WordRight => driver.select_word_right(),
+        TextMotion::LineStart => driver.select_to_line_start(),
+        TextMotion::LineEnd => driver.select_to_line_end(),
+        TextMotion::ParagraphStart => {
+            driver.select_left();
+            driver.select_to_hard_line_start();
+        }
+        TextMotion::ParagraphEnd => {
+            driver.select_to_hard_line_end();
+            driver.select_right();
+        }
+        TextMotion::TextStart => driver.select_to_text_start(),
+        TextMotion::TextEnd => driver.select_to_text_end(),
+    }
+}
+
+/// Deletes the selection, or the text between the cursor and where `motion` would move it.
+fn delete_by(driver: &mut PlainEditorDriver, motion: TextMotion) {
+    match motion {
+        TextMotion::Left => driver.backdelete(true),
+        TextMotion::Right => driver.delete(true),
+        TextMotion::WordLeft => driver.backdelete_word(true),
+        TextMotion::WordRight => driver.delete_word(true),
+        _ => {
+            if driver.editor.raw_selection().is_collapsed() {
+                select_by(driver, motion);
+            }
+            driver.insert_or_replace_selection("", true);
+        }
+    }
+}
+
 #[cfg(all(
     any(target_os = "windows", target_os = "macos", target_os = "linux"),
     feature = "clipboard"
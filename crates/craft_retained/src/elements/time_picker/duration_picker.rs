@@ -0,0 +1,366 @@
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+use std::time::Duration;
+
+use craft_primitives::geometry::{Affine, Point, Rectangle};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use ui_events::keyboard::{Code, KeyState};
+
+use crate::app::queue_event;
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, draw_generic_container, push_child_to_element};
+use crate::elements::time_picker::{BORDER_COLOR, INVALID_BORDER_COLOR};
+use crate::elements::traits::DeepClone;
+use crate::elements::{resolve_clip_for_scrollable, AsElement, Element, ElementInternals, TextInput};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::{AlignItems, Display, FlexDirection, Overflow};
+use crate::text::text_context::TextContext;
+use crate::px;
+
+#[derive(Clone)]
+pub struct DurationPicker {
+    pub inner: Rc<RefCell<DurationPickerInner>>,
+}
+
+/// A duration picker with second precision.
+///
+/// Durations are typed as `1h 30m`, `90m`, `45s`, `1:30` or `1:30:15`, and a plain number is read as minutes.
+/// The arrow keys change the duration by the step. Typed durations that can't be parsed or are outside of the
+/// allowed range are highlighted and ignored.
+#[derive(Clone)]
+pub struct DurationPickerInner {
+    element_data: ElementData,
+    value: Duration,
+    step: Duration,
+    min: Duration,
+    max: Option<Duration>,
+    pub input: TextInput,
+}
+
+impl Default for DurationPicker {
+    fn default() -> Self {
+        Self::new(Duration::ZERO)
+    }
+}
+
+impl Element for DurationPicker {}
+
+impl Drop for DurationPickerInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for DurationPicker {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for DurationPickerInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for DurationPickerInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        draw_generic_container(self, renderer, resource_manager, text_context, scale_factor);
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        _target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        let previous_value = self.value;
+
+        let new_value = match message {
+            EventKind::TextInputChanged(changed) => {
+                let parsed = parse_duration(&changed.value).filter(|duration| self.is_in_range(*duration));
+                self.set_input_valid(parsed.is_some());
+                if let Some(duration) = parsed {
+                    self.value = duration;
+                }
+                parsed
+            }
+            EventKind::KeyboardInputEvent(key) if key.state == KeyState::Down => {
+                let duration = match key.code {
+                    Code::ArrowUp => self.value.saturating_add(self.step),
+                    Code::ArrowDown => self.value.saturating_sub(self.step),
+                    _ => return,
+                };
+                self.set_value(duration);
+                Some(self.value)
+            }
+            _ => None,
+        };
+
+        // Text changes reach `on_event` twice, so only the first call sends an event.
+        if let Some(new_value) = new_value
+            && new_value != previous_value
+        {
+            let new_event = Event::new(event.target.clone());
+            queue_event(new_event, EventKind::DurationChanged(new_value));
+        }
+    }
+
+    fn apply_clip(&mut self, clip_bounds: Option<Rectangle>) {
+        let overflow = self.style().get_overflow();
+        if overflow[0] == Overflow::Scroll || overflow[1] == Overflow::Scroll {
+            resolve_clip_for_scrollable(self, clip_bounds);
+        } else {
+            self.element_data.layout.apply_clip(clip_bounds);
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn handles_arrow_keys(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl DurationPicker {
+    pub fn new(value: Duration) -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<DurationPickerInner>>| {
+            RefCell::new(DurationPickerInner {
+                element_data: ElementData::new(me.clone(), true),
+                value,
+                step: Duration::from_secs(60),
+                min: Duration::ZERO,
+                max: None,
+                input: TextInput::new("").width(px(120)),
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        inner_mut.set_display(Display::Flex);
+        inner_mut.set_flex_direction(FlexDirection::Row);
+        inner_mut.set_align_items(Some(AlignItems::Center));
+
+        let input = inner_mut.input.clone();
+        inner_mut.push(input.inner);
+        inner_mut.set_value(value);
+
+        drop(inner_mut);
+        Self { inner }
+    }
+
+    pub fn value(self, value: Duration) -> Self {
+        self.inner.borrow_mut().set_value(value);
+        self
+    }
+
+    pub fn get_value(&self) -> Duration {
+        self.inner.borrow().value
+    }
+
+    /// Set the change of the duration when pressing the arrow keys. Defaults to one minute.
+    pub fn step(self, step: Duration) -> Self {
+        self.inner.borrow_mut().step = step;
+        self
+    }
+
+    /// Set the shortest duration that can be picked. Defaults to zero.
+    pub fn min(self, min: Duration) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.min = min;
+        let value = inner.value;
+        inner.set_value(value);
+        drop(inner);
+        self
+    }
+
+    /// Set the longest duration that can be picked. Unbounded by default.
+    pub fn max(self, max: Duration) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.max = Some(max);
+        let value = inner.value;
+        inner.set_value(value);
+        drop(inner);
+        self
+    }
+}
+
+impl DurationPickerInner {
+    pub fn set_value(&mut self, value: Duration) {
+        let value = value.max(self.min);
+        self.value = self.max.map_or(value, |max| value.min(max.max(self.min)));
+        self.input.inner.borrow_mut().set_text(&format_duration(self.value));
+        self.set_input_valid(true);
+    }
+
+    fn is_in_range(&self, duration: Duration) -> bool {
+        self.min <= duration && self.max.is_none_or(|max| duration <= max)
+    }
+
+    fn set_input_valid(&mut self, is_valid: bool) {
+        self.input
+            .clone()
+            .border_color_all(if is_valid { BORDER_COLOR } else { INVALID_BORDER_COLOR });
+    }
+}
+
+/// Parses durations such as `1h 30m`, `90m`, `45s`, `1:30`, `1:30:15` or `90`, which is read as minutes.
+fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim().to_ascii_lowercase();
+    if text.is_empty() {
+        return None;
+    }
+
+    if text.contains(':') {
+        let parts = text
+            .split(':')
+            .map(|part| part.trim().parse::<u64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        let seconds = match parts.as_slice() {
+            [hours, minutes] if *minutes < 60 => hours * 3600 + minutes * 60,
+            [hours, minutes, seconds] if *minutes < 60 && *seconds < 60 => hours * 3600 + minutes * 60 + seconds,
+            _ => return None,
+        };
+        return Some(Duration::from_secs(seconds));
+    }
+
+    if let Ok(minutes) = text.parse::<u64>() {
+        return Some(Duration::from_secs(minutes * 60));
+    }
+
+    let mut seconds = 0;
+    let mut number = String::new();
+    for character in text.chars().filter(|character| !character.is_whitespace()) {
+        if character.is_ascii_digit() {
+            number.push(character);
+            continue;
+        }
+        let unit = match character {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        seconds += number.parse::<u64>().ok()? * unit;
+        number.clear();
+    }
+    // A number without a unit at the end is ambiguous.
+    if !number.is_empty() {
+        return None;
+    }
+    Some(Duration::from_secs(seconds))
+}
+
+/// Formats a duration as `1h 30m 15s`, leaving out the parts that are zero.
+fn format_duration(duration: Duration) -> String {
+    let total_seconds = duration.as_secs();
+    let hours = total_seconds / 3600;
+    let minutes = total_seconds / 60 % 60;
+    let seconds = total_seconds % 60;
+
+    let mut parts = Vec::new();
+    if hours > 0 {
+        parts.push(format!("{hours}h"));
+    }
+    if minutes > 0 {
+        parts.push(format!("{minutes}m"));
+    }
+    if seconds > 0 {
+        parts.push(format!("{seconds}s"));
+    }
+    if parts.is_empty() {
+        return "0m".to_string();
+    }
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_unit_and_clock_formats() {
+        assert_eq!(parse_duration("1h 30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("45s"), Some(Duration::from_secs(45)));
+        assert_eq!(parse_duration("90"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("1:30"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("0:01:05"), Some(Duration::from_secs(65)));
+    }
+
+    #[test]
+    fn rejects_malformed_durations() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("1:75"), None);
+        assert_eq!(parse_duration("1h 30"), None);
+        assert_eq!(parse_duration("2 days"), None);
+    }
+
+    #[test]
+    fn formats_round_trip() {
+        for seconds in [0, 45, 60, 3600, 5415] {
+            let duration = Duration::from_secs(seconds);
+            assert_eq!(parse_duration(&format_duration(duration)), Some(duration));
+        }
+    }
+}
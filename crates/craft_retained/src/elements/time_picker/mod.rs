@@ -0,0 +1,388 @@
+//! Time and duration pickers.
+
+mod duration_picker;
+mod time_of_day;
+
+pub use duration_picker::{DurationPicker, DurationPickerInner};
+pub use time_of_day::{HourCycle, TimeOfDay};
+
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+
+use craft_primitives::geometry::{Affine, Point, Rectangle};
+use craft_renderer::renderer::Renderer;
+use craft_resource_manager::ResourceManager;
+use peniko::Color;
+use ui_events::keyboard::{Code, KeyState};
+
+use crate::app::queue_event;
+use crate::elements::element_data::ElementData;
+use crate::elements::internal_helpers::{apply_generic_container_layout, draw_generic_container, push_child_to_element};
+use crate::elements::traits::DeepClone;
+use crate::elements::{resolve_clip_for_scrollable, AsElement, Dropdown, Element, ElementInternals, Text, TextInput};
+use crate::events::{Event, EventKind};
+use crate::layout::TaffyTree;
+use crate::style::{AlignItems, Display, FlexDirection, Overflow};
+use crate::text::text_context::TextContext;
+use crate::{px, rgb};
+
+const BORDER_COLOR: Color = rgb(199, 199, 206);
+const INVALID_BORDER_COLOR: Color = rgb(220, 38, 38);
+
+#[derive(Clone)]
+pub struct TimePicker {
+    pub inner: Rc<RefCell<TimePickerInner>>,
+}
+
+/// A time of day picker.
+///
+/// The time can be typed into the text input, or picked with the hour, minute and AM/PM dropdowns.
+/// The arrow keys move the typed time by the minute step. Typed times that can't be parsed or are outside
+/// of the allowed range are highlighted and ignored.
+#[derive(Clone)]
+pub struct TimePickerInner {
+    element_data: ElementData,
+    value: TimeOfDay,
+    hour_cycle: HourCycle,
+    minute_step: u8,
+    min: TimeOfDay,
+    max: TimeOfDay,
+    pub input: TextInput,
+    pub hour_dropdown: Dropdown,
+    pub minute_dropdown: Dropdown,
+    pub period_dropdown: Dropdown,
+}
+
+impl Default for TimePicker {
+    fn default() -> Self {
+        Self::new(TimeOfDay::default())
+    }
+}
+
+impl Element for TimePicker {}
+
+impl Drop for TimePickerInner {
+    fn drop(&mut self) {
+        ElementInternals::drop(self)
+    }
+}
+
+impl AsElement for TimePicker {
+    fn as_element_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.inner.clone()
+    }
+
+    fn borrow(&self) -> Ref<'_, dyn ElementInternals> {
+        self.inner.borrow()
+    }
+
+    fn borrow_mut(&self) -> RefMut<'_, dyn ElementInternals> {
+        self.inner.borrow_mut()
+    }
+}
+
+impl crate::elements::ElementData for TimePickerInner {
+    fn element_data(&self) -> &ElementData {
+        &self.element_data
+    }
+
+    fn element_data_mut(&mut self) -> &mut ElementData {
+        &mut self.element_data
+    }
+}
+
+impl ElementInternals for TimePickerInner {
+    fn deep_clone(&self) -> Rc<RefCell<dyn ElementInternals>> {
+        self.deep_clone_internal()
+    }
+
+    fn apply_layout(
+        &mut self,
+        taffy_tree: &mut TaffyTree,
+        position: Point,
+        z_index: &mut u32,
+        transform: Affine,
+        text_context: &mut TextContext,
+        clip_bounds: Option<Rectangle>,
+        scale_factor: f64,
+    ) {
+        apply_generic_container_layout(
+            self,
+            taffy_tree,
+            position,
+            z_index,
+            transform,
+            text_context,
+            clip_bounds,
+            scale_factor,
+        );
+    }
+
+    fn draw(&mut self, renderer: &mut dyn Renderer, resource_manager: Arc<ResourceManager>, scale_factor: f64, text_context: &mut TextContext) {
+        draw_generic_container(self, renderer, resource_manager, text_context, scale_factor);
+    }
+
+    fn on_event(
+        &mut self,
+        message: &EventKind,
+        _text_context: &mut TextContext,
+        event: &mut Event,
+        target: Option<Rc<RefCell<dyn ElementInternals>>>,
+    ) {
+        let Some(target_id) = target.and_then(|target| Some(target.try_borrow().ok()?.element_data().internal_id)) else {
+            return;
+        };
+        let input_id = self.input.borrow().element_data().internal_id;
+        let previous_value = self.value;
+
+        let new_value = match message {
+            EventKind::TextInputChanged(changed) if target_id == input_id => {
+                let parsed = TimeOfDay::parse(&changed.value).filter(|time| self.is_in_range(*time));
+                self.set_input_valid(parsed.is_some());
+                if let Some(time) = parsed {
+                    self.value = time;
+                    self.update_dropdowns();
+                }
+                parsed
+            }
+            EventKind::KeyboardInputEvent(key) if key.state == KeyState::Down && target_id == input_id => {
+                let delta = match key.code {
+                    Code::ArrowUp => self.minute_step as i32,
+                    Code::ArrowDown => -(self.minute_step as i32),
+                    _ => return,
+                };
+                let time = self.clamp(TimeOfDay::from_minutes(self.value.minutes() + delta));
+                self.set_value(time);
+                Some(time)
+            }
+            EventKind::DropdownItemSelected(index) => {
+                let time = if target_id == self.hour_dropdown.borrow().element_data().internal_id {
+                    self.time_with_hour_index(*index)
+                } else if target_id == self.minute_dropdown.borrow().element_data().internal_id {
+                    TimeOfDay::new(self.value.hour(), *index as u8 * self.minute_step)
+                } else if target_id == self.period_dropdown.borrow().element_data().internal_id {
+                    TimeOfDay::new(self.value.hour() % 12 + if *index == 1 { 12 } else { 0 }, self.value.minute())
+                } else {
+                    None
+                };
+                time.map(|time| {
+                    let time = self.clamp(time);
+                    self.set_value(time);
+                    time
+                })
+            }
+            _ => None,
+        };
+
+        // Text and dropdown changes reach `on_event` twice, so only the first call sends an event.
+        if let Some(new_value) = new_value
+            && new_value != previous_value
+        {
+            let new_event = Event::new(event.target.clone());
+            queue_event(new_event, EventKind::TimeChanged(new_value));
+        }
+    }
+
+    fn apply_clip(&mut self, clip_bounds: Option<Rectangle>) {
+        let overflow = self.style().get_overflow();
+        if overflow[0] == Overflow::Scroll || overflow[1] == Overflow::Scroll {
+            resolve_clip_for_scrollable(self, clip_bounds);
+        } else {
+            self.element_data.layout.apply_clip(clip_bounds);
+        }
+    }
+
+    fn supports_children(&self) -> bool {
+        true
+    }
+
+    fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
+        push_child_to_element(self, child);
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn handles_arrow_keys(&self) -> bool {
+        true
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl TimePicker {
+    pub fn new(value: TimeOfDay) -> Self {
+        let inner = Rc::new_cyclic(|me: &Weak<RefCell<TimePickerInner>>| {
+            RefCell::new(TimePickerInner {
+                element_data: ElementData::new(me.clone(), true),
+                value,
+                hour_cycle: HourCycle::default(),
+                minute_step: 1,
+                min: TimeOfDay::from_minutes(0),
+                max: TimeOfDay::from_minutes(-1),
+                input: TextInput::new("").width(px(90)),
+                hour_dropdown: Dropdown::new().width(px(64)),
+                minute_dropdown: Dropdown::new().width(px(64)),
+                period_dropdown: Dropdown::new().width(px(64)),
+            })
+        });
+        let mut inner_mut = inner.borrow_mut();
+        inner_mut.element_data.create_layout_node(None);
+
+        inner_mut.set_display(Display::Flex);
+        inner_mut.set_flex_direction(FlexDirection::Row);
+        inner_mut.set_align_items(Some(AlignItems::Center));
+        inner_mut.set_gap(px(6), px(6));
+
+        for period in ["AM", "PM"] {
+            inner_mut.period_dropdown.clone().push(Text::new(period));
+        }
+        let input = inner_mut.input.clone();
+        inner_mut.push(input.inner);
+        let hour_dropdown = inner_mut.hour_dropdown.clone();
+        inner_mut.push(hour_dropdown.inner);
+        let minute_dropdown = inner_mut.minute_dropdown.clone();
+        inner_mut.push(minute_dropdown.inner);
+        let period_dropdown = inner_mut.period_dropdown.clone();
+        inner_mut.push(period_dropdown.inner);
+
+        inner_mut.setup_dropdowns();
+        inner_mut.set_value(value);
+
+        drop(inner_mut);
+        Self { inner }
+    }
+
+    pub fn value(self, value: TimeOfDay) -> Self {
+        self.inner.borrow_mut().set_value(value);
+        self
+    }
+
+    pub fn get_value(&self) -> TimeOfDay {
+        self.inner.borrow().value
+    }
+
+    /// Set whether hours go from 0 to 23 or from 1 to 12 with AM/PM. Defaults to 24 hours.
+    pub fn hour_cycle(self, hour_cycle: HourCycle) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.hour_cycle = hour_cycle;
+        inner.setup_dropdowns();
+        let value = inner.value;
+        inner.set_value(value);
+        drop(inner);
+        self
+    }
+
+    /// Set the minutes between the entries of the minute dropdown. Defaults to 1.
+    pub fn minute_step(self, minute_step: u8) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.minute_step = minute_step.clamp(1, 30);
+        inner.setup_dropdowns();
+        let value = inner.value;
+        inner.set_value(value);
+        drop(inner);
+        self
+    }
+
+    /// Set the earliest time that can be picked.
+    pub fn min(self, min: TimeOfDay) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.min = min;
+        let value = inner.value;
+        inner.set_value(value);
+        drop(inner);
+        self
+    }
+
+    /// Set the latest time that can be picked.
+    pub fn max(self, max: TimeOfDay) -> Self {
+        let mut inner = self.inner.borrow_mut();
+        inner.max = max;
+        let value = inner.value;
+        inner.set_value(value);
+        drop(inner);
+        self
+    }
+}
+
+impl TimePickerInner {
+    pub fn set_value(&mut self, value: TimeOfDay) {
+        self.value = self.clamp(value);
+        self.input.inner.borrow_mut().set_text(&self.value.format(self.hour_cycle));
+        self.set_input_valid(true);
+        self.update_dropdowns();
+    }
+
+    fn is_in_range(&self, time: TimeOfDay) -> bool {
+        self.min <= time && time <= self.max
+    }
+
+    fn clamp(&self, time: TimeOfDay) -> TimeOfDay {
+        time.clamp(self.min, self.max.max(self.min))
+    }
+
+    fn set_input_valid(&mut self, is_valid: bool) {
+        self.input
+            .clone()
+            .border_color_all(if is_valid { BORDER_COLOR } else { INVALID_BORDER_COLOR });
+    }
+
+    /// The time with the hour of the hour dropdown entry at `index`.
+    fn time_with_hour_index(&self, index: usize) -> Option<TimeOfDay> {
+        let hour = match self.hour_cycle {
+            HourCycle::H24 => index as u8,
+            // The entries go from 12 to 11, so the index is the hour within the half of the day.
+            HourCycle::H12 => index as u8 + if self.value.is_pm() { 12 } else { 0 },
+        };
+        TimeOfDay::new(hour, self.value.minute())
+    }
+
+    fn setup_dropdowns(&mut self) {
+        let hour_dropdown = self.hour_dropdown.clone();
+        hour_dropdown.remove_all_children();
+        for index in 0..self.hour_count() {
+            let label = match self.hour_cycle {
+                HourCycle::H24 => format!("{index:02}"),
+                HourCycle::H12 if index == 0 => "12".to_string(),
+                HourCycle::H12 => index.to_string(),
+            };
+            hour_dropdown.clone().push(Text::new(&label));
+        }
+
+        let minute_dropdown = self.minute_dropdown.clone();
+        minute_dropdown.remove_all_children();
+        for minute in (0..60).step_by(self.minute_step as usize) {
+            minute_dropdown.clone().push(Text::new(&format!("{minute:02}")));
+        }
+
+        self.period_dropdown.clone().display(match self.hour_cycle {
+            HourCycle::H24 => Display::None,
+            HourCycle::H12 => Display::Flex,
+        });
+    }
+
+    fn hour_count(&self) -> u8 {
+        match self.hour_cycle {
+            HourCycle::H24 => 24,
+            HourCycle::H12 => 12,
+        }
+    }
+
+    fn update_dropdowns(&mut self) {
+        let hour = self.value.hour() % self.hour_count();
+        self.hour_dropdown.clone().selected_item(hour as usize);
+        self.minute_dropdown
+            .clone()
+            .selected_item((self.value.minute() / self.minute_step) as usize);
+        self.period_dropdown.clone().selected_item(self.value.is_pm() as usize);
+    }
+}
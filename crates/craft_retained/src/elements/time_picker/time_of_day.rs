@@ -0,0 +1,135 @@
+use std::fmt;
+
+/// Whether hours are shown from 0 to 23 or from 1 to 12 with AM/PM.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HourCycle {
+    #[default]
+    H24,
+    H12,
+}
+
+const MINUTES_PER_DAY: i32 = 24 * 60;
+
+/// A time of day with minute precision.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimeOfDay {
+    hour: u8,
+    minute: u8,
+}
+
+impl TimeOfDay {
+    /// Returns None if the hour is above 23 or the minute above 59.
+    pub const fn new(hour: u8, minute: u8) -> Option<Self> {
+        if hour < 24 && minute < 60 {
+            Some(Self { hour, minute })
+        } else {
+            None
+        }
+    }
+
+    /// The time `minutes` after midnight, wrapping around the day.
+    pub const fn from_minutes(minutes: i32) -> Self {
+        let minutes = minutes.rem_euclid(MINUTES_PER_DAY);
+        Self {
+            hour: (minutes / 60) as u8,
+            minute: (minutes % 60) as u8,
+        }
+    }
+
+    pub const fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    pub const fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// The number of minutes since midnight.
+    pub const fn minutes(&self) -> i32 {
+        self.hour as i32 * 60 + self.minute as i32
+    }
+
+    pub const fn is_pm(&self) -> bool {
+        self.hour >= 12
+    }
+
+    /// Parses times such as `14:30`, `9`, `2:30 pm` or `12am`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().to_ascii_lowercase();
+        let (text, pm) = if let Some(text) = text.strip_suffix("pm").or_else(|| text.strip_suffix('p')) {
+            (text.trim_end(), Some(true))
+        } else if let Some(text) = text.strip_suffix("am").or_else(|| text.strip_suffix('a')) {
+            (text.trim_end(), Some(false))
+        } else {
+            (text.as_str(), None)
+        };
+
+        let (hour, minute) = match text.split_once([':', '.']) {
+            Some((hour, minute)) if minute.len() == 2 => (hour, minute.parse().ok()?),
+            Some(_) => return None,
+            None => (text, 0),
+        };
+        let hour: u8 = hour.parse().ok()?;
+
+        let hour = match pm {
+            Some(_) if !(1..=12).contains(&hour) => return None,
+            Some(pm) => hour % 12 + if pm { 12 } else { 0 },
+            None => hour,
+        };
+        Self::new(hour, minute)
+    }
+
+    pub fn format(&self, hour_cycle: HourCycle) -> String {
+        match hour_cycle {
+            HourCycle::H24 => format!("{:02}:{:02}", self.hour, self.minute),
+            HourCycle::H12 => {
+                let hour = match self.hour % 12 {
+                    0 => 12,
+                    hour => hour,
+                };
+                let period = if self.is_pm() { "PM" } else { "AM" };
+                format!("{hour}:{:02} {period}", self.minute)
+            }
+        }
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format(HourCycle::H24))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_24_hour_times() {
+        assert_eq!(TimeOfDay::parse("14:30"), TimeOfDay::new(14, 30));
+        assert_eq!(TimeOfDay::parse(" 9 "), TimeOfDay::new(9, 0));
+        assert_eq!(TimeOfDay::parse("24:00"), None);
+        assert_eq!(TimeOfDay::parse("10:7"), None);
+    }
+
+    #[test]
+    fn parses_12_hour_times() {
+        assert_eq!(TimeOfDay::parse("2:30 pm"), TimeOfDay::new(14, 30));
+        assert_eq!(TimeOfDay::parse("12am"), TimeOfDay::new(0, 0));
+        assert_eq!(TimeOfDay::parse("12 PM"), TimeOfDay::new(12, 0));
+        assert_eq!(TimeOfDay::parse("13pm"), None);
+    }
+
+    #[test]
+    fn formats_both_hour_cycles() {
+        let time = TimeOfDay::new(0, 5).unwrap();
+        assert_eq!(time.format(HourCycle::H24), "00:05");
+        assert_eq!(time.format(HourCycle::H12), "12:05 AM");
+    }
+
+    #[test]
+    fn from_minutes_wraps_around_the_day() {
+        assert_eq!(TimeOfDay::from_minutes(-1), TimeOfDay::new(23, 59).unwrap());
+        assert_eq!(TimeOfDay::from_minutes(24 * 60 + 61), TimeOfDay::new(1, 1).unwrap());
+    }
+}
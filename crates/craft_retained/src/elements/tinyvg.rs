@@ -37,6 +37,8 @@ pub struct TinyVg {
 pub struct TinyVgInner {
     is_tiny_vg_dirty: bool,
     resource_id: ResourceId,
+    /// Whether the icon points somewhere, like a back arrow, and is mirrored right to left.
+    directional: bool,
     element_data: ElementData,
 }
 
@@ -77,6 +79,10 @@ impl ElementInternals for TinyVgInner {
         self.deep_clone_internal()
     }
 
+    fn uses_resource(&self, resource_id: &ResourceId) -> bool {
+        self.resource_id == *resource_id || self.element_data.mask_image.as_ref() == Some(resource_id)
+    }
+
     fn apply_layout(
         &mut self,
         taffy_tree: &mut TaffyTree,
@@ -103,6 +109,9 @@ impl ElementInternals for TinyVgInner {
             return;
         }
 
+        self.maybe_start_effect_layer(renderer, scale_factor);
+        self.maybe_start_mask(renderer, scale_factor);
+
         // We draw the borders before we start any layers, so that we don't clip the borders.
         self.draw_borders(renderer, scale_factor);
 
@@ -121,7 +130,10 @@ impl ElementInternals for TinyVgInner {
             &resource_manager,
             self.resource_id.clone(),
             &color,
+            self.directional && self.style().is_mirrored(),
         );
+        self.maybe_end_mask(renderer, resource_manager, scale_factor, _text_context);
+        self.maybe_end_effect_layer(renderer, scale_factor);
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -139,6 +151,7 @@ impl TinyVg {
             RefCell::new(TinyVgInner {
                 is_tiny_vg_dirty: false,
                 resource_id: resource_id.clone(),
+                directional: false,
                 element_data: ElementData::new(me.clone(), false),
             })
         });
@@ -158,6 +171,7 @@ impl TinyVg {
             RefCell::new(TinyVgInner {
                 is_tiny_vg_dirty: false,
                 resource_id: ResourceId::DUMMY,
+                directional: false,
                 element_data: ElementData::new(me.clone(), false),
             })
         });
@@ -176,6 +190,14 @@ impl TinyVg {
     pub fn get_resource_id(&self) -> ResourceId {
         self.inner.borrow().get_resource_id().clone()
     }
+
+    /// Marks the icon as pointing somewhere, like a chevron or a back arrow, so that it is mirrored when laid out
+    /// right to left. See [`Direction`](crate::style::Direction).
+    pub fn directional(self, directional: bool) -> Self {
+        self.inner.borrow_mut().directional = directional;
+        self.inner.borrow_mut().invalidate_layer_cache();
+        self
+    }
 }
 
 impl TinyVgInner {
@@ -208,7 +230,12 @@ impl TinyVgInner {
         resource_manager: &Arc<ResourceManager>,
         resource_id: ResourceId,
         override_color: &Option<Color>,
+        mirrored: bool,
     ) {
+        if renderer.is_culled(&rectangle) {
+            return;
+        }
+
         let resource = resource_manager.get(&resource_id);
         if resource.is_none() {
             return;
@@ -231,11 +258,14 @@ impl TinyVgInner {
             svg_height = rectangle.height;
         }
 
-        let vg_transform = vg_transform.with_translation(kurbo::Vec2::new(rectangle.x as f64, rectangle.y as f64));
-        let vg_transform = vg_transform.pre_scale_non_uniform(
-            rectangle.width as f64 / svg_width as f64,
-            rectangle.height as f64 / svg_height as f64,
-        );
+        // Mirrored icons are drawn from the right edge of the rectangle with their x axis flipped.
+        let (left, scale_x) = if mirrored {
+            (rectangle.x + rectangle.width, -(rectangle.width as f64) / svg_width as f64)
+        } else {
+            (rectangle.x, rectangle.width as f64 / svg_width as f64)
+        };
+        let vg_transform = vg_transform.with_translation(kurbo::Vec2::new(left as f64, rectangle.y as f64));
+        let vg_transform = vg_transform.pre_scale_non_uniform(scale_x, rectangle.height as f64 / svg_height as f64);
 
         let old_transform = renderer.get_transform();
         renderer.set_transform(vg_transform * old_transform);
@@ -1,5 +1,10 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
 use craft_primitives::Color;
 use craft_primitives::geometry::ElementBox;
+use craft_resource_manager::ResourceId;
 use smol_str::SmolStr;
 
 use ui_events::pointer::PointerId;
@@ -10,9 +15,23 @@ use winit::event::{DeviceId, ElementState, MouseButton};
 use crate::CraftError;
 use crate::app::queue_window_event;
 use crate::elements::scrollable::{ScrollOptions, ScrollState};
-use crate::elements::{AsElement, DynElement};
-use crate::events::{CheckboxToggledHandler, KeyboardInputHandler, PointerCaptureHandler, PointerEnterHandler, PointerEventHandler, PointerLeaveHandler, PointerUpdateHandler, RadioValueChangedHandler, ScrollHandler, SliderValueChangedHandler, TextInputChangedHandler};
-use crate::style::{AlignItems, BoxShadow, BoxSizing, Display, FlexDirection, FlexWrap, FontFamily, FontStyle, FontWeight, JustifyContent, Overflow, Position, ScrollbarColor, TextAlign, Underline, Unit};
+use crate::elements::{AsElement, DynElement, ElementData};
+use crate::scroll_animation::{ScrollAnimation, add_scroll_animation};
+use crate::tree_updates::ElementHandle;
+use crate::events::{CellEditedHandler, CheckboxToggledHandler, ColorPickedHandler, DragListItemMovedHandler, DropdownItemSelectedHandler, DurationChangedHandler, ElementCapturedHandler, FilesChangedHandler, FilesDroppedHandler, FilesSelectedHandler, HoverIntent, HoverIntentEndedHandler, HoverIntentHandler, HttpStreamHandler, ImageCropChangedHandler, JumpListActivatedHandler, KeyboardInputHandler, KeyringHandler, KioskResetHandler, ListBoxSelectionChangedHandler, MapClickedHandler, NetworkStatusChangedHandler, PdfPageChangedHandler, PointerCaptureHandler, PointerEnterHandler, PointerEventHandler, PointerLeaveHandler, PointerUpdateHandler, PowerStatusChangedHandler, ProcessHandler, PropertyChangedHandler, RadioValueChangedHandler, RatingChangedHandler, RecentFilesChangedHandler, ScrollHandler, SegmentedInputChangedHandler, SelectionAreaChangedHandler, SliderValueChangedHandler, SpeechFinishedHandler, SpellingSuggestionsHandler, StepperFinishedHandler, StepperStepChangedHandler, SystemSettingsChangedHandler, TagsChangedHandler, TextInputChangedHandler, TimeChangedHandler, UpdateAvailableHandler, UpdateDownloadedHandler, UserActiveHandler, UserIdleHandler, ViewportChangedHandler, WebSocketHandler};
+#[cfg(all(feature = "http_stream", not(target_arch = "wasm32")))]
+use crate::http_stream::{HttpStream, HttpStreamRequest};
+use crate::keyring::Keyring;
+use crate::layout::custom_layout::CustomLayout;
+#[cfg(feature = "speech")]
+use crate::speech::{SpeechError, SpeechOptions};
+#[cfg(all(feature = "websocket", not(target_arch = "wasm32")))]
+use crate::websocket::{WebSocket, WebSocketOptions};
+#[cfg(all(feature = "file_watch", not(target_arch = "wasm32")))]
+use {crate::file_watch::{FileWatch, FileWatchOptions}, std::path::Path};
+#[cfg(not(target_arch = "wasm32"))]
+use {crate::process::{ChildProcess, ProcessCommand}, std::io};
+use crate::style::{AlignItems, BoxShadow, BoxSizing, ColorFilter, CornerRadius, Direction, Display, FlexDirection, FlexWrap, FontFamily, FontStyle, FontWeight, JustifyContent, Mix, Overflow, Position, ScrollbarColor, Style, TextAlign, Underline, Unit};
 
 /// Exposes a fluent/builder-pattern like API for elements.
 /// Setters in this trait return Self and have no prefix.
@@ -75,6 +94,26 @@ pub trait Element: Clone + AsElement {
         self
     }
 
+    /// Called once the pointer rested on the element, for UI that opens on hover, like tooltips.
+    ///
+    /// Unlike [`Element::on_pointer_enter`], this isn't called when the pointer only passes over the element.
+    fn on_hover_intent(self, on_hover_intent: HoverIntentHandler) -> Self {
+        self.borrow_mut().on_hover_intent(on_hover_intent);
+        self
+    }
+
+    /// Called when the pointer leaves the element after [`Element::on_hover_intent`] was called.
+    fn on_hover_intent_ended(self, on_hover_intent_ended: HoverIntentEndedHandler) -> Self {
+        self.borrow_mut().on_hover_intent_ended(on_hover_intent_ended);
+        self
+    }
+
+    /// Sets how long, and how still, the pointer has to rest on the element for [`Element::on_hover_intent`].
+    fn hover_intent(self, hover_intent: HoverIntent) -> Self {
+        self.borrow_mut().set_hover_intent(hover_intent);
+        self
+    }
+
     fn on_radio_value_changed(self, on_radio_value_changed: RadioValueChangedHandler) -> Self {
         self.borrow_mut().on_radio_value_changed(on_radio_value_changed);
         self
@@ -90,6 +129,210 @@ pub trait Element: Clone + AsElement {
         self
     }
 
+    fn on_dropdown_item_selected(self, on_dropdown_item_selected: DropdownItemSelectedHandler) -> Self {
+        self.borrow_mut().on_dropdown_item_selected(on_dropdown_item_selected);
+        self
+    }
+
+    fn on_drag_list_item_moved(self, on_drag_list_item_moved: DragListItemMovedHandler) -> Self {
+        self.borrow_mut().on_drag_list_item_moved(on_drag_list_item_moved);
+        self
+    }
+
+    fn on_list_box_selection_changed(self, on_list_box_selection_changed: ListBoxSelectionChangedHandler) -> Self {
+        self.borrow_mut().on_list_box_selection_changed(on_list_box_selection_changed);
+        self
+    }
+
+    fn on_selection_area_changed(self, on_selection_area_changed: SelectionAreaChangedHandler) -> Self {
+        self.borrow_mut().on_selection_area_changed(on_selection_area_changed);
+        self
+    }
+
+    fn on_stepper_step_changed(self, on_stepper_step_changed: StepperStepChangedHandler) -> Self {
+        self.borrow_mut().on_stepper_step_changed(on_stepper_step_changed);
+        self
+    }
+
+    fn on_stepper_finished(self, on_stepper_finished: StepperFinishedHandler) -> Self {
+        self.borrow_mut().on_stepper_finished(on_stepper_finished);
+        self
+    }
+
+    fn on_property_changed(self, on_property_changed: PropertyChangedHandler) -> Self {
+        self.borrow_mut().on_property_changed(on_property_changed);
+        self
+    }
+
+    fn on_cell_edited(self, on_cell_edited: CellEditedHandler) -> Self {
+        self.borrow_mut().on_cell_edited(on_cell_edited);
+        self
+    }
+
+    fn on_viewport_changed(self, on_viewport_changed: ViewportChangedHandler) -> Self {
+        self.borrow_mut().on_viewport_changed(on_viewport_changed);
+        self
+    }
+
+    fn on_image_crop_changed(self, on_image_crop_changed: ImageCropChangedHandler) -> Self {
+        self.borrow_mut().on_image_crop_changed(on_image_crop_changed);
+        self
+    }
+
+    fn on_color_picked(self, on_color_picked: ColorPickedHandler) -> Self {
+        self.borrow_mut().on_color_picked(on_color_picked);
+        self
+    }
+
+    fn on_map_clicked(self, on_map_clicked: MapClickedHandler) -> Self {
+        self.borrow_mut().on_map_clicked(on_map_clicked);
+        self
+    }
+
+    fn on_pdf_page_changed(self, on_pdf_page_changed: PdfPageChangedHandler) -> Self {
+        self.borrow_mut().on_pdf_page_changed(on_pdf_page_changed);
+        self
+    }
+
+    fn on_element_captured(self, on_element_captured: ElementCapturedHandler) -> Self {
+        self.borrow_mut().on_element_captured(on_element_captured);
+        self
+    }
+
+    fn on_rating_changed(self, on_rating_changed: RatingChangedHandler) -> Self {
+        self.borrow_mut().on_rating_changed(on_rating_changed);
+        self
+    }
+
+    fn on_tags_changed(self, on_tags_changed: TagsChangedHandler) -> Self {
+        self.borrow_mut().on_tags_changed(on_tags_changed);
+        self
+    }
+
+    fn on_segmented_input_changed(self, on_segmented_input_changed: SegmentedInputChangedHandler) -> Self {
+        self.borrow_mut().on_segmented_input_changed(on_segmented_input_changed);
+        self
+    }
+
+    fn on_time_changed(self, on_time_changed: TimeChangedHandler) -> Self {
+        self.borrow_mut().on_time_changed(on_time_changed);
+        self
+    }
+
+    fn on_duration_changed(self, on_duration_changed: DurationChangedHandler) -> Self {
+        self.borrow_mut().on_duration_changed(on_duration_changed);
+        self
+    }
+
+    fn on_files_dropped(self, on_files_dropped: FilesDroppedHandler) -> Self {
+        self.borrow_mut().on_files_dropped(on_files_dropped);
+        self
+    }
+
+    fn on_files_selected(self, on_files_selected: FilesSelectedHandler) -> Self {
+        self.borrow_mut().on_files_selected(on_files_selected);
+        self
+    }
+
+    fn on_spelling_suggestions(self, on_spelling_suggestions: SpellingSuggestionsHandler) -> Self {
+        self.borrow_mut().on_spelling_suggestions(on_spelling_suggestions);
+        self
+    }
+
+    fn on_speech_finished(self, on_speech_finished: SpeechFinishedHandler) -> Self {
+        self.borrow_mut().on_speech_finished(on_speech_finished);
+        self
+    }
+
+    /// Called with the events of the WebSocket connections opened with [`Element::connect_web_socket`].
+    fn on_web_socket(self, on_web_socket: WebSocketHandler) -> Self {
+        self.borrow_mut().on_web_socket(on_web_socket);
+        self
+    }
+
+    /// Called with the changes of the files watched with [`Element::watch_files`].
+    fn on_files_changed(self, on_files_changed: FilesChangedHandler) -> Self {
+        self.borrow_mut().on_files_changed(on_files_changed);
+        self
+    }
+
+    /// Called with the output and exit of the processes spawned with [`Element::spawn_process`].
+    fn on_process(self, on_process: ProcessHandler) -> Self {
+        self.borrow_mut().on_process(on_process);
+        self
+    }
+
+    /// Called with the results of the requests sent with [`Element::keyring`].
+    fn on_keyring(self, on_keyring: KeyringHandler) -> Self {
+        self.borrow_mut().on_keyring(on_keyring);
+        self
+    }
+
+    /// Called with the response of the requests sent with [`Element::stream_http`] as it arrives.
+    fn on_http_stream(self, on_http_stream: HttpStreamHandler) -> Self {
+        self.borrow_mut().on_http_stream(on_http_stream);
+        self
+    }
+
+    fn on_system_settings_changed(self, on_system_settings_changed: SystemSettingsChangedHandler) -> Self {
+        self.borrow_mut().on_system_settings_changed(on_system_settings_changed);
+        self
+    }
+
+    /// Called on windows when the user didn't use the app for one of the idle thresholds, see [`crate::idle`].
+    fn on_user_idle(self, on_user_idle: UserIdleHandler) -> Self {
+        self.borrow_mut().on_user_idle(on_user_idle);
+        self
+    }
+
+    /// Called on windows when the user uses the app again after [`Element::on_user_idle`] was called.
+    fn on_user_active(self, on_user_active: UserActiveHandler) -> Self {
+        self.borrow_mut().on_user_active(on_user_active);
+        self
+    }
+
+    /// Called on windows when a kiosk was left alone for its reset time. See [`crate::kiosk`].
+    fn on_kiosk_reset(self, on_kiosk_reset: KioskResetHandler) -> Self {
+        self.borrow_mut().on_kiosk_reset(on_kiosk_reset);
+        self
+    }
+
+    /// Called on windows when the battery or power saving state of the device changed, see [`crate::power`].
+    fn on_power_status_changed(self, on_power_status_changed: PowerStatusChangedHandler) -> Self {
+        self.borrow_mut().on_power_status_changed(on_power_status_changed);
+        self
+    }
+
+    /// Called on windows when the device went offline or came back online, see [`crate::network`].
+    fn on_network_status_changed(self, on_network_status_changed: NetworkStatusChangedHandler) -> Self {
+        self.borrow_mut().on_network_status_changed(on_network_status_changed);
+        self
+    }
+
+    /// Called on windows when the user chose an entry of the jump list or dock menu, see [`crate::shell`].
+    fn on_jump_list_activated(self, on_jump_list_activated: JumpListActivatedHandler) -> Self {
+        self.borrow_mut().on_jump_list_activated(on_jump_list_activated);
+        self
+    }
+
+    /// Called on windows when the recent files changed, see [`crate::recent_files`].
+    fn on_recent_files_changed(self, on_recent_files_changed: RecentFilesChangedHandler) -> Self {
+        self.borrow_mut().on_recent_files_changed(on_recent_files_changed);
+        self
+    }
+
+    /// Called on windows when a newer version of the app was found, see the `update` module.
+    fn on_update_available(self, on_update_available: UpdateAvailableHandler) -> Self {
+        self.borrow_mut().on_update_available(on_update_available);
+        self
+    }
+
+    /// Called on windows when the installer of an update was downloaded, or failed to download.
+    fn on_update_downloaded(self, on_update_downloaded: UpdateDownloadedHandler) -> Self {
+        self.borrow_mut().on_update_downloaded(on_update_downloaded);
+        self
+    }
+
     fn id(self, id: &str) -> Self {
         self.borrow_mut().set_id(id);
         self
@@ -99,6 +342,40 @@ pub trait Element: Clone + AsElement {
         self.borrow().get_id()
     }
 
+    /// Returns an id derived from where the element is in its tree, which is the same across runs. See
+    /// [`ElementData::stable_id`](crate::elements::ElementData::stable_id).
+    fn get_stable_id(&self) -> Option<u64> {
+        self.borrow().stable_id()
+    }
+
+    /// Places the children with `custom_layout` instead of with the flexbox or block layout of the element's style.
+    /// See [`crate::layout::custom_layout`].
+    fn custom_layout(self, custom_layout: impl CustomLayout) -> Self {
+        self.borrow_mut().set_custom_layout(Some(Rc::new(RefCell::new(custom_layout))));
+        self
+    }
+
+    /// Associates data with the element, like the id of the record a row shows, replacing the data set before.
+    fn user_data<T: 'static>(self, user_data: T) -> Self {
+        self.borrow_mut().set_user_data(Some(Rc::new(user_data)));
+        self
+    }
+
+    /// Returns the data set with [`Element::user_data`], or `None` if there is none or it isn't a `T`.
+    fn get_user_data<T: 'static>(&self) -> Option<Rc<T>> {
+        self.borrow().get_user_data()?.downcast().ok()
+    }
+
+    fn clear_user_data(&self) {
+        self.borrow_mut().set_user_data(None);
+    }
+
+    /// Returns a handle that refers to this element from other threads, see
+    /// [`TreeBatch`](crate::tree_updates::TreeBatch).
+    fn get_handle(&self) -> ElementHandle {
+        ElementHandle(self.borrow().element_data().internal_id)
+    }
+
     fn on_pointer_button_down(self, on_pointer_button_down: PointerEventHandler) -> Self {
         self.borrow_mut().on_pointer_button_down(on_pointer_button_down);
         self
@@ -173,6 +450,17 @@ pub trait Element: Clone + AsElement {
         self.borrow_mut().get_scroll_state()
     }
 
+    /// Drives `animation` on `target` with the scroll position of this element, e.g. for a parallax header or content
+    /// that fades in as it is scrolled into view. See [`crate::scroll_animation`].
+    fn scroll_animation(self, target: &impl AsElement, animation: ScrollAnimation) -> Self {
+        add_scroll_animation(
+            Rc::downgrade(&self.as_element_rc()),
+            Rc::downgrade(&target.as_element_rc()),
+            animation,
+        );
+        self
+    }
+
     fn display(self, display: Display) -> Self {
         self.borrow_mut().set_display(display);
         self
@@ -413,23 +701,34 @@ pub trait Element: Clone + AsElement {
         self
     }
 
-    fn border_radius(self, top: (f32, f32), right: (f32, f32), bottom: (f32, f32), left: (f32, f32)) -> Self {
-        self.borrow_mut().set_border_radius(top, right, bottom, left);
+    /// Sets the radii of the top-left, top-right, bottom-right and bottom-left corners.
+    ///
+    /// Accepts `(f32, f32)` pixel pairs, a [`Unit`] for circular corners or a `(Unit, Unit)` pair for elliptical
+    /// corners. Radii that don't fit are scaled down following the CSS overlap rules.
+    fn border_radius(
+        self,
+        top: impl Into<CornerRadius>,
+        right: impl Into<CornerRadius>,
+        bottom: impl Into<CornerRadius>,
+        left: impl Into<CornerRadius>,
+    ) -> Self {
+        self.borrow_mut()
+            .set_border_radius(top.into(), right.into(), bottom.into(), left.into());
         self
     }
 
-    fn border_radius_all(self, value: (f32, f32)) -> Self {
-        self.borrow_mut().set_border_radius_all(value);
+    fn border_radius_all(self, value: impl Into<CornerRadius>) -> Self {
+        self.borrow_mut().set_border_radius_all(value.into());
         self
     }
 
-    fn border_radius_vertical(self, value: (f32, f32)) -> Self {
-        self.borrow_mut().set_border_radius_vertical(value);
+    fn border_radius_vertical(self, value: impl Into<CornerRadius>) -> Self {
+        self.borrow_mut().set_border_radius_vertical(value.into());
         self
     }
 
-    fn border_radius_horizontal(self, value: (f32, f32)) -> Self {
-        self.borrow_mut().set_border_radius_horizontal(value);
+    fn border_radius_horizontal(self, value: impl Into<CornerRadius>) -> Self {
+        self.borrow_mut().set_border_radius_horizontal(value.into());
         self
     }
 
@@ -458,11 +757,150 @@ pub trait Element: Clone + AsElement {
         self
     }
 
+    /// Draws the element on the overlay layer with a shadow matching `elevation`, for popups such as menus and
+    /// tooltips that must escape the clipping of their parents.
+    fn elevation(self, elevation: f32) -> Self {
+        self.borrow_mut().set_elevation(elevation);
+        self
+    }
+
+    /// Snap borders and backgrounds to physical pixels so hairlines stay crisp at fractional scale factors.
+    ///
+    /// Enabled by default.
+    fn pixel_snapping(self, pixel_snapping: bool) -> Self {
+        self.borrow_mut().set_pixel_snapping(pixel_snapping);
+        self
+    }
+
+    /// Thicken small text to keep it legible on low-DPI screens, from `0.0` to `1.0`.
+    ///
+    /// `None` uses [`CraftOptions::text_contrast`](crate::CraftOptions::text_contrast).
+    fn text_contrast(self, text_contrast: Option<f32>) -> Self {
+        self.borrow_mut().set_text_contrast(text_contrast);
+        self
+    }
+
+    /// The background color of selected text.
+    fn selection_color(self, selection_color: Color) -> Self {
+        self.borrow_mut().set_selection_color(selection_color);
+        self
+    }
+
+    /// The color of selected text. `None` keeps the text color.
+    fn selection_text_color(self, selection_text_color: Option<Color>) -> Self {
+        self.borrow_mut().set_selection_text_color(selection_text_color);
+        self
+    }
+
+    /// The background color of selected text while its window or element doesn't have focus.
+    ///
+    /// `None` uses a muted variant of the selection color.
+    fn inactive_selection_color(self, inactive_selection_color: Option<Color>) -> Self {
+        self.borrow_mut().set_inactive_selection_color(inactive_selection_color);
+        self
+    }
+
+    /// The color of the text cursor. `None` uses the text color.
+    fn cursor_color(self, cursor_color: Option<Color>) -> Self {
+        self.borrow_mut().set_cursor_color(cursor_color);
+        self
+    }
+
+    /// The width of the text cursor in logical pixels. Defaults to `1.0`.
+    fn cursor_width(self, cursor_width: f32) -> Self {
+        self.borrow_mut().set_cursor_width(cursor_width);
+        self
+    }
+
+    /// How long the text cursor stays shown, and then hidden, while it blinks. Defaults to 500ms.
+    ///
+    /// `Duration::ZERO` keeps the cursor shown. The cursor doesn't blink with
+    /// [`CraftOptions::reduced_motion`](crate::CraftOptions::reduced_motion) either.
+    fn cursor_blink_interval(self, cursor_blink_interval: Duration) -> Self {
+        self.borrow_mut().set_cursor_blink_interval(cursor_blink_interval);
+        self
+    }
+
+    /// Replaces every style property of the element, e.g. with a [`Style`] loaded from a theme file.
+    fn style(self, style: Style) -> Self {
+        self.borrow_mut().set_style(style);
+        self
+    }
+
+    /// Sets how the element is blended with the content behind it.
+    fn mix_blend_mode(self, mix_blend_mode: Mix) -> Self {
+        self.borrow_mut().set_mix_blend_mode(mix_blend_mode);
+        self
+    }
+
+    /// Sets the color filters applied, in order, to the element and its children.
+    fn color_filters(self, color_filters: Vec<ColorFilter>) -> Self {
+        self.borrow_mut().set_color_filters(color_filters);
+        self
+    }
+
+    /// Lays the element and the elements inside it out right to left or left to right. See [`Direction`].
+    fn direction(self, direction: Direction) -> Self {
+        self.borrow_mut().set_direction(direction);
+        self
+    }
+
+    /// Whether the paddings, margins and directional icons of the element are mirrored right to left.
+    ///
+    /// Enabled by default.
+    fn auto_mirror(self, auto_mirror: bool) -> Self {
+        self.borrow_mut().set_auto_mirror(auto_mirror);
+        self
+    }
+
+    /// Masks the element by the alpha of `mask`, e.g. a rounded container with a blurred box shadow for a feathered
+    /// circular avatar.
+    ///
+    /// The mask is laid out over the element's padding box and is never drawn on its own. Only elements that accept
+    /// children support mask elements, the others return [`CraftError::ChildrenNotSupported`] and can use
+    /// [`Element::mask_image`] instead.
+    fn mask(self, mask: impl AsElement) -> Result<Self, CraftError> {
+        let mask_rc = mask.as_element_rc();
+        self.as_element_rc().borrow_mut().set_mask(mask_rc)?;
+        Ok(self)
+    }
+
+    /// Masks the element by the alpha of an image stretched over its border box, e.g. a gradient for a fade out.
+    fn mask_image(self, resource_id: ResourceId) -> Self {
+        self.borrow_mut().set_mask_image(Some(resource_id));
+        self
+    }
+
+    /// Draws this element and its subtree into an offscreen layer, and composites the layer each frame until the
+    /// subtree changes.
+    ///
+    /// Useful for heavy subtrees, such as a chart or a markdown document, that rarely change while other parts of the
+    /// window animate. Text in the layer is antialiased in grayscale, and popups of the subtree are drawn into the
+    /// layer rather than above the window.
+    fn cache_as_layer(self) -> Self {
+        self.borrow_mut().set_cache_as_layer(true);
+        self
+    }
+
+    /// Forces the layers of the element and its ancestors, and the window, to be drawn again on the next frame. See
+    /// [`Element::cache_as_layer`] and [`crate::scene`].
+    fn invalidate_layer(self) -> Self {
+        self.borrow().invalidate_layer_cache();
+        self
+    }
+
     fn focus(self) -> Self {
         self.borrow_mut().focus();
         self
     }
 
+    /// Lets spatial navigation move the focus to the element, like a card or a button of a TV interface. See
+    /// [`crate::spatial_navigation`].
+    fn focusable(self, focusable: bool) -> Self {
+        self.borrow_mut().set_focusable(focusable);
+        self
+    }
+
     fn is_focused(&self) -> bool {
         self.borrow().is_focused()
     }
@@ -472,6 +910,59 @@ pub trait Element: Clone + AsElement {
         self
     }
 
+    /// Sends the color under the user's next click in the window to this element. See
+    /// [`ElementInternals::pick_color`].
+    fn pick_color(&self) {
+        self.borrow_mut().pick_color();
+    }
+
+    /// Draws this element to an image after the next frame, at its laid-out size times `scale`. See
+    /// [`ElementInternals::capture`].
+    fn capture(&self, scale: f64) {
+        self.borrow_mut().capture(scale);
+    }
+
+    /// Speaks `text`, and sends a [`EventKind::SpeechFinished`] event to this element when it is done. See
+    /// [`ElementInternals::speak`].
+    #[cfg(feature = "speech")]
+    fn speak(&self, text: &str, options: &SpeechOptions) -> Result<u64, SpeechError> {
+        self.borrow().speak(text, options)
+    }
+
+    /// Opens a WebSocket connection that sends [`EventKind::WebSocket`] events to this element. See
+    /// [`ElementInternals::connect_web_socket`].
+    #[cfg(all(feature = "websocket", not(target_arch = "wasm32")))]
+    fn connect_web_socket(&self, url: &str, options: WebSocketOptions) -> WebSocket {
+        self.borrow().connect_web_socket(url, options)
+    }
+
+    /// Sends a request, and sends its response to this element in [`EventKind::HttpStream`] events as it arrives.
+    /// See [`ElementInternals::stream_http`].
+    #[cfg(all(feature = "http_stream", not(target_arch = "wasm32")))]
+    fn stream_http(&self, request: HttpStreamRequest) -> HttpStream {
+        self.borrow().stream_http(request)
+    }
+
+    /// Watches a file or directory, and sends its changes to this element in [`EventKind::FilesChanged`] events. See
+    /// [`ElementInternals::watch_files`].
+    #[cfg(all(feature = "file_watch", not(target_arch = "wasm32")))]
+    fn watch_files(&self, path: impl AsRef<Path>, options: FileWatchOptions) -> io::Result<FileWatch> {
+        self.borrow().watch_files(path.as_ref(), options)
+    }
+
+    /// Spawns a child process that sends its output and exit to this element in [`EventKind::Process`] events. See
+    /// [`ElementInternals::spawn_process`].
+    #[cfg(not(target_arch = "wasm32"))]
+    fn spawn_process(&self, command: ProcessCommand) -> io::Result<ChildProcess> {
+        self.borrow().spawn_process(&command)
+    }
+
+    /// The secrets of `service`, like `com.example.mail`, with results sent to this element in [`EventKind::Keyring`]
+    /// events. See [`ElementInternals::keyring`].
+    fn keyring(&self, service: &str) -> Keyring {
+        self.borrow().keyring(service)
+    }
+
     fn get_computed_box_transformed(&self) -> ElementBox {
         self.borrow().get_computed_box_transformed()
     }
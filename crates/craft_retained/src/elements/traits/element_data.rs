@@ -2,6 +2,7 @@ use std::cell::RefCell;
 use std::rc::{Rc, Weak};
 
 use crate::elements::ElementInternals;
+use crate::elements::element_id::{PathSegment, stable_id_of_path};
 
 /// Used as a super trait and forces implementations to
 /// support the retrieval and mutation of `ElementData`(struct).
@@ -17,6 +18,40 @@ pub trait ElementData {
         self.element_data().internal_id
     }
 
+    /// Returns an id derived from where the element is in its tree, which, unlike [`ElementData::id`], is the same
+    /// across runs and hot reloads as long as the tree is built the same way. Use it to save scroll positions, focus
+    /// or selections and find the element again with
+    /// [`Window::find_by_stable_id`](crate::elements::Window::find_by_stable_id).
+    ///
+    /// The path from the root uses the id set with [`Element::id`](crate::elements::Element::id) where there is one
+    /// and the index among siblings otherwise, so elements with an id keep their stable id when siblings are added,
+    /// removed or moved. Give windows an id when an app has several.
+    ///
+    /// Returns `None` while an ancestor of the element is mutably borrowed.
+    fn stable_id(&self) -> Option<u64> {
+        let mut path = vec![match &self.element_data().id {
+            Some(key) => PathSegment::Key(key.clone()),
+            None => PathSegment::Index(0),
+        }];
+        let mut current = (self as *const Self).cast::<()>();
+        let mut parent = self.parent().and_then(|parent| parent.upgrade());
+        while let Some(element) = parent {
+            let element_ref = element.try_borrow().ok()?;
+            let index = element_ref
+                .children()
+                .iter()
+                .position(|child| child.as_ptr().cast::<()>().cast_const() == current)?;
+            if let PathSegment::Index(last) = path.last_mut().unwrap() {
+                *last = index;
+            }
+            path.push(PathSegment::of(&*element_ref, 0));
+            current = element.as_ptr().cast::<()>().cast_const();
+            parent = element_ref.parent().and_then(|parent| parent.upgrade());
+        }
+        path.reverse();
+        Some(stable_id_of_path(&path))
+    }
+
     /// Returns the element's parent element.
     fn parent(&self) -> Option<Weak<RefCell<dyn ElementInternals>>> {
         self.element_data().parent.clone()
@@ -2,26 +2,48 @@ use std::any::Any;
 use std::cell::RefCell;
 use std::rc::{Rc, Weak};
 use std::sync::Arc;
+use std::time::Duration;
 
 #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
 use accesskit::{Action, Role};
 
 use ui_events::pointer::PointerId;
+use winit::window::CursorIcon;
 
-use crate::app::{ELEMENTS, FOCUS, TAFFY_TREE};
+use crate::app::{CAPTURES, COLOR_PICK, ELEMENTS, FOCUS, PENDING_RESOURCES, TAFFY_TREE};
+use crate::document::{record_element_change, Attribute, Mutation};
 use crate::elements::scrollable::{draw_scrollbar, ScrollState};
+use crate::elements::layer_cache::{LayerCache, draw_with_layer_cache};
+use crate::elements::internal_helpers::{remove_child_from_element, swap_children_of_element};
 use crate::elements::{ElementData, ScrollOptions, WindowInternal};
-use crate::events::pointer_capture::PointerCapture;
-use crate::events::{CheckboxToggledHandler, DropdownItemSelectedHandler, Event, EventKind, KeyboardInputHandler, PointerCaptureHandler, PointerEnterHandler, PointerEventHandler, PointerLeaveHandler, PointerUpdateHandler, RadioValueChangedHandler, ScrollHandler, SliderValueChangedHandler, TextInputChangedHandler};
+use crate::events::pointer_capture::{captured_pointer_id, PointerCapture};
+use crate::events::{CellEditedHandler, CheckboxToggledHandler, ColorPickedHandler, DragListItemMovedHandler, DropdownItemSelectedHandler, DurationChangedHandler, ElementCapturedHandler, Event, EventKind, FilesChangedHandler, FilesDroppedHandler, FilesSelectedHandler, HoverIntent, HoverIntentEndedHandler, HoverIntentHandler, HttpStreamHandler, ImageCropChangedHandler, JumpListActivatedHandler, KeyboardInputHandler, KeyringHandler, KioskResetHandler, ListBoxSelectionChangedHandler, MapClickedHandler, NetworkStatusChangedHandler, PdfPageChangedHandler, PointerCaptureHandler, PointerEnterHandler, PointerEventHandler, PointerLeaveHandler, PointerUpdateHandler, PowerStatusChangedHandler, ProcessHandler, PropertyChangedHandler, RadioValueChangedHandler, RatingChangedHandler, RecentFilesChangedHandler, ScrollHandler, SegmentedInputChangedHandler, SelectionAreaChangedHandler, SliderValueChangedHandler, SpeechFinishedHandler, SpellingSuggestionsHandler, StepperFinishedHandler, StepperStepChangedHandler, SystemSettingsChangedHandler, TagsChangedHandler, TextInputChangedHandler, TimeChangedHandler, UpdateAvailableHandler, UpdateDownloadedHandler, UserActiveHandler, UserIdleHandler, ViewportChangedHandler, WebSocketHandler};
+use crate::keyring::Keyring;
 use crate::layout::TaffyTree;
-use crate::style::{AlignItems, BoxShadow, BoxSizing, Display, FlexDirection, FlexWrap, FontFamily, FontStyle, FontWeight, JustifyContent, Overflow, Position, ScrollbarColor, Style, TextAlign, Underline, Unit};
+use crate::layout::custom_layout::{CustomLayout, CustomLayoutState};
+use crate::scene::scene_changed;
+#[cfg(all(feature = "http_stream", not(target_arch = "wasm32")))]
+use crate::http_stream::{HttpStream, HttpStreamRequest};
+#[cfg(feature = "speech")]
+use crate::speech::{SpeechError, SpeechOptions};
+#[cfg(all(feature = "websocket", not(target_arch = "wasm32")))]
+use crate::websocket::{WebSocket, WebSocketOptions};
+#[cfg(all(feature = "file_watch", not(target_arch = "wasm32")))]
+use {crate::file_watch::{FileWatch, FileWatchOptions}, std::path::Path};
+#[cfg(not(target_arch = "wasm32"))]
+use {crate::process::{ChildProcess, ProcessCommand}, std::io};
+use crate::style::{rounded_rect_contains, AlignItems, BoxShadow, BoxSizing, CornerRadius, Direction, Display, FlexDirection, FlexWrap, FontFamily, FontStyle, FontWeight, JustifyContent, Overflow, Position, ScrollbarColor, Style, TextAlign, Underline, Unit};
 use crate::text::text_context::TextContext;
 use crate::{Color, CraftError};
 use craft_primitives::geometry::{Affine, ElementBox, Point, Rectangle, TrblRectangle};
+use craft_renderer::effects::ColorFilter;
 use craft_renderer::renderer::Renderer;
-use craft_resource_manager::ResourceManager;
+use peniko::{BlendMode, Compose, Mix};
+use craft_resource_manager::resource_type::ResourceType;
+use craft_resource_manager::{ResourceId, ResourceManager};
 
-/// Internal element methods that should typically be ignored by users. Public for custom elements.
+/// Internal element methods that should typically be ignored by users. Elements implemented outside of craft implement
+/// [`CustomElement`](crate::elements::CustomElement) instead.
 ///
 /// Drop is required to clean up any taffy nodes allocated by the element.
 #[allow(drop_bounds)]
@@ -85,10 +107,70 @@ pub trait ElementInternals: ElementData + Any + Drop {
         text_context: &mut TextContext,
     ) {
         for child in self.children() {
-            child
-                .borrow_mut()
-                .draw(renderer, resource_manager.clone(), scale_factor, text_context);
+            let mut child = child.borrow_mut();
+            if child.element_data().is_mask || child.is_outside_viewport(renderer, scale_factor) {
+                continue;
+            }
+            if child.element_data().layer_cache.is_some() {
+                draw_with_layer_cache(&mut *child, renderer, resource_manager.clone(), scale_factor, text_context);
+            } else {
+                child.draw(renderer, resource_manager.clone(), scale_factor, text_context);
+            }
+        }
+    }
+
+    /// Returns the transformed border box of the element, expanded to include its outset box shadows.
+    fn visual_bounds(&self) -> Rectangle {
+        let element_data = self.element_data();
+        let border_rectangle = element_data.layout.computed_box_transformed.border_rectangle();
+        let shadow_extent = element_data
+            .style
+            .get_computed_box_shadows()
+            .iter()
+            .filter(|shadow| !shadow.inset)
+            .map(|shadow| {
+                (shadow.offset_x.abs().max(shadow.offset_y.abs()) + shadow.blur_radius + shadow.spread_radius) as f32
+            })
+            .fold(0.0f32, f32::max);
+
+        Rectangle::new(
+            border_rectangle.x - shadow_extent,
+            border_rectangle.y - shadow_extent,
+            border_rectangle.width + shadow_extent * 2.0,
+            border_rectangle.height + shadow_extent * 2.0,
+        )
+    }
+
+    /// Returns the bounds of everything the element and its subtree draw. Descendants that overflow the element, like
+    /// absolutely positioned popups, are included unless the element clips its overflow.
+    fn subtree_bounds(&self) -> Rectangle {
+        let mut bounds = self.visual_bounds();
+        if self.element_data().layout.is_scrollable_layout() {
+            return bounds;
         }
+        for child in self.children() {
+            let child = child.borrow();
+            if child.element_data().is_mask || !child.is_visible() {
+                continue;
+            }
+            bounds = Rectangle::from_kurbo(bounds.to_kurbo().union(child.subtree_bounds().to_kurbo()));
+        }
+        bounds
+    }
+
+    /// Returns true if the element and its subtree can be skipped while drawing, because everything they draw is
+    /// entirely outside the clip bounds of the element (e.g. scrolled out of a scroll container) or outside the window.
+    fn is_outside_viewport(&self, renderer: &dyn Renderer, scale_factor: f64) -> bool {
+        let element_data = self.element_data();
+        let bounds = self.subtree_bounds();
+
+        if let Some(clip_bounds) = element_data.layout.clip_bounds
+            && !bounds.intersects(&clip_bounds)
+        {
+            return true;
+        }
+
+        renderer.is_culled(&bounds.scale(scale_factor))
     }
 
     /// A helper to re-apply the style to the layout node when dirty.
@@ -248,6 +330,132 @@ pub trait ElementInternals: ElementData + Any + Drop {
         }
     }
 
+    /// Returns true if the element has a blend mode or color filters that need to be drawn in their own layer.
+    fn has_effects(&self) -> bool {
+        let style = self.style();
+        style.get_mix_blend_mode() != Mix::Normal || !style.get_color_filters().is_empty()
+    }
+
+    /// Moves the element onto the overlay layer if it is elevated.
+    ///
+    /// Overlays are drawn after the rest of their parent's layer, so the element and its elevation shadow are not
+    /// clipped by its ancestors. Must be called before the element adds its hit testable, so it is sorted above the
+    /// elements below it.
+    fn maybe_start_overlay(&self, renderer: &mut dyn Renderer) {
+        if self.element_data().style.get_elevation() > 0.0 {
+            renderer.start_overlay();
+        }
+    }
+
+    fn maybe_end_overlay(&self, renderer: &mut dyn Renderer) {
+        if self.element_data().style.get_elevation() > 0.0 {
+            renderer.end_overlay();
+        }
+    }
+
+    /// Starts a layer for the element's blend mode and color filters, if it has any.
+    fn maybe_start_effect_layer(&self, renderer: &mut dyn Renderer, scale_factor: f64) {
+        if self.has_effects() {
+            let blend_mode = BlendMode::new(self.style().get_mix_blend_mode(), Compose::SrcOver);
+            renderer.push_effect_layer(self.visual_bounds().scale(scale_factor), blend_mode);
+        }
+    }
+
+    /// Applies the element's color filters and ends the layer started by [`ElementInternals::maybe_start_effect_layer`].
+    fn maybe_end_effect_layer(&self, renderer: &mut dyn Renderer, scale_factor: f64) {
+        if self.has_effects() {
+            let bounds = self.visual_bounds().scale(scale_factor);
+            for filter in self.style().get_color_filters() {
+                renderer.apply_color_filter(bounds, *filter);
+            }
+            renderer.pop_layer();
+        }
+    }
+
+    /// Returns the child used as this element's mask, see [`ElementInternals::set_mask`].
+    fn mask_element(&self) -> Option<Rc<RefCell<dyn ElementInternals>>> {
+        self.children()
+            .iter()
+            .find(|child| child.borrow().element_data().is_mask)
+            .cloned()
+    }
+
+    fn has_mask(&self) -> bool {
+        self.element_data().mask_image.is_some() || self.mask_element().is_some()
+    }
+
+    /// Starts the layer that is masked by [`ElementInternals::maybe_end_mask`], if the element has a mask.
+    fn maybe_start_mask(&self, renderer: &mut dyn Renderer, scale_factor: f64) {
+        if self.has_mask() {
+            renderer.push_effect_layer(self.visual_bounds().scale(scale_factor), BlendMode::default());
+        }
+    }
+
+    /// Draws the element's mask image and mask element into a mask layer and ends the masked layer.
+    ///
+    /// The mask element is drawn without hit testing, so it never receives events.
+    fn maybe_end_mask(
+        &self,
+        renderer: &mut dyn Renderer,
+        resource_manager: Arc<ResourceManager>,
+        scale_factor: f64,
+        text_context: &mut TextContext,
+    ) {
+        if !self.has_mask() {
+            return;
+        }
+
+        renderer.push_mask_layer(self.visual_bounds().scale(scale_factor));
+        if let Some(mask_image) = self.element_data().mask_image.clone() {
+            let border_rectangle = self.element_data().layout.computed_box_transformed.border_rectangle();
+            renderer.draw_image(border_rectangle.scale(scale_factor), mask_image);
+        }
+        if let Some(mask) = self.mask_element() {
+            let targets_len = renderer.render_list().targets.len();
+            mask.borrow_mut().draw(renderer, resource_manager, scale_factor, text_context);
+            renderer.render_list_mut().targets.truncate(targets_len);
+        }
+        renderer.pop_layer();
+        renderer.pop_layer();
+    }
+
+    /// Uses the alpha of `mask` to mask this element.
+    ///
+    /// The mask is pushed as a child that is laid out over the element's padding box, it is never drawn on its own.
+    /// Anything outside the mask is hidden. Replaces the previous mask element, if any.
+    ///
+    /// Returns [`CraftError::ChildrenNotSupported`] for elements that don't accept children.
+    fn set_mask(&mut self, mask: Rc<RefCell<dyn ElementInternals>>) -> Result<(), CraftError> {
+        if !self.supports_children() {
+            return Err(CraftError::ChildrenNotSupported);
+        }
+        if let Some(previous_mask) = self.mask_element() {
+            self.remove_child(previous_mask).unwrap();
+        }
+
+        {
+            let mut mask = mask.borrow_mut();
+            mask.element_data_mut().is_mask = true;
+            mask.set_position(Position::Absolute);
+            mask.set_inset(Unit::Px(0.0), Unit::Px(0.0), Unit::Px(0.0), Unit::Px(0.0));
+            mask.set_width(Unit::Percentage(100.0));
+            mask.set_height(Unit::Percentage(100.0));
+        }
+        self.push(mask);
+        Ok(())
+    }
+
+    /// Uses the alpha of an image, stretched over the element's border box, to mask this element.
+    fn set_mask_image(&mut self, mask_image: Option<ResourceId>) {
+        if let Some(mask_image) = &mask_image {
+            PENDING_RESOURCES.with_borrow_mut(|pending_resources| {
+                pending_resources.push_back((mask_image.clone(), ResourceType::Image));
+            });
+        }
+        self.element_data_mut().mask_image = mask_image;
+        self.invalidate_layer_cache();
+    }
+
     fn maybe_end_layer(&self, renderer: &mut dyn Renderer) {
         if self.should_start_new_layer() {
             renderer.pop_layer();
@@ -278,8 +486,42 @@ pub trait ElementInternals: ElementData + Any + Drop {
         Style::new()
     }
 
+    /// Enables or disables drawing this element and its subtree into an offscreen layer.
+    fn set_cache_as_layer(&mut self, cache_as_layer: bool) {
+        self.element_data_mut().layer_cache = cache_as_layer.then(LayerCache::default);
+    }
+
+    /// Returns true if the element draws the resource, so its layer is invalidated once the resource finished
+    /// loading.
+    fn uses_resource(&self, resource_id: &ResourceId) -> bool {
+        self.element_data().mask_image.as_ref() == Some(resource_id)
+    }
+
+    /// Invalidates the layer of this element and of every ancestor.
+    ///
+    /// Ancestors that are currently mutably borrowed, e.g. because they are being drawn, are skipped. A layer whose
+    /// subtree changes while it is drawn is drawn again anyway.
+    fn invalidate_layer_cache(&self) {
+        scene_changed();
+        if let Some(layer_cache) = &self.element_data().layer_cache {
+            layer_cache.invalidate();
+        }
+
+        let mut parent = self.parent().and_then(|parent| parent.upgrade());
+        while let Some(current) = parent {
+            let Ok(current) = current.try_borrow() else {
+                break;
+            };
+            if let Some(layer_cache) = &current.element_data().layer_cache {
+                layer_cache.invalidate();
+            }
+            parent = current.parent().and_then(|parent| parent.upgrade());
+        }
+    }
+
     /// Mark layout node dirty.
     fn mark_dirty(&mut self) {
+        self.invalidate_layer_cache();
         let id = self.element_data().layout.taffy_node_id;
         if let Some(id) = id {
             TAFFY_TREE.with_borrow_mut(|taffy_tree| {
@@ -290,6 +532,7 @@ pub trait ElementInternals: ElementData + Any + Drop {
 
     /// Updates taffy's style to reflect craft's style struct.
     fn update_taffy_style(&mut self) {
+        self.invalidate_layer_cache();
         let id = self.element_data().layout.taffy_node_id;
         if let Some(id) = id {
             TAFFY_TREE.with_borrow_mut(|taffy_tree| {
@@ -353,53 +596,7 @@ pub trait ElementInternals: ElementData + Any + Drop {
         child_1: Rc<RefCell<dyn ElementInternals>>,
         child_2: Rc<RefCell<dyn ElementInternals>>,
     ) -> Result<(), CraftError> {
-        let children = &mut self.element_data_mut().children;
-        let position_1 = children
-            .iter()
-            .position(|x| Rc::ptr_eq(x, &child_1))
-            .ok_or(CraftError::ElementNotFound)?;
-
-        let position_2 = children
-            .iter()
-            .position(|x| Rc::ptr_eq(x, &child_2))
-            .ok_or(CraftError::ElementNotFound)?;
-
-        // Swap the children.
-        self.element_data_mut().children.swap(position_1, position_2);
-
-        // Swap the children's taffy nodes.
-        TAFFY_TREE.with_borrow_mut(|taffy_tree| {
-            let parent_id = self.element_data().layout.taffy_node_id;
-            let child_1_id = child_1.borrow().element_data().layout.taffy_node_id;
-            let child_2_id = child_2.borrow().element_data().layout.taffy_node_id;
-
-            if let Some(parent_id) = parent_id
-                && let Some(child_1_id) = child_1_id
-                && let Some(child_2_id) = child_2_id
-            {
-                // There isn't a swap API in the taffy tree. Instead swap the children and call set_children.
-                let mut tchildren = taffy_tree.children(parent_id).to_vec();
-
-                let i1 = tchildren
-                    .iter()
-                    .position(|x| *x == child_1_id)
-                    .ok_or(CraftError::ElementNotFound)
-                    .expect("Failed to find taffy child");
-                let i2 = tchildren
-                    .iter()
-                    .position(|x| *x == child_2_id)
-                    .ok_or(CraftError::ElementNotFound)
-                    .expect("Failed to find taffy child");
-
-                tchildren.swap(i1, i2);
-
-                taffy_tree.set_children(parent_id, &tchildren);
-                taffy_tree.mark_dirty(parent_id);
-                taffy_tree.request_layout();
-            }
-        });
-
-        Ok(())
+        swap_children_of_element(self, child_1, child_2)
     }
 
     /// Removes a direct child of this element and returns the removed node.
@@ -414,50 +611,7 @@ pub trait ElementInternals: ElementData + Any + Drop {
         &mut self,
         child: Rc<RefCell<dyn ElementInternals>>,
     ) -> Result<Rc<RefCell<dyn ElementInternals>>, CraftError> {
-        // Find the node.
-        let children = &mut self.element_data_mut().children;
-        let position = children
-            .iter()
-            .position(|x| Rc::ptr_eq(x, &child))
-            .ok_or(CraftError::ElementNotFound)?;
-
-        let child = children[position].clone();
-
-        // Remove the node from the element.
-
-        children.remove(position);
-
-        // Remove the parent reference.
-        child.borrow_mut().element_data_mut().parent = None;
-        //child.borrow_mut().element_data_mut().window = None;
-        child.borrow_mut().propagate_window_down();
-
-        TAFFY_TREE.with_borrow_mut(|taffy_tree| {
-            let child_id = child.borrow().element_data().layout.taffy_node_id;
-
-            if let Some(child_id) = child_id {
-                taffy_tree.unparent_node(child_id);
-            }
-
-            let parent_id = self.element_data().layout.taffy_node_id;
-            taffy_tree.mark_dirty(parent_id.unwrap());
-        });
-
-        // TODO: Move to document
-        fn remove_element_from_document(node: Rc<RefCell<dyn ElementInternals>>, pointer_capture: &mut PointerCapture) {
-            pointer_capture.remove_element(&node);
-            for child in node.borrow().children() {
-                remove_element_from_document(child.clone(), pointer_capture);
-            }
-        }
-
-        if let Some(pointer_capture) = self.pointer_capture() {
-            remove_element_from_document(child.clone(), &mut pointer_capture.borrow_mut());
-        }
-
-        child.borrow_mut().unfocus();
-
-        Ok(child)
+        remove_child_from_element(self, child)
     }
 
     fn remove_all_children(&mut self) {
@@ -467,6 +621,11 @@ pub trait ElementInternals: ElementData + Any + Drop {
         }
     }
 
+    /// Returns true if the element accepts children with [`ElementInternals::push`].
+    fn supports_children(&self) -> bool {
+        false
+    }
+
     fn push(&mut self, _child: Rc<RefCell<dyn ElementInternals>>) {
         panic!("Pushing children is not supported.")
     }
@@ -474,6 +633,25 @@ pub trait ElementInternals: ElementData + Any + Drop {
     /// Called after a node is added to the taffy tree.
     fn on_post_add_layout_tree(&mut self, _taffy_tree: &mut TaffyTree) {}
 
+    /// The baseline of the first line of the element's own text from the top of its content box, in logical pixels, or
+    /// `None` if it doesn't show text itself. Used to line up elements in a
+    /// [`FlowLayout`](crate::layout::custom_layout::FlowLayout).
+    fn text_baseline(&self) -> Option<f32> {
+        None
+    }
+
+    /// Measures the content of a leaf element whose layout node has a [`LayoutContext::Element`] context.
+    ///
+    /// [`LayoutContext::Element`]: crate::layout::layout_context::LayoutContext::Element
+    fn measure(
+        &mut self,
+        _known_dimensions: taffy::Size<Option<f32>>,
+        _available_space: taffy::Size<taffy::AvailableSpace>,
+        _text_context: &mut TextContext,
+    ) -> taffy::Size<f32> {
+        taffy::Size::ZERO
+    }
+
     fn on_pointer_enter(&mut self, on_pointer_enter: PointerEnterHandler) {
         self.element_data_mut().on_pointer_enter.push(on_pointer_enter);
     }
@@ -484,6 +662,162 @@ pub trait ElementInternals: ElementData + Any + Drop {
             .push(on_dropdown_item_selected);
     }
 
+    fn on_drag_list_item_moved(&mut self, on_drag_list_item_moved: DragListItemMovedHandler) {
+        self.element_data_mut()
+            .on_drag_list_item_moved
+            .push(on_drag_list_item_moved);
+    }
+
+    fn on_list_box_selection_changed(&mut self, on_list_box_selection_changed: ListBoxSelectionChangedHandler) {
+        self.element_data_mut()
+            .on_list_box_selection_changed
+            .push(on_list_box_selection_changed);
+    }
+
+    fn on_selection_area_changed(&mut self, on_selection_area_changed: SelectionAreaChangedHandler) {
+        self.element_data_mut()
+            .on_selection_area_changed
+            .push(on_selection_area_changed);
+    }
+
+    fn on_stepper_step_changed(&mut self, on_stepper_step_changed: StepperStepChangedHandler) {
+        self.element_data_mut().on_stepper_step_changed.push(on_stepper_step_changed);
+    }
+
+    fn on_stepper_finished(&mut self, on_stepper_finished: StepperFinishedHandler) {
+        self.element_data_mut().on_stepper_finished.push(on_stepper_finished);
+    }
+
+    fn on_property_changed(&mut self, on_property_changed: PropertyChangedHandler) {
+        self.element_data_mut().on_property_changed.push(on_property_changed);
+    }
+
+    fn on_cell_edited(&mut self, on_cell_edited: CellEditedHandler) {
+        self.element_data_mut().on_cell_edited.push(on_cell_edited);
+    }
+
+    fn on_viewport_changed(&mut self, on_viewport_changed: ViewportChangedHandler) {
+        self.element_data_mut().on_viewport_changed.push(on_viewport_changed);
+    }
+
+    fn on_image_crop_changed(&mut self, on_image_crop_changed: ImageCropChangedHandler) {
+        self.element_data_mut().on_image_crop_changed.push(on_image_crop_changed);
+    }
+
+    fn on_color_picked(&mut self, on_color_picked: ColorPickedHandler) {
+        self.element_data_mut().on_color_picked.push(on_color_picked);
+    }
+
+    fn on_map_clicked(&mut self, on_map_clicked: MapClickedHandler) {
+        self.element_data_mut().on_map_clicked.push(on_map_clicked);
+    }
+
+    fn on_pdf_page_changed(&mut self, on_pdf_page_changed: PdfPageChangedHandler) {
+        self.element_data_mut().on_pdf_page_changed.push(on_pdf_page_changed);
+    }
+
+    fn on_element_captured(&mut self, on_element_captured: ElementCapturedHandler) {
+        self.element_data_mut().on_element_captured.push(on_element_captured);
+    }
+
+    fn on_rating_changed(&mut self, on_rating_changed: RatingChangedHandler) {
+        self.element_data_mut().on_rating_changed.push(on_rating_changed);
+    }
+
+    fn on_tags_changed(&mut self, on_tags_changed: TagsChangedHandler) {
+        self.element_data_mut().on_tags_changed.push(on_tags_changed);
+    }
+
+    fn on_segmented_input_changed(&mut self, on_segmented_input_changed: SegmentedInputChangedHandler) {
+        self.element_data_mut().on_segmented_input_changed.push(on_segmented_input_changed);
+    }
+
+    fn on_time_changed(&mut self, on_time_changed: TimeChangedHandler) {
+        self.element_data_mut().on_time_changed.push(on_time_changed);
+    }
+
+    fn on_duration_changed(&mut self, on_duration_changed: DurationChangedHandler) {
+        self.element_data_mut().on_duration_changed.push(on_duration_changed);
+    }
+
+    fn on_files_dropped(&mut self, on_files_dropped: FilesDroppedHandler) {
+        self.element_data_mut().on_files_dropped.push(on_files_dropped);
+    }
+
+    fn on_files_selected(&mut self, on_files_selected: FilesSelectedHandler) {
+        self.element_data_mut().on_files_selected.push(on_files_selected);
+    }
+
+    fn on_spelling_suggestions(&mut self, on_spelling_suggestions: SpellingSuggestionsHandler) {
+        self.element_data_mut().on_spelling_suggestions.push(on_spelling_suggestions);
+    }
+
+    fn on_speech_finished(&mut self, on_speech_finished: SpeechFinishedHandler) {
+        self.element_data_mut().on_speech_finished.push(on_speech_finished);
+    }
+
+    fn on_files_changed(&mut self, on_files_changed: FilesChangedHandler) {
+        self.element_data_mut().on_files_changed.push(on_files_changed);
+    }
+
+    fn on_web_socket(&mut self, on_web_socket: WebSocketHandler) {
+        self.element_data_mut().on_web_socket.push(on_web_socket);
+    }
+
+    fn on_process(&mut self, on_process: ProcessHandler) {
+        self.element_data_mut().on_process.push(on_process);
+    }
+
+    fn on_keyring(&mut self, on_keyring: KeyringHandler) {
+        self.element_data_mut().on_keyring.push(on_keyring);
+    }
+
+    fn on_http_stream(&mut self, on_http_stream: HttpStreamHandler) {
+        self.element_data_mut().on_http_stream.push(on_http_stream);
+    }
+
+    fn on_system_settings_changed(&mut self, on_system_settings_changed: SystemSettingsChangedHandler) {
+        self.element_data_mut()
+            .on_system_settings_changed
+            .push(on_system_settings_changed);
+    }
+
+    fn on_user_idle(&mut self, on_user_idle: UserIdleHandler) {
+        self.element_data_mut().on_user_idle.push(on_user_idle);
+    }
+
+    fn on_user_active(&mut self, on_user_active: UserActiveHandler) {
+        self.element_data_mut().on_user_active.push(on_user_active);
+    }
+
+    fn on_kiosk_reset(&mut self, on_kiosk_reset: KioskResetHandler) {
+        self.element_data_mut().on_kiosk_reset.push(on_kiosk_reset);
+    }
+
+    fn on_power_status_changed(&mut self, on_power_status_changed: PowerStatusChangedHandler) {
+        self.element_data_mut().on_power_status_changed.push(on_power_status_changed);
+    }
+
+    fn on_network_status_changed(&mut self, on_network_status_changed: NetworkStatusChangedHandler) {
+        self.element_data_mut().on_network_status_changed.push(on_network_status_changed);
+    }
+
+    fn on_jump_list_activated(&mut self, on_jump_list_activated: JumpListActivatedHandler) {
+        self.element_data_mut().on_jump_list_activated.push(on_jump_list_activated);
+    }
+
+    fn on_recent_files_changed(&mut self, on_recent_files_changed: RecentFilesChangedHandler) {
+        self.element_data_mut().on_recent_files_changed.push(on_recent_files_changed);
+    }
+
+    fn on_update_available(&mut self, on_update_available: UpdateAvailableHandler) {
+        self.element_data_mut().on_update_available.push(on_update_available);
+    }
+
+    fn on_update_downloaded(&mut self, on_update_downloaded: UpdateDownloadedHandler) {
+        self.element_data_mut().on_update_downloaded.push(on_update_downloaded);
+    }
+
     fn on_slider_value_changed(&mut self, on_slider_value_changed: SliderValueChangedHandler) {
         self.element_data_mut()
             .on_slider_value_changed
@@ -494,6 +828,18 @@ pub trait ElementInternals: ElementData + Any + Drop {
         self.element_data_mut().on_pointer_leave.push(on_pointer_leave);
     }
 
+    fn on_hover_intent(&mut self, on_hover_intent: HoverIntentHandler) {
+        self.element_data_mut().on_hover_intent.push(on_hover_intent);
+    }
+
+    fn on_hover_intent_ended(&mut self, on_hover_intent_ended: HoverIntentEndedHandler) {
+        self.element_data_mut().on_hover_intent_ended.push(on_hover_intent_ended);
+    }
+
+    fn set_hover_intent(&mut self, hover_intent: HoverIntent) {
+        self.element_data_mut().hover_intent = hover_intent;
+    }
+
     fn on_radio_value_changed(&mut self, on_radio_value_changed: RadioValueChangedHandler) {
         self.element_data_mut()
             .on_radio_value_changed
@@ -528,6 +874,27 @@ pub trait ElementInternals: ElementData + Any + Drop {
 
     fn set_id(&mut self, id: &str) {
         self.element_data_mut().id = Some(id.into());
+        record_element_change(self, |element| Mutation::AttributeChanged(element, Attribute::Id));
+    }
+
+    fn get_user_data(&self) -> Option<Rc<dyn Any>> {
+        self.element_data().user_data.clone()
+    }
+
+    fn set_user_data(&mut self, user_data: Option<Rc<dyn Any>>) {
+        self.element_data_mut().user_data = user_data;
+        record_element_change(self, |element| Mutation::AttributeChanged(element, Attribute::UserData));
+    }
+
+    fn set_custom_layout(&mut self, custom_layout: Option<Rc<RefCell<dyn CustomLayout>>>) {
+        self.element_data_mut().custom_layout = custom_layout.map(CustomLayoutState::new);
+        // Put back the styles the custom layout replaced, or let it replace them in the next layout pass.
+        for child in self.children() {
+            if let Ok(mut child) = child.try_borrow_mut() {
+                child.update_taffy_style();
+            }
+        }
+        self.update_taffy_style();
     }
 
     fn on_pointer_button_down(&mut self, on_pointer_button_down: PointerEventHandler) {
@@ -588,19 +955,38 @@ pub trait ElementInternals: ElementData + Any + Drop {
 
     /// Returns a mutable reference to the element's [`Style`].
     fn style_mut(&mut self) -> &mut Style {
+        self.invalidate_layer_cache();
+        record_element_change(self, Mutation::StyleChanged);
         &mut self.element_data_mut().style
     }
 
+    /// Replaces the element's [`Style`], e.g. with one loaded from a theme file.
+    fn set_style(&mut self, style: Style) {
+        *self.style_mut() = style;
+        self.style_mut().is_dirty = true;
+        self.update_taffy_style();
+        self.mark_dirty();
+    }
+
+    /// Applies the properties that were set on `style` over the element's [`Style`], keeping the element's defaults
+    /// for the others. See [`Style::merge`].
+    fn merge_style(&mut self, style: &Style) {
+        self.style_mut().merge(style);
+        self.update_taffy_style();
+        self.mark_dirty();
+    }
+
     /// Determines if a point is within the bound of the element.
     ///
     /// Visual order and visibility shall not be accounted for.
     fn in_bounds(&self, point: Point) -> bool {
         let element_data = self.element_data();
         let rect = element_data.layout.computed_box_transformed.border_rectangle();
+        let radii = element_data.style.get_resolved_border_radius(rect.width, rect.height);
 
         if let Some(clip) = element_data.layout.clip_bounds {
             match rect.intersection(&clip) {
-                Some(bounds) => bounds.contains(&point),
+                Some(bounds) => bounds.contains(&point) && rounded_rect_contains(&rect, &radii, &point),
                 None => false,
             }
         } else {
@@ -627,6 +1013,17 @@ pub trait ElementInternals: ElementData + Any + Drop {
         }
     }
 
+    /// Sends the following pointer events to this element, until [`ElementInternals::release_pointer`] is called or
+    /// the pointer is released.
+    fn capture_pointer(&self) {
+        self.set_pointer_capture(captured_pointer_id());
+    }
+
+    /// Stops sending the pointer events to this element, if it captured the pointer.
+    fn release_pointer(&self) {
+        self.release_pointer_capture(captured_pointer_id());
+    }
+
     fn set_pointer_capture(&self, pointer_id: PointerId) {
         // 9.2 Setting pointer capture
         // https://w3c.github.io/pointerevents/#setting-pointer-capture
@@ -925,21 +1322,21 @@ pub trait ElementInternals: ElementData + Any + Drop {
         self.set_border_width(border_width.top, value, border_width.bottom, value);
     }
 
-    fn set_border_radius(&mut self, top: (f32, f32), right: (f32, f32), bottom: (f32, f32), left: (f32, f32)) {
+    fn set_border_radius(&mut self, top: CornerRadius, right: CornerRadius, bottom: CornerRadius, left: CornerRadius) {
         self.style_mut().set_border_radius([top, right, bottom, left]);
         self.update_taffy_style();
     }
 
-    fn set_border_radius_all(&mut self, value: (f32, f32)) {
+    fn set_border_radius_all(&mut self, value: CornerRadius) {
         self.set_border_radius(value, value, value, value);
     }
 
-    fn set_border_radius_vertical(&mut self, value: (f32, f32)) {
+    fn set_border_radius_vertical(&mut self, value: CornerRadius) {
         let border_radius = self.style().get_border_radius();
         self.set_border_radius(value, border_radius[1], value, border_radius[3]);
     }
 
-    fn set_border_radius_horizontal(&mut self, value: (f32, f32)) {
+    fn set_border_radius_horizontal(&mut self, value: CornerRadius) {
         let border_radius = self.style().get_border_radius();
         self.set_border_radius(border_radius[0], value, border_radius[2], value);
     }
@@ -965,18 +1362,121 @@ pub trait ElementInternals: ElementData + Any + Drop {
         self.style_mut().set_selection_color(selection_color);
     }
 
+    fn set_selection_text_color(&mut self, selection_text_color: Option<Color>) {
+        self.style_mut().set_selection_text_color(selection_text_color);
+    }
+
+    fn set_inactive_selection_color(&mut self, inactive_selection_color: Option<Color>) {
+        self.style_mut().set_inactive_selection_color(inactive_selection_color);
+    }
+
     fn set_box_shadows(&mut self, box_shadows: Vec<BoxShadow>) {
         self.style_mut().set_box_shadows(box_shadows);
     }
 
+    fn set_elevation(&mut self, elevation: f32) {
+        self.style_mut().set_elevation(elevation);
+    }
+
+    fn set_pixel_snapping(&mut self, pixel_snapping: bool) {
+        self.style_mut().set_pixel_snapping(pixel_snapping);
+    }
+
+    fn set_text_contrast(&mut self, text_contrast: Option<f32>) {
+        self.style_mut().set_text_contrast(text_contrast);
+    }
+
+    fn set_cursor_color(&mut self, cursor_color: Option<Color>) {
+        self.style_mut().set_cursor_color(cursor_color);
+    }
+
+    fn set_cursor_width(&mut self, cursor_width: f32) {
+        self.style_mut().set_cursor_width(cursor_width);
+    }
+
+    fn set_cursor_blink_interval(&mut self, cursor_blink_interval: Duration) {
+        self.style_mut().set_cursor_blink_interval(cursor_blink_interval);
+    }
+
+    fn set_mix_blend_mode(&mut self, mix_blend_mode: Mix) {
+        self.style_mut().set_mix_blend_mode(mix_blend_mode);
+    }
+
+    fn set_color_filters(&mut self, color_filters: Vec<ColorFilter>) {
+        self.style_mut().set_color_filters(color_filters);
+    }
+
+    fn set_direction(&mut self, direction: Direction) {
+        self.style_mut().set_direction(direction);
+        self.update_taffy_style();
+        self.on_direction_changed();
+        self.inherit_direction_down();
+    }
+
+    /// Takes the direction of the parent, unless the element sets its own.
+    fn inherit_direction(&mut self, direction: Direction) {
+        // The style isn't set, so this isn't recorded as a style change.
+        if self.element_data_mut().style.inherit_direction(direction) {
+            self.update_taffy_style();
+            self.on_direction_changed();
+            self.inherit_direction_down();
+        }
+    }
+
+    /// Gives the children the direction of this element.
+    fn inherit_direction_down(&mut self) {
+        let direction = self.style().get_direction();
+        for child in &self.element_data().children {
+            child.borrow_mut().inherit_direction(direction);
+        }
+    }
+
+    /// Called when the direction of the element changes, for elements that mirror what they draw right to left.
+    fn on_direction_changed(&mut self) {}
+
+    fn set_auto_mirror(&mut self, auto_mirror: bool) {
+        self.style_mut().set_auto_mirror(auto_mirror);
+        self.update_taffy_style();
+        self.on_direction_changed();
+    }
+
     /// Sets focus on the specified element, if it can be focused.
     ///
     /// The focused element is the element that will receive keyboard and similar events by default.
     fn focus(&mut self) {
         // Todo: check if the element is focusable. Should we return a result?
-        FOCUS.with_borrow_mut(|focus| {
-            *focus = Some(self.element_data().me.clone());
-        });
+        let me = self.element_data().me.clone();
+        let previous = FOCUS.with_borrow_mut(|focus| focus.replace(me.clone()));
+        if previous.as_ref().is_some_and(|previous| Weak::ptr_eq(previous, &me)) {
+            return;
+        }
+        // The previous element isn't told while it is borrowed, like when it focuses one of its children.
+        if let Some(previous) = previous.and_then(|previous| previous.upgrade())
+            && let Ok(mut previous) = previous.try_borrow_mut()
+        {
+            previous.invalidate_layer_cache();
+            previous.on_focus_changed(false);
+        }
+        self.invalidate_layer_cache();
+        self.on_focus_changed(true);
+    }
+
+    /// Called when the element gains or loses the focus.
+    fn on_focus_changed(&mut self, _focused: bool) {}
+
+    /// Whether spatial navigation can move the focus to the element. Elements that take keyboard input are always
+    /// focusable, others when set with [`Element::focusable`](crate::elements::Element::focusable).
+    fn is_focusable(&self) -> bool {
+        self.element_data().focusable
+    }
+
+    fn set_focusable(&mut self, focusable: bool) {
+        self.element_data_mut().focusable = focusable;
+    }
+
+    /// Whether the element uses the arrow keys while focused, so they don't move the focus with spatial navigation.
+    fn handles_arrow_keys(&self) -> bool {
+        false
     }
 
     /// Returns true if the element has focus.
@@ -998,9 +1498,84 @@ pub trait ElementInternals: ElementData + Any + Drop {
             FOCUS.with(|focus| {
                 *focus.borrow_mut() = None;
             });
+            self.invalidate_layer_cache();
+            self.on_focus_changed(false);
+        }
+    }
+
+    /// Waits for the user to click anywhere in the window, and sends the color under the pointer to this element in
+    /// a [`EventKind::ColorPicked`] event.
+    ///
+    /// The click doesn't reach the elements under the pointer, and escape cancels picking. Nothing is sent if the
+    /// renderer can't read back what it drew.
+    fn pick_color(&mut self) {
+        COLOR_PICK.with_borrow_mut(|pick| {
+            *pick = Some(self.element_data().me.clone());
+        });
+        if let Some(window) = self.element_data().window.as_ref().and_then(Weak::upgrade) {
+            window.borrow().set_cursor(CursorIcon::Crosshair);
         }
     }
 
+    /// Draws this element and its children to an image after the next frame, and sends it to this element in a
+    /// [`EventKind::ElementCaptured`] event.
+    ///
+    /// The image is the element's border box at its laid-out size times `scale`, in physical pixels, on a transparent
+    /// background. Nothing is sent if the renderer can't render offscreen.
+    fn capture(&mut self, scale: f64) {
+        CAPTURES.with_borrow_mut(|captures| {
+            captures.push((self.element_data().me.clone(), scale));
+        });
+        self.request_window_redraw();
+    }
+
+    /// Speaks `text` with the text-to-speech of the platform, and sends a [`EventKind::SpeechFinished`] event to this
+    /// element when it is done.
+    ///
+    /// Returns the id of the text, which is in the event. The event isn't sent on platforms that can't tell when
+    /// speech ends.
+    #[cfg(feature = "speech")]
+    fn speak(&self, text: &str, options: &SpeechOptions) -> Result<u64, SpeechError> {
+        crate::speech::speak_for(text, options, self.element_data().me.clone())
+    }
+
+    /// Opens a WebSocket connection on its own thread, and sends a [`EventKind::WebSocket`] event to this element when
+    /// it connected, received a message or was lost. The connection is opened again when it is lost, as set in
+    /// `options`, until it is closed or this element is dropped.
+    #[cfg(all(feature = "websocket", not(target_arch = "wasm32")))]
+    fn connect_web_socket(&self, url: &str, options: WebSocketOptions) -> WebSocket {
+        crate::websocket::connect_for(url, options, self.element_data().me.clone())
+    }
+
+    /// Sends a request on its own thread, and sends a [`EventKind::HttpStream`] event to this element when the
+    /// response started, for every chunk or server-sent event of it, and when it ended. Reading the response stops
+    /// when the stream is cancelled or this element is dropped.
+    #[cfg(all(feature = "http_stream", not(target_arch = "wasm32")))]
+    fn stream_http(&self, request: HttpStreamRequest) -> HttpStream {
+        crate::http_stream::stream_for(request, self.element_data().me.clone())
+    }
+
+    /// Watches a file or directory, and sends a [`EventKind::FilesChanged`] event to this element with the changes
+    /// once none came for the debounce time in `options`. Watching stops when the watch is stopped or this element is
+    /// dropped.
+    #[cfg(all(feature = "file_watch", not(target_arch = "wasm32")))]
+    fn watch_files(&self, path: &Path, options: FileWatchOptions) -> io::Result<FileWatch> {
+        crate::file_watch::watch_for(path, options, self.element_data().me.clone())
+    }
+
+    /// Spawns a child process, and sends a [`EventKind::Process`] event to this element for every line it writes to
+    /// stdout or stderr, and when it exited. The process is killed when this element is dropped.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn spawn_process(&self, command: &ProcessCommand) -> io::Result<ChildProcess> {
+        crate::process::spawn_for(command, self.element_data().me.clone(), self.element_data().internal_id)
+    }
+
+    /// The secrets of `service` in the credential store of the platform. The result of every request is sent to this
+    /// element in a [`EventKind::Keyring`] event, see [`crate::keyring`].
+    fn keyring(&self, service: &str) -> Keyring {
+        Keyring::new(service, self.element_data().me.clone())
+    }
+
     /// Re-
     fn to_rc(&self) -> Rc<RefCell<dyn ElementInternals>> {
         self.element_data().me.upgrade().unwrap()
@@ -1057,10 +1632,14 @@ pub trait ElementInternals: ElementData + Any + Drop {
         ELEMENTS.with_borrow_mut(|elements| {
             elements.remove_id(self.element_data().internal_id);
         });
+        #[cfg(not(target_arch = "wasm32"))]
+        crate::process::kill_processes_of(self.element_data().internal_id);
     }
 
-    /// Use the element's window to request a redraw.
+    /// Use the element's window to request a redraw. Also invalidates the layers the element is drawn into and marks
+    /// the scene as changed, so the redraw isn't skipped when it happens outside of an event.
     fn request_window_redraw(&self) {
+        self.invalidate_layer_cache();
         let Some(winit_window_weak) = &self.element_data().window else {
             return;
         };
@@ -4,16 +4,24 @@ use std::any::Any;
 use std::cell::{Ref, RefCell, RefMut};
 use std::rc::{Rc, Weak};
 use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time;
+#[cfg(target_arch = "wasm32")]
+use web_time as time;
 
 #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
 use {accesskit::{Action, Role, TreeUpdate}, accesskit_winit::Adapter};
 
-use craft_logging::info;
+use craft_logging::{info, warn};
 
 use craft_primitives::geometry::{Affine, Point, Rectangle, Size};
 
 use craft_renderer::renderer::{Renderer, Screenshot};
 use craft_renderer::RendererType;
+use craft_renderer::image_cache::{ImageCacheStats, ImageRetention};
+use craft_renderer::text_renderer_data::TextAntialiasing;
+#[cfg(not(target_arch = "wasm32"))]
+use craft_renderer::threaded::ThreadedRenderer;
 
 use craft_resource_manager::ResourceManager;
 
@@ -28,7 +36,7 @@ use ui_events::pointer::PointerScrollEvent;
 
 use winit::event::WindowEvent;
 use winit::event_loop::ActiveEventLoop;
-use winit::window::{Window as WinitWindow, WindowAttributes};
+use winit::window::{CursorIcon, ImePurpose, UserAttentionType, Window as WinitWindow, WindowAttributes};
 
 #[cfg(target_arch = "wasm32")]
 use {wasm_bindgen::JsCast, winit::platform::web::WindowAttributesExtWebSys};
@@ -37,17 +45,34 @@ use craft_renderer::blank_renderer::BlankRenderer;
 use crate::accessibility::{access_handler::CraftAccessHandler, activation_handler::CraftActivationHandler, deactivation_handler::CraftDeactivationHandler};
 #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
 use crate::app::FOCUS;
-use crate::app::{App, TAFFY_TREE, WINDOW_MANAGER, queue_window_event};
+use crate::attract_mode::{AttractMode, leave_attract_mode};
+use crate::app::{
+    App, CAPTURES, TAFFY_TREE, WINDOW_MANAGER, queue_event, queue_window_event, set_drawing_window_active,
+};
 use crate::elements::element_data::ElementData;
-use crate::elements::internal_helpers::{apply_generic_container_layout, draw_generic_container, push_child_to_element};
-use crate::elements::{AsElement, Element, ElementInternals, resolve_clip_for_scrollable, scrollable};
+use crate::elements::element_id::find_by_stable_id;
+use crate::elements::internal_helpers::{
+    apply_generic_container_layout, capture_element, draw_generic_container, push_child_to_element,
+};
+use crate::elements::{AsElement, DynElement, Element, ElementInternals, resolve_clip_for_scrollable, scrollable};
 #[cfg(target_arch = "wasm32")]
 use crate::events::internal::InternalMessage;
 use crate::events::pointer_capture::PointerCapture;
 use crate::events::{Event, EventKind};
+use crate::frame_stats::FrameStats;
+use crate::shell::{ShellError, TaskbarProgress};
+use crate::kiosk;
 use crate::layout::TaffyTree;
+use crate::layout::custom_layout::{apply_custom_layouts, custom_layouts_changed, find_custom_layouts};
+use crate::layout::layout_debug::{LayoutIssue, find_layout_issues};
+use crate::layout::popup::set_layout_viewport;
+use crate::quality::{QualityFeature, is_degraded};
+use crate::scene::{FrameKey, FrameSkipper, scene_changed, scene_generation};
+use crate::snapping::{SnapGuide, draw_snap_guides};
+use crate::spatial;
 use crate::style::Overflow;
 use crate::text::text_context::TextContext;
+use crate::WindowMaterial;
 #[cfg(target_arch = "wasm32")]
 use crate::wasm_queue::WASM_QUEUE;
 
@@ -81,6 +106,12 @@ pub struct WindowInternal {
     /// The renderer is chosen based on the features enabled at compile time.
     /// See [`RendererType`] for details.
     renderer_type: RendererType,
+    /// The requested text antialiasing mode.
+    text_antialiasing: TextAntialiasing,
+    /// How long the renderer keeps images uploaded after they were last drawn.
+    image_retention: ImageRetention,
+    /// The platform material behind the window, from [`CraftOptions::window_material`](crate::CraftOptions::window_material).
+    window_material: WindowMaterial,
     /// The window's scale factor from winit.
     scale_factor: f64,
     /// Zoom scale factor.
@@ -88,6 +119,26 @@ pub struct WindowInternal {
     mouse_positon: Option<Point>,
     element_data: ElementData,
     pub(crate) modifiers: Modifiers,
+    /// Whether the layout is checked for common bugs after each layout pass.
+    debug_layout: bool,
+    /// The issues found by the last layout check.
+    layout_issues: Vec<LayoutIssue>,
+    /// The guides of the element being dragged, see [`Window::show_snap_guides`].
+    snap_guides: Vec<SnapGuide>,
+    /// The element shown instead of the children after a while without input, see [`Window::attract_mode`].
+    pub(crate) attract_mode: Option<AttractMode>,
+    /// When an element asked for this window to be drawn again.
+    ///
+    /// See [`request_animation_frame_at`](crate::request_animation_frame_at).
+    pub(crate) next_frame_time: Option<time::Instant>,
+    /// Whether the window has keyboard focus.
+    pub(crate) is_active: bool,
+    /// The purpose of the focused text input, as last told to the IME.
+    ime_purpose: ImePurpose,
+    /// What the last frame cost.
+    pub(crate) frame_stats: FrameStats,
+    /// Tells whether the next frame would be the same as the last one.
+    pub(crate) frame_skipper: FrameSkipper,
 }
 
 impl Clone for WindowInternal {
@@ -224,6 +275,10 @@ impl ElementInternals for WindowInternal {
         }
     }
 
+    fn supports_children(&self) -> bool {
+        true
+    }
+
     fn push(&mut self, child: Rc<RefCell<dyn ElementInternals>>) {
         push_child_to_element(self, child);
     }
@@ -267,10 +322,140 @@ impl Window {
         Window { inner }
     }
 
+    /// Returns the last frame of the window.
+    ///
+    /// Some renderers only copy frames after [`Window::request_screenshot`], or after a screenshot that came back
+    /// empty.
     pub fn screenshot(&self) -> Screenshot {
         self.inner.borrow().screenshot()
     }
 
+    /// Makes the renderer copy the next frames, until [`Window::screenshot`] reads one.
+    pub fn request_screenshot(&self) {
+        self.inner.borrow().request_screenshot();
+    }
+
+    /// Sets how text in this window is antialiased.
+    ///
+    /// Subpixel antialiasing falls back to grayscale when the renderer or surface does not support it, and for text
+    /// drawn inside layers.
+    pub fn text_antialiasing(self, text_antialiasing: TextAntialiasing) -> Self {
+        self.inner.borrow_mut().text_antialiasing = text_antialiasing;
+        self.inner.borrow().request_redraw();
+        self
+    }
+
+    pub fn get_text_antialiasing(&self) -> TextAntialiasing {
+        self.inner.borrow().text_antialiasing
+    }
+
+    /// Sets how long images stay uploaded to the GPU after they were last drawn in this window.
+    ///
+    /// Keeping them longer, or raising the byte budget, avoids uploading the images of a long list again each time it
+    /// is scrolled back, at the cost of GPU memory. Only the GPU renderer caches images.
+    pub fn image_retention(self, image_retention: ImageRetention) -> Self {
+        self.inner.borrow_mut().image_retention = image_retention;
+        self
+    }
+
+    pub fn get_image_retention(&self) -> ImageRetention {
+        self.inner.borrow().image_retention
+    }
+
+    /// What the renderer of this window did with the images it uploaded, like how many it evicted.
+    pub fn image_cache_stats(&self) -> ImageCacheStats {
+        self.inner.borrow().renderer.borrow().image_cache_stats()
+    }
+
+    /// What the last frame of this window cost, like how long its layout took and how much it drew.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.inner.borrow().frame_stats
+    }
+
+    /// Finds the element of this window whose [`stable_id`](crate::elements::ElementData::stable_id) is `stable_id`,
+    /// like one saved before a restart.
+    pub fn find_by_stable_id(&self, stable_id: u64) -> Option<Rc<RefCell<dyn ElementInternals>>> {
+        let root: Rc<RefCell<dyn ElementInternals>> = self.inner.clone();
+        find_by_stable_id(&root, stable_id)
+    }
+
+    /// The elements drawn at `point`, in logical pixels, from the topmost down, like the ones a click there would hit.
+    /// See [`crate::spatial`].
+    pub fn elements_at(&self, point: Point) -> Vec<DynElement> {
+        let renderer = self.inner.borrow().renderer.clone();
+        spatial::elements_at(&*renderer.borrow(), point)
+    }
+
+    /// The elements drawn over any part of `rectangle`, in logical pixels, from the topmost down, like the ones a
+    /// selection marquee covers.
+    pub fn elements_in_rect(&self, rectangle: Rectangle) -> Vec<DynElement> {
+        let renderer = self.inner.borrow().renderer.clone();
+        spatial::elements_in_rect(&*renderer.borrow(), rectangle)
+    }
+
+    /// The drawn element closest to `point` within `max_distance` logical pixels, like the one to snap to. Of the
+    /// elements as close, the topmost.
+    pub fn nearest_element(&self, point: Point, max_distance: f64) -> Option<DynElement> {
+        let renderer = self.inner.borrow().renderer.clone();
+        spatial::nearest_element(&*renderer.borrow(), point, max_distance)
+    }
+
+    /// Draws the guides of a snapped element above everything else, until other guides are shown. Pass no guides when
+    /// the drag ends. See [`crate::snapping`].
+    pub fn show_snap_guides(&self, guides: &[SnapGuide]) {
+        let mut inner = self.inner.borrow_mut();
+        if inner.snap_guides != guides {
+            inner.snap_guides = guides.to_vec();
+            inner.request_redraw();
+        }
+    }
+
+    /// Shows `attract` instead of the children of the window after `after` without input, until the next input. See
+    /// [`crate::attract_mode`].
+    pub fn attract_mode(self, after: time::Duration, attract: impl AsElement) -> Self {
+        self.clear_attract_mode();
+        self.inner.borrow_mut().attract_mode = Some(AttractMode::new(after, attract.as_element_rc()));
+        self
+    }
+
+    /// Stops showing an attract element, and puts the children back if it was shown.
+    pub fn clear_attract_mode(&self) {
+        let focus = leave_attract_mode(&mut self.inner.borrow_mut());
+        self.inner.borrow_mut().attract_mode = None;
+        if let Some(focus) = focus.and_then(|focus| focus.upgrade()) {
+            focus.borrow_mut().focus();
+        }
+    }
+
+    /// Whether the window shows its attract element.
+    pub fn is_attracting(&self) -> bool {
+        self.inner.borrow().attract_mode.as_ref().is_some_and(AttractMode::is_active)
+    }
+
+    /// Checks the layout of this window for common bugs after each layout pass.
+    ///
+    /// Children overflowing parents that don't clip, flex items shrunk to nothing, percentage sizes inside
+    /// auto-sized parents, and NaN or negative sizes are logged as warnings when they first appear, and outlined in
+    /// red on top of the window.
+    pub fn debug_layout(self, debug_layout: bool) -> Self {
+        {
+            let mut inner = self.inner.borrow_mut();
+            inner.debug_layout = debug_layout;
+            inner.layout_issues.clear();
+        }
+        self.inner.borrow().request_redraw();
+        self
+    }
+
+    pub fn get_debug_layout(&self) -> bool {
+        self.inner.borrow().debug_layout
+    }
+
+    /// The layout issues found in the last layout pass, when [`Window::debug_layout`] is on.
+    pub fn get_layout_issues(&self) -> Vec<LayoutIssue> {
+        self.inner.borrow().layout_issues.clone()
+    }
+
     pub fn close(&self) {
         self.inner.borrow().close();
     }
@@ -301,6 +486,54 @@ impl Window {
         self.inner.borrow().zoom_scale_factor()
     }
 
+    /// Get the bounds of the monitor the window is on, in logical pixels relative to the top left of the window.
+    ///
+    /// Popups positioned within these bounds stay on the screen even when they extend past the window, like popups
+    /// shown in their own window. Returns `None` if the window isn't shown, or the platform doesn't say where it is.
+    pub fn screen_bounds(&self) -> Option<Rectangle> {
+        let winit_window = self.winit_window()?;
+        let monitor = winit_window.current_monitor()?;
+        let window_position = winit_window.inner_position().ok()?;
+        let monitor_position = monitor.position();
+        let monitor_size = monitor.size();
+        let scale_factor = self.effective_scale_factor() as f32;
+        Some(Rectangle::new(
+            (monitor_position.x - window_position.x) as f32 / scale_factor,
+            (monitor_position.y - window_position.y) as f32 / scale_factor,
+            monitor_size.width as f32 / scale_factor,
+            monitor_size.height as f32 / scale_factor,
+        ))
+    }
+
+    /// Converts a point in the window, in logical pixels, to a position on the screen in physical pixels, e.g. to
+    /// open a popup window at it.
+    pub fn screen_position(&self, point: Point) -> Option<Point> {
+        let window_position = self.winit_window()?.inner_position().ok()?;
+        let scale_factor = self.effective_scale_factor();
+        Some(Point::new(
+            window_position.x as f64 + point.x * scale_factor,
+            window_position.y as f64 + point.y * scale_factor,
+        ))
+    }
+
+    /// Shows the progress of a long-running operation on the window's taskbar button on Windows, or on the dock icon on
+    /// macOS, see [`crate::shell`].
+    pub fn set_taskbar_progress(&self, progress: TaskbarProgress) -> Result<(), ShellError> {
+        let winit_window = self
+            .winit_window()
+            .ok_or_else(|| ShellError::Failed("The window isn't shown".to_string()))?;
+        crate::shell::set_taskbar_progress(&winit_window, progress)
+    }
+
+    /// Flashes the window's taskbar button or bounces the dock icon until the window is focused, or stops with `None`.
+    ///
+    /// Does nothing while the window is focused.
+    pub fn request_attention(&self, attention: Option<UserAttentionType>) {
+        if let Some(winit_window) = self.winit_window() {
+            winit_window.request_user_attention(attention);
+        }
+    }
+
     /// Updates the reactive tree, layouts the elements, and draws the view.
     #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
     pub fn on_request_redraw(&self, craft_app: &mut App) -> Option<TreeUpdate> {
@@ -337,6 +570,14 @@ impl Window {
         self.inner.borrow_mut().on_redraw(text_context, resource_manager)
     }
 
+    /// Builds the AccessKit tree for the window as it would be sent to the platform adapter.
+    ///
+    /// Useful for asserting the semantics (roles, labels, states, and focus) of a window in tests.
+    #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+    pub fn accessibility_tree(&self) -> TreeUpdate {
+        self.compute_accessibility_tree_window()
+    }
+
     #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
     pub(crate) fn compute_accessibility_tree_window(&self) -> TreeUpdate {
         self.inner.borrow_mut().compute_accessibility_tree_window()
@@ -370,8 +611,20 @@ impl WindowInternal {
                 advanced_window_fn: f.map(|f| Box::new(f) as WindowConstructor),
                 title: title.map(|title| title.to_string()),
                 renderer_type,
+                text_antialiasing: TextAntialiasing::default(),
+                image_retention: ImageRetention::default(),
+                window_material: WindowMaterial::None,
                 pointer_capture: Default::default(),
                 modifiers: Default::default(),
+                debug_layout: false,
+                layout_issues: Vec::new(),
+                snap_guides: Vec::new(),
+                attract_mode: None,
+                next_frame_time: None,
+                is_active: true,
+                ime_purpose: ImePurpose::Normal,
+                frame_stats: FrameStats::default(),
+                frame_skipper: FrameSkipper::default(),
             })
         });
 
@@ -389,12 +642,31 @@ impl WindowInternal {
         inner
     }
 
+    /// Draws the window again. The scene is marked as changed, so the frame isn't skipped.
     pub fn request_redraw(&self) {
+        scene_changed();
         if let Some(winit_window) = &self.winit_window {
             winit_window.request_redraw();
         }
     }
 
+    pub(crate) fn set_cursor(&self, cursor: CursorIcon) {
+        if let Some(winit_window) = &self.winit_window {
+            winit_window.set_cursor(cursor);
+        }
+    }
+
+    /// Tells the IME what the focused text input is for, like a password, if that changed.
+    pub(crate) fn set_ime_purpose(&mut self, ime_purpose: ImePurpose) {
+        if self.ime_purpose == ime_purpose {
+            return;
+        }
+        self.ime_purpose = ime_purpose;
+        if let Some(winit_window) = &self.winit_window {
+            winit_window.set_ime_purpose(ime_purpose);
+        }
+    }
+
     pub fn winit_window(&self) -> Option<Arc<winit::window::Window>> {
         self.winit_window.clone()
     }
@@ -426,10 +698,11 @@ impl WindowInternal {
     /// Updates the reactive tree, layouts the elements, and draws the view.
     #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
     pub fn on_request_redraw(&mut self, craft_app: &mut App) -> Option<TreeUpdate> {
-        self.on_redraw(
-            craft_app.text_context.as_mut().unwrap(),
-            craft_app.resource_manager.clone(),
-        );
+        self.renderer.borrow_mut().set_text_contrast(craft_app.craft_options.text_contrast);
+        // Nothing is laid out or drawn until the fonts are loaded.
+        if let Some(text_context) = craft_app.text_context.as_mut() {
+            self.on_redraw(text_context, craft_app.resource_manager.clone());
+        }
 
         let tree_update = self.compute_accessibility_tree_window();
         if let Some(accesskit_adapter) = &mut self.accesskit_adapter {
@@ -443,10 +716,11 @@ impl WindowInternal {
     /// Updates the reactive tree, layouts the elements, and draws the view.
     #[cfg(any(not(feature = "accesskit"), target_arch = "wasm32"))]
     pub fn on_request_redraw(&mut self, craft_app: &mut App) {
-        self.on_redraw(
-            craft_app.text_context.as_mut().unwrap(),
-            craft_app.resource_manager.clone(),
-        );
+        self.renderer.borrow_mut().set_text_contrast(craft_app.craft_options.text_contrast);
+        // Nothing is laid out or drawn until the fonts are loaded.
+        if let Some(text_context) = craft_app.text_context.as_mut() {
+            self.on_redraw(text_context, craft_app.resource_manager.clone());
+        }
     }
 
     pub(crate) fn zoom_in(&mut self) {
@@ -535,11 +809,54 @@ impl WindowInternal {
         //    return;
         //}
 
-        self.renderer.borrow_mut().surface_set_clear_color(Color::WHITE);
+        let clear_color = if self.window_material.is_translucent() {
+            Color::TRANSPARENT
+        } else {
+            Color::WHITE
+        };
+        self.renderer.borrow_mut().surface_set_clear_color(clear_color);
+        let text_antialiasing = if is_degraded(QualityFeature::SubpixelText) {
+            TextAntialiasing::Grayscale
+        } else {
+            self.text_antialiasing
+        };
+        self.renderer.borrow_mut().set_text_antialiasing(text_antialiasing);
+        self.renderer.borrow_mut().set_image_retention(self.image_retention);
 
+        let layout_start = time::Instant::now();
         self.layout_window(text_context, resource_manager.clone());
+        self.frame_stats.layout = layout_start.elapsed();
 
         self.draw_window(text_context, resource_manager);
+        self.frame_stats.reused = false;
+    }
+
+    /// Everything the next frame would be drawn from.
+    pub(crate) fn frame_key(&self) -> FrameKey {
+        FrameKey {
+            generation: scene_generation(),
+            width: self.window_size.width,
+            height: self.window_size.height,
+            scale_factor: self.effective_scale_factor(),
+            is_active: self.is_active,
+        }
+    }
+
+    /// Submits the render list of the last frame again, without laying out or drawing the elements.
+    pub(crate) fn present_last_frame(&mut self, resource_manager: Arc<ResourceManager>) {
+        self.winit_window.clone().unwrap().pre_present_notify();
+
+        let renderer = self.renderer.clone();
+        let window = Rectangle::new(0.0, 0.0, renderer.borrow().surface_width(), renderer.borrow().surface_height());
+        let encode_start = time::Instant::now();
+        renderer.borrow_mut().prepare(resource_manager.clone(), window);
+        let submit_start = time::Instant::now();
+        renderer.borrow_mut().submit(resource_manager);
+
+        self.frame_stats.layout = time::Duration::ZERO;
+        self.frame_stats.encode = submit_start - encode_start;
+        self.frame_stats.gpu = submit_start.elapsed();
+        self.frame_stats.reused = true;
     }
 
     pub(crate) fn on_scale_factor_changed(&mut self, scale_factor: f64) {
@@ -549,12 +866,16 @@ impl WindowInternal {
     }
 
     pub(crate) fn create(&mut self, craft_app: &mut App, event_loop: &ActiveEventLoop) {
+        self.window_material = craft_app.craft_options.window_material;
+
         let winit_window: Arc<WinitWindow> = Arc::new(if let Some(window_fn) = &mut self.advanced_window_fn {
             (*window_fn)(event_loop)
         } else {
             let window_attributes = WindowAttributes::default()
                 .with_title(self.title.as_ref().unwrap())
                 .with_visible(false);
+            let window_attributes = with_window_material(window_attributes, self.window_material);
+            let window_attributes = kiosk::window_attributes(window_attributes);
             #[cfg(target_arch = "wasm32")]
             let window_attributes = {
                 let canvas = web_sys::window()
@@ -576,12 +897,65 @@ impl WindowInternal {
         self.set_winit_window(Some(winit_window.clone()));
         self.on_scale_factor_changed(winit_window.scale_factor());
 
+        self.create_renderer(craft_app, winit_window.clone());
+        if let Some(splash_color) = craft_app.craft_options.splash_color {
+            self.present_splash(splash_color, craft_app.resource_manager.clone());
+            winit_window.set_visible(true);
+        }
+
+        #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+        {
+            // Until the fonts are loaded, the initial tree isn't laid out. It's updated with the first frame.
+            let action_handler = CraftAccessHandler {};
+            let deactivation_handler = CraftDeactivationHandler::new();
+
+            let tree_update = self.on_request_redraw(craft_app);
+
+            let craft_activation_handler = CraftActivationHandler::new(tree_update);
+            self.accesskit_adapter = Some(Adapter::with_direct_handlers(
+                event_loop,
+                &winit_window,
+                craft_activation_handler,
+                action_handler,
+                deactivation_handler,
+            ));
+        }
+
+        winit_window.set_visible(true);
+    }
+
+    /// Presents a frame of only `color`, so the window isn't blank while the first frame is laid out.
+    fn present_splash(&mut self, color: Color, resource_manager: Arc<ResourceManager>) {
+        self.winit_window.clone().unwrap().pre_present_notify();
+
+        let renderer = self.renderer.clone();
+        let mut renderer = renderer.borrow_mut();
+        renderer.clear();
+        renderer.surface_set_clear_color(color);
+        let window = Rectangle::new(0.0, 0.0, renderer.surface_width(), renderer.surface_height());
+        renderer.prepare(resource_manager.clone(), window);
+        renderer.submit(resource_manager);
+    }
+
+    /// Creates the renderer for the window. On the web the renderer is set when it was created, see
+    /// [`InternalMessage::RendererCreated`].
+    fn create_renderer(&mut self, craft_app: &App, winit_window: Arc<WinitWindow>) {
         let renderer_type = self.renderer_type;
 
         cfg_select! {
             not(target_arch = "wasm32") => {
-                    let renderer = craft_app.runtime.borrow_tokio_runtime().block_on(async {
-                        let renderer: Rc<RefCell<dyn Renderer>> = renderer_type.create(winit_window.clone()).await;
+                // macOS only creates the surface of a window on the main thread.
+                let render_thread = craft_app.craft_options.render_thread && !cfg!(target_os = "macos");
+                if craft_app.craft_options.render_thread && !render_thread {
+                    warn!("Render threads aren't supported on macOS, drawing on the UI thread");
+                }
+                if render_thread {
+                    self.renderer = Rc::new(RefCell::new(ThreadedRenderer::new(renderer_type, winit_window)));
+                    info!("Created renderer on a render thread");
+                    return;
+                }
+                let renderer = craft_app.runtime.borrow_tokio_runtime().block_on(async {
+                    let renderer: Rc<RefCell<dyn Renderer>> = renderer_type.create(winit_window.clone()).await;
                     renderer
                 });
                 self.renderer = renderer;
@@ -598,25 +972,16 @@ impl WindowInternal {
                 });
             }
         }
+    }
 
-        #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
-        {
-            let action_handler = CraftAccessHandler {};
-            let deactivation_handler = CraftDeactivationHandler::new();
-
-            let tree_update = self.on_request_redraw(craft_app);
-
-            let craft_activation_handler = CraftActivationHandler::new(tree_update);
-            self.accesskit_adapter = Some(Adapter::with_direct_handlers(
-                event_loop,
-                &winit_window,
-                craft_activation_handler,
-                action_handler,
-                deactivation_handler,
-            ));
-        }
-
-        winit_window.set_visible(true);
+    /// Replaces a renderer that failed to draw a frame with a new one. See [`crate::kiosk`].
+    pub(crate) fn restart_renderer(&mut self, craft_app: &App) {
+        let Some(winit_window) = self.winit_window.clone() else {
+            return;
+        };
+        self.create_renderer(craft_app, winit_window);
+        self.on_resize(self.window_size);
+        scene_changed();
     }
 
     #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
@@ -669,7 +1034,23 @@ impl WindowInternal {
             if root_dirty {
                 /*let span = span!(Level::INFO, "layout(taffy)");
                 let _enter = span.enter();*/
-                taffy_tree.compute_layout(root_node, available_space, text_context, resource_manager.clone());
+                let custom_layouts = find_custom_layouts(&*self);
+                let scale_factor = self.effective_scale_factor();
+                // A second pass fixes elements whose custom layout was given a different width than they got.
+                for _ in 0..2 {
+                    apply_custom_layouts(
+                        &custom_layouts,
+                        taffy_tree,
+                        text_context,
+                        resource_manager.clone(),
+                        scale_factor,
+                        window_size.width,
+                    );
+                    taffy_tree.compute_layout(root_node, available_space, text_context, resource_manager.clone());
+                    if !custom_layouts_changed(&custom_layouts, taffy_tree, window_size.width) {
+                        break;
+                    }
+                }
             }
 
             //if self.taffy_tree.borrow().is_apply_layout_dirty() {
@@ -680,6 +1061,7 @@ impl WindowInternal {
                 // TODO: move into taffy_tree
                 let mut layout_order: u32 = 0;
                 let sf = self.effective_scale_factor();
+                set_layout_viewport(Some(Rectangle::new(0.0, 0.0, window_size.width, window_size.height)));
                 self.apply_layout(
                     taffy_tree,
                     Point::new(0.0, 0.0),
@@ -694,19 +1076,55 @@ impl WindowInternal {
                     )),
                     sf,
                 );
+                set_layout_viewport(None);
                 taffy_tree.apply_layout(root_node);
             }
             //}
         });
 
+        if self.debug_layout {
+            self.check_layout();
+        }
+
         root_node
     }
 
+    /// Looks for layout bugs and warns about the ones that weren't there after the last layout pass.
+    fn check_layout(&mut self) {
+        let issues = find_layout_issues(&*self);
+        for issue in &issues {
+            if !self.layout_issues.iter().any(|previous| previous.is_same(issue)) {
+                warn!("Layout issue: {issue}");
+            }
+        }
+        self.layout_issues = issues;
+    }
+
+    /// Outlines the elements with layout issues on top of everything else.
+    fn draw_layout_issues(&self, renderer: &mut dyn Renderer) {
+        if self.layout_issues.is_empty() {
+            return;
+        }
+        let scale_factor = self.effective_scale_factor();
+        renderer.start_overlay();
+        for issue in &self.layout_issues {
+            let bounds = issue.bounds.scale(scale_factor);
+            renderer.draw_rect_outline(bounds, Color::from_rgb8(255, 0, 0), 2.0 * scale_factor);
+        }
+        renderer.end_overlay();
+    }
+
     fn draw_window(&mut self, text_context: &mut TextContext, resource_manager: Arc<ResourceManager>) {
+        let encode_start = time::Instant::now();
         let renderer_clone = self.renderer.clone();
         self.renderer.borrow_mut().clear();
 
+        set_drawing_window_active(self.is_active);
         self.draw(&mut *renderer_clone.borrow_mut(), resource_manager.clone(), self.effective_scale_factor(), text_context);
+        draw_snap_guides(&mut *renderer_clone.borrow_mut(), &self.snap_guides, self.effective_scale_factor());
+        if self.debug_layout {
+            self.draw_layout_issues(&mut *renderer_clone.borrow_mut());
+        }
 
         self.winit_window.clone().unwrap().pre_present_notify();
 
@@ -716,7 +1134,56 @@ impl WindowInternal {
 
             let window = Rectangle::new(0.0, 0.0, renderer.borrow().surface_width(), renderer.borrow().surface_height());
             renderer.borrow_mut().prepare(resource_manager.clone(), window);
+            self.frame_stats.encode = encode_start.elapsed();
+
+            let submit_start = time::Instant::now();
             renderer.borrow_mut().submit(resource_manager.clone());
+            self.frame_stats.gpu = submit_start.elapsed();
+
+            let renderer = renderer.borrow();
+            self.frame_stats.count_commands(&renderer.render_list().commands);
+            self.frame_stats.texture_bytes = renderer.image_cache_stats().resident_bytes;
+        }
+
+        self.capture_elements(text_context, resource_manager);
+    }
+
+    /// Draws the elements of this window waiting to be captured, and queues their images for them.
+    fn capture_elements(&mut self, text_context: &mut TextContext, resource_manager: Arc<ResourceManager>) {
+        let window = self.element_data.me.as_ptr().cast::<()>();
+        let mut captures = Vec::new();
+        CAPTURES.with_borrow_mut(|pending| {
+            pending.retain(|(element, scale)| {
+                let Some(element) = element.upgrade() else {
+                    return false;
+                };
+                // This window is borrowed while drawing, and other elements are checked for being in it.
+                let in_window = element.as_ptr().cast::<()>() == window
+                    || element.borrow().element_data().window.as_ref().is_some_and(|element_window| {
+                        element_window.as_ptr().cast::<()>() == window
+                    });
+                if in_window {
+                    captures.push((element, *scale));
+                }
+                !in_window
+            });
+        });
+
+        let renderer = self.renderer.clone();
+        let mut renderer = renderer.borrow_mut();
+        for (element, scale) in captures {
+            let scale_factor = self.effective_scale_factor() * scale;
+            let image = if element.as_ptr().cast::<()>() == window {
+                capture_element(self, &mut *renderer, resource_manager.clone(), scale_factor, text_context)
+            } else {
+                let mut element_mut = element.borrow_mut();
+                capture_element(&mut *element_mut, &mut *renderer, resource_manager.clone(), scale_factor, text_context)
+            };
+
+            match image {
+                Some(image) => queue_event(Event::new(element), EventKind::ElementCaptured(Rc::new(image))),
+                None => warn!("The renderer can't draw an element to an image."),
+            }
         }
     }
 
@@ -724,9 +1191,36 @@ impl WindowInternal {
         self.renderer.borrow_mut().screenshot()
     }
 
+    fn request_screenshot(&self) {
+        self.renderer.borrow().request_readback();
+    }
+
     fn close(&self) {
         if let Some(winit_window) = &self.winit_window {
             queue_window_event(winit_window.id(), WindowEvent::CloseRequested);
         }
     }
 }
+
+/// Makes the window transparent and asks the platform to draw the material behind it.
+fn with_window_material(window_attributes: WindowAttributes, material: WindowMaterial) -> WindowAttributes {
+    if !material.is_translucent() {
+        return window_attributes;
+    }
+
+    // Blur is implemented by macOS and by Wayland compositors that support it, and ignored elsewhere.
+    let window_attributes = window_attributes.with_transparent(true).with_blur(true);
+
+    #[cfg(target_os = "windows")]
+    let window_attributes = {
+        use winit::platform::windows::{BackdropType, WindowAttributesExtWindows};
+
+        let backdrop = match material {
+            WindowMaterial::Mica => BackdropType::MainWindow,
+            _ => BackdropType::TransientWindow,
+        };
+        window_attributes.with_system_backdrop(backdrop)
+    };
+
+    window_attributes
+}
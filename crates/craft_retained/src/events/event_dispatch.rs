@@ -2,13 +2,18 @@ use craft_renderer::renderer::Renderer;
 use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::rc::{Rc, Weak};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
 
 use craft_primitives::geometry::Point;
+#[cfg(target_arch = "wasm32")]
+use web_time::Instant;
 
 use crate::app::{FOCUS, dequeue_event};
 use crate::elements::ElementInternals;
 use crate::events::helpers::{call_default_element_event_handler, call_user_event_handlers, find_target, freeze_target_list};
-use crate::events::{Event, EventKind};
+use crate::events::{Event, EventKind, HoverIntentTracker};
+use crate::spatial_navigation;
 use crate::text::text_context::TextContext;
 
 pub(super) fn dispatch_capturing_event(
@@ -17,6 +22,18 @@ pub(super) fn dispatch_capturing_event(
 ) {
 }
 
+/// Drains the event dispatch queue and invokes user callbacks.
+pub(crate) fn dispatch_queued_events(text_context: &mut TextContext) {
+    while let Some((event, message)) = dequeue_event() {
+        let mut targets: VecDeque<Rc<RefCell<dyn ElementInternals>>> = freeze_target_list(event.target);
+        // Handle capturing
+        dispatch_capturing_event(&message, &mut targets);
+
+        // Handle bubbling
+        let _ = dispatch_bubbling_event(&message, &mut targets, text_context);
+    }
+}
+
 /// Dispatches 1 event to many elements.
 /// The first dispatch happens at the top-most visual element.
 pub(super) fn dispatch_bubbling_event(
@@ -43,6 +60,8 @@ pub(crate) struct EventDispatcher {
     /// A "frozen" target list used to diff against the current target list.
     /// This is useful for pointer enter, leave, etc.
     previous_targets: VecDeque<Weak<RefCell<dyn ElementInternals>>>,
+    /// Times how long the pointer rests on the hovered elements that want hover intent events.
+    hover_intent: HoverIntentTracker,
 }
 
 impl EventDispatcher {
@@ -50,6 +69,7 @@ impl EventDispatcher {
     pub fn new() -> Self {
         Self {
             previous_targets: Default::default(),
+            hover_intent: Default::default(),
         }
     }
 
@@ -78,7 +98,7 @@ impl EventDispatcher {
     ///
     /// Note: This event does not bubble.
     pub(super) fn maybe_dispatch_pointer_leave(
-        &self,
+        &mut self,
         text_context: &mut TextContext,
         targets: &VecDeque<Rc<RefCell<dyn ElementInternals>>>,
     ) {
@@ -105,6 +125,9 @@ impl EventDispatcher {
             // We had a prev target, but we don't in the new list. (PointerLeave)
             if !found {
                 self.dispatch_once(&EventKind::PointerLeave(), text_context, &prev_target.clone());
+                if self.hover_intent.leave(prev_target_id) {
+                    self.dispatch_once(&EventKind::HoverIntentEnded(), text_context, &prev_target.clone());
+                }
             }
         }
     }
@@ -113,11 +136,15 @@ impl EventDispatcher {
     /// `pointer_enter` to any element that exists in the current list
     /// but not in the previous one.
     ///
+    /// Elements that want hover intent events start being timed.
+    ///
     /// Note: This event does not bubble.
     pub(super) fn maybe_dispatch_pointer_enter(
-        &self,
+        &mut self,
         text_context: &mut TextContext,
         targets: &VecDeque<Rc<RefCell<dyn ElementInternals>>>,
+        mouse_position: Option<Point>,
+        now: Instant,
     ) {
         for target in targets.iter().rev() {
             let mut found = false;
@@ -140,6 +167,18 @@ impl EventDispatcher {
             // We weren't in the prev target list, but we are in the new list. (PointerEnter)
             if !found {
                 self.dispatch_once(&EventKind::PointerEnter(), text_context, &target.clone());
+
+                let (wants_hover_intent, hover_intent) = {
+                    let target = target.borrow();
+                    let element_data = target.element_data();
+                    (
+                        !element_data.on_hover_intent.is_empty() || !element_data.on_hover_intent_ended.is_empty(),
+                        element_data.hover_intent,
+                    )
+                };
+                if wants_hover_intent && let Some(mouse_position) = mouse_position {
+                    self.hover_intent.enter(target_id, Rc::downgrade(target), hover_intent, mouse_position, now);
+                }
             }
         }
     }
@@ -195,8 +234,14 @@ impl EventDispatcher {
         }
 
         if message.is_pointer_event() {
+            let now = Instant::now();
             self.maybe_dispatch_pointer_leave(text_context, &targets);
-            self.maybe_dispatch_pointer_enter(text_context, &targets);
+            self.maybe_dispatch_pointer_enter(text_context, &targets, mouse_position, now);
+            if let EventKind::PointerMovedEvent(_) = message
+                && let Some(mouse_position) = mouse_position
+            {
+                self.hover_intent.pointer_moved(mouse_position, now);
+            }
         }
 
         // Handle capturing
@@ -216,6 +261,10 @@ impl EventDispatcher {
                     break;
                 }
             }
+
+            if let EventKind::KeyboardInputEvent(keyboard_event) = message {
+                spatial_navigation::on_keyboard_input(&root, keyboard_event);
+            }
         }
 
         // NOTE: May dispatch gotpointercapture and lostpointercapture. Handles capturing and bubbling.
@@ -229,16 +278,25 @@ impl EventDispatcher {
                 .maybe_handle_implicit_pointer_capture_release(message, text_context);
         }
 
-        // Drain the event dispatch queue and invoke user callbacks.
-        while let Some((event, message)) = dequeue_event() {
-            let mut targets: VecDeque<Rc<RefCell<dyn ElementInternals>>> = freeze_target_list(event.target);
-            // Handle capturing
-            dispatch_capturing_event(&message, &mut targets);
-
-            // Handle bubbling
-            let _ = dispatch_bubbling_event(&message, &mut targets, text_context);
-        }
+        dispatch_queued_events(text_context);
 
         self.previous_targets = targets.iter().map(Rc::downgrade).collect();
     }
+
+    /// Dispatches hover intent to the elements the pointer rested on long enough. Returns true if any were
+    /// dispatched.
+    ///
+    /// Note: This event does not bubble.
+    pub(crate) fn dispatch_hover_intents(&mut self, text_context: &mut TextContext) -> bool {
+        let due = self.hover_intent.take_due(Instant::now());
+        let mut dispatched = false;
+        for target in due.iter().filter_map(Weak::upgrade) {
+            self.dispatch_once(&EventKind::HoverIntent(), text_context, &target);
+            dispatched = true;
+        }
+        if dispatched {
+            dispatch_queued_events(text_context);
+        }
+        dispatched
+    }
 }
@@ -1,7 +1,7 @@
 use craft_renderer::renderer::Renderer;
 use std::cell::RefCell;
 use std::collections::VecDeque;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 
 use craft_primitives::geometry::Point;
 
@@ -9,8 +9,9 @@ use craft_renderer::TargetItem;
 
 use crate::app::ELEMENTS;
 use crate::elements::ElementInternals;
-use crate::events::pointer_capture::PointerCapture;
+use crate::events::pointer_capture::{PointerCapture, captured_pointer_id};
 use crate::events::{Event, EventKind};
+use crate::scene::scene_changed;
 use crate::text::text_context::TextContext;
 
 pub(super) fn freeze_target_list(
@@ -72,6 +73,8 @@ pub(super) fn call_user_event_handlers(
     message: &EventKind,
     text_context: &mut TextContext,
 ) {
+    // Every event may change what is drawn, even when the handlers prevent the default handling.
+    scene_changed();
     match message {
         EventKind::PointerEnter() => {
             let element_data = current_target.borrow().element_data().clone();
@@ -87,6 +90,20 @@ pub(super) fn call_user_event_handlers(
                 (*handler)(event);
             }
         }
+        EventKind::HoverIntent() => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_hover_intent {
+                (*handler)(event);
+            }
+        }
+        EventKind::HoverIntentEnded() => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_hover_intent_ended {
+                (*handler)(event);
+            }
+        }
         EventKind::PointerButtonUp(e) => {
             let element_data = current_target.borrow().element_data().clone();
 
@@ -116,7 +133,143 @@ pub(super) fn call_user_event_handlers(
             }
         }
         EventKind::PointerScroll(_) => {}
+        EventKind::PointerGesture(_) => {}
         EventKind::ImeEvent(_) => {}
+        EventKind::FileHovered(_) => {}
+        EventKind::FileHoverCancelled() => {}
+        EventKind::FilesDropped(paths) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_files_dropped {
+                (*handler)(event, paths);
+            }
+        }
+        EventKind::FilesSelected(paths) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_files_selected {
+                (*handler)(event, paths);
+            }
+        }
+        EventKind::SpellingSuggestions(suggestions) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_spelling_suggestions {
+                (*handler)(event, suggestions);
+            }
+        }
+        EventKind::SpeechFinished(finished) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_speech_finished {
+                (*handler)(event, *finished);
+            }
+        }
+        EventKind::HttpStream(http_stream) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_http_stream {
+                (*handler)(event, http_stream);
+            }
+        }
+        EventKind::FilesChanged(files_changed) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_files_changed {
+                (*handler)(event, files_changed);
+            }
+        }
+        EventKind::Process(process) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_process {
+                (*handler)(event, process);
+            }
+        }
+        EventKind::Keyring(keyring) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_keyring {
+                (*handler)(event, keyring);
+            }
+        }
+        EventKind::WebSocket(web_socket) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_web_socket {
+                (*handler)(event, web_socket);
+            }
+        }
+        EventKind::SystemSettingsChanged(settings) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_system_settings_changed {
+                (*handler)(event, settings);
+            }
+        }
+        EventKind::UserIdle(idle_time) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_user_idle {
+                (*handler)(event, *idle_time);
+            }
+        }
+        EventKind::UserActive() => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_user_active {
+                (*handler)(event);
+            }
+        }
+        EventKind::KioskReset() => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_kiosk_reset {
+                (*handler)(event);
+            }
+        }
+        EventKind::PowerStatusChanged(status) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_power_status_changed {
+                (*handler)(event, status);
+            }
+        }
+        EventKind::NetworkStatusChanged(status) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_network_status_changed {
+                (*handler)(event, *status);
+            }
+        }
+        EventKind::JumpListActivated(activation) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_jump_list_activated {
+                (*handler)(event, activation);
+            }
+        }
+        EventKind::RecentFilesChanged(files) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_recent_files_changed {
+                (*handler)(event, files);
+            }
+        }
+        EventKind::UpdateAvailable(update) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_update_available {
+                (*handler)(event, update);
+            }
+        }
+        EventKind::UpdateDownloaded(download) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_update_downloaded {
+                (*handler)(event, download);
+            }
+        }
         EventKind::LinkClicked(_) => {}
         EventKind::DropdownToggled(_) => {}
         EventKind::DropdownItemSelected(item) => {
@@ -129,6 +282,132 @@ pub(super) fn call_user_event_handlers(
                 current_target.borrow_mut().on_event(message, text_context, event, Some(event.target.clone()));
             }
         }
+        EventKind::DragListItemMoved(moved) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_drag_list_item_moved {
+                (*handler)(event, moved);
+            }
+        }
+        EventKind::ListBoxSelectionChanged(selected) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_list_box_selection_changed {
+                (*handler)(event, selected);
+            }
+        }
+        EventKind::SelectionAreaChanged(selected) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_selection_area_changed {
+                (*handler)(event, selected);
+            }
+        }
+        EventKind::RatingChanged(rating) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_rating_changed {
+                (*handler)(event, *rating);
+            }
+        }
+        EventKind::TagsChanged(tags) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_tags_changed {
+                (*handler)(event, tags);
+            }
+        }
+        EventKind::SegmentedInputChanged(value) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_segmented_input_changed {
+                (*handler)(event, value);
+            }
+        }
+        EventKind::TimeChanged(time) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_time_changed {
+                (*handler)(event, *time);
+            }
+        }
+        EventKind::DurationChanged(duration) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_duration_changed {
+                (*handler)(event, *duration);
+            }
+        }
+        EventKind::StepperStepChanged(step) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_stepper_step_changed {
+                (*handler)(event, *step);
+            }
+        }
+        EventKind::StepperFinished() => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_stepper_finished {
+                (*handler)(event);
+            }
+        }
+        EventKind::PropertyChanged(changed) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_property_changed {
+                (*handler)(event, changed);
+            }
+        }
+        EventKind::CellEdited(edited) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_cell_edited {
+                (*handler)(event, edited);
+            }
+        }
+        EventKind::ViewportChanged(viewport) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_viewport_changed {
+                (*handler)(event, *viewport);
+            }
+        }
+        EventKind::ImageCropChanged(crop) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_image_crop_changed {
+                (*handler)(event, *crop);
+            }
+        }
+        EventKind::ElementCaptured(image) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_element_captured {
+                (*handler)(event, image.clone());
+            }
+        }
+        EventKind::PdfPageChanged(page) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_pdf_page_changed {
+                (*handler)(event, *page);
+            }
+        }
+        EventKind::MapClicked(click) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_map_clicked {
+                (*handler)(event, click);
+            }
+        }
+        EventKind::ColorPicked(color) => {
+            let element_data = current_target.borrow().element_data().clone();
+
+            for handler in &element_data.on_color_picked {
+                (*handler)(event, *color);
+            }
+        }
         EventKind::SwitchToggled(_) => {}
         EventKind::SliderValueChanged(slider_value) => {
             let element_data = current_target.borrow().element_data().clone();
@@ -193,7 +472,45 @@ pub(super) fn call_default_element_event_handler(
     text_context: &mut TextContext,
     message: &EventKind,
 ) {
-    current_target
-        .borrow_mut()
-        .on_event(message, text_context, event, Some(target.clone()));
+    let mut current_target = current_target.borrow_mut();
+    current_target.on_event(message, text_context, event, Some(target.clone()));
+    // Events may change state that is not tracked by layout or style, so the layers the element is drawn into are
+    // drawn again.
+    if may_change_drawing(message, &*current_target) {
+        current_target.invalidate_layer_cache();
+    }
+}
+
+/// Whether handling an event may change what an element draws without a setter or a redraw request.
+///
+/// The pointer passing over an element only does while the element holds the pointer capture, like during a drag.
+/// Elements that draw hover state invalidate their layers themselves.
+fn may_change_drawing(message: &EventKind, element: &dyn ElementInternals) -> bool {
+    match message {
+        EventKind::PointerEnter()
+        | EventKind::PointerLeave()
+        | EventKind::HoverIntent()
+        | EventKind::HoverIntentEnded()
+        | EventKind::FileHovered(_)
+        | EventKind::FileHoverCancelled() => false,
+        EventKind::PointerMovedEvent(_) => holds_pointer_capture(element),
+        _ => true,
+    }
+}
+
+fn holds_pointer_capture(element: &dyn ElementInternals) -> bool {
+    let element_data = element.element_data();
+    // The window can't be borrowed while it handles the event itself.
+    let Some(window) = element_data.window.as_ref().and_then(|window| window.upgrade()) else {
+        return false;
+    };
+    let Ok(window) = window.try_borrow() else {
+        return false;
+    };
+    window
+        .pointer_capture
+        .borrow()
+        .pointer_captures
+        .get(&captured_pointer_id())
+        .is_some_and(|captured| Weak::ptr_eq(captured, &element_data.me))
 }
@@ -0,0 +1,101 @@
+//! App-wide hooks that see every input event of the windows before the elements do, for logging, analytics, input
+//! remapping and app-wide shortcuts.
+
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::elements::Window;
+use crate::events::EventKind;
+
+thread_local! {
+    static EVENT_HOOKS: RefCell<Vec<(EventHookId, EventHook)>> = const { RefCell::new(Vec::new()) };
+    static NEXT_EVENT_HOOK_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Called with the window an event was sent to and the event.
+pub type EventHook = Rc<dyn Fn(&Window, &EventKind) -> EventHookResult>;
+
+/// What happens to an event after a hook saw it.
+pub enum EventHookResult {
+    /// The event goes on to the next hook, and then to the elements.
+    Continue,
+    /// The event is dropped, e.g. because the hook handled it as a shortcut.
+    Consume,
+    /// This event goes on instead, e.g. a remapped key.
+    Replace(EventKind),
+}
+
+/// Identifies a hook, to remove it with [`remove_event_hook`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EventHookId(u64);
+
+/// Adds a hook that is called with every input event of the windows, like pointer, keyboard and IME events, before
+/// it is dispatched to the elements. Hooks are called in the order they were added.
+pub fn add_event_hook(hook: EventHook) -> EventHookId {
+    let id = EventHookId(NEXT_EVENT_HOOK_ID.get());
+    NEXT_EVENT_HOOK_ID.set(id.0 + 1);
+    EVENT_HOOKS.with_borrow_mut(|hooks| hooks.push((id, hook)));
+    id
+}
+
+pub fn remove_event_hook(id: EventHookId) {
+    EVENT_HOOKS.with_borrow_mut(|hooks| hooks.retain(|(hook_id, _)| *hook_id != id));
+}
+
+/// Runs the hooks on an event. Returns the event to dispatch, or `None` if a hook consumed it.
+pub(crate) fn run_event_hooks<'a>(window: &Window, event: &'a EventKind) -> Option<Cow<'a, EventKind>> {
+    // The hooks are cloned, so they can add and remove hooks.
+    let hooks: Vec<EventHook> = EVENT_HOOKS.with_borrow(|hooks| hooks.iter().map(|(_, hook)| hook.clone()).collect());
+    let mut event = Cow::Borrowed(event);
+    for hook in hooks {
+        match hook(window, &event) {
+            EventHookResult::Continue => {}
+            EventHookResult::Consume => return None,
+            EventHookResult::Replace(replacement) => event = Cow::Owned(replacement),
+        }
+    }
+    Some(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn hooks_can_replace_and_consume_events() {
+        let window = Window::new("Hooks");
+        let seen = Rc::new(RefCell::new(Vec::new()));
+
+        let remap = add_event_hook(Rc::new(|_: &Window, event: &EventKind| match event {
+            EventKind::FileHoverCancelled() => EventHookResult::Replace(EventKind::FilesDropped(Vec::new())),
+            _ => EventHookResult::Continue,
+        }));
+        let log = {
+            let seen = seen.clone();
+            add_event_hook(Rc::new(move |_: &Window, event: &EventKind| {
+                if let EventKind::FilesDropped(paths) = event {
+                    seen.borrow_mut().push(paths.len());
+                }
+                EventHookResult::Continue
+            }))
+        };
+        let consume = add_event_hook(Rc::new(|_: &Window, event: &EventKind| match event {
+            EventKind::FileHovered(_) => EventHookResult::Consume,
+            _ => EventHookResult::Continue,
+        }));
+
+        let cancelled = EventKind::FileHoverCancelled();
+        let replaced = run_event_hooks(&window, &cancelled);
+        assert!(matches!(replaced.as_deref(), Some(EventKind::FilesDropped(_))));
+        assert_eq!(*seen.borrow(), vec![0]);
+        assert!(run_event_hooks(&window, &EventKind::FileHovered(PathBuf::from("a.txt"))).is_none());
+
+        for id in [remap, log, consume] {
+            remove_event_hook(id);
+        }
+        assert!(matches!(run_event_hooks(&window, &cancelled).as_deref(), Some(EventKind::FileHoverCancelled())));
+    }
+}
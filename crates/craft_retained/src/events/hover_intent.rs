@@ -0,0 +1,163 @@
+//! Hover intent, for UI that opens on hover, like tooltips and menus.
+//!
+//! A [`EventKind::HoverIntent`] event is sent once the pointer rested on an element for a while, instead of on every
+//! pointer enter, so passing over the element on the way somewhere else doesn't open anything. The pointer counts as
+//! resting as long as it stays within a few pixels of where it stopped.
+//!
+//! [`EventKind::HoverIntent`]: crate::events::EventKind::HoverIntent
+
+use std::cell::RefCell;
+use std::rc::Weak;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+
+use craft_primitives::geometry::Point;
+#[cfg(target_arch = "wasm32")]
+use web_time::{Duration, Instant};
+
+use crate::elements::ElementInternals;
+
+/// When the pointer counts as resting on an element.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HoverIntent {
+    /// How long the pointer has to rest on the element.
+    pub delay: Duration,
+    /// How far the pointer may move, in logical pixels, and still count as resting.
+    pub max_movement: f64,
+}
+
+impl Default for HoverIntent {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(150),
+            max_movement: 6.0,
+        }
+    }
+}
+
+/// An element the pointer is over, which wants hover intent events.
+struct HoverCandidate {
+    id: u64,
+    target: Weak<RefCell<dyn ElementInternals>>,
+    intent: HoverIntent,
+    /// Where the pointer last stopped.
+    anchor: Point,
+    /// When the pointer stopped there.
+    since: Instant,
+    /// Whether the hover intent event was sent.
+    sent: bool,
+}
+
+/// Tracks how long the pointer rested on the hovered elements.
+#[derive(Default)]
+pub(crate) struct HoverIntentTracker {
+    candidates: Vec<HoverCandidate>,
+}
+
+impl HoverIntentTracker {
+    /// Starts timing an element the pointer entered.
+    pub(crate) fn enter(
+        &mut self,
+        id: u64,
+        target: Weak<RefCell<dyn ElementInternals>>,
+        intent: HoverIntent,
+        position: Point,
+        now: Instant,
+    ) {
+        self.candidates.retain(|candidate| candidate.id != id);
+        self.candidates.push(HoverCandidate {
+            id,
+            target,
+            intent,
+            anchor: position,
+            since: now,
+            sent: false,
+        });
+    }
+
+    /// Stops timing an element the pointer left. Returns true if its hover intent event was sent, so it has to be
+    /// ended.
+    pub(crate) fn leave(&mut self, id: u64) -> bool {
+        let Some(index) = self.candidates.iter().position(|candidate| candidate.id == id) else {
+            return false;
+        };
+        self.candidates.remove(index).sent
+    }
+
+    /// Restarts the timers of the elements the pointer moved too far on.
+    pub(crate) fn pointer_moved(&mut self, position: Point, now: Instant) {
+        for candidate in self.candidates.iter_mut().filter(|candidate| !candidate.sent) {
+            if candidate.anchor.distance(position) > candidate.intent.max_movement {
+                candidate.anchor = position;
+                candidate.since = now;
+            }
+        }
+    }
+
+    /// Returns the elements the pointer rested on long enough, which haven't been sent a hover intent event yet.
+    pub(crate) fn take_due(&mut self, now: Instant) -> Vec<Weak<RefCell<dyn ElementInternals>>> {
+        self.candidates
+            .iter_mut()
+            .filter(|candidate| !candidate.sent && now.duration_since(candidate.since) >= candidate.intent.delay)
+            .map(|candidate| {
+                candidate.sent = true;
+                candidate.target.clone()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::ContainerInner;
+
+    fn target() -> Weak<RefCell<dyn ElementInternals>> {
+        Weak::<RefCell<ContainerInner>>::new()
+    }
+
+    fn intent() -> HoverIntent {
+        HoverIntent {
+            delay: Duration::from_millis(100),
+            max_movement: 5.0,
+        }
+    }
+
+    #[test]
+    fn intent_is_sent_once_after_the_delay() {
+        let start = Instant::now();
+        let mut tracker = HoverIntentTracker::default();
+        tracker.enter(1, target(), intent(), Point::new(10.0, 10.0), start);
+
+        assert!(tracker.take_due(start + Duration::from_millis(50)).is_empty());
+        assert_eq!(tracker.take_due(start + Duration::from_millis(100)).len(), 1);
+        assert!(tracker.take_due(start + Duration::from_millis(200)).is_empty());
+        assert!(tracker.leave(1));
+    }
+
+    #[test]
+    fn moving_past_the_threshold_restarts_the_delay() {
+        let start = Instant::now();
+        let mut tracker = HoverIntentTracker::default();
+        tracker.enter(1, target(), intent(), Point::new(10.0, 10.0), start);
+
+        // Small movements still count as resting.
+        tracker.pointer_moved(Point::new(13.0, 14.0), start + Duration::from_millis(60));
+        assert_eq!(tracker.take_due(start + Duration::from_millis(100)).len(), 1);
+
+        tracker.enter(2, target(), intent(), Point::new(10.0, 10.0), start);
+        tracker.pointer_moved(Point::new(30.0, 10.0), start + Duration::from_millis(60));
+        assert!(tracker.take_due(start + Duration::from_millis(100)).is_empty());
+        assert_eq!(tracker.take_due(start + Duration::from_millis(160)).len(), 1);
+    }
+
+    #[test]
+    fn leaving_before_the_delay_sends_nothing() {
+        let start = Instant::now();
+        let mut tracker = HoverIntentTracker::default();
+        tracker.enter(1, target(), intent(), Point::new(10.0, 10.0), start);
+
+        assert!(!tracker.leave(1));
+        assert!(tracker.take_due(start + Duration::from_millis(200)).is_empty());
+    }
+}
@@ -1,45 +1,102 @@
 use std::any::Any;
 use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Duration;
 
 pub use ui_events;
 
 pub use winit::event::{ElementState, Ime, Modifiers, MouseButton};
 
+pub use crate::events::hooks::{EventHook, EventHookId, EventHookResult, add_event_hook, remove_event_hook};
+pub use crate::events::hover_intent::HoverIntent;
 pub use crate::events::mouse_wheel::MouseWheel;
 
-pub(crate) use event_dispatch::EventDispatcher;
+pub(crate) use event_dispatch::{EventDispatcher, dispatch_queued_events};
+pub(crate) use hooks::run_event_hooks;
+pub(crate) use hover_intent::HoverIntentTracker;
 
+use craft_primitives::geometry::Rectangle;
+use image::RgbaImage;
+use peniko::Color;
 use ui_events::keyboard::KeyboardEvent;
-use ui_events::pointer::{PointerButtonEvent, PointerScrollEvent, PointerUpdate};
+use ui_events::pointer::{PointerButtonEvent, PointerGestureEvent, PointerScrollEvent, PointerUpdate};
 
 use crate::PinnedFutureAny;
-use crate::elements::ElementInternals;
+use crate::elements::{CellValue, ChartViewport, ElementInternals, LatLng, PropertyValue, SegmentedValue, TimeOfDay};
+use crate::network::NetworkStatus;
+use crate::power::PowerStatus;
+use crate::shell::JumpListActivation;
+use crate::system_settings::SystemSettings;
+use crate::text::spell_check::SpellingSuggestions;
 use crate::utils::cloneable_any::CloneableAny;
 
 pub mod internal;
+pub mod timing;
 
 pub(crate) mod pointer_capture;
 
 mod event_dispatch;
 mod helpers;
+mod hooks;
+mod hover_intent;
 mod mouse_wheel;
 
 
+pub type CellEditedHandler = Rc<dyn Fn(&mut Event, &CellEdited)>;
 pub type CheckboxToggledHandler = Rc<dyn Fn(&mut Event, CheckboxToggled)>;
+pub type ColorPickedHandler = Rc<dyn Fn(&mut Event, Color)>;
+pub type DragListItemMovedHandler = Rc<dyn Fn(&mut Event, &DragListMove)>;
+pub type DurationChangedHandler = Rc<dyn Fn(&mut Event, Duration)>;
 pub type DropdownItemSelectedHandler = Rc<dyn Fn(&mut Event, usize)>;
+pub type ListBoxSelectionChangedHandler = Rc<dyn Fn(&mut Event, &BTreeSet<usize>)>;
+pub type MapClickedHandler = Rc<dyn Fn(&mut Event, &MapClick)>;
+pub type NetworkStatusChangedHandler = Rc<dyn Fn(&mut Event, NetworkStatus)>;
+pub type JumpListActivatedHandler = Rc<dyn Fn(&mut Event, &JumpListActivation)>;
+pub type RecentFilesChangedHandler = Rc<dyn Fn(&mut Event, &[PathBuf])>;
+pub type UpdateAvailableHandler = Rc<dyn Fn(&mut Event, &UpdateInfo)>;
+pub type UpdateDownloadedHandler = Rc<dyn Fn(&mut Event, &UpdateDownload)>;
+pub type PdfPageChangedHandler = Rc<dyn Fn(&mut Event, usize)>;
+pub type ElementCapturedHandler = Rc<dyn Fn(&mut Event, Rc<RgbaImage>)>;
+pub type ViewportChangedHandler = Rc<dyn Fn(&mut Event, ChartViewport)>;
+pub type FilesDroppedHandler = Rc<dyn Fn(&mut Event, &[PathBuf])>;
+pub type FilesSelectedHandler = Rc<dyn Fn(&mut Event, &[PathBuf])>;
+pub type HoverIntentHandler = Rc<dyn Fn(&mut Event)>;
+pub type HoverIntentEndedHandler = Rc<dyn Fn(&mut Event)>;
+pub type HttpStreamHandler = Rc<dyn Fn(&mut Event, &HttpStreamEvent)>;
+pub type ImageCropChangedHandler = Rc<dyn Fn(&mut Event, Rectangle)>;
 pub type KeyboardInputHandler = Rc<dyn Fn(&mut Event, &KeyboardEvent)>;
 pub type PointerEnterHandler = Rc<dyn Fn(&mut Event)>;
 pub type PointerEventHandler = Rc<dyn Fn(&mut Event, &PointerButtonEvent)>;
 pub type PointerLeaveHandler = Rc<dyn Fn(&mut Event)>;
 pub type PointerUpdateHandler = Rc<dyn Fn(&mut Event, &PointerUpdate)>;
+pub type PowerStatusChangedHandler = Rc<dyn Fn(&mut Event, &PowerStatus)>;
 pub type PointerCaptureHandler = Rc<dyn Fn(&mut Event)>;
+pub type PropertyChangedHandler = Rc<dyn Fn(&mut Event, &PropertyChanged)>;
 pub type RadioValueChangedHandler = Rc<dyn Fn(&mut Event, Rc<RefCell<String>>)>;
+pub type RatingChangedHandler = Rc<dyn Fn(&mut Event, f32)>;
 pub type ScrollHandler = Rc<dyn Fn(&mut Event)>;
+pub type SelectionAreaChangedHandler = Rc<dyn Fn(&mut Event, &BTreeSet<usize>)>;
+pub type SegmentedInputChangedHandler = Rc<dyn Fn(&mut Event, &SegmentedValue)>;
 pub type SliderValueChangedHandler = Rc<dyn Fn(&mut Event, f64)>;
+pub type SpeechFinishedHandler = Rc<dyn Fn(&mut Event, SpeechFinished)>;
+pub type SpellingSuggestionsHandler = Rc<dyn Fn(&mut Event, &SpellingSuggestions)>;
+pub type StepperFinishedHandler = Rc<dyn Fn(&mut Event)>;
+pub type StepperStepChangedHandler = Rc<dyn Fn(&mut Event, usize)>;
+pub type SystemSettingsChangedHandler = Rc<dyn Fn(&mut Event, &SystemSettings)>;
+pub type TagsChangedHandler = Rc<dyn Fn(&mut Event, &[String])>;
 pub type TextInputChangedHandler = Rc<dyn Fn(&mut Event, &TextInputChanged)>;
+pub type TimeChangedHandler = Rc<dyn Fn(&mut Event, TimeOfDay)>;
+pub type UserActiveHandler = Rc<dyn Fn(&mut Event)>;
+pub type KioskResetHandler = Rc<dyn Fn(&mut Event)>;
+pub type UserIdleHandler = Rc<dyn Fn(&mut Event, Duration)>;
 pub type UserMessage = dyn CloneableAny;
+pub type WebSocketHandler = Rc<dyn Fn(&mut Event, &WebSocketEvent)>;
+pub type FilesChangedHandler = Rc<dyn Fn(&mut Event, &FilesChangedEvent)>;
+pub type ProcessHandler = Rc<dyn Fn(&mut Event, &ProcessEvent)>;
+pub type KeyringHandler = Rc<dyn Fn(&mut Event, &KeyringEvent)>;
 
 
 #[derive(Clone)]
@@ -53,23 +110,110 @@ pub enum EventKind {
     LostPointerCapture(),
     PointerEnter(),
     PointerLeave(),
+    /// Generated when the pointer rested on an element, see [`HoverIntent`]. Like pointer enter, this does not bubble.
+    HoverIntent(),
+    /// Generated when the pointer left an element that was sent a hover intent event.
+    HoverIntentEnded(),
     PointerButtonUp(PointerButtonEvent),
     PointerButtonDown(PointerButtonEvent),
     KeyboardInputEvent(KeyboardEvent),
     PointerMovedEvent(PointerUpdate),
     PointerScroll(PointerScrollEvent),
+    /// Generated by touchpad gestures, such as pinching to zoom.
+    PointerGesture(PointerGestureEvent),
     Scroll(),
     ImeEvent(Ime),
+    /// Generated when a file dragged from the OS is over the window.
+    /// Sent to the element under the pointer once per dragged file.
+    FileHovered(PathBuf),
+    /// Generated when a file dragged from the OS leaves the window without being dropped.
+    FileHoverCancelled(),
+    /// The files dropped from the OS onto the element under the pointer.
+    FilesDropped(Vec<PathBuf>),
+    /// The files picked in a file picker, by browsing or by dropping them.
+    FilesSelected(Vec<PathBuf>),
     TextInputChanged(TextInputChanged),
+    /// Generated when a misspelled word in a text input is right clicked, with what it may have been meant to be.
+    SpellingSuggestions(SpellingSuggestions),
+    /// Generated when text an element asked to be spoken with `speak` is done.
+    SpeechFinished(SpeechFinished),
+    /// Generated when a WebSocket connection an element opened with `connect_web_socket` connected, received a
+    /// message or was lost.
+    WebSocket(WebSocketEvent),
+    /// Generated when the response of a request an element sent with `stream_http` started, sent more or ended.
+    HttpStream(HttpStreamEvent),
+    /// Generated when files under a path an element watches with `watch_files` changed.
+    FilesChanged(FilesChangedEvent),
+    /// Generated when a child process an element spawned with `spawn_process` wrote a line or exited.
+    Process(ProcessEvent),
+    /// Generated when a request an element sent to its `keyring` finished.
+    Keyring(KeyringEvent),
+    /// Sent to every window when the appearance settings of the operating system changed, like the accent color.
+    SystemSettingsChanged(SystemSettings),
+    /// Sent to every window when the time since the last input passed one of the idle thresholds, with the threshold.
+    UserIdle(Duration),
+    /// Sent to every window on the first input after a [`EventKind::UserIdle`] event.
+    UserActive(),
+    /// Sent to every window when a kiosk was left alone for its reset time, so the app goes back to its start screen.
+    /// See [`KioskOptions::reset_after`](crate::kiosk::KioskOptions::reset_after).
+    KioskReset(),
+    /// Sent to every window when the battery or power saving state of the device changed.
+    PowerStatusChanged(PowerStatus),
+    /// Sent to every window when the device went offline or came back online.
+    NetworkStatusChanged(NetworkStatus),
+    /// Sent to every window when the user chose an entry of the jump list or dock menu.
+    JumpListActivated(JumpListActivation),
+    /// Sent to every window when the recent files changed, with the files, most recent first.
+    RecentFilesChanged(Vec<PathBuf>),
+    /// Sent to every window when a newer version of the app was found, see the `update` module.
+    UpdateAvailable(UpdateInfo),
+    /// Sent to every window when the installer of an update was downloaded, or failed to download.
+    UpdateDownloaded(UpdateDownload),
     LinkClicked(String),
     /// Generated when a dropdown is opened or closed. The boolean is the status of is_open after the event has occurred.
     DropdownToggled(bool),
     /// The index of the item selected in the list.
     /// For example, if you select the first item the index will be 0.
     DropdownItemSelected(usize),
+    /// Generated when an item is dropped into a different position of a drag list group.
+    DragListItemMoved(DragListMove),
+    /// The indices of the items selected in a list box, after the selection changed.
+    ListBoxSelectionChanged(BTreeSet<usize>),
+    /// The indices of the children selected in a selection area, after the selection changed.
+    SelectionAreaChanged(BTreeSet<usize>),
     /// Generated when a switch is toggled. The boolean is the status of toggled after the event has occurred.
     SwitchToggled(bool),
     SliderValueChanged(f64),
+    /// The value of a rating, after the user picked it.
+    RatingChanged(f32),
+    /// The tags of a tag input, after a tag was added or removed.
+    TagsChanged(Vec<String>),
+    /// The value of a segmented input, after a segment changed.
+    SegmentedInputChanged(SegmentedValue),
+    /// The time of a time picker, after it changed.
+    TimeChanged(TimeOfDay),
+    /// The duration of a duration picker, after it changed.
+    DurationChanged(Duration),
+    /// The index of the step shown by a stepper, after the step changed.
+    StepperStepChanged(usize),
+    /// Generated when the last step of a stepper is confirmed.
+    StepperFinished(),
+    /// A property edited in a property grid, after its value changed.
+    PropertyChanged(PropertyChanged),
+    /// A cell edited in a data grid, after its value changed.
+    CellEdited(CellEdited),
+    /// The visible part of a chart, after the user zoomed or panned it.
+    ViewportChanged(ChartViewport),
+    /// The crop rectangle of an image crop, in image pixels, after the user moved or resized it.
+    ImageCropChanged(Rectangle),
+    /// The color under the pointer when the user clicked, after an element asked for it with `pick_color`.
+    ColorPicked(Color),
+    /// Generated when a map is clicked without dragging it.
+    MapClicked(MapClick),
+    /// Generated when a PDF viewer shows another page, with the index of the page.
+    PdfPageChanged(usize),
+    /// An image of an element and its children, after the element asked for it with `capture`.
+    ElementCaptured(Rc<RgbaImage>),
     ElementMessage(Arc<UserMessage>),
     RadioValueChanged(Rc<RefCell<String>>),
     CheckboxToggled(CheckboxToggled),
@@ -81,6 +225,188 @@ pub struct CheckboxToggled {
     pub status: bool,
 }
 
+/// Where an item of a drag list group was moved from and to.
+///
+/// Lists are identified by the order they were created in their group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DragListMove {
+    pub source_list: usize,
+    pub source_index: usize,
+    pub destination_list: usize,
+    /// The index of the item in the destination list after the move.
+    pub destination_index: usize,
+}
+
+/// The new value of a property in a property grid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PropertyChanged {
+    pub name: String,
+    pub value: PropertyValue,
+}
+
+/// The new value of a cell in a data grid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellEdited {
+    pub row: usize,
+    pub col: usize,
+    pub value: CellValue,
+}
+
+/// Where a map was clicked, and the marker or line under the pointer.
+///
+/// Markers and lines are identified by their index in the map. Markers are on top of lines, so only one of them is
+/// set.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapClick {
+    pub location: LatLng,
+    pub marker: Option<usize>,
+    pub polyline: Option<usize>,
+}
+
+/// Which text was spoken, and how it ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpeechFinished {
+    /// The id returned when the text was queued.
+    pub utterance: u64,
+    /// Whether the text was cut off by a call to stop, or by other text that interrupted it.
+    pub interrupted: bool,
+}
+
+/// What happened to a WebSocket connection, see the `websocket` module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebSocketEvent {
+    /// The id of the connection, see `WebSocket::id`.
+    pub connection: u64,
+    pub kind: WebSocketEventKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum WebSocketEventKind {
+    /// The connection was opened, or opened again after it was lost.
+    Connected,
+    Message(WebSocketMessage),
+    /// The connection failed or was lost. `retry_in` is when it is tried again, or `None` if it isn't.
+    Disconnected { error: String, retry_in: Option<Duration> },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WebSocketMessage {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+/// What arrived of the response to a request, see the `http_stream` module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpStreamEvent {
+    /// The id of the request, see `HttpStream::id`.
+    pub stream: u64,
+    pub kind: HttpStreamEventKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum HttpStreamEventKind {
+    /// The response started, with its status code.
+    Started { status: u16 },
+    /// Bytes of a response that isn't a `text/event-stream`.
+    Chunk(Vec<u8>),
+    ServerSentEvent(ServerSentEvent),
+    /// The whole response arrived.
+    Finished,
+    /// The request failed, or the response was cut off.
+    Failed(String),
+}
+
+/// An event of a `text/event-stream` response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServerSentEvent {
+    /// The type of the event, `message` unless the server named it.
+    pub event: String,
+    /// The data lines of the event, joined by newlines.
+    pub data: String,
+    /// The last event id the server sent.
+    pub id: Option<String>,
+}
+
+/// The files that changed under a watched path, see the `file_watch` module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilesChangedEvent {
+    /// The id of the watch, see `FileWatch::id`.
+    pub watch: u64,
+    pub changes: Vec<FileChange>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub kind: FileChangeKind,
+}
+
+/// What happened to a file since the last [`FilesChangedEvent`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileChangeKind {
+    /// The file was created, or renamed to this path.
+    Created,
+    Modified,
+    /// The file was removed, or renamed to another path.
+    Removed,
+}
+
+/// What a child process wrote or did, see the `process` module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProcessEvent {
+    /// The id of the process, see `ChildProcess::id`.
+    pub process: u64,
+    pub kind: ProcessEventKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProcessEventKind {
+    /// A line the process wrote to stdout, without the line break.
+    Stdout(String),
+    /// A line the process wrote to stderr, without the line break.
+    Stderr(String),
+    /// The process exited with a code, or `None` if it was killed by a signal.
+    Exited(Option<i32>),
+}
+
+/// The result of a request to the keyring, see the `keyring` module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyringEvent {
+    /// The id the request method of `Keyring` returned.
+    pub request: u64,
+    pub account: String,
+    pub kind: KeyringEventKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeyringEventKind {
+    /// The secret that was read, or `None` if none is stored.
+    Secret(Option<String>),
+    Stored,
+    Deleted,
+    /// The credential store refused the request, e.g. because the user didn't unlock it.
+    Failed(String),
+}
+
+/// A newer version of the app, see the `update` module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdateInfo {
+    pub version: String,
+    /// What changed, as published with the version.
+    pub notes: String,
+    /// The installer of the version, if one was published.
+    pub download_url: Option<String>,
+    /// The SHA-256 checksum of the installer, in hex. The installer is only downloaded if it was published.
+    pub sha256: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UpdateDownload {
+    /// The installer, in a directory of the temporary directory only the user can access. Its checksum was verified.
+    Finished(PathBuf),
+    Failed(String),
+}
+
 #[derive(Clone)]
 pub struct TextInputChanged {
     pub value: String,
@@ -108,6 +434,10 @@ impl EventKind {
                 | EventKind::GotPointerCapture()
                 | EventKind::LostPointerCapture()
                 | EventKind::PointerScroll(_)
+                | EventKind::PointerGesture(_)
+                | EventKind::FileHovered(_)
+                | EventKind::FileHoverCancelled()
+                | EventKind::FilesDropped(_)
         )
     }
 
@@ -9,6 +9,14 @@ use crate::events::EventKind;
 use crate::events::event_dispatch::{dispatch_bubbling_event, dispatch_capturing_event};
 use crate::text::text_context::TextContext;
 
+/// The id pointer captures are tracked under.
+///
+/// Pointer events don't carry the id of their pointer yet, so every pointer shares this one and a capture applies to
+/// all of them.
+pub(crate) fn captured_pointer_id() -> PointerId {
+    PointerId::new(1).unwrap()
+}
+
 /// Stores window specific information like pointer captures, focus (soon), etc.
 #[derive(Default, Clone)]
 pub struct PointerCapture {
@@ -33,7 +41,7 @@ impl PointerCapture {
         // https://w3c.github.io/pointerevents/#implicit-pointer-capture
         //
         let pointer_capture_element_id: Option<Weak<RefCell<dyn ElementInternals>>> = {
-            let key = &PointerId::new(1).unwrap();
+            let key = &captured_pointer_id();
             if matches!(message, EventKind::GotPointerCapture()) {
                 // Check pending (step 2):
                 // https://w3c.github.io/pointerevents/#process-pending-pointer-capture
@@ -49,7 +57,7 @@ impl PointerCapture {
     /// Checks if Got or Lost events need to be dispatched and updates the current pointer capture.
     pub(super) fn process_pending_pointer_capture(&mut self, text_context: &mut TextContext) {
         // 4.1.3.2 Process pending pointer capture
-        let key = &PointerId::new(1).unwrap();
+        let key = &captured_pointer_id();
         let (pointer_capture_val, pending_pointer_capture_val) = {
             let pointer_capture_val = self.pointer_captures.get(key);
             let pending_pointer_capture_val = self.pending_pointer_captures.get(key);
@@ -118,7 +126,7 @@ impl PointerCapture {
         {
             // Immediately after firing the pointerup or pointercancel events, the user agent MUST clear the pending pointer capture target override
             // for the pointerId of the pointerup or pointercancel event that was just dispatched
-            let key = &PointerId::new(1).unwrap();
+            let key = &captured_pointer_id();
             let _ = self.pending_pointer_captures.remove(key);
 
             self.process_pending_pointer_capture(text_context);
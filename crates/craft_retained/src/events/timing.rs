@@ -0,0 +1,299 @@
+//! Debounced and throttled handlers, for work that shouldn't run on every event, like searching as the user types.
+//!
+//! The work runs later on the GUI thread, after the event was handled, so it gets a copy of the event data instead of
+//! the [`Event`] itself.
+
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::rc::Rc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+
+use craft_runtime::{Job, run_later};
+#[cfg(target_arch = "wasm32")]
+use web_time::{Duration, Instant};
+
+use crate::events::Event;
+
+type Work = Box<dyn FnOnce()>;
+
+/// Runs only the last of a burst of calls, once no call came in for a while.
+#[derive(Clone)]
+pub struct Debounce {
+    delay: Duration,
+    state: Rc<RefCell<DebounceState>>,
+}
+
+struct DebounceState {
+    work: Option<Work>,
+    /// When the work runs, unless another call comes in first.
+    deadline: Instant,
+    scheduled: bool,
+}
+
+impl Debounce {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            state: Rc::new(RefCell::new(DebounceState {
+                work: None,
+                deadline: Instant::now(),
+                scheduled: false,
+            })),
+        }
+    }
+
+    /// Runs `work` after the delay, unless this is called again before then, in which case only the newer work runs.
+    pub fn call(&self, work: impl FnOnce() + 'static) {
+        let mut state = self.state.borrow_mut();
+        state.work = Some(Box::new(work));
+        state.deadline = Instant::now() + self.delay;
+        if !state.scheduled {
+            state.scheduled = true;
+            schedule_debounce(self.state.clone(), self.delay);
+        }
+    }
+
+    /// Drops the work waiting to run.
+    pub fn cancel(&self) {
+        self.state.borrow_mut().work = None;
+    }
+
+    /// Whether work is waiting to run.
+    pub fn is_pending(&self) -> bool {
+        self.state.borrow().work.is_some()
+    }
+}
+
+fn schedule_debounce(state: Rc<RefCell<DebounceState>>, delay: Duration) {
+    run_later(Job::after(
+        Box::new(move || {
+            let now = Instant::now();
+            let deadline = state.borrow().deadline;
+            // Calls that came in since the job was scheduled moved the deadline.
+            if now < deadline {
+                schedule_debounce(state.clone(), deadline - now);
+                return;
+            }
+            let work = {
+                let mut state = state.borrow_mut();
+                state.scheduled = false;
+                state.work.take()
+            };
+            if let Some(work) = work {
+                work();
+            }
+        }),
+        delay,
+    ));
+}
+
+/// Runs at most one call per interval. The first call runs right away, and the last call of an interval runs at its
+/// end.
+#[derive(Clone)]
+pub struct Throttle {
+    interval: Duration,
+    state: Rc<RefCell<ThrottleState>>,
+}
+
+struct ThrottleState {
+    work: Option<Work>,
+    last_run: Option<Instant>,
+    scheduled: bool,
+}
+
+impl Throttle {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            state: Rc::new(RefCell::new(ThrottleState {
+                work: None,
+                last_run: None,
+                scheduled: false,
+            })),
+        }
+    }
+
+    /// Runs `work` now if nothing ran in the last interval, or else at the end of the interval, unless a newer call
+    /// replaces it.
+    pub fn call(&self, work: impl FnOnce() + 'static) {
+        let now = Instant::now();
+        let mut state = self.state.borrow_mut();
+        let next_run = state.last_run.map(|last_run| last_run + self.interval);
+        match next_run {
+            Some(next_run) if now < next_run => {
+                state.work = Some(Box::new(work));
+                if !state.scheduled {
+                    state.scheduled = true;
+                    schedule_throttle(self.state.clone(), next_run - now);
+                }
+            }
+            _ => {
+                state.last_run = Some(now);
+                drop(state);
+                work();
+            }
+        }
+    }
+
+    /// Drops the work waiting for the end of the interval.
+    pub fn cancel(&self) {
+        self.state.borrow_mut().work = None;
+    }
+}
+
+fn schedule_throttle(state: Rc<RefCell<ThrottleState>>, delay: Duration) {
+    run_later(Job::after(
+        Box::new(move || {
+            let work = {
+                let mut state = state.borrow_mut();
+                state.scheduled = false;
+                let work = state.work.take();
+                if work.is_some() {
+                    state.last_run = Some(Instant::now());
+                }
+                work
+            };
+            if let Some(work) = work {
+                work();
+            }
+        }),
+        delay,
+    ));
+}
+
+/// Wraps a handler that takes event data by reference, like
+/// [`TextInputChangedHandler`](crate::events::TextInputChangedHandler), so it only runs once the events stopped for
+/// `delay`, with the data of the last one.
+pub fn debounced<T>(delay: Duration, handler: impl Fn(&T) + 'static) -> Rc<dyn Fn(&mut Event, &T)>
+where
+    T: ToOwned + ?Sized + 'static,
+{
+    let debounce = Debounce::new(delay);
+    let handler = Rc::new(handler);
+    Rc::new(move |_event: &mut Event, data: &T| {
+        let data = data.to_owned();
+        let handler = handler.clone();
+        debounce.call(move || handler(data.borrow()));
+    })
+}
+
+/// Wraps a handler that takes event data by reference so it runs at most once per `interval`.
+pub fn throttled<T>(interval: Duration, handler: impl Fn(&T) + 'static) -> Rc<dyn Fn(&mut Event, &T)>
+where
+    T: ToOwned + ?Sized + 'static,
+{
+    let throttle = Throttle::new(interval);
+    let handler = Rc::new(handler);
+    Rc::new(move |_event: &mut Event, data: &T| {
+        let data = data.to_owned();
+        let handler = handler.clone();
+        throttle.call(move || handler(data.borrow()));
+    })
+}
+
+/// Like [`debounced`], for handlers that take event data by value, like
+/// [`SliderValueChangedHandler`](crate::events::SliderValueChangedHandler).
+pub fn debounced_value<T>(delay: Duration, handler: impl Fn(T) + 'static) -> Rc<dyn Fn(&mut Event, T)>
+where
+    T: 'static,
+{
+    let debounce = Debounce::new(delay);
+    let handler = Rc::new(handler);
+    Rc::new(move |_event: &mut Event, data: T| {
+        let handler = handler.clone();
+        debounce.call(move || handler(data));
+    })
+}
+
+/// Like [`throttled`], for handlers that take event data by value.
+pub fn throttled_value<T>(interval: Duration, handler: impl Fn(T) + 'static) -> Rc<dyn Fn(&mut Event, T)>
+where
+    T: 'static,
+{
+    let throttle = Throttle::new(interval);
+    let handler = Rc::new(handler);
+    Rc::new(move |_event: &mut Event, data: T| {
+        let handler = handler.clone();
+        throttle.call(move || handler(data));
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use craft_runtime::pop_gui_thread_work;
+
+    use super::*;
+    use crate::elements::Container;
+
+    /// Runs the jobs that are due, and keeps the others queued.
+    fn run_due_jobs() {
+        let mut waiting = Vec::new();
+        while let Some(mut job) = pop_gui_thread_work() {
+            let due = job.interval.is_none_or(|interval| job.last_run.elapsed() >= interval);
+            if due {
+                (job.callback)();
+            } else {
+                waiting.push(job);
+            }
+        }
+        for job in waiting {
+            run_later(job);
+        }
+    }
+
+    #[test]
+    fn debounce_runs_only_the_last_call() {
+        let runs = Rc::new(RefCell::new(Vec::new()));
+        let debounce = Debounce::new(Duration::from_millis(20));
+        for value in 0..3 {
+            let runs = runs.clone();
+            debounce.call(move || runs.borrow_mut().push(value));
+        }
+        run_due_jobs();
+        assert!(runs.borrow().is_empty());
+        assert!(debounce.is_pending());
+
+        thread::sleep(Duration::from_millis(30));
+        run_due_jobs();
+        assert_eq!(*runs.borrow(), vec![2]);
+        assert!(!debounce.is_pending());
+    }
+
+    #[test]
+    fn throttle_runs_the_first_and_last_call_of_an_interval() {
+        let runs = Rc::new(RefCell::new(Vec::new()));
+        let throttle = Throttle::new(Duration::from_millis(20));
+        for value in 0..3 {
+            let runs = runs.clone();
+            throttle.call(move || runs.borrow_mut().push(value));
+        }
+        assert_eq!(*runs.borrow(), vec![0]);
+
+        thread::sleep(Duration::from_millis(30));
+        run_due_jobs();
+        assert_eq!(*runs.borrow(), vec![0, 2]);
+    }
+
+    #[test]
+    fn debounced_handlers_get_the_data_of_the_last_event() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let handler = {
+            let calls = calls.clone();
+            debounced(Duration::from_millis(20), move |text: &str| calls.borrow_mut().push(text.to_string()))
+        };
+
+        let mut event = Event::new(Container::new().inner);
+        handler(&mut event, "c");
+        handler(&mut event, "ca");
+        handler(&mut event, "cat");
+        run_due_jobs();
+        assert!(calls.borrow().is_empty());
+
+        thread::sleep(Duration::from_millis(30));
+        run_due_jobs();
+        assert_eq!(*calls.borrow(), vec!["cat".to_string()]);
+    }
+}
@@ -0,0 +1,113 @@
+//! Saving what elements show as files: CSV for data grids, and SVG or PNG for charts.
+//!
+//! [`DataGrid::to_csv`](crate::elements::DataGrid::to_csv), [`Sparkline::to_svg`](crate::elements::Sparkline::to_svg)
+//! and [`Gauge::to_svg`](crate::elements::Gauge::to_svg) return the text of the file at once. PNG images are drawn by
+//! the renderer after the next frame, so `to_png` on a chart asks for one and it is sent to the chart in a
+//! [`EventKind::ElementCaptured`](crate::events::EventKind::ElementCaptured) event, which [`encode_png`] turns into
+//! the bytes of a file.
+
+use std::fmt::Write;
+
+use craft_primitives::geometry::BezPath;
+#[cfg(feature = "png")]
+use image::{ImageFormat, RgbaImage};
+use peniko::Color;
+
+/// Encodes a captured image as a PNG file.
+#[cfg(feature = "png")]
+pub fn encode_png(image: &RgbaImage) -> Option<Vec<u8>> {
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut bytes, ImageFormat::Png).ok()?;
+    Some(bytes.into_inner())
+}
+
+/// An SVG document built from the same paths an element draws, in logical pixels.
+pub(crate) struct SvgDocument {
+    width: f32,
+    height: f32,
+    body: String,
+}
+
+impl SvgDocument {
+    pub(crate) fn new(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            body: String::new(),
+        }
+    }
+
+    pub(crate) fn fill_path(&mut self, path: &BezPath, color: Color) {
+        let (fill, opacity) = svg_color(color);
+        write!(self.body, r#"<path d="{}" fill="{fill}""#, path.to_svg()).unwrap();
+        if opacity < 1.0 {
+            write!(self.body, r#" fill-opacity="{opacity}""#).unwrap();
+        }
+        self.body.push_str("/>");
+    }
+
+    /// Adds text centered on a point.
+    pub(crate) fn text(&mut self, (x, y): (f64, f64), text: &str, font_size: f32, color: Color) {
+        let (fill, opacity) = svg_color(color);
+        write!(
+            self.body,
+            r#"<text x="{x}" y="{y}" font-family="sans-serif" font-size="{font_size}" fill="{fill}""#
+        )
+        .unwrap();
+        if opacity < 1.0 {
+            write!(self.body, r#" fill-opacity="{opacity}""#).unwrap();
+        }
+        write!(
+            self.body,
+            r#" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+            escape_xml(text)
+        )
+        .unwrap();
+    }
+
+    pub(crate) fn finish(self) -> String {
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">{2}</svg>"#,
+            self.width, self.height, self.body
+        )
+    }
+}
+
+/// A color as `#rrggbb` and an opacity from 0 to 1.
+fn svg_color(color: Color) -> (String, f32) {
+    let [red, green, blue, alpha] = color.to_rgba8().to_u8_array();
+    (format!("#{red:02x}{green:02x}{blue:02x}"), alpha as f32 / 255.0)
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn svg_documents_have_a_path_per_fill() {
+        let mut path = BezPath::new();
+        path.move_to((0.0, 0.0));
+        path.line_to((10.0, 5.0));
+        path.close_path();
+
+        let mut document = SvgDocument::new(20.0, 10.0);
+        document.fill_path(&path, Color::from_rgb8(37, 99, 235));
+        document.fill_path(&path, Color::from_rgba8(0, 0, 0, 51));
+        document.text((10.0, 5.0), "1 < 2", 12.0, Color::BLACK);
+
+        assert_eq!(
+            document.finish(),
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="10" viewBox="0 0 20 10">"#,
+                r##"<path d="M0,0 L10,5 Z" fill="#2563eb"/>"##,
+                r##"<path d="M0,0 L10,5 Z" fill="#000000" fill-opacity="0.2"/>"##,
+                r##"<text x="10" y="5" font-family="sans-serif" font-size="12" fill="#000000" text-anchor="middle" "##,
+                r#"dominant-baseline="central">1 &lt; 2</text></svg>"#,
+            )
+        );
+    }
+}
@@ -0,0 +1,334 @@
+//! Watching files and directories for changes, so editors and viewers can load a file again when another program
+//! changed it.
+//!
+//! ```ignore
+//! let editor = TextInput::new("").on_files_changed(Rc::new(|event, changes| {
+//!     for change in &changes.changes {
+//!         if change.kind == FileChangeKind::Modified {
+//!             let mut target = event.target.borrow_mut();
+//!             let input = target.as_any_mut().downcast_mut::<TextInputInner>().unwrap();
+//!             input.set_text(&std::fs::read_to_string(&change.path).unwrap_or_default());
+//!         }
+//!     }
+//! }));
+//! let watch = editor.watch_files("notes.txt", FileWatchOptions::default())?;
+//! ```
+//!
+//! Programs often write a file in several steps, so the changes are collected until none came for the debounce time
+//! of the watch, and sent to the element together. A watch stops with [`FileWatch::stop`], or when the element it
+//! sends to is dropped.
+//!
+//! With [`hot_reload_resources`], the images and fonts loaded from files are loaded again when the files changed.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Weak;
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+use craft_logging::warn;
+use craft_resource_manager::ResourceId;
+use craft_resource_manager::resource_type::ResourceType;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::app::queue_event;
+use crate::elements::ElementInternals;
+use crate::events::{Event, EventKind, FileChange, FileChangeKind, FilesChangedEvent};
+
+thread_local! {
+    static WATCHES: RefCell<Vec<Watch>> = const { RefCell::new(Vec::new()) };
+    static NEXT_WATCH_ID: Cell<u64> = const { Cell::new(0) };
+    static RESOURCES: RefCell<Option<ResourceWatch>> = const { RefCell::new(None) };
+}
+
+/// How a path is watched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileWatchOptions {
+    /// Whether the files in the subdirectories of a watched directory are watched too.
+    pub recursive: bool,
+    /// How long no change has to come before the changes are sent.
+    pub debounce: Duration,
+}
+
+impl Default for FileWatchOptions {
+    fn default() -> Self {
+        Self {
+            recursive: false,
+            debounce: Duration::from_millis(100),
+        }
+    }
+}
+
+/// A handle to a watch started with [`Element::watch_files`](crate::elements::Element::watch_files).
+///
+/// Dropping the handle doesn't stop the watch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileWatch {
+    id: u64,
+}
+
+impl FileWatch {
+    /// The id in the [`FilesChangedEvent`]s of this watch.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Stops watching. Changes that weren't sent yet are dropped.
+    pub fn stop(&self) {
+        WATCHES.with_borrow_mut(|watches| watches.retain(|watch| watch.id != self.id));
+    }
+}
+
+struct Watch {
+    id: u64,
+    target: Weak<RefCell<dyn ElementInternals>>,
+    /// Stops watching when dropped.
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+    changes: Debouncer,
+}
+
+/// The changes of a watch, collected until no change came for the debounce time.
+pub(crate) struct Debouncer {
+    debounce: Duration,
+    changes: Vec<FileChange>,
+    last_change: Option<Instant>,
+}
+
+impl Debouncer {
+    pub(crate) fn new(debounce: Duration) -> Self {
+        Self {
+            debounce,
+            changes: Vec::new(),
+            last_change: None,
+        }
+    }
+
+    /// Adds a change to the others of the same path. A file that was created and removed again didn't change.
+    pub(crate) fn record(&mut self, path: PathBuf, kind: FileChangeKind, now: Instant) {
+        self.last_change = Some(now);
+        let Some(index) = self.changes.iter().position(|change| change.path == path) else {
+            self.changes.push(FileChange { path, kind });
+            return;
+        };
+        let merged = match (self.changes[index].kind, kind) {
+            (FileChangeKind::Created, FileChangeKind::Removed) => None,
+            (FileChangeKind::Created, _) => Some(FileChangeKind::Created),
+            (FileChangeKind::Removed, FileChangeKind::Created) => Some(FileChangeKind::Modified),
+            (_, kind) => Some(kind),
+        };
+        match merged {
+            Some(kind) => self.changes[index].kind = kind,
+            None => {
+                self.changes.remove(index);
+            }
+        }
+    }
+
+    /// Returns the changes once none came for the debounce time.
+    pub(crate) fn take_due(&mut self, now: Instant) -> Option<Vec<FileChange>> {
+        let last_change = self.last_change?;
+        if now.duration_since(last_change) < self.debounce {
+            return None;
+        }
+        self.last_change = None;
+        let changes = std::mem::take(&mut self.changes);
+        (!changes.is_empty()).then_some(changes)
+    }
+
+    /// Records the changes of an event of the watcher.
+    fn record_event(&mut self, event: notify::Event, now: Instant) {
+        let kind = match event.kind {
+            notify::EventKind::Create(_) => FileChangeKind::Created,
+            notify::EventKind::Remove(_) => FileChangeKind::Removed,
+            notify::EventKind::Modify(ModifyKind::Name(RenameMode::From)) => FileChangeKind::Removed,
+            notify::EventKind::Modify(ModifyKind::Name(RenameMode::To)) => FileChangeKind::Created,
+            // The first path was renamed to the second.
+            notify::EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+                let mut paths = event.paths.into_iter();
+                if let (Some(from), Some(to)) = (paths.next(), paths.next()) {
+                    self.record(from, FileChangeKind::Removed, now);
+                    self.record(to, FileChangeKind::Created, now);
+                }
+                return;
+            }
+            notify::EventKind::Modify(_) | notify::EventKind::Any => FileChangeKind::Modified,
+            notify::EventKind::Access(_) | notify::EventKind::Other => return,
+        };
+        for path in event.paths {
+            self.record(path, kind, now);
+        }
+    }
+
+    /// Records the events the watcher sent since the last call.
+    fn receive(&mut self, events: &Receiver<notify::Result<notify::Event>>, now: Instant) {
+        for result in events.try_iter() {
+            match result {
+                Ok(event) => self.record_event(event, now),
+                Err(error) => warn!("Failed to watch files: {error}"),
+            }
+        }
+    }
+}
+
+/// Watches the files loaded as resources, see [`hot_reload_resources`].
+struct ResourceWatch {
+    watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+    changes: Debouncer,
+    resources: HashMap<PathBuf, ResourceType>,
+}
+
+/// Starts watching `path` for `target`.
+pub(crate) fn watch_for(
+    path: &Path,
+    options: FileWatchOptions,
+    target: Weak<RefCell<dyn ElementInternals>>,
+) -> io::Result<FileWatch> {
+    let (sender, events) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(io::Error::other)?;
+    let mode = if options.recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    watcher.watch(path, mode).map_err(io::Error::other)?;
+
+    let id = NEXT_WATCH_ID.get();
+    NEXT_WATCH_ID.set(id + 1);
+    WATCHES.with_borrow_mut(|watches| {
+        watches.push(Watch {
+            id,
+            target,
+            _watcher: watcher,
+            events,
+            changes: Debouncer::new(options.debounce),
+        })
+    });
+    Ok(FileWatch { id })
+}
+
+/// Queues the changes that are due for their elements. Returns true if any were queued.
+///
+/// Called once per iteration of the event loop.
+pub(crate) fn poll_file_watches() -> bool {
+    let now = Instant::now();
+    WATCHES.with_borrow_mut(|watches| {
+        let mut queued = false;
+        watches.retain_mut(|watch| {
+            let Some(target) = watch.target.upgrade() else {
+                return false;
+            };
+            watch.changes.receive(&watch.events, now);
+            if let Some(changes) = watch.changes.take_due(now) {
+                let event = FilesChangedEvent {
+                    watch: watch.id,
+                    changes,
+                };
+                queue_event(Event::new(target), EventKind::FilesChanged(event));
+                queued = true;
+            }
+            true
+        });
+        queued
+    })
+}
+
+/// Loads the images and fonts loaded from files again when the files changed, e.g. while designing the app.
+pub fn hot_reload_resources(enabled: bool) -> io::Result<()> {
+    if !enabled {
+        RESOURCES.set(None);
+        return Ok(());
+    }
+    if RESOURCES.with_borrow(Option::is_some) {
+        return Ok(());
+    }
+    let (sender, events) = mpsc::channel();
+    let watcher = notify::recommended_watcher(sender).map_err(io::Error::other)?;
+    RESOURCES.set(Some(ResourceWatch {
+        watcher,
+        events,
+        changes: Debouncer::new(FileWatchOptions::default().debounce),
+        resources: HashMap::new(),
+    }));
+    Ok(())
+}
+
+/// Watches the file of a resource that was loaded, if resources are hot reloaded.
+pub(crate) fn watch_resource(resource_id: &ResourceId, resource_type: &ResourceType) {
+    let ResourceId::File(path) = resource_id else {
+        return;
+    };
+    RESOURCES.with_borrow_mut(|resources| {
+        let Some(resources) = resources else {
+            return;
+        };
+        if resources.resources.contains_key(path) {
+            return;
+        }
+        match resources.watcher.watch(path, RecursiveMode::NonRecursive) {
+            Ok(()) => {
+                resources.resources.insert(path.clone(), resource_type.clone());
+            }
+            Err(error) => warn!("Failed to watch {}: {error}", path.display()),
+        }
+    });
+}
+
+/// Returns the resources whose files changed, once the changes are due. Called once per iteration of the event loop.
+pub(crate) fn poll_resource_changes() -> Vec<(ResourceId, ResourceType)> {
+    let now = Instant::now();
+    RESOURCES.with_borrow_mut(|resources| {
+        let Some(resources) = resources else {
+            return Vec::new();
+        };
+        resources.changes.receive(&resources.events, now);
+        let changes = resources.changes.take_due(now).unwrap_or_default();
+        changes
+            .into_iter()
+            // A removed file is kept as it was until it is written again.
+            .filter(|change| change.kind != FileChangeKind::Removed)
+            .filter_map(|change| {
+                let resource_type = resources.resources.get(&change.path)?.clone();
+                Some((ResourceId::File(change.path), resource_type))
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_are_merged_until_they_are_due() {
+        let start = Instant::now();
+        let mut debouncer = Debouncer::new(Duration::from_millis(100));
+        debouncer.record(PathBuf::from("a.txt"), FileChangeKind::Created, start);
+        debouncer.record(PathBuf::from("a.txt"), FileChangeKind::Modified, start);
+        debouncer.record(PathBuf::from("b.txt"), FileChangeKind::Removed, start);
+        debouncer.record(PathBuf::from("b.txt"), FileChangeKind::Created, start);
+        debouncer.record(PathBuf::from("c.txt"), FileChangeKind::Created, start);
+        debouncer.record(PathBuf::from("c.txt"), FileChangeKind::Removed, start);
+
+        assert_eq!(debouncer.take_due(start + Duration::from_millis(50)), None);
+        let changes = debouncer.take_due(start + Duration::from_millis(100)).unwrap();
+        assert_eq!(
+            changes,
+            [
+                FileChange {
+                    path: PathBuf::from("a.txt"),
+                    kind: FileChangeKind::Created
+                },
+                FileChange {
+                    path: PathBuf::from("b.txt"),
+                    kind: FileChangeKind::Modified
+                },
+            ]
+        );
+        assert_eq!(debouncer.take_due(start + Duration::from_millis(200)), None);
+    }
+}
@@ -0,0 +1,85 @@
+//! What the last frame of a window cost, so apps can show their own performance overlay or lower their quality when
+//! frames get slow.
+//!
+//! ```ignore
+//! let stats = window.frame_stats();
+//! if stats.total() > Duration::from_millis(16) {
+//!     card.box_shadows(Vec::new());
+//! }
+//! ```
+
+use std::time::Duration;
+
+use craft_renderer::render_command::RenderCommand;
+
+/// The time each stage of the last frame of a window took, and what it drew. See
+/// [`Window::frame_stats`](crate::elements::Window::frame_stats).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    /// Loading resources and advancing animations before the layout.
+    pub update: Duration,
+    pub layout: Duration,
+    /// Drawing the elements into the render list, and preparing it for the renderer.
+    pub encode: Duration,
+    /// Submitting the frame to the renderer. GPU renderers may wait here for the GPU to finish an earlier frame.
+    pub gpu: Duration,
+    /// The shapes, text and images drawn.
+    pub draw_commands: usize,
+    /// The layers pushed for clips, effects and color filters.
+    pub layers: usize,
+    /// The bytes of the images uploaded to the renderer, or 0 if it doesn't keep images uploaded.
+    pub texture_bytes: usize,
+    /// Whether the frame before was presented again, as nothing it draws had changed. See [`crate::scene`].
+    pub reused: bool,
+    /// The time from the start of the app until the first frame of the window was drawn, or `None` before it was.
+    pub time_to_first_frame: Option<Duration>,
+}
+
+impl FrameStats {
+    /// The time the whole frame took.
+    pub fn total(&self) -> Duration {
+        self.update + self.layout + self.encode + self.gpu
+    }
+
+    /// Counts the draw commands and layers of a render list.
+    pub(crate) fn count_commands(&mut self, commands: &[RenderCommand]) {
+        self.draw_commands = 0;
+        self.layers = 0;
+        for command in commands {
+            match command {
+                RenderCommand::PushLayer(_) | RenderCommand::PushEffectLayer(_) => self.layers += 1,
+                RenderCommand::PopLayer | RenderCommand::StartOverlay | RenderCommand::EndOverlay => {}
+                _ => self.draw_commands += 1,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use craft_primitives::geometry::{Affine, Rectangle};
+    use craft_renderer::render_command::{DrawRectCmd, PushLayerCmd};
+    use peniko::Color;
+
+    use super::*;
+
+    #[test]
+    fn layers_are_counted_apart_from_draw_commands() {
+        let rect = Rectangle::new(0.0, 0.0, 10.0, 10.0);
+        let draw_rect = RenderCommand::DrawRect(DrawRectCmd {
+            rect,
+            color: Color::BLACK,
+            transform: Affine::IDENTITY,
+        });
+        let mut stats = FrameStats::default();
+        stats.count_commands(&[
+            RenderCommand::PushLayer(PushLayerCmd::Rect(rect, Affine::IDENTITY)),
+            draw_rect.clone(),
+            RenderCommand::PopLayer,
+            RenderCommand::StartOverlay,
+            draw_rect,
+            RenderCommand::EndOverlay,
+        ]);
+        assert_eq!((stats.draw_commands, stats.layers), (2, 1));
+    }
+}
@@ -0,0 +1,306 @@
+//! HTTP requests whose response is sent to an element piece by piece as it arrives, like the tokens of a language
+//! model or the server-sent events of a live feed.
+//!
+//! ```ignore
+//! let answer = Text::new("").on_http_stream(Rc::new(|event, stream| {
+//!     if let HttpStreamEventKind::ServerSentEvent(token) = &stream.kind {
+//!         let mut target = event.target.borrow_mut();
+//!         let text = target.as_any_mut().downcast_mut::<TextInner>().unwrap();
+//!         text.set_text(&format!("{}{}", text.get_text(), token.data));
+//!     }
+//! }));
+//! answer.stream_http(HttpStreamRequest::post("https://example.com/complete", prompt));
+//! ```
+//!
+//! A response with the `text/event-stream` content type is parsed into [`ServerSentEvent`]s, any other response is
+//! sent as the chunks of bytes that arrive. Every request runs on its own thread. It is cancelled with
+//! [`HttpStream::cancel`], or when the element it sends to is dropped.
+
+use std::cell::{Cell, RefCell};
+use std::io::Read;
+use std::rc::Weak;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+
+use craft_logging::warn;
+
+use crate::app::queue_event;
+use crate::elements::ElementInternals;
+use crate::events::{Event, EventKind, HttpStreamEvent, HttpStreamEventKind, ServerSentEvent};
+
+/// The most bytes read at once.
+const CHUNK_SIZE: usize = 16 * 1024;
+
+thread_local! {
+    static STREAMS: RefCell<Vec<Stream>> = const { RefCell::new(Vec::new()) };
+    static NEXT_STREAM_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// The request to stream the response of.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HttpStreamRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+}
+
+impl HttpStreamRequest {
+    pub fn get(url: impl Into<String>) -> Self {
+        Self {
+            method: "GET".to_string(),
+            url: url.into(),
+            headers: Vec::new(),
+            body: None,
+        }
+    }
+
+    pub fn post(url: impl Into<String>, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            method: "POST".to_string(),
+            url: url.into(),
+            headers: Vec::new(),
+            body: Some(body.into()),
+        }
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// A handle to a request started with [`Element::stream_http`](crate::elements::Element::stream_http).
+#[derive(Clone)]
+pub struct HttpStream {
+    id: u64,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl HttpStream {
+    /// The id in the [`HttpStreamEvent`]s of this request.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Stops reading the response. No more events are sent for it.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+struct Stream {
+    id: u64,
+    target: Weak<RefCell<dyn ElementInternals>>,
+    events: Receiver<HttpStreamEventKind>,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Parses server-sent events from the bytes of a `text/event-stream` response, which may split lines anywhere.
+#[derive(Default)]
+pub(crate) struct ServerSentEventParser {
+    /// The bytes of the line that hasn't ended yet.
+    line: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+    id: Option<String>,
+}
+
+impl ServerSentEventParser {
+    /// Returns the events completed by `bytes`.
+    pub(crate) fn feed(&mut self, bytes: &[u8]) -> Vec<ServerSentEvent> {
+        let mut events = Vec::new();
+        for &byte in bytes {
+            if byte != b'\n' {
+                self.line.push(byte);
+                continue;
+            }
+            let line = std::mem::take(&mut self.line);
+            let line = String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(&line));
+            if let Some(event) = self.parse_line(&line) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// Handles one line. An empty line ends the event.
+    fn parse_line(&mut self, line: &str) -> Option<ServerSentEvent> {
+        if line.is_empty() {
+            let event = self.event.take();
+            if self.data.is_empty() {
+                return None;
+            }
+            return Some(ServerSentEvent {
+                event: event.unwrap_or_else(|| "message".to_string()),
+                data: std::mem::take(&mut self.data).join("\n"),
+                id: self.id.clone(),
+            });
+        }
+
+        // Lines starting with a colon are comments, often sent to keep the connection alive.
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            "id" => self.id = Some(value.to_string()),
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Sends the request, and the response to `target` as it arrives.
+pub(crate) fn stream_for(request: HttpStreamRequest, target: Weak<RefCell<dyn ElementInternals>>) -> HttpStream {
+    let id = NEXT_STREAM_ID.get();
+    NEXT_STREAM_ID.set(id + 1);
+
+    let (event_sender, events) = mpsc::channel();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let thread_cancelled = cancelled.clone();
+    let spawned = std::thread::Builder::new()
+        .name("craft-http-stream".to_string())
+        .spawn(move || {
+            let result = run_stream(request, &event_sender, &thread_cancelled);
+            if !thread_cancelled.load(Ordering::Relaxed) {
+                let kind = match result {
+                    Ok(()) => HttpStreamEventKind::Finished,
+                    Err(error) => HttpStreamEventKind::Failed(error),
+                };
+                event_sender.send(kind).ok();
+            }
+        });
+    if let Err(error) = spawned {
+        warn!("Failed to start the HTTP stream thread: {error}");
+    }
+
+    STREAMS.with_borrow_mut(|streams| {
+        streams.push(Stream {
+            id,
+            target,
+            events,
+            cancelled: cancelled.clone(),
+        })
+    });
+    HttpStream { id, cancelled }
+}
+
+/// Queues the events of the streams for their elements. Returns true if any were queued.
+///
+/// Called once per iteration of the event loop.
+pub(crate) fn poll_http_streams() -> bool {
+    STREAMS.with_borrow_mut(|streams| {
+        let mut queued = false;
+        streams.retain(|stream| {
+            let target = stream.target.upgrade().filter(|_| !stream.cancelled.load(Ordering::Relaxed));
+            let Some(target) = target else {
+                stream.cancelled.store(true, Ordering::Relaxed);
+                return false;
+            };
+            loop {
+                match stream.events.try_recv() {
+                    Ok(kind) => {
+                        let event = HttpStreamEvent { stream: stream.id, kind };
+                        queue_event(Event::new(target.clone()), EventKind::HttpStream(event));
+                        queued = true;
+                    }
+                    Err(TryRecvError::Empty) => return true,
+                    // The response ended.
+                    Err(TryRecvError::Disconnected) => return false,
+                }
+            }
+        });
+        queued
+    })
+}
+
+/// Sends the request and reads the response until it ends or the stream is cancelled.
+fn run_stream(
+    request: HttpStreamRequest,
+    events: &Sender<HttpStreamEventKind>,
+    cancelled: &AtomicBool,
+) -> Result<(), String> {
+    let method = reqwest::Method::from_bytes(request.method.as_bytes()).map_err(|error| error.to_string())?;
+    let mut builder = reqwest::blocking::Client::new().request(method, request.url.as_str());
+    for (name, value) in request.headers {
+        builder = builder.header(name, value);
+    }
+    if let Some(body) = request.body {
+        builder = builder.body(body);
+    }
+    let mut response = builder.send().map_err(|error| error.to_string())?;
+
+    let status = response.status();
+    if events.send(HttpStreamEventKind::Started { status: status.as_u16() }).is_err() {
+        return Ok(());
+    }
+    if !status.is_success() {
+        return Err(format!("The server responded with {status}"));
+    }
+
+    let event_stream = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("text/event-stream"));
+    let mut parser = event_stream.then(ServerSentEventParser::default);
+
+    let mut buffer = vec![0; CHUNK_SIZE];
+    loop {
+        let read = response.read(&mut buffer).map_err(|error| error.to_string())?;
+        if read == 0 || cancelled.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        let chunk = &buffer[..read];
+        let sent = match parser.as_mut() {
+            Some(parser) => parser
+                .feed(chunk)
+                .into_iter()
+                .all(|event| events.send(HttpStreamEventKind::ServerSentEvent(event)).is_ok()),
+            None => events.send(HttpStreamEventKind::Chunk(chunk.to_vec())).is_ok(),
+        };
+        // The element is gone when the events can't be sent.
+        if !sent {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_sent_events_are_parsed_across_chunks() {
+        let mut parser = ServerSentEventParser::default();
+        assert!(parser.feed(b": keep alive\r\ndata: Hel").is_empty());
+        let events = parser.feed(b"lo\r\ndata: world\r\n\r\nevent: done\nid: 7\ndata:\n\n");
+        assert_eq!(
+            events,
+            vec![
+                ServerSentEvent {
+                    event: "message".to_string(),
+                    data: "Hello\nworld".to_string(),
+                    id: None,
+                },
+                ServerSentEvent {
+                    event: "done".to_string(),
+                    data: String::new(),
+                    id: Some("7".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn lines_without_data_dispatch_nothing() {
+        let mut parser = ServerSentEventParser::default();
+        assert!(parser.feed(b"event: ping\n\nid: 1\n\n").is_empty());
+        assert_eq!(parser.feed(b"data: a\n\n")[0].event, "message");
+    }
+}
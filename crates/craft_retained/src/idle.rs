@@ -0,0 +1,124 @@
+//! Tells when the user stopped using the app, so it can dim the screen, lock the session or pause expensive work.
+//!
+//! Every window is sent a [`EventKind::UserIdle`] event each time the time since the last input passes one of the
+//! idle thresholds, see [`CraftOptions::idle_thresholds`](crate::CraftOptions::idle_thresholds), and a
+//! [`EventKind::UserActive`] event on the first input after that.
+//!
+//! [`EventKind::UserIdle`]: crate::events::EventKind::UserIdle
+//! [`EventKind::UserActive`]: crate::events::EventKind::UserActive
+
+use std::cell::RefCell;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+
+#[cfg(target_arch = "wasm32")]
+use web_time::{Duration, Instant};
+
+thread_local! {
+    static IDLE_TRACKER: RefCell<IdleTracker> = RefCell::new(IdleTracker::new(Vec::new(), Instant::now()));
+}
+
+/// Tracks how long ago the last input was, and which idle thresholds were passed since.
+pub(crate) struct IdleTracker {
+    /// Sorted from shortest to longest.
+    thresholds: Vec<Duration>,
+    last_activity: Instant,
+    /// How many of the thresholds were passed and reported.
+    reported: usize,
+}
+
+impl IdleTracker {
+    pub(crate) fn new(mut thresholds: Vec<Duration>, now: Instant) -> Self {
+        thresholds.sort();
+        thresholds.dedup();
+        Self {
+            thresholds,
+            last_activity: now,
+            reported: 0,
+        }
+    }
+
+    /// Records input from the user. Returns true if the user was idle.
+    pub(crate) fn activity(&mut self, now: Instant) -> bool {
+        let was_idle = self.reported > 0;
+        self.last_activity = now;
+        self.reported = 0;
+        was_idle
+    }
+
+    /// Returns the thresholds passed since the last call, from shortest to longest.
+    pub(crate) fn poll(&mut self, now: Instant) -> Vec<Duration> {
+        let idle_time = now.saturating_duration_since(self.last_activity);
+        let passed: Vec<Duration> = self.thresholds[self.reported..]
+            .iter()
+            .take_while(|threshold| idle_time >= **threshold)
+            .copied()
+            .collect();
+        self.reported += passed.len();
+        passed
+    }
+}
+
+/// How long ago the user last used the app, e.g. pressed a key or moved the pointer over a window.
+pub fn user_idle_time() -> Duration {
+    IDLE_TRACKER.with_borrow(|tracker| Instant::now().saturating_duration_since(tracker.last_activity))
+}
+
+/// Changes [`CraftOptions::idle_thresholds`](crate::CraftOptions::idle_thresholds) while the app runs. The thresholds
+/// the current idle time already passed are reported again.
+pub fn set_idle_thresholds(thresholds: Vec<Duration>) {
+    IDLE_TRACKER.with_borrow_mut(|tracker| {
+        let last_activity = tracker.last_activity;
+        *tracker = IdleTracker::new(thresholds, last_activity);
+    });
+}
+
+/// Records input from the user. Returns true if the user was idle, so the windows should be sent a
+/// [`EventKind::UserActive`](crate::events::EventKind::UserActive) event.
+pub(crate) fn note_user_activity() -> bool {
+    IDLE_TRACKER.with_borrow_mut(|tracker| tracker.activity(Instant::now()))
+}
+
+/// Returns the idle thresholds passed since the last call. Called once per iteration of the event loop.
+pub(crate) fn poll_idle() -> Vec<Duration> {
+    IDLE_TRACKER.with_borrow_mut(|tracker| tracker.poll(Instant::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seconds(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn each_threshold_is_reported_once() {
+        let start = Instant::now();
+        let mut tracker = IdleTracker::new(vec![seconds(300), seconds(60)], start);
+
+        assert!(tracker.poll(start + seconds(30)).is_empty());
+        assert_eq!(tracker.poll(start + seconds(61)), vec![seconds(60)]);
+        assert!(tracker.poll(start + seconds(120)).is_empty());
+        assert_eq!(tracker.poll(start + seconds(400)), vec![seconds(300)]);
+    }
+
+    #[test]
+    fn thresholds_passed_together_are_all_reported() {
+        let start = Instant::now();
+        let mut tracker = IdleTracker::new(vec![seconds(60), seconds(300)], start);
+        assert_eq!(tracker.poll(start + seconds(500)), vec![seconds(60), seconds(300)]);
+    }
+
+    #[test]
+    fn activity_restarts_the_idle_time() {
+        let start = Instant::now();
+        let mut tracker = IdleTracker::new(vec![seconds(60)], start);
+
+        assert!(!tracker.activity(start + seconds(10)));
+        assert!(tracker.poll(start + seconds(61)).is_empty());
+        assert_eq!(tracker.poll(start + seconds(71)), vec![seconds(60)]);
+        assert!(tracker.activity(start + seconds(80)));
+        assert!(!tracker.activity(start + seconds(81)));
+    }
+}
@@ -0,0 +1,290 @@
+//! Secrets like passwords and tokens, kept in the credential store of the platform instead of a config file: the
+//! Keychain on macOS, the Credential Manager on Windows and the Secret Service on Linux.
+//!
+//! ```ignore
+//! let login = Container::new().on_keyring(Rc::new(|_event, keyring| {
+//!     if let KeyringEventKind::Secret(Some(token)) = &keyring.kind {
+//!         sign_in(&keyring.account, token);
+//!     }
+//! }));
+//! login.keyring("com.example.mail").get("ada@example.com");
+//! ```
+//!
+//! The platform store can block while it asks the user to unlock it, so requests run in order on a thread, and the
+//! result of every request is sent to the element in a [`EventKind::Keyring`] event.
+//!
+//! Without the `keyring` feature, on the web, or when the platform store can't be reached, secrets are kept in memory
+//! until the app exits.
+//!
+//! [`EventKind::Keyring`]: crate::events::EventKind::Keyring
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Weak;
+
+use crate::app::queue_event;
+use crate::elements::ElementInternals;
+use crate::events::{Event, EventKind, KeyringEvent, KeyringEventKind};
+
+thread_local! {
+    static PENDING: RefCell<Vec<PendingRequest>> = const { RefCell::new(Vec::new()) };
+    static NEXT_REQUEST_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+#[cfg(all(feature = "keyring", not(target_arch = "wasm32")))]
+thread_local! {
+    static WORKER: RefCell<Option<platform::Worker>> = const { RefCell::new(None) };
+}
+
+#[cfg(not(all(feature = "keyring", not(target_arch = "wasm32"))))]
+thread_local! {
+    static MEMORY: RefCell<MemoryStore> = RefCell::new(MemoryStore::default());
+}
+
+/// The secrets of a service, for the element that asked for them. Made with
+/// [`Element::keyring`](crate::elements::Element::keyring).
+#[derive(Clone)]
+pub struct Keyring {
+    service: String,
+    target: Weak<RefCell<dyn ElementInternals>>,
+}
+
+impl Keyring {
+    pub(crate) fn new(service: &str, target: Weak<RefCell<dyn ElementInternals>>) -> Self {
+        Self {
+            service: service.to_string(),
+            target,
+        }
+    }
+
+    /// Reads the secret of `account`. The result is a [`KeyringEventKind::Secret`], which is `None` if no secret is
+    /// stored. Returns the id of the request.
+    pub fn get(&self, account: &str) -> u64 {
+        self.request(account, Operation::Get)
+    }
+
+    /// Stores the secret of `account`, replacing the one stored before. Returns the id of the request.
+    pub fn set(&self, account: &str, secret: &str) -> u64 {
+        self.request(account, Operation::Set(secret.to_string()))
+    }
+
+    /// Removes the secret of `account`. Removing a secret that isn't stored succeeds. Returns the id of the request.
+    pub fn delete(&self, account: &str) -> u64 {
+        self.request(account, Operation::Delete)
+    }
+
+    fn request(&self, account: &str, operation: Operation) -> u64 {
+        let id = NEXT_REQUEST_ID.get();
+        NEXT_REQUEST_ID.set(id + 1);
+        let request = Request {
+            id,
+            service: self.service.clone(),
+            account: account.to_string(),
+            operation,
+        };
+        PENDING.with_borrow_mut(|pending| {
+            pending.push(PendingRequest {
+                id,
+                account: request.account.clone(),
+                target: self.target.clone(),
+            })
+        });
+        send(request);
+        id
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Operation {
+    Get,
+    Set(String),
+    Delete,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Request {
+    id: u64,
+    service: String,
+    account: String,
+    operation: Operation,
+}
+
+struct PendingRequest {
+    id: u64,
+    account: String,
+    target: Weak<RefCell<dyn ElementInternals>>,
+}
+
+/// Secrets kept until the app exits, when there is no platform store.
+#[derive(Default)]
+struct MemoryStore {
+    secrets: HashMap<(String, String), String>,
+}
+
+impl MemoryStore {
+    fn handle(&mut self, request: Request) -> KeyringEventKind {
+        let key = (request.service, request.account);
+        match request.operation {
+            Operation::Get => KeyringEventKind::Secret(self.secrets.get(&key).cloned()),
+            Operation::Set(secret) => {
+                self.secrets.insert(key, secret);
+                KeyringEventKind::Stored
+            }
+            Operation::Delete => {
+                self.secrets.remove(&key);
+                KeyringEventKind::Deleted
+            }
+        }
+    }
+}
+
+/// Runs requests on a thread with the platform store, falling back to memory when the store can't be reached.
+#[cfg(all(feature = "keyring", not(target_arch = "wasm32")))]
+mod platform {
+    use std::sync::mpsc::{self, Receiver, Sender};
+
+    use craft_logging::warn;
+
+    use super::{KeyringEventKind, MemoryStore, Operation, Request};
+
+    pub(super) struct Worker {
+        pub(super) requests: Sender<Request>,
+        pub(super) results: Receiver<(u64, KeyringEventKind)>,
+    }
+
+    impl Worker {
+        pub(super) fn start() -> Self {
+            let (requests, request_receiver) = mpsc::channel();
+            let (result_sender, results) = mpsc::channel();
+            let spawned = std::thread::Builder::new()
+                .name("craft-keyring".to_string())
+                .spawn(move || run(&request_receiver, &result_sender));
+            if let Err(error) = spawned {
+                warn!("Failed to start the keyring thread: {error}");
+            }
+            Self { requests, results }
+        }
+    }
+
+    fn run(requests: &Receiver<Request>, results: &Sender<(u64, KeyringEventKind)>) {
+        let mut memory: Option<MemoryStore> = None;
+        for request in requests {
+            let id = request.id;
+            let kind = match &mut memory {
+                Some(memory) => memory.handle(request),
+                None => match handle(&request) {
+                    Err(::keyring::Error::NoStorageAccess(error) | ::keyring::Error::PlatformFailure(error)) => {
+                        warn!("The credential store can't be reached, secrets are kept in memory: {error}");
+                        memory.insert(MemoryStore::default()).handle(request)
+                    }
+                    Err(error) => KeyringEventKind::Failed(error.to_string()),
+                    Ok(kind) => kind,
+                },
+            };
+            if results.send((id, kind)).is_err() {
+                return;
+            }
+        }
+    }
+
+    fn handle(request: &Request) -> ::keyring::Result<KeyringEventKind> {
+        let entry = ::keyring::Entry::new(&request.service, &request.account)?;
+        match &request.operation {
+            Operation::Get => match entry.get_password() {
+                Ok(secret) => Ok(KeyringEventKind::Secret(Some(secret))),
+                Err(::keyring::Error::NoEntry) => Ok(KeyringEventKind::Secret(None)),
+                Err(error) => Err(error),
+            },
+            Operation::Set(secret) => entry.set_password(secret).map(|_| KeyringEventKind::Stored),
+            Operation::Delete => match entry.delete_credential() {
+                Ok(()) | Err(::keyring::Error::NoEntry) => Ok(KeyringEventKind::Deleted),
+                Err(error) => Err(error),
+            },
+        }
+    }
+}
+
+/// Runs a request on the keyring thread, which is started the first time it is needed.
+#[cfg(all(feature = "keyring", not(target_arch = "wasm32")))]
+fn send(request: Request) {
+    WORKER.with_borrow_mut(|worker| {
+        let worker = worker.get_or_insert_with(platform::Worker::start);
+        if let Err(error) = worker.requests.send(request) {
+            deliver(error.0.id, KeyringEventKind::Failed("The keyring thread stopped".to_string()));
+        }
+    });
+}
+
+/// Runs a request on the memory store. The result is still sent as an event, like the results of a platform store.
+#[cfg(not(all(feature = "keyring", not(target_arch = "wasm32"))))]
+fn send(request: Request) {
+    let id = request.id;
+    let kind = MEMORY.with_borrow_mut(|memory| memory.handle(request));
+    deliver(id, kind);
+}
+
+/// Queues the result of a request for the element that sent it.
+fn deliver(id: u64, kind: KeyringEventKind) -> bool {
+    let Some(request) = PENDING.with_borrow_mut(|pending| {
+        let index = pending.iter().position(|request| request.id == id)?;
+        Some(pending.remove(index))
+    }) else {
+        return false;
+    };
+    let Some(target) = request.target.upgrade() else {
+        return false;
+    };
+    let event = KeyringEvent {
+        request: id,
+        account: request.account,
+        kind,
+    };
+    queue_event(Event::new(target), EventKind::Keyring(event));
+    true
+}
+
+/// Queues the results of the requests that finished. Returns true if any were queued.
+///
+/// Called once per iteration of the event loop.
+#[cfg(all(feature = "keyring", not(target_arch = "wasm32")))]
+pub(crate) fn poll_keyring() -> bool {
+    let results: Vec<(u64, KeyringEventKind)> = WORKER.with_borrow(|worker| {
+        worker
+            .as_ref()
+            .map(|worker| worker.results.try_iter().collect())
+            .unwrap_or_default()
+    });
+    let mut queued = false;
+    for (id, kind) in results {
+        queued |= deliver(id, kind);
+    }
+    queued
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(account: &str, operation: Operation) -> Request {
+        Request {
+            id: 0,
+            service: "com.example.mail".to_string(),
+            account: account.to_string(),
+            operation,
+        }
+    }
+
+    #[test]
+    fn memory_store_keeps_secrets_per_account() {
+        let mut memory = MemoryStore::default();
+        let set = request("ada", Operation::Set("hunter2".to_string()));
+        assert_eq!(memory.handle(set), KeyringEventKind::Stored);
+        assert_eq!(
+            memory.handle(request("ada", Operation::Get)),
+            KeyringEventKind::Secret(Some("hunter2".to_string()))
+        );
+        assert_eq!(memory.handle(request("grace", Operation::Get)), KeyringEventKind::Secret(None));
+        assert_eq!(memory.handle(request("ada", Operation::Delete)), KeyringEventKind::Deleted);
+        assert_eq!(memory.handle(request("ada", Operation::Get)), KeyringEventKind::Secret(None));
+    }
+}
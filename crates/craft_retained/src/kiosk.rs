@@ -0,0 +1,205 @@
+//! Options for apps deployed on kiosks, information screens and other unattended devices, set with
+//! [`CraftOptions::kiosk`](crate::CraftOptions::kiosk).
+//!
+//! In kiosk mode the windows cover the screen, the pointer is hidden while nobody uses it, the shortcuts that would
+//! leave the app are ignored, every window is sent a [`EventKind::KioskReset`] event after the kiosk was left alone
+//! for a while, and a frame that panics recreates the renderer instead of taking the app down.
+//!
+//! ```ignore
+//! let options = CraftOptions {
+//!     kiosk: Some(KioskOptions::default()),
+//!     ..CraftOptions::basic("Museum guide")
+//! };
+//! Window::new("Museum guide").on_kiosk_reset(Rc::new(|_event| show_start_screen()));
+//! craft_main(options);
+//! ```
+//!
+//! [`EventKind::KioskReset`]: crate::events::EventKind::KioskReset
+
+use std::cell::RefCell;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+
+#[cfg(target_arch = "wasm32")]
+use web_time::{Duration, Instant};
+use winit::window::{Fullscreen, Window as WinitWindow, WindowAttributes, WindowLevel};
+
+use crate::idle::IdleTracker;
+
+/// How many frames in a row can fail before the watchdog gives up and lets the app crash.
+const MAX_RENDER_RESTARTS: u32 = 3;
+
+/// How a kiosk behaves. See the [module docs](self).
+#[derive(Clone, Debug, PartialEq)]
+pub struct KioskOptions {
+    /// Covers the screen with the windows, and puts them back in fullscreen when the platform takes them out of it.
+    pub fullscreen: bool,
+    /// Hides the pointer over the windows after this long without input, until the next input.
+    pub hide_cursor_after: Option<Duration>,
+    /// Ignores the shortcuts that would leave or change the app where the platform lets it: Escape quitting,
+    /// closing the windows from the keyboard or the title bar, zooming, and other windows covering the app. Shortcuts
+    /// the operating system handles itself, like switching users, still work.
+    pub block_shortcuts: bool,
+    /// Sends every window a [`EventKind::KioskReset`](crate::events::EventKind::KioskReset) event after this long
+    /// without input, for the app to go back to its start screen.
+    pub reset_after: Option<Duration>,
+    /// Recreates the renderer of a window and draws it again when drawing a frame panics, like when the GPU driver
+    /// was reset. The app still crashes if several frames in a row fail.
+    pub restart_on_render_failure: bool,
+}
+
+impl Default for KioskOptions {
+    fn default() -> Self {
+        Self {
+            fullscreen: true,
+            hide_cursor_after: Some(Duration::from_secs(5)),
+            block_shortcuts: true,
+            reset_after: Some(Duration::from_secs(120)),
+            restart_on_render_failure: true,
+        }
+    }
+}
+
+struct KioskState {
+    options: KioskOptions,
+    /// Reports when the cursor should be hidden and the kiosk reset.
+    idle: IdleTracker,
+    cursor_hidden: bool,
+    render_failures: u32,
+}
+
+impl KioskState {
+    fn new(options: KioskOptions, now: Instant) -> Self {
+        let thresholds = [options.hide_cursor_after, options.reset_after].into_iter().flatten().collect();
+        Self {
+            options,
+            idle: IdleTracker::new(thresholds, now),
+            cursor_hidden: false,
+            render_failures: 0,
+        }
+    }
+
+    /// Returns true if the cursor was hidden.
+    fn activity(&mut self, now: Instant) -> bool {
+        self.idle.activity(now);
+        std::mem::replace(&mut self.cursor_hidden, false)
+    }
+
+    fn poll(&mut self, now: Instant) -> KioskIdle {
+        let passed = self.idle.poll(now);
+        let idle = KioskIdle {
+            hide_cursor: self.options.hide_cursor_after.is_some_and(|after| passed.contains(&after)),
+            reset: self.options.reset_after.is_some_and(|after| passed.contains(&after)),
+        };
+        self.cursor_hidden |= idle.hide_cursor;
+        idle
+    }
+}
+
+thread_local! {
+    static KIOSK: RefCell<Option<KioskState>> = const { RefCell::new(None) };
+}
+
+/// What a kiosk left alone does now, from [`poll_kiosk`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct KioskIdle {
+    pub(crate) hide_cursor: bool,
+    pub(crate) reset: bool,
+}
+
+/// The kiosk options the app runs with, or `None` when it isn't a kiosk.
+pub fn kiosk_options() -> Option<KioskOptions> {
+    KIOSK.with_borrow(|kiosk| kiosk.as_ref().map(|kiosk| kiosk.options.clone()))
+}
+
+pub(crate) fn set_kiosk(options: Option<KioskOptions>) {
+    KIOSK.set(options.map(|options| KioskState::new(options, Instant::now())));
+}
+
+fn is_enabled(option: impl Fn(&KioskOptions) -> bool) -> bool {
+    KIOSK.with_borrow(|kiosk| kiosk.as_ref().is_some_and(|kiosk| option(&kiosk.options)))
+}
+
+/// Whether the shortcuts that would leave or change the app are ignored.
+pub(crate) fn blocks_shortcuts() -> bool {
+    is_enabled(|options| options.block_shortcuts)
+}
+
+/// Whether a frame that panics recreates the renderer.
+pub(crate) fn restarts_on_render_failure() -> bool {
+    is_enabled(|options| options.restart_on_render_failure)
+}
+
+/// Makes new windows cover the screen and stay above the others, as the options ask.
+pub(crate) fn window_attributes(window_attributes: WindowAttributes) -> WindowAttributes {
+    let mut window_attributes = window_attributes;
+    if is_enabled(|options| options.fullscreen) {
+        window_attributes = window_attributes.with_fullscreen(Some(Fullscreen::Borderless(None)));
+    }
+    if blocks_shortcuts() {
+        window_attributes = window_attributes.with_window_level(WindowLevel::AlwaysOnTop);
+    }
+    window_attributes
+}
+
+/// Puts a window back in fullscreen if the platform took it out of it.
+pub(crate) fn keep_fullscreen(window: &WinitWindow) {
+    if is_enabled(|options| options.fullscreen) && window.fullscreen().is_none() {
+        window.set_fullscreen(Some(Fullscreen::Borderless(None)));
+    }
+}
+
+/// Records input from the user. Returns true if the cursor was hidden, so it should be shown again.
+pub(crate) fn note_kiosk_activity() -> bool {
+    KIOSK.with_borrow_mut(|kiosk| kiosk.as_mut().is_some_and(|kiosk| kiosk.activity(Instant::now())))
+}
+
+/// Returns what the kiosk should do since it was last polled. Called once per iteration of the event loop.
+pub(crate) fn poll_kiosk() -> KioskIdle {
+    KIOSK.with_borrow_mut(|kiosk| kiosk.as_mut().map(|kiosk| kiosk.poll(Instant::now())).unwrap_or_default())
+}
+
+/// Records a frame that panicked. Returns false if too many frames in a row failed to restart the renderer again.
+pub(crate) fn note_render_failure() -> bool {
+    KIOSK.with_borrow_mut(|kiosk| {
+        let Some(kiosk) = kiosk else {
+            return false;
+        };
+        kiosk.render_failures += 1;
+        kiosk.render_failures <= MAX_RENDER_RESTARTS
+    })
+}
+
+/// Records a frame that was drawn, so earlier failures don't count towards the next ones.
+pub(crate) fn note_render_success() {
+    KIOSK.with_borrow_mut(|kiosk| {
+        if let Some(kiosk) = kiosk {
+            kiosk.render_failures = 0;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seconds(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn the_cursor_hides_before_the_kiosk_resets_and_shows_on_input() {
+        let start = Instant::now();
+        let mut kiosk = KioskState::new(KioskOptions::default(), start);
+
+        assert_eq!(kiosk.poll(start + seconds(1)), KioskIdle::default());
+        let hidden = kiosk.poll(start + seconds(6));
+        assert!(hidden.hide_cursor && !hidden.reset);
+        let reset = kiosk.poll(start + seconds(121));
+        assert!(!reset.hide_cursor && reset.reset);
+
+        assert!(kiosk.activity(start + seconds(130)));
+        assert!(!kiosk.activity(start + seconds(131)));
+        assert_eq!(kiosk.poll(start + seconds(132)), KioskIdle::default());
+    }
+}
@@ -0,0 +1,593 @@
+//! Lets an element place its children itself, like on a circle or in columns of different heights, instead of with
+//! flexbox or block layout.
+//!
+//! Before each layout pass, every child of an element with a [`CustomLayout`] is measured on its own at its max
+//! content size within the element, then the layout places the children and sizes the element's content. The children
+//! are absolutely positioned there for the pass, so the element still takes part in the layout of the elements around
+//! it. Elements with a custom layout inside others are laid out first, and the pass runs again when an element's width
+//! or padding turned out different from the ones its layout was given.
+
+use std::cell::RefCell;
+use std::f32::consts::TAU;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use craft_primitives::geometry::{Point, Size};
+use craft_resource_manager::ResourceManager;
+use taffy::{AvailableSpace, NodeId};
+
+use crate::elements::ElementInternals;
+use crate::layout::TaffyTree;
+use crate::style::{BoxSizing, Display};
+use crate::text::text_context::TextContext;
+
+/// What a [`CustomLayout`] places, in logical pixels.
+pub struct CustomLayoutInput<'a> {
+    /// The width of the content box of the element.
+    pub available_width: f32,
+    /// The scale factor of the window, to put children on whole physical pixels.
+    pub scale_factor: f64,
+    /// The size of each child that is displayed, in order.
+    pub child_sizes: &'a [Size<f32>],
+    /// The baseline of the first line of text in each child from its top, or `None` for children without text.
+    pub child_baselines: &'a [Option<f32>],
+}
+
+/// Where a [`CustomLayout`] placed the children, in logical pixels.
+#[derive(Clone, Debug, Default)]
+pub struct CustomLayoutOutput {
+    /// The top left corner of each child, relative to the content box of the element.
+    pub child_positions: Vec<Point>,
+    /// The size of the content of the element. The element is at least as large.
+    pub content_size: Size<f32>,
+}
+
+/// Places the children of an element. See the [module docs](self) and
+/// [`Element::custom_layout`](crate::elements::Element::custom_layout).
+pub trait CustomLayout: 'static {
+    fn layout(&mut self, input: &CustomLayoutInput) -> CustomLayoutOutput;
+}
+
+/// Places children evenly on a circle, like the items of a radial menu, going clockwise from `start_angle`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CircularLayout {
+    /// The distance from the center of the circle to the center of each child.
+    pub radius: f32,
+    /// The angle of the first child in radians, where 0 is at the top.
+    pub start_angle: f32,
+}
+
+impl CircularLayout {
+    pub fn new(radius: f32) -> Self {
+        Self {
+            radius,
+            start_angle: 0.0,
+        }
+    }
+}
+
+impl CustomLayout for CircularLayout {
+    fn layout(&mut self, input: &CustomLayoutInput) -> CustomLayoutOutput {
+        let largest = input
+            .child_sizes
+            .iter()
+            .fold(0.0f32, |largest, size| largest.max(size.width).max(size.height));
+        let center = self.radius + largest / 2.0;
+        let step = TAU / input.child_sizes.len().max(1) as f32;
+
+        let child_positions = input
+            .child_sizes
+            .iter()
+            .enumerate()
+            .map(|(index, size)| {
+                let angle = self.start_angle + step * index as f32;
+                let x = center + self.radius * angle.sin() - size.width / 2.0;
+                let y = center - self.radius * angle.cos() - size.height / 2.0;
+                Point::new(snap(x, input.scale_factor), snap(y, input.scale_factor))
+            })
+            .collect();
+
+        CustomLayoutOutput {
+            child_positions,
+            content_size: Size::new(center * 2.0, center * 2.0),
+        }
+    }
+}
+
+/// Places children in lines like the words of a paragraph, starting a new line when a child doesn't fit, and lines
+/// up the first baseline of the children on each line. Children without text sit on the baseline, like images in a
+/// paragraph.
+///
+/// A child wider than the element gets a line of its own, so text that should wrap around other children is split
+/// into one child per word, like the paragraphs with images that `render_markdown` renders.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FlowLayout {
+    pub column_gap: f32,
+    pub row_gap: f32,
+}
+
+impl FlowLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn gap(mut self, column_gap: f32, row_gap: f32) -> Self {
+        self.column_gap = column_gap;
+        self.row_gap = row_gap;
+        self
+    }
+}
+
+impl CustomLayout for FlowLayout {
+    fn layout(&mut self, input: &CustomLayoutInput) -> CustomLayoutOutput {
+        let sizes = input.child_sizes;
+        let baseline = |index: usize| {
+            let baseline = input.child_baselines.get(index).copied().flatten();
+            baseline.unwrap_or(sizes[index].height)
+        };
+
+        let mut child_positions = vec![Point::ZERO; sizes.len()];
+        let mut content_size = Size::new(0.0f32, 0.0f32);
+        let mut line_start = 0;
+        let mut x = 0.0f32;
+        let mut y = 0.0f32;
+
+        let finish_line = |line: std::ops::Range<usize>, y: &mut f32, child_positions: &mut [Point]| {
+            let ascent = line.clone().fold(0.0f32, |ascent, index| ascent.max(baseline(index)));
+            let descent = line
+                .clone()
+                .fold(0.0f32, |descent, index| descent.max(sizes[index].height - baseline(index)));
+            for index in line {
+                child_positions[index].y = snap(*y + ascent - baseline(index), input.scale_factor);
+            }
+            *y += ascent + descent + self.row_gap;
+        };
+
+        for (index, size) in sizes.iter().enumerate() {
+            if index > line_start && x + size.width > input.available_width {
+                finish_line(line_start..index, &mut y, &mut child_positions);
+                line_start = index;
+                x = 0.0;
+            }
+            child_positions[index].x = snap(x, input.scale_factor);
+            content_size.width = content_size.width.max(x + size.width);
+            x += size.width + self.column_gap;
+        }
+        if !sizes.is_empty() {
+            finish_line(line_start..sizes.len(), &mut y, &mut child_positions);
+            content_size.height = y - self.row_gap;
+        }
+
+        CustomLayoutOutput {
+            child_positions,
+            content_size,
+        }
+    }
+}
+
+/// How many columns and rows a cell of a [`GridLayout`] covers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GridSpan {
+    pub columns: usize,
+    pub rows: usize,
+}
+
+impl Default for GridSpan {
+    fn default() -> Self {
+        Self { columns: 1, rows: 1 }
+    }
+}
+
+/// Places children in a grid with a fixed number of columns, like the cells of a table, going along each row and
+/// skipping the cells that a cell above covers. Each column is as wide as its widest cell and each row as tall as its
+/// tallest, and cells that span several columns or rows widen or heighten them evenly when they don't fit.
+///
+/// ```ignore
+/// Container::new()
+///     .custom_layout(GridLayout::new(3).span(0, 3, 1))
+///     .push(Text::new("Totals"))
+///     .push(Text::new("Q1"))
+///     .push(Text::new("Q2"))
+///     .push(Text::new("Q3"));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct GridLayout {
+    pub columns: usize,
+    pub column_gap: f32,
+    pub row_gap: f32,
+    /// The spans of the children that cover more than one cell, by the index of the child.
+    pub spans: Vec<(usize, GridSpan)>,
+}
+
+impl GridLayout {
+    pub fn new(columns: usize) -> Self {
+        Self {
+            columns,
+            column_gap: 0.0,
+            row_gap: 0.0,
+            spans: Vec::new(),
+        }
+    }
+
+    pub fn gap(mut self, column_gap: f32, row_gap: f32) -> Self {
+        self.column_gap = column_gap;
+        self.row_gap = row_gap;
+        self
+    }
+
+    /// Makes the child at `index` cover `columns` columns and `rows` rows.
+    pub fn span(mut self, index: usize, columns: usize, rows: usize) -> Self {
+        self.spans.retain(|(spanned, _)| *spanned != index);
+        self.spans.push((index, GridSpan { columns, rows }));
+        self
+    }
+
+    fn span_of(&self, index: usize) -> GridSpan {
+        let span = self.spans.iter().find(|(spanned, _)| *spanned == index).map(|(_, span)| *span);
+        let span = span.unwrap_or_default();
+        GridSpan {
+            columns: span.columns.clamp(1, self.columns.max(1)),
+            rows: span.rows.max(1),
+        }
+    }
+}
+
+/// The first cell of a [`GridLayout`] a child covers, and how many it covers.
+#[derive(Clone, Copy, Debug)]
+struct GridCell {
+    column: usize,
+    row: usize,
+    span: GridSpan,
+}
+
+/// Grows `tracks` so that a cell as long as `length` fits in the ones it covers, sharing the extra length evenly.
+fn fit_span(tracks: &mut [f32], start: usize, count: usize, length: f32, gap: f32) {
+    let covered = &mut tracks[start..start + count];
+    let available = covered.iter().sum::<f32>() + gap * (count - 1) as f32;
+    if length > available {
+        let extra = (length - available) / count as f32;
+        covered.iter_mut().for_each(|track| *track += extra);
+    }
+}
+
+impl CustomLayout for GridLayout {
+    fn layout(&mut self, input: &CustomLayoutInput) -> CustomLayoutOutput {
+        let columns = self.columns.max(1);
+
+        // Place each cell in the first free cells it fits in, going along the rows.
+        let mut occupied: Vec<Vec<bool>> = Vec::new();
+        let mut cells = Vec::with_capacity(input.child_sizes.len());
+        let (mut row, mut column) = (0, 0);
+        for index in 0..input.child_sizes.len() {
+            let span = self.span_of(index);
+            loop {
+                if column + span.columns > columns {
+                    row += 1;
+                    column = 0;
+                    continue;
+                }
+                let is_free = |row: usize| {
+                    let covered = column..column + span.columns;
+                    occupied.get(row).is_none_or(|occupied: &Vec<bool>| !occupied[covered].contains(&true))
+                };
+                let free = (row..row + span.rows).all(is_free);
+                if free {
+                    break;
+                }
+                column += 1;
+            }
+            if occupied.len() < row + span.rows {
+                occupied.resize(row + span.rows, vec![false; columns]);
+            }
+            for occupied in &mut occupied[row..row + span.rows] {
+                occupied[column..column + span.columns].fill(true);
+            }
+            cells.push(GridCell { column, row, span });
+            column += span.columns;
+        }
+
+        // Size the columns and rows by the cells in one of them first, then widen them for the spanning cells.
+        let mut widths = vec![0.0f32; columns];
+        let mut heights = vec![0.0f32; occupied.len()];
+        let mut by_span: Vec<(&GridCell, &Size<f32>)> = cells.iter().zip(input.child_sizes).collect();
+        by_span.sort_by_key(|(cell, _)| cell.span.columns);
+        for (cell, size) in &by_span {
+            fit_span(&mut widths, cell.column, cell.span.columns, size.width, self.column_gap);
+        }
+        by_span.sort_by_key(|(cell, _)| cell.span.rows);
+        for (cell, size) in &by_span {
+            fit_span(&mut heights, cell.row, cell.span.rows, size.height, self.row_gap);
+        }
+
+        let offsets = |tracks: &[f32], gap: f32| {
+            let mut offset = 0.0f32;
+            tracks
+                .iter()
+                .map(|track| {
+                    let start = offset;
+                    offset += track + gap;
+                    start
+                })
+                .collect::<Vec<f32>>()
+        };
+        let (column_offsets, row_offsets) = (offsets(&widths, self.column_gap), offsets(&heights, self.row_gap));
+        let child_positions = cells
+            .iter()
+            .map(|cell| {
+                let x = snap(column_offsets[cell.column], input.scale_factor);
+                Point::new(x, snap(row_offsets[cell.row], input.scale_factor))
+            })
+            .collect();
+
+        let length = |tracks: &[f32], gap: f32| {
+            let gaps = tracks.len().saturating_sub(1) as f32;
+            tracks.iter().sum::<f32>() + gap * gaps
+        };
+        CustomLayoutOutput {
+            child_positions,
+            content_size: Size::new(length(&widths, self.column_gap), length(&heights, self.row_gap)),
+        }
+    }
+}
+
+/// Rounds a logical position to a whole physical pixel.
+fn snap(value: f32, scale_factor: f64) -> f64 {
+    (value as f64 * scale_factor).round() / scale_factor
+}
+
+/// The custom layout of an element, and the box of the element it last laid out for.
+#[derive(Clone)]
+pub(crate) struct CustomLayoutState {
+    pub(crate) layout: Rc<RefCell<dyn CustomLayout>>,
+    laid_out_for: Option<ContentBox>,
+}
+
+impl CustomLayoutState {
+    pub(crate) fn new(layout: Rc<RefCell<dyn CustomLayout>>) -> Self {
+        Self {
+            layout,
+            laid_out_for: None,
+        }
+    }
+}
+
+/// The part of an element's last layout its custom layout depends on.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ContentBox {
+    width: f32,
+    /// The offset of the content box from the padding box, which absolute positions are relative to.
+    left: f32,
+    top: f32,
+    /// The padding and border around the content box.
+    extra_width: f32,
+    extra_height: f32,
+}
+
+impl ContentBox {
+    /// Elements that weren't laid out yet get `fallback_width`, usually the width of the window.
+    fn of(layout: &taffy::Layout, fallback_width: f32) -> Self {
+        let extra_width = layout.padding.left + layout.padding.right + layout.border.left + layout.border.right;
+        let extra_height = layout.padding.top + layout.padding.bottom + layout.border.top + layout.border.bottom;
+        let width = if layout.size.width > 0.0 {
+            (layout.size.width - extra_width).max(0.0)
+        } else {
+            fallback_width
+        };
+        Self {
+            width,
+            left: layout.padding.left,
+            top: layout.padding.top,
+            extra_width,
+            extra_height,
+        }
+    }
+}
+
+/// Finds the elements below `root` with a custom layout, the deepest first.
+pub(crate) fn find_custom_layouts(root: &dyn ElementInternals) -> Vec<Rc<RefCell<dyn ElementInternals>>> {
+    fn visit(
+        element: &Rc<RefCell<dyn ElementInternals>>,
+        depth: usize,
+        found: &mut Vec<(usize, Rc<RefCell<dyn ElementInternals>>)>,
+    ) {
+        let Ok(element_ref) = element.try_borrow() else {
+            return;
+        };
+        if element_ref.element_data().custom_layout.is_some() {
+            found.push((depth, element.clone()));
+        }
+        for child in element_ref.children() {
+            visit(child, depth + 1, found);
+        }
+    }
+
+    let mut found = Vec::new();
+    for child in root.children() {
+        visit(child, 0, &mut found);
+    }
+    found.sort_by_key(|(depth, _)| std::cmp::Reverse(*depth));
+    found.into_iter().map(|(_, element)| element).collect()
+}
+
+/// Measures the children of each element and places them with its custom layout.
+pub(crate) fn apply_custom_layouts(
+    elements: &[Rc<RefCell<dyn ElementInternals>>],
+    taffy_tree: &mut TaffyTree,
+    text_context: &mut TextContext,
+    resource_manager: Arc<ResourceManager>,
+    scale_factor: f64,
+    fallback_width: f32,
+) {
+    for element in elements {
+        let Ok(mut element) = element.try_borrow_mut() else {
+            continue;
+        };
+        let (Some(node), Some(state)) = (
+            element.element_data().layout.taffy_node_id,
+            element.element_data().custom_layout.clone(),
+        ) else {
+            continue;
+        };
+        let content_box = ContentBox::of(taffy_tree.get_layout(node), fallback_width);
+
+        let mut children: Vec<(NodeId, taffy::Style)> = Vec::new();
+        let mut child_sizes = Vec::new();
+        let mut child_baselines = Vec::new();
+        for child in element.children() {
+            let Ok(child) = child.try_borrow() else {
+                continue;
+            };
+            if child.element_data().is_mask || child.style().get_display() == Display::None {
+                continue;
+            }
+            let Some(child_node) = child.element_data().layout.taffy_node_id else {
+                continue;
+            };
+            let style = child.style().to_taffy_style();
+            taffy_tree.set_style(child_node, style.clone());
+            let available_space = taffy::Size {
+                width: AvailableSpace::Definite(content_box.width),
+                height: AvailableSpace::MaxContent,
+            };
+            taffy_tree.compute_layout(child_node, available_space, text_context, resource_manager.clone());
+            let size = taffy_tree.get_layout(child_node).size;
+            child_sizes.push(Size::new(size.width, size.height));
+            child_baselines.push(first_baseline(&*child, taffy_tree));
+            children.push((child_node, style));
+        }
+
+        let output = state.layout.borrow_mut().layout(&CustomLayoutInput {
+            available_width: content_box.width,
+            scale_factor,
+            child_sizes: &child_sizes,
+            child_baselines: &child_baselines,
+        });
+
+        let placed = children.into_iter().zip(&child_sizes).zip(&output.child_positions);
+        for (((child_node, mut style), size), position) in placed {
+            style.position = taffy::Position::Absolute;
+            style.inset = taffy::Rect {
+                left: taffy::LengthPercentageAuto::length(content_box.left + position.x as f32),
+                top: taffy::LengthPercentageAuto::length(content_box.top + position.y as f32),
+                right: taffy::LengthPercentageAuto::auto(),
+                bottom: taffy::LengthPercentageAuto::auto(),
+            };
+            style.size = taffy::Size {
+                width: taffy::Dimension::length(size.width),
+                height: taffy::Dimension::length(size.height),
+            };
+            taffy_tree.set_style(child_node, style);
+        }
+
+        let (extra_width, extra_height) = match element.style().get_box_sizing() {
+            BoxSizing::BorderBox => (content_box.extra_width, content_box.extra_height),
+            BoxSizing::ContentBox => (0.0, 0.0),
+        };
+        let mut style = element.style().to_taffy_style();
+        style.min_size = taffy::Size {
+            width: taffy::Dimension::length(output.content_size.width + extra_width),
+            height: taffy::Dimension::length(output.content_size.height + extra_height),
+        };
+        taffy_tree.set_style(node, style);
+
+        if let Some(state) = &mut element.element_data_mut().custom_layout {
+            state.laid_out_for = Some(content_box);
+        }
+    }
+}
+
+/// The baseline of the first line of text in an element or its first descendants, from the top of its border box.
+fn first_baseline(element: &dyn ElementInternals, taffy_tree: &TaffyTree) -> Option<f32> {
+    let node = element.element_data().layout.taffy_node_id?;
+    let layout = taffy_tree.get_layout(node);
+    if let Some(baseline) = element.text_baseline() {
+        return Some(layout.border.top + layout.padding.top + baseline);
+    }
+    element.children().iter().find_map(|child| {
+        let child = child.try_borrow().ok()?;
+        if child.element_data().is_mask || child.style().get_display() == Display::None {
+            return None;
+        }
+        let child_node = child.element_data().layout.taffy_node_id?;
+        let top = taffy_tree.get_layout(child_node).location.y;
+        first_baseline(&*child, taffy_tree).map(|baseline| top + baseline)
+    })
+}
+
+/// Whether the box of an element turned out different from the one its custom layout was given.
+pub(crate) fn custom_layouts_changed(
+    elements: &[Rc<RefCell<dyn ElementInternals>>],
+    taffy_tree: &TaffyTree,
+    fallback_width: f32,
+) -> bool {
+    elements.iter().any(|element| {
+        let Ok(element) = element.try_borrow() else {
+            return false;
+        };
+        let (Some(node), Some(state)) =
+            (element.element_data().layout.taffy_node_id, &element.element_data().custom_layout)
+        else {
+            return false;
+        };
+        state.laid_out_for != Some(ContentBox::of(taffy_tree.get_layout(node), fallback_width))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn circular_layout_centers_children_on_the_circle() {
+        let mut layout = CircularLayout::new(50.0);
+        let child_sizes = [Size::new(20.0, 20.0); 4];
+        let output = layout.layout(&CustomLayoutInput {
+            available_width: 500.0,
+            scale_factor: 1.0,
+            child_sizes: &child_sizes,
+            child_baselines: &[None; 4],
+        });
+        assert_eq!((output.content_size.width, output.content_size.height), (120.0, 120.0));
+        let positions: Vec<(f64, f64)> = output.child_positions.iter().map(|point| (point.x, point.y)).collect();
+        assert_eq!(positions, vec![(50.0, 0.0), (100.0, 50.0), (50.0, 100.0), (0.0, 50.0)]);
+    }
+
+    #[test]
+    fn flow_layout_wraps_children_and_lines_up_their_baselines() {
+        let mut layout = FlowLayout::new();
+        // Two words with a baseline 12px down, and an image 30px tall between them.
+        let child_sizes = [Size::new(40.0, 16.0), Size::new(30.0, 30.0), Size::new(50.0, 16.0)];
+        let output = layout.layout(&CustomLayoutInput {
+            available_width: 100.0,
+            scale_factor: 1.0,
+            child_sizes: &child_sizes,
+            child_baselines: &[Some(12.0), None, Some(12.0)],
+        });
+        let positions: Vec<(f64, f64)> = output.child_positions.iter().map(|point| (point.x, point.y)).collect();
+        // The image sits on the baseline of the first line, and the last word wraps.
+        assert_eq!(positions, vec![(0.0, 18.0), (40.0, 0.0), (0.0, 34.0)]);
+        assert_eq!((output.content_size.width, output.content_size.height), (70.0, 50.0));
+    }
+
+    #[test]
+    fn grid_layout_places_spanning_cells_and_sizes_the_tracks_around_them() {
+        // A header across both columns, then a cell two rows tall next to two short ones.
+        let mut layout = GridLayout::new(2).gap(10.0, 5.0).span(0, 2, 1).span(1, 1, 2);
+        let child_sizes = [
+            Size::new(100.0, 20.0),
+            Size::new(30.0, 20.0),
+            Size::new(40.0, 10.0),
+            Size::new(20.0, 10.0),
+        ];
+        let output = layout.layout(&CustomLayoutInput {
+            available_width: 500.0,
+            scale_factor: 1.0,
+            child_sizes: &child_sizes,
+            child_baselines: &[None; 4],
+        });
+        let positions: Vec<(f64, f64)> = output.child_positions.iter().map(|point| (point.x, point.y)).collect();
+        // The header widens both columns by 10px, and the last cell skips the one the tall cell covers.
+        assert_eq!(positions, vec![(0.0, 0.0), (0.0, 25.0), (50.0, 25.0), (50.0, 40.0)]);
+        assert_eq!((output.content_size.width, output.content_size.height), (100.0, 50.0));
+    }
+}
@@ -9,6 +9,7 @@ use peniko::Color;
 use taffy::NodeId;
 use craft_renderer::renderer::Renderer;
 use crate::elements::scrollable::ScrollState;
+use crate::quality::{QualityFeature, is_degraded};
 use crate::style::{BoxShadow, Position, Style};
 
 #[derive(Clone, Default)]
@@ -86,6 +87,7 @@ struct BorderSpec {
     radii: [(f32, f32); 4],
     scale_factor: f64,
     box_shadows: Vec<BoxShadow>,
+    pixel_snapping: bool,
 }
 
 #[derive(Clone, Default)]
@@ -206,6 +208,7 @@ impl Layout {
         scale_factor: f64,
         border_color: TrblRectangle<Color>,
         box_shadows: Vec<BoxShadow>,
+        pixel_snapping: bool,
     ) {
         let element_rect = self.computed_box_transformed;
         let border_spec = BorderSpec {
@@ -214,6 +217,7 @@ impl Layout {
             radii: border_radius,
             scale_factor,
             box_shadows: box_shadows.to_vec(),
+            pixel_snapping,
         };
 
         if Some(&border_spec) == self.cache_border_spec.as_ref() {
@@ -235,9 +239,16 @@ impl Layout {
             return;
         }
 
-        let borders = element_rect.border;
+        let (border_rectangle, borders) = if pixel_snapping {
+            (
+                element_rect.border_rectangle().snap_to_device_pixels(scale_factor),
+                snap_border_widths(element_rect.border, scale_factor),
+            )
+        } else {
+            (element_rect.border_rectangle(), element_rect.border)
+        };
         let border_spec = CssRoundedRect::new(
-            element_rect.border_rectangle().to_kurbo(),
+            border_rectangle.to_kurbo(),
             [
                 borders.top as f64,
                 borders.right as f64,
@@ -354,7 +365,11 @@ impl Layout {
     }
 
     pub fn draw_borders(&self, renderer: &mut dyn Renderer, current_style: &Style, scale_factor: f64) {
-        if let Some(cache_box_shadows) = &self.cache_box_shadows {
+        // The shadows are lowered while frames are slow, see crate::quality.
+        let box_shadows = self.cache_box_shadows.as_ref().filter(|_| !is_degraded(QualityFeature::Shadows));
+        let blur_scale = if is_degraded(QualityFeature::ShadowBlur) { 0.5 } else { 1.0 };
+
+        if let Some(cache_box_shadows) = box_shadows {
             for shadow in &cache_box_shadows.box_shadows {
                 if shadow.inset {
                     continue;
@@ -365,7 +380,7 @@ impl Layout {
                     offset: shadow.offset,
                     outline: cache_box_shadows.outline.to_path(),
                     path: shadow.shape.to_path(),
-                    blur_radius: shadow.blur_radius,
+                    blur_radius: shadow.blur_radius * blur_scale,
                     color: shadow.color,
                     border_box: cache_box_shadows.border_box,
                 });
@@ -378,16 +393,22 @@ impl Layout {
         match &self.computed_border {
             ComputedBorder::None => {}
             ComputedBorder::Simple => {
-                let padding_rect = self.computed_box_transformed.padding_rectangle().scale(scale_factor);
-                let border_rect = self.computed_box_transformed.border_rectangle().scale(scale_factor);
+                let border_spec = self.cache_border_spec.as_ref().unwrap();
+                let mut padding_rect = self.computed_box_transformed.padding_rectangle().scale(scale_factor);
+                let mut border_rect = self.computed_box_transformed.border_rectangle().scale(scale_factor);
+                let mut thickness = border_spec.width.top as f64 * scale_factor;
+                if border_spec.pixel_snapping {
+                    padding_rect = padding_rect.snap_to_pixels();
+                    border_rect = border_rect.snap_to_pixels();
+                    thickness = snap_border_width(thickness);
+                }
                 // Draw the background.
                 if background_color.components[3] != 0.0 {
                     renderer.draw_rect(padding_rect, background_color);
                 }
-                let thickness = self.cache_border_spec.as_ref().unwrap().width.top;
                 let border_color = current_style.get_border_color().top;
                 if thickness != 0.0 && border_color.components[3] != 0.0 {
-                    renderer.draw_rect_outline(border_rect, border_color, thickness as f64 * scale_factor);
+                    renderer.draw_rect_outline(border_rect, border_color, thickness);
                 }
             }
             ComputedBorder::CssComputed(computed_border) => {
@@ -400,7 +421,7 @@ impl Layout {
             }
         }
 
-        if let Some(cache_box_shadows) = &self.cache_box_shadows {
+        if let Some(cache_box_shadows) = box_shadows {
             for shadow in &cache_box_shadows.box_shadows {
                 if !shadow.inset {
                     continue;
@@ -411,7 +432,7 @@ impl Layout {
                     offset: shadow.offset,
                     outline: cache_box_shadows.inline.to_path(),
                     path: shadow.shape.to_path(),
-                    blur_radius: shadow.blur_radius,
+                    blur_radius: shadow.blur_radius * blur_scale,
                     color: shadow.color,
                     border_box: cache_box_shadows.border_box,
                 });
@@ -468,6 +489,21 @@ impl BezPathOrRect {
     }
 }
 
+/// Rounds a border width in physical pixels to whole pixels, keeping non-zero borders at least one pixel wide.
+fn snap_border_width(width: f64) -> f64 {
+    if width <= 0.0 {
+        0.0
+    } else {
+        width.round().max(1.0)
+    }
+}
+
+/// Snaps logical border widths so that each side covers a whole number of physical pixels.
+fn snap_border_widths(border: TrblRectangle<f32>, scale_factor: f64) -> TrblRectangle<f32> {
+    let snap = |width: f32| (snap_border_width(width as f64 * scale_factor) / scale_factor) as f32;
+    TrblRectangle::new(snap(border.top), snap(border.right), snap(border.bottom), snap(border.left))
+}
+
 #[inline(always)]
 fn from_taffy_point(p: taffy::Point<f32>) -> Point {
     Point {
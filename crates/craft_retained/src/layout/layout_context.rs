@@ -2,7 +2,7 @@ use std::cell::RefCell;
 use std::rc::Weak;
 use std::sync::Arc;
 
-use crate::elements::{TextInner, TextInputInner};
+use crate::elements::{ElementInternals, TextInner, TextInputInner};
 use crate::text::text_context::TextContext;
 use craft_resource_manager::image::ImageResource;
 use craft_resource_manager::{ResourceId, ResourceManager};
@@ -19,6 +19,12 @@ pub struct TaffyTextInputContext {
     pub element: Weak<RefCell<TextInputInner>>,
 }
 
+/// Measures an element with [`ElementInternals::measure`], like a [`Custom`](crate::elements::Custom) element.
+#[derive(Clone)]
+pub struct TaffyElementContext {
+    pub element: Weak<RefCell<dyn ElementInternals>>,
+}
+
 #[derive(Eq, Hash, PartialEq, Copy, Clone, Debug)]
 pub struct TextHashKey {
     pub width_constraint: Option<u32>,
@@ -95,6 +101,7 @@ pub enum LayoutContext {
     TextInput(TaffyTextInputContext),
     Image(ImageContext),
     TinyVg(TinyVgContext),
+    Element(TaffyElementContext),
     Other(LayoutFn),
 }
 //////////////////////////////////////////////////////////////////////////////
@@ -189,6 +196,14 @@ pub fn measure_content(
         Some(LayoutContext::TinyVg(tinyvg_context)) => {
             tinyvg_context.measure(known_dimensions, available_space, resource_manager, style)
         }
+        Some(LayoutContext::Element(taffy_element_context)) => {
+            if let Some(element) = taffy_element_context.element.upgrade()
+                && let Ok(mut element) = element.try_borrow_mut()
+            {
+                return element.measure(known_dimensions, available_space, text_context);
+            }
+            Size::ZERO
+        }
         Some(LayoutContext::Other(_measure_fn)) => Size::ZERO,
     }
 }
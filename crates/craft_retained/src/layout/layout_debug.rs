@@ -0,0 +1,286 @@
+//! Checks a laid out element tree for common layout bugs.
+//!
+//! Turned on per window with [`Window::debug_layout`](crate::elements::Window::debug_layout). Each new issue is
+//! logged as a warning, and the elements with issues are outlined on top of the window.
+
+use std::fmt;
+
+use craft_primitives::geometry::{Rectangle, Size};
+use smol_str::SmolStr;
+
+use crate::elements::{ElementData, ElementInternals};
+use crate::style::{Display, Overflow, Position, Unit};
+
+/// How far a child may go past its parent before it counts as overflowing, to allow for rounding.
+const OVERFLOW_EPSILON: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayoutAxis {
+    Horizontal,
+    Vertical,
+}
+
+impl fmt::Display for LayoutAxis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutAxis::Horizontal => write!(f, "horizontally"),
+            LayoutAxis::Vertical => write!(f, "vertically"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayoutIssueKind {
+    /// The element goes past the padding box of a parent that doesn't clip or scroll.
+    ChildOverflows(LayoutAxis),
+    /// A flex item that was shrunk to nothing even though it has content.
+    ZeroSizedFlexItem,
+    /// A percentage size in a parent whose size in that axis depends on its children, so it has nothing to resolve
+    /// against.
+    PercentageInAutoParent(LayoutAxis),
+    /// The computed size or position is NaN, infinite, or negative.
+    InvalidSize,
+}
+
+impl fmt::Display for LayoutIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutIssueKind::ChildOverflows(axis) => write!(f, "overflows its parent {axis}"),
+            LayoutIssueKind::ZeroSizedFlexItem => write!(f, "is a flex item with content but no size"),
+            LayoutIssueKind::PercentageInAutoParent(axis) => {
+                write!(f, "has a percentage size {axis} inside an auto-sized parent")
+            }
+            LayoutIssueKind::InvalidSize => write!(f, "has a NaN or negative computed size"),
+        }
+    }
+}
+
+/// A layout bug found in an element.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayoutIssue {
+    /// The internal id of the element.
+    pub element: u64,
+    /// The id the element was given, if any.
+    pub id: Option<SmolStr>,
+    pub kind: LayoutIssueKind,
+    /// The border box of the element in logical pixels, as drawn.
+    pub bounds: Rectangle,
+}
+
+impl LayoutIssue {
+    /// Whether two issues are the same problem, even if the element has since moved.
+    pub(crate) fn is_same(&self, other: &LayoutIssue) -> bool {
+        self.element == other.element && self.kind == other.kind
+    }
+}
+
+impl fmt::Display for LayoutIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.id {
+            Some(id) => write!(f, "Element '{id}' ({}) {}", self.element, self.kind),
+            None => write!(f, "Element {} {}", self.element, self.kind),
+        }
+    }
+}
+
+/// The parts of an element's style and layout the checks look at.
+#[derive(Clone, Debug)]
+pub(crate) struct LayoutNode {
+    pub(crate) border_box: Rectangle,
+    pub(crate) padding_box: Rectangle,
+    pub(crate) content_size: Size<f32>,
+    pub(crate) width: Unit,
+    pub(crate) height: Unit,
+    pub(crate) display: Display,
+    pub(crate) position: Position,
+    pub(crate) overflow: [Overflow; 2],
+    /// The window, which is always sized by the window rather than its children.
+    pub(crate) is_root: bool,
+}
+
+impl LayoutNode {
+    fn from_element(element: &dyn ElementInternals, is_root: bool) -> Self {
+        let style = element.style();
+        let layout = &element.element_data().layout;
+        LayoutNode {
+            border_box: layout.computed_box.border_rectangle(),
+            padding_box: layout.computed_box.padding_rectangle(),
+            content_size: layout.content_size,
+            width: style.get_width(),
+            height: style.get_height(),
+            display: style.get_display(),
+            position: style.get_position(),
+            overflow: style.get_overflow(),
+            is_root,
+        }
+    }
+}
+
+/// Returns the issues of an element laid out inside `parent`.
+pub(crate) fn check_node(node: &LayoutNode, parent: Option<&LayoutNode>) -> Vec<LayoutIssueKind> {
+    let mut issues = Vec::new();
+    if node.display == Display::None {
+        return issues;
+    }
+
+    let border_box = node.border_box;
+    let values = [border_box.x, border_box.y, border_box.width, border_box.height];
+    if values.iter().any(|value| !value.is_finite()) || border_box.width < 0.0 || border_box.height < 0.0 {
+        issues.push(LayoutIssueKind::InvalidSize);
+        // The other checks compare sizes, which mean nothing here.
+        return issues;
+    }
+
+    let Some(parent) = parent else {
+        return issues;
+    };
+
+    if node.position == Position::Relative {
+        let inside = parent.padding_box;
+        if parent.overflow[0] == Overflow::Visible
+            && (border_box.left() < inside.left() - OVERFLOW_EPSILON
+                || border_box.right() > inside.right() + OVERFLOW_EPSILON)
+        {
+            issues.push(LayoutIssueKind::ChildOverflows(LayoutAxis::Horizontal));
+        }
+        if parent.overflow[1] == Overflow::Visible
+            && (border_box.top() < inside.top() - OVERFLOW_EPSILON
+                || border_box.bottom() > inside.bottom() + OVERFLOW_EPSILON)
+        {
+            issues.push(LayoutIssueKind::ChildOverflows(LayoutAxis::Vertical));
+        }
+    }
+
+    if parent.display == Display::Flex
+        && ((border_box.width == 0.0 && node.content_size.width > 0.0)
+            || (border_box.height == 0.0 && node.content_size.height > 0.0))
+    {
+        issues.push(LayoutIssueKind::ZeroSizedFlexItem);
+    }
+
+    if !parent.is_root {
+        if matches!(node.width, Unit::Percentage(_)) && parent.width == Unit::Auto {
+            issues.push(LayoutIssueKind::PercentageInAutoParent(LayoutAxis::Horizontal));
+        }
+        if matches!(node.height, Unit::Percentage(_)) && parent.height == Unit::Auto {
+            issues.push(LayoutIssueKind::PercentageInAutoParent(LayoutAxis::Vertical));
+        }
+    }
+
+    issues
+}
+
+/// Checks every element under `root`, which must not have any of its descendants borrowed.
+pub(crate) fn find_layout_issues(root: &dyn ElementInternals) -> Vec<LayoutIssue> {
+    let mut issues = Vec::new();
+    visit(root, None, &mut issues);
+    issues
+}
+
+fn visit(element: &dyn ElementInternals, parent: Option<&LayoutNode>, issues: &mut Vec<LayoutIssue>) {
+    let node = LayoutNode::from_element(element, parent.is_none());
+    let element_data = element.element_data();
+    for kind in check_node(&node, parent) {
+        issues.push(LayoutIssue {
+            element: element_data.internal_id,
+            id: element_data.id.clone(),
+            kind,
+            bounds: element_data.layout.computed_box_transformed.border_rectangle(),
+        });
+    }
+
+    if node.display == Display::None {
+        return;
+    }
+    for child in &element_data.children {
+        visit(&*child.borrow(), Some(&node), issues);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(x: f32, y: f32, width: f32, height: f32) -> LayoutNode {
+        let rectangle = Rectangle::new(x, y, width, height);
+        LayoutNode {
+            border_box: rectangle,
+            padding_box: rectangle,
+            content_size: Size::new(width, height),
+            width: Unit::Px(width),
+            height: Unit::Px(height),
+            display: Display::Flex,
+            position: Position::Relative,
+            overflow: [Overflow::Visible, Overflow::Visible],
+            is_root: false,
+        }
+    }
+
+    #[test]
+    fn child_inside_parent_has_no_issues() {
+        let parent = node(0.0, 0.0, 100.0, 100.0);
+        assert!(check_node(&node(10.0, 10.0, 50.0, 50.0), Some(&parent)).is_empty());
+    }
+
+    #[test]
+    fn overflowing_child_is_reported_unless_the_parent_clips() {
+        let mut parent = node(0.0, 0.0, 100.0, 100.0);
+        let child = node(50.0, 10.0, 80.0, 20.0);
+        assert_eq!(
+            check_node(&child, Some(&parent)),
+            vec![LayoutIssueKind::ChildOverflows(LayoutAxis::Horizontal)]
+        );
+
+        parent.overflow = [Overflow::Scroll, Overflow::Visible];
+        assert!(check_node(&child, Some(&parent)).is_empty());
+    }
+
+    #[test]
+    fn absolute_children_may_overflow() {
+        let parent = node(0.0, 0.0, 100.0, 100.0);
+        let mut child = node(-20.0, 0.0, 10.0, 10.0);
+        child.position = Position::Absolute;
+        assert!(check_node(&child, Some(&parent)).is_empty());
+    }
+
+    #[test]
+    fn flex_item_shrunk_to_nothing_is_reported() {
+        let parent = node(0.0, 0.0, 100.0, 100.0);
+        let mut child = node(0.0, 0.0, 0.0, 20.0);
+        child.content_size = Size::new(40.0, 20.0);
+        assert_eq!(check_node(&child, Some(&parent)), vec![LayoutIssueKind::ZeroSizedFlexItem]);
+
+        child.content_size = Size::new(0.0, 0.0);
+        assert!(check_node(&child, Some(&parent)).is_empty());
+    }
+
+    #[test]
+    fn percentage_in_auto_parent_is_reported() {
+        let mut parent = node(0.0, 0.0, 100.0, 100.0);
+        parent.height = Unit::Auto;
+        let mut child = node(0.0, 0.0, 50.0, 50.0);
+        child.width = Unit::Percentage(50.0);
+        child.height = Unit::Percentage(50.0);
+        assert_eq!(
+            check_node(&child, Some(&parent)),
+            vec![LayoutIssueKind::PercentageInAutoParent(LayoutAxis::Vertical)]
+        );
+
+        parent.is_root = true;
+        assert!(check_node(&child, Some(&parent)).is_empty());
+    }
+
+    #[test]
+    fn nan_and_negative_sizes_are_reported() {
+        assert_eq!(check_node(&node(0.0, 0.0, f32::NAN, 10.0), None), vec![LayoutIssueKind::InvalidSize]);
+        assert_eq!(check_node(&node(0.0, 0.0, 10.0, -1.0), None), vec![LayoutIssueKind::InvalidSize]);
+    }
+
+    #[test]
+    fn hidden_elements_are_skipped() {
+        let parent = node(0.0, 0.0, 100.0, 100.0);
+        let mut child = node(0.0, 0.0, f32::NAN, 500.0);
+        child.display = Display::None;
+        assert!(check_node(&child, Some(&parent)).is_empty());
+    }
+}
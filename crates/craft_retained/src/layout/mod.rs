@@ -1,6 +1,9 @@
+pub mod custom_layout;
 #[allow(clippy::module_inception)]
 pub mod layout;
 pub mod layout_context;
+pub mod layout_debug;
+pub mod popup;
 mod taffy_tree;
 
 pub(crate) use taffy_tree::TaffyTree;
@@ -0,0 +1,234 @@
+//! Positions popups, like dropdown lists, tooltips and menus, next to the element they belong to.
+//!
+//! A popup is placed on a side of its anchor, then flipped to the other side if it doesn't fit, and shifted along the
+//! side to stay within the bounds. The bounds are usually the window, see [`layout_viewport`], or the screen for
+//! popups shown in their own window, see [`Window::screen_bounds`](crate::elements::Window::screen_bounds).
+
+use std::cell::Cell;
+
+use craft_primitives::geometry::{Point, Rectangle, Size};
+
+thread_local! {
+    static LAYOUT_VIEWPORT: Cell<Option<Rectangle>> = const { Cell::new(None) };
+}
+
+/// The side of the anchor a popup is placed on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Side {
+    Top,
+    #[default]
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Side {
+    pub fn opposite(self) -> Side {
+        match self {
+            Side::Top => Side::Bottom,
+            Side::Bottom => Side::Top,
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        }
+    }
+
+    fn is_vertical(self) -> bool {
+        matches!(self, Side::Top | Side::Bottom)
+    }
+}
+
+/// How a popup lines up with its anchor along the side it is placed on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Align {
+    /// The left or top edges line up.
+    #[default]
+    Start,
+    Center,
+    /// The right or bottom edges line up.
+    End,
+}
+
+/// Where a popup goes relative to its anchor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Placement {
+    pub side: Side,
+    pub align: Align,
+}
+
+impl Placement {
+    pub fn new(side: Side, align: Align) -> Self {
+        Self { side, align }
+    }
+}
+
+/// How a popup is positioned.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PopupOptions {
+    pub placement: Placement,
+    /// The gap between the anchor and the popup.
+    pub offset: f32,
+    /// Whether the popup moves to the opposite side when it has more room there.
+    pub flip: bool,
+    /// Whether the popup slides along the side of the anchor to stay within the bounds.
+    pub shift: bool,
+    /// The space kept between the popup and the edges of the bounds.
+    pub padding: f32,
+}
+
+impl Default for PopupOptions {
+    fn default() -> Self {
+        Self {
+            placement: Placement::default(),
+            offset: 0.0,
+            flip: true,
+            shift: true,
+            padding: 4.0,
+        }
+    }
+}
+
+/// Where a popup ended up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PopupPosition {
+    /// The top left corner of the popup.
+    pub position: Point,
+    /// The placement that was used, which has the opposite side if the popup was flipped.
+    pub placement: Placement,
+    /// The room there is on the side the popup was placed on, along the side's axis. A popup that is taller or wider
+    /// than this, like a long dropdown list, still overflows the bounds unless it is made smaller, e.g. by scrolling.
+    pub available: f32,
+}
+
+/// Positions a popup of `size` next to `anchor`, within `bounds`.
+pub fn position_popup(anchor: Rectangle, size: Size<f32>, bounds: Rectangle, options: &PopupOptions) -> PopupPosition {
+    let mut side = options.placement.side;
+    if options.flip {
+        let needed = (if side.is_vertical() { size.height } else { size.width }) + options.offset;
+        let room = room_on(side, anchor, bounds, options.padding);
+        let opposite_room = room_on(side.opposite(), anchor, bounds, options.padding);
+        if room < needed && opposite_room > room {
+            side = side.opposite();
+        }
+    }
+
+    let main = match side {
+        Side::Top => anchor.top() - options.offset - size.height,
+        Side::Bottom => anchor.bottom() + options.offset,
+        Side::Left => anchor.left() - options.offset - size.width,
+        Side::Right => anchor.right() + options.offset,
+    };
+
+    let (anchor_start, anchor_length, length, bounds_start, bounds_length) = if side.is_vertical() {
+        (anchor.x, anchor.width, size.width, bounds.x, bounds.width)
+    } else {
+        (anchor.y, anchor.height, size.height, bounds.y, bounds.height)
+    };
+    let mut cross = match options.placement.align {
+        Align::Start => anchor_start,
+        Align::Center => anchor_start + (anchor_length - length) / 2.0,
+        Align::End => anchor_start + anchor_length - length,
+    };
+    if options.shift {
+        let min = bounds_start + options.padding;
+        let max = bounds_start + bounds_length - options.padding - length;
+        // A popup larger than the bounds sticks to their start, so its beginning stays visible.
+        cross = cross.min(max).max(min);
+    }
+
+    let position = if side.is_vertical() {
+        Point::new(cross as f64, main as f64)
+    } else {
+        Point::new(main as f64, cross as f64)
+    };
+    PopupPosition {
+        position,
+        placement: Placement::new(side, options.placement.align),
+        available: (room_on(side, anchor, bounds, options.padding) - options.offset).max(0.0),
+    }
+}
+
+/// The room between `side` of the anchor and the edge of the bounds.
+fn room_on(side: Side, anchor: Rectangle, bounds: Rectangle, padding: f32) -> f32 {
+    let room = match side {
+        Side::Top => anchor.top() - bounds.top(),
+        Side::Bottom => bounds.bottom() - anchor.bottom(),
+        Side::Left => anchor.left() - bounds.left(),
+        Side::Right => bounds.right() - anchor.right(),
+    };
+    room - padding
+}
+
+/// The visible area of the window being laid out, in logical pixels.
+///
+/// Elements with popups use this during layout to keep them in the window, and to reposition them when the window is
+/// resized or their anchor is scrolled.
+pub fn layout_viewport() -> Option<Rectangle> {
+    LAYOUT_VIEWPORT.get()
+}
+
+pub(crate) fn set_layout_viewport(viewport: Option<Rectangle>) {
+    LAYOUT_VIEWPORT.set(viewport);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: Rectangle = Rectangle {
+        x: 0.0,
+        y: 0.0,
+        width: 400.0,
+        height: 300.0,
+    };
+
+    fn popup(width: f32, height: f32) -> Size<f32> {
+        Size { width, height }
+    }
+
+    fn options(side: Side, align: Align) -> PopupOptions {
+        PopupOptions {
+            placement: Placement::new(side, align),
+            offset: 2.0,
+            padding: 0.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn popup_is_placed_on_the_side_and_aligned() {
+        let anchor = Rectangle::new(100.0, 100.0, 80.0, 20.0);
+        let below = position_popup(anchor, popup(120.0, 50.0), BOUNDS, &options(Side::Bottom, Align::Start));
+        assert_eq!(below.position, Point::new(100.0, 122.0));
+        assert_eq!(below.placement.side, Side::Bottom);
+        assert_eq!(below.available, 178.0);
+
+        let right = position_popup(anchor, popup(40.0, 40.0), BOUNDS, &options(Side::Right, Align::Center));
+        assert_eq!(right.position, Point::new(182.0, 90.0));
+
+        let above = position_popup(anchor, popup(120.0, 50.0), BOUNDS, &options(Side::Top, Align::End));
+        assert_eq!(above.position, Point::new(60.0, 48.0));
+    }
+
+    #[test]
+    fn popup_flips_when_it_has_more_room_on_the_other_side() {
+        let anchor = Rectangle::new(100.0, 250.0, 80.0, 20.0);
+        let flipped = position_popup(anchor, popup(120.0, 100.0), BOUNDS, &options(Side::Bottom, Align::Start));
+        assert_eq!(flipped.placement.side, Side::Top);
+        assert_eq!(flipped.position, Point::new(100.0, 148.0));
+
+        // Neither side fits, and the side asked for has more room, so it is kept.
+        let anchor = Rectangle::new(100.0, 120.0, 80.0, 20.0);
+        let kept = position_popup(anchor, popup(120.0, 200.0), BOUNDS, &options(Side::Bottom, Align::Start));
+        assert_eq!(kept.placement.side, Side::Bottom);
+    }
+
+    #[test]
+    fn popup_shifts_to_stay_in_the_bounds() {
+        let anchor = Rectangle::new(350.0, 100.0, 40.0, 20.0);
+        let shifted = position_popup(anchor, popup(120.0, 50.0), BOUNDS, &options(Side::Bottom, Align::Start));
+        assert_eq!(shifted.position, Point::new(280.0, 122.0));
+
+        let too_wide = position_popup(anchor, popup(500.0, 50.0), BOUNDS, &options(Side::Bottom, Align::Start));
+        assert_eq!(too_wide.position.x, 0.0);
+    }
+}
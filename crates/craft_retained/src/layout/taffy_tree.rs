@@ -4,6 +4,7 @@ use craft_resource_manager::ResourceManager;
 use taffy::{Layout, NodeId, PrintTree, Size, Style};
 
 use crate::layout::layout_context::{LayoutContext, measure_content};
+use crate::scene::scene_changed;
 use crate::text::text_context::TextContext;
 
 pub struct TaffyTree {
@@ -172,12 +173,14 @@ impl TaffyTree {
 
     #[inline(always)]
     pub fn request_layout(&mut self) {
+        scene_changed();
         self.is_layout_dirty = true;
         self.is_apply_layout_dirty.clear();
     }
 
     #[inline(always)]
     pub fn request_apply_layout(&mut self, node: NodeId) {
+        scene_changed();
         let root = self.root_of(node);
         if !self.is_apply_layout_dirty(&root) {
             self.is_apply_layout_dirty.push(root);
@@ -1,8 +1,14 @@
 //! A retained GUI.
 
+#[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
+pub use accesskit;
+
 pub use craft_primitives::{Color, geometry, palette};
 
-pub use craft_renderer::RendererType;
+pub use craft_renderer::renderer::Renderer;
+pub use craft_renderer::{Brush, RendererType};
+pub use craft_renderer::image_cache::{ImageCacheStats, ImageRetention};
+pub use craft_renderer::text_renderer_data::TextAntialiasing;
 
 pub use craft_resource_manager::ResourceId;
 
@@ -10,14 +16,21 @@ pub use craft_runtime::{self, CraftRuntime};
 
 pub use image;
 
+pub use taffy;
+
 pub use winit::dpi::{PhysicalSize as WinitPhysicalSize, Size as WinitSize};
 #[cfg(target_os = "android")]
 pub use winit::platform::android::activity::*;
 pub use winit::window::{Cursor, CursorIcon, Window as WinitWindow, WindowAttributes};
 
-pub use crate::app::queue_window_event;
+pub use crate::app::{
+    is_reduced_motion, queue_keyboard_event, queue_window_event, request_animation_frame, request_animation_frame_at,
+    set_reduced_motion,
+};
 pub use crate::craftcallback::CraftCallback;
-pub use crate::options::CraftOptions;
+pub use crate::options::{CraftOptions, WindowMaterial};
+#[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+pub use crate::recording::InputRecording;
 pub use crate::utils::craft_error::CraftError;
 pub use crate::utils::style_helpers::{auto, pct, px, rgb, rgba};
 
@@ -26,6 +39,10 @@ use std::cell::RefCell;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time;
+#[cfg(target_arch = "wasm32")]
+use web_time as time;
 
 use craft_logging::info;
 
@@ -39,6 +56,11 @@ use winit::platform::android::EventLoopBuilderExtAndroid;
 
 use crate::craft_winit_state::CraftWinitState;
 use crate::events::EventDispatcher;
+use crate::idle::set_idle_thresholds;
+use crate::kiosk::set_kiosk;
+use crate::power::set_power_saving;
+use crate::quality::set_adaptive_quality;
+use crate::spatial_navigation::set_spatial_navigation;
 use crate::utils::cloneable_any::CloneableAny;
 #[cfg(target_arch = "wasm32")]
 use crate::wasm_queue::WASM_QUEUE;
@@ -49,12 +71,52 @@ use crate::events::internal::InternalMessage;
 
 #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
 pub mod accessibility;
+pub mod attract_mode;
+pub mod clipboard;
 pub mod craft_winit_state;
+pub mod diagnostics;
+pub mod document;
 pub mod elements;
 pub mod events;
+pub mod export;
+#[cfg(all(feature = "file_watch", not(target_arch = "wasm32")))]
+pub mod file_watch;
+pub mod frame_stats;
+#[cfg(all(feature = "http_stream", not(target_arch = "wasm32")))]
+pub mod http_stream;
+pub mod idle;
+pub mod keyring;
+pub mod kiosk;
 pub mod layout;
+#[cfg(feature = "markup")]
+pub mod markup;
+#[cfg(all(feature = "monkey", not(target_arch = "wasm32")))]
+pub mod monkey;
+pub mod network;
+pub mod power;
+pub mod preview;
+pub mod quality;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod process;
+pub mod scene;
+pub mod recent_files;
+pub mod scroll_animation;
+pub mod shell;
+pub mod snapping;
+pub mod spatial;
+pub mod spatial_navigation;
+#[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+pub mod recording;
+#[cfg(feature = "speech")]
+pub mod speech;
 pub mod style;
+pub mod system_settings;
 pub mod text;
+pub mod tree_updates;
+#[cfg(all(feature = "update_check", not(target_arch = "wasm32")))]
+pub mod update;
+#[cfg(all(feature = "websocket", not(target_arch = "wasm32")))]
+pub mod websocket;
 #[cfg(target_arch = "wasm32")]
 pub mod wasm_queue;
 pub mod winit {
@@ -130,6 +192,12 @@ fn craft_main_internal(options: Option<CraftOptions>) {
 
 fn setup_craft(craft_options: Option<CraftOptions>) -> CraftState {
     let craft_options = craft_options.unwrap_or_default();
+    set_reduced_motion(craft_options.reduced_motion);
+    set_idle_thresholds(craft_options.idle_thresholds.clone());
+    set_power_saving(craft_options.power_saving);
+    set_adaptive_quality(craft_options.adaptive_quality);
+    set_spatial_navigation(craft_options.spatial_navigation);
+    set_kiosk(craft_options.kiosk.clone());
 
     let (app_sender, app_receiver) = channel::<InternalMessage>(100);
     let (runtime_sender, mut runtime_receiver) = channel::<CraftRuntimeHandle>(1);
@@ -166,12 +234,16 @@ fn setup_craft(craft_options: Option<CraftOptions>) -> CraftState {
         event_dispatcher: EventDispatcher::new(),
         app_sender: app_sender.clone(),
         text_context: None,
+        #[cfg(all(not(target_arch = "wasm32"), feature = "system_fonts"))]
+        loading_text_context: None,
         resource_manager,
         reload_fonts: false,
         runtime: runtime.clone(),
         target_scratch: Vec::new(),
         craft_options: craft_options.clone(),
         active: false,
+        dropped_files: None,
+        started_at: time::Instant::now(),
     });
 
     CraftState::new(runtime, winit_receiver, app_sender, craft_options, craft_app)
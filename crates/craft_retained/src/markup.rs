@@ -0,0 +1,231 @@
+//! Builds element trees from a declarative RON markup file.
+//!
+//! A markup file describes a tree of elements with their ids and styles:
+//!
+//! ```ron
+//! #![enable(implicit_some)]
+//! (
+//!     element: Container,
+//!     id: "root",
+//!     style: (flex_direction: Column, gap: [Px(8.0), Px(8.0)]),
+//!     children: [
+//!         (element: Text("Todos")),
+//!         (element: Slot("items")),
+//!     ],
+//! )
+//! ```
+//!
+//! Slots are empty containers that the code owning the markup fills with data, see [`MarkupTree::slot`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ResourceId;
+use crate::elements::{AsElement, Checkbox, Container, DynElement, Element, Image, Slider, Text, TextInput, TinyVg};
+use crate::style::Style;
+
+/// The element a [`MarkupNode`] creates.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MarkupElement {
+    Container,
+    Text(String),
+    TextInput(String),
+    Checkbox { label: String, checked: bool },
+    Slider { thumb_size: f32 },
+    Image(PathBuf),
+    TinyVg(PathBuf),
+    /// A named container for content provided by code.
+    Slot(String),
+}
+
+impl MarkupElement {
+    fn accepts_children(&self) -> bool {
+        matches!(self, MarkupElement::Container | MarkupElement::Slot(_))
+    }
+}
+
+/// A node of a markup document.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MarkupNode {
+    pub element: MarkupElement,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub style: Option<Style>,
+    #[serde(default)]
+    pub children: Vec<MarkupNode>,
+}
+
+#[derive(Debug)]
+pub enum MarkupError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    /// Thrown when an element that doesn't accept children has children.
+    UnexpectedChildren(MarkupElement),
+    /// Thrown when two slots share a name.
+    DuplicateSlot(String),
+}
+
+impl fmt::Display for MarkupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarkupError::Io(error) => write!(f, "Failed to read the markup: {error}"),
+            MarkupError::Parse(error) => write!(f, "Failed to parse the markup: {error}"),
+            MarkupError::UnexpectedChildren(element) => write!(f, "{element:?} does not accept children"),
+            MarkupError::DuplicateSlot(name) => write!(f, "The slot {name:?} is defined more than once"),
+        }
+    }
+}
+
+impl std::error::Error for MarkupError {}
+
+/// A parsed markup document.
+#[derive(Clone, Debug)]
+pub struct Markup {
+    pub root: MarkupNode,
+}
+
+impl Markup {
+    pub fn from_ron(source: &str) -> Result<Self, MarkupError> {
+        let root = ron::from_str(source).map_err(MarkupError::Parse)?;
+        Ok(Self { root })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MarkupError> {
+        let source = std::fs::read_to_string(path).map_err(MarkupError::Io)?;
+        Self::from_ron(&source)
+    }
+
+    /// Creates the elements described by the markup.
+    ///
+    /// Each call creates a new tree, so the same markup can be instantiated many times.
+    pub fn build(&self) -> Result<MarkupTree, MarkupError> {
+        let mut ids = HashMap::new();
+        let mut slots = HashMap::new();
+        let root = build_node(&self.root, &mut ids, &mut slots)?;
+        Ok(MarkupTree { root, ids, slots })
+    }
+}
+
+/// The elements created from a [`Markup`].
+#[derive(Clone)]
+pub struct MarkupTree {
+    pub root: DynElement,
+    ids: HashMap<String, DynElement>,
+    slots: HashMap<String, DynElement>,
+}
+
+impl MarkupTree {
+    /// Returns the element with the given id, if any.
+    pub fn get(&self, id: &str) -> Option<&DynElement> {
+        self.ids.get(id)
+    }
+
+    /// Returns the container created for the slot with the given name, if any.
+    pub fn slot(&self, name: &str) -> Option<&DynElement> {
+        self.slots.get(name)
+    }
+}
+
+fn build_node(
+    node: &MarkupNode,
+    ids: &mut HashMap<String, DynElement>,
+    slots: &mut HashMap<String, DynElement>,
+) -> Result<DynElement, MarkupError> {
+    if !node.children.is_empty() && !node.element.accepts_children() {
+        return Err(MarkupError::UnexpectedChildren(node.element.clone()));
+    }
+
+    let element = match &node.element {
+        MarkupElement::Container | MarkupElement::Slot(_) => Container::new().as_element_rc(),
+        MarkupElement::Text(text) => Text::new(text).as_element_rc(),
+        MarkupElement::TextInput(text) => TextInput::new(text).as_element_rc(),
+        MarkupElement::Checkbox { label, checked } => Checkbox::new(label, *checked).as_element_rc(),
+        MarkupElement::Slider { thumb_size } => Slider::new(*thumb_size).as_element_rc(),
+        MarkupElement::Image(path) => Image::new(ResourceId::File(path.clone())).as_element_rc(),
+        MarkupElement::TinyVg(path) => TinyVg::new(ResourceId::File(path.clone())).as_element_rc(),
+    };
+    let mut element = DynElement::new(element);
+
+    // Only the properties in the markup are applied, so elements keep their default style for the others.
+    if let Some(style) = &node.style {
+        element.borrow_mut().merge_style(style);
+    }
+    if let Some(id) = &node.id {
+        element = element.id(id);
+        ids.insert(id.clone(), element.clone());
+    }
+    if let MarkupElement::Slot(name) = &node.element
+        && slots.insert(name.clone(), element.clone()).is_some()
+    {
+        return Err(MarkupError::DuplicateSlot(name.clone()));
+    }
+
+    for child in &node.children {
+        let child = build_node(child, ids, slots)?;
+        element = element.push(child);
+    }
+
+    Ok(element)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::{FlexDirection, Unit};
+    use craft_primitives::geometry::TrblRectangle;
+
+    #[test]
+    fn parses_elements_styles_and_slots() {
+        let markup = Markup::from_ron(
+            r#"
+            #![enable(implicit_some)]
+            (
+                element: Container,
+                id: "root",
+                style: (flex_direction: Column),
+                children: [
+                    (element: Text("Todos")),
+                    (element: Slot("items")),
+                ],
+            )
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(markup.root.id.as_deref(), Some("root"));
+        assert!(matches!(
+            markup.root.style.as_ref().unwrap().get_flex_direction(),
+            FlexDirection::Column
+        ));
+        assert_eq!(markup.root.children[0].element, MarkupElement::Text("Todos".to_string()));
+        assert_eq!(markup.root.children[1].element, MarkupElement::Slot("items".to_string()));
+    }
+
+    #[test]
+    fn keeps_the_default_style_of_built_elements() {
+        let markup = Markup::from_ron(
+            r#"
+            #![enable(implicit_some)]
+            (element: TextInput("Name"), id: "name", style: (width: Px(200.0)))
+            "#,
+        )
+        .unwrap();
+        let tree = markup.build().unwrap();
+
+        let input = tree.get("name").unwrap().borrow();
+        assert_eq!(input.style().get_width(), Unit::Px(200.0));
+        assert_eq!(input.style().get_padding(), TrblRectangle::new_all(Unit::Px(4.0)));
+        assert_eq!(input.style().get_border_width(), TrblRectangle::new_all(Unit::Px(1.0)));
+    }
+
+    #[test]
+    fn rejects_children_of_leaf_elements() {
+        let markup = Markup::from_ron(r#"(element: Text("Leaf"), children: [(element: Container)])"#).unwrap();
+
+        assert!(matches!(markup.build(), Err(MarkupError::UnexpectedChildren(_))));
+    }
+}
@@ -0,0 +1,378 @@
+//! Monkey testing: random but valid input against a window, to find panics in the event dispatch and the elements.
+//!
+//! A [`Monkey`] moves, clicks, drags and scrolls the pointer over the elements of a window and types keys, waiting a
+//! frame between gestures. After each gesture it checks the invariants of the element tree with
+//! [`check_invariants`] and panics with its seed if one is broken, so the failure can be reproduced.
+//!
+//! Run a monkey from a [`CraftCallback`](crate::CraftCallback) in a test. Leave out elements that open native
+//! dialogs or other programs, such as file pickers and links, because the monkey will click them.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+use craft_primitives::geometry::{Point, Rectangle};
+use craft_runtime::time::{Duration, sleep};
+use ui_events::keyboard::{Code, Key, KeyState, KeyboardEvent, Modifiers, NamedKey};
+use ui_events::pointer::PointerId;
+use winit::dpi::PhysicalPosition;
+use winit::event::{DeviceId, ElementState, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent};
+
+use crate::app::{ELEMENTS, FOCUS, QueuedWindowEvent, WINDOW_MANAGER};
+use crate::elements::{ElementData, ElementInternals, Window, WindowInternal};
+use crate::utils::random::Random;
+use crate::{queue_keyboard_event, queue_window_event};
+
+/// The keys a monkey types. Escape is left out because it closes the app.
+const KEYS: &[(&str, Code)] = &[
+    ("a", Code::KeyA),
+    ("z", Code::KeyZ),
+    ("c", Code::KeyC),
+    ("v", Code::KeyV),
+    ("1", Code::Digit1),
+    ("9", Code::Digit9),
+    (" ", Code::Space),
+    (".", Code::Period),
+    ("Enter", Code::Enter),
+    ("Backspace", Code::Backspace),
+    ("Delete", Code::Delete),
+    ("Tab", Code::Tab),
+    ("ArrowLeft", Code::ArrowLeft),
+    ("ArrowRight", Code::ArrowRight),
+    ("ArrowUp", Code::ArrowUp),
+    ("ArrowDown", Code::ArrowDown),
+    ("Home", Code::Home),
+    ("End", Code::End),
+    ("PageUp", Code::PageUp),
+    ("PageDown", Code::PageDown),
+];
+
+/// The number of moves between pressing and releasing the pointer in a drag.
+const DRAG_MOVES: usize = 4;
+
+/// Sends random pointer, keyboard and scroll gestures to a window and checks the invariants after each one.
+pub struct Monkey {
+    window: Window,
+    seed: u64,
+    random: Random,
+    wait: Duration,
+}
+
+impl Monkey {
+    /// Creates a monkey for a window. The same seed sends the same gestures to the same tree.
+    pub fn new(window: &Window, seed: u64) -> Self {
+        Self {
+            window: window.clone(),
+            seed,
+            random: Random::new(seed),
+            wait: Duration::from_millis(16),
+        }
+    }
+
+    /// How long to wait after each gesture for the window to handle it. Defaults to 16 milliseconds.
+    pub fn wait(mut self, wait: Duration) -> Self {
+        self.wait = wait;
+        self
+    }
+
+    /// Sends `gestures` gestures to the window, or fewer if the window closes.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the seed and the number of gestures sent if an invariant is broken.
+    pub async fn run(&mut self, gestures: usize) {
+        for sent in 1..=gestures {
+            let Some(winit_window) = self.window.winit_window() else {
+                return;
+            };
+            let window_id = winit_window.id();
+
+            let (area, targets) = self.targets();
+            for event in gesture(&mut self.random, area, &targets) {
+                match event {
+                    QueuedWindowEvent::Window(event) => queue_window_event(window_id, event),
+                    QueuedWindowEvent::Keyboard(event) => queue_keyboard_event(window_id, event),
+                }
+            }
+            sleep(self.wait).await;
+
+            if let Err(violations) = check_invariants() {
+                let violations: Vec<String> = violations.iter().map(ToString::to_string).collect();
+                panic!(
+                    "The monkey with seed {} broke an invariant after {sent} gestures: {}",
+                    self.seed,
+                    violations.join(", ")
+                );
+            }
+        }
+    }
+
+    /// Returns the window's area and the padding rectangles of its visible elements, in physical pixels.
+    fn targets(&self) -> (Rectangle, Vec<Rectangle>) {
+        let window = self.window.inner.borrow();
+        let scale_factor = window.effective_scale_factor() as f32;
+        let area = Rectangle::new(0.0, 0.0, window.window_size.width, window.window_size.height);
+
+        let mut targets = Vec::new();
+        let mut stack: Vec<Rc<RefCell<dyn ElementInternals>>> = window.element_data().children.clone();
+        while let Some(element) = stack.pop() {
+            let element = element.borrow();
+            if !element.is_visible() {
+                continue;
+            }
+            let rectangle = element.get_computed_box_transformed().padding_rectangle();
+            if rectangle.width > 0.0 && rectangle.height > 0.0 {
+                targets.push(Rectangle::new(
+                    rectangle.x * scale_factor,
+                    rectangle.y * scale_factor,
+                    rectangle.width * scale_factor,
+                    rectangle.height * scale_factor,
+                ));
+            }
+            stack.extend(element.element_data().children.iter().cloned());
+        }
+        (area, targets)
+    }
+}
+
+/// Returns the events of a random gesture, with every pressed button and key released again.
+fn gesture(random: &mut Random, area: Rectangle, targets: &[Rectangle]) -> Vec<QueuedWindowEvent> {
+    let choice = random.next_f32();
+    let point = random_point(random, area, targets);
+    if choice < 0.3 {
+        vec![cursor_moved(point)]
+    } else if choice < 0.55 {
+        let button = random_button(random);
+        vec![
+            cursor_moved(point),
+            mouse_input(button, ElementState::Pressed),
+            mouse_input(button, ElementState::Released),
+        ]
+    } else if choice < 0.7 {
+        let end = random_point(random, area, targets);
+        let mut events = vec![cursor_moved(point), mouse_input(MouseButton::Left, ElementState::Pressed)];
+        for step in 1..=DRAG_MOVES {
+            let progress = step as f64 / DRAG_MOVES as f64;
+            events.push(cursor_moved(point.lerp(end, progress)));
+        }
+        events.push(mouse_input(MouseButton::Left, ElementState::Released));
+        events
+    } else if choice < 0.85 {
+        let delta = if random.next_f32() < 0.5 {
+            MouseScrollDelta::LineDelta(0.0, random.in_range(&(-3.0..=3.0)).round())
+        } else {
+            MouseScrollDelta::PixelDelta(PhysicalPosition::new(
+                random.in_range(&(-40.0..=40.0)) as f64,
+                random.in_range(&(-120.0..=120.0)) as f64,
+            ))
+        };
+        vec![
+            cursor_moved(point),
+            QueuedWindowEvent::Window(WindowEvent::MouseWheel {
+                device_id: DeviceId::dummy(),
+                delta,
+                phase: TouchPhase::Moved,
+            }),
+        ]
+    } else {
+        let (key, code) = random.pick(KEYS).cloned().unwrap_or(("a", Code::KeyA));
+        let key = key.parse().unwrap_or(Key::Named(NamedKey::Unidentified));
+        let mut modifiers = Modifiers::empty();
+        if random.next_f32() < 0.2 {
+            modifiers |= Modifiers::CONTROL;
+        }
+        if random.next_f32() < 0.2 {
+            modifiers |= Modifiers::SHIFT;
+        }
+        [KeyState::Down, KeyState::Up]
+            .into_iter()
+            .map(|state| {
+                QueuedWindowEvent::Keyboard(KeyboardEvent {
+                    state,
+                    key: key.clone(),
+                    code,
+                    modifiers,
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+}
+
+/// Returns a point in an element most of the time, and anywhere in the window otherwise.
+fn random_point(random: &mut Random, area: Rectangle, targets: &[Rectangle]) -> Point {
+    let rectangle = if random.next_f32() < 0.75 {
+        random.pick(targets).copied().unwrap_or(area)
+    } else {
+        area
+    };
+    Point::new(
+        (rectangle.x + rectangle.width * random.next_f32()) as f64,
+        (rectangle.y + rectangle.height * random.next_f32()) as f64,
+    )
+}
+
+fn random_button(random: &mut Random) -> MouseButton {
+    match random.next_f32() {
+        choice if choice < 0.8 => MouseButton::Left,
+        choice if choice < 0.95 => MouseButton::Right,
+        _ => MouseButton::Middle,
+    }
+}
+
+fn cursor_moved(point: Point) -> QueuedWindowEvent {
+    QueuedWindowEvent::Window(WindowEvent::CursorMoved {
+        device_id: DeviceId::dummy(),
+        position: PhysicalPosition::new(point.x, point.y),
+    })
+}
+
+fn mouse_input(button: MouseButton, state: ElementState) -> QueuedWindowEvent {
+    QueuedWindowEvent::Window(WindowEvent::MouseInput {
+        device_id: DeviceId::dummy(),
+        state,
+        button,
+    })
+}
+
+/// A broken invariant of the element tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InvariantViolation {
+    /// The focused element isn't in any open window.
+    FocusOutsideWindows,
+    /// An element that was dropped is still registered.
+    DroppedElementRegistered { id: u64 },
+    /// An element's parent doesn't have it as a child.
+    OrphanedElement { id: u64 },
+    /// A pointer is captured by an element that was dropped.
+    DroppedElementCaptured { pointer: PointerId },
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantViolation::FocusOutsideWindows => write!(f, "the focused element isn't in an open window"),
+            InvariantViolation::DroppedElementRegistered { id } => {
+                write!(f, "the dropped element {id} is still registered")
+            }
+            InvariantViolation::OrphanedElement { id } => write!(f, "element {id} isn't a child of its parent"),
+            InvariantViolation::DroppedElementCaptured { pointer } => {
+                write!(f, "pointer {pointer:?} is captured by a dropped element")
+            }
+        }
+    }
+}
+
+/// Checks the invariants of the element trees of all windows.
+///
+/// Elements that are borrowed while this runs are skipped.
+pub fn check_invariants() -> Result<(), Vec<InvariantViolation>> {
+    let mut violations = Vec::new();
+
+    let windows: Vec<Window> = WINDOW_MANAGER.with_borrow(|window_manager| window_manager.windows().to_vec());
+
+    let focus = FOCUS.with_borrow(|focus| focus.as_ref().and_then(Weak::upgrade));
+    if let Some(focus) = focus
+        && !windows.iter().any(|window| is_in_window(&focus, window))
+    {
+        violations.push(InvariantViolation::FocusOutsideWindows);
+    }
+
+    ELEMENTS.with_borrow(|elements| {
+        for (id, element) in elements.iter() {
+            let Some(element) = element.upgrade() else {
+                violations.push(InvariantViolation::DroppedElementRegistered { id });
+                continue;
+            };
+            let Ok(element) = element.try_borrow() else {
+                continue;
+            };
+            let Some(parent) = element.parent().and_then(|parent| parent.upgrade()) else {
+                continue;
+            };
+            let Ok(parent) = parent.try_borrow() else {
+                continue;
+            };
+            let me = &element.element_data().me;
+            if !parent
+                .element_data()
+                .children
+                .iter()
+                .any(|child| std::ptr::addr_eq(Rc::as_ptr(child), me.as_ptr()))
+            {
+                violations.push(InvariantViolation::OrphanedElement { id });
+            }
+        }
+    });
+
+    for window in &windows {
+        let Ok(window) = window.inner.try_borrow() else {
+            continue;
+        };
+        for (pointer, element) in &window.pointer_capture.borrow().pointer_captures {
+            if element.upgrade().is_none() {
+                violations.push(InvariantViolation::DroppedElementCaptured { pointer: *pointer });
+            }
+        }
+    }
+
+    if violations.is_empty() { Ok(()) } else { Err(violations) }
+}
+
+/// Returns true if the element is the window or one of its descendants.
+fn is_in_window(element: &Rc<RefCell<dyn ElementInternals>>, window: &Window) -> bool {
+    let Ok(element) = element.try_borrow() else {
+        // Assume a borrowed element is being changed and is fine.
+        return true;
+    };
+    let Some(root) = element.get_root_element().upgrade() else {
+        return false;
+    };
+    let Ok(root) = root.try_borrow() else {
+        return true;
+    };
+    root.as_any()
+        .downcast_ref::<WindowInternal>()
+        .is_some_and(|root| std::ptr::eq(root, window.inner.as_ptr()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gestures(seed: u64) -> Vec<QueuedWindowEvent> {
+        let mut random = Random::new(seed);
+        let area = Rectangle::new(0.0, 0.0, 800.0, 600.0);
+        let targets = [Rectangle::new(100.0, 100.0, 50.0, 20.0)];
+        (0..200).flat_map(|_| gesture(&mut random, area, &targets)).collect()
+    }
+
+    #[test]
+    fn gestures_repeat_with_the_seed() {
+        assert_eq!(gestures(42), gestures(42));
+        assert_ne!(gestures(42), gestures(43));
+    }
+
+    #[test]
+    fn gestures_release_what_they_press() {
+        let mut buttons_down = 0;
+        let mut keys_down = 0;
+        for event in gestures(7) {
+            match event {
+                QueuedWindowEvent::Window(WindowEvent::MouseInput { state, .. }) => {
+                    buttons_down += if state.is_pressed() { 1 } else { -1 };
+                }
+                QueuedWindowEvent::Window(WindowEvent::CursorMoved { position, .. }) => {
+                    assert!((0.0..=800.0).contains(&position.x) && (0.0..=600.0).contains(&position.y));
+                }
+                QueuedWindowEvent::Keyboard(keyboard_event) => {
+                    assert_ne!(keyboard_event.key, Key::Named(NamedKey::Escape));
+                    keys_down += if keyboard_event.state.is_down() { 1 } else { -1 };
+                }
+                QueuedWindowEvent::Window(_) => {}
+            }
+            assert!((0..=1).contains(&buttons_down) && (0..=1).contains(&keys_down));
+        }
+        assert_eq!((buttons_down, keys_down), (0, 0));
+    }
+}
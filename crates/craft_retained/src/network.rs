@@ -0,0 +1,102 @@
+//! Whether the device is connected to a network, so apps can show that they are offline.
+//!
+//! The connectivity is checked every few seconds, with `navigator.onLine` on the web and by looking for a route to the
+//! internet on other platforms. Every window is sent a [`EventKind::NetworkStatusChanged`] event when it changed.
+//! While the device is offline, downloads of remote resources wait in the queue. The remote resources that failed to
+//! download are tried again once the device is back online.
+//!
+//! [`EventKind::NetworkStatusChanged`]: crate::events::EventKind::NetworkStatusChanged
+
+use std::cell::RefCell;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+
+thread_local! {
+    static NETWORK: RefCell<NetworkMonitor> = RefCell::new(NetworkMonitor::default());
+}
+
+/// How often the connectivity is checked.
+#[cfg(not(target_arch = "wasm32"))]
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Whether the device is connected to a network.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NetworkStatus {
+    /// Also assumed until the connectivity was first checked.
+    #[default]
+    Online,
+    Offline,
+}
+
+/// Tracks the network status, to report when it changed.
+#[derive(Default)]
+pub(crate) struct NetworkMonitor {
+    status: NetworkStatus,
+    #[cfg(not(target_arch = "wasm32"))]
+    last_check: Option<Instant>,
+}
+
+impl NetworkMonitor {
+    /// Records the result of a check. Returns the new status if it changed.
+    pub(crate) fn update(&mut self, online: bool) -> Option<NetworkStatus> {
+        let status = if online { NetworkStatus::Online } else { NetworkStatus::Offline };
+        (self.status != status).then(|| {
+            self.status = status;
+            status
+        })
+    }
+}
+
+pub fn network_status() -> NetworkStatus {
+    NETWORK.with_borrow(|network| network.status)
+}
+
+pub fn is_online() -> bool {
+    network_status() == NetworkStatus::Online
+}
+
+/// Checks the connectivity when it is due, and returns the network status if it changed. Called once per iteration of
+/// the event loop.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn poll_network_status() -> Option<NetworkStatus> {
+    NETWORK.with_borrow_mut(|network| {
+        let now = Instant::now();
+        if network.last_check.is_some_and(|last_check| now.duration_since(last_check) < CHECK_INTERVAL) {
+            return None;
+        }
+        network.last_check = Some(now);
+        network.update(has_route_to_internet())
+    })
+}
+
+/// Reading `navigator.onLine` is cheap, so it is read every time.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn poll_network_status() -> Option<NetworkStatus> {
+    let online = web_sys::window().is_none_or(|window| window.navigator().on_line());
+    NETWORK.with_borrow_mut(|network| network.update(online))
+}
+
+/// Whether the system has a route to a public address. Connecting a UDP socket only looks up the route, so nothing is
+/// sent.
+#[cfg(not(target_arch = "wasm32"))]
+fn has_route_to_internet() -> bool {
+    use std::net::UdpSocket;
+
+    [("0.0.0.0:0", "8.8.8.8:53"), ("[::]:0", "[2001:4860:4860::8888]:53")]
+        .into_iter()
+        .any(|(local, remote)| UdpSocket::bind(local).and_then(|socket| socket.connect(remote)).is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_changes_are_reported() {
+        let mut network = NetworkMonitor::default();
+        assert_eq!(network.update(true), None);
+        assert_eq!(network.update(false), Some(NetworkStatus::Offline));
+        assert_eq!(network.update(false), None);
+        assert_eq!(network.update(true), Some(NetworkStatus::Online));
+    }
+}
@@ -1,4 +1,13 @@
+use std::time::Duration;
+
+use craft_primitives::Color;
+
 use crate::craftcallback::CraftCallback;
+use crate::kiosk::KioskOptions;
+use crate::power::PowerSaving;
+use crate::quality::AdaptiveQuality;
+#[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+use crate::recording::InputRecording;
 
 /// Configuration options for the Craft application.
 ///
@@ -12,6 +21,92 @@ pub struct CraftOptions {
     /// Defaults to `"craft"`.
     pub app_name: String,
     pub craft_callback: Option<CraftCallback>,
+    /// How much small text is thickened to stay legible on low-DPI screens, from `0.0` to `1.0`.
+    ///
+    /// Can be overridden per element with the `text_contrast` style. Defaults to `0.0`.
+    pub text_contrast: f32,
+    /// The material drawn by the platform behind the content of the windows.
+    ///
+    /// Defaults to [`WindowMaterial::None`].
+    pub window_material: WindowMaterial,
+    /// Keeps animations to a minimum, e.g. the text cursor doesn't blink.
+    ///
+    /// Defaults to `false`. Can be changed later with [`set_reduced_motion`](crate::set_reduced_motion).
+    pub reduced_motion: bool,
+    /// How long the user has to not use the app before the windows are sent a
+    /// [`EventKind::UserIdle`](crate::events::EventKind::UserIdle) event, for each threshold. See [`crate::idle`].
+    ///
+    /// Defaults to one minute. Can be changed later with [`set_idle_thresholds`](crate::idle::set_idle_thresholds).
+    pub idle_thresholds: Vec<Duration>,
+    /// When animations are drawn at a lower frame rate, and motion is reduced, to save power. See [`crate::power`].
+    ///
+    /// Defaults to [`PowerSaving::PowerSaver`]. Can be changed later with
+    /// [`set_power_saving`](crate::power::set_power_saving).
+    pub power_saving: PowerSaving,
+    /// Lowers the quality of expensive effects while frames take longer than their budget. See [`crate::quality`].
+    ///
+    /// Defaults to `None`. Can be changed later with [`set_adaptive_quality`](crate::quality::set_adaptive_quality).
+    pub adaptive_quality: Option<AdaptiveQuality>,
+    /// Presents the last frame of a window again, instead of laying it out and drawing it, when the platform asks for
+    /// a redraw and nothing the window draws has changed. See [`crate::scene`].
+    ///
+    /// Defaults to `true`. Elements that draw state changed outside of events and setters must call
+    /// [`Element::invalidate_layer`](crate::elements::Element::invalidate_layer) to be drawn again.
+    pub skip_unchanged_frames: bool,
+    /// A color the windows are filled with as soon as they are created, while the fonts are loaded and the first
+    /// frame is laid out.
+    ///
+    /// Defaults to `None`, which keeps the windows hidden until their first frame.
+    pub splash_color: Option<Color>,
+    /// Encodes and submits the frames on a render thread, one frame behind the layout and drawing of the next frame
+    /// on the UI thread. Helps windows with heavy scenes stay responsive.
+    ///
+    /// Defaults to `false`. Not on the web, which draws on the UI thread, and ignored on macOS, which only creates
+    /// the surface of a window on the main thread.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub render_thread: bool,
+    /// Moves the focus with the arrow keys to the nearest focusable element in that direction, for TV, kiosk and
+    /// gamepad interfaces. See [`crate::spatial_navigation`].
+    ///
+    /// Defaults to `false`. Can be changed later with
+    /// [`set_spatial_navigation`](crate::spatial_navigation::set_spatial_navigation).
+    pub spatial_navigation: bool,
+    /// Runs the app as a kiosk: fullscreen, with the pointer hidden while nobody uses it, shortcuts that would leave
+    /// the app ignored, a reset after a while without input, and the renderer restarted when drawing fails. See
+    /// [`crate::kiosk`].
+    ///
+    /// Defaults to `None`.
+    pub kiosk: Option<KioskOptions>,
+    /// Records the input of the session to a file, or replays a recording.
+    ///
+    /// Defaults to `None`.
+    #[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+    pub input_recording: Option<InputRecording>,
+}
+
+/// A translucent background material provided by the platform window manager.
+///
+/// When a material is set, windows are created transparent and the renderers clear to a transparent color, so
+/// the material shows through wherever the elements don't draw an opaque background.
+///
+/// Platforms that don't support a material fall back to a blurred or plain transparent window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WindowMaterial {
+    /// An opaque window.
+    #[default]
+    None,
+    /// A blurred view of the content behind the window, like the macOS vibrancy materials.
+    Vibrancy,
+    /// The Windows 11 acrylic material, used for transient windows.
+    Acrylic,
+    /// The Windows 11 mica material, tinted with the desktop wallpaper.
+    Mica,
+}
+
+impl WindowMaterial {
+    pub fn is_translucent(&self) -> bool {
+        *self != WindowMaterial::None
+    }
 }
 
 impl Default for CraftOptions {
@@ -19,6 +114,20 @@ impl Default for CraftOptions {
         Self {
             app_name: "craft".to_string(),
             craft_callback: None,
+            text_contrast: 0.0,
+            window_material: WindowMaterial::None,
+            reduced_motion: false,
+            idle_thresholds: vec![Duration::from_secs(60)],
+            power_saving: PowerSaving::default(),
+            adaptive_quality: None,
+            skip_unchanged_frames: true,
+            splash_color: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            render_thread: false,
+            spatial_navigation: false,
+            kiosk: None,
+            #[cfg(all(feature = "recording", not(target_arch = "wasm32")))]
+            input_recording: None,
         }
     }
 }
@@ -27,7 +136,7 @@ impl CraftOptions {
     pub fn basic(app_name: &str) -> Self {
         Self {
             app_name: app_name.to_string(),
-            craft_callback: None,
+            ..Default::default()
         }
     }
 
@@ -35,6 +144,7 @@ impl CraftOptions {
         Self {
             app_name: title.to_string(),
             craft_callback: Some(callback),
+            ..Default::default()
         }
     }
 }
@@ -0,0 +1,313 @@
+//! The battery and power saving state of the device, so apps can do less work when power is scarce.
+//!
+//! The state is read every 30 seconds on a background thread. Every window is sent a
+//! [`EventKind::PowerStatusChanged`] event when it changed. While the device saves power, see
+//! [`CraftOptions::power_saving`](crate::CraftOptions::power_saving), animations are drawn at a lower frame rate and
+//! [`is_reduced_motion`](crate::is_reduced_motion) is true, so continuous animations stop.
+//!
+//! On Windows the state is only read with the `power` feature.
+//!
+//! [`EventKind::PowerStatusChanged`]: crate::events::EventKind::PowerStatusChanged
+
+use std::cell::RefCell;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc::{Receiver, TryRecvError};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+
+#[cfg(target_arch = "wasm32")]
+use web_time::Duration;
+
+/// How often the power status is read.
+#[cfg(not(target_arch = "wasm32"))]
+const READ_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The time between animation frames while the device saves power, for 30 frames per second.
+pub(crate) const POWER_SAVING_FRAME_INTERVAL: Duration = Duration::from_micros(33_333);
+
+thread_local! {
+    static POWER: RefCell<PowerState> = RefCell::new(PowerState::default());
+}
+
+/// The battery and power saving state of the device. A value is `None` when it can't be read on this platform.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PowerStatus {
+    /// How full the battery is, from `0.0` to `1.0`. `None` if the device has no battery.
+    pub battery_level: Option<f32>,
+    pub charging: Option<bool>,
+    /// Whether the device runs on its battery rather than on external power.
+    pub on_battery: Option<bool>,
+    /// Whether the user turned on the power saving mode of the system, like battery saver or low power mode.
+    pub power_saver: Option<bool>,
+}
+
+/// When Craft saves power by lowering the frame rate of animations and reducing motion.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PowerSaving {
+    Never,
+    /// While the power saving mode of the system is on.
+    #[default]
+    PowerSaver,
+    /// While the power saving mode of the system is on, or the device runs on its battery.
+    OnBattery,
+}
+
+impl PowerSaving {
+    /// Whether power is saved with `status`.
+    pub fn applies(self, status: &PowerStatus) -> bool {
+        let power_saver = status.power_saver == Some(true);
+        match self {
+            PowerSaving::Never => false,
+            PowerSaving::PowerSaver => power_saver,
+            PowerSaving::OnBattery => power_saver || status.on_battery == Some(true),
+        }
+    }
+}
+
+#[derive(Default)]
+struct PowerState {
+    /// The last status read, or `None` before the first read finished.
+    status: Option<PowerStatus>,
+    saving: PowerSaving,
+    #[cfg(not(target_arch = "wasm32"))]
+    last_read: Option<Instant>,
+    /// Receives the status from the thread reading it.
+    #[cfg(not(target_arch = "wasm32"))]
+    reading: Option<Receiver<PowerStatus>>,
+}
+
+/// Returns the last power status read. Everything is `None` until the first read finished.
+pub fn power_status() -> PowerStatus {
+    POWER.with_borrow(|power| power.status.clone().unwrap_or_default())
+}
+
+/// Whether Craft saves power right now. See [`CraftOptions::power_saving`](crate::CraftOptions::power_saving).
+pub fn is_power_saving() -> bool {
+    POWER.with_borrow(|power| power.status.as_ref().is_some_and(|status| power.saving.applies(status)))
+}
+
+/// Changes [`CraftOptions::power_saving`](crate::CraftOptions::power_saving) while the app runs.
+pub fn set_power_saving(power_saving: PowerSaving) {
+    POWER.with_borrow_mut(|power| power.saving = power_saving);
+}
+
+/// Starts reading the power status when it is due, and returns the status read if it changed. Called once per
+/// iteration of the event loop.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn poll_power_status() -> Option<PowerStatus> {
+    POWER.with_borrow_mut(|power| {
+        if let Some(reading) = &power.reading {
+            let status = match reading.try_recv() {
+                Ok(status) => status,
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => {
+                    power.reading = None;
+                    return None;
+                }
+            };
+            power.reading = None;
+            let previous = power.status.replace(status.clone());
+            // The first read isn't a change.
+            return previous.is_some_and(|previous| previous != status).then_some(status);
+        }
+
+        let now = Instant::now();
+        if power.last_read.is_none_or(|last_read| now.duration_since(last_read) >= READ_INTERVAL) {
+            power.last_read = Some(now);
+            let (sender, receiver) = std::sync::mpsc::channel();
+            let spawned = std::thread::Builder::new()
+                .name("craft-power-status".to_string())
+                .spawn(move || {
+                    sender.send(read_power_status()).ok();
+                });
+            if spawned.is_ok() {
+                power.reading = Some(receiver);
+            }
+        }
+        None
+    })
+}
+
+/// The web only has an asynchronous battery API, which isn't supported yet.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn poll_power_status() -> Option<PowerStatus> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn read_power_status() -> PowerStatus {
+    let mut status = PowerStatus::default();
+    let mut batteries = Vec::new();
+    if let Ok(entries) = std::fs::read_dir("/sys/class/power_supply") {
+        for entry in entries.flatten() {
+            let read = |name: &str| std::fs::read_to_string(entry.path().join(name)).ok();
+            match read("type").as_deref().map(str::trim) {
+                Some("Battery") => batteries.push((read("capacity"), read("status"))),
+                Some("Mains") => {
+                    if let Some(online) = read("online") {
+                        status.on_battery = Some(online.trim() == "0");
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    if let Some((capacity, battery_status)) = batteries.first() {
+        status.battery_level = capacity
+            .as_deref()
+            .and_then(|capacity| capacity.trim().parse::<f32>().ok())
+            .map(|capacity| capacity / 100.0);
+        status.charging = battery_status.as_deref().map(|battery_status| battery_status.trim() == "Charging");
+        if status.on_battery.is_none() {
+            status.on_battery = battery_status.as_deref().map(|battery_status| battery_status.trim() == "Discharging");
+        }
+    }
+    status.power_saver = crate::system_settings::command_output("powerprofilesctl", &["get"])
+        .map(|profile| profile.trim() == "power-saver");
+    status
+}
+
+#[cfg(target_os = "macos")]
+fn read_power_status() -> PowerStatus {
+    use crate::system_settings::command_output;
+
+    let mut status = command_output("pmset", &["-g", "batt"])
+        .map(|output| parse_pmset_battery(&output))
+        .unwrap_or_default();
+    status.power_saver = command_output("pmset", &["-g"]).map(|output| parse_pmset_low_power_mode(&output));
+    status
+}
+
+#[cfg(all(feature = "power", target_os = "windows"))]
+fn read_power_status() -> PowerStatus {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut power = SYSTEM_POWER_STATUS::default();
+    // SAFETY: `power` is a valid `SYSTEM_POWER_STATUS` for the call to write to.
+    if unsafe { GetSystemPowerStatus(&mut power) }.is_err() {
+        return PowerStatus::default();
+    }
+    power_status_from(
+        power.ACLineStatus,
+        power.BatteryFlag,
+        power.BatteryLifePercent,
+        power.SystemStatusFlag,
+    )
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "macos",
+    all(feature = "power", target_os = "windows"),
+    target_arch = "wasm32"
+)))]
+fn read_power_status() -> PowerStatus {
+    PowerStatus::default()
+}
+
+/// Parses the output of `pmset -g batt`, e.g. `Now drawing from 'Battery Power'` followed by
+/// `-InternalBattery-0 (id=123)	85%; discharging; 3:45 remaining present: true`.
+#[cfg(any(test, target_os = "macos"))]
+fn parse_pmset_battery(output: &str) -> PowerStatus {
+    let mut status = PowerStatus::default();
+    if output.contains("'Battery Power'") {
+        status.on_battery = Some(true);
+    } else if output.contains("'AC Power'") {
+        status.on_battery = Some(false);
+    }
+
+    let battery = output.lines().find(|line| line.contains("InternalBattery"));
+    if let Some((_, details)) = battery.and_then(|battery| battery.split_once('\t')) {
+        let mut details = details.split(';').map(str::trim);
+        status.battery_level = details
+            .next()
+            .and_then(|level| level.strip_suffix('%'))
+            .and_then(|level| level.parse::<f32>().ok())
+            .map(|level| level / 100.0);
+        status.charging = details.next().map(|state| state == "charging");
+    }
+    status
+}
+
+/// Parses the output of `pmset -g`, which has a `lowpowermode 1` line while low power mode is on.
+#[cfg(any(test, target_os = "macos"))]
+fn parse_pmset_low_power_mode(output: &str) -> bool {
+    output.lines().any(|line| {
+        let mut words = line.split_whitespace();
+        words.next() == Some("lowpowermode") && words.next() == Some("1")
+    })
+}
+
+/// Converts the fields of the `SYSTEM_POWER_STATUS` of Windows, where 255 means unknown. A battery flag of 128 means
+/// the device has no battery, and a system status flag of 1 means battery saver is on.
+#[cfg(any(test, all(feature = "power", target_os = "windows")))]
+fn power_status_from(
+    ac_line_status: u8,
+    battery_flag: u8,
+    battery_life_percent: u8,
+    system_status_flag: u8,
+) -> PowerStatus {
+    const UNKNOWN: u8 = 255;
+    const NO_BATTERY: u8 = 128;
+    const CHARGING: u8 = 8;
+
+    let has_battery = battery_flag != UNKNOWN && battery_flag & NO_BATTERY == 0;
+    PowerStatus {
+        battery_level: (has_battery && battery_life_percent != UNKNOWN)
+            .then(|| battery_life_percent.min(100) as f32 / 100.0),
+        charging: has_battery.then_some(battery_flag & CHARGING != 0),
+        on_battery: (ac_line_status != UNKNOWN).then_some(ac_line_status == 0),
+        power_saver: Some(system_status_flag == 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pmset_battery_is_parsed() {
+        let output = "Now drawing from 'Battery Power'\n \
+                      -InternalBattery-0 (id=4653155)\t85%; discharging; 3:45 remaining present: true\n";
+        let status = parse_pmset_battery(output);
+        assert_eq!(status.on_battery, Some(true));
+        assert_eq!(status.battery_level, Some(0.85));
+        assert_eq!(status.charging, Some(false));
+
+        assert!(parse_pmset_low_power_mode("System-wide power settings:\n lowpowermode         1\n"));
+        assert!(!parse_pmset_low_power_mode(" lowpowermode         0\n"));
+    }
+
+    #[test]
+    fn windows_power_status_is_converted() {
+        let status = power_status_from(1, 8, 40, 0);
+        assert_eq!(status.on_battery, Some(false));
+        assert_eq!(status.charging, Some(true));
+        assert_eq!(status.battery_level, Some(0.4));
+        assert_eq!(status.power_saver, Some(false));
+
+        // A desktop without a battery, with battery saver on.
+        let status = power_status_from(1, 128, 255, 1);
+        assert_eq!(status.battery_level, None);
+        assert_eq!(status.charging, None);
+        assert_eq!(status.power_saver, Some(true));
+    }
+
+    #[test]
+    fn power_is_saved_as_configured() {
+        let on_battery = PowerStatus {
+            on_battery: Some(true),
+            power_saver: Some(false),
+            ..Default::default()
+        };
+        assert!(!PowerSaving::PowerSaver.applies(&on_battery));
+        assert!(PowerSaving::OnBattery.applies(&on_battery));
+
+        let power_saver = PowerStatus {
+            power_saver: Some(true),
+            ..Default::default()
+        };
+        assert!(PowerSaving::PowerSaver.applies(&power_saver));
+        assert!(!PowerSaving::Never.applies(&power_saver));
+    }
+}
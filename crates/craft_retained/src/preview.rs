@@ -0,0 +1,413 @@
+//! Mounts a single component with editable props, so widgets can be developed without wiring a full app.
+//!
+//! ```no_run
+//! use craft_retained::elements::{Element, Text};
+//! use craft_retained::preview::{Knob, Preview};
+//!
+//! Preview::new("Greeting", |props| {
+//!     Text::new(&format!("Hello, {}!", props.text("name"))).font_size(props.number("size") as f32)
+//! })
+//! .knob(Knob::text("name", "World"))
+//! .knob(Knob::number("size", 8.0, 64.0, 16.0))
+//! .run();
+//! ```
+//!
+//! Run the binary with `craft_preview` to rebuild and restart it whenever its sources change. Many previews can be
+//! browsed together with a [`Gallery`].
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::elements::{AsElement, Checkbox, Container, Dropdown, DynElement, Element, ElementInternals, Slider, Text, TextInput, Window};
+use crate::style::{AlignItems, Display, FlexDirection, FontWeight, JustifyContent, Overflow};
+use crate::{Color, CraftOptions, craft_main, pct, px, rgb};
+
+/// The value of a [`Knob`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum KnobValue {
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    /// The index of the selected option.
+    Choice(usize),
+}
+
+#[derive(Clone, Debug)]
+enum KnobKind {
+    Bool,
+    Number { min: f64, max: f64 },
+    Text,
+    Choice(Vec<String>),
+}
+
+/// An editor for one prop of a previewed component.
+#[derive(Clone, Debug)]
+pub struct Knob {
+    name: String,
+    kind: KnobKind,
+    default: KnobValue,
+}
+
+impl Knob {
+    pub fn bool(name: &str, default: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: KnobKind::Bool,
+            default: KnobValue::Bool(default),
+        }
+    }
+
+    pub fn number(name: &str, min: f64, max: f64, default: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: KnobKind::Number { min, max },
+            default: KnobValue::Number(default.clamp(min, max)),
+        }
+    }
+
+    pub fn text(name: &str, default: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: KnobKind::Text,
+            default: KnobValue::Text(default.to_string()),
+        }
+    }
+
+    pub fn choice(name: &str, options: &[&str], default: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: KnobKind::Choice(options.iter().map(|option| option.to_string()).collect()),
+            default: KnobValue::Choice(default.min(options.len().saturating_sub(1))),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Creates the element that edits this knob, calling `on_change` with every new value.
+    fn editor(&self, on_change: Rc<dyn Fn(KnobValue)>) -> DynElement {
+        let label = Text::new(&self.name).font_weight(FontWeight::SEMIBOLD);
+
+        let editor = match (&self.kind, &self.default) {
+            (KnobKind::Bool, KnobValue::Bool(default)) => Checkbox::new(&self.name, *default)
+                .on_checkbox_toggled(Rc::new(move |_event, toggled| on_change(KnobValue::Bool(toggled.status))))
+                .as_element_rc(),
+            (KnobKind::Number { min, max }, KnobValue::Number(default)) => Slider::new(16.0)
+                .min(*min)
+                .max(*max)
+                .value(*default)
+                .width(pct(100))
+                .on_slider_value_changed(Rc::new(move |_event, value| on_change(KnobValue::Number(value))))
+                .as_element_rc(),
+            (KnobKind::Text, KnobValue::Text(default)) => TextInput::new(default)
+                .width(pct(100))
+                .on_textinput_changed(Rc::new(move |_event, changed| on_change(KnobValue::Text(changed.value.clone()))))
+                .as_element_rc(),
+            (KnobKind::Choice(options), KnobValue::Choice(default)) => {
+                let mut dropdown = Dropdown::new();
+                for option in options {
+                    dropdown = dropdown.push(Text::new(option));
+                }
+                dropdown
+                    .selected_item(*default)
+                    .on_dropdown_item_selected(Rc::new(move |_event, index| on_change(KnobValue::Choice(index))))
+                    .as_element_rc()
+            }
+            _ => unreachable!("A knob's default value always matches its kind."),
+        };
+
+        DynElement::new(
+            Container::new()
+                .display(Display::Flex)
+                .flex_direction(FlexDirection::Column)
+                .gap(px(4), px(4))
+                .push(label)
+                .push(DynElement::new(editor))
+                .as_element_rc(),
+        )
+    }
+}
+
+/// The current values of a preview's knobs, passed to the component builder.
+#[derive(Clone, Debug, Default)]
+pub struct Props {
+    values: HashMap<String, KnobValue>,
+}
+
+impl Props {
+    pub fn get(&self, name: &str) -> Option<&KnobValue> {
+        self.values.get(name)
+    }
+
+    pub fn set(&mut self, name: &str, value: KnobValue) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    /// Returns the value of a bool knob, or `false` if there is none.
+    pub fn bool(&self, name: &str) -> bool {
+        matches!(self.get(name), Some(KnobValue::Bool(true)))
+    }
+
+    /// Returns the value of a number knob, or `0.0` if there is none.
+    pub fn number(&self, name: &str) -> f64 {
+        match self.get(name) {
+            Some(KnobValue::Number(value)) => *value,
+            _ => 0.0,
+        }
+    }
+
+    /// Returns the value of a text knob, or an empty string if there is none.
+    pub fn text(&self, name: &str) -> &str {
+        match self.get(name) {
+            Some(KnobValue::Text(value)) => value,
+            _ => "",
+        }
+    }
+
+    /// Returns the selected index of a choice knob, or `0` if there is none.
+    pub fn choice(&self, name: &str) -> usize {
+        match self.get(name) {
+            Some(KnobValue::Choice(index)) => *index,
+            _ => 0,
+        }
+    }
+}
+
+type PropsBuilder = Rc<dyn Fn(&Props) -> Rc<RefCell<dyn ElementInternals>>>;
+
+/// A single component with knobs that edit its props.
+#[derive(Clone)]
+pub struct Preview {
+    name: String,
+    knobs: Vec<Knob>,
+    build: PropsBuilder,
+}
+
+impl Preview {
+    /// Creates a preview of the component returned by `build` for the current props.
+    pub fn new<E: AsElement>(name: &str, build: impl Fn(&Props) -> E + 'static) -> Self {
+        Self {
+            name: name.to_string(),
+            knobs: Vec::new(),
+            build: Rc::new(move |props| build(props).as_element_rc()),
+        }
+    }
+
+    pub fn knob(mut self, knob: Knob) -> Self {
+        self.knobs.push(knob);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn knobs(&self) -> &[Knob] {
+        &self.knobs
+    }
+
+    /// Returns the props made of every knob's default value.
+    pub fn default_props(&self) -> Props {
+        let mut props = Props::default();
+        for knob in &self.knobs {
+            props.set(&knob.name, knob.default.clone());
+        }
+        props
+    }
+
+    /// Builds the component for `props`.
+    pub fn build(&self, props: &Props) -> DynElement {
+        DynElement::new((self.build)(props))
+    }
+
+    /// Creates the knobs panel and a stage with the component, which is rebuilt whenever a knob changes.
+    pub fn mount(&self) -> Container {
+        let props = Rc::new(RefCell::new(self.default_props()));
+        let stage = Container::new()
+            .display(Display::Flex)
+            .flex_grow(1.0)
+            .align_items(Some(AlignItems::Center))
+            .justify_content(Some(JustifyContent::Center))
+            .overflow(Overflow::Scroll, Overflow::Scroll)
+            .push(self.build(&props.borrow()));
+
+        let mut knobs_panel = Container::new()
+            .display(Display::Flex)
+            .flex_direction(FlexDirection::Column)
+            .gap(px(12), px(12))
+            .padding_all(px(12))
+            .width(px(240))
+            .border_width(px(0), px(1), px(0), px(0))
+            .border_color_all(rgb(220, 220, 220))
+            .push(Text::new(&self.name).font_size(18.0).font_weight(FontWeight::BOLD));
+
+        for knob in &self.knobs {
+            let name = knob.name.clone();
+            let props = props.clone();
+            let stage = stage.clone();
+            let build = self.build.clone();
+            let on_change: Rc<dyn Fn(KnobValue)> = Rc::new(move |value| {
+                props.borrow_mut().set(&name, value);
+                stage.remove_all_children();
+                stage.clone().push(DynElement::new(build(&props.borrow())));
+            });
+            knobs_panel = knobs_panel.push(knob.editor(on_change));
+        }
+
+        Container::new()
+            .display(Display::Flex)
+            .width(pct(100))
+            .height(pct(100))
+            .push(knobs_panel)
+            .push(stage)
+    }
+
+    /// Opens a window with the preview and runs the app.
+    pub fn run(self) {
+        let window = Window::new(&self.name).width(pct(100)).height(pct(100));
+        window.push(self.mount());
+        craft_main(CraftOptions::basic(&self.name));
+    }
+}
+
+/// A browsable catalog of registered [`Preview`]s, each shown with its knobs.
+#[derive(Clone, Default)]
+pub struct Gallery {
+    previews: Vec<Preview>,
+}
+
+impl Gallery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(mut self, preview: Preview) -> Self {
+        self.previews.push(preview);
+        self
+    }
+
+    pub fn previews(&self) -> &[Preview] {
+        &self.previews
+    }
+
+    /// Returns the registered previews whose name contains `filter`, ignoring case.
+    pub fn filter(&self, filter: &str) -> impl Iterator<Item = &Preview> {
+        let filter = filter.to_lowercase();
+        self.previews
+            .iter()
+            .filter(move |preview| preview.name.to_lowercase().contains(&filter))
+    }
+
+    /// Creates a sidebar listing the previews, with a search field, and the selected preview next to it.
+    pub fn mount(&self) -> Container {
+        let content = Container::new()
+            .display(Display::Flex)
+            .flex_grow(1.0)
+            .height(pct(100));
+        if let Some(first) = self.previews.first() {
+            content.clone().push(first.mount());
+        }
+
+        let entries: Vec<(String, Text)> = self
+            .previews
+            .iter()
+            .enumerate()
+            .map(|(index, preview)| {
+                let background_color = if index == 0 { SELECTED_COLOR } else { Color::TRANSPARENT };
+                let entry = Text::new(&preview.name)
+                    .padding(px(4), px(8), px(4), px(8))
+                    .selectable(false)
+                    .background_color(background_color);
+                (preview.name.clone(), entry)
+            })
+            .collect();
+
+        for (index, (_, entry)) in entries.iter().enumerate() {
+            let content = content.clone();
+            let preview = self.previews[index].clone();
+            let entries = entries.clone();
+            entry.clone().on_pointer_button_down(Rc::new(move |_event, _button| {
+                for (other_index, (_, other)) in entries.iter().enumerate() {
+                    let color = if other_index == index { SELECTED_COLOR } else { Color::TRANSPARENT };
+                    other.clone().background_color(color);
+                }
+                content.remove_all_children();
+                content.clone().push(preview.mount());
+            }));
+        }
+
+        let search_entries = entries.clone();
+        let search = TextInput::new("")
+            .width(pct(100))
+            .on_textinput_changed(Rc::new(move |_event, changed| {
+                let filter = changed.value.to_lowercase();
+                for (name, entry) in &search_entries {
+                    let display = if name.to_lowercase().contains(&filter) { Display::Flex } else { Display::None };
+                    entry.clone().display(display);
+                }
+            }));
+
+        let mut sidebar = Container::new()
+            .display(Display::Flex)
+            .flex_direction(FlexDirection::Column)
+            .gap(px(4), px(4))
+            .padding_all(px(8))
+            .width(px(200))
+            .height(pct(100))
+            .overflow(Overflow::Clip, Overflow::Scroll)
+            .border_width(px(0), px(1), px(0), px(0))
+            .border_color_all(rgb(220, 220, 220))
+            .push(search);
+        for (_, entry) in entries {
+            sidebar = sidebar.push(entry);
+        }
+
+        Container::new()
+            .display(Display::Flex)
+            .width(pct(100))
+            .height(pct(100))
+            .push(sidebar)
+            .push(content)
+    }
+
+    /// Opens a window with the gallery and runs the app.
+    pub fn run(self, title: &str) {
+        let window = Window::new(title).width(pct(100)).height(pct(100));
+        window.push(self.mount());
+        craft_main(CraftOptions::basic(title));
+    }
+}
+
+const SELECTED_COLOR: Color = Color::from_rgb8(225, 235, 250);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_props_come_from_knobs() {
+        let preview = Preview::new("Label", |props| Text::new(props.text("text")))
+            .knob(Knob::text("text", "Hello"))
+            .knob(Knob::number("size", 8.0, 32.0, 64.0))
+            .knob(Knob::choice("align", &["Start", "End"], 5));
+
+        let props = preview.default_props();
+        assert_eq!(props.text("text"), "Hello");
+        assert_eq!(props.number("size"), 32.0);
+        assert_eq!(props.choice("align"), 1);
+        assert!(!props.bool("missing"));
+    }
+
+    #[test]
+    fn gallery_filters_by_name() {
+        let gallery = Gallery::new()
+            .register(Preview::new("Button", |_| Text::new("Button")))
+            .register(Preview::new("Text Input", |_| Text::new("Text Input")))
+            .register(Preview::new("Slider", |_| Text::new("Slider")));
+
+        let names: Vec<&str> = gallery.filter("t").map(Preview::name).collect();
+        assert_eq!(names, ["Button", "Text Input"]);
+    }
+}
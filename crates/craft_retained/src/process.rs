@@ -0,0 +1,276 @@
+//! Child processes whose output is sent to an element line by line, like a build shown in a log view.
+//!
+//! ```ignore
+//! let log = LogView::new().on_process(Rc::new(|event, process| {
+//!     let mut target = event.target.borrow_mut();
+//!     let log = target.as_any_mut().downcast_mut::<LogViewInner>().unwrap();
+//!     match &process.kind {
+//!         ProcessEventKind::Stdout(line) => log.push(LogRecord::new(LogLevel::Info, "cargo", line)),
+//!         ProcessEventKind::Stderr(line) => log.push(LogRecord::new(LogLevel::Warn, "cargo", line)),
+//!         ProcessEventKind::Exited(code) => log.push(LogRecord::new(LogLevel::Info, "cargo", &format!("{code:?}"))),
+//!     }
+//! }));
+//! let build = log.spawn_process(ProcessCommand::new("cargo").arg("build"))?;
+//! ```
+//!
+//! The output of every process is read on its own threads. The exit is sent after all of the output. A process is
+//! killed with [`ChildProcess::kill`], or when the element it sends to is dropped.
+
+use std::cell::{Cell, RefCell};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::rc::Weak;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+
+use craft_logging::warn;
+
+use crate::app::queue_event;
+use crate::elements::ElementInternals;
+use crate::events::{Event, EventKind, ProcessEvent, ProcessEventKind};
+
+thread_local! {
+    static PROCESSES: RefCell<Vec<Process>> = const { RefCell::new(Vec::new()) };
+    static NEXT_PROCESS_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// The program to run, with its arguments and environment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProcessCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    /// The working directory of the process, or the one of the app.
+    pub current_dir: Option<PathBuf>,
+    /// Variables set in the environment of the process, on top of the ones of the app.
+    pub env: Vec<(String, String)>,
+}
+
+impl ProcessCommand {
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            current_dir: None,
+            env: Vec::new(),
+        }
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn current_dir(mut self, current_dir: impl Into<PathBuf>) -> Self {
+        self.current_dir = Some(current_dir.into());
+        self
+    }
+
+    pub fn env(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// A handle to a process spawned with [`Element::spawn_process`](crate::elements::Element::spawn_process).
+///
+/// Dropping the handle doesn't kill the process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChildProcess {
+    id: u64,
+}
+
+impl ChildProcess {
+    /// The id in the [`ProcessEvent`]s of this process.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Kills the process. The output it wrote before and its exit are still sent.
+    pub fn kill(&self) {
+        PROCESSES.with_borrow_mut(|processes| {
+            if let Some(process) = processes.iter_mut().find(|process| process.id == self.id) {
+                process.child.kill().ok();
+            }
+        });
+    }
+}
+
+struct Process {
+    id: u64,
+    target: Weak<RefCell<dyn ElementInternals>>,
+    /// The internal id of the target, to find its processes while it's dropped.
+    target_id: u64,
+    child: Child,
+    /// The lines of stdout and stderr, until both are closed.
+    output: Receiver<ProcessEventKind>,
+}
+
+/// Spawns `command` and sends its output to `target`.
+pub(crate) fn spawn_for(
+    command: &ProcessCommand,
+    target: Weak<RefCell<dyn ElementInternals>>,
+    target_id: u64,
+) -> io::Result<ChildProcess> {
+    let mut builder = Command::new(&command.program);
+    builder
+        .args(&command.args)
+        .envs(command.env.iter().map(|(name, value)| (name, value)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(current_dir) = &command.current_dir {
+        builder.current_dir(current_dir);
+    }
+    let mut child = builder.spawn()?;
+
+    let (sender, output) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        read_lines(stdout, sender.clone(), ProcessEventKind::Stdout);
+    }
+    if let Some(stderr) = child.stderr.take() {
+        read_lines(stderr, sender, ProcessEventKind::Stderr);
+    }
+
+    let id = NEXT_PROCESS_ID.get();
+    NEXT_PROCESS_ID.set(id + 1);
+    PROCESSES.with_borrow_mut(|processes| {
+        processes.push(Process {
+            id,
+            target,
+            target_id,
+            child,
+            output,
+        })
+    });
+    Ok(ChildProcess { id })
+}
+
+/// Sends the lines of an output of a process on a thread, until the process closes it.
+fn read_lines<R: Read + Send + 'static>(
+    output: R,
+    sender: Sender<ProcessEventKind>,
+    kind: fn(String) -> ProcessEventKind,
+) {
+    let spawned = std::thread::Builder::new()
+        .name("craft-process-output".to_string())
+        .spawn(move || {
+            let mut reader = BufReader::new(output);
+            let mut line = Vec::new();
+            loop {
+                line.clear();
+                match reader.read_until(b'\n', &mut line) {
+                    Ok(0) | Err(_) => return,
+                    Ok(_) => {
+                        if sender.send(kind(line_text(&line))).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+        });
+    if let Err(error) = spawned {
+        warn!("Failed to start the thread reading the output of a process: {error}");
+    }
+}
+
+/// Kills the processes of an element, as it's dropped and can't receive their output anymore.
+pub(crate) fn kill_processes_of(element_id: u64) {
+    PROCESSES.with(|processes| {
+        // An element dropped while the processes are polled has its processes killed by the poll instead.
+        let Ok(mut processes) = processes.try_borrow_mut() else {
+            return;
+        };
+        processes.retain_mut(|process| {
+            if process.target_id != element_id {
+                return true;
+            }
+            kill_and_wait(&mut process.child);
+            false
+        });
+    });
+}
+
+/// Kills a process, and waits for it so it doesn't linger as a zombie. A killed process exits right away.
+fn kill_and_wait(child: &mut Child) {
+    child.kill().ok();
+    child.wait().ok();
+}
+
+/// The text of a line of output, without its line break. Output that isn't UTF-8 is replaced.
+fn line_text(line: &[u8]) -> String {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(line)).into_owned()
+}
+
+/// Queues the output and exits of the processes for their elements. Returns true if any were queued.
+///
+/// Called once per iteration of the event loop.
+pub(crate) fn poll_processes() -> bool {
+    PROCESSES.with_borrow_mut(|processes| {
+        let mut queued = false;
+        processes.retain_mut(|process| {
+            let Some(target) = process.target.upgrade() else {
+                kill_and_wait(&mut process.child);
+                return false;
+            };
+            let mut queue = |kind| {
+                let event = ProcessEvent {
+                    process: process.id,
+                    kind,
+                };
+                queue_event(Event::new(target.clone()), EventKind::Process(event));
+                queued = true;
+            };
+            loop {
+                match process.output.try_recv() {
+                    Ok(kind) => queue(kind),
+                    Err(TryRecvError::Empty) => return true,
+                    // Both outputs were closed, so the process exited or is about to.
+                    Err(TryRecvError::Disconnected) => break,
+                }
+            }
+            match process.child.try_wait() {
+                Ok(Some(status)) => {
+                    queue(ProcessEventKind::Exited(status.code()));
+                    false
+                }
+                Ok(None) => true,
+                Err(error) => {
+                    warn!("Failed to wait for a process: {error}");
+                    queue(ProcessEventKind::Exited(None));
+                    false
+                }
+            }
+        });
+        queued
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::{Element, Text};
+
+    #[test]
+    fn line_breaks_are_removed() {
+        assert_eq!(line_text(b"Compiling craft\r\n"), "Compiling craft");
+        assert_eq!(line_text(b"no line break"), "no line break");
+        assert_eq!(line_text(b"\xffok\n"), "\u{fffd}ok");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn processes_are_killed_when_their_element_is_dropped() {
+        let text = Text::new("Building");
+        text.spawn_process(ProcessCommand::new("sleep").arg("60")).unwrap();
+        assert_eq!(PROCESSES.with_borrow(Vec::len), 1);
+
+        drop(text);
+        assert!(PROCESSES.with_borrow(Vec::is_empty));
+    }
+}
@@ -0,0 +1,318 @@
+//! Lowering the quality of expensive effects while frames take longer than their budget, and raising it again once
+//! there is headroom.
+//!
+//! ```ignore
+//! craft_main(CraftOptions {
+//!     adaptive_quality: Some(AdaptiveQuality::default()),
+//!     ..Default::default()
+//! });
+//!
+//! // Drawn after the built-in effects were lowered.
+//! quality::register_degradable("particles", Rc::new(|degraded| {
+//!     PARTICLE_COUNT.set(if degraded { 50 } else { 500 });
+//! }));
+//! ```
+//!
+//! The governor averages the time of the frames that were drawn. While the average is over the budget, it lowers one
+//! more feature at a time, in the order of [`QualityFeature`] followed by the features of the app in the order they
+//! were registered. While the average is well below the budget, it restores them in reverse order. A window that
+//! stopped drawing has headroom too, so while features are lowered the governor also restores one every
+//! [`AdaptiveQuality::restore_delay`] in which no frame was drawn.
+//!
+//! The renderers antialias paths analytically rather than with MSAA, so there is no sample count to lower. Subpixel
+//! text antialiasing, the most expensive antialiasing they do, is lowered in its place.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::{Duration, Instant};
+
+#[cfg(target_arch = "wasm32")]
+use web_time::{Duration, Instant};
+
+use craft_runtime::{Job, run_later};
+
+use crate::scene::scene_changed;
+
+thread_local! {
+    static QUALITY: RefCell<QualityState> = RefCell::new(QualityState::default());
+}
+
+/// When the governor lowers and restores the quality. See
+/// [`CraftOptions::adaptive_quality`](crate::CraftOptions::adaptive_quality).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdaptiveQuality {
+    /// How long a frame may take, e.g. 16.6 ms for 60 frames per second.
+    pub frame_budget: Duration,
+    /// The share of the budget the average frame has to stay under for the quality to be raised again.
+    pub headroom: f32,
+    /// How long the governor waits after a change before it lowers the quality again.
+    pub degrade_delay: Duration,
+    /// How long the governor waits after a change before it raises the quality again. Longer than
+    /// [`AdaptiveQuality::degrade_delay`], so the quality doesn't flicker.
+    pub restore_delay: Duration,
+}
+
+impl Default for AdaptiveQuality {
+    fn default() -> Self {
+        Self {
+            frame_budget: Duration::from_micros(16_666),
+            headroom: 0.6,
+            degrade_delay: Duration::from_millis(500),
+            restore_delay: Duration::from_secs(3),
+        }
+    }
+}
+
+/// The built-in features the governor lowers, in the order they are lowered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QualityFeature {
+    /// Continuous animations stop, like with [`is_reduced_motion`](crate::is_reduced_motion).
+    Animations,
+    /// Box shadows are drawn with half their blur radius.
+    ShadowBlur,
+    /// Box shadows aren't drawn.
+    Shadows,
+    /// Text is antialiased in grayscale instead of subpixels.
+    SubpixelText,
+}
+
+/// How many [`QualityFeature`]s there are.
+const BUILT_IN_FEATURES: usize = 4;
+
+/// Called with `true` when a feature of the app is lowered, and with `false` when it is restored.
+pub type DegradableHandler = Rc<dyn Fn(bool)>;
+
+struct Degradable {
+    name: String,
+    handler: DegradableHandler,
+}
+
+#[derive(Default)]
+struct QualityState {
+    governor: Option<Governor>,
+    /// How many features are lowered, counting the built-in ones first.
+    level: usize,
+    degradables: Vec<Degradable>,
+    /// Whether a check for a window that stopped drawing is scheduled.
+    idle_check_scheduled: bool,
+}
+
+impl QualityState {
+    fn features(&self) -> usize {
+        BUILT_IN_FEATURES + self.degradables.len()
+    }
+}
+
+/// Decides when the quality changes, from the times of the frames.
+pub(crate) struct Governor {
+    options: AdaptiveQuality,
+    /// The moving average of the frame times, in seconds.
+    average: Option<f64>,
+    last_change: Option<Instant>,
+    last_frame: Option<Instant>,
+}
+
+impl Governor {
+    /// How much the latest frame counts in the average.
+    const SMOOTHING: f64 = 0.2;
+
+    pub(crate) fn new(options: AdaptiveQuality) -> Self {
+        Self {
+            options,
+            average: None,
+            last_change: None,
+            last_frame: None,
+        }
+    }
+
+    /// Records the time of a frame, and returns the new level if it should change.
+    pub(crate) fn record(&mut self, frame: Duration, now: Instant, level: usize, max_level: usize) -> Option<usize> {
+        let frame = frame.as_secs_f64();
+        let average = match self.average {
+            Some(average) => average + (frame - average) * Self::SMOOTHING,
+            None => frame,
+        };
+        self.average = Some(average);
+        self.last_frame = Some(now);
+
+        let since_change = self
+            .last_change
+            .map_or(Duration::MAX, |last_change| now.duration_since(last_change));
+        let budget = self.options.frame_budget.as_secs_f64();
+        let new_level = if average > budget && level < max_level && since_change >= self.options.degrade_delay {
+            level + 1
+        } else if average < budget * self.options.headroom as f64
+            && level > 0
+            && since_change >= self.options.restore_delay
+        {
+            level - 1
+        } else {
+            return None;
+        };
+        self.last_change = Some(now);
+        Some(new_level)
+    }
+
+    /// Returns the new level if no frame was drawn for long enough to restore a feature.
+    pub(crate) fn check_idle(&mut self, now: Instant, level: usize) -> Option<usize> {
+        let restore_delay = self.options.restore_delay;
+        let waited = |since: Option<Instant>| since.is_none_or(|since| now.duration_since(since) >= restore_delay);
+        if level == 0 || !waited(self.last_frame) || !waited(self.last_change) {
+            return None;
+        }
+        // The frames before the pause don't say how fast the next ones are.
+        self.average = None;
+        self.last_change = Some(now);
+        Some(level - 1)
+    }
+
+    pub(crate) fn restore_delay(&self) -> Duration {
+        self.options.restore_delay
+    }
+}
+
+/// Turns the governor on with `options`, or off with `None`, which restores the quality. See
+/// [`CraftOptions::adaptive_quality`](crate::CraftOptions::adaptive_quality).
+pub fn set_adaptive_quality(options: Option<AdaptiveQuality>) {
+    QUALITY.with_borrow_mut(|quality| quality.governor = options.map(Governor::new));
+    if options.is_none() {
+        set_level(0);
+    }
+}
+
+/// Registers a feature of the app the governor can lower, after the built-in features and the ones registered
+/// before. Registering a name again replaces its handler.
+pub fn register_degradable(name: &str, handler: DegradableHandler) {
+    QUALITY.with_borrow_mut(|quality| {
+        if let Some(degradable) = quality
+            .degradables
+            .iter_mut()
+            .find(|degradable| degradable.name == name)
+        {
+            degradable.handler = handler;
+        } else {
+            quality.degradables.push(Degradable {
+                name: name.to_string(),
+                handler,
+            });
+        }
+    });
+}
+
+/// Whether the governor lowered a built-in feature.
+pub fn is_degraded(feature: QualityFeature) -> bool {
+    QUALITY.with_borrow(|quality| quality.level > feature as usize)
+}
+
+/// How many features are lowered right now, from 0 for the full quality.
+pub fn quality_level() -> usize {
+    QUALITY.with_borrow(|quality| quality.level)
+}
+
+/// Records the time of a frame that was drawn, and lowers or restores a feature if the governor decides to.
+pub(crate) fn record_frame(frame: Duration) {
+    let level = QUALITY.with_borrow_mut(|quality| {
+        let max_level = quality.features();
+        let level = quality.level;
+        quality
+            .governor
+            .as_mut()?
+            .record(frame, Instant::now(), level, max_level)
+    });
+    if let Some(level) = level {
+        set_level(level);
+    }
+}
+
+/// Lowers or restores the features up to `level`, and tells the features of the app that changed.
+fn set_level(level: usize) {
+    let (previous, changed) = QUALITY.with_borrow_mut(|quality| {
+        let previous = std::mem::replace(&mut quality.level, level);
+        let (low, high) = (previous.min(level), previous.max(level));
+        let changed: Vec<DegradableHandler> = quality
+            .degradables
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| (low..high).contains(&(BUILT_IN_FEATURES + index)))
+            .map(|(_, degradable)| degradable.handler.clone())
+            .collect();
+        (previous, changed)
+    });
+    if previous == level {
+        return;
+    }
+    // Handlers are called outside of the borrow, so they can register features themselves.
+    for handler in changed {
+        handler(level > previous);
+    }
+    scene_changed();
+    schedule_idle_check();
+}
+
+/// Checks after the restore delay whether a feature can be restored because no frame was drawn, while features are
+/// lowered.
+fn schedule_idle_check() {
+    let delay = QUALITY.with_borrow_mut(|quality| {
+        if quality.level == 0 || quality.idle_check_scheduled {
+            return None;
+        }
+        let delay = quality.governor.as_ref()?.restore_delay();
+        quality.idle_check_scheduled = true;
+        Some(delay)
+    });
+    if let Some(delay) = delay {
+        run_later(Job::after(Box::new(check_idle), delay));
+    }
+}
+
+fn check_idle() {
+    let level = QUALITY.with_borrow_mut(|quality| {
+        quality.idle_check_scheduled = false;
+        let level = quality.level;
+        quality.governor.as_mut()?.check_idle(Instant::now(), level)
+    });
+    match level {
+        Some(level) => set_level(level),
+        // Frames are still drawn, or the last change is too recent.
+        None => schedule_idle_check(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quality_is_lowered_over_budget_and_restored_with_headroom() {
+        let mut governor = Governor::new(AdaptiveQuality::default());
+        let start = Instant::now();
+        let slow = Duration::from_millis(30);
+        let fast = Duration::from_millis(5);
+
+        assert_eq!(governor.record(slow, start, 0, 4), Some(1));
+        // Too soon after the last change.
+        assert_eq!(governor.record(slow, start + Duration::from_millis(100), 1, 4), None);
+        assert_eq!(governor.record(slow, start + Duration::from_millis(600), 1, 4), Some(2));
+        // Nothing left to lower.
+        assert_eq!(governor.record(slow, start + Duration::from_secs(2), 4, 4), None);
+
+        let mut now = start + Duration::from_secs(2);
+        for _ in 0..20 {
+            now += Duration::from_millis(16);
+            assert_eq!(governor.record(fast, now, 2, 4), None);
+        }
+        assert_eq!(governor.record(fast, start + Duration::from_secs(6), 2, 4), Some(1));
+    }
+
+    #[test]
+    fn quality_is_restored_once_no_frame_was_drawn_for_the_restore_delay() {
+        let mut governor = Governor::new(AdaptiveQuality::default());
+        let start = Instant::now();
+        assert_eq!(governor.record(Duration::from_millis(30), start, 0, 4), Some(1));
+
+        assert_eq!(governor.check_idle(start + Duration::from_secs(1), 1), None);
+        assert_eq!(governor.check_idle(start + Duration::from_secs(3), 1), Some(0));
+        assert_eq!(governor.check_idle(start + Duration::from_secs(9), 0), None);
+    }
+}
@@ -0,0 +1,176 @@
+//! The documents the user opened recently, for an "Open Recent" menu.
+//!
+//! The list is kept in a file, one path per line, once [`load_recent_files`] said where. Every file added with
+//! [`add_recent_file`] is also given to the OS: the recent documents of the app on Windows and macOS, which show in
+//! the jump list and dock menu too, see [`crate::shell`]. Every window is sent a [`EventKind::RecentFilesChanged`]
+//! event when the list changed.
+//!
+//! ```ignore
+//! recent_files::load_recent_files(config_dir.join("recent_files"))?;
+//!
+//! fn open(path: &Path) {
+//!     // ...
+//!     recent_files::add_recent_file(path);
+//! }
+//!
+//! window.on_recent_files_changed(Rc::new(|_event, files| rebuild_open_recent_menu(files)));
+//! ```
+//!
+//! [`EventKind::RecentFilesChanged`]: crate::events::EventKind::RecentFilesChanged
+
+use std::cell::RefCell;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use craft_logging::warn;
+
+/// How many files are kept by default.
+const DEFAULT_MAX_RECENT_FILES: usize = 10;
+
+thread_local! {
+    static RECENT_FILES: RefCell<RecentFiles> = RefCell::new(RecentFiles::default());
+}
+
+struct RecentFiles {
+    /// Most recent first.
+    files: Vec<PathBuf>,
+    max: usize,
+    /// Where the list is kept, once it was loaded.
+    storage: Option<PathBuf>,
+    /// Whether the list changed since the windows were last told.
+    changed: bool,
+}
+
+impl Default for RecentFiles {
+    fn default() -> Self {
+        Self {
+            files: Vec::new(),
+            max: DEFAULT_MAX_RECENT_FILES,
+            storage: None,
+            changed: false,
+        }
+    }
+}
+
+impl RecentFiles {
+    /// Marks the list as changed, and writes it to its file.
+    fn save(&mut self) {
+        self.changed = true;
+        if let Some(storage) = &self.storage
+            && let Err(error) = std::fs::write(storage, format_recent_files(&self.files))
+        {
+            warn!("Failed to save the recent files to {}: {error}", storage.display());
+        }
+        crate::shell::set_recent_files(&self.files);
+    }
+}
+
+/// Reads the list from `path` and keeps it there from now on. A missing file is an empty list.
+pub fn load_recent_files(path: impl Into<PathBuf>) -> io::Result<()> {
+    let path = path.into();
+    let files = match std::fs::read_to_string(&path) {
+        Ok(contents) => parse_recent_files(&contents),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(error) => return Err(error),
+    };
+    RECENT_FILES.with_borrow_mut(|recent_files| {
+        recent_files.files = files;
+        recent_files.files.truncate(recent_files.max);
+        recent_files.storage = Some(path);
+        recent_files.changed = true;
+        crate::shell::set_recent_files(&recent_files.files);
+    });
+    Ok(())
+}
+
+/// The recent files, most recent first.
+pub fn recent_files() -> Vec<PathBuf> {
+    RECENT_FILES.with_borrow(|recent_files| recent_files.files.clone())
+}
+
+/// Moves `path` to the top of the list, and gives it to the OS.
+pub fn add_recent_file(path: impl AsRef<Path>) {
+    let path = path.as_ref();
+    crate::shell::note_recent_file(path);
+    RECENT_FILES.with_borrow_mut(|recent_files| {
+        let max = recent_files.max;
+        push_recent_file(&mut recent_files.files, path.to_path_buf(), max);
+        recent_files.save();
+    });
+}
+
+/// Removes `path` from the list, like when it no longer exists. The OS keeps it in its own list.
+pub fn remove_recent_file(path: impl AsRef<Path>) {
+    RECENT_FILES.with_borrow_mut(|recent_files| {
+        let count = recent_files.files.len();
+        recent_files.files.retain(|file| file != path.as_ref());
+        if recent_files.files.len() != count {
+            recent_files.save();
+        }
+    });
+}
+
+/// Empties the list, and the recent documents the OS keeps for the app.
+pub fn clear_recent_files() {
+    crate::shell::clear_recent_files();
+    RECENT_FILES.with_borrow_mut(|recent_files| {
+        recent_files.files.clear();
+        recent_files.save();
+    });
+}
+
+/// How many files are kept, dropping the oldest ones.
+pub fn set_max_recent_files(max: usize) {
+    RECENT_FILES.with_borrow_mut(|recent_files| {
+        recent_files.max = max;
+        if recent_files.files.len() > max {
+            recent_files.files.truncate(max);
+            recent_files.save();
+        }
+    });
+}
+
+/// Returns the list if it changed since the last call. Called once per iteration of the event loop.
+pub(crate) fn poll_recent_files() -> Option<Vec<PathBuf>> {
+    RECENT_FILES
+        .with_borrow_mut(|recent_files| std::mem::take(&mut recent_files.changed).then(|| recent_files.files.clone()))
+}
+
+fn push_recent_file(files: &mut Vec<PathBuf>, path: PathBuf, max: usize) {
+    files.retain(|file| *file != path);
+    files.insert(0, path);
+    files.truncate(max);
+}
+
+/// Paths that aren't UTF-8, or have a line break, can't be written and are left out.
+fn format_recent_files(files: &[PathBuf]) -> String {
+    files
+        .iter()
+        .filter_map(|file| file.to_str())
+        .filter(|file| !file.contains(['\n', '\r']))
+        .map(|file| format!("{file}\n"))
+        .collect()
+}
+
+fn parse_recent_files(contents: &str) -> Vec<PathBuf> {
+    contents
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reopened_files_move_to_the_top() {
+        let mut files = Vec::new();
+        for path in ["a.txt", "b.txt", "c.txt", "a.txt"] {
+            push_recent_file(&mut files, PathBuf::from(path), 2);
+        }
+        assert_eq!(files, [PathBuf::from("a.txt"), PathBuf::from("c.txt")]);
+        assert_eq!(parse_recent_files(&format_recent_files(&files)), files);
+    }
+}
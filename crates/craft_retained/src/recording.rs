@@ -0,0 +1,410 @@
+//! Records the input of a session to a file and replays it.
+//!
+//! A recording is a file of JSON lines, one for each input event, with the time since the recording started and the
+//! window the event was sent to. The size and scale factor of each window are recorded before its first event, so a
+//! replay lays out the windows the same way. Attach a recording to a bug report to reproduce it, or replay it in a
+//! test.
+//!
+//! Enable the `recording` feature and set [`CraftOptions::input_recording`](crate::CraftOptions::input_recording).
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, LineWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use ui_events::keyboard::{Code, Key, KeyState, KeyboardEvent, Modifiers, NamedKey};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{DeviceId, ElementState, Ime, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent};
+use winit::keyboard::ModifiersState;
+
+/// Whether to record the input of the session or replay a recording.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InputRecording {
+    /// Write the input to a file, replacing it.
+    Record(PathBuf),
+    /// Send the input of a recording to the windows, and then take input from the user again.
+    Replay(PathBuf),
+}
+
+/// An input event of a recording.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// Seconds since the recording started.
+    pub time: f64,
+    /// The window in the order the windows were created.
+    pub window: usize,
+    pub input: RecordedInput,
+}
+
+/// The input of a window, in physical pixels.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum RecordedInput {
+    Resized { width: u32, height: u32 },
+    ScaleFactorChanged { scale_factor: f64 },
+    CursorMoved { x: f64, y: f64 },
+    CursorEntered,
+    CursorLeft,
+    MouseInput { button: RecordedButton, pressed: bool },
+    /// A scroll in lines, or in pixels if `lines` is false.
+    MouseWheel { lines: bool, x: f64, y: f64 },
+    ModifiersChanged { modifiers: u32 },
+    /// A key, with the key and code written the way [`Key`] and [`Code`] display them.
+    Keyboard {
+        key: String,
+        code: String,
+        pressed: bool,
+        repeat: bool,
+        modifiers: u32,
+    },
+    ImeEnabled,
+    ImePreedit { text: String, cursor: Option<(usize, usize)> },
+    ImeCommit { text: String },
+    ImeDisabled,
+    HoveredFile { path: PathBuf },
+    HoveredFileCancelled,
+    DroppedFile { path: PathBuf },
+    Focused { focused: bool },
+    CloseRequested,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecordedButton {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+    Other(u16),
+}
+
+impl From<MouseButton> for RecordedButton {
+    fn from(button: MouseButton) -> Self {
+        match button {
+            MouseButton::Left => RecordedButton::Left,
+            MouseButton::Right => RecordedButton::Right,
+            MouseButton::Middle => RecordedButton::Middle,
+            MouseButton::Back => RecordedButton::Back,
+            MouseButton::Forward => RecordedButton::Forward,
+            MouseButton::Other(other) => RecordedButton::Other(other),
+        }
+    }
+}
+
+impl From<RecordedButton> for MouseButton {
+    fn from(button: RecordedButton) -> Self {
+        match button {
+            RecordedButton::Left => MouseButton::Left,
+            RecordedButton::Right => MouseButton::Right,
+            RecordedButton::Middle => MouseButton::Middle,
+            RecordedButton::Back => MouseButton::Back,
+            RecordedButton::Forward => MouseButton::Forward,
+            RecordedButton::Other(other) => MouseButton::Other(other),
+        }
+    }
+}
+
+impl RecordedInput {
+    /// Returns the input of a winit event, or `None` if the event isn't input or is recorded some other way.
+    ///
+    /// Keyboard input is recorded after winit's key events are translated, with [`RecordedInput::from_keyboard_event`],
+    /// because winit's key events can't be created again.
+    pub fn from_window_event(event: &WindowEvent) -> Option<Self> {
+        let input = match event {
+            WindowEvent::Resized(size) => RecordedInput::Resized {
+                width: size.width,
+                height: size.height,
+            },
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => RecordedInput::ScaleFactorChanged {
+                scale_factor: *scale_factor,
+            },
+            WindowEvent::CursorMoved { position, .. } => RecordedInput::CursorMoved {
+                x: position.x,
+                y: position.y,
+            },
+            WindowEvent::CursorEntered { .. } => RecordedInput::CursorEntered,
+            WindowEvent::CursorLeft { .. } => RecordedInput::CursorLeft,
+            WindowEvent::MouseInput { state, button, .. } => RecordedInput::MouseInput {
+                button: (*button).into(),
+                pressed: state.is_pressed(),
+            },
+            WindowEvent::MouseWheel { delta, .. } => match delta {
+                MouseScrollDelta::LineDelta(x, y) => RecordedInput::MouseWheel {
+                    lines: true,
+                    x: *x as f64,
+                    y: *y as f64,
+                },
+                MouseScrollDelta::PixelDelta(position) => RecordedInput::MouseWheel {
+                    lines: false,
+                    x: position.x,
+                    y: position.y,
+                },
+            },
+            WindowEvent::ModifiersChanged(modifiers) => RecordedInput::ModifiersChanged {
+                modifiers: modifiers.state().bits(),
+            },
+            WindowEvent::Ime(ime) => match ime {
+                Ime::Enabled => RecordedInput::ImeEnabled,
+                Ime::Preedit(text, cursor) => RecordedInput::ImePreedit {
+                    text: text.clone(),
+                    cursor: *cursor,
+                },
+                Ime::Commit(text) => RecordedInput::ImeCommit { text: text.clone() },
+                Ime::Disabled => RecordedInput::ImeDisabled,
+            },
+            WindowEvent::HoveredFile(path) => RecordedInput::HoveredFile { path: path.clone() },
+            WindowEvent::HoveredFileCancelled => RecordedInput::HoveredFileCancelled,
+            WindowEvent::DroppedFile(path) => RecordedInput::DroppedFile { path: path.clone() },
+            WindowEvent::Focused(focused) => RecordedInput::Focused { focused: *focused },
+            WindowEvent::CloseRequested => RecordedInput::CloseRequested,
+            _ => return None,
+        };
+        Some(input)
+    }
+
+    pub fn from_keyboard_event(keyboard_event: &KeyboardEvent) -> Self {
+        RecordedInput::Keyboard {
+            key: keyboard_event.key.to_string(),
+            code: keyboard_event.code.to_string(),
+            pressed: keyboard_event.state.is_down(),
+            repeat: keyboard_event.repeat,
+            modifiers: keyboard_event.modifiers.bits(),
+        }
+    }
+
+    /// Returns the winit event to replay the input with.
+    ///
+    /// Returns `None` for keyboard input and scale factor changes, which winit's events can't be created for.
+    pub fn to_window_event(&self) -> Option<WindowEvent> {
+        let device_id = DeviceId::dummy();
+        let event = match self {
+            RecordedInput::Resized { width, height } => WindowEvent::Resized(PhysicalSize::new(*width, *height)),
+            RecordedInput::ScaleFactorChanged { .. } | RecordedInput::Keyboard { .. } => return None,
+            RecordedInput::CursorMoved { x, y } => WindowEvent::CursorMoved {
+                device_id,
+                position: PhysicalPosition::new(*x, *y),
+            },
+            RecordedInput::CursorEntered => WindowEvent::CursorEntered { device_id },
+            RecordedInput::CursorLeft => WindowEvent::CursorLeft { device_id },
+            RecordedInput::MouseInput { button, pressed } => WindowEvent::MouseInput {
+                device_id,
+                state: if *pressed {
+                    ElementState::Pressed
+                } else {
+                    ElementState::Released
+                },
+                button: (*button).into(),
+            },
+            RecordedInput::MouseWheel { lines, x, y } => WindowEvent::MouseWheel {
+                device_id,
+                delta: if *lines {
+                    MouseScrollDelta::LineDelta(*x as f32, *y as f32)
+                } else {
+                    MouseScrollDelta::PixelDelta(PhysicalPosition::new(*x, *y))
+                },
+                phase: TouchPhase::Moved,
+            },
+            RecordedInput::ModifiersChanged { modifiers } => {
+                WindowEvent::ModifiersChanged(ModifiersState::from_bits_truncate(*modifiers).into())
+            }
+            RecordedInput::ImeEnabled => WindowEvent::Ime(Ime::Enabled),
+            RecordedInput::ImePreedit { text, cursor } => WindowEvent::Ime(Ime::Preedit(text.clone(), *cursor)),
+            RecordedInput::ImeCommit { text } => WindowEvent::Ime(Ime::Commit(text.clone())),
+            RecordedInput::ImeDisabled => WindowEvent::Ime(Ime::Disabled),
+            RecordedInput::HoveredFile { path } => WindowEvent::HoveredFile(path.clone()),
+            RecordedInput::HoveredFileCancelled => WindowEvent::HoveredFileCancelled,
+            RecordedInput::DroppedFile { path } => WindowEvent::DroppedFile(path.clone()),
+            RecordedInput::Focused { focused } => WindowEvent::Focused(*focused),
+            RecordedInput::CloseRequested => WindowEvent::CloseRequested,
+        };
+        Some(event)
+    }
+
+    /// Returns the keyboard event of recorded keyboard input.
+    ///
+    /// Keys that this version of Craft doesn't know are replayed as unidentified keys.
+    pub fn to_keyboard_event(&self) -> Option<KeyboardEvent> {
+        let RecordedInput::Keyboard {
+            key,
+            code,
+            pressed,
+            repeat,
+            modifiers,
+        } = self
+        else {
+            return None;
+        };
+
+        Some(KeyboardEvent {
+            state: if *pressed { KeyState::Down } else { KeyState::Up },
+            key: Key::from_str(key).unwrap_or(Key::Named(NamedKey::Unidentified)),
+            code: Code::from_str(code).unwrap_or(Code::Unidentified),
+            modifiers: Modifiers::from_bits_truncate(*modifiers),
+            repeat: *repeat,
+            ..Default::default()
+        })
+    }
+}
+
+/// Writes the input of a session to a file as it happens.
+pub(crate) struct InputRecorder {
+    writer: LineWriter<File>,
+    start: Instant,
+    /// The windows whose size was recorded.
+    windows_seen: Vec<usize>,
+}
+
+impl InputRecorder {
+    pub(crate) fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            writer: LineWriter::new(File::create(path)?),
+            start: Instant::now(),
+            windows_seen: Vec::new(),
+        })
+    }
+
+    /// Returns true if the window's size hasn't been recorded yet, and remembers that it has now.
+    pub(crate) fn first_sight(&mut self, window: usize) -> bool {
+        if self.windows_seen.contains(&window) {
+            return false;
+        }
+        self.windows_seen.push(window);
+        true
+    }
+
+    pub(crate) fn record(&mut self, window: usize, input: RecordedInput) -> io::Result<()> {
+        let event = RecordedEvent {
+            time: self.start.elapsed().as_secs_f64(),
+            window,
+            input,
+        };
+        let line = serde_json::to_string(&event).map_err(io::Error::other)?;
+        writeln!(self.writer, "{line}")
+    }
+}
+
+/// Sends the events of a recording at the times they were recorded.
+pub(crate) struct InputReplayer {
+    events: VecDeque<RecordedEvent>,
+    /// When the replay started, which is when the app first asks for events.
+    start: Option<Instant>,
+}
+
+impl InputReplayer {
+    pub(crate) fn open(path: &Path) -> io::Result<Self> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    pub(crate) fn read(reader: impl BufRead) -> io::Result<Self> {
+        let mut events = VecDeque::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let event: RecordedEvent = serde_json::from_str(&line).map_err(|error| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {error}", index + 1))
+            })?;
+            events.push_back(event);
+        }
+        Ok(Self { events, start: None })
+    }
+
+    /// Returns the events that are due, the first time starting the clock.
+    pub(crate) fn due_events(&mut self) -> Vec<RecordedEvent> {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        self.take_due(start.elapsed())
+    }
+
+    fn take_due(&mut self, elapsed: Duration) -> Vec<RecordedEvent> {
+        let elapsed = elapsed.as_secs_f64();
+        let mut due = Vec::new();
+        while let Some(event) = self.events.front()
+            && event.time <= elapsed
+        {
+            due.extend(self.events.pop_front());
+        }
+        due
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_round_trip_through_json() {
+        let event = RecordedEvent {
+            time: 1.5,
+            window: 0,
+            input: RecordedInput::MouseInput {
+                button: RecordedButton::Other(7),
+                pressed: true,
+            },
+        };
+
+        let line = serde_json::to_string(&event).unwrap();
+        assert_eq!(serde_json::from_str::<RecordedEvent>(&line).unwrap(), event);
+    }
+
+    #[test]
+    fn window_events_convert_back() {
+        let inputs = [
+            RecordedInput::CursorMoved { x: 10.5, y: 20.0 },
+            RecordedInput::MouseWheel {
+                lines: true,
+                x: 0.0,
+                y: -3.0,
+            },
+            RecordedInput::ModifiersChanged {
+                modifiers: ModifiersState::SHIFT.bits(),
+            },
+            RecordedInput::ImePreedit {
+                text: "か".to_string(),
+                cursor: Some((0, 3)),
+            },
+        ];
+
+        for input in inputs {
+            let event = input.to_window_event().unwrap();
+            assert_eq!(RecordedInput::from_window_event(&event), Some(input));
+        }
+    }
+
+    #[test]
+    fn keyboard_events_convert_back() {
+        let keyboard_event = KeyboardEvent {
+            state: KeyState::Down,
+            key: Key::Named(NamedKey::Enter),
+            code: Code::Enter,
+            modifiers: Modifiers::CONTROL,
+            ..Default::default()
+        };
+
+        let input = RecordedInput::from_keyboard_event(&keyboard_event);
+        assert_eq!(input.to_keyboard_event(), Some(keyboard_event));
+        assert_eq!(input.to_window_event(), None);
+    }
+
+    #[test]
+    fn replays_events_when_they_are_due() {
+        let recording = "{\"time\":0.0,\"window\":0,\"input\":{\"type\":\"CursorEntered\"}}\n\n\
+                         {\"time\":0.5,\"window\":0,\"input\":{\"type\":\"CursorLeft\"}}\n";
+        let mut replayer = InputReplayer::read(recording.as_bytes()).unwrap();
+
+        assert_eq!(replayer.take_due(Duration::ZERO).len(), 1);
+        assert!(replayer.take_due(Duration::from_millis(100)).is_empty());
+        assert_eq!(replayer.take_due(Duration::from_secs(1))[0].input, RecordedInput::CursorLeft);
+        assert!(replayer.is_finished());
+
+        let error = InputReplayer::read("{}".as_bytes()).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
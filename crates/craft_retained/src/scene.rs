@@ -0,0 +1,107 @@
+//! Notices when nothing a window draws has changed, so the platform asking for a redraw, like when the window is
+//! uncovered, presents the last frame again instead of laying out and drawing every element.
+//!
+//! Every change that may affect a frame bumps a generation: elements marked dirty, style changes, events, redraw
+//! requests, work scheduled for later, and layout requests. A window draws again when the generation, its size, scale
+//! factor or active state changed since its last frame, or when that frame asked for an animation frame. See
+//! [`CraftOptions::skip_unchanged_frames`](crate::CraftOptions::skip_unchanged_frames).
+
+use std::cell::Cell;
+
+thread_local! {
+    static SCENE_GENERATION: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Records that something that may affect what the windows draw changed.
+pub(crate) fn scene_changed() {
+    SCENE_GENERATION.set(SCENE_GENERATION.get().wrapping_add(1));
+}
+
+pub(crate) fn scene_generation() -> u64 {
+    SCENE_GENERATION.get()
+}
+
+/// Everything a frame of a window was drawn from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct FrameKey {
+    pub(crate) generation: u64,
+    pub(crate) width: f32,
+    pub(crate) height: f32,
+    pub(crate) scale_factor: f64,
+    pub(crate) is_active: bool,
+}
+
+/// The key of the last frame of a window, to tell whether the next frame would be the same.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct FrameSkipper {
+    /// `None` before the first frame, and after a frame that animates.
+    last_frame: Option<FrameKey>,
+}
+
+impl FrameSkipper {
+    /// Whether a frame drawn from `key` would be the same as the last frame.
+    pub(crate) fn is_unchanged(&self, key: FrameKey) -> bool {
+        self.last_frame == Some(key)
+    }
+
+    /// Records a frame that was drawn. A frame that animates is never the same as the next one.
+    pub(crate) fn drawn(&mut self, key: FrameKey, animating: bool) {
+        self.last_frame = (!animating).then_some(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::{AsElement, Text};
+
+    fn key(generation: u64) -> FrameKey {
+        FrameKey {
+            generation,
+            width: 800.0,
+            height: 600.0,
+            scale_factor: 1.0,
+            is_active: true,
+        }
+    }
+
+    /// Counts the frames drawn for the redraws, each with its generation and whether the frame animates.
+    fn frames_drawn(redraws: &[(u64, bool)]) -> usize {
+        let mut skipper = FrameSkipper::default();
+        let mut drawn = 0;
+        for &(generation, animating) in redraws {
+            if !skipper.is_unchanged(key(generation)) {
+                skipper.drawn(key(generation), animating);
+                drawn += 1;
+            }
+        }
+        drawn
+    }
+
+    #[test]
+    fn unchanged_frames_are_skipped() {
+        assert_eq!(frames_drawn(&[(0, false), (0, false), (0, false)]), 1);
+        assert_eq!(frames_drawn(&[(0, false), (1, false), (1, false), (2, false)]), 3);
+    }
+
+    #[test]
+    fn animating_frames_are_always_drawn() {
+        assert_eq!(frames_drawn(&[(0, true), (0, true), (0, false), (0, false)]), 3);
+    }
+
+    #[test]
+    fn changes_outside_events_are_drawn() {
+        let mut skipper = FrameSkipper::default();
+        let text = Text::new("Waiting");
+        skipper.drawn(key(scene_generation()), false);
+
+        // Like a timer that changes what an element draws, and asks for it to be drawn.
+        text.borrow().request_window_redraw();
+        assert!(!skipper.is_unchanged(key(scene_generation())));
+        skipper.drawn(key(scene_generation()), false);
+
+        // Like a timer that uses a setter.
+        let _text = text.text("Done");
+        assert!(!skipper.is_unchanged(key(scene_generation())));
+    }
+}
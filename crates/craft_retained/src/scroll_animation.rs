@@ -0,0 +1,198 @@
+//! Animations driven by scrolling instead of time, like parallax headers and elements that fade in as they are
+//! scrolled into view.
+//!
+//! An animation links a scroll container to a target element, see
+//! [`Element::scroll_animation`](crate::elements::Element::scroll_animation). Before each frame is laid out, the scroll
+//! position of the container is turned into a progress from 0 to 1, and the effects of the animation are applied to
+//! the target at that progress.
+
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+use craft_renderer::effects::ColorFilter;
+
+use crate::elements::ElementInternals;
+use crate::{Color, auto, px};
+
+thread_local! {
+    static SCROLL_ANIMATIONS: RefCell<Vec<LinkedScrollAnimation>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Which part of the scrolling drives an animation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScrollRange {
+    /// From the top of the content to the bottom, e.g. for a parallax background or a reading progress bar.
+    Container,
+    /// Between two scroll offsets in logical pixels, e.g. to shrink a header over the first 200 pixels.
+    Offsets(f32, f32),
+    /// While the target scrolls into view from the bottom of the container, for reveal-on-scroll effects.
+    Entry,
+}
+
+/// What an animation changes on its target, from the value at progress 0 to the value at progress 1.
+#[derive(Clone)]
+pub enum ScrollEffect {
+    /// Moves the target by an offset in logical pixels, without moving the elements around it. This sets the top and
+    /// left inset of the target.
+    Translate { from: (f32, f32), to: (f32, f32) },
+    BackgroundColor { from: Color, to: Color },
+    /// Scales the brightness of the target, see [`ColorFilter::Brightness`]. This replaces its color filters.
+    Brightness { from: f32, to: f32 },
+    /// Calls a function with the target and the progress, for effects not covered here.
+    Custom(Rc<dyn Fn(&mut dyn ElementInternals, f32)>),
+}
+
+/// Maps the scroll position of a container to effects on a target element.
+#[derive(Clone)]
+pub struct ScrollAnimation {
+    pub range: ScrollRange,
+    pub effects: Vec<ScrollEffect>,
+}
+
+impl ScrollAnimation {
+    pub fn new(range: ScrollRange) -> Self {
+        Self {
+            range,
+            effects: Vec::new(),
+        }
+    }
+
+    pub fn effect(mut self, effect: ScrollEffect) -> Self {
+        self.effects.push(effect);
+        self
+    }
+}
+
+struct LinkedScrollAnimation {
+    container: Weak<RefCell<dyn ElementInternals>>,
+    target: Weak<RefCell<dyn ElementInternals>>,
+    animation: ScrollAnimation,
+    /// The progress the effects were last applied at.
+    progress: Option<f32>,
+    /// The offset applied by a translate effect, which is taken out of the layout of the target.
+    translation: (f32, f32),
+}
+
+pub(crate) fn add_scroll_animation(
+    container: Weak<RefCell<dyn ElementInternals>>,
+    target: Weak<RefCell<dyn ElementInternals>>,
+    animation: ScrollAnimation,
+) {
+    SCROLL_ANIMATIONS.with_borrow_mut(|animations| {
+        animations.push(LinkedScrollAnimation {
+            container,
+            target,
+            animation,
+            progress: None,
+            translation: (0.0, 0.0),
+        });
+    });
+}
+
+/// Applies the scroll animations whose progress changed, and drops the ones whose container or target is gone.
+///
+/// This runs before layout, so the effects show in the same frame as the scroll. The positions come from the last
+/// layout, which is fine as the position of the target in the scrolled content doesn't change with scrolling.
+pub(crate) fn update_scroll_animations() {
+    SCROLL_ANIMATIONS.with_borrow_mut(|animations| {
+        animations.retain_mut(|linked| {
+            let (Some(container), Some(target)) = (linked.container.upgrade(), linked.target.upgrade()) else {
+                return false;
+            };
+            let (Ok(container), Ok(mut target)) = (container.try_borrow(), target.try_borrow_mut()) else {
+                return true;
+            };
+
+            let container_data = container.element_data();
+            let viewport = container_data.layout.computed_box.padding_rectangle();
+            let target_box = target.element_data().layout.computed_box.border_rectangle();
+            let progress = scroll_progress(
+                linked.animation.range,
+                container_data.scroll().scroll_y(),
+                container_data.layout.max_scroll_y,
+                viewport.height,
+                target_box.y - linked.translation.1 - viewport.y,
+                target_box.height,
+            );
+            if linked.progress == Some(progress) {
+                return true;
+            }
+            linked.progress = Some(progress);
+
+            for effect in &linked.animation.effects {
+                match effect {
+                    ScrollEffect::Translate { from, to } => {
+                        let x = lerp(from.0, to.0, progress);
+                        let y = lerp(from.1, to.1, progress);
+                        target.set_inset(px(y), auto(), auto(), px(x));
+                        linked.translation = (x, y);
+                    }
+                    ScrollEffect::BackgroundColor { from, to } => {
+                        target.set_background_color(from.lerp_rect(*to, progress));
+                    }
+                    ScrollEffect::Brightness { from, to } => {
+                        target.set_color_filters(vec![ColorFilter::Brightness(lerp(*from, *to, progress))]);
+                    }
+                    ScrollEffect::Custom(apply) => apply(&mut *target, progress),
+                }
+            }
+            true
+        });
+    });
+}
+
+/// Returns how far through `range` the scrolling is, from 0 to 1.
+///
+/// `target_top` is the top of the target relative to the top of the content, and `viewport_height` the height of the
+/// part of the content that is shown.
+fn scroll_progress(
+    range: ScrollRange,
+    scroll_y: f32,
+    max_scroll_y: f32,
+    viewport_height: f32,
+    target_top: f32,
+    target_height: f32,
+) -> f32 {
+    let progress = match range {
+        ScrollRange::Container if max_scroll_y > 0.0 => scroll_y / max_scroll_y,
+        ScrollRange::Container => 0.0,
+        ScrollRange::Offsets(start, end) if end > start => (scroll_y - start) / (end - start),
+        ScrollRange::Offsets(_, end) => f32::from(scroll_y >= end),
+        ScrollRange::Entry => (scroll_y + viewport_height - target_top) / target_height.max(1.0),
+    };
+    progress.clamp(0.0, 1.0)
+}
+
+fn lerp(from: f32, to: f32, progress: f32) -> f32 {
+    from + (to - from) * progress
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn container_range_follows_the_scroll_position() {
+        assert_eq!(scroll_progress(ScrollRange::Container, 50.0, 200.0, 100.0, 0.0, 10.0), 0.25);
+        assert_eq!(scroll_progress(ScrollRange::Container, 0.0, 0.0, 100.0, 0.0, 10.0), 0.0);
+    }
+
+    #[test]
+    fn offsets_range_is_clamped() {
+        let range = ScrollRange::Offsets(100.0, 300.0);
+        assert_eq!(scroll_progress(range, 50.0, 1000.0, 100.0, 0.0, 10.0), 0.0);
+        assert_eq!(scroll_progress(range, 200.0, 1000.0, 100.0, 0.0, 10.0), 0.5);
+        assert_eq!(scroll_progress(range, 400.0, 1000.0, 100.0, 0.0, 10.0), 1.0);
+    }
+
+    #[test]
+    fn entry_range_follows_the_target_into_view() {
+        // A target 40 pixels tall, 500 pixels down content that shows 300 pixels at a time.
+        let entry = |scroll_y| scroll_progress(ScrollRange::Entry, scroll_y, 1000.0, 300.0, 500.0, 40.0);
+        assert_eq!(entry(100.0), 0.0);
+        assert_eq!(entry(200.0), 0.0);
+        assert_eq!(entry(220.0), 0.5);
+        assert_eq!(entry(240.0), 1.0);
+        assert_eq!(entry(600.0), 1.0);
+    }
+}
@@ -0,0 +1,173 @@
+//! The menu, progress and badge of the dock icon.
+//!
+//! The menu is given to AppKit by the application delegate of winit. Winit's delegate has no dock menu, so
+//! `applicationDockMenu:` is added to its class, along with the action of the menu items. The menu is built from the
+//! current jump list each time the dock asks for it.
+//!
+//! The dock has no progress bar of its own, so the progress is drawn over the app icon by a view of the dock tile.
+
+use std::path::Path;
+
+use objc2::rc::Retained;
+use objc2::runtime::{AnyClass, AnyObject, Imp, Sel};
+use objc2::{MainThreadMarker, ffi, sel};
+use objc2_app_kit::{NSApplication, NSDocumentController, NSImageView, NSMenu, NSMenuItem, NSProgressIndicator, NSProgressIndicatorStyle, NSView};
+use objc2_foundation::{NSPoint, NSRect, NSSize, NSString, NSURL};
+
+use super::{JumpList, JumpListActivation, ShellError, TaskbarProgress, activate, get_jump_list};
+
+/// The height of the progress bar over the dock icon, as a fraction of the icon's height.
+const PROGRESS_HEIGHT: f64 = 0.15;
+
+/// The dock can only be changed on the main thread.
+fn main_thread() -> Result<MainThreadMarker, ShellError> {
+    MainThreadMarker::new()
+        .ok_or_else(|| ShellError::Failed("The dock can only be changed on the main thread".to_string()))
+}
+
+pub(super) fn set_jump_list(_jump_list: &JumpList) -> Result<(), ShellError> {
+    let mtm = main_thread()?;
+    let application = NSApplication::sharedApplication(mtm);
+    let delegate = application
+        .delegate()
+        .ok_or_else(|| ShellError::Failed("The app has no delegate yet".to_string()))?;
+    let class = AsRef::<AnyObject>::as_ref(&*delegate).class();
+    if class.responds_to(sel!(applicationDockMenu:)) {
+        return Ok(());
+    }
+
+    let dock_menu: extern "C-unwind" fn(&AnyObject, Sel, &AnyObject) -> *mut NSMenu = application_dock_menu;
+    let item_selected: extern "C-unwind" fn(&AnyObject, Sel, &NSMenuItem) = dock_menu_item_selected;
+    let class = class as *const AnyClass as *mut AnyClass;
+    // SAFETY: The type encodings match the signatures of the functions.
+    let added = unsafe {
+        ffi::class_addMethod(
+            class,
+            sel!(applicationDockMenu:),
+            std::mem::transmute::<_, Imp>(dock_menu),
+            c"@@:@".as_ptr(),
+        )
+        .as_bool()
+            && ffi::class_addMethod(
+                class,
+                sel!(craftDockMenuItemSelected:),
+                std::mem::transmute::<_, Imp>(item_selected),
+                c"v@:@".as_ptr(),
+            )
+            .as_bool()
+    };
+    if !added {
+        return Err(ShellError::Failed(
+            "The dock menu couldn't be added to the app delegate".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The dock icon is shared by every window, so the last progress set is shown.
+pub(super) fn set_taskbar_progress(
+    _window: &winit::window::Window,
+    progress: TaskbarProgress,
+) -> Result<(), ShellError> {
+    let mtm = main_thread()?;
+    let application = NSApplication::sharedApplication(mtm);
+    let dock_tile = application.dockTile();
+
+    if progress == TaskbarProgress::None {
+        dock_tile.setContentView(None);
+    } else {
+        let size = dock_tile.size();
+        let icon = NSImageView::imageViewWithImage(&application.applicationIconImage(), mtm);
+        icon.setFrame(NSRect::new(NSPoint::new(0.0, 0.0), size));
+
+        let frame = NSRect::new(
+            NSPoint::new(0.0, 0.0),
+            NSSize::new(size.width, size.height * PROGRESS_HEIGHT),
+        );
+        let indicator = NSProgressIndicator::initWithFrame(NSProgressIndicator::alloc(mtm), frame);
+        indicator.setStyle(NSProgressIndicatorStyle::Bar);
+        indicator.setMinValue(0.0);
+        indicator.setMaxValue(1.0);
+        match progress.fraction() {
+            Some(fraction) => indicator.setDoubleValue(fraction),
+            None => indicator.setIndeterminate(true),
+        }
+        icon.addSubview(&indicator);
+        dock_tile.setContentView(Some(AsRef::<NSView>::as_ref(&*icon)));
+    }
+    dock_tile.display();
+    Ok(())
+}
+
+pub(super) fn set_badge_count(count: Option<u32>) -> Result<(), ShellError> {
+    let mtm = main_thread()?;
+    let label = count.map(|count| NSString::from_str(&count.to_string()));
+    NSApplication::sharedApplication(mtm)
+        .dockTile()
+        .setBadgeLabel(label.as_deref());
+    Ok(())
+}
+
+pub(super) fn note_recent_file(path: &Path) {
+    let Ok(mtm) = main_thread() else {
+        return;
+    };
+    let url = NSURL::fileURLWithPath(&NSString::from_str(&path.to_string_lossy()));
+    NSDocumentController::sharedDocumentController(mtm).noteNewRecentDocumentURL(&url);
+}
+
+pub(super) fn clear_recent_files() {
+    let Ok(mtm) = main_thread() else {
+        return;
+    };
+    NSDocumentController::sharedDocumentController(mtm).clearRecentDocuments(None);
+}
+
+extern "C-unwind" fn application_dock_menu(this: &AnyObject, _cmd: Sel, _application: &AnyObject) -> *mut NSMenu {
+    // AppKit asks for the dock menu on the main thread.
+    let mtm = MainThreadMarker::new().unwrap();
+    let jump_list = get_jump_list();
+    let menu = NSMenu::new(mtm);
+
+    // Tasks, then recent files after a separator, tagged with their index in the activations.
+    let titles = jump_list
+        .tasks
+        .iter()
+        .map(|task| task.title.clone())
+        .chain(jump_list.recent_files.iter().map(|path| {
+            path.file_name()
+                .unwrap_or(path.as_os_str())
+                .to_string_lossy()
+                .into_owned()
+        }));
+    for (index, title) in titles.enumerate() {
+        if index == jump_list.tasks.len() && index > 0 {
+            menu.addItem(&NSMenuItem::separatorItem(mtm));
+        }
+        // SAFETY: The action is a method of the target, which takes the menu item.
+        let item = unsafe {
+            let item = NSMenuItem::initWithTitle_action_keyEquivalent(
+                NSMenuItem::alloc(mtm),
+                &NSString::from_str(&title),
+                Some(sel!(craftDockMenuItemSelected:)),
+                &NSString::from_str(""),
+            );
+            item.setTarget(Some(this));
+            item
+        };
+        item.setTag(index as isize);
+        menu.addItem(&item);
+    }
+    Retained::autorelease_return(menu)
+}
+
+extern "C-unwind" fn dock_menu_item_selected(_this: &AnyObject, _cmd: Sel, item: &NSMenuItem) {
+    let activations: Vec<JumpListActivation> = get_jump_list().activations();
+    if let Some(activation) = usize::try_from(item.tag())
+        .ok()
+        .and_then(|index| activations.get(index))
+        .cloned()
+    {
+        activate(activation);
+    }
+}
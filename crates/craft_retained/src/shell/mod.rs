@@ -0,0 +1,327 @@
+//! Integration with the desktop shell: the jump list of the app's taskbar button on Windows, and the menu of its dock
+//! icon on macOS, along with progress and badges shown on them.
+//!
+//! A [`JumpList`] has recent files and tasks, and choosing one sends a [`EventKind::JumpListActivated`] event to every
+//! window. On macOS the dock menu is used while the app runs. On Windows, choosing an entry starts the app with an
+//! argument naming the entry, and the new instance gets the event once its windows are open.
+//!
+//! The shell is only integrated on Windows and macOS with the `shell` feature. Elsewhere, setting a jump list returns
+//! [`ShellError::Unsupported`].
+//!
+//! ```ignore
+//! shell::set_jump_list(
+//!     JumpList::new()
+//!         .task(JumpListTask::new("new-document", "New document"))
+//!         .recent_file("/home/ada/notes.txt"),
+//! )?;
+//!
+//! window.on_jump_list_activated(Rc::new(|_event, activation| match activation {
+//!     JumpListActivation::Task(id) if id == "new-document" => new_document(),
+//!     JumpListActivation::File(path) => open(path),
+//!     _ => {}
+//! }));
+//! ```
+//!
+//! Long-running operations show their progress with [`Window::set_taskbar_progress`], on the window's taskbar button
+//! on Windows and on the dock icon on macOS. [`set_badge_count`] shows a count on the dock icon on macOS, like unread
+//! messages, and [`Window::request_attention`] flashes the taskbar button or bounces the dock icon on every platform.
+//!
+//! [`EventKind::JumpListActivated`]: crate::events::EventKind::JumpListActivated
+//! [`Window::set_taskbar_progress`]: crate::elements::Window::set_taskbar_progress
+//! [`Window::request_attention`]: crate::elements::Window::request_attention
+
+#[cfg(all(feature = "shell", target_os = "macos"))]
+mod macos;
+#[cfg(all(feature = "shell", target_os = "windows"))]
+mod win32;
+
+use std::cell::RefCell;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[cfg(all(feature = "shell", target_os = "macos"))]
+use macos as system;
+#[cfg(all(feature = "shell", target_os = "windows"))]
+use win32 as system;
+
+#[cfg(not(all(feature = "shell", any(target_os = "windows", target_os = "macos"))))]
+mod system {
+    use std::path::Path;
+
+    use super::{JumpList, ShellError, TaskbarProgress};
+
+    pub(super) fn set_jump_list(_jump_list: &JumpList) -> Result<(), ShellError> {
+        Err(ShellError::Unsupported)
+    }
+
+    pub(super) fn set_taskbar_progress(
+        _window: &winit::window::Window,
+        _progress: TaskbarProgress,
+    ) -> Result<(), ShellError> {
+        Err(ShellError::Unsupported)
+    }
+
+    pub(super) fn set_badge_count(_count: Option<u32>) -> Result<(), ShellError> {
+        Err(ShellError::Unsupported)
+    }
+
+    pub(super) fn note_recent_file(_path: &Path) {}
+
+    pub(super) fn clear_recent_files() {}
+}
+
+/// The argument that starts the app for a task of its jump list, followed by the id of the task.
+const TASK_ARGUMENT: &str = "--craft-jump-list-task=";
+
+/// The argument that starts the app for a recent file of its jump list, followed by the path of the file.
+const FILE_ARGUMENT: &str = "--craft-jump-list-file=";
+
+thread_local! {
+    static SHELL: RefCell<ShellState> = RefCell::new(ShellState::default());
+}
+
+#[derive(Default)]
+struct ShellState {
+    jump_list: JumpList,
+    /// The entries chosen since the last iteration of the event loop.
+    activations: Vec<JumpListActivation>,
+    /// Whether the arguments the app was started with were read for an entry.
+    read_arguments: bool,
+}
+
+/// An action of the app in its jump list, like creating a new document.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JumpListTask {
+    /// Identifies the task in [`JumpListActivation::Task`].
+    pub id: String,
+    pub title: String,
+    /// Shown when the pointer rests on the task, on Windows.
+    pub description: String,
+}
+
+impl JumpListTask {
+    pub fn new(id: &str, title: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            title: title.to_string(),
+            description: String::new(),
+        }
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+}
+
+/// The entries of the jump list on Windows and the dock menu on macOS.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct JumpList {
+    /// The files the user worked on, most recent first.
+    pub recent_files: Vec<PathBuf>,
+    pub tasks: Vec<JumpListTask>,
+}
+
+impl JumpList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn recent_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.recent_files.push(path.into());
+        self
+    }
+
+    pub fn task(mut self, task: JumpListTask) -> Self {
+        self.tasks.push(task);
+        self
+    }
+
+    /// What each entry does, tasks first.
+    #[cfg(any(test, all(feature = "shell", target_os = "macos")))]
+    pub(crate) fn activations(&self) -> Vec<JumpListActivation> {
+        let tasks = self.tasks.iter().map(|task| JumpListActivation::Task(task.id.clone()));
+        let files = self.recent_files.iter().cloned().map(JumpListActivation::File);
+        tasks.chain(files).collect()
+    }
+}
+
+/// The entry of the jump list or dock menu the user chose.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JumpListActivation {
+    /// The id of a task.
+    Task(String),
+    File(PathBuf),
+}
+
+/// The progress of a long-running operation, shown on the window's taskbar button on Windows and the dock icon on
+/// macOS.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TaskbarProgress {
+    /// No progress is shown.
+    #[default]
+    None,
+    /// The operation runs, but how far it got isn't known.
+    Indeterminate,
+    /// How far the operation got, from 0 to 1.
+    Normal(f64),
+    /// A paused operation, shown in yellow on Windows.
+    Paused(f64),
+    /// A failed operation, shown in red on Windows.
+    Error(f64),
+}
+
+impl TaskbarProgress {
+    /// How far the operation got, from 0 to 1, if that is known.
+    #[cfg(any(
+        test,
+        all(feature = "shell", any(target_os = "windows", target_os = "macos"))
+    ))]
+    pub(crate) fn fraction(&self) -> Option<f64> {
+        match *self {
+            TaskbarProgress::None | TaskbarProgress::Indeterminate => None,
+            TaskbarProgress::Normal(fraction)
+            | TaskbarProgress::Paused(fraction)
+            | TaskbarProgress::Error(fraction) => Some(if fraction.is_nan() {
+                0.0
+            } else {
+                fraction.clamp(0.0, 1.0)
+            }),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShellError {
+    /// The shell isn't integrated on this platform, or without the `shell` feature.
+    Unsupported,
+    /// The shell refused the change.
+    Failed(String),
+}
+
+impl fmt::Display for ShellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShellError::Unsupported => write!(f, "The desktop shell isn't supported on this platform"),
+            ShellError::Failed(error) => write!(f, "The desktop shell failed: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ShellError {}
+
+/// Replaces the jump list on Windows, or the dock menu on macOS.
+pub fn set_jump_list(jump_list: JumpList) -> Result<(), ShellError> {
+    let result = system::set_jump_list(&jump_list);
+    SHELL.with_borrow_mut(|shell| shell.jump_list = jump_list);
+    result
+}
+
+pub fn get_jump_list() -> JumpList {
+    SHELL.with_borrow(|shell| shell.jump_list.clone())
+}
+
+/// Shows a progress on the taskbar button of a window, see [`Window::set_taskbar_progress`].
+///
+/// [`Window::set_taskbar_progress`]: crate::elements::Window::set_taskbar_progress
+pub(crate) fn set_taskbar_progress(
+    window: &winit::window::Window,
+    progress: TaskbarProgress,
+) -> Result<(), ShellError> {
+    system::set_taskbar_progress(window, progress)
+}
+
+/// Shows a count on the dock icon on macOS, like the number of unread messages. `None` or 0 removes it.
+pub fn set_badge_count(count: Option<u32>) -> Result<(), ShellError> {
+    system::set_badge_count(count.filter(|count| *count > 0))
+}
+
+/// Shows the files of [`crate::recent_files`] in the jump list.
+pub(crate) fn set_recent_files(files: &[PathBuf]) {
+    let jump_list = SHELL.with_borrow_mut(|shell| {
+        shell.jump_list.recent_files = files.to_vec();
+        shell.jump_list.clone()
+    });
+    // The app learns whether the jump list works from set_jump_list, the recent files only keep it up to date.
+    let _ = system::set_jump_list(&jump_list);
+}
+
+/// Adds a file to the recent documents the OS keeps for the app.
+pub(crate) fn note_recent_file(path: &Path) {
+    system::note_recent_file(path);
+}
+
+/// Empties the recent documents the OS keeps for the app.
+pub(crate) fn clear_recent_files() {
+    system::clear_recent_files();
+}
+
+/// Queues the entry the user chose, for the next iteration of the event loop.
+#[cfg(all(feature = "shell", target_os = "macos"))]
+pub(crate) fn activate(activation: JumpListActivation) {
+    SHELL.with_borrow_mut(|shell| shell.activations.push(activation));
+}
+
+/// Returns the entries chosen since the last call, starting with the one the app was started for. Called once per
+/// iteration of the event loop once the windows are open.
+pub(crate) fn poll_jump_list() -> Vec<JumpListActivation> {
+    SHELL.with_borrow_mut(|shell| {
+        if !shell.read_arguments {
+            shell.read_arguments = true;
+            let launched = std::env::args_os()
+                .skip(1)
+                .find_map(|argument| parse_activation(argument.to_str()?));
+            shell.activations.splice(0..0, launched);
+        }
+        std::mem::take(&mut shell.activations)
+    })
+}
+
+/// The command line argument that starts the app for an entry, quoted for Windows.
+#[cfg(any(test, all(feature = "shell", target_os = "windows")))]
+fn activation_argument(activation: &JumpListActivation) -> String {
+    match activation {
+        JumpListActivation::Task(id) => format!("\"{TASK_ARGUMENT}{id}\""),
+        JumpListActivation::File(path) => format!("\"{FILE_ARGUMENT}{}\"", path.display()),
+    }
+}
+
+/// The entry a command line argument starts the app for, if it is one.
+fn parse_activation(argument: &str) -> Option<JumpListActivation> {
+    if let Some(id) = argument.strip_prefix(TASK_ARGUMENT) {
+        Some(JumpListActivation::Task(id.to_string()))
+    } else {
+        argument
+            .strip_prefix(FILE_ARGUMENT)
+            .map(|path| JumpListActivation::File(PathBuf::from(path)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn activations_round_trip_through_arguments() {
+        let activations = JumpList::new()
+            .task(JumpListTask::new("new-document", "New document"))
+            .recent_file("C:\\Users\\Ada\\My notes.txt")
+            .activations();
+
+        for activation in activations {
+            let argument = activation_argument(&activation);
+            // Windows removes the quotes when it splits the command line.
+            let unquoted = argument.trim_matches('"');
+            assert_eq!(parse_activation(unquoted), Some(activation));
+        }
+        assert_eq!(parse_activation("--verbose"), None);
+    }
+
+    #[test]
+    fn progress_fractions_are_clamped() {
+        assert_eq!(TaskbarProgress::Normal(0.25).fraction(), Some(0.25));
+        assert_eq!(TaskbarProgress::Paused(1.5).fraction(), Some(1.0));
+        assert_eq!(TaskbarProgress::Error(f64::NAN).fraction(), Some(0.0));
+        assert_eq!(TaskbarProgress::Indeterminate.fraction(), None);
+    }
+}
@@ -0,0 +1,134 @@
+//! The jump list and progress of the taskbar button, set with the COM interfaces of the Windows shell.
+//!
+//! Every entry of the jump list is a shell link that starts the app with the argument of the entry, see
+//! [`activation_argument`](super::activation_argument).
+
+use std::ffi::c_void;
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+
+use windows::Win32::Foundation::HWND;
+use windows::Win32::Storage::EnhancedStorage::PKEY_Title;
+use windows::Win32::System::Com::{CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED, CoCreateInstance, CoInitializeEx};
+use windows::Win32::UI::Shell::Common::{IObjectArray, IObjectCollection};
+use windows::Win32::UI::Shell::PropertiesSystem::IPropertyStore;
+use windows::Win32::UI::Shell::{DestinationList, EnumerableObjectCollection, ICustomDestinationList, IShellLinkW, ITaskbarList3, SHARD_PATHW, SHAddToRecentDocs, ShellLink, TBPF_ERROR, TBPF_INDETERMINATE, TBPF_NOPROGRESS, TBPF_NORMAL, TBPF_PAUSED, TaskbarList};
+use windows::core::{HSTRING, Interface, PROPVARIANT};
+use winit::raw_window_handle::{HasWindowHandle, RawWindowHandle};
+
+use super::{JumpList, JumpListActivation, ShellError, TaskbarProgress, activation_argument};
+
+/// The title of the category of recent files.
+const RECENT_CATEGORY: &str = "Recent";
+
+/// The steps of the progress bar on the taskbar button.
+const PROGRESS_STEPS: u64 = 1000;
+
+pub(super) fn set_jump_list(jump_list: &JumpList) -> Result<(), ShellError> {
+    let executable = std::env::current_exe().map_err(|error| ShellError::Failed(error.to_string()))?;
+    unsafe { commit_jump_list(jump_list, &executable) }.map_err(|error| ShellError::Failed(error.message()))
+}
+
+unsafe fn commit_jump_list(jump_list: &JumpList, executable: &Path) -> windows::core::Result<()> {
+    unsafe {
+        // Fails harmlessly if COM was already started on this thread.
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+
+        let list: ICustomDestinationList = CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+        let mut max_slots = 0;
+        // The entries the user removed from the last list. Recent files are written again as they are used, so they
+        // aren't kept out.
+        let _removed: IObjectArray = list.BeginList(&mut max_slots)?;
+
+        if !jump_list.recent_files.is_empty() {
+            let files: IObjectCollection = CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+            for path in &jump_list.recent_files {
+                let title = path.file_name().unwrap_or(path.as_os_str()).to_string_lossy();
+                let activation = JumpListActivation::File(path.clone());
+                let link = shell_link(executable, &activation, &title, &path.to_string_lossy())?;
+                files.AddObject(&link)?;
+            }
+            list.AppendCategory(&HSTRING::from(RECENT_CATEGORY), &files.cast::<IObjectArray>()?)?;
+        }
+
+        if !jump_list.tasks.is_empty() {
+            let tasks: IObjectCollection = CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+            for task in &jump_list.tasks {
+                let activation = JumpListActivation::Task(task.id.clone());
+                tasks.AddObject(&shell_link(executable, &activation, &task.title, &task.description)?)?;
+            }
+            list.AddUserTasks(&tasks.cast::<IObjectArray>()?)?;
+        }
+
+        list.CommitList()
+    }
+}
+
+pub(super) fn set_taskbar_progress(
+    window: &winit::window::Window,
+    progress: TaskbarProgress,
+) -> Result<(), ShellError> {
+    let hwnd = match window.window_handle().map(|handle| handle.as_raw()) {
+        Ok(RawWindowHandle::Win32(handle)) => HWND(handle.hwnd.get() as *mut c_void),
+        _ => return Err(ShellError::Failed("The window has no Win32 handle".to_string())),
+    };
+    unsafe { update_taskbar_progress(hwnd, progress) }.map_err(|error| ShellError::Failed(error.message()))
+}
+
+/// The taskbar button has no badge, only an overlay icon.
+pub(super) fn set_badge_count(_count: Option<u32>) -> Result<(), ShellError> {
+    Err(ShellError::Unsupported)
+}
+
+unsafe fn update_taskbar_progress(hwnd: HWND, progress: TaskbarProgress) -> windows::core::Result<()> {
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+
+        let taskbar: ITaskbarList3 = CoCreateInstance(&TaskbarList, None, CLSCTX_INPROC_SERVER)?;
+        taskbar.HrInit()?;
+        // Setting the value shows a normal progress, so the state is set after it.
+        if let Some(fraction) = progress.fraction() {
+            let completed = (fraction * PROGRESS_STEPS as f64).round() as u64;
+            taskbar.SetProgressValue(hwnd, completed, PROGRESS_STEPS)?;
+        }
+        let state = match progress {
+            TaskbarProgress::None => TBPF_NOPROGRESS,
+            TaskbarProgress::Indeterminate => TBPF_INDETERMINATE,
+            TaskbarProgress::Normal(_) => TBPF_NORMAL,
+            TaskbarProgress::Paused(_) => TBPF_PAUSED,
+            TaskbarProgress::Error(_) => TBPF_ERROR,
+        };
+        taskbar.SetProgressState(hwnd, state)
+    }
+}
+
+pub(super) fn note_recent_file(path: &Path) {
+    let path: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    unsafe { SHAddToRecentDocs(SHARD_PATHW.0 as u32, Some(path.as_ptr().cast())) };
+}
+
+pub(super) fn clear_recent_files() {
+    // No path clears the recent documents of the app.
+    unsafe { SHAddToRecentDocs(SHARD_PATHW.0 as u32, None) };
+}
+
+/// A link that starts the app for an entry.
+unsafe fn shell_link(
+    executable: &Path,
+    activation: &JumpListActivation,
+    title: &str,
+    description: &str,
+) -> windows::core::Result<IShellLinkW> {
+    unsafe {
+        let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+        link.SetPath(&HSTRING::from(executable.as_os_str()))?;
+        link.SetArguments(&HSTRING::from(activation_argument(activation)))?;
+        link.SetDescription(&HSTRING::from(description))?;
+
+        // The jump list shows the title property of a link.
+        let properties: IPropertyStore = link.cast()?;
+        properties.SetValue(&PKEY_Title, &PROPVARIANT::from(title))?;
+        properties.Commit()?;
+        Ok(link)
+    }
+}
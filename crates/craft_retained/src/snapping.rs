@@ -0,0 +1,241 @@
+//! Snapping for elements the app moves by dragging, like floating panels or the shapes of a canvas: to a grid, and
+//! to the edges and centers of the other elements, with guide lines showing what the element lines up with.
+//!
+//! The app moves the element where the pointer would put it, snapped, and shows the guides of the snap in the
+//! window until the drag ends:
+//!
+//! ```ignore
+//! let snapping = Snapping::new().grid(8.0);
+//! // While dragging, with `proposed` where the pointer would put the panel:
+//! let snap = snapping.snap_element(&panel, proposed);
+//! // Move the panel to `snap.rectangle`, e.g. with `inset` from the box of its parent.
+//! window.show_snap_guides(&snap.guides);
+//! // When the drag ends:
+//! window.show_snap_guides(&[]);
+//! ```
+//!
+//! Rectangles are in logical pixels, in the window, like the boxes of elements after layout.
+
+use std::rc::Rc;
+
+use craft_primitives::geometry::Rectangle;
+use craft_renderer::renderer::Renderer;
+use peniko::Color;
+
+use crate::elements::AsElement;
+use crate::spatial::visible_rectangle;
+
+/// How far apart, in logical pixels, two edges can be and still count as lined up when drawing the guides.
+const GUIDE_TOLERANCE: f32 = 0.5;
+
+/// The color the guides are drawn with.
+const GUIDE_COLOR: Color = Color::from_rgb8(236, 72, 153);
+
+/// How a dragged element snaps. See the [module docs](self).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Snapping {
+    grid: Option<f32>,
+    align_edges: bool,
+    threshold: f32,
+}
+
+impl Default for Snapping {
+    fn default() -> Self {
+        Self {
+            grid: None,
+            align_edges: true,
+            threshold: 6.0,
+        }
+    }
+}
+
+/// A line showing that the edges or centers of elements line up, from [`Snapping::snap`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SnapGuide {
+    /// A line at `x`, across the elements that line up on it.
+    Vertical { x: f32, top: f32, bottom: f32 },
+    /// A line at `y`, across the elements that line up on it.
+    Horizontal { y: f32, left: f32, right: f32 },
+}
+
+/// Where a dragged element snapped to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snap {
+    /// The rectangle moved to where it snapped, with the same size.
+    pub rectangle: Rectangle,
+    /// The lines to show while the element stays there.
+    pub guides: Vec<SnapGuide>,
+}
+
+impl Snapping {
+    /// Snaps to the edges and centers of other elements, without a grid.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snaps the top left corner to multiples of `size` when no edge lines up, or `None` for no grid.
+    pub fn grid(mut self, size: impl Into<Option<f32>>) -> Self {
+        self.grid = size.into().filter(|size| *size > 0.0);
+        self
+    }
+
+    /// Whether the edges and centers snap to the edges and centers of the other elements.
+    pub fn align_edges(mut self, align_edges: bool) -> Self {
+        self.align_edges = align_edges;
+        self
+    }
+
+    /// How close, in logical pixels, an edge must come to another one to snap to it.
+    pub fn threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Snaps `moving` to `others` and the grid. Lining up with an edge or center wins over the grid, on each axis.
+    pub fn snap(&self, moving: Rectangle, others: &[Rectangle]) -> Snap {
+        let others = if self.align_edges { others } else { &[] };
+
+        let x = nearest_offset(horizontal_lines(&moving), others.iter().map(horizontal_lines), self.threshold)
+            .or_else(|| self.grid.map(|grid| (moving.x / grid).round() * grid - moving.x))
+            .unwrap_or(0.0);
+        let y = nearest_offset(vertical_lines(&moving), others.iter().map(vertical_lines), self.threshold)
+            .or_else(|| self.grid.map(|grid| (moving.y / grid).round() * grid - moving.y))
+            .unwrap_or(0.0);
+
+        let rectangle = Rectangle::new(moving.x + x, moving.y + y, moving.width, moving.height);
+        Snap {
+            rectangle,
+            guides: guides(&rectangle, others),
+        }
+    }
+
+    /// Snaps `proposed`, where the app would move `element`, to the other children of its parent as they were last
+    /// drawn.
+    pub fn snap_element(&self, element: &impl AsElement, proposed: Rectangle) -> Snap {
+        let element = element.as_element_rc();
+        let parent = element.borrow().element_data().parent.as_ref().and_then(|parent| parent.upgrade());
+        let siblings: Vec<Rectangle> = parent
+            .map(|parent| {
+                parent
+                    .borrow()
+                    .children()
+                    .iter()
+                    .filter(|child| !Rc::ptr_eq(child, &element))
+                    .filter_map(|child| visible_rectangle(&*child.borrow()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.snap(proposed, &siblings)
+    }
+}
+
+/// The left edge, center and right edge of a rectangle.
+fn horizontal_lines(rectangle: &Rectangle) -> [f32; 3] {
+    [rectangle.left(), rectangle.x + rectangle.width / 2.0, rectangle.right()]
+}
+
+/// The top edge, middle and bottom edge of a rectangle.
+fn vertical_lines(rectangle: &Rectangle) -> [f32; 3] {
+    [rectangle.top(), rectangle.y + rectangle.height / 2.0, rectangle.bottom()]
+}
+
+/// The smallest offset within `threshold` that puts a line of `moving` on a line of one of `others`.
+fn nearest_offset(moving: [f32; 3], others: impl Iterator<Item = [f32; 3]>, threshold: f32) -> Option<f32> {
+    let mut nearest: Option<f32> = None;
+    for other in others {
+        for (line, target) in moving.iter().flat_map(|line| other.iter().map(move |target| (*line, *target))) {
+            let offset = target - line;
+            if offset.abs() <= threshold && nearest.is_none_or(|nearest| offset.abs() < nearest.abs()) {
+                nearest = Some(offset);
+            }
+        }
+    }
+    nearest
+}
+
+/// The guides across `rectangle` and the ones of `others` that line up with it, one per line.
+fn guides(rectangle: &Rectangle, others: &[Rectangle]) -> Vec<SnapGuide> {
+    let mut guides: Vec<SnapGuide> = Vec::new();
+    for other in others {
+        for x in horizontal_lines(rectangle) {
+            if !horizontal_lines(other).iter().any(|line| (line - x).abs() <= GUIDE_TOLERANCE) {
+                continue;
+            }
+            let (top, bottom) = (rectangle.top().min(other.top()), rectangle.bottom().max(other.bottom()));
+            match guides.iter_mut().find(|guide| matches!(guide, SnapGuide::Vertical { x: at, .. } if *at == x)) {
+                Some(SnapGuide::Vertical { top: t, bottom: b, .. }) => (*t, *b) = (t.min(top), b.max(bottom)),
+                _ => guides.push(SnapGuide::Vertical { x, top, bottom }),
+            }
+        }
+        for y in vertical_lines(rectangle) {
+            if !vertical_lines(other).iter().any(|line| (line - y).abs() <= GUIDE_TOLERANCE) {
+                continue;
+            }
+            let (left, right) = (rectangle.left().min(other.left()), rectangle.right().max(other.right()));
+            match guides.iter_mut().find(|guide| matches!(guide, SnapGuide::Horizontal { y: at, .. } if *at == y)) {
+                Some(SnapGuide::Horizontal { left: l, right: r, .. }) => (*l, *r) = (l.min(left), r.max(right)),
+                _ => guides.push(SnapGuide::Horizontal { y, left, right }),
+            }
+        }
+    }
+    guides
+}
+
+/// Draws the guides above everything else, one physical pixel wide.
+pub(crate) fn draw_snap_guides(renderer: &mut dyn Renderer, guides: &[SnapGuide], scale_factor: f64) {
+    if guides.is_empty() {
+        return;
+    }
+    let width = 1.0;
+    renderer.start_overlay();
+    for guide in guides {
+        let line = match *guide {
+            SnapGuide::Vertical { x, top, bottom } => Rectangle::new(x, top, 0.0, bottom - top).scale(scale_factor),
+            SnapGuide::Horizontal { y, left, right } => Rectangle::new(left, y, right - left, 0.0).scale(scale_factor),
+        };
+        let line = Rectangle::new(line.x - width / 2.0, line.y - width / 2.0, line.width + width, line.height + width);
+        renderer.draw_rect(line, GUIDE_COLOR);
+    }
+    renderer.end_overlay();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edges_snap_before_the_grid_and_show_a_guide() {
+        let snapping = Snapping::new().grid(10.0);
+        let others = [Rectangle::new(100.0, 0.0, 50.0, 20.0)];
+
+        // The left edge is 3px from the left edge of the other rectangle, and nothing lines up vertically.
+        let snap = snapping.snap(Rectangle::new(103.0, 44.0, 30.0, 10.0), &others);
+        assert_eq!(snap.rectangle, Rectangle::new(100.0, 40.0, 30.0, 10.0));
+        assert_eq!(
+            snap.guides,
+            vec![SnapGuide::Vertical {
+                x: 100.0,
+                top: 0.0,
+                bottom: 50.0
+            }]
+        );
+
+        // Too far from any edge, so only the grid applies.
+        let snap = snapping.snap(Rectangle::new(16.0, 44.0, 30.0, 10.0), &others);
+        assert_eq!(snap.rectangle, Rectangle::new(20.0, 40.0, 30.0, 10.0));
+        assert!(snap.guides.is_empty());
+    }
+
+    #[test]
+    fn centers_line_up_with_edges() {
+        let others = [Rectangle::new(0.0, 0.0, 100.0, 100.0)];
+        // The center of the moving rectangle, at 98, snaps to the right edge of the other one.
+        let snap = Snapping::new().snap(Rectangle::new(88.0, 200.0, 20.0, 20.0), &others);
+        assert_eq!(snap.rectangle.x, 90.0);
+        assert!(snap.guides.contains(&SnapGuide::Vertical {
+            x: 100.0,
+            top: 0.0,
+            bottom: 220.0
+        }));
+    }
+}
@@ -0,0 +1,106 @@
+//! Queries for the elements a window drew at a point or in an area, for selection marquees, snapping and custom
+//! picking. See [`Window::elements_at`](crate::elements::Window::elements_at).
+//!
+//! The queries look at the same elements as hit testing: the ones drawn in the last frame of the window, from the
+//! topmost down, with their boxes after transforms and clipping, in logical pixels.
+
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+use craft_primitives::geometry::{Point, Rectangle};
+use craft_renderer::TargetItem;
+use craft_renderer::renderer::Renderer;
+
+use crate::app::ELEMENTS;
+use crate::elements::{DynElement, ElementInternals};
+
+/// The elements drawn in the last frame that can be hit, from the topmost down.
+fn drawn_elements(renderer: &dyn Renderer) -> Vec<Rc<RefCell<dyn ElementInternals>>> {
+    let mut targets: Vec<&TargetItem> = renderer.render_list().targets.iter().collect();
+    // Overlays are above the elements drawn before them, as when hit testing.
+    targets.sort_by_key(|target| target.overlay_depth);
+    ELEMENTS.with_borrow(|elements| {
+        targets
+            .iter()
+            .rev()
+            .filter_map(|target| elements.get(target.custom_id).and_then(Weak::upgrade))
+            .collect()
+    })
+}
+
+/// The part of the border box of an element that is drawn, after transforms and clipping.
+pub(crate) fn visible_rectangle(element: &dyn ElementInternals) -> Option<Rectangle> {
+    let layout = &element.element_data().layout;
+    layout.clip_bounds?.intersection(&layout.computed_box_transformed.border_rectangle())
+}
+
+/// The distance from `point` to the closest point of `rectangle`, 0 inside it.
+fn distance_to(rectangle: &Rectangle, point: Point) -> f64 {
+    let dx = (rectangle.left() as f64 - point.x).max(point.x - rectangle.right() as f64).max(0.0);
+    let dy = (rectangle.top() as f64 - point.y).max(point.y - rectangle.bottom() as f64).max(0.0);
+    dx.hypot(dy)
+}
+
+/// The index of the rectangle closest to `point` within `max_distance`, the first one of those as close.
+fn nearest_index(rectangles: &[Rectangle], point: Point, max_distance: f64) -> Option<usize> {
+    let mut nearest: Option<(usize, f64)> = None;
+    for (index, rectangle) in rectangles.iter().enumerate() {
+        let distance = distance_to(rectangle, point);
+        if distance <= max_distance && nearest.is_none_or(|(_, nearest)| distance < nearest) {
+            nearest = Some((index, distance));
+        }
+    }
+    nearest.map(|(index, _)| index)
+}
+
+/// The drawn elements with their visible rectangles, from the topmost down. Elements that are borrowed, like one
+/// handling the current event, are skipped.
+fn visible_elements(renderer: &dyn Renderer) -> Vec<(Rc<RefCell<dyn ElementInternals>>, Rectangle)> {
+    drawn_elements(renderer)
+        .into_iter()
+        .filter_map(|element| {
+            let rectangle = visible_rectangle(&*element.try_borrow().ok()?)?;
+            Some((element, rectangle))
+        })
+        .collect()
+}
+
+pub(crate) fn elements_at(renderer: &dyn Renderer, point: Point) -> Vec<DynElement> {
+    drawn_elements(renderer)
+        .into_iter()
+        .filter(|element| element.try_borrow().is_ok_and(|element| element.in_bounds(point)))
+        .map(DynElement::new)
+        .collect()
+}
+
+pub(crate) fn elements_in_rect(renderer: &dyn Renderer, rectangle: Rectangle) -> Vec<DynElement> {
+    visible_elements(renderer)
+        .into_iter()
+        .filter(|(_, visible)| visible.intersects(&rectangle))
+        .map(|(element, _)| DynElement::new(element))
+        .collect()
+}
+
+pub(crate) fn nearest_element(renderer: &dyn Renderer, point: Point, max_distance: f64) -> Option<DynElement> {
+    let (elements, rectangles): (Vec<_>, Vec<_>) = visible_elements(renderer).into_iter().unzip();
+    let index = nearest_index(&rectangles, point, max_distance)?;
+    Some(DynElement::new(elements[index].clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_nearest_rectangle_is_the_topmost_of_the_closest() {
+        let rectangles = [
+            Rectangle::new(100.0, 0.0, 10.0, 10.0),
+            Rectangle::new(20.0, 0.0, 10.0, 10.0),
+            Rectangle::new(0.0, 20.0, 10.0, 10.0),
+        ];
+        // 10px from the second and third rectangles, the second being above the third.
+        assert_eq!(nearest_index(&rectangles, Point::new(10.0, 10.0), 50.0), Some(1));
+        assert_eq!(nearest_index(&rectangles, Point::new(105.0, 5.0), 0.0), Some(0));
+        assert_eq!(nearest_index(&rectangles, Point::new(60.0, 60.0), 5.0), None);
+    }
+}
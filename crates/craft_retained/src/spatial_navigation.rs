@@ -0,0 +1,213 @@
+//! Moves the focus with the arrow keys to the nearest focusable element in that direction, for TV, kiosk and gamepad
+//! interfaces where the tab order isn't enough.
+//!
+//! Turned on with [`CraftOptions::spatial_navigation`](crate::CraftOptions::spatial_navigation). An arrow key pressed
+//! without modifiers then moves the focus, unless a handler of the focused element prevented the default, or the
+//! focused element uses the arrow keys itself, like a slider or a text input. See
+//! [`ElementInternals::is_focusable`] and [`ElementInternals::handles_arrow_keys`]. Other input, like the d-pad of a
+//! gamepad, can move the focus with [`move_focus`].
+//!
+//! The elements are compared by their border boxes after the last layout, in logical pixels.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use craft_primitives::geometry::Rectangle;
+use ui_events::keyboard::{Key, KeyState, KeyboardEvent, NamedKey};
+
+use crate::app::FOCUS;
+use crate::elements::{ElementInternals, Window, scrollable};
+
+thread_local! {
+    /// See [`CraftOptions::spatial_navigation`](crate::CraftOptions::spatial_navigation).
+    static SPATIAL_NAVIGATION: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Where to move the focus to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NavigationDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Whether the arrow keys move the focus. See the [module docs](self).
+pub fn is_spatial_navigation() -> bool {
+    SPATIAL_NAVIGATION.get()
+}
+
+/// Changes [`CraftOptions::spatial_navigation`](crate::CraftOptions::spatial_navigation) while the app runs.
+pub fn set_spatial_navigation(spatial_navigation: bool) {
+    SPATIAL_NAVIGATION.set(spatial_navigation);
+}
+
+/// Moves the focus to the nearest focusable element of `window` in `direction`, or to the first focusable element
+/// if none has the focus. Returns false if there was none to move to.
+pub fn move_focus(window: &Window, direction: NavigationDirection) -> bool {
+    let root: Rc<RefCell<dyn ElementInternals>> = window.inner.clone();
+    move_focus_in(&root, direction)
+}
+
+/// Moves the focus for an arrow key that reached no handler preventing the default.
+pub(crate) fn on_keyboard_input(root: &Rc<RefCell<dyn ElementInternals>>, keyboard_event: &KeyboardEvent) {
+    if !is_spatial_navigation() || keyboard_event.state != KeyState::Down || !keyboard_event.modifiers.is_empty() {
+        return;
+    }
+    let direction = match keyboard_event.key {
+        Key::Named(NamedKey::ArrowUp) => NavigationDirection::Up,
+        Key::Named(NamedKey::ArrowDown) => NavigationDirection::Down,
+        Key::Named(NamedKey::ArrowLeft) => NavigationDirection::Left,
+        Key::Named(NamedKey::ArrowRight) => NavigationDirection::Right,
+        _ => return,
+    };
+    let focused = FOCUS.with_borrow(|focus| focus.as_ref().and_then(|focus| focus.upgrade()));
+    if focused.is_some_and(|focused| focused.borrow().handles_arrow_keys()) {
+        return;
+    }
+    move_focus_in(root, direction);
+}
+
+fn move_focus_in(root: &Rc<RefCell<dyn ElementInternals>>, direction: NavigationDirection) -> bool {
+    let mut candidates = Vec::new();
+    collect_focusable(root, &mut candidates);
+    let focused = FOCUS.with_borrow(|focus| focus.as_ref().and_then(|focus| focus.upgrade()));
+
+    let current = focused.and_then(|focused| candidates.iter().position(|(element, _)| Rc::ptr_eq(element, &focused)));
+    let next = match current {
+        Some(current) => {
+            let rectangles: Vec<Rectangle> = candidates.iter().map(|(_, rectangle)| *rectangle).collect();
+            next_in_direction(&rectangles, current, direction)
+        }
+        None => (!candidates.is_empty()).then_some(0),
+    };
+    let Some(next) = next else {
+        return false;
+    };
+
+    let (element, rectangle) = &candidates[next];
+    element.borrow_mut().focus();
+    reveal(element, *rectangle);
+    true
+}
+
+/// The focusable elements below `element`, in tree order, with their border boxes. Hidden elements are skipped with
+/// their children.
+fn collect_focusable(
+    element: &Rc<RefCell<dyn ElementInternals>>,
+    candidates: &mut Vec<(Rc<RefCell<dyn ElementInternals>>, Rectangle)>,
+) {
+    let Ok(element_ref) = element.try_borrow() else {
+        return;
+    };
+    if !element_ref.is_visible() {
+        return;
+    }
+    if element_ref.is_focusable() {
+        let rectangle = element_ref.element_data().layout.computed_box_transformed.border_rectangle();
+        candidates.push((element.clone(), rectangle));
+    }
+    for child in element_ref.children() {
+        collect_focusable(child, candidates);
+    }
+}
+
+/// Scrolls the nearest scrollable ancestor of the newly focused element until the element is in view.
+fn reveal(element: &Rc<RefCell<dyn ElementInternals>>, rectangle: Rectangle) {
+    let mut parent = element.borrow().element_data().parent.as_ref().and_then(|parent| parent.upgrade());
+    while let Some(ancestor) = parent {
+        let Ok(mut ancestor_ref) = ancestor.try_borrow_mut() else {
+            return;
+        };
+        if ancestor_ref.element_data().is_scrollable() {
+            let viewport = ancestor_ref.element_data().layout.computed_box_transformed.padding_rectangle();
+            let delta = if rectangle.top() < viewport.top() {
+                rectangle.top() - viewport.top()
+            } else if rectangle.bottom() > viewport.bottom() {
+                (rectangle.bottom() - viewport.bottom()).min(rectangle.top() - viewport.top())
+            } else {
+                return;
+            };
+            scrollable::scroll_by(ancestor_ref.element_data_mut(), delta);
+            return;
+        }
+        parent = ancestor_ref.element_data().parent.as_ref().and_then(|parent| parent.upgrade());
+    }
+}
+
+/// The index of the rectangle nearest to the one at `current` in `direction`, the first one of those as near.
+///
+/// Rectangles count as in the direction when their center and their far edge are past the ones of the current one.
+/// They are ranked by the gap to them along the direction, and twice the distance across it, so that the one straight
+/// ahead wins over a closer one to the side.
+fn next_in_direction(rectangles: &[Rectangle], current: usize, direction: NavigationDirection) -> Option<usize> {
+    let from = rectangles[current];
+    let center = |rectangle: &Rectangle| (rectangle.x + rectangle.width / 2.0, rectangle.y + rectangle.height / 2.0);
+    let (from_x, from_y) = center(&from);
+
+    let mut nearest: Option<(usize, f32)> = None;
+    for (index, rectangle) in rectangles.iter().enumerate() {
+        let (x, y) = center(rectangle);
+        let across_columns = range_gap(from.left(), from.right(), rectangle.left(), rectangle.right());
+        let across_rows = range_gap(from.top(), from.bottom(), rectangle.top(), rectangle.bottom());
+        let (is_ahead, along, across) = match direction {
+            NavigationDirection::Up => (
+                y < from_y && rectangle.top() < from.top(),
+                from.top() - rectangle.bottom(),
+                across_columns,
+            ),
+            NavigationDirection::Down => (
+                y > from_y && rectangle.bottom() > from.bottom(),
+                rectangle.top() - from.bottom(),
+                across_columns,
+            ),
+            NavigationDirection::Left => (
+                x < from_x && rectangle.left() < from.left(),
+                from.left() - rectangle.right(),
+                across_rows,
+            ),
+            NavigationDirection::Right => (
+                x > from_x && rectangle.right() > from.right(),
+                rectangle.left() - from.right(),
+                across_rows,
+            ),
+        };
+        if index == current || !is_ahead {
+            continue;
+        }
+        let distance = along.max(0.0) + 2.0 * across;
+        if nearest.is_none_or(|(_, nearest)| distance < nearest) {
+            nearest = Some((index, distance));
+        }
+    }
+    nearest.map(|(index, _)| index)
+}
+
+/// The distance between two ranges, 0 when they overlap.
+fn range_gap(start: f32, end: f32, other_start: f32, other_end: f32) -> f32 {
+    (other_start - end).max(start - other_end).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arrows_prefer_the_element_straight_ahead() {
+        // A row of three buttons, with a wide one below the first two.
+        let rectangles = [
+            Rectangle::new(0.0, 0.0, 50.0, 20.0),
+            Rectangle::new(60.0, 0.0, 50.0, 20.0),
+            Rectangle::new(120.0, 0.0, 50.0, 20.0),
+            Rectangle::new(0.0, 100.0, 110.0, 20.0),
+        ];
+        assert_eq!(next_in_direction(&rectangles, 0, NavigationDirection::Right), Some(1));
+        assert_eq!(next_in_direction(&rectangles, 2, NavigationDirection::Left), Some(1));
+        assert_eq!(next_in_direction(&rectangles, 2, NavigationDirection::Down), Some(3));
+        assert_eq!(next_in_direction(&rectangles, 3, NavigationDirection::Up), Some(0));
+        // The third button sticks out past the right edge of the wide one.
+        assert_eq!(next_in_direction(&rectangles, 3, NavigationDirection::Right), Some(2));
+        assert_eq!(next_in_direction(&rectangles, 2, NavigationDirection::Right), None);
+        assert_eq!(next_in_direction(&rectangles, 1, NavigationDirection::Up), None);
+    }
+}
@@ -0,0 +1,234 @@
+//! Speaks text aloud with the text-to-speech of the platform, or the Web Speech API on the web.
+//!
+//! This is for apps that want explicit audio feedback. Screen readers already read the accessibility tree, so text
+//! shown in the window doesn't need to be spoken too.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Weak;
+use std::sync::mpsc;
+
+use craft_logging::warn;
+use tts::{Tts, UtteranceId};
+
+use crate::app::queue_event;
+use crate::elements::ElementInternals;
+use crate::events::{Event, EventKind, SpeechFinished};
+
+thread_local! {
+    static SPEECH: RefCell<Option<Speech>> = const { RefCell::new(None) };
+}
+
+/// How text is spoken.
+#[derive(Clone, Debug)]
+pub struct SpeechOptions {
+    /// The speed relative to the normal speed of the voice, e.g. 1.5 to speak half again as fast.
+    pub rate: f32,
+    /// The id or name of the voice to speak with, see [`voices`]. The current voice is used if `None`.
+    pub voice: Option<String>,
+    /// Whether to stop what is being spoken first. Otherwise the text is spoken after it.
+    pub interrupt: bool,
+}
+
+impl Default for SpeechOptions {
+    fn default() -> Self {
+        Self {
+            rate: 1.0,
+            voice: None,
+            interrupt: false,
+        }
+    }
+}
+
+/// A voice that text can be spoken with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpeechVoice {
+    pub id: String,
+    pub name: String,
+    /// The language as a BCP 47 tag, like `en-US`.
+    pub language: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SpeechError {
+    /// The platform has no text-to-speech, or it failed to start.
+    Unavailable(String),
+    /// No voice has the id or name in [`SpeechOptions::voice`].
+    VoiceNotFound(String),
+    /// The platform text-to-speech failed to speak.
+    Failed(String),
+}
+
+impl fmt::Display for SpeechError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpeechError::Unavailable(error) => write!(f, "Text-to-speech is unavailable: {error}"),
+            SpeechError::VoiceNotFound(voice) => write!(f, "There is no voice named '{voice}'"),
+            SpeechError::Failed(error) => write!(f, "Text-to-speech failed: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for SpeechError {}
+
+struct Speech {
+    tts: Tts,
+    /// Utterances that ended, and whether they were stopped, sent from the thread of the platform text-to-speech.
+    finished: mpsc::Receiver<(UtteranceId, bool)>,
+    /// The utterances elements are waiting on.
+    pending: Vec<PendingUtterance>,
+    next_utterance: u64,
+}
+
+struct PendingUtterance {
+    id: UtteranceId,
+    utterance: u64,
+    target: Weak<RefCell<dyn ElementInternals>>,
+}
+
+impl Speech {
+    fn new() -> Result<Self, SpeechError> {
+        let tts = Tts::default().map_err(|error| SpeechError::Unavailable(error.to_string()))?;
+        let (sender, finished) = mpsc::channel();
+        if tts.supported_features().utterance_callbacks {
+            let end_sender = sender.clone();
+            let callbacks = tts
+                .on_utterance_end(Some(Box::new(move |id| {
+                    end_sender.send((id, false)).ok();
+                })))
+                .and_then(|_| {
+                    tts.on_utterance_stop(Some(Box::new(move |id| {
+                        sender.send((id, true)).ok();
+                    })))
+                });
+            if let Err(error) = callbacks {
+                warn!("Speech finished events are unavailable: {error}");
+            }
+        }
+        Ok(Self {
+            tts,
+            finished,
+            pending: Vec::new(),
+            next_utterance: 1,
+        })
+    }
+
+    fn speak(&mut self, text: &str, options: &SpeechOptions) -> Result<(u64, Option<UtteranceId>), SpeechError> {
+        let failed = |error: tts::Error| SpeechError::Failed(error.to_string());
+        let features = self.tts.supported_features();
+
+        if features.rate {
+            let rate = (self.tts.normal_rate() * options.rate).clamp(self.tts.min_rate(), self.tts.max_rate());
+            self.tts.set_rate(rate).map_err(failed)?;
+        }
+        if let Some(name) = &options.voice
+            && features.voice
+        {
+            let voices = self.tts.voices().map_err(failed)?;
+            let voice = voices
+                .iter()
+                .find(|voice| voice.id() == *name || voice.name() == *name)
+                .ok_or_else(|| SpeechError::VoiceNotFound(name.clone()))?;
+            self.tts.set_voice(voice).map_err(failed)?;
+        }
+
+        let id = self.tts.speak(text, options.interrupt).map_err(failed)?;
+        let utterance = self.next_utterance;
+        self.next_utterance += 1;
+        Ok((utterance, id))
+    }
+}
+
+/// Runs `f` with the text-to-speech of this thread, which is started the first time it is needed.
+fn with_speech<R>(f: impl FnOnce(&mut Speech) -> Result<R, SpeechError>) -> Result<R, SpeechError> {
+    SPEECH.with_borrow_mut(|speech| {
+        if speech.is_none() {
+            *speech = Some(Speech::new()?);
+        }
+        f(speech.as_mut().unwrap())
+    })
+}
+
+/// Speaks `text`, and returns an id for it.
+pub fn speak(text: &str, options: &SpeechOptions) -> Result<u64, SpeechError> {
+    with_speech(|speech| speech.speak(text, options).map(|(utterance, _)| utterance))
+}
+
+/// Speaks `text`, and sends an [`EventKind::SpeechFinished`] event to `target` when it is done.
+///
+/// The event isn't sent on platforms that can't tell when speech ends.
+pub(crate) fn speak_for(
+    text: &str,
+    options: &SpeechOptions,
+    target: Weak<RefCell<dyn ElementInternals>>,
+) -> Result<u64, SpeechError> {
+    with_speech(|speech| {
+        let (utterance, id) = speech.speak(text, options)?;
+        if let Some(id) = id
+            && speech.tts.supported_features().utterance_callbacks
+        {
+            speech.pending.push(PendingUtterance { id, utterance, target });
+        }
+        Ok(utterance)
+    })
+}
+
+/// Stops speaking, and drops the text waiting to be spoken.
+pub fn stop() -> Result<(), SpeechError> {
+    with_speech(|speech| {
+        speech
+            .tts
+            .stop()
+            .map(|_| ())
+            .map_err(|error| SpeechError::Failed(error.to_string()))
+    })
+}
+
+/// Whether text is being spoken. Always false on platforms that can't tell.
+pub fn is_speaking() -> bool {
+    SPEECH.with_borrow(|speech| {
+        speech.as_ref().is_some_and(|speech| {
+            speech.tts.supported_features().is_speaking && speech.tts.is_speaking().unwrap_or(false)
+        })
+    })
+}
+
+/// Returns the voices text can be spoken with.
+pub fn voices() -> Result<Vec<SpeechVoice>, SpeechError> {
+    with_speech(|speech| {
+        let voices = speech.tts.voices().map_err(|error| SpeechError::Failed(error.to_string()))?;
+        Ok(voices
+            .iter()
+            .map(|voice| SpeechVoice {
+                id: voice.id(),
+                name: voice.name(),
+                language: voice.language().to_string(),
+            })
+            .collect())
+    })
+}
+
+/// Queues the events of the utterances that finished. Returns true if any were queued.
+pub(crate) fn poll_speech() -> bool {
+    SPEECH.with_borrow_mut(|speech| {
+        let Some(speech) = speech.as_mut() else {
+            return false;
+        };
+        let mut queued = false;
+        while let Ok((id, interrupted)) = speech.finished.try_recv() {
+            let Some(index) = speech.pending.iter().position(|pending| pending.id == id) else {
+                continue;
+            };
+            let pending = speech.pending.remove(index);
+            if let Some(target) = pending.target.upgrade() {
+                let finished = SpeechFinished {
+                    utterance: pending.utterance,
+                    interrupted,
+                };
+                queue_event(Event::new(target), EventKind::SpeechFinished(finished));
+                queued = true;
+            }
+        }
+        queued
+    })
+}
@@ -3,6 +3,7 @@
 use craft_primitives::Color;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BoxShadow {
     pub inset: bool,
     pub offset_x: f64,
@@ -23,4 +24,20 @@ impl BoxShadow {
             color,
         }
     }
+
+    /// The shadows of an element raised `elevation` pixels above its parent: a sharper key shadow offset downwards and
+    /// a softer ambient shadow around it.
+    ///
+    /// Returns no shadows if `elevation` is not positive.
+    pub fn elevation(elevation: f32) -> Vec<BoxShadow> {
+        if elevation <= 0.0 {
+            return Vec::new();
+        }
+
+        let elevation = elevation as f64;
+        vec![
+            BoxShadow::new(false, 0.0, elevation / 2.0, elevation, 0.0, Color::from_rgba8(0, 0, 0, 51)),
+            BoxShadow::new(false, 0.0, elevation / 8.0, elevation * 2.0, elevation / 8.0, Color::from_rgba8(0, 0, 0, 31)),
+        ]
+    }
 }
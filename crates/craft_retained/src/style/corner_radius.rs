@@ -0,0 +1,145 @@
+// https://www.w3.org/TR/css-backgrounds-3/#border-radius
+use craft_primitives::geometry::{Point, Rectangle};
+
+use crate::style::Unit;
+
+/// The horizontal and vertical radii of one corner of an element's border box.
+///
+/// Percentages resolve against the width of the border box for `x` and its height for `y`. `Auto` resolves to 0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CornerRadius {
+    pub x: Unit,
+    pub y: Unit,
+}
+
+impl CornerRadius {
+    pub const ZERO: CornerRadius = CornerRadius::circular(Unit::Px(0.0));
+
+    pub const fn circular(radius: Unit) -> Self {
+        Self { x: radius, y: radius }
+    }
+
+    pub const fn elliptical(x: Unit, y: Unit) -> Self {
+        Self { x, y }
+    }
+
+    /// Resolves the radii to pixels for a border box of the given size, without applying the overlap rules.
+    pub fn resolve(&self, width: f32, height: f32) -> (f32, f32) {
+        let resolve = |unit: Unit, length: f32| match unit {
+            Unit::Px(value) => value.max(0.0),
+            Unit::Percentage(value) => (value / 100.0 * length).max(0.0),
+            Unit::Auto => 0.0,
+        };
+
+        let x = resolve(self.x, width);
+        let y = resolve(self.y, height);
+
+        // A corner is square if either of its radii is zero.
+        if x == 0.0 || y == 0.0 { (0.0, 0.0) } else { (x, y) }
+    }
+}
+
+impl Default for CornerRadius {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl From<(f32, f32)> for CornerRadius {
+    fn from((x, y): (f32, f32)) -> Self {
+        Self::elliptical(Unit::Px(x), Unit::Px(y))
+    }
+}
+
+impl From<(Unit, Unit)> for CornerRadius {
+    fn from((x, y): (Unit, Unit)) -> Self {
+        Self::elliptical(x, y)
+    }
+}
+
+impl From<Unit> for CornerRadius {
+    fn from(radius: Unit) -> Self {
+        Self::circular(radius)
+    }
+}
+
+/// Resolves the corner radii, in top-left, top-right, bottom-right, bottom-left order, for a border box.
+///
+/// If the radii of two adjacent corners don't fit along a side, all radii are scaled down by the same factor, as in
+/// the CSS overlap rules.
+pub fn resolve_border_radius(radii: [CornerRadius; 4], width: f32, height: f32) -> [(f32, f32); 4] {
+    let [top_left, top_right, bottom_right, bottom_left] = radii.map(|radius| radius.resolve(width, height));
+
+    let scale = |length: f32, sum: f32| if sum > 0.0 { length / sum } else { f32::INFINITY };
+    let factor = scale(width, top_left.0 + top_right.0)
+        .min(scale(width, bottom_left.0 + bottom_right.0))
+        .min(scale(height, top_left.1 + bottom_left.1))
+        .min(scale(height, top_right.1 + bottom_right.1))
+        .min(1.0);
+
+    [top_left, top_right, bottom_right, bottom_left].map(|(x, y)| (x * factor, y * factor))
+}
+
+/// Returns true if `point` is inside `rect` with its corners rounded by the resolved `radii`.
+pub fn rounded_rect_contains(rect: &Rectangle, radii: &[(f32, f32); 4], point: &Point) -> bool {
+    if !rect.contains(point) {
+        return false;
+    }
+
+    let (x, y) = (point.x as f32, point.y as f32);
+    let corners = [
+        (rect.left(), rect.top(), 1.0, 1.0),
+        (rect.right(), rect.top(), -1.0, 1.0),
+        (rect.right(), rect.bottom(), -1.0, -1.0),
+        (rect.left(), rect.bottom(), 1.0, -1.0),
+    ];
+
+    for ((corner_x, corner_y, sign_x, sign_y), (radius_x, radius_y)) in corners.into_iter().zip(radii.iter().copied()) {
+        if radius_x <= 0.0 || radius_y <= 0.0 {
+            continue;
+        }
+
+        let center_x = corner_x + radius_x * sign_x;
+        let center_y = corner_y + radius_y * sign_y;
+        let in_corner = (x - center_x) * sign_x < 0.0 && (y - center_y) * sign_y < 0.0;
+        if in_corner {
+            let dx = (x - center_x) / radius_x;
+            let dy = (y - center_y) / radius_y;
+            return dx * dx + dy * dy <= 1.0;
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentages_resolve_against_each_axis() {
+        let radius = CornerRadius::circular(Unit::Percentage(50.0));
+        let radii = resolve_border_radius([radius; 4], 200.0, 100.0);
+
+        assert_eq!(radii, [(100.0, 50.0); 4]);
+    }
+
+    #[test]
+    fn overlapping_radii_are_scaled_down() {
+        let radii = resolve_border_radius([CornerRadius::from((100.0, 10.0)); 4], 100.0, 100.0);
+
+        assert_eq!(radii, [(50.0, 5.0); 4]);
+    }
+
+    #[test]
+    fn hit_testing_excludes_rounded_corners() {
+        let rect = Rectangle::new(0.0, 0.0, 100.0, 100.0);
+        let radii = resolve_border_radius([CornerRadius::from((50.0, 50.0)); 4], 100.0, 100.0);
+
+        assert!(rounded_rect_contains(&rect, &radii, &Point::new(50.0, 50.0)));
+        assert!(rounded_rect_contains(&rect, &radii, &Point::new(50.0, 1.0)));
+        assert!(!rounded_rect_contains(&rect, &radii, &Point::new(5.0, 5.0)));
+        assert!(!rounded_rect_contains(&rect, &radii, &Point::new(95.0, 95.0)));
+    }
+}
@@ -1,8 +1,14 @@
 mod box_shadow;
+mod corner_radius;
+#[cfg(feature = "serde")]
+mod style_serde;
 mod styles;
 mod taffy_conversions;
 
 pub use box_shadow::BoxShadow;
+pub use corner_radius::{CornerRadius, resolve_border_radius, rounded_rect_contains};
+pub use craft_renderer::effects::ColorFilter;
+pub use peniko::Mix;
 use craft_primitives::{Color, ColorBrush};
 use parley::GenericFamily;
 use std::borrow::Cow;
@@ -11,6 +17,7 @@ use std::fmt::Debug;
 pub use styles::*;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Unit {
     Px(f32),
     Percentage(f32),
@@ -43,6 +50,7 @@ impl Unit {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Display {
     Flex,
     Block,
@@ -50,6 +58,7 @@ pub enum Display {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AlignItems {
     Start,
     End,
@@ -61,6 +70,7 @@ pub enum AlignItems {
 }
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AlignContent {
     Start,
     End,
@@ -74,6 +84,7 @@ pub enum AlignContent {
 }
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum JustifyContent {
     Start,
     End,
@@ -87,6 +98,7 @@ pub enum JustifyContent {
 }
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FlexDirection {
     Row,
     Column,
@@ -95,9 +107,11 @@ pub enum FlexDirection {
 }
 
 #[derive(Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FontWeight(pub u16);
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScrollbarColor {
     pub thumb_color: Color,
     pub track_color: Color,
@@ -125,6 +139,7 @@ impl FontWeight {
 }
 
 #[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FlexWrap {
     #[default]
     NoWrap,
@@ -133,6 +148,7 @@ pub enum FlexWrap {
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Overflow {
     #[default]
     Visible,
@@ -149,6 +165,7 @@ impl Default for FontWeight {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Underline {
     pub thickness: Option<f32>,
     pub color: Color,
@@ -156,6 +173,7 @@ pub struct Underline {
 }
 
 #[derive(Copy, Clone, Default, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TextAlign {
     #[default]
     Start,
@@ -167,6 +185,7 @@ pub enum TextAlign {
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FontStyle {
     Normal,
     Italic,
@@ -193,9 +212,12 @@ pub enum TextStyleProperty {
     UnderlineBrush(Color),
     Link(String),
     BackgroundColor(Color),
+    /// A wavy underline, like the one under misspelled words.
+    SquigglyUnderline(Color),
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BoxSizing {
     #[default]
     BorderBox,
@@ -203,12 +225,27 @@ pub enum BoxSizing {
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Position {
     #[default]
     Relative,
     Absolute,
 }
 
+/// The direction an element and the elements inside it are laid out in, for right-to-left languages like Arabic and
+/// Hebrew.
+///
+/// Elements that don't set a direction take the one of their parent. Right to left, the start and end paddings and
+/// margins of elements swap sides and directional icons are mirrored, unless an element opts out with
+/// [`Element::auto_mirror`](crate::elements::Element::auto_mirror).
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
 #[derive(Clone, Debug)]
 pub struct StyleProperty<T>
 where
@@ -291,7 +328,9 @@ impl TextStyleProperty {
 
                 Some(parley::StyleProperty::UnderlineBrush(Some(brush)))
             }
-            TextStyleProperty::Link(_) | TextStyleProperty::BackgroundColor(_) => None,
+            TextStyleProperty::Link(_)
+            | TextStyleProperty::BackgroundColor(_)
+            | TextStyleProperty::SquigglyUnderline(_) => None,
         }
     }
 }
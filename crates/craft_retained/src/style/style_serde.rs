@@ -0,0 +1,73 @@
+//! Serde support for styles, enabled with the `serde` feature.
+
+use std::fmt::Debug;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::style::{FontFamily, StyleProperty};
+
+/// Style properties are (de)serialized as their value. Deserialized properties are dirty, so they are applied.
+impl<T> Serialize for StyleProperty<T>
+where
+    T: Clone + Debug + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.get().serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for StyleProperty<T>
+where
+    T: Clone + Debug + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(StyleProperty {
+            property: T::deserialize(deserializer)?,
+            is_dirty: true,
+        })
+    }
+}
+
+/// Font families are (de)serialized as their name, an empty name is the default font family.
+impl Serialize for FontFamily {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.name().unwrap_or_default().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for FontFamily {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        if name.len() > 64 {
+            return Err(serde::de::Error::custom("font family names are limited to 64 bytes"));
+        }
+        Ok(FontFamily::new(&name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::style::{CornerRadius, FlexDirection, Style, Unit};
+
+    #[test]
+    fn round_trips_through_json() {
+        let mut style = Style::default();
+        style.set_width(Unit::Percentage(50.0));
+        style.set_border_radius([CornerRadius::circular(Unit::Px(4.0)); 4]);
+
+        let json = serde_json::to_string(&style).unwrap();
+        let loaded: Style = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded.get_width(), Unit::Percentage(50.0));
+        assert_eq!(loaded.get_border_radius(), [CornerRadius::circular(Unit::Px(4.0)); 4]);
+    }
+
+    #[test]
+    fn missing_fields_use_defaults_and_unknown_fields_are_ignored() {
+        let loaded: Style = serde_json::from_str(r#"{ "flex_grow": 2.0, "not_a_style_property": true }"#).unwrap();
+
+        assert_eq!(loaded.get_flex_grow(), 2.0);
+        assert!(matches!(loaded.get_flex_direction(), FlexDirection::Row));
+        assert_eq!(loaded.get_height(), Unit::Auto);
+    }
+}
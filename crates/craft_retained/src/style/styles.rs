@@ -1,12 +1,19 @@
 use std::borrow::Cow;
 use std::fmt::Debug;
+use std::time::Duration;
 
 use crate::style::box_shadow::BoxShadow;
 use crate::style::*;
 use craft_primitives::geometry::TrblRectangle;
 use craft_primitives::{Color, ColorBrush};
 
+/// The style of an element.
+///
+/// With the `serde` feature a style can be loaded from any serde format, such as RON, JSON or TOML. Missing fields
+/// keep their default value and unknown fields are ignored, so themes stay loadable as properties are added.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Style {
     box_sizing: StyleProperty<BoxSizing>,
     position: StyleProperty<Position>,
@@ -47,7 +54,7 @@ pub struct Style {
 
     border_color: StyleProperty<TrblRectangle<Color>>,
     border_width: StyleProperty<TrblRectangle<Unit>>,
-    border_radius: StyleProperty<[(f32, f32); 4]>,
+    border_radius: StyleProperty<[CornerRadius; 4]>,
 
     scrollbar_color: StyleProperty<ScrollbarColor>,
     scrollbar_thumb_margin: StyleProperty<TrblRectangle<f32>>,
@@ -56,11 +63,23 @@ pub struct Style {
 
     visible: StyleProperty<bool>,
     selection_color: StyleProperty<Color>,
+    selection_text_color: StyleProperty<Option<Color>>,
+    inactive_selection_color: StyleProperty<Option<Color>>,
     cursor_color: StyleProperty<Option<Color>>,
+    cursor_width: StyleProperty<f32>,
+    cursor_blink_interval: StyleProperty<Duration>,
 
     box_shadows: StyleProperty<Vec<BoxShadow>>,
+    elevation: StyleProperty<f32>,
+    pixel_snapping: StyleProperty<bool>,
+    text_contrast: StyleProperty<Option<f32>>,
+    mix_blend_mode: StyleProperty<Mix>,
+    color_filters: StyleProperty<Vec<ColorFilter>>,
+    direction: StyleProperty<Direction>,
+    auto_mirror: StyleProperty<bool>,
 
     /// Set to true anytime a setter is called.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub is_dirty: bool,
 }
 const SCROLLBAR_THUMB_MARGIN: TrblRectangle<f32> = if cfg!(any(target_os = "android", target_os = "ios")) {
@@ -105,7 +124,7 @@ impl Style {
             overflow: StyleProperty::new([Overflow::default(); 2]),
             border_color: StyleProperty::new(TrblRectangle::new_all(Color::BLACK)),
             border_width: StyleProperty::new(TrblRectangle::new_all(Unit::Px(0.0))),
-            border_radius: StyleProperty::new([(0.0, 0.0); 4]),
+            border_radius: StyleProperty::new([CornerRadius::ZERO; 4]),
             scrollbar_color: StyleProperty::new(ScrollbarColor {
                 thumb_color: Color::from_rgb8(150, 150, 152),
                 track_color: Color::TRANSPARENT,
@@ -119,12 +138,29 @@ impl Style {
             }),
             visible: StyleProperty::new(true),
             selection_color: StyleProperty::new(Color::from_rgb8(0, 120, 215)),
+            selection_text_color: StyleProperty::new(None),
+            inactive_selection_color: StyleProperty::new(None),
             cursor_color: StyleProperty::new(None),
+            cursor_width: StyleProperty::new(1.0),
+            cursor_blink_interval: StyleProperty::new(Duration::from_millis(500)),
             box_shadows: StyleProperty::new(Vec::new()),
+            elevation: StyleProperty::new(0.0),
+            pixel_snapping: StyleProperty::new(true),
+            text_contrast: StyleProperty::new(None),
+            mix_blend_mode: StyleProperty::new(Mix::Normal),
+            color_filters: StyleProperty::new(Vec::new()),
+            direction: StyleProperty::new(Direction::Ltr),
+            auto_mirror: StyleProperty::new(true),
         }
     }
 }
 
+impl Default for Style {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Style {
     pub fn get_box_sizing(&self) -> BoxSizing {
         *self.box_sizing.get()
@@ -414,11 +450,16 @@ impl Style {
         self.border_width.set(val);
     }
 
-    pub fn get_border_radius(&self) -> [(f32, f32); 4] {
+    pub fn get_border_radius(&self) -> [CornerRadius; 4] {
         *self.border_radius.get()
     }
 
-    pub fn set_border_radius(&mut self, val: [(f32, f32); 4]) {
+    /// Returns the border radius in pixels for a border box of the given size, see [`resolve_border_radius`].
+    pub fn get_resolved_border_radius(&self, width: f32, height: f32) -> [(f32, f32); 4] {
+        resolve_border_radius(*self.border_radius.get(), width, height)
+    }
+
+    pub fn set_border_radius(&mut self, val: [CornerRadius; 4]) {
         self.is_dirty = true;
         self.border_radius.set(val);
     }
@@ -477,6 +518,39 @@ impl Style {
         self.selection_color.set(val);
     }
 
+    pub fn get_selection_text_color(&self) -> Option<Color> {
+        *self.selection_text_color.get()
+    }
+
+    pub fn set_selection_text_color(&mut self, val: Option<Color>) {
+        self.is_dirty = true;
+        self.selection_text_color.set(val);
+    }
+
+    pub fn get_inactive_selection_color(&self) -> Option<Color> {
+        *self.inactive_selection_color.get()
+    }
+
+    pub fn set_inactive_selection_color(&mut self, val: Option<Color>) {
+        self.is_dirty = true;
+        self.inactive_selection_color.set(val);
+    }
+
+    /// Returns the background and text colors of a selection.
+    ///
+    /// While the selection isn't active, because its window or element doesn't have focus, the background is the
+    /// inactive selection color, or a muted variant of the selection color, and the text keeps its own color.
+    pub fn get_selection_colors(&self, active: bool) -> (Color, Option<Color>) {
+        if active {
+            (self.get_selection_color(), self.get_selection_text_color())
+        } else {
+            let background = self
+                .get_inactive_selection_color()
+                .unwrap_or_else(|| muted_selection_color(self.get_selection_color()));
+            (background, None)
+        }
+    }
+
     pub fn get_cursor_color(&self) -> Option<Color> {
         *self.cursor_color.get()
     }
@@ -486,6 +560,24 @@ impl Style {
         self.cursor_color.set(val);
     }
 
+    pub fn get_cursor_width(&self) -> f32 {
+        *self.cursor_width.get()
+    }
+
+    pub fn set_cursor_width(&mut self, val: f32) {
+        self.is_dirty = true;
+        self.cursor_width.set(val);
+    }
+
+    pub fn get_cursor_blink_interval(&self) -> Duration {
+        *self.cursor_blink_interval.get()
+    }
+
+    pub fn set_cursor_blink_interval(&mut self, val: Duration) {
+        self.is_dirty = true;
+        self.cursor_blink_interval.set(val);
+    }
+
     pub fn get_box_shadows(&self) -> &[BoxShadow] {
         self.box_shadows.get()
     }
@@ -493,6 +585,120 @@ impl Style {
     pub fn set_box_shadows(&mut self, box_shadows: Vec<BoxShadow>) {
         self.box_shadows = StyleProperty::new(box_shadows)
     }
+
+    pub fn get_elevation(&self) -> f32 {
+        *self.elevation.get()
+    }
+
+    /// Raises the element onto the overlay layer, above its siblings and outside the clipping of its ancestors, with a
+    /// shadow matching the elevation. `0.0` keeps the element in the normal flow of drawing.
+    pub fn set_elevation(&mut self, val: f32) {
+        self.is_dirty = true;
+        self.elevation.set(val);
+    }
+
+    /// Returns the box shadows followed by the shadows of the elevation.
+    pub fn get_computed_box_shadows(&self) -> Vec<BoxShadow> {
+        let mut box_shadows = self.get_box_shadows().to_vec();
+        box_shadows.extend(BoxShadow::elevation(self.get_elevation()));
+        box_shadows
+    }
+
+    pub fn get_pixel_snapping(&self) -> bool {
+        *self.pixel_snapping.get()
+    }
+
+    /// Whether borders and backgrounds are snapped to physical pixels when drawn.
+    pub fn set_pixel_snapping(&mut self, val: bool) {
+        self.is_dirty = true;
+        self.pixel_snapping.set(val);
+    }
+
+    pub fn get_text_contrast(&self) -> Option<f32> {
+        *self.text_contrast.get()
+    }
+
+    /// Overrides [`CraftOptions::text_contrast`](crate::CraftOptions::text_contrast) for this element's text.
+    pub fn set_text_contrast(&mut self, val: Option<f32>) {
+        self.is_dirty = true;
+        self.text_contrast.set(val);
+    }
+
+    pub fn get_mix_blend_mode(&self) -> Mix {
+        *self.mix_blend_mode.get()
+    }
+
+    pub fn set_mix_blend_mode(&mut self, val: Mix) {
+        self.is_dirty = true;
+        self.mix_blend_mode.set(val);
+    }
+
+    pub fn get_color_filters(&self) -> &[ColorFilter] {
+        self.color_filters.get()
+    }
+
+    pub fn set_color_filters(&mut self, val: Vec<ColorFilter>) {
+        self.is_dirty = true;
+        self.color_filters.set(val);
+    }
+
+    pub fn get_direction(&self) -> Direction {
+        *self.direction.get()
+    }
+
+    pub fn set_direction(&mut self, val: Direction) {
+        self.is_dirty = true;
+        self.direction.set(val);
+    }
+
+    /// Takes the direction of the parent, unless the direction was set on this style. Returns whether it changed.
+    pub(crate) fn inherit_direction(&mut self, val: Direction) -> bool {
+        if self.direction.is_dirty() || self.direction.property == val {
+            return false;
+        }
+        self.is_dirty = true;
+        self.direction.property = val;
+        true
+    }
+
+    pub fn get_auto_mirror(&self) -> bool {
+        *self.auto_mirror.get()
+    }
+
+    /// Whether paddings, margins and directional icons are mirrored when the direction is right to left.
+    pub fn set_auto_mirror(&mut self, val: bool) {
+        self.is_dirty = true;
+        self.auto_mirror.set(val);
+    }
+
+    /// Whether the element is laid out right to left and mirrors its paddings, margins and directional icons.
+    pub fn is_mirrored(&self) -> bool {
+        self.get_direction() == Direction::Rtl && self.get_auto_mirror()
+    }
+
+    /// Copies the properties that were set on `other`, with a setter or because they were in a loaded style, and
+    /// keeps the others.
+    pub fn merge(&mut self, other: &Style) {
+        macro_rules! merge_properties {
+            ($($property:ident),* $(,)?) => {
+                $(
+                    if other.$property.is_dirty() {
+                        self.$property.set(other.$property.get().clone());
+                    }
+                )*
+            };
+        }
+        merge_properties!(
+            box_sizing, position, margin, padding, gap, inset, width, min_width, max_width, height, min_height,
+            max_height, display, wrap, align_items, justify_content, flex_direction, flex_grow, flex_shrink, flex_basis,
+            font_family, background_color, color, line_height, font_size, font_weight, font_style, text_align,
+            underline, overflow, border_color, border_width, border_radius, scrollbar_color, scrollbar_thumb_margin,
+            scrollbar_thumb_radius, scrollbar_width, visible, selection_color, selection_text_color,
+            inactive_selection_color, cursor_color, cursor_width, cursor_blink_interval, box_shadows, elevation,
+            pixel_snapping, text_contrast, mix_blend_mode, color_filters, direction, auto_mirror,
+        );
+        self.is_dirty = true;
+    }
 }
 
 impl Style {
@@ -625,3 +831,67 @@ impl Style {
         style_set.insert(parley::StyleProperty::UnderlineSize(underline_size));
     }
 }
+
+/// Desaturates a selection color and moves it halfway to white, so an inactive selection stays visible but recedes.
+fn muted_selection_color(color: Color) -> Color {
+    let [r, g, b, a] = color.components;
+    let gray = 0.299 * r + 0.587 * g + 0.114 * b;
+    let muted = gray + (1.0 - gray) * 0.5;
+    Color::new([muted, muted, muted, a])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn active_selection_uses_the_selection_colors() {
+        let mut style = Style::new();
+        style.set_selection_color(Color::from_rgb8(0, 0, 255));
+        style.set_selection_text_color(Some(Color::WHITE));
+        assert_eq!(style.get_selection_colors(true), (Color::from_rgb8(0, 0, 255), Some(Color::WHITE)));
+    }
+
+    #[test]
+    fn inactive_selection_is_muted_gray() {
+        let style = Style::new();
+        let (background, text) = style.get_selection_colors(false);
+        let [r, g, b, a] = background.components;
+        assert_eq!(r, g);
+        assert_eq!(g, b);
+        assert!(r > 0.5 && r < 1.0);
+        assert_eq!(a, 1.0);
+        assert_eq!(text, None);
+    }
+
+    #[test]
+    fn inactive_selection_color_overrides_the_muted_color() {
+        let mut style = Style::new();
+        style.set_inactive_selection_color(Some(Color::from_rgb8(200, 200, 200)));
+        assert_eq!(style.get_selection_colors(false).0, Color::from_rgb8(200, 200, 200));
+    }
+
+    #[test]
+    fn directions_set_on_a_style_are_not_inherited_over() {
+        let mut style = Style::new();
+        assert!(style.inherit_direction(Direction::Rtl));
+        assert!(style.is_mirrored());
+
+        style.set_direction(Direction::Ltr);
+        assert!(!style.inherit_direction(Direction::Rtl));
+        assert_eq!(style.get_direction(), Direction::Ltr);
+    }
+
+    #[test]
+    fn right_to_left_styles_swap_their_start_and_end_paddings() {
+        let mut style = Style::new();
+        style.set_padding(TrblRectangle::new(Unit::Px(0.0), Unit::Px(4.0), Unit::Px(0.0), Unit::Px(12.0)));
+        style.set_direction(Direction::Rtl);
+        let padding = style.to_taffy_style().padding;
+        let (start, end) = (taffy::LengthPercentage::length(12.0), taffy::LengthPercentage::length(4.0));
+        assert_eq!((padding.left, padding.right), (end, start));
+
+        style.set_auto_mirror(false);
+        assert_eq!(style.to_taffy_style().padding.left, start);
+    }
+}
@@ -54,18 +54,25 @@ impl Style {
             height: unit_to_taffy_dimension(style.get_min_height()),
         };
 
+        // The left and right margins and paddings are the start and end ones, which swap sides right to left.
+        let (mut style_margin, mut style_padding) = (style.get_margin(), style.get_padding());
+        if style.is_mirrored() {
+            std::mem::swap(&mut style_margin.left, &mut style_margin.right);
+            std::mem::swap(&mut style_padding.left, &mut style_padding.right);
+        }
+
         let margin: taffy::Rect<taffy::LengthPercentageAuto> = taffy::Rect {
-            top: unit_to_taffy_lengthpercentageauto(style.get_margin().top),
-            right: unit_to_taffy_lengthpercentageauto(style.get_margin().right),
-            bottom: unit_to_taffy_lengthpercentageauto(style.get_margin().bottom),
-            left: unit_to_taffy_lengthpercentageauto(style.get_margin().left),
+            top: unit_to_taffy_lengthpercentageauto(style_margin.top),
+            right: unit_to_taffy_lengthpercentageauto(style_margin.right),
+            bottom: unit_to_taffy_lengthpercentageauto(style_margin.bottom),
+            left: unit_to_taffy_lengthpercentageauto(style_margin.left),
         };
 
         let padding: taffy::Rect<taffy::LengthPercentage> = taffy::Rect {
-            top: unit_to_taffy_length_percentage(style.get_padding().top),
-            right: unit_to_taffy_length_percentage(style.get_padding().right),
-            bottom: unit_to_taffy_length_percentage(style.get_padding().bottom),
-            left: unit_to_taffy_length_percentage(style.get_padding().left),
+            top: unit_to_taffy_length_percentage(style_padding.top),
+            right: unit_to_taffy_length_percentage(style_padding.right),
+            bottom: unit_to_taffy_length_percentage(style_padding.bottom),
+            left: unit_to_taffy_length_percentage(style_padding.left),
         };
 
         let border: taffy::Rect<taffy::LengthPercentage> = taffy::Rect {
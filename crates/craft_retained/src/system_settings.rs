@@ -0,0 +1,325 @@
+//! The appearance settings of the operating system, so apps can match the rest of the desktop.
+//!
+//! The settings are read on a background thread when the app starts, and again when the theme changes or a window
+//! gains focus, e.g. after the user comes back from the system settings. Every window is sent a
+//! [`EventKind::SystemSettingsChanged`] event when they changed.
+//!
+//! On Windows the settings are only read with the `system_settings` feature.
+//!
+//! [`EventKind::SystemSettingsChanged`]: crate::events::EventKind::SystemSettingsChanged
+
+use std::cell::RefCell;
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use craft_primitives::Color;
+use winit::window::Theme;
+
+thread_local! {
+    static SYSTEM_SETTINGS: RefCell<SettingsState> = RefCell::new(SettingsState::default());
+}
+
+/// The appearance settings of the operating system. A setting is `None` when it can't be read on this platform.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SystemSettings {
+    /// Whether the system uses light or dark colors.
+    pub theme: Option<Theme>,
+    /// The color the user picked to highlight controls and selections.
+    pub accent_color: Option<Color>,
+    /// The font family of the user interface.
+    pub ui_font: Option<String>,
+    /// The font size of the user interface, in logical pixels.
+    pub ui_font_size: Option<f32>,
+    /// How many lines one notch of the mouse wheel scrolls.
+    pub wheel_scroll_lines: Option<u32>,
+}
+
+#[derive(Default)]
+struct SettingsState {
+    /// The last settings read, or `None` before the first read finished.
+    settings: Option<SystemSettings>,
+    /// The theme reported by a window, which is used over the theme read from the system.
+    theme: Option<Theme>,
+    /// False until the settings are read for the first time, and again when they may have changed since.
+    is_current: bool,
+    /// Receives the settings from the thread reading them.
+    reading: Option<Receiver<SystemSettings>>,
+}
+
+/// Returns the last settings of the operating system read. Everything is `None` until the first read finished.
+pub fn system_settings() -> SystemSettings {
+    SYSTEM_SETTINGS.with_borrow(|state| state.settings.clone().unwrap_or_default())
+}
+
+/// Reads the settings again, with the theme reported by a window if there is one.
+pub(crate) fn refresh_system_settings(theme: Option<Theme>) {
+    SYSTEM_SETTINGS.with_borrow_mut(|state| {
+        if theme.is_some() {
+            state.theme = theme;
+        }
+        state.is_current = false;
+    });
+}
+
+/// Starts reading the settings when they may have changed, and returns the settings read if they changed. Called once
+/// per iteration of the event loop.
+pub(crate) fn poll_system_settings() -> Option<SystemSettings> {
+    SYSTEM_SETTINGS.with_borrow_mut(|state| {
+        let mut changed = None;
+        if let Some(reading) = &state.reading {
+            match reading.try_recv() {
+                Ok(mut settings) => {
+                    if state.theme.is_some() {
+                        settings.theme = state.theme;
+                    }
+                    // Settings asked for before the first read were all `None`.
+                    let previous = state.settings.replace(settings.clone()).unwrap_or_default();
+                    changed = (previous != settings).then_some(settings);
+                }
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => {}
+            }
+            state.reading = None;
+        }
+
+        if !state.is_current {
+            state.is_current = true;
+            state.reading = read_in_background();
+        }
+        changed
+    })
+}
+
+fn read_in_background() -> Option<Receiver<SystemSettings>> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    #[cfg(not(target_arch = "wasm32"))]
+    std::thread::Builder::new()
+        .name("craft-system-settings".to_string())
+        .spawn(move || {
+            sender.send(read_platform_settings()).ok();
+        })
+        .ok()?;
+    // The web has no settings to read, so there is nothing to wait for.
+    #[cfg(target_arch = "wasm32")]
+    sender.send(read_platform_settings()).ok();
+    Some(receiver)
+}
+
+#[cfg(target_os = "linux")]
+fn read_platform_settings() -> SystemSettings {
+    command_output("gsettings", &["list-recursively", "org.gnome.desktop.interface"])
+        .map(|output| parse_gsettings(&output))
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "macos")]
+fn read_platform_settings() -> SystemSettings {
+    use objc2_foundation::{NSString, NSUserDefaults};
+
+    let defaults = NSUserDefaults::standardUserDefaults();
+    let read = |key: &str| defaults.stringForKey(&NSString::from_str(key)).map(|value| value.to_string());
+    let mut settings = macos_settings(read("AppleInterfaceStyle").as_deref(), read("AppleAccentColor").as_deref());
+    // The system font can't be changed, and has no family name outside of AppKit.
+    settings.ui_font = Some("SF Pro".to_string());
+    settings.ui_font_size = Some(13.0);
+    settings
+}
+
+#[cfg(all(feature = "system_settings", target_os = "windows"))]
+fn read_platform_settings() -> SystemSettings {
+    use windows::Win32::System::Registry::{HKEY_CURRENT_USER, RRF_RT_REG_DWORD, RegGetValueW};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SPI_GETWHEELSCROLLLINES, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS, SystemParametersInfoW,
+    };
+    use windows::core::w;
+
+    let mut accent_color = 0u32;
+    let mut size = size_of::<u32>() as u32;
+    // SAFETY: `accent_color` is a DWORD for the call to write to, and `size` is its size.
+    let accent_color = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            w!(r"Software\Microsoft\Windows\DWM"),
+            w!("AccentColor"),
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut accent_color as *mut u32 as *mut _),
+            Some(&mut size),
+        )
+    }
+    .is_ok()
+    .then_some(accent_color);
+
+    let mut wheel_scroll_lines = 0u32;
+    // SAFETY: `wheel_scroll_lines` is a UINT for the call to write to.
+    let wheel_scroll_lines = unsafe {
+        SystemParametersInfoW(
+            SPI_GETWHEELSCROLLLINES,
+            0,
+            Some(&mut wheel_scroll_lines as *mut u32 as *mut _),
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+    }
+    .is_ok()
+    .then_some(wheel_scroll_lines);
+
+    SystemSettings {
+        theme: None,
+        accent_color: accent_color.map(windows_accent_color),
+        // The font of the user interface has been Segoe UI at 9 points since Windows Vista.
+        ui_font: Some("Segoe UI".to_string()),
+        ui_font_size: Some(points_to_pixels(9.0)),
+        wheel_scroll_lines,
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "macos",
+    all(feature = "system_settings", target_os = "windows")
+)))]
+fn read_platform_settings() -> SystemSettings {
+    SystemSettings::default()
+}
+
+/// Runs a command and returns what it printed, if it succeeded.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+pub(crate) fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(any(
+    test,
+    target_os = "linux",
+    all(feature = "system_settings", target_os = "windows")
+))]
+fn points_to_pixels(points: f32) -> f32 {
+    points * 96.0 / 72.0
+}
+
+/// Parses the output of `gsettings list-recursively org.gnome.desktop.interface`.
+#[cfg(any(test, target_os = "linux"))]
+fn parse_gsettings(output: &str) -> SystemSettings {
+    let mut settings = SystemSettings::default();
+    for line in output.lines() {
+        let mut parts = line.splitn(3, ' ');
+        let (Some(_schema), Some(key), Some(value)) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        let value = value.trim().trim_matches('\'');
+        match key {
+            "color-scheme" => {
+                settings.theme = match value {
+                    "prefer-dark" => Some(Theme::Dark),
+                    "prefer-light" | "default" => Some(Theme::Light),
+                    _ => None,
+                };
+            }
+            "accent-color" => settings.accent_color = gnome_accent_color(value),
+            "font-name" => {
+                // Like "Cantarell 11", with the size in points.
+                if let Some((family, size)) = value.rsplit_once(' ')
+                    && let Ok(size) = size.parse::<f32>()
+                {
+                    settings.ui_font = Some(family.to_string());
+                    settings.ui_font_size = Some(points_to_pixels(size));
+                } else {
+                    settings.ui_font = Some(value.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    settings
+}
+
+/// The accent colors of GNOME, as drawn by libadwaita.
+#[cfg(any(test, target_os = "linux"))]
+fn gnome_accent_color(name: &str) -> Option<Color> {
+    let rgb = match name {
+        "blue" => (0x35, 0x84, 0xe4),
+        "teal" => (0x21, 0x90, 0xa4),
+        "green" => (0x3a, 0x94, 0x4a),
+        "yellow" => (0xc8, 0x88, 0x00),
+        "orange" => (0xed, 0x5b, 0x00),
+        "red" => (0xe6, 0x2d, 0x42),
+        "pink" => (0xd5, 0x61, 0x99),
+        "purple" => (0x91, 0x41, 0xac),
+        "slate" => (0x6f, 0x83, 0x96),
+        _ => return None,
+    };
+    Some(Color::from_rgb8(rgb.0, rgb.1, rgb.2))
+}
+
+/// Converts the `AppleInterfaceStyle` and `AppleAccentColor` user defaults, which are missing for the light theme and
+/// the blue accent color.
+#[cfg(any(test, target_os = "macos"))]
+fn macos_settings(interface_style: Option<&str>, accent_color: Option<&str>) -> SystemSettings {
+    let theme = if interface_style == Some("Dark") {
+        Theme::Dark
+    } else {
+        Theme::Light
+    };
+    // Blue is the accent color when none was picked.
+    let accent = accent_color.and_then(|accent_color| accent_color.parse().ok()).unwrap_or(4);
+
+    let rgb = match accent {
+        -1 => (0x8e, 0x8e, 0x93),
+        0 => (0xff, 0x3b, 0x30),
+        1 => (0xff, 0x95, 0x00),
+        2 => (0xff, 0xcc, 0x00),
+        3 => (0x28, 0xcd, 0x41),
+        5 => (0xaf, 0x52, 0xde),
+        6 => (0xff, 0x2d, 0x55),
+        _ => (0x00, 0x7a, 0xff),
+    };
+    SystemSettings {
+        theme: Some(theme),
+        accent_color: Some(Color::from_rgb8(rgb.0, rgb.1, rgb.2)),
+        ..Default::default()
+    }
+}
+
+/// Converts the accent color of the window manager, a DWORD laid out as 0xAABBGGRR.
+#[cfg(any(test, all(feature = "system_settings", target_os = "windows")))]
+fn windows_accent_color(value: u32) -> Color {
+    let [red, green, blue, _alpha] = value.to_le_bytes();
+    Color::from_rgb8(red, green, blue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gsettings_are_parsed() {
+        let output = "org.gnome.desktop.interface accent-color 'teal'\n\
+                      org.gnome.desktop.interface color-scheme 'prefer-dark'\n\
+                      org.gnome.desktop.interface font-name 'Noto Sans 12'\n\
+                      org.gnome.desktop.interface clock-format '24h'\n";
+        let settings = parse_gsettings(output);
+        assert_eq!(settings.theme, Some(Theme::Dark));
+        assert_eq!(settings.accent_color, Some(Color::from_rgb8(0x21, 0x90, 0xa4)));
+        assert_eq!(settings.ui_font.as_deref(), Some("Noto Sans"));
+        assert_eq!(settings.ui_font_size, Some(16.0));
+    }
+
+    #[test]
+    fn macos_defaults_are_converted() {
+        let settings = macos_settings(Some("Dark"), Some("3"));
+        assert_eq!(settings.theme, Some(Theme::Dark));
+        assert_eq!(settings.accent_color, Some(Color::from_rgb8(0x28, 0xcd, 0x41)));
+
+        let settings = macos_settings(None, None);
+        assert_eq!(settings.theme, Some(Theme::Light));
+        assert_eq!(settings.accent_color, Some(Color::from_rgb8(0x00, 0x7a, 0xff)));
+    }
+
+    #[test]
+    fn windows_accent_color_is_converted() {
+        assert_eq!(windows_accent_color(0xffd77800), Color::from_rgb8(0x00, 0x78, 0xd7));
+    }
+}
@@ -1,7 +1,10 @@
+pub mod number_format;
 pub(crate) mod parley_editor;
+pub mod spell_check;
 pub mod text_commands;
 pub mod text_context;
 pub(crate) mod text_render_data;
+pub mod whitespace;
 
 use std::ops::Range;
 
@@ -0,0 +1,200 @@
+//! Locale aware numbers in text inputs.
+//!
+//! A [`TextInput`](crate::elements::TextInput) with a [`NumberFormat`] shows its number with the separators of the
+//! locale while it isn't focused, like `1.234,50` in German. When it is focused the number is shown without rounding
+//! or padding, so it can be edited, and the group separators are put back while the user types without moving the
+//! cursor off the digit it was next to.
+
+use craft_calendar::sys_locale::get_locale_or_default;
+use craft_calendar::{Locale, number_symbols};
+
+/// How the number of a text input is written.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub decimal_separator: char,
+    /// The separator between groups of three digits, or `None` to not group digits.
+    pub group_separator: Option<char>,
+    /// The fewest digits shown after the decimal separator when the input isn't focused.
+    pub min_fraction_digits: usize,
+    /// The most digits shown after the decimal separator when the input isn't focused. The number is rounded to them.
+    pub max_fraction_digits: usize,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            decimal_separator: '.',
+            group_separator: Some(','),
+            min_fraction_digits: 0,
+            max_fraction_digits: 3,
+        }
+    }
+}
+
+impl NumberFormat {
+    /// The separators of the locale of the system.
+    pub fn system() -> Self {
+        Self::for_locale(&get_locale_or_default())
+    }
+
+    pub fn for_locale(locale: &Locale) -> Self {
+        let symbols = number_symbols(locale);
+        Self {
+            decimal_separator: symbols.decimal_separator,
+            group_separator: symbols.group_separator,
+            ..Self::default()
+        }
+    }
+
+    pub fn fraction_digits(mut self, min: usize, max: usize) -> Self {
+        self.min_fraction_digits = min;
+        self.max_fraction_digits = max.max(min);
+        self
+    }
+
+    /// Writes `value` for an input that isn't focused, rounded to the fraction digits.
+    pub fn format(&self, value: f64) -> String {
+        let rounded = format!("{:.*}", self.max_fraction_digits, value.abs());
+        let (integer, all_fraction) = rounded.split_once('.').unwrap_or((&rounded, ""));
+        let trimmed = all_fraction.trim_end_matches('0');
+        let fraction = if trimmed.len() < self.min_fraction_digits {
+            &all_fraction[..self.min_fraction_digits]
+        } else {
+            trimmed
+        };
+        // A negative number rounded to zero has no sign.
+        let negative = value.is_sign_negative() && rounded.bytes().any(|byte| matches!(byte, b'1'..=b'9'));
+        self.join(negative, integer, fraction)
+    }
+
+    /// Writes `value` for an input that is focused, with all of its digits.
+    pub fn edit_text(&self, value: f64) -> String {
+        let text = value.abs().to_string();
+        let (integer, fraction) = text.split_once('.').unwrap_or((&text, ""));
+        self.join(value.is_sign_negative() && value != 0.0, integer, fraction)
+    }
+
+    /// Reads a number written with these separators. Group separators may be anywhere in the integer part.
+    pub fn parse(&self, text: &str) -> Option<f64> {
+        let mut number = String::new();
+        for (index, character) in text.trim().chars().enumerate() {
+            match character {
+                '0'..='9' => number.push(character),
+                '-' | '+' if index == 0 => number.push(character),
+                _ if character == self.decimal_separator => number.push('.'),
+                _ if Some(character) == self.group_separator => {}
+                _ => return None,
+            }
+        }
+        number.parse().ok()
+    }
+
+    /// Puts the group separators of `text` where they belong, and returns the new text with the byte index `cursor`
+    /// moved to stay after the same digit. The text is returned unchanged if it isn't a number being typed.
+    pub fn regroup(&self, text: &str, cursor: usize) -> (String, usize) {
+        let is_group_separator = |character: char| Some(character) == self.group_separator;
+        let ungrouped: String = text.chars().filter(|&character| !is_group_separator(character)).collect();
+        let (sign, digits) = match ungrouped.strip_prefix(['-', '+']) {
+            Some(digits) => (&ungrouped[..1], digits),
+            None => ("", ungrouped.as_str()),
+        };
+        let (integer, fraction) = match digits.split_once(self.decimal_separator) {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (digits, None),
+        };
+        let is_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+        if !is_digits(integer) || !fraction.is_none_or(is_digits) {
+            return (text.to_string(), cursor);
+        }
+
+        let mut regrouped = format!("{sign}{}", self.group(integer));
+        if let Some(fraction) = fraction {
+            regrouped.push(self.decimal_separator);
+            regrouped.push_str(fraction);
+        }
+
+        let kept_before_cursor = text[..cursor.min(text.len())]
+            .chars()
+            .filter(|&character| !is_group_separator(character))
+            .count();
+        let mut kept = 0;
+        let mut new_cursor = 0;
+        for (index, character) in regrouped.char_indices() {
+            if kept == kept_before_cursor {
+                break;
+            }
+            if !is_group_separator(character) {
+                kept += 1;
+            }
+            new_cursor = index + character.len_utf8();
+        }
+        (regrouped, new_cursor)
+    }
+
+    fn join(&self, negative: bool, integer: &str, fraction: &str) -> String {
+        let mut text = String::new();
+        if negative {
+            text.push('-');
+        }
+        text.push_str(&self.group(integer));
+        if !fraction.is_empty() {
+            text.push(self.decimal_separator);
+            text.push_str(fraction);
+        }
+        text
+    }
+
+    /// Separates groups of three digits.
+    fn group(&self, digits: &str) -> String {
+        let Some(separator) = self.group_separator else {
+            return digits.to_string();
+        };
+        let mut grouped = String::new();
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index) % 3 == 0 {
+                grouped.push(separator);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn german() -> NumberFormat {
+        NumberFormat {
+            decimal_separator: ',',
+            group_separator: Some('.'),
+            ..NumberFormat::default()
+        }
+    }
+
+    #[test]
+    fn numbers_are_formatted_and_parsed_with_the_separators() {
+        let format = german().fraction_digits(2, 2);
+        assert_eq!(format.format(1234567.5), "1.234.567,50");
+        assert_eq!(format.format(-0.001), "0,00");
+        assert_eq!(format.edit_text(-1234.125), "-1.234,125");
+        assert_eq!(format.parse(" 1.234,5 "), Some(1234.5));
+        assert_eq!(format.parse("12.34"), Some(1234.0));
+        assert_eq!(format.parse("1,2,3"), None);
+        assert_eq!(format.parse("1e5"), None);
+        assert_eq!(NumberFormat::default().format(2.0), "2");
+    }
+
+    #[test]
+    fn regrouping_keeps_the_cursor_after_the_same_digit() {
+        let format = NumberFormat::default();
+        // A digit typed at the end, with the cursor after it.
+        assert_eq!(format.regroup("1,2345", 6), ("12,345".to_string(), 6));
+        // A digit typed before the separator, with the cursor after the 2.
+        assert_eq!(format.regroup("12,3456", 2), ("123,456".to_string(), 2));
+        // The first digit deleted.
+        assert_eq!(format.regroup(",234", 0), ("234".to_string(), 0));
+        assert_eq!(format.regroup("-1234.5", 7), ("-1,234.5".to_string(), 8));
+        assert_eq!(format.regroup("12a", 3), ("12a".to_string(), 3));
+    }
+}
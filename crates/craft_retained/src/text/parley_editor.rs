@@ -24,6 +24,7 @@ use parley::layout::LayoutAccessibility;
 use crate::app::{request_apply_layout, request_layout};
 use crate::text::RangedStyles;
 use crate::text::text_commands::TextCommand;
+use crate::text::whitespace::tab_stop_spacing;
 
 /// Opaque representation of a generation.
 ///
@@ -127,6 +128,8 @@ pub struct PlainEditor {
     // linebreak_dirty: bool,
     // alignment_dirty: bool,
     alignment: Alignment,
+    /// Aligns tabs to a tab stop every this many columns, instead of the width of the tab glyph.
+    tab_width: Option<u8>,
     generation: Generation,
     undo_manager: UndoManager<TextCommand>,
 }
@@ -149,6 +152,7 @@ impl Default for PlainEditor {
             quantize: false,
             layout_dirty: false,
             alignment: Default::default(),
+            tab_width: None,
             generation: Default::default(),
             undo_manager: UndoManager::new(),
         }
@@ -173,6 +177,7 @@ impl PlainEditor {
             quantize: true,
             layout_dirty: true,
             alignment: Alignment::Start,
+            tab_width: None,
             // We don't use the `default` value to start with, as our consumers
             // will choose to use that as their initial value, but will probably need
             // to redraw if they haven't already.
@@ -572,7 +577,6 @@ impl PlainEditorDriver<'_> {
     /// Move the cursor to a byte index.
     ///
     /// No-op if index is not a char boundary.
-    #[allow(dead_code)]
     pub fn move_to_byte(&mut self, index: usize) {
         if self.editor.buffer.is_char_boundary(index) {
             self.refresh_layout();
@@ -1117,6 +1121,14 @@ impl PlainEditor {
         self.ranged_styles = ranged_styles;
     }
 
+    /// Sets the number of columns between tab stops, or `None` to leave tabs as wide as their glyph.
+    pub fn set_tab_width(&mut self, tab_width: Option<u8>) {
+        if self.tab_width != tab_width {
+            self.tab_width = tab_width;
+            self.layout_dirty = true;
+        }
+    }
+
     /// Whether the editor is currently in IME composing mode.
     pub fn is_composing(&self) -> bool {
         self.compose.is_some()
@@ -1286,6 +1298,16 @@ impl PlainEditor {
 
     /// Update the layout.
     fn update_layout(&mut self, font_cx: &mut FontContext, layout_cx: &mut LayoutContext<ColorBrush>) {
+        let tab_spacing = match self.tab_width {
+            Some(tab_width) if self.buffer.contains('\t') => {
+                let without = self.measure(font_cx, layout_cx, "xx");
+                let space_advance = self.measure(font_cx, layout_cx, "x x") - without;
+                let tab_advance = self.measure(font_cx, layout_cx, "x\tx") - without;
+                tab_stop_spacing(&self.buffer, tab_width, space_advance, tab_advance)
+            }
+            _ => Vec::new(),
+        };
+
         let mut builder = layout_cx.ranged_builder(font_cx, &self.buffer, self.scale as f32, self.quantize);
         for prop in self.default_style.inner().values() {
             builder.push_default(prop.to_owned());
@@ -1297,6 +1319,11 @@ impl PlainEditor {
             }
         }
 
+        // Widens the tabs up to their tab stops.
+        for (range, spacing) in tab_spacing {
+            builder.push(StyleProperty::LetterSpacing(spacing), range);
+        }
+
         if let Some(preedit_range) = &self.compose {
             builder.push(StyleProperty::Underline(true), preedit_range.clone());
         }
@@ -1308,6 +1335,17 @@ impl PlainEditor {
         self.generation.nudge();
     }
 
+    /// The width of `text` laid out on one line in the default style.
+    fn measure(&self, font_cx: &mut FontContext, layout_cx: &mut LayoutContext<ColorBrush>, text: &str) -> f32 {
+        let mut builder = layout_cx.ranged_builder(font_cx, text, self.scale as f32, self.quantize);
+        for prop in self.default_style.inner().values() {
+            builder.push_default(prop.to_owned());
+        }
+        let mut layout: Layout<ColorBrush> = builder.build(text);
+        layout.break_all_lines(None);
+        layout.width()
+    }
+
     #[cfg(all(feature = "accesskit", not(target_arch = "wasm32")))]
     /// Perform an accessibility update, assuming that the layout is valid.
     ///
@@ -0,0 +1,141 @@
+//! Spell checking for text inputs.
+//!
+//! A [`TextInput`](crate::elements::TextInput) with a [`SpellChecker`] underlines the words the checker doesn't know,
+//! and sends [`SpellingSuggestions`] when one of them is right clicked. Any spell checker can be plugged in, like the
+//! one of the system, and the `hunspell` feature adds [`HunspellSpellChecker`].
+
+use std::ops::Range;
+
+/// Checks the spelling of single words.
+pub trait SpellChecker {
+    /// Whether `word` is spelled correctly.
+    fn is_correct(&self, word: &str) -> bool;
+
+    /// Returns the words `word` may have been meant to be, best first.
+    fn suggestions(&self, word: &str) -> Vec<String>;
+}
+
+/// The suggestions for a misspelled word, sent when it is right clicked.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpellingSuggestions {
+    /// The byte range of the word in the text.
+    pub range: Range<usize>,
+    pub word: String,
+    pub suggestions: Vec<String>,
+}
+
+/// Returns the byte ranges of the words in `text`.
+///
+/// A word is a run of letters and digits, which may have apostrophes inside, like "don't".
+pub fn words(text: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut end = 0;
+    for (index, character) in text.char_indices() {
+        if character.is_alphanumeric() {
+            start.get_or_insert(index);
+            end = index + character.len_utf8();
+        } else if start.is_some() && is_apostrophe(character) {
+            // Only kept if a letter follows, which moves the end past it.
+        } else if let Some(word_start) = start.take() {
+            words.push(word_start..end);
+        }
+    }
+    if let Some(word_start) = start {
+        words.push(word_start..end);
+    }
+    words
+}
+
+/// Returns the byte ranges of the words in `text` that `checker` says are misspelled.
+///
+/// Words with digits, like "h264", are skipped.
+pub fn misspelled_ranges(checker: &dyn SpellChecker, text: &str) -> Vec<Range<usize>> {
+    words(text)
+        .into_iter()
+        .filter(|range| {
+            let word = &text[range.clone()];
+            !word.chars().any(|character| character.is_numeric()) && !checker.is_correct(word)
+        })
+        .collect()
+}
+
+fn is_apostrophe(character: char) -> bool {
+    character == '\'' || character == '\u{2019}'
+}
+
+/// A spell checker that reads a Hunspell dictionary, like the ones shipped with LibreOffice and Firefox.
+#[cfg(feature = "hunspell")]
+pub struct HunspellSpellChecker {
+    dictionary: spellbook::Dictionary,
+}
+
+#[cfg(feature = "hunspell")]
+impl HunspellSpellChecker {
+    /// Reads a dictionary from the contents of its `.aff` and `.dic` files.
+    pub fn new(aff: &str, dic: &str) -> Result<Self, String> {
+        let dictionary = spellbook::Dictionary::new(aff, dic).map_err(|error| error.to_string())?;
+        Ok(Self { dictionary })
+    }
+}
+
+#[cfg(feature = "hunspell")]
+impl SpellChecker for HunspellSpellChecker {
+    fn is_correct(&self, word: &str) -> bool {
+        self.dictionary.check(word)
+    }
+
+    fn suggestions(&self, word: &str) -> Vec<String> {
+        let mut suggestions = Vec::new();
+        self.dictionary.suggest(word, &mut suggestions);
+        suggestions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct WordList(&'static [&'static str]);
+
+    impl SpellChecker for WordList {
+        fn is_correct(&self, word: &str) -> bool {
+            self.0.contains(&word.to_lowercase().as_str())
+        }
+
+        fn suggestions(&self, _word: &str) -> Vec<String> {
+            Vec::new()
+        }
+    }
+
+    fn word_texts(text: &str) -> Vec<&str> {
+        words(text).into_iter().map(|range| &text[range]).collect()
+    }
+
+    #[test]
+    fn words_are_split_on_spaces_and_punctuation() {
+        assert_eq!(word_texts("Hello, world! (again)"), vec!["Hello", "world", "again"]);
+    }
+
+    #[test]
+    fn apostrophes_are_kept_inside_words_only() {
+        assert_eq!(word_texts("'don't' rock’n’roll'"), vec!["don't", "rock’n’roll"]);
+    }
+
+    #[test]
+    fn ranges_are_in_bytes() {
+        let text = "café über";
+        assert_eq!(words(text), vec![0..5, 6..11]);
+    }
+
+    #[test]
+    fn only_unknown_words_are_misspelled() {
+        let checker = WordList(&["the", "quick", "fox"]);
+        let text = "The quikc fox h264 jumpd";
+        let misspelled: Vec<&str> = misspelled_ranges(&checker, text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect();
+        assert_eq!(misspelled, vec!["quikc", "jumpd"]);
+    }
+}
@@ -8,7 +8,10 @@ pub fn from_editor(layout: &Layout<ColorBrush>) -> TextRender {
     let mut text_render = TextRender {
         lines: Vec::new(),
         cursor: None,
+        extra_cursors: Vec::new(),
         override_brush: None,
+        contrast: None,
+        selection_text_color: None,
     };
 
     for line in layout.lines() {
@@ -17,6 +20,8 @@ pub fn from_editor(layout: &Layout<ColorBrush>) -> TextRender {
             items: Vec::new(),
             selections: Vec::new(),
             backgrounds: Vec::new(),
+            squiggles: Vec::new(),
+            whitespace_marks: Vec::new(),
             // Note: This needs to be changed when we handle vertical text.
             min_y: metrics.block_min_coord,
             max_y: metrics.block_max_coord,
@@ -0,0 +1,118 @@
+//! Marking invisible characters and soft wraps in text inputs, and aligning tabs to tab stops.
+//!
+//! Marks are faint glyphs drawn over the text in a lighter shade of its color, set with
+//! [`TextInput::show_whitespace`](crate::elements::TextInput::show_whitespace). The tab width, set with
+//! [`TextInput::tab_width`](crate::elements::TextInput::tab_width), widens each tab up to the next multiple of the
+//! width in columns of a space, so it changes the size of the text too.
+
+use std::ops::Range;
+
+pub use craft_renderer::text_renderer_data::WhitespaceMark;
+
+/// Which invisible characters a text input marks. None are marked by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ShowWhitespace {
+    /// Marks the end of lines broken to fit the width of the input, rather than by a line break in the text.
+    pub wraps: bool,
+    pub tabs: bool,
+    /// Marks the spaces at the end of lines.
+    pub trailing_spaces: bool,
+    pub non_breaking_spaces: bool,
+}
+
+impl ShowWhitespace {
+    /// Marks everything.
+    pub fn all() -> Self {
+        Self {
+            wraps: true,
+            tabs: true,
+            trailing_spaces: true,
+            non_breaking_spaces: true,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// The characters of `text` to mark, as byte ranges. Soft wraps depend on the layout and aren't included.
+pub(crate) fn whitespace_marks(text: &str, show: ShowWhitespace) -> Vec<(Range<usize>, WhitespaceMark)> {
+    let mut marks = Vec::new();
+    let mut line_start = 0;
+    for line in text.split('\n') {
+        let content = line.strip_suffix('\r').unwrap_or(line);
+        let trailing_start = content.trim_end_matches(' ').len();
+        for (offset, character) in content.char_indices() {
+            let range = line_start + offset..line_start + offset + character.len_utf8();
+            let mark = match character {
+                '\t' if show.tabs => WhitespaceMark::Tab,
+                '\u{00A0}' | '\u{202F}' if show.non_breaking_spaces => WhitespaceMark::NonBreakingSpace,
+                ' ' if show.trailing_spaces && offset >= trailing_start => WhitespaceMark::Space,
+                _ => continue,
+            };
+            marks.push((range, mark));
+        }
+        line_start += line.len() + 1;
+    }
+    marks
+}
+
+/// The extra spacing after each tab of `text` that moves what follows it to the next tab stop, every `tab_width`
+/// columns. Characters other than tabs are one column wide, as in a monospaced font.
+pub(crate) fn tab_stop_spacing(
+    text: &str,
+    tab_width: u8,
+    space_advance: f32,
+    tab_advance: f32,
+) -> Vec<(Range<usize>, f32)> {
+    let tab_width = usize::from(tab_width.max(1));
+    let mut spacing = Vec::new();
+    let mut column = 0;
+    for (index, character) in text.char_indices() {
+        match character {
+            '\n' => column = 0,
+            '\t' => {
+                let stop = (column / tab_width + 1) * tab_width;
+                let advance = (stop - column) as f32 * space_advance;
+                spacing.push((index..index + 1, advance - tab_advance));
+                column = stop;
+            }
+            _ => column += 1,
+        }
+    }
+    spacing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_tabs_trailing_spaces_and_non_breaking_spaces() {
+        let text = "a\tb  \nc\u{00A0}d e";
+        let marks = whitespace_marks(text, ShowWhitespace::all());
+        assert_eq!(
+            marks,
+            vec![
+                (1..2, WhitespaceMark::Tab),
+                (3..4, WhitespaceMark::Space),
+                (4..5, WhitespaceMark::Space),
+                (7..9, WhitespaceMark::NonBreakingSpace),
+            ]
+        );
+
+        let tabs_only = ShowWhitespace {
+            tabs: true,
+            ..ShowWhitespace::default()
+        };
+        assert_eq!(whitespace_marks(text, tabs_only), vec![(1..2, WhitespaceMark::Tab)]);
+    }
+
+    #[test]
+    fn tabs_reach_the_next_tab_stop() {
+        // Tabs are 2 pixels wide and spaces 10, with a tab stop every 4 columns.
+        let spacing = tab_stop_spacing("\tab\tc\n\t", 4, 10.0, 2.0);
+        assert_eq!(spacing, vec![(0..1, 38.0), (3..4, 18.0), (6..7, 38.0)]);
+    }
+}
@@ -0,0 +1,194 @@
+//! Updates to the element tree sent from other threads, for data feeds like sockets and sensors.
+//!
+//! Elements can only be used on the GUI thread, so other threads refer to them by [`ElementHandle`] and send their
+//! changes in a [`TreeBatch`]. The batches are applied on the GUI thread once per iteration of the event loop, each in
+//! one go, so a frame never shows half of a batch.
+//!
+//! ```ignore
+//! let label = Text::new("Waiting").get_handle();
+//! let sender = tree_sender();
+//! std::thread::spawn(move || {
+//!     for reading in sensor.readings() {
+//!         sender.send(TreeBatch::new().set_text(label, format!("{reading} °C")));
+//!     }
+//! });
+//! ```
+
+use std::rc::Weak;
+use std::sync::Mutex;
+
+use craft_logging::warn;
+
+use crate::app::ELEMENTS;
+use crate::elements::{AsElement, DynElement, TextInner, TextInputInner};
+use crate::style::Style;
+
+/// The batches sent since the event loop last applied them.
+static PENDING_BATCHES: Mutex<Vec<TreeBatch>> = Mutex::new(Vec::new());
+
+/// Refers to an element from any thread, see [`Element::get_handle`](crate::elements::Element::get_handle).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ElementHandle(pub(crate) u64);
+
+/// A change to the element tree.
+pub enum TreeMutation {
+    /// Sets the text of a [`Text`](crate::elements::Text) or [`TextInput`](crate::elements::TextInput).
+    SetText(ElementHandle, String),
+    /// Builds an element on the GUI thread and adds it as the last child of an element.
+    PushChild(ElementHandle, Box<dyn FnOnce() -> DynElement + Send>),
+    RemoveAllChildren(ElementHandle),
+    /// Changes the style of an element.
+    UpdateStyle(ElementHandle, Box<dyn FnOnce(&mut Style) + Send>),
+    /// Calls a function with an element on the GUI thread, for changes not covered here.
+    Update(ElementHandle, Box<dyn FnOnce(DynElement) + Send>),
+}
+
+/// Changes to the element tree that are applied together.
+#[derive(Default)]
+pub struct TreeBatch {
+    mutations: Vec<TreeMutation>,
+}
+
+impl TreeBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mutation(mut self, mutation: TreeMutation) -> Self {
+        self.mutations.push(mutation);
+        self
+    }
+
+    pub fn set_text(self, element: ElementHandle, text: impl Into<String>) -> Self {
+        self.mutation(TreeMutation::SetText(element, text.into()))
+    }
+
+    /// Adds the element `build` returns as the last child of `parent`. `build` is called on the GUI thread.
+    pub fn push_child<E: AsElement>(self, parent: ElementHandle, build: impl FnOnce() -> E + Send + 'static) -> Self {
+        self.mutation(TreeMutation::PushChild(
+            parent,
+            Box::new(move || DynElement::new(build().as_element_rc())),
+        ))
+    }
+
+    pub fn remove_all_children(self, element: ElementHandle) -> Self {
+        self.mutation(TreeMutation::RemoveAllChildren(element))
+    }
+
+    pub fn update_style(self, element: ElementHandle, update: impl FnOnce(&mut Style) + Send + 'static) -> Self {
+        self.mutation(TreeMutation::UpdateStyle(element, Box::new(update)))
+    }
+
+    pub fn update(self, element: ElementHandle, update: impl FnOnce(DynElement) + Send + 'static) -> Self {
+        self.mutation(TreeMutation::Update(element, Box::new(update)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mutations.is_empty()
+    }
+}
+
+/// Sends [`TreeBatch`]es to the GUI thread. Can be cloned and moved to any thread.
+#[derive(Clone, Default)]
+pub struct TreeSender {
+    _private: (),
+}
+
+impl TreeSender {
+    /// Queues a batch, which is applied at the next iteration of the event loop.
+    pub fn send(&self, batch: TreeBatch) {
+        if batch.is_empty() {
+            return;
+        }
+        PENDING_BATCHES.lock().unwrap().push(batch);
+    }
+}
+
+/// Returns a sender for updating the element tree from other threads.
+pub fn tree_sender() -> TreeSender {
+    TreeSender::default()
+}
+
+/// Applies the batches sent from other threads, in the order they were sent. Returns true if any were applied.
+///
+/// Called once per iteration of the event loop.
+pub(crate) fn apply_tree_updates() -> bool {
+    let batches = std::mem::take(&mut *PENDING_BATCHES.lock().unwrap());
+    let applied = !batches.is_empty();
+    for batch in batches {
+        for mutation in batch.mutations {
+            apply_mutation(mutation);
+        }
+    }
+    applied
+}
+
+fn apply_mutation(mutation: TreeMutation) {
+    let handle = match &mutation {
+        TreeMutation::SetText(handle, _)
+        | TreeMutation::PushChild(handle, _)
+        | TreeMutation::RemoveAllChildren(handle)
+        | TreeMutation::UpdateStyle(handle, _)
+        | TreeMutation::Update(handle, _) => *handle,
+    };
+    // The element may have been removed since the batch was sent.
+    let Some(element) = ELEMENTS.with_borrow(|elements| elements.get(handle.0).and_then(Weak::upgrade)) else {
+        return;
+    };
+
+    match mutation {
+        TreeMutation::SetText(_, text) => {
+            let mut element = element.borrow_mut();
+            if let Some(text_element) = element.as_any_mut().downcast_mut::<TextInner>() {
+                text_element.set_text(&text);
+            } else if let Some(text_input) = element.as_any_mut().downcast_mut::<TextInputInner>() {
+                text_input.set_text(&text);
+            } else {
+                warn!("Can't set the text of element {}, which isn't a text or text input", handle.0);
+            }
+        }
+        TreeMutation::PushChild(_, build) => {
+            let child = build();
+            element.borrow_mut().push(child.inner);
+        }
+        TreeMutation::RemoveAllChildren(_) => element.borrow_mut().remove_all_children(),
+        TreeMutation::UpdateStyle(_, update) => {
+            let mut element = element.borrow_mut();
+            let mut style = element.style().clone();
+            update(&mut style);
+            element.set_style(style);
+        }
+        TreeMutation::Update(_, update) => update(DynElement::new(element)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::{Container, Element, Text};
+    use crate::style::Display;
+
+    #[test]
+    fn batches_are_applied_on_the_gui_thread() {
+        let list = Container::new();
+        let label = Text::new("Waiting");
+        let (list_handle, label_handle) = (list.get_handle(), label.get_handle());
+
+        let sender = tree_sender();
+        std::thread::spawn(move || {
+            sender.send(
+                TreeBatch::new()
+                    .set_text(label_handle, "21 °C")
+                    .push_child(list_handle, || Text::new("Reading 1"))
+                    .update_style(list_handle, |style| style.set_display(Display::Flex)),
+            );
+        })
+        .join()
+        .unwrap();
+
+        assert!(apply_tree_updates());
+        assert_eq!(label.get_text(), "21 °C");
+        assert_eq!(list.get_children().len(), 1);
+        assert!(!apply_tree_updates());
+    }
+}
@@ -0,0 +1,398 @@
+//! Checks for new versions of the app, and downloads their installers for the app to run.
+//!
+//! ```ignore
+//! update::start_update_checks(UpdateOptions::new(
+//!     UpdateSource::GitHubReleases { owner: "example".to_string(), repo: "notes".to_string() },
+//!     env!("CARGO_PKG_VERSION"),
+//! ).asset("windows-x64.msi"));
+//!
+//! window.on_update_available(Rc::new(|_event, update| {
+//!     if ask_to_update(&update.version, &update.notes) {
+//!         update::download_update(update);
+//!     }
+//! }));
+//! window.on_update_downloaded(Rc::new(|_event, download| {
+//!     if let UpdateDownload::Finished(installer) = download {
+//!         run_installer_and_exit(installer);
+//!     }
+//! }));
+//! ```
+//!
+//! The checks run on their own thread, right away and then every [`UpdateOptions::interval`]. Every window is sent a
+//! [`EventKind::UpdateAvailable`] event the first time a newer version was found, and a
+//! [`EventKind::UpdateDownloaded`] event when its installer was downloaded. Applying the update is up to the app, as
+//! installers differ between platforms.
+//!
+//! Only `https` URLs are used, and an installer is only reported as downloaded when its SHA-256 checksum matches the
+//! one published with the version. It is downloaded to a new directory of the temporary directory that only the user
+//! can access, so other users can't replace it before it runs.
+//!
+//! [`EventKind::UpdateAvailable`]: crate::events::EventKind::UpdateAvailable
+//! [`EventKind::UpdateDownloaded`]: crate::events::EventKind::UpdateDownloaded
+
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+
+use craft_logging::warn;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::events::{UpdateDownload, UpdateInfo};
+
+thread_local! {
+    static UPDATES: RefCell<Option<UpdateChecker>> = const { RefCell::new(None) };
+}
+
+/// Where new versions are published.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UpdateSource {
+    /// An `https` URL that responds with the latest version as JSON:
+    /// `{ "version": "1.2.0", "notes": "...", "url": "...", "sha256": "..." }`, where `url` is the installer to
+    /// download and `sha256` its checksum in hex. `notes` is optional, and `url` and `sha256` are only needed to
+    /// download the installer.
+    Endpoint(String),
+    /// The latest release of a GitHub repository. Its tag is the version, and its description the notes. The
+    /// checksum of the installer is the digest GitHub publishes for the asset.
+    GitHubReleases { owner: String, repo: String },
+}
+
+/// How updates are checked for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UpdateOptions {
+    pub source: UpdateSource,
+    /// The version of the running app, like `1.1.0`. Only newer versions are reported.
+    pub current_version: String,
+    /// How long to wait between checks.
+    pub interval: Duration,
+    /// Part of the name of the release asset to download from GitHub, like `windows-x64.msi`. Without it, the
+    /// first asset is downloaded.
+    pub asset: Option<String>,
+}
+
+impl UpdateOptions {
+    pub fn new(source: UpdateSource, current_version: &str) -> Self {
+        Self {
+            source,
+            current_version: current_version.to_string(),
+            interval: Duration::from_secs(6 * 60 * 60),
+            asset: None,
+        }
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn asset(mut self, asset: &str) -> Self {
+        self.asset = Some(asset.to_string());
+        self
+    }
+}
+
+/// What the update thread found, for the windows.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum UpdateMessage {
+    Available(UpdateInfo),
+    Downloaded(UpdateDownload),
+}
+
+enum Command {
+    Check,
+    Download { url: String, sha256: Option<String> },
+}
+
+struct UpdateChecker {
+    commands: Sender<Command>,
+    messages: Receiver<UpdateMessage>,
+}
+
+/// Starts checking for updates, replacing the checks started before.
+pub fn start_update_checks(options: UpdateOptions) {
+    let (commands, command_receiver) = mpsc::channel();
+    let (message_sender, messages) = mpsc::channel();
+    let spawned = std::thread::Builder::new()
+        .name("craft-update-check".to_string())
+        .spawn(move || run_checks(&options, &command_receiver, &message_sender));
+    if let Err(error) = spawned {
+        warn!("Failed to start the update check thread: {error}");
+    }
+    // Dropping the old checker closes its channel, which stops its thread.
+    UPDATES.set(Some(UpdateChecker { commands, messages }));
+}
+
+/// Stops checking for updates.
+pub fn stop_update_checks() {
+    UPDATES.set(None);
+}
+
+/// Checks for an update now, instead of waiting for the next check. Does nothing before
+/// [`start_update_checks`].
+pub fn check_for_updates() {
+    UPDATES.with_borrow(|updates| {
+        if let Some(updates) = updates {
+            updates.commands.send(Command::Check).ok();
+        }
+    });
+}
+
+/// Downloads the installer of an update and verifies its checksum. Every window is sent a
+/// [`EventKind::UpdateDownloaded`](crate::events::EventKind::UpdateDownloaded) event when it is done, which fails if
+/// the update has no checksum.
+pub fn download_update(update: &UpdateInfo) {
+    UPDATES.with_borrow(|updates| {
+        let (Some(updates), Some(url)) = (updates, &update.download_url) else {
+            return;
+        };
+        let command = Command::Download {
+            url: url.clone(),
+            sha256: update.sha256.clone(),
+        };
+        updates.commands.send(command).ok();
+    });
+}
+
+/// Returns what the update thread found since the last call. Called once per iteration of the event loop.
+pub(crate) fn poll_updates() -> Vec<UpdateMessage> {
+    UPDATES.with_borrow(|updates| {
+        updates
+            .as_ref()
+            .map(|updates| updates.messages.try_iter().collect())
+            .unwrap_or_default()
+    })
+}
+
+/// Checks right away and then at every interval, and runs the commands in between, until the checker is dropped.
+fn run_checks(options: &UpdateOptions, commands: &Receiver<Command>, messages: &Sender<UpdateMessage>) {
+    let client = match reqwest::blocking::Client::builder()
+        .user_agent("craft-update-check")
+        // Redirects to other schemes are refused too.
+        .https_only(true)
+        .build()
+    {
+        Ok(client) => client,
+        Err(error) => {
+            warn!("Failed to start checking for updates: {error}");
+            return;
+        }
+    };
+    // The newest version reported, so it is only reported once.
+    let mut reported: Option<String> = None;
+    let mut command = Command::Check;
+    loop {
+        let message = match command {
+            Command::Check => match check(&client, options) {
+                Ok(Some(update)) if reported.as_ref() != Some(&update.version) => {
+                    reported = Some(update.version.clone());
+                    Some(UpdateMessage::Available(update))
+                }
+                Ok(_) => None,
+                Err(error) => {
+                    warn!("Failed to check for updates: {error}");
+                    None
+                }
+            },
+            Command::Download { url, sha256 } => {
+                let downloaded = sha256
+                    .ok_or_else(|| "The update has no SHA-256 checksum".to_string())
+                    .and_then(|sha256| download(&client, &url, &sha256));
+                Some(UpdateMessage::Downloaded(match downloaded {
+                    Ok(path) => UpdateDownload::Finished(path),
+                    Err(error) => UpdateDownload::Failed(error),
+                }))
+            }
+        };
+        if let Some(message) = message
+            && messages.send(message).is_err()
+        {
+            return;
+        }
+        command = match commands.recv_timeout(options.interval) {
+            Ok(command) => command,
+            Err(RecvTimeoutError::Timeout) => Command::Check,
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+    }
+}
+
+/// Returns the latest version if it is newer than the running one.
+fn check(client: &reqwest::blocking::Client, options: &UpdateOptions) -> Result<Option<UpdateInfo>, String> {
+    let url = match &options.source {
+        UpdateSource::Endpoint(url) => url.clone(),
+        UpdateSource::GitHubReleases { owner, repo } => {
+            format!("https://api.github.com/repos/{owner}/{repo}/releases/latest")
+        }
+    };
+    require_https(&url)?;
+    let response = client
+        .get(url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.text())
+        .map_err(|error| error.to_string())?;
+    let json: Value = serde_json::from_str(&response).map_err(|error| error.to_string())?;
+    let latest = match &options.source {
+        UpdateSource::Endpoint(_) => parse_endpoint(&json),
+        UpdateSource::GitHubReleases { .. } => parse_github_release(&json, options.asset.as_deref()),
+    }
+    .ok_or_else(|| "The response has no version".to_string())?;
+    Ok((compare_versions(&latest.version, &options.current_version) == Ordering::Greater).then_some(latest))
+}
+
+/// Downloads the installer at `url` to a new private directory of the temporary directory, and checks that its
+/// SHA-256 checksum is `sha256`.
+fn download(client: &reqwest::blocking::Client, url: &str, sha256: &str) -> Result<PathBuf, String> {
+    require_https(url)?;
+    let name = url
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.split(['?', '#']).next())
+        .filter(|name| !name.is_empty())
+        .unwrap_or("update");
+    let mut response = client
+        .get(url)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map_err(|error| error.to_string())?;
+    // Removed again when the download fails.
+    let directory = tempfile::Builder::new()
+        .prefix("craft-update-")
+        .tempdir()
+        .map_err(|error| error.to_string())?;
+    let path = directory.path().join(name);
+    let mut file = File::create_new(&path).map_err(|error| error.to_string())?;
+    response.copy_to(&mut file).map_err(|error| error.to_string())?;
+    drop(file);
+
+    let mut hasher = Sha256::new();
+    File::open(&path)
+        .and_then(|mut file| std::io::copy(&mut file, &mut hasher))
+        .map_err(|error| error.to_string())?;
+    let actual = to_hex(&hasher.finalize());
+    if !actual.eq_ignore_ascii_case(sha256.trim()) {
+        return Err(format!(
+            "The checksum of the update is {actual}, but {sha256} was published"
+        ));
+    }
+    Ok(directory.keep().join(name))
+}
+
+/// Fails for URLs that aren't `https`, so updates can't be changed on the way.
+fn require_https(url: &str) -> Result<(), String> {
+    let scheme = url.split_once("://").map(|(scheme, _)| scheme);
+    if scheme.is_some_and(|scheme| scheme.eq_ignore_ascii_case("https")) {
+        Ok(())
+    } else {
+        Err(format!("Updates are only downloaded over https, not from {url}"))
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn parse_endpoint(json: &Value) -> Option<UpdateInfo> {
+    Some(UpdateInfo {
+        version: json["version"].as_str()?.to_string(),
+        notes: json["notes"].as_str().unwrap_or_default().to_string(),
+        download_url: json["url"].as_str().map(str::to_string),
+        sha256: json["sha256"].as_str().map(str::to_string),
+    })
+}
+
+fn parse_github_release(json: &Value, asset: Option<&str>) -> Option<UpdateInfo> {
+    let asset = json["assets"].as_array().into_iter().flatten().find(|candidate| {
+        asset.is_none_or(|asset| candidate["name"].as_str().is_some_and(|name| name.contains(asset)))
+    });
+    Some(UpdateInfo {
+        version: json["tag_name"].as_str()?.to_string(),
+        notes: json["body"].as_str().unwrap_or_default().to_string(),
+        download_url: asset
+            .and_then(|asset| asset["browser_download_url"].as_str())
+            .map(str::to_string),
+        // Like `sha256:2cf24dba...`.
+        sha256: asset
+            .and_then(|asset| asset["digest"].as_str())
+            .and_then(|digest| digest.strip_prefix("sha256:"))
+            .map(str::to_string),
+    })
+}
+
+/// Compares versions like `v1.10.0` and `1.9.2` by their numbers. A pre-release like `1.2.0-beta.1` comes before its
+/// release.
+pub(crate) fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |version: &str| {
+        let version = version.trim().trim_start_matches(['v', 'V']);
+        let (release, pre_release) = match version.split_once('-') {
+            Some((release, pre_release)) => (release, Some(pre_release.to_string())),
+            None => (version, None),
+        };
+        let numbers: Vec<u64> = release.split('.').map(|part| part.parse().unwrap_or(0)).collect();
+        (numbers, pre_release)
+    };
+    let (a_numbers, a_pre_release) = parse(a);
+    let (b_numbers, b_pre_release) = parse(b);
+    let length = a_numbers.len().max(b_numbers.len());
+    let number = |numbers: &[u64], index: usize| numbers.get(index).copied().unwrap_or(0);
+    (0..length)
+        .map(|index| number(&a_numbers, index).cmp(&number(&b_numbers, index)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or_else(|| match (a_pre_release, b_pre_release) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => a.cmp(&b),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_are_compared_by_number() {
+        assert_eq!(compare_versions("v1.10.0", "1.9.2"), Ordering::Greater);
+        assert_eq!(compare_versions("1.2", "1.2.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.2.0-beta.1", "1.2.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.2.0", "1.2.1"), Ordering::Less);
+    }
+
+    #[test]
+    fn the_matching_asset_of_a_release_is_downloaded() {
+        let release = serde_json::json!({
+            "tag_name": "v2.0.0",
+            "body": "Faster startup",
+            "assets": [
+                { "name": "notes-macos.dmg", "browser_download_url": "https://example.com/notes-macos.dmg" },
+                {
+                    "name": "notes-windows-x64.msi",
+                    "browser_download_url": "https://example.com/notes.msi",
+                    "digest": "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824",
+                },
+            ],
+        });
+        let update = parse_github_release(&release, Some("windows-x64")).unwrap();
+        assert_eq!(update.version, "v2.0.0");
+        assert_eq!(update.notes, "Faster startup");
+        assert_eq!(update.download_url.as_deref(), Some("https://example.com/notes.msi"));
+        assert_eq!(
+            update.sha256.as_deref(),
+            Some("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+        );
+    }
+
+    #[test]
+    fn updates_are_only_downloaded_over_https() {
+        assert!(require_https("https://example.com/notes.msi").is_ok());
+        assert!(require_https("http://example.com/notes.msi").is_err());
+        assert!(require_https("file:///tmp/notes.msi").is_err());
+        assert_eq!(
+            to_hex(&Sha256::digest(b"hello")),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+    }
+}
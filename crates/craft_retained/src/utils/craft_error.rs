@@ -2,4 +2,6 @@
 pub enum CraftError {
     /// Thrown when an element cannot be found.
     ElementNotFound,
+    /// Thrown when an element that doesn't accept children is given one, e.g. a mask element.
+    ChildrenNotSupported,
 }
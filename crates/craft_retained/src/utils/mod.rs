@@ -1,3 +1,4 @@
 pub mod cloneable_any;
 pub(crate) mod craft_error;
+pub(crate) mod random;
 pub mod style_helpers;
@@ -0,0 +1,41 @@
+//! Random numbers for effects and tests that must be repeatable from a seed.
+
+use std::ops::RangeInclusive;
+
+/// A small and fast random number generator (xorshift64*), good enough for effects and tests.
+#[derive(Clone, Debug)]
+pub(crate) struct Random(u64);
+
+impl Random {
+    pub(crate) fn new(seed: u64) -> Self {
+        // The state must not be zero.
+        Self(seed | 1)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Returns a number from 0 up to but not including 1.
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub(crate) fn next_f32(&mut self) -> f32 {
+        self.next_f64() as f32
+    }
+
+    pub(crate) fn in_range(&mut self, range: &RangeInclusive<f32>) -> f32 {
+        range.start() + (range.end() - range.start()) * self.next_f32()
+    }
+
+    pub(crate) fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+        items.get((self.next_u64() % items.len() as u64) as usize)
+    }
+}
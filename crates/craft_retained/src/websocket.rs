@@ -0,0 +1,307 @@
+//! WebSocket connections that send what they receive to an element, and reconnect when the connection is lost.
+//!
+//! ```ignore
+//! let prices = Text::new("Connecting").on_web_socket(Rc::new(|event, web_socket| {
+//!     if let WebSocketEventKind::Message(WebSocketMessage::Text(price)) = &web_socket.kind {
+//!         event.target.borrow_mut().as_any_mut().downcast_mut::<TextInner>().unwrap().set_text(price);
+//!     }
+//! }));
+//! let socket = prices.connect_web_socket("wss://example.com/prices", WebSocketOptions::default());
+//! socket.send(WebSocketMessage::Text("subscribe".to_string()));
+//! ```
+//!
+//! Every connection runs on its own thread. It is closed with [`WebSocket::close`], or when the element it sends to
+//! is dropped.
+
+use std::cell::{Cell, RefCell};
+use std::net::TcpStream;
+use std::rc::Weak;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
+use std::time::{Duration, Instant};
+
+use craft_logging::warn;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket as Socket};
+
+use crate::app::queue_event;
+use crate::elements::ElementInternals;
+use crate::events::{Event, EventKind, WebSocketEvent, WebSocketEventKind, WebSocketMessage};
+
+/// How long a read waits for a message before the connection checks for messages to send.
+const READ_TIMEOUT: Duration = Duration::from_millis(50);
+
+thread_local! {
+    static CONNECTIONS: RefCell<Vec<Connection>> = const { RefCell::new(Vec::new()) };
+    static NEXT_CONNECTION_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// How a connection is kept up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WebSocketOptions {
+    /// Whether to connect again when the connection fails or is lost.
+    pub reconnect: bool,
+    /// How long to wait before the first attempt to connect again. The wait doubles with every failed attempt.
+    pub initial_backoff: Duration,
+    /// The longest wait between attempts to connect again.
+    pub max_backoff: Duration,
+}
+
+impl Default for WebSocketOptions {
+    fn default() -> Self {
+        Self {
+            reconnect: true,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+/// A handle to a connection opened with
+/// [`Element::connect_web_socket`](crate::elements::Element::connect_web_socket).
+///
+/// Dropping the handle doesn't close the connection.
+#[derive(Clone)]
+pub struct WebSocket {
+    id: u64,
+    commands: Sender<Command>,
+}
+
+impl WebSocket {
+    /// The id in the [`WebSocketEvent`]s of this connection.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Sends a message. Messages sent while the connection is down are dropped. Returns false if the connection was
+    /// closed.
+    pub fn send(&self, message: WebSocketMessage) -> bool {
+        self.commands.send(Command::Send(message)).is_ok()
+    }
+
+    /// Closes the connection. No more events are sent for it.
+    pub fn close(&self) {
+        self.commands.send(Command::Close).ok();
+    }
+}
+
+enum Command {
+    Send(WebSocketMessage),
+    Close,
+}
+
+struct Connection {
+    id: u64,
+    target: Weak<RefCell<dyn ElementInternals>>,
+    events: Receiver<WebSocketEventKind>,
+    commands: Sender<Command>,
+}
+
+/// The time to wait before each attempt to connect again.
+pub(crate) struct Backoff {
+    options: WebSocketOptions,
+    next: Duration,
+}
+
+impl Backoff {
+    pub(crate) fn new(options: WebSocketOptions) -> Self {
+        Self {
+            options,
+            next: options.initial_backoff,
+        }
+    }
+
+    /// Returns the time to wait before the next attempt.
+    pub(crate) fn next_delay(&mut self) -> Duration {
+        let delay = self.next.min(self.options.max_backoff);
+        self.next = delay.saturating_mul(2);
+        delay
+    }
+
+    /// Starts over after a connection succeeded.
+    pub(crate) fn reset(&mut self) {
+        self.next = self.options.initial_backoff;
+    }
+}
+
+/// Opens a connection that sends its events to `target`.
+pub(crate) fn connect_for(
+    url: &str,
+    options: WebSocketOptions,
+    target: Weak<RefCell<dyn ElementInternals>>,
+) -> WebSocket {
+    let id = NEXT_CONNECTION_ID.get();
+    NEXT_CONNECTION_ID.set(id + 1);
+
+    let (event_sender, events) = mpsc::channel();
+    let (commands, command_receiver) = mpsc::channel();
+    let url = url.to_string();
+    let spawned = std::thread::Builder::new()
+        .name("craft-websocket".to_string())
+        .spawn(move || run_connection(&url, options, &event_sender, &command_receiver));
+    if let Err(error) = spawned {
+        warn!("Failed to start the WebSocket thread: {error}");
+    }
+
+    CONNECTIONS.with_borrow_mut(|connections| {
+        connections.push(Connection {
+            id,
+            target,
+            events,
+            commands: commands.clone(),
+        })
+    });
+    WebSocket { id, commands }
+}
+
+/// Queues the events the connections received for their elements. Returns true if any were queued.
+///
+/// Called once per iteration of the event loop.
+pub(crate) fn poll_web_sockets() -> bool {
+    CONNECTIONS.with_borrow_mut(|connections| {
+        let mut queued = false;
+        connections.retain(|connection| {
+            let Some(target) = connection.target.upgrade() else {
+                connection.commands.send(Command::Close).ok();
+                return false;
+            };
+            loop {
+                match connection.events.try_recv() {
+                    Ok(kind) => {
+                        let event = WebSocketEvent {
+                            connection: connection.id,
+                            kind,
+                        };
+                        queue_event(Event::new(target.clone()), EventKind::WebSocket(event));
+                        queued = true;
+                    }
+                    Err(TryRecvError::Empty) => return true,
+                    // The connection was closed.
+                    Err(TryRecvError::Disconnected) => return false,
+                }
+            }
+        });
+        queued
+    })
+}
+
+/// Connects, and connects again after the connection is lost, until the connection is closed.
+fn run_connection(
+    url: &str,
+    options: WebSocketOptions,
+    events: &Sender<WebSocketEventKind>,
+    commands: &Receiver<Command>,
+) {
+    let mut backoff = Backoff::new(options);
+    loop {
+        let error = match tungstenite::connect(url) {
+            Ok((mut socket, _response)) => {
+                backoff.reset();
+                if events.send(WebSocketEventKind::Connected).is_err() {
+                    return;
+                }
+                match serve(&mut socket, events, commands) {
+                    Ok(()) => return,
+                    Err(error) => error,
+                }
+            }
+            Err(error) => error.to_string(),
+        };
+
+        let retry_in = options.reconnect.then(|| backoff.next_delay());
+        if events.send(WebSocketEventKind::Disconnected { error, retry_in }).is_err() {
+            return;
+        }
+        let Some(retry_in) = retry_in else {
+            return;
+        };
+        if !wait_to_reconnect(retry_in, commands) {
+            return;
+        }
+    }
+}
+
+/// Sends and receives messages until the connection is closed, which returns `Ok`, or lost.
+fn serve(
+    socket: &mut Socket<MaybeTlsStream<TcpStream>>,
+    events: &Sender<WebSocketEventKind>,
+    commands: &Receiver<Command>,
+) -> Result<(), String> {
+    // Reads time out, so messages to send don't wait for a message to arrive.
+    let timeout = match socket.get_mut() {
+        MaybeTlsStream::Plain(stream) => stream.set_read_timeout(Some(READ_TIMEOUT)),
+        MaybeTlsStream::NativeTls(stream) => stream.get_mut().set_read_timeout(Some(READ_TIMEOUT)),
+        _ => Ok(()),
+    };
+    timeout.map_err(|error| error.to_string())?;
+
+    loop {
+        loop {
+            match commands.try_recv() {
+                Ok(Command::Send(message)) => {
+                    let message = match message {
+                        WebSocketMessage::Text(text) => Message::text(text),
+                        WebSocketMessage::Binary(bytes) => Message::binary(bytes),
+                    };
+                    socket.send(message).map_err(|error| error.to_string())?;
+                }
+                Ok(Command::Close) | Err(TryRecvError::Disconnected) => {
+                    socket.close(None).ok();
+                    socket.flush().ok();
+                    return Ok(());
+                }
+                Err(TryRecvError::Empty) => break,
+            }
+        }
+
+        let message = match socket.read() {
+            Ok(Message::Text(text)) => WebSocketMessage::Text(text.as_str().to_string()),
+            Ok(Message::Binary(bytes)) => WebSocketMessage::Binary(bytes.to_vec()),
+            Ok(Message::Close(_)) => return Err("The server closed the connection".to_string()),
+            // Pings are answered by tungstenite.
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(error))
+                if matches!(error.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) =>
+            {
+                continue;
+            }
+            Err(error) => return Err(error.to_string()),
+        };
+        // The element is gone when the events can't be sent.
+        if events.send(WebSocketEventKind::Message(message)).is_err() {
+            socket.close(None).ok();
+            return Ok(());
+        }
+    }
+}
+
+/// Waits before connecting again. Messages to send are dropped while waiting. Returns false if the connection was
+/// closed.
+fn wait_to_reconnect(delay: Duration, commands: &Receiver<Command>) -> bool {
+    let deadline = Instant::now() + delay;
+    loop {
+        match commands.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(Command::Send(_)) => {}
+            Ok(Command::Close) | Err(RecvTimeoutError::Disconnected) => return false,
+            Err(RecvTimeoutError::Timeout) => return true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let mut backoff = Backoff::new(WebSocketOptions {
+            reconnect: true,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+        });
+        let delays: Vec<u64> = (0..5).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 5, 5]);
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+    }
+}
@@ -1,11 +1,18 @@
 use std::cell::RefCell;
 use std::rc::Rc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time;
+#[cfg(target_arch = "wasm32")]
+use web_time as time;
 
 use winit::event_loop::ActiveEventLoop;
 use winit::window::WindowId;
 use craft_renderer::blank_renderer::BlankRenderer;
 use crate::app::{App, TAFFY_TREE};
+use craft_resource_manager::ResourceId;
+use crate::elements::layer_cache::invalidate_resource_users;
 use crate::elements::{ElementData, Window};
+use crate::scene::scene_changed;
 
 pub(crate) struct WindowManager {
     windows: Vec<Window>,
@@ -31,11 +38,31 @@ impl WindowManager {
         None
     }
 
+    /// Returns the position of a window in the order the windows were added.
+    pub(crate) fn index_of(&self, window_id: WindowId) -> Option<usize> {
+        self.windows.iter().position(|window| {
+            window
+                .winit_window()
+                .is_some_and(|winit_window| winit_window.id() == window_id)
+        })
+    }
+
+    pub(crate) fn windows(&self) -> &[Window] {
+        &self.windows
+    }
+
+    pub(crate) fn get_window_by_index(&self, index: usize) -> Option<Window> {
+        self.windows.get(index).cloned()
+    }
+
     // Improve this.
+    /// Redraws each window, e.g. after work scheduled for later ran. The scene is marked as changed, as the work may
+    /// have changed state that isn't tracked.
     pub(crate) fn redraw_all(&mut self, craft_app: &mut App) {
         if !craft_app.active {
             return;
         }
+        scene_changed();
 
         // Create windows that were created during the program run.
         for window_element in &self.windows {
@@ -45,6 +72,13 @@ impl WindowManager {
         }
     }
 
+    /// Invalidates the layers of the elements that draw a resource that finished loading.
+    pub(crate) fn invalidate_resource_users(&self, resource_id: &ResourceId) {
+        for window_element in &self.windows {
+            invalidate_resource_users(window_element.inner.clone(), resource_id);
+        }
+    }
+
     /// Dirties all taffy nodes and redraws each window.
     pub(crate) fn dirty_and_redraw_all_windows(&mut self, craft_app: &mut App) {
         if !craft_app.active {
@@ -86,6 +120,18 @@ impl WindowManager {
                 window_element.create(craft_app, event_loop);
             }
         }
+
+        // Draw the windows with an animation frame that is due.
+        let now = time::Instant::now();
+        for window_element in &self.windows {
+            let due = window_element.inner.borrow().next_frame_time.is_some_and(|frame_time| frame_time <= now);
+            if due {
+                window_element.inner.borrow_mut().next_frame_time = None;
+                if let Some(winit_window) = window_element.winit_window() {
+                    winit_window.request_redraw();
+                }
+            }
+        }
     }
 
     pub fn close_window(&mut self, window: &Window) {
@@ -0,0 +1,73 @@
+#[macro_use]
+extern crate libtest_mimic_collect;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use craft_renderer::RendererType;
+use craft_retained::accesskit::{Role, Toggled};
+use craft_retained::elements::{Checkbox, Element, Window};
+use craft_retained::style::FlexDirection;
+use craft_retained::{CraftCallback, CraftOptions, pct, px};
+use libtest_mimic_collect::TestCollection;
+use libtest_mimic_collect::libtest_mimic::Arguments;
+
+#[cfg(test)]
+mod accessibility_utils;
+
+use accessibility_utils::AccessibilityTree;
+
+#[test]
+fn checkbox_semantics() {
+    let apples = Checkbox::new("Apples", false);
+    let bananas = Checkbox::new("Bananas", true);
+
+    let window = Window::new_with_renderer("Accessibility", RendererType::VelloCPU)
+        .flex_direction(FlexDirection::Column)
+        .width(pct(100))
+        .height(pct(100))
+        .gap(px(10), px(10))
+        .push(apples.clone())
+        .push(bananas.clone());
+
+    let trees: Rc<RefCell<Vec<AccessibilityTree>>> = Rc::new(RefCell::new(Vec::new()));
+    let trees_clone = trees.clone();
+    let cb = CraftCallback(Box::new(move || {
+        let window = window.clone();
+        let apples = apples.clone();
+        let trees = trees_clone.clone();
+        async move {
+            craft_retained::craft_runtime::time::sleep(craft_runtime::time::Duration::from_millis(500)).await;
+            trees.borrow_mut().push(AccessibilityTree::new(window.accessibility_tree()));
+
+            apples.clone().focus();
+            apples.click().await;
+            craft_retained::craft_runtime::time::sleep(craft_runtime::time::Duration::from_millis(500)).await;
+            trees.borrow_mut().push(AccessibilityTree::new(window.accessibility_tree()));
+
+            window.close();
+        }
+    }));
+    craft_retained::craft_main(CraftOptions::test("accessibility_test", cb));
+
+    let trees = trees.take();
+    let [before, after] = trees.as_slice() else {
+        panic!("Expected two accessibility snapshots.");
+    };
+
+    assert_eq!(before.root().role(), Role::Window);
+    before.assert_toggled(Role::CheckBox, "Apples", Toggled::False);
+    before.assert_toggled(Role::CheckBox, "Bananas", Toggled::True);
+    before.assert_focus_order(&["Apples", "Bananas"]);
+
+    let apples = after.assert_role(Role::CheckBox, "Apples");
+    after.assert_focus(apples);
+    after.assert_toggled(Role::CheckBox, "Apples", Toggled::True);
+    after.assert_toggled(Role::CheckBox, "Bananas", Toggled::True);
+}
+
+pub fn main() {
+    let mut args = Arguments::from_args();
+    args.test_threads = Some(1);
+    TestCollection::run_with_args(args);
+}
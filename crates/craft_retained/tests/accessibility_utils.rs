@@ -0,0 +1,84 @@
+//! Helpers for asserting over the AccessKit tree generated by a window.
+
+use std::collections::HashMap;
+
+use craft_retained::accesskit::{Action, Node, NodeId, Role, Toggled, TreeUpdate};
+
+/// A queryable view over a [`TreeUpdate`].
+pub struct AccessibilityTree {
+    root: NodeId,
+    focus: NodeId,
+    nodes: HashMap<NodeId, Node>,
+}
+
+impl AccessibilityTree {
+    pub fn new(tree_update: TreeUpdate) -> Self {
+        let root = tree_update.tree.as_ref().expect("The tree update must contain a tree.").root;
+        let focus = tree_update.focus;
+        let nodes = tree_update.nodes.into_iter().collect();
+        Self { root, focus, nodes }
+    }
+
+    pub fn root(&self) -> &Node {
+        self.node(self.root)
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        self.nodes
+            .get(&id)
+            .unwrap_or_else(|| panic!("No node with id {id:?} in the accessibility tree."))
+    }
+
+    /// Returns the ids of all nodes in depth-first (document) order, starting at the root.
+    pub fn document_order(&self) -> Vec<NodeId> {
+        let mut order = Vec::new();
+        let mut stack = vec![self.root];
+        while let Some(id) = stack.pop() {
+            order.push(id);
+            stack.extend(self.node(id).children().iter().rev().copied());
+        }
+        order
+    }
+
+    /// Returns the first node, in document order, with the given role and label.
+    pub fn find(&self, role: Role, label: &str) -> Option<(NodeId, &Node)> {
+        self.document_order()
+            .into_iter()
+            .map(|id| (id, self.node(id)))
+            .find(|(_, node)| node.role() == role && node.label() == Some(label))
+    }
+
+    /// Returns the labels of the interactive nodes in the order a user would reach them.
+    pub fn focus_order(&self) -> Vec<String> {
+        self.document_order()
+            .into_iter()
+            .map(|id| self.node(id))
+            .filter(|node| node.supports_action(Action::Click) || node.supports_action(Action::Focus))
+            .filter_map(|node| node.label().map(str::to_string))
+            .collect()
+    }
+
+    pub fn assert_role(&self, role: Role, label: &str) -> NodeId {
+        match self.find(role, label) {
+            Some((id, _)) => id,
+            None => panic!("Expected a {role:?} labeled {label:?} in the accessibility tree."),
+        }
+    }
+
+    pub fn assert_toggled(&self, role: Role, label: &str, toggled: Toggled) {
+        let id = self.assert_role(role, label);
+        assert_eq!(
+            self.node(id).toggled(),
+            Some(toggled),
+            "{role:?} {label:?} has the wrong toggled state."
+        );
+    }
+
+    pub fn assert_focus(&self, id: NodeId) {
+        assert_eq!(self.focus, id, "The focused accessibility node is incorrect.");
+    }
+
+    pub fn assert_focus_order(&self, expected: &[&str]) {
+        assert_eq!(self.focus_order(), expected, "The focus order is incorrect.");
+    }
+}
@@ -35,14 +35,14 @@ fn create_button(label: &str, base_color: Color, delta: i64, state: Rc<RefCell<i
 #[cfg(test)]
 mod test_utils;
 
-#[test]
-fn counter() {
+/// Builds the counter in a window drawn by `renderer`, and returns the window and its add button.
+fn counter_window(renderer: RendererType) -> (Window, Container) {
     let count = Rc::new(RefCell::new(0));
     let count_text = Text::new(&format!("Count: {}", count.borrow()));
 
     let add_button = create_button("+", rgb(76, 175, 80), 1, count.clone(), count_text.clone());
 
-    let window = Window::new_with_renderer("Counter", RendererType::VelloCPU)
+    let window = Window::new_with_renderer(&format!("Counter ({renderer})"), renderer)
         .flex_direction(FlexDirection::Column)
         .justify_content(Some(JustifyContent::Center))
         .align_items(Some(AlignItems::Center))
@@ -62,35 +62,45 @@ fn counter() {
                 ))
                 .push(add_button.clone())
         });
+    (window, add_button)
+}
 
-    let result_image: Rc<RefCell<Option<RgbImage>>> = Rc::new(RefCell::new(None));
-    let result_image_clone = result_image.clone();
+#[test]
+fn counter() {
+    let renderers = test_utils::renderers();
+    let counters: Vec<(Window, Container)> = renderers.iter().map(|renderer| counter_window(*renderer)).collect();
+
+    let result_images: Rc<RefCell<Vec<RgbImage>>> = Rc::new(RefCell::new(Vec::new()));
+    let result_images_clone = result_images.clone();
     let cb = CraftCallback(Box::new(move || {
-        let add_button = add_button.clone();
-        let window = window.clone();
-        let result_image = result_image_clone.clone();
+        let counters = counters.clone();
+        let result_images = result_images_clone.clone();
         async move {
             for _ in 0..3 {
                 craft_retained::craft_runtime::time::sleep(craft_runtime::time::Duration::from_millis(500)).await;
-                add_button.click().await;
+                for (_window, add_button) in &counters {
+                    add_button.click().await;
+                }
             }
 
+            for (window, _add_button) in &counters {
+                window.request_screenshot();
+            }
             craft_retained::craft_runtime::time::sleep(craft_runtime::time::Duration::from_millis(500)).await;
 
-            let screenshot = window.clone().screenshot();
-            let img_buffer = image::ImageBuffer::<image::Rgba<u8>, _>::from_raw(
-                screenshot.width as u32,
-                screenshot.height as u32,
-                screenshot.pixels,
-            )
-            .unwrap();
-            let dynamic_img = image::DynamicImage::ImageRgba8(img_buffer);
-            *result_image.borrow_mut() = Some(dynamic_img.to_rgb8());
-            window.close();
+            for (window, _add_button) in &counters {
+                result_images.borrow_mut().push(test_utils::screenshot_image(window.screenshot()));
+                window.close();
+            }
         }
     }));
     craft_retained::craft_main(CraftOptions::test("counter_test", cb));
-    test_utils::check_snapshot(result_image.take().unwrap(), "counter.png");
+
+    let result_images = result_images.take();
+    assert_eq!(result_images.len(), renderers.len());
+    for (renderer, image) in renderers.into_iter().zip(result_images) {
+        test_utils::check_backend_snapshot(image, renderer, "counter");
+    }
 }
 
 pub fn main() {
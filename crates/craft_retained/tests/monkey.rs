@@ -0,0 +1,56 @@
+#[macro_use]
+extern crate libtest_mimic_collect;
+
+use std::rc::Rc;
+
+use craft_renderer::RendererType;
+use craft_retained::elements::{Checkbox, Container, Element, Slider, Text, TextInput, Window};
+use craft_retained::monkey::{Monkey, check_invariants};
+use craft_retained::style::{FlexDirection, Overflow};
+use craft_retained::{CraftCallback, CraftOptions, pct, px, rgb};
+use libtest_mimic_collect::TestCollection;
+use libtest_mimic_collect::libtest_mimic::Arguments;
+
+#[test]
+fn monkey() {
+    let list = Container::new()
+        .flex_direction(FlexDirection::Column)
+        .overflow_y(Overflow::Scroll)
+        .height(px(150));
+    for row in 0..30 {
+        let label = Text::new(&format!("Row {row}"));
+        let label_clone = label.clone();
+        list.clone().push(label.on_pointer_button_up(Rc::new(move |event, _| {
+            label_clone.clone().text(&format!("Row {row} clicked"));
+            event.prevent_propagate();
+        })));
+    }
+
+    let window = Window::new_with_renderer("Monkey", RendererType::VelloCPU)
+        .flex_direction(FlexDirection::Column)
+        .width(pct(100))
+        .height(pct(100))
+        .gap(px(10), px(10))
+        .push(TextInput::new("Type here"))
+        .push(Checkbox::new("Check me", false))
+        .push(Slider::new(16.0).min(0.0).max(100.0))
+        .push(Container::new().background_color(rgb(76, 175, 80)).height(px(40)))
+        .push(list);
+
+    let cb = CraftCallback(Box::new(move || {
+        let window = window.clone();
+        async move {
+            craft_retained::craft_runtime::time::sleep(craft_runtime::time::Duration::from_millis(500)).await;
+            Monkey::new(&window, 42).run(300).await;
+            check_invariants().unwrap();
+            window.close();
+        }
+    }));
+    craft_retained::craft_main(CraftOptions::test("monkey_test", cb));
+}
+
+pub fn main() {
+    let mut args = Arguments::from_args();
+    args.test_threads = Some(1);
+    TestCollection::run_with_args(args);
+}
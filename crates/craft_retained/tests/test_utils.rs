@@ -3,6 +3,8 @@
 
 use std::path::{Path, PathBuf};
 
+use craft_renderer::RendererType;
+use craft_renderer::renderer::Screenshot;
 use image::RgbImage;
 
 /// Directory where current tests creates images
@@ -31,8 +33,71 @@ pub fn is_generate_all_mode() -> bool {
         .unwrap_or(false)
 }
 
+/// How much an image may differ from its snapshot and still match.
+#[derive(Clone, Copy, Debug)]
+pub struct Tolerance {
+    /// The largest difference of a color channel for pixels that count as the same.
+    pub max_channel_difference: u8,
+    /// The fraction of the pixels that may differ by more than `max_channel_difference`.
+    pub max_differing_pixels: f32,
+}
+
+impl Tolerance {
+    /// Allows for the small antialiasing differences between runs on different GPUs and drivers.
+    pub const DEFAULT: Tolerance = Tolerance {
+        max_channel_difference: 8,
+        max_differing_pixels: 0.005,
+    };
+}
+
+/// The renderers every scenario is drawn with.
+///
+/// These are the CPU renderers compiled into the tests. The GPU renderers need an adapter and have no snapshots
+/// blessed in the repository, so they are only used when they are named in `CRAFT_RETAINED_TEST_RENDERERS`, like
+/// `vello_cpu,vello_hybrid`, which replaces the default list.
+pub fn renderers() -> Vec<RendererType> {
+    let mut renderers = Vec::new();
+    #[cfg(feature = "vello_cpu_renderer")]
+    renderers.push(RendererType::VelloCPU);
+    #[cfg(feature = "vello_hybrid_renderer")]
+    renderers.push(RendererType::VelloHybrid);
+
+    match std::env::var("CRAFT_RETAINED_TEST_RENDERERS") {
+        Ok(names) => {
+            renderers.retain(|renderer| names.split(',').any(|name| name.trim() == backend_name(*renderer)));
+        }
+        Err(_) => renderers.retain(|renderer| matches!(renderer, RendererType::VelloCPU)),
+    }
+    renderers
+}
+
+/// The name of a renderer in snapshot file names, like `vello_cpu`.
+pub fn backend_name(renderer: RendererType) -> String {
+    renderer.to_string().replace('/', "_")
+}
+
+/// Converts a window screenshot to an image.
+pub fn screenshot_image(screenshot: Screenshot) -> RgbImage {
+    let image = image::RgbaImage::from_raw(
+        screenshot.width as u32,
+        screenshot.height as u32,
+        screenshot.to_unpremultiplied_rgba8(),
+    )
+    .expect("The screenshot should have a pixel for each point of its size");
+    image::DynamicImage::ImageRgba8(image).to_rgb8()
+}
+
+/// Check an image drawn by a renderer against the snapshot for that renderer, `<name>_<backend>.png`.
+///
+/// Each renderer has its own snapshots, so the differences between renderers are reviewed when a snapshot is
+/// blessed, and a renderer that starts drawing differently fails on its own.
+pub fn check_backend_snapshot(image: RgbImage, renderer: RendererType, name: &str) {
+    let image_name = format!("{name}_{}.png", backend_name(renderer));
+    check_snapshot(image, &image_name, Tolerance::DEFAULT);
+}
+
 /// Check an image against snapshot
-pub fn check_snapshot(image: RgbImage, image_name: &str) {
+pub fn check_snapshot(image: RgbImage, image_name: &str, tolerance: Tolerance) {
     let snapshot_dir = snapshot_dir();
     println!("Snapshots DIR: {}", snapshot_dir.to_str().unwrap());
     let snapshot = image::ImageReader::open(snapshot_dir.join(image_name))
@@ -40,16 +105,47 @@ pub fn check_snapshot(image: RgbImage, image_name: &str) {
         .and_then(|x| x.decode().map_err(|e| e.to_string()))
         .map(|x| x.to_rgb8());
     if let Ok(snapshot) = snapshot {
-        if snapshot != image {
+        if let Err(difference) = compare(&image, &snapshot, tolerance) {
             image.save(current_dir().join(image_name)).unwrap();
-            panic!("Snapshot is different; run 'cargo xtask report' for report")
+            panic!("Snapshot {image_name} is different: {difference}; run 'cargo xtask report' for report")
         }
     } else {
-        println!("writing test to {}", current_dir().join(image_name).display());
-        image.save(current_dir().join(image_name)).unwrap();
-        snapshot.unwrap();
+        let current = current_dir().join(image_name);
+        image.save(&current).unwrap();
+        panic!(
+            "Snapshot {image_name} is missing; generate it with 'CRAFT_RETAINED_TEST=generate-all cargo test --test \
+             counter', check it with 'cargo xtask report', and copy {} to {}",
+            current.display(),
+            snapshot_dir.join(image_name).display()
+        )
     }
     if is_generate_all_mode() {
         image.save(current_dir().join(image_name)).unwrap();
     }
 }
+
+/// Returns how an image differs from its snapshot if it differs by more than the tolerance.
+fn compare(image: &RgbImage, snapshot: &RgbImage, tolerance: Tolerance) -> Result<(), String> {
+    if image.dimensions() != snapshot.dimensions() {
+        return Err(format!(
+            "the size is {:?} instead of {:?}",
+            image.dimensions(),
+            snapshot.dimensions()
+        ));
+    }
+
+    let differing_pixels = image
+        .pixels()
+        .zip(snapshot.pixels())
+        .filter(|(pixel, snapshot_pixel)| {
+            pixel.0.iter().zip(snapshot_pixel.0).any(|(channel, snapshot_channel)| {
+                channel.abs_diff(snapshot_channel) > tolerance.max_channel_difference
+            })
+        })
+        .count();
+    let total_pixels = (image.width() as usize * image.height() as usize).max(1);
+    if differing_pixels as f32 / total_pixels as f32 > tolerance.max_differing_pixels {
+        return Err(format!("{differing_pixels} of {total_pixels} pixels differ"));
+    }
+    Ok(())
+}
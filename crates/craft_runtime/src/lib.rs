@@ -11,6 +11,20 @@ thread_local! {
     pub(crate) static LOCAL_SET: task::LocalSet = task::LocalSet::new();
 }
 
+/// Runs a future on the GUI thread, where it is polled by the event loop between events.
+///
+/// Unlike [`CraftRuntime::spawn`] the future doesn't have to be `Send`, so it can hold elements.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn spawn_local<F>(future: F)
+where
+    F: Future + 'static,
+    F::Output: 'static,
+{
+    LOCAL_SET.with(|local_set| {
+        local_set.spawn_local(future);
+    });
+}
+
 pub struct CraftRuntime {
     #[cfg(not(target_arch = "wasm32"))]
     tokio_runtime: runtime::LocalRuntime,
@@ -169,6 +183,16 @@ impl CraftRuntimeHandle {
         self.tokio_runtime.spawn(future);
     }
 
+    /// Runs a function that blocks, like loading files, on a thread of the runtime.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn_blocking<F, R>(&self, function: F) -> task::JoinHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        self.tokio_runtime.spawn_blocking(function)
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub fn borrow_tokio_runtime(&mut self) -> &mut runtime::Handle {
         &mut self.tokio_runtime
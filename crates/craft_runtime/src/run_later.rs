@@ -5,7 +5,9 @@ use std::time::{Duration, Instant};
 pub struct Job {
     pub callback: JobCallback,
     pub interval: Option<Duration>,
-    pub last_run: Instant
+    pub last_run: Instant,
+    /// Whether the job runs again every interval, or only once after the first one.
+    pub repeat: bool,
 }
 
 impl Job {
@@ -14,6 +16,17 @@ impl Job {
             callback,
             interval,
             last_run: Instant::now(),
+            repeat: true,
+        }
+    }
+
+    /// A job that runs once, after `delay`.
+    pub fn after(callback: JobCallback, delay: Duration) -> Job {
+        Job {
+            callback,
+            interval: Some(delay),
+            last_run: Instant::now(),
+            repeat: false,
         }
     }
 }
@@ -0,0 +1,112 @@
+//! A dial implemented outside of craft with the `CustomElement` trait.
+
+use std::f32::consts::PI;
+
+use craft_retained::elements::{Container, Custom, CustomChange, CustomElement, DrawContext, Element, Text, Window};
+use craft_retained::events::ui_events::keyboard::{Key, KeyState, NamedKey};
+use craft_retained::events::{Event, EventKind};
+use craft_retained::geometry::Circle;
+use craft_retained::style::{AlignItems, FlexDirection, JustifyContent};
+use craft_retained::taffy::{AvailableSpace, Size};
+use craft_retained::text::text_context::TextContext;
+use craft_retained::{Color, CraftOptions, craft_main, pct, px, rgb};
+use util::setup_logging;
+
+const STEPS: u32 = 10;
+
+/// A knob with a mark showing its value, from 0 to [`STEPS`]. Clicking it or pressing an arrow key turns it one step.
+#[derive(Clone, Default)]
+struct Dial {
+    value: u32,
+}
+
+impl Dial {
+    fn turn(&mut self, steps: i32) -> CustomChange {
+        self.value = self.value.saturating_add_signed(steps).min(STEPS);
+        CustomChange::Paint
+    }
+}
+
+impl CustomElement for Dial {
+    fn measure(
+        &mut self,
+        known_dimensions: Size<Option<f32>>,
+        _available_space: Size<AvailableSpace>,
+        _text_context: &mut TextContext,
+    ) -> Size<f32> {
+        Size {
+            width: known_dimensions.width.unwrap_or(64.0),
+            height: known_dimensions.height.unwrap_or(64.0),
+        }
+    }
+
+    fn draw(&mut self, cx: &mut DrawContext) {
+        let bounds = cx.content_rectangle;
+        let radius = bounds.width.min(bounds.height) / 2.0;
+        let (center_x, center_y) = (bounds.x + bounds.width / 2.0, bounds.y + bounds.height / 2.0);
+        cx.renderer.draw_circle(Circle::new(center_x, center_y, radius), rgb(55, 65, 81));
+        let thickness = 3.0 * cx.scale_factor as f32;
+        cx.renderer.draw_circle_outline(Circle::new(center_x, center_y, radius), rgb(16, 185, 129), thickness);
+
+        // The mark goes from the bottom left to the bottom right, clockwise.
+        let angle = 0.75 * PI + 1.5 * PI * self.value as f32 / STEPS as f32;
+        let distance = radius * 0.7;
+        let mark = Circle::new(center_x + distance * angle.cos(), center_y + distance * angle.sin(), radius * 0.12);
+        cx.renderer.draw_circle(mark, Color::WHITE);
+    }
+
+    fn on_event(&mut self, message: &EventKind, event: &mut Event) -> CustomChange {
+        match message {
+            EventKind::PointerButtonUp(_) => {
+                event.prevent_propagate();
+                if self.value == STEPS {
+                    self.turn(-(STEPS as i32))
+                } else {
+                    self.turn(1)
+                }
+            }
+            EventKind::KeyboardInputEvent(key) if key.state == KeyState::Down => match key.key {
+                Key::Named(NamedKey::ArrowUp | NamedKey::ArrowRight) => self.turn(1),
+                Key::Named(NamedKey::ArrowDown | NamedKey::ArrowLeft) => self.turn(-1),
+                _ => CustomChange::Nothing,
+            },
+            _ => CustomChange::Nothing,
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn handles_arrow_keys(&self) -> bool {
+        true
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn accessibility(&self, node: &mut craft_retained::accesskit::Node) {
+        node.set_role(craft_retained::accesskit::Role::Slider);
+        node.set_numeric_value(self.value as f64);
+        node.set_min_numeric_value(0.0);
+        node.set_max_numeric_value(STEPS as f64);
+        node.set_numeric_value_step(1.0);
+    }
+}
+
+pub fn main() {
+    setup_logging();
+
+    let dial = Custom::new(Dial::default());
+    Window::new("Custom element").width(pct(100)).height(pct(100)).push(
+        Container::new()
+            .flex_direction(FlexDirection::Column)
+            .justify_content(Some(JustifyContent::Center))
+            .align_items(Some(AlignItems::Center))
+            .width(pct(100))
+            .height(pct(100))
+            .row_gap(px(20))
+            .push(dial.width(px(120)).height(px(120)).focus())
+            .push(Text::new("Click the dial or use the arrow keys to turn it.")),
+    );
+
+    craft_main(CraftOptions::basic("Custom element"));
+}